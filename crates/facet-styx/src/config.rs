@@ -0,0 +1,258 @@
+//! Loading application config from layered sources.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use facet_format::DeserializeError;
+use styx_tree::{BuildError, Value};
+
+use crate::schema_error::ValidationError;
+use crate::schema_types::SchemaFile;
+
+/// Loads a typed config from a base file, an optional local override file
+/// and environment variables, validating the result against a schema.
+///
+/// Layers are merged with [`Value::merge`] in that order, so later layers
+/// win: objects are merged key by key and any other value is replaced.
+///
+/// Environment variables named `{PREFIX}_{PATH}` override single values.
+/// `PATH` is lowercased and split on `__`, so with the prefix `APP`,
+/// `APP_SERVER__MAX_CONNECTIONS=64` sets `server.max_connections`. Their
+/// values are read as scalars.
+///
+/// # Example
+///
+/// ```no_run
+/// use facet::Facet;
+/// use facet_styx::Config;
+///
+/// #[derive(Facet)]
+/// struct Settings {
+///     name: String,
+///     port: u16,
+/// }
+///
+/// let settings: Settings = Config::new("config.styx")
+///     .local_override("config.local.styx")
+///     .env_prefix("APP")
+///     .load()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Config {
+    base: PathBuf,
+    local_override: Option<PathBuf>,
+    env: Option<(String, Vec<(String, String)>)>,
+    schema: Option<SchemaFile>,
+}
+
+impl Config {
+    /// Load the config from the file at `base`, which must exist.
+    pub fn new(base: impl Into<PathBuf>) -> Self {
+        Self {
+            base: base.into(),
+            local_override: None,
+            env: None,
+            schema: None,
+        }
+    }
+
+    /// Merge the file at `path` over the base file, if it exists.
+    pub fn local_override(mut self, path: impl Into<PathBuf>) -> Self {
+        self.local_override = Some(path.into());
+        self
+    }
+
+    /// Apply overrides from the process environment variables starting
+    /// with `{prefix}_`. Variables whose name or value is not UTF-8 are
+    /// skipped.
+    pub fn env_prefix(self, prefix: impl Into<String>) -> Self {
+        let vars = std::env::vars_os().filter_map(|(name, value)| {
+            Some((name.into_string().ok()?, value.into_string().ok()?))
+        });
+        self.env_vars(prefix, vars)
+    }
+
+    /// Apply overrides from `vars` instead of the process environment;
+    /// useful in tests.
+    pub fn env_vars(
+        mut self,
+        prefix: impl Into<String>,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        self.env = Some((prefix.into(), vars.into_iter().collect()));
+        self
+    }
+
+    /// Validate the merged config against `schema` before deserializing it.
+    pub fn schema(mut self, schema: SchemaFile) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Load, merge and validate every layer, and deserialize the result.
+    pub fn load<T>(&self) -> Result<T, ConfigError>
+    where
+        T: facet_core::Facet<'static>,
+    {
+        let value = self.load_value()?;
+        crate::from_value(&value).map_err(ConfigError::Deserialize)
+    }
+
+    /// Load, merge and validate every layer, without deserializing.
+    pub fn load_value(&self) -> Result<Value, ConfigError> {
+        let base = ConfigLayer::Base(self.base.clone());
+        let mut layers = vec![(base.clone(), read_layer(base, &self.base)?)];
+
+        if let Some(path) = &self.local_override
+            && path.exists()
+        {
+            let layer = ConfigLayer::LocalOverride(path.clone());
+            layers.push((layer.clone(), read_layer(layer, path)?));
+        }
+
+        if let Some((prefix, vars)) = &self.env {
+            layers.push((
+                ConfigLayer::Env(prefix.clone()),
+                env_overrides(prefix, vars),
+            ));
+        }
+
+        let mut value = layers[0].1.clone();
+        for (_, layer) in &layers[1..] {
+            value.merge(layer.clone());
+        }
+
+        if let Some(schema) = &self.schema {
+            let result = crate::validate(&value, schema);
+            if !result.is_valid() {
+                let errors = result
+                    .errors
+                    .into_iter()
+                    .map(|error| (source_layer(&layers, &error.path), error))
+                    .collect();
+                return Err(ConfigError::Invalid(errors));
+            }
+        }
+        Ok(value)
+    }
+}
+
+fn read_layer(layer: ConfigLayer, path: &Path) -> Result<Value, ConfigError> {
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) => return Err(ConfigError::Read { layer, error }),
+    };
+    styx_tree::parse(&source).map_err(|error| ConfigError::Parse { layer, error })
+}
+
+/// The layer the value at `path` in the merged config came from: the last
+/// one setting it, or failing that its closest parent, or the base file.
+fn source_layer(layers: &[(ConfigLayer, Value)], mut path: &str) -> ConfigLayer {
+    while !path.is_empty() {
+        if let Some((layer, _)) = layers
+            .iter()
+            .rev()
+            .find(|(_, value)| value.get(path).is_some())
+        {
+            return layer.clone();
+        }
+        path = &path[..path.rfind(['.', '[']).unwrap_or(0)];
+    }
+    layers[0].0.clone()
+}
+
+/// A document holding the overrides set by `{prefix}_` variables.
+fn env_overrides(prefix: &str, vars: &[(String, String)]) -> Value {
+    let prefix = format!("{prefix}_");
+    let mut vars: Vec<_> = vars
+        .iter()
+        .filter_map(|(name, value)| Some((name.strip_prefix(&prefix)?, value)))
+        .filter(|(path, _)| !path.is_empty())
+        .collect();
+    vars.sort();
+
+    let mut overrides = Value::object();
+    for (path, text) in vars {
+        let mut value = Value::scalar(text.as_str());
+        for segment in path.rsplit("__") {
+            let mut object = Value::object();
+            if let Some(obj) = object.as_object_mut() {
+                obj.insert(segment.to_lowercase(), value);
+            }
+            value = object;
+        }
+        overrides.merge(value);
+    }
+    overrides
+}
+
+/// A config layer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigLayer {
+    /// The base config file.
+    Base(PathBuf),
+    /// The local override file.
+    LocalOverride(PathBuf),
+    /// The environment variables with this prefix.
+    Env(String),
+}
+
+impl fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigLayer::Base(path) => write!(f, "config file '{}'", path.display()),
+            ConfigLayer::LocalOverride(path) => {
+                write!(f, "override file '{}'", path.display())
+            }
+            ConfigLayer::Env(prefix) => write!(f, "environment variables '{prefix}_*'"),
+        }
+    }
+}
+
+/// Why a [`Config`] failed to load.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// A config file could not be read.
+    Read {
+        layer: ConfigLayer,
+        error: std::io::Error,
+    },
+    /// A config file is not valid Styx.
+    Parse {
+        layer: ConfigLayer,
+        error: BuildError,
+    },
+    /// The merged config does not match the schema. Each error comes with
+    /// the layer its value came from, which its span points into.
+    Invalid(Vec<(ConfigLayer, ValidationError)>),
+    /// The merged config does not fit the target type.
+    Deserialize(DeserializeError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read { layer, error } => write!(f, "cannot read {layer}: {error}"),
+            ConfigError::Parse { layer, error } => write!(f, "cannot parse {layer}: {error}"),
+            ConfigError::Invalid(errors) => {
+                write!(f, "config does not match its schema")?;
+                for (layer, error) in errors {
+                    write!(f, "\n  {layer}: {error}")?;
+                }
+                Ok(())
+            }
+            ConfigError::Deserialize(error) => write!(f, "invalid config: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Read { error, .. } => Some(error),
+            ConfigError::Parse { error, .. } => Some(error),
+            ConfigError::Invalid(_) | ConfigError::Deserialize(_) => None,
+        }
+    }
+}
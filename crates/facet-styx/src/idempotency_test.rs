@@ -682,6 +682,7 @@ fn idempotent_dibs_like_schema() {
             version: None,
             cli: Some("dibs".to_string()),
             description: Some("Configuration loaded from `dibs.styx`.".to_string()),
+            crate_name: None,
             lsp: None,
         },
         imports: None,
@@ -1182,6 +1183,7 @@ fn idempotent_object_key_complex_schema_file() {
             version: Some("1.0.0".to_string()),
             cli: Some("testcli".to_string()),
             description: Some("A complex test schema".to_string()),
+            crate_name: None,
             lsp: None,
         },
         imports: None,
@@ -40,6 +40,7 @@
 //! assert!(styx.contains("port 8080"));
 //! ```
 
+mod config;
 mod error;
 #[cfg(feature = "figue")]
 mod figue_format;
@@ -48,35 +49,70 @@ mod idempotency_test;
 #[cfg(test)]
 mod other_variant_test;
 mod parser;
+mod partial;
+mod schema_coerce;
+mod schema_compat;
+mod schema_doc;
 mod schema_error;
+mod schema_examples;
 mod schema_gen;
+mod schema_hints;
+mod schema_infer;
+mod schema_merge;
 mod schema_meta;
+mod schema_order;
 mod schema_types;
 mod schema_validate;
+mod schema_validate_stream;
 mod serializer;
 #[cfg(test)]
 mod tag_events_test;
 #[cfg(test)]
 mod test_utils;
 mod tracing_macros;
+mod tree_events;
+#[cfg(test)]
+mod untagged_test;
+mod validated;
 #[cfg(test)]
 mod value_expr_test;
 
+pub use config::{Config, ConfigError, ConfigLayer};
 pub use error::RenderError;
 pub use facet_format::DeserializeError;
 pub use facet_format::SerializeError;
 #[cfg(feature = "figue")]
 pub use figue_format::StyxFormat;
 pub use parser::StyxParser;
-pub use schema_error::{ValidationError, ValidationErrorKind, ValidationResult, ValidationWarning};
-pub use schema_gen::{GenerateSchema, schema_file_from_type, schema_from_type};
-pub use schema_meta::META_SCHEMA_SOURCE;
+pub use partial::from_str_collect;
+pub use schema_coerce::coerce_to_schema;
+pub use schema_compat::{ChangeKind, Compatibility, SchemaChange, compatibility};
+pub use schema_doc::{DocComment, DocTag};
+pub use schema_error::{
+    Diagnostic, Position, Severity, ValidationError, ValidationErrorKind, ValidationResult,
+    ValidationWarning,
+};
+pub use schema_examples::validate_examples;
+pub use schema_gen::{
+    GenerateSchema, SchemaSourceMap, SourceLocation, schema_file_from_type, schema_from_type,
+    source_map_from_type,
+};
+pub use schema_hints::format_with_field_hints;
+pub use schema_infer::InferSchema;
+pub use schema_merge::{SchemaMergeError, merge_schemas};
+pub use schema_meta::{
+    META_SCHEMA, META_SCHEMA_SOURCE, MetaError, parse_meta, validate_schema_file,
+};
+pub use schema_order::{to_string_ordered, to_string_ordered_with_options};
 pub use schema_types::*;
 pub use schema_validate::{Validator, validate, validate_as};
+pub use schema_validate_stream::{StreamingValidator, validate_streaming};
 pub use serializer::{
-    SerializeOptions, StyxSerializeError, StyxSerializer, peek_to_string, peek_to_string_expr,
-    peek_to_string_with_options, to_string, to_string_compact, to_string_with_options,
+    EnumRepr, SerializeOptions, StyxSerializeError, StyxSerializer, peek_to_string,
+    peek_to_string_expr, peek_to_string_with_options, to_string, to_string_compact,
+    to_string_with_options, to_writer, to_writer_with_options,
 };
+pub use validated::{ValidatedError, from_str_validated};
 
 /// Deserialize a value from a Styx string into an owned type.
 ///
@@ -99,6 +135,29 @@ pub use serializer::{
 /// assert_eq!(person.name, "Alice");
 /// assert_eq!(person.age, 30);
 /// ```
+///
+/// # Doc comments
+///
+/// Fields wrapped in [`Documented<T>`] receive the `///` doc comments that
+/// precede their key in the source, and [`to_string`] writes them back out,
+/// so config-editing tools can round-trip a document through a typed value
+/// without losing its documentation. Other fields drop their doc comments.
+///
+/// ```
+/// use facet::Facet;
+/// use facet_styx::{Documented, from_str, to_string};
+///
+/// #[derive(Facet, Debug)]
+/// struct Config {
+///     port: Documented<u16>,
+/// }
+///
+/// let styx = "/// Port to listen on\nport 8080";
+/// let config: Config = from_str(styx).unwrap();
+/// assert_eq!(*config.port, 8080);
+/// assert_eq!(config.port.doc(), Some(&["Port to listen on".to_string()][..]));
+/// assert_eq!(to_string(&config).unwrap().trim(), styx);
+/// ```
 pub fn from_str<T>(input: &str) -> Result<T, DeserializeError>
 where
     T: facet_core::Facet<'static>,
@@ -175,5 +234,110 @@ where
     de.deserialize_root()
 }
 
+/// Deserialize an already-parsed [`styx_tree::Value`] into an owned type.
+///
+/// The tree is fed to the deserializer directly instead of being printed and
+/// parsed again, so tools that keep a tree around (like the language server)
+/// can read typed views of it cheaply. Errors carry the spans of the source
+/// the tree was parsed from.
+///
+/// A document root is read like [`from_str`] reads its source, skipping its
+/// `@schema` entry; any other value is read like [`from_str_expr`].
+///
+/// # Example
+///
+/// ```
+/// use facet::Facet;
+/// use facet_styx::from_value;
+///
+/// #[derive(Facet, Debug, PartialEq)]
+/// struct Config {
+///     name: String,
+///     port: u16,
+/// }
+///
+/// let doc = styx_tree::parse("name myapp\nport 8080").unwrap();
+/// let config: Config = from_value(&doc).unwrap();
+/// assert_eq!(config.name, "myapp");
+/// assert_eq!(config.port, 8080);
+/// ```
+pub fn from_value<T>(value: &styx_tree::Value) -> Result<T, DeserializeError>
+where
+    T: facet_core::Facet<'static>,
+{
+    use facet_format::FormatDeserializer;
+    let mut parser = StyxParser::from_value(value);
+    let mut de = FormatDeserializer::new_owned(&mut parser);
+    de.deserialize_root()
+}
+
+/// Deserialize an already-parsed [`styx_tree::Value`], allowing zero-copy
+/// borrowing from the tree.
+///
+/// # Example
+///
+/// ```
+/// use facet::Facet;
+/// use facet_styx::from_value_borrowed;
+///
+/// #[derive(Facet, Debug, PartialEq)]
+/// struct Person<'a> {
+///     name: &'a str,
+///     age: u32,
+/// }
+///
+/// let doc = styx_tree::parse("name Alice\nage 30").unwrap();
+/// let person: Person = from_value_borrowed(&doc).unwrap();
+/// assert_eq!(person.name, "Alice");
+/// assert_eq!(person.age, 30);
+/// ```
+pub fn from_value_borrowed<'input, 'facet, T>(
+    value: &'input styx_tree::Value,
+) -> Result<T, DeserializeError>
+where
+    T: facet_core::Facet<'facet>,
+    'input: 'facet,
+{
+    use facet_format::FormatDeserializer;
+    let mut parser = StyxParser::from_value(value);
+    let mut de = FormatDeserializer::new(&mut parser);
+    de.deserialize_root()
+}
+
+/// Typed access to an already-parsed [`styx_tree::Value`].
+pub trait ValueExt {
+    /// Deserialize this value into an owned type; see [`from_value`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use facet::Facet;
+    /// use facet_styx::ValueExt;
+    ///
+    /// #[derive(Facet, Debug, PartialEq)]
+    /// struct Server {
+    ///     host: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// let doc = styx_tree::parse("server {host localhost, port 8080}").unwrap();
+    /// let server: Server = doc.get("server").unwrap().as_typed().unwrap();
+    /// assert_eq!(server.host, "localhost");
+    /// assert_eq!(server.port, 8080);
+    /// ```
+    fn as_typed<T>(&self) -> Result<T, DeserializeError>
+    where
+        T: facet_core::Facet<'static>;
+}
+
+impl ValueExt for styx_tree::Value {
+    fn as_typed<T>(&self) -> Result<T, DeserializeError>
+    where
+        T: facet_core::Facet<'static>,
+    {
+        from_value(self)
+    }
+}
+
 #[cfg(test)]
 mod tests;
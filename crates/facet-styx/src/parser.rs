@@ -8,6 +8,7 @@
 use std::borrow::Cow;
 
 use crate::trace;
+use crate::tree_events::TreeEvents;
 use facet_core::Facet;
 use facet_format::{
     ContainerKind, DeserializeErrorKind, FieldKey, FieldLocationHint, FormatParser, ParseError,
@@ -15,6 +16,7 @@ use facet_format::{
 };
 use facet_reflect::Span as ReflectSpan;
 use styx_parse::{Event, EventKind, ParseErrorKind, Parser, ScalarKind as StyxScalarKind, Span};
+use styx_tree::Value;
 
 mod inner {
     use super::*;
@@ -80,6 +82,31 @@ mod inner {
 
 use inner::InnerParser;
 
+/// Where a [`StyxParser`] gets its events from.
+#[derive(Clone)]
+enum Source<'de> {
+    /// Source text, parsed and validated as we go.
+    Text(InnerParser<'de>),
+    /// A tree that was already parsed.
+    Tree(TreeEvents<'de>),
+}
+
+impl<'de> Source<'de> {
+    fn next_event(&mut self) -> Result<Option<Event<'de>>, ParseError> {
+        match self {
+            Source::Text(parser) => parser.next_event(),
+            Source::Tree(events) => Ok(events.next_event()),
+        }
+    }
+
+    fn input(&self) -> Option<&'de str> {
+        match self {
+            Source::Text(parser) => Some(parser.input()),
+            Source::Tree(_) => None,
+        }
+    }
+}
+
 /// Streaming Styx parser implementing FormatParser.
 ///
 /// This parser wraps `styx-parse::Parser2` which performs full validation
@@ -89,9 +116,12 @@ use inner::InnerParser;
 /// - Invalid escape sequence validation
 /// - Dotted path validation (ReopenedPath, NestIntoTerminal)
 /// - TooManyAtoms detection
+///
+/// It can also replay a tree that was already parsed, see
+/// [`StyxParser::from_value`].
 #[derive(Clone)]
 pub struct StyxParser<'de> {
-    inner: InnerParser<'de>,
+    inner: Source<'de>,
     /// Peeked events queue (if any).
     peeked_events: Vec<ParseEvent<'de>>,
     /// Current span for error reporting.
@@ -118,7 +148,7 @@ impl<'de> StyxParser<'de> {
     /// Create a new parser for the given source (document mode).
     pub fn new(source: &'de str) -> Self {
         Self {
-            inner: InnerParser::new(source),
+            inner: Source::Text(InnerParser::new(source)),
             peeked_events: Vec::new(),
             current_span: None,
             complete: false,
@@ -137,7 +167,7 @@ impl<'de> StyxParser<'de> {
     /// Use this for parsing embedded values like default values in schemas.
     pub fn new_expr(source: &'de str) -> Self {
         Self {
-            inner: InnerParser::new_expr(source),
+            inner: Source::Text(InnerParser::new_expr(source)),
             peeked_events: Vec::new(),
             current_span: None,
             complete: false,
@@ -150,15 +180,39 @@ impl<'de> StyxParser<'de> {
         }
     }
 
+    /// Create a parser that reads an already-parsed tree.
+    ///
+    /// An untagged object is read like a document, so its `@schema` entry is
+    /// skipped; any other value is read like an expression. Spans come from
+    /// the tree, so errors point into the source it was parsed from.
+    pub fn from_value(value: &'de Value) -> Self {
+        let (events, at_implicit_root) = match value.as_object() {
+            Some(root) if value.tag.is_none() => (TreeEvents::document(root), true),
+            _ => (TreeEvents::expr(value), false),
+        };
+        Self {
+            inner: Source::Tree(events),
+            peeked_events: Vec::new(),
+            current_span: None,
+            complete: false,
+            tag_has_payload_stack: Vec::new(),
+            scalar_type_hint: None,
+            pending_doc: Vec::new(),
+            saved_state: None,
+            at_implicit_root,
+            depth: 0,
+        }
+    }
+
     /// Convert a Styx span to a facet_reflect span.
     fn to_reflect_span(&self, span: Span) -> ReflectSpan {
         ReflectSpan::new(span.start as usize, (span.end - span.start) as usize)
     }
 
-    /// Get the text for a span.
-    fn span_text(&self, span: Span) -> &'de str {
-        let input = self.inner.input();
-        &input[span.start as usize..span.end as usize]
+    /// Get the text for a span, if parsing from text.
+    fn span_text(&self, span: Span) -> Option<&'de str> {
+        let input = self.inner.input()?;
+        Some(&input[span.start as usize..span.end as usize])
     }
 
     /// Get the current span for event creation.
@@ -185,12 +239,15 @@ impl<'de> StyxParser<'de> {
     ///
     /// In styx, ALL scalars are syntactically strings - whether bare, quoted, raw, or heredoc.
     /// The target type (via hint) determines how the string is interpreted.
-    fn parse_scalar(&mut self, value: Cow<'de, str>, _kind: StyxScalarKind) -> ScalarValue<'de> {
+    fn parse_scalar(&mut self, value: Cow<'de, str>, kind: StyxScalarKind) -> ScalarValue<'de> {
         // Take the hint (it's consumed after use)
         let hint = self.scalar_type_hint.take();
 
         // All scalar kinds are treated the same - the hint determines interpretation
         match hint {
+            // Unhinted scalars are peeked for optional targets, where a bare
+            // `null` means absence. A hinted string keeps it as text.
+            None if kind == StyxScalarKind::Bare && value == "null" => ScalarValue::Null,
             Some(ScalarTypeHint::String) | None => ScalarValue::Str(value),
             Some(ScalarTypeHint::Bool) => {
                 if value == "true" {
@@ -353,13 +410,16 @@ impl<'de> StyxParser<'de> {
                 Ok(Some(self.event(ParseEventKind::FieldKey(field_key))))
             }
 
-            EventKind::Scalar { value, kind: _ } => {
+            EventKind::Scalar { value, kind } => {
                 self.current_span = Some(span);
                 // Determine scalar kind from value (Parser2 already unescaped it)
                 // We need to figure out if it was bare or quoted from the raw text
-                let text = self.span_text(span);
-                let kind =
-                    if text.starts_with('"') || text.starts_with("r#") || text.starts_with("<<") {
+                let kind = match self.span_text(span) {
+                    Some(text)
+                        if text.starts_with('"')
+                            || text.starts_with("r#")
+                            || text.starts_with("<<") =>
+                    {
                         if text.starts_with('"') {
                             StyxScalarKind::Quoted
                         } else if text.starts_with("r#") {
@@ -367,9 +427,11 @@ impl<'de> StyxParser<'de> {
                         } else {
                             StyxScalarKind::Heredoc
                         }
-                    } else {
-                        StyxScalarKind::Bare
-                    };
+                    }
+                    Some(_) => StyxScalarKind::Bare,
+                    // A tree records the kind it was parsed as
+                    None => kind,
+                };
                 let scalar = self.parse_scalar(value, kind);
                 trace!(?scalar, "convert_event: Scalar");
                 self.mark_tag_has_payload();
@@ -382,7 +444,10 @@ impl<'de> StyxParser<'de> {
 
                 // Check if this Unit represents an actual @ token in the source
                 // vs an implicit unit (key with no value).
-                let is_at_token = self.span_text(span) == "@";
+                let is_at_token = match &self.inner {
+                    Source::Text(_) => self.span_text(span) == Some("@"),
+                    Source::Tree(events) => events.unit_is_token(),
+                };
 
                 if is_at_token && self.tag_has_payload_stack.is_empty() {
                     // Standalone @ is a unit tag - emit VariantTag(None) + Scalar(Unit)
@@ -425,6 +490,12 @@ impl<'de> StyxParser<'de> {
                 Ok(None)
             }
 
+            EventKind::Anchor { .. } => {
+                // Only emitted by parsers with anchors enabled; aliases are
+                // not expanded here, so the anchor is skipped
+                Ok(None)
+            }
+
             EventKind::DocComment { lines } => {
                 self.current_span = Some(span);
                 // Buffer doc comments for the next field key
@@ -624,11 +695,12 @@ impl<'de> FormatParser<'de> for StyxParser<'de> {
     }
 
     fn raw_capture_shape(&self) -> Option<&'static facet_core::Shape> {
-        Some(crate::RawStyx::SHAPE)
+        // Raw capture slices the input, which a tree doesn't have
+        self.inner.input().map(|_| crate::RawStyx::SHAPE)
     }
 
     fn input(&self) -> Option<&'de [u8]> {
-        Some(self.inner.input().as_bytes())
+        self.inner.input().map(str::as_bytes)
     }
 
     fn hint_scalar_type(&mut self, hint: ScalarTypeHint) {
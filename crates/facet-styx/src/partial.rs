@@ -0,0 +1,197 @@
+//! Deserialization that reports every bad field instead of the first.
+
+use facet_format::{DeserializeError, DeserializeErrorKind};
+use styx_tree::{Object, Payload, Span, Value};
+
+use crate::schema_compat::child_path;
+
+/// Deserialize a value from a Styx string, collecting an error for each
+/// field that fails instead of stopping at the first.
+///
+/// A field that fails to deserialize is reported and then treated as
+/// missing, so it gets its default (`#[facet(default)]`, `None` for
+/// options) while the rest of the document is still read. Unknown fields
+/// are reported and skipped the same way. The value is `None` when a failed
+/// field has no default, or when the document doesn't parse; the errors
+/// found up to that point are still returned.
+///
+/// # Example
+///
+/// ```
+/// use facet::Facet;
+/// use facet_styx::from_str_collect;
+///
+/// #[derive(Facet, Debug, PartialEq)]
+/// struct Server {
+///     host: String,
+///     #[facet(default = 8080)]
+///     port: u16,
+///     #[facet(default)]
+///     workers: u32,
+/// }
+///
+/// let (server, errors) = from_str_collect::<Server>("host localhost\nport eighty\nworkers -1");
+/// assert_eq!(errors.len(), 2);
+/// assert_eq!(
+///     server,
+///     Some(Server { host: "localhost".into(), port: 8080, workers: 0 })
+/// );
+/// ```
+pub fn from_str_collect<T>(input: &str) -> (Option<T>, Vec<DeserializeError>)
+where
+    T: facet_core::Facet<'static>,
+{
+    let first_error = match crate::from_str(input) {
+        Ok(value) => return (Some(value), Vec::new()),
+        Err(e) => e,
+    };
+    let Ok(tree) = styx_tree::parse(input) else {
+        return (None, vec![first_error]);
+    };
+    from_value_collect(tree)
+}
+
+/// Like [`from_str_collect`], for an already-parsed tree.
+pub(crate) fn from_value_collect<T>(mut tree: Value) -> (Option<T>, Vec<DeserializeError>)
+where
+    T: facet_core::Facet<'static>,
+{
+    let mut errors = Vec::new();
+    let mut removed_keys = Vec::new();
+    loop {
+        let error = match crate::from_value(&tree) {
+            Ok(value) => return (Some(value), errors),
+            Err(e) => e,
+        };
+        if let DeserializeErrorKind::MissingField { field, .. } = &error.kind {
+            // A field we removed has no default; its error is already reported
+            if !removed_keys.contains(&field.to_string()) {
+                errors.push(error);
+            }
+            return (None, errors);
+        }
+
+        let removed = error
+            .span
+            .as_ref()
+            .and_then(|span| remove_entry_at(&mut tree, span.offset as u32));
+        errors.push(error);
+        match removed {
+            Some(key) => removed_keys.push(key),
+            None => return (None, errors),
+        }
+    }
+}
+
+/// Remove the innermost entry whose key or value contains `offset`,
+/// returning its key.
+fn remove_entry_at(value: &mut Value, offset: u32) -> Option<String> {
+    match &mut value.payload {
+        Some(Payload::Object(obj)) => {
+            let index = entry_at(obj, offset)?;
+            remove_entry_at(&mut obj.entries[index].value, offset).or_else(|| {
+                let entry = obj.entries.remove(index);
+                Some(entry.key.as_str().unwrap_or_default().to_string())
+            })
+        }
+        Some(Payload::Sequence(seq)) => seq
+            .items
+            .iter_mut()
+            .find_map(|item| remove_entry_at(item, offset)),
+        _ => None,
+    }
+}
+
+/// Dotted path, below `path`, of the innermost entry in `value` whose key or
+/// value contains `offset`.
+pub(crate) fn entry_path_at(value: &Value, offset: u32, path: &str) -> String {
+    let inner = match &value.payload {
+        Some(Payload::Object(obj)) => entry_at(obj, offset).map(|index| {
+            let entry = &obj.entries[index];
+            let key = entry.key.as_str().unwrap_or("@");
+            entry_path_at(&entry.value, offset, &child_path(path, key))
+        }),
+        Some(Payload::Sequence(seq)) => seq
+            .items
+            .iter()
+            .find(|item| contains(item.span, offset))
+            .map(|item| entry_path_at(item, offset, path)),
+        _ => None,
+    };
+    inner.unwrap_or_else(|| path.to_string())
+}
+
+/// Index of the entry of `obj` whose key or value contains `offset`.
+fn entry_at(obj: &Object, offset: u32) -> Option<usize> {
+    obj.entries
+        .iter()
+        .position(|entry| contains(entry.key.span, offset) || contains(entry.value.span, offset))
+}
+
+fn contains(span: Option<Span>, offset: u32) -> bool {
+    span.is_some_and(|span| span.start <= offset && offset < span.end.max(span.start + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use facet::Facet;
+    use facet_testhelpers::test;
+
+    #[derive(Facet, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        #[facet(default = 8080)]
+        port: u16,
+        #[facet(default)]
+        workers: u32,
+        tls: Option<bool>,
+    }
+
+    fn error_text<'a>(source: &'a str, error: &DeserializeError) -> &'a str {
+        let span = error.span.as_ref().unwrap();
+        let start = span.offset as usize;
+        &source[start..start + span.len as usize]
+    }
+
+    #[test]
+    fn test_collects_every_bad_field() {
+        let source = "host localhost\nport eighty\nworkers -1\ntls true";
+        let (server, errors) = from_str_collect::<Server>(source);
+
+        let texts: Vec<&str> = errors.iter().map(|e| error_text(source, e)).collect();
+        assert_eq!(texts, ["eighty", "-1"]);
+        assert_eq!(
+            server,
+            Some(Server {
+                host: "localhost".into(),
+                port: 8080,
+                workers: 0,
+                tls: Some(true),
+            })
+        );
+    }
+
+    #[test]
+    fn test_bad_required_field_gives_no_value() {
+        let source = "port eighty\nhost {name localhost}";
+        let (server, errors) = from_str_collect::<Server>(source);
+        assert_eq!(server, None);
+        let texts: Vec<&str> = errors.iter().map(|e| error_text(source, e)).collect();
+        assert_eq!(texts.len(), 2, "{errors:?}");
+        assert_eq!(texts[0], "eighty");
+        assert!(texts[1].starts_with('{'), "{texts:?}");
+
+        // A field that was never there is reported as missing
+        let (server, errors) = from_str_collect::<Server>("port eighty");
+        assert_eq!(server, None);
+        assert!(matches!(
+            &errors[1].kind,
+            DeserializeErrorKind::MissingField { field, .. } if field.to_string() == "host"
+        ));
+
+        let (server, errors) = from_str_collect::<Server>("host localhost\nport 80");
+        assert_eq!(server.map(|s| s.port), Some(80));
+        assert!(errors.is_empty());
+    }
+}
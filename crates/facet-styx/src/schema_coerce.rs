@@ -0,0 +1,238 @@
+//! Coercing string values to the types a schema declares.
+//!
+//! Documents imported from environment variables or JSON often hold every
+//! value as a string, like `port "8080"`. Validation accepts those, but
+//! deserializing a quoted scalar into a number or `bool` doesn't. Coercion
+//! rewrites such scalars in the bare form their declared type expects.
+
+use styx_tree::{Payload, ScalarKind, Value};
+
+use crate::schema_compat::child_path;
+use crate::schema_error::{ValidationError, ValidationErrorKind, ValidationResult};
+use crate::schema_types::{Documented, ObjectKey, ObjectSchema, Schema, SchemaFile};
+
+/// Rewrite the scalars of `value` declared as `@bool`, `@int` or `@float`
+/// by `schema` in their bare, typed form.
+///
+/// `"true"` becomes `true`, and `" 8080"` becomes `8080`. Booleans are
+/// matched case-insensitively. A scalar that can't be read as its declared
+/// type is left as it is and reported as an error. Values the schema doesn't
+/// describe, and tagged values, are left alone; run [`validate`] afterwards
+/// to check the whole document.
+///
+/// # Example
+///
+/// ```
+/// use facet_styx::{SchemaFile, coerce_to_schema};
+///
+/// let schema: SchemaFile = facet_styx::from_str(
+///     "meta {id example}\nschema {@ @object{port @int, debug @bool}}",
+/// )
+/// .unwrap();
+/// let mut config = styx_tree::parse("port \"8080\"\ndebug \"TRUE\"").unwrap();
+///
+/// assert!(coerce_to_schema(&mut config, &schema).is_valid());
+/// let expected = styx_tree::parse("port 8080\ndebug true").unwrap();
+/// assert_eq!(
+///     styx_format::format_value(&config, Default::default()),
+///     styx_format::format_value(&expected, Default::default()),
+/// );
+/// ```
+///
+/// [`validate`]: crate::validate
+pub fn coerce_to_schema(value: &mut Value, schema: &SchemaFile) -> ValidationResult {
+    let mut result = ValidationResult::ok();
+    if let Some(root) = schema.schema.get(&None) {
+        Coercer {
+            schema_file: schema,
+            result: &mut result,
+        }
+        .coerce(value, root, "");
+    }
+    result
+}
+
+struct Coercer<'a> {
+    schema_file: &'a SchemaFile,
+    result: &'a mut ValidationResult,
+}
+
+impl<'a> Coercer<'a> {
+    fn coerce(&mut self, value: &mut Value, schema: &Schema, path: &str) {
+        let Some(schema) = self.schema_file.resolve(schema) else {
+            return;
+        };
+        match schema {
+            Schema::Bool => self.coerce_scalar(value, path, "boolean", |text| {
+                let text = text.trim();
+                ["true", "false"]
+                    .into_iter()
+                    .find(|b| text.eq_ignore_ascii_case(b))
+                    .map(String::from)
+            }),
+            Schema::Int(_) => self.coerce_scalar(value, path, "integer", |text| {
+                text.trim().parse::<i128>().ok().map(|n| n.to_string())
+            }),
+            Schema::Float(_) => self.coerce_scalar(value, path, "number", |text| {
+                let text = text.trim();
+                text.parse::<f64>().ok().map(|_| text.to_string())
+            }),
+
+            Schema::Object(fields) => self.coerce_object(value, fields, path),
+            Schema::Seq(seq) => {
+                if let Some(Payload::Sequence(items)) = &mut value.payload {
+                    for (i, item) in items.items.iter_mut().enumerate() {
+                        self.coerce(item, &seq.0.0.value, &format!("{path}[{i}]"));
+                    }
+                }
+            }
+            Schema::Tuple(tuple) => {
+                if let Some(Payload::Sequence(items)) = &mut value.payload {
+                    for (i, (item, item_schema)) in items.items.iter_mut().zip(&tuple.0).enumerate()
+                    {
+                        self.coerce(item, &item_schema.value, &format!("{path}[{i}]"));
+                    }
+                }
+            }
+            Schema::Map(map) => {
+                if let (Some(value_schema), Some(Payload::Object(obj))) =
+                    (map.0.last(), &mut value.payload)
+                {
+                    for entry in &mut obj.entries {
+                        let key = entry.key.as_str().unwrap_or("@");
+                        let entry_path = child_path(path, key);
+                        self.coerce(&mut entry.value, &value_schema.value, &entry_path);
+                    }
+                }
+            }
+
+            Schema::Optional(optional) => {
+                if !value.is_unit() {
+                    self.coerce(value, &optional.0.0.value, path);
+                }
+            }
+            Schema::Default(default) => self.coerce(value, &default.0.1.value, path),
+            Schema::Deprecated(deprecated) => self.coerce(value, &deprecated.0.1.value, path),
+            Schema::OneOf(one_of) => self.coerce(value, &one_of.0.0.value, path),
+
+            // Which member or variant a value belongs to depends on its
+            // type, so there's nothing to coerce it to
+            Schema::String(_)
+            | Schema::Unit
+            | Schema::Any
+            | Schema::Union(_)
+            | Schema::Enum(_)
+            | Schema::Flatten(_)
+            | Schema::Literal(_)
+            | Schema::Type { .. } => {}
+        }
+    }
+
+    fn coerce_object(&mut self, value: &mut Value, fields: &ObjectSchema, path: &str) {
+        let Some(Payload::Object(obj)) = &mut value.payload else {
+            return;
+        };
+        let catch_all = fields
+            .0
+            .iter()
+            .find_map(|(key, schema)| key.value.tag.is_some().then_some(schema));
+        for entry in &mut obj.entries {
+            let Some(key) = entry.key.as_str() else {
+                continue;
+            };
+            let lookup = Documented::new(ObjectKey::named(key));
+            let field_path = child_path(path, key);
+            if let Some(field_schema) = fields.0.get(&lookup).or(catch_all) {
+                self.coerce(&mut entry.value, field_schema, &field_path);
+            }
+        }
+    }
+
+    /// Rewrite an untagged scalar with `convert`, reporting it if that fails.
+    fn coerce_scalar(
+        &mut self,
+        value: &mut Value,
+        path: &str,
+        expected: &str,
+        convert: impl Fn(&str) -> Option<String>,
+    ) {
+        if value.tag.is_some() {
+            return;
+        }
+        let Some(Payload::Scalar(scalar)) = &mut value.payload else {
+            return;
+        };
+        match convert(&scalar.text) {
+            Some(text) => {
+                scalar.text = text;
+                scalar.kind = ScalarKind::Bare;
+            }
+            None => self.result.error(
+                ValidationError::new(
+                    path,
+                    ValidationErrorKind::InvalidValue {
+                        reason: format!("cannot coerce to {expected}"),
+                    },
+                    format!("'{}' cannot be coerced to {expected}", scalar.text),
+                )
+                .with_span(value.span),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use facet_testhelpers::test;
+
+    fn schema() -> SchemaFile {
+        crate::from_str(
+            "meta {id test}\nschema {\n  @ @object{\n    debug @bool\n    port @int\n    ratio @optional(@float)\n    name @string\n    workers @seq(@Worker)\n  }\n  Worker @object{threads @default(1 @int)}\n}",
+        )
+        .unwrap()
+    }
+
+    fn scalar(value: &Value, path: &str) -> (String, ScalarKind) {
+        match &value.get(path).unwrap().payload {
+            Some(Payload::Scalar(s)) => (s.text.clone(), s.kind),
+            other => panic!("{path} is not a scalar: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_coerces_strings_to_declared_types() {
+        let mut value = styx_tree::parse(
+            "debug \"true\"\nport \"8080\"\nratio \" 0.5\"\nname \"8080\"\nworkers ({threads \"4\"})",
+        )
+        .unwrap();
+        let result = coerce_to_schema(&mut value, &schema());
+        assert!(result.is_valid(), "{:?}", result.errors);
+
+        assert_eq!(scalar(&value, "debug"), ("true".into(), ScalarKind::Bare));
+        assert_eq!(scalar(&value, "port"), ("8080".into(), ScalarKind::Bare));
+        assert_eq!(scalar(&value, "ratio"), ("0.5".into(), ScalarKind::Bare));
+        assert_eq!(
+            scalar(&value, "workers[0].threads"),
+            ("4".into(), ScalarKind::Bare)
+        );
+        // Strings stay strings
+        assert_eq!(scalar(&value, "name"), ("8080".into(), ScalarKind::Quoted));
+    }
+
+    #[test]
+    fn test_reports_values_that_cannot_coerce() {
+        let mut value = styx_tree::parse("debug \"yes\"\nport \"8080\"\nratio @").unwrap();
+        let result = coerce_to_schema(&mut value, &schema());
+
+        assert_eq!(result.errors.len(), 1, "{:?}", result.errors);
+        assert_eq!(result.errors[0].path, "debug");
+        assert_eq!(
+            result.errors[0].message,
+            "'yes' cannot be coerced to boolean"
+        );
+        // The rest of the document is still coerced
+        assert_eq!(scalar(&value, "port"), ("8080".into(), ScalarKind::Bare));
+        assert_eq!(scalar(&value, "debug"), ("yes".into(), ScalarKind::Quoted));
+    }
+}
@@ -0,0 +1,912 @@
+//! Schema compatibility checking.
+//!
+//! Compares two versions of a schema and classifies every difference by its
+//! effect on documents written against the older one:
+//!
+//! - **breaking**: a document valid under the baseline may now fail validation
+//! - **additive**: the schema now accepts documents it used to reject
+//! - **patch**: documents validate exactly as before (docs, defaults, deprecations)
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::schema_types::{
+    Documented, EnumSchema, FloatConstraints, IntConstraints, ObjectKey, ObjectSchema, Schema,
+    SchemaFile, StringConstraints,
+};
+use crate::schema_validate::schema_type_name;
+
+/// What changed between two schema versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// A named type was removed.
+    TypeRemoved,
+    /// A named type was added.
+    TypeAdded,
+    /// A value changed to an incompatible type.
+    TypeChanged,
+    /// A value was widened to `@any`.
+    TypeWidened,
+    /// Scalar constraints (`min`, `maxLen`, `pattern`, ...) were tightened.
+    ConstraintsNarrowed,
+    /// Scalar constraints were relaxed or removed.
+    ConstraintsWidened,
+    /// An object field was removed.
+    FieldRemoved,
+    /// An optional object field was added.
+    OptionalFieldAdded,
+    /// A required object field was added.
+    RequiredFieldAdded,
+    /// A value was wrapped in `@optional` or `@default`.
+    MadeOptional,
+    /// A value was unwrapped from `@optional` or `@default`.
+    MadeRequired,
+    /// An optional value gained a default.
+    DefaultAdded,
+    /// A defaulted value lost its default but stays optional.
+    DefaultRemoved,
+    /// A default value changed.
+    DefaultChanged,
+    /// A value was marked `@deprecated`.
+    Deprecated,
+    /// A value is no longer `@deprecated`.
+    Undeprecated,
+    /// Field or variant documentation changed.
+    DocChanged,
+    /// An enum variant was removed.
+    VariantRemoved,
+    /// An enum variant was added.
+    VariantAdded,
+    /// A union member was removed.
+    UnionMemberRemoved,
+    /// A union member was added.
+    UnionMemberAdded,
+    /// A `@one-of` value was removed.
+    AllowedValueRemoved,
+    /// A `@one-of` value was added.
+    AllowedValueAdded,
+    /// A tuple gained or lost elements.
+    TupleLengthChanged,
+}
+
+impl ChangeKind {
+    /// Stable kebab-case identifier, e.g. `field-removed`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeKind::TypeRemoved => "type-removed",
+            ChangeKind::TypeAdded => "type-added",
+            ChangeKind::TypeChanged => "type-changed",
+            ChangeKind::TypeWidened => "type-widened",
+            ChangeKind::ConstraintsNarrowed => "constraints-narrowed",
+            ChangeKind::ConstraintsWidened => "constraints-widened",
+            ChangeKind::FieldRemoved => "field-removed",
+            ChangeKind::OptionalFieldAdded => "optional-field-added",
+            ChangeKind::RequiredFieldAdded => "required-field-added",
+            ChangeKind::MadeOptional => "made-optional",
+            ChangeKind::MadeRequired => "made-required",
+            ChangeKind::DefaultAdded => "default-added",
+            ChangeKind::DefaultRemoved => "default-removed",
+            ChangeKind::DefaultChanged => "default-changed",
+            ChangeKind::Deprecated => "deprecated",
+            ChangeKind::Undeprecated => "undeprecated",
+            ChangeKind::DocChanged => "doc-changed",
+            ChangeKind::VariantRemoved => "variant-removed",
+            ChangeKind::VariantAdded => "variant-added",
+            ChangeKind::UnionMemberRemoved => "union-member-removed",
+            ChangeKind::UnionMemberAdded => "union-member-added",
+            ChangeKind::AllowedValueRemoved => "allowed-value-removed",
+            ChangeKind::AllowedValueAdded => "allowed-value-added",
+            ChangeKind::TupleLengthChanged => "tuple-length-changed",
+        }
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single difference between two schema versions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaChange {
+    /// What changed.
+    pub kind: ChangeKind,
+    /// Dotted path to the changed definition, e.g. `Server.port`.
+    /// Root fields have no prefix; the root type itself is `@`.
+    pub path: String,
+    /// Human-readable description of the change.
+    pub message: String,
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Differences between two schema versions, grouped by semver impact.
+///
+/// Each list is sorted by path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Compatibility {
+    /// Changes that can reject previously valid documents (major bump).
+    pub breaking: Vec<SchemaChange>,
+    /// Changes that only accept more documents (minor bump).
+    pub additive: Vec<SchemaChange>,
+    /// Changes that do not affect validation (patch bump).
+    pub patch: Vec<SchemaChange>,
+}
+
+impl Compatibility {
+    /// Returns true if the two schemas are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.breaking.is_empty() && self.additive.is_empty() && self.patch.is_empty()
+    }
+
+    /// Returns true if every document valid under the baseline is still valid.
+    pub fn is_compatible(&self) -> bool {
+        self.breaking.is_empty()
+    }
+}
+
+/// Compare two versions of a schema.
+///
+/// # Example
+///
+/// ```
+/// use facet_styx::{SchemaFile, compatibility, from_str};
+///
+/// let baseline: SchemaFile =
+///     from_str("meta {id app}\nschema {@ @object{host @string, port @int}}").unwrap();
+/// let current: SchemaFile =
+///     from_str("meta {id app}\nschema {@ @object{host @string}}").unwrap();
+///
+/// let compat = compatibility(&baseline, &current);
+/// assert!(!compat.is_compatible());
+/// assert_eq!(compat.breaking[0].to_string(), "removed field `port` from `(root)`");
+/// ```
+pub fn compatibility(baseline: &SchemaFile, current: &SchemaFile) -> Compatibility {
+    let mut checker = Checker::default();
+
+    for (name, baseline_schema) in &baseline.schema {
+        let path = type_path(name);
+        match current.schema.get(name) {
+            Some(current_schema) => {
+                let field_prefix = name.as_deref().unwrap_or("");
+                checker.compare_definition(&path, field_prefix, baseline_schema, current_schema);
+            }
+            None => {
+                let message = format!("removed type `{}`", type_label(name));
+                checker.breaking(ChangeKind::TypeRemoved, &path, message);
+            }
+        }
+    }
+
+    for name in current.schema.keys() {
+        if !baseline.schema.contains_key(name) {
+            let message = format!("added type `{}`", type_label(name));
+            checker.additive(ChangeKind::TypeAdded, &type_path(name), message);
+        }
+    }
+
+    let mut compat = checker.changes;
+    for list in [
+        &mut compat.breaking,
+        &mut compat.additive,
+        &mut compat.patch,
+    ] {
+        list.sort_by(|a, b| (&a.path, &a.message).cmp(&(&b.path, &b.message)));
+    }
+    compat
+}
+
+/// Compare two definitions found at the same path, e.g. one object field.
+pub(crate) fn compare_definitions(
+    path: &str,
+    baseline: &Schema,
+    current: &Schema,
+) -> Compatibility {
+    let mut checker = Checker::default();
+    checker.compare(path, baseline, current);
+    checker.changes
+}
+
+/// Path of a top-level type definition.
+pub(crate) fn type_path(name: &Option<String>) -> String {
+    name.clone().unwrap_or_else(|| "@".into())
+}
+
+/// Name of a top-level type definition as shown in messages.
+fn type_label(name: &Option<String>) -> &str {
+    name.as_deref().unwrap_or("(root)")
+}
+
+/// Name of an object or enum owning `path`, as shown in messages.
+fn owner_label(path: &str) -> &str {
+    if path.is_empty() { "(root)" } else { path }
+}
+
+/// Append a field name to a path.
+pub(crate) fn child_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+/// Display form of an object key: field name, `@type` pattern, or `@`.
+pub(crate) fn key_label(key: &ObjectKey) -> String {
+    match (&key.value, &key.tag) {
+        (Some(name), _) => name.clone(),
+        (None, Some(tag)) => format!("@{tag}"),
+        (None, None) => "@".into(),
+    }
+}
+
+/// Whether a field may be absent from a document.
+fn is_optional_field(schema: &Schema) -> bool {
+    match schema {
+        Schema::Optional(_) | Schema::Default(_) => true,
+        Schema::Deprecated(d) => is_optional_field(&d.0.1.value),
+        _ => false,
+    }
+}
+
+/// Inner schema of `@optional(@T)` or `@default(value @T)`.
+fn optional_inner(schema: &Schema) -> &Schema {
+    match schema {
+        Schema::Optional(o) => &o.0.0.value,
+        Schema::Default(d) => &d.0.1.value,
+        _ => schema,
+    }
+}
+
+/// Key schema of an explicit `@map(@K @V)`.
+fn map_key_schema(items: &[Documented<Schema>]) -> Option<&Schema> {
+    match items {
+        [key, _] => Some(&key.value),
+        _ => None,
+    }
+}
+
+/// How a single constraint moved between versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Same,
+    Widened,
+    Narrowed,
+}
+
+/// Compare an inclusive lower bound (`min`, `minLen`).
+fn lower_bound<T: PartialOrd>(baseline: Option<T>, current: Option<T>) -> Bound {
+    match (baseline, current) {
+        (None, None) => Bound::Same,
+        (Some(_), None) => Bound::Widened,
+        (None, Some(_)) => Bound::Narrowed,
+        (Some(b), Some(c)) if c < b => Bound::Widened,
+        (Some(b), Some(c)) if c > b => Bound::Narrowed,
+        _ => Bound::Same,
+    }
+}
+
+/// Compare an inclusive upper bound (`max`, `maxLen`).
+fn upper_bound<T: PartialOrd>(baseline: Option<T>, current: Option<T>) -> Bound {
+    match lower_bound(baseline, current) {
+        Bound::Widened => Bound::Narrowed,
+        Bound::Narrowed => Bound::Widened,
+        Bound::Same => Bound::Same,
+    }
+}
+
+/// Compare a regex pattern. Two different patterns cannot be ordered, so any
+/// change other than removal counts as narrowing.
+fn pattern_bound(baseline: Option<&str>, current: Option<&str>) -> Bound {
+    match (baseline, current) {
+        (None, None) => Bound::Same,
+        (Some(_), None) => Bound::Widened,
+        (Some(b), Some(c)) if b == c => Bound::Same,
+        _ => Bound::Narrowed,
+    }
+}
+
+fn string_bounds(constraints: &Option<StringConstraints>) -> (Option<usize>, Option<usize>) {
+    constraints
+        .as_ref()
+        .map_or((None, None), |c| (c.min_len, c.max_len))
+}
+
+fn int_bounds(constraints: &Option<IntConstraints>) -> (Option<i128>, Option<i128>) {
+    constraints
+        .as_ref()
+        .map_or((None, None), |c| (c.min, c.max))
+}
+
+fn float_bounds(constraints: &Option<FloatConstraints>) -> (Option<f64>, Option<f64>) {
+    constraints
+        .as_ref()
+        .map_or((None, None), |c| (c.min, c.max))
+}
+
+/// Accumulates changes while walking both schemas.
+#[derive(Default)]
+struct Checker {
+    changes: Compatibility,
+}
+
+impl Checker {
+    fn breaking(&mut self, kind: ChangeKind, path: &str, message: String) {
+        self.changes.breaking.push(SchemaChange {
+            kind,
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    fn additive(&mut self, kind: ChangeKind, path: &str, message: String) {
+        self.changes.additive.push(SchemaChange {
+            kind,
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    fn patch(&mut self, kind: ChangeKind, path: &str, message: String) {
+        self.changes.patch.push(SchemaChange {
+            kind,
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    /// Compare a top-level type definition. Fields of the definition are
+    /// reported under `field_prefix` (empty for the root type).
+    fn compare_definition(
+        &mut self,
+        path: &str,
+        field_prefix: &str,
+        baseline: &Schema,
+        current: &Schema,
+    ) {
+        match (baseline, current) {
+            (Schema::Object(b), Schema::Object(c)) => self.compare_objects(field_prefix, b, c),
+            (Schema::Enum(b), Schema::Enum(c)) => self.compare_enums(field_prefix, b, c),
+            _ => self.compare(path, baseline, current),
+        }
+    }
+
+    /// Compare two schemas found at the same path.
+    fn compare(&mut self, path: &str, baseline: &Schema, current: &Schema) {
+        match (baseline, current) {
+            (Schema::Deprecated(b), Schema::Deprecated(c)) => {
+                self.compare(path, &b.0.1.value, &c.0.1.value);
+            }
+            (Schema::Deprecated(b), _) => {
+                self.patch(
+                    ChangeKind::Undeprecated,
+                    path,
+                    format!("`{path}` is no longer deprecated"),
+                );
+                self.compare(path, &b.0.1.value, current);
+            }
+            (_, Schema::Deprecated(c)) => {
+                self.patch(
+                    ChangeKind::Deprecated,
+                    path,
+                    format!("deprecated `{path}`: {}", c.0.0),
+                );
+                self.compare(path, baseline, &c.0.1.value);
+            }
+
+            (Schema::Object(b), Schema::Object(c)) => self.compare_objects(path, b, c),
+            (Schema::Enum(b), Schema::Enum(c)) => self.compare_enums(path, b, c),
+
+            (Schema::String(b), Schema::String(c)) => {
+                let (b_min, b_max) = string_bounds(b);
+                let (c_min, c_max) = string_bounds(c);
+                let b_pattern = b.as_ref().and_then(|s| s.pattern.as_deref());
+                let c_pattern = c.as_ref().and_then(|s| s.pattern.as_deref());
+                self.compare_bounds(
+                    path,
+                    "string",
+                    &[
+                        lower_bound(b_min, c_min),
+                        upper_bound(b_max, c_max),
+                        pattern_bound(b_pattern, c_pattern),
+                    ],
+                );
+            }
+            (Schema::Int(b), Schema::Int(c)) => {
+                let (b_min, b_max) = int_bounds(b);
+                let (c_min, c_max) = int_bounds(c);
+                self.compare_bounds(
+                    path,
+                    "int",
+                    &[lower_bound(b_min, c_min), upper_bound(b_max, c_max)],
+                );
+            }
+            (Schema::Float(b), Schema::Float(c)) => {
+                let (b_min, b_max) = float_bounds(b);
+                let (c_min, c_max) = float_bounds(c);
+                self.compare_bounds(
+                    path,
+                    "float",
+                    &[lower_bound(b_min, c_min), upper_bound(b_max, c_max)],
+                );
+            }
+            (Schema::Bool, Schema::Bool)
+            | (Schema::Unit, Schema::Unit)
+            | (Schema::Any, Schema::Any) => {}
+
+            (Schema::Seq(b), Schema::Seq(c)) => {
+                self.compare(&format!("{path}[]"), &b.0.0.value, &c.0.0.value);
+            }
+            (Schema::Optional(b), Schema::Optional(c)) => {
+                self.compare(path, &b.0.0.value, &c.0.0.value);
+            }
+            (Schema::Flatten(b), Schema::Flatten(c)) => {
+                self.compare(path, &b.0.0.value, &c.0.0.value);
+            }
+            (Schema::Default(b), Schema::Default(c)) => {
+                if b.0.0 != c.0.0 {
+                    self.patch(
+                        ChangeKind::DefaultChanged,
+                        path,
+                        format!("changed default of `{path}` from {} to {}", b.0.0, c.0.0),
+                    );
+                }
+                self.compare(path, &b.0.1.value, &c.0.1.value);
+            }
+
+            // Wrapping in @optional or @default lets the value be absent;
+            // unwrapping makes it mandatory.
+            (Schema::Optional(b), Schema::Default(c)) => {
+                self.patch(
+                    ChangeKind::DefaultAdded,
+                    path,
+                    format!("added default {} to `{path}`", c.0.0),
+                );
+                self.compare(path, &b.0.0.value, &c.0.1.value);
+            }
+            (Schema::Default(b), Schema::Optional(c)) => {
+                self.patch(
+                    ChangeKind::DefaultRemoved,
+                    path,
+                    format!("removed default of `{path}`"),
+                );
+                self.compare(path, &b.0.1.value, &c.0.0.value);
+            }
+            (Schema::Optional(_) | Schema::Default(_), _) => {
+                self.breaking(
+                    ChangeKind::MadeRequired,
+                    path,
+                    format!("made `{path}` required"),
+                );
+                self.compare(path, optional_inner(baseline), current);
+            }
+            (_, Schema::Optional(_) | Schema::Default(_)) => {
+                self.additive(
+                    ChangeKind::MadeOptional,
+                    path,
+                    format!("made `{path}` optional"),
+                );
+                self.compare(path, baseline, optional_inner(current));
+            }
+
+            (Schema::Map(b), Schema::Map(c)) => {
+                // `@map(@V)` is shorthand for `@map(@string @V)`
+                let string_key = Schema::String(None);
+                self.compare(
+                    path,
+                    map_key_schema(&b.0).unwrap_or(&string_key),
+                    map_key_schema(&c.0).unwrap_or(&string_key),
+                );
+                if let (Some(b_value), Some(c_value)) = (b.0.last(), c.0.last()) {
+                    self.compare(path, &b_value.value, &c_value.value);
+                }
+            }
+            (Schema::Tuple(b), Schema::Tuple(c)) => {
+                if b.0.len() != c.0.len() {
+                    self.breaking(
+                        ChangeKind::TupleLengthChanged,
+                        path,
+                        format!(
+                            "changed tuple `{path}` from {} to {} elements",
+                            b.0.len(),
+                            c.0.len()
+                        ),
+                    );
+                    return;
+                }
+                for (b_item, c_item) in b.0.iter().zip(&c.0) {
+                    self.compare(path, &b_item.value, &c_item.value);
+                }
+            }
+            (Schema::Union(b), Schema::Union(c)) => {
+                // Members are matched by type, since their order doesn't
+                // change which documents the union accepts
+                let mut unmatched: Vec<_> = c.0.iter().collect();
+                for b_member in &b.0 {
+                    let type_name = schema_type_name(&b_member.value);
+                    match unmatched
+                        .iter()
+                        .position(|c_member| schema_type_name(&c_member.value) == type_name)
+                    {
+                        Some(index) => {
+                            let c_member = unmatched.remove(index);
+                            self.compare(path, &b_member.value, &c_member.value);
+                        }
+                        None => self.breaking(
+                            ChangeKind::UnionMemberRemoved,
+                            path,
+                            format!("removed @{type_name} from union `{path}`"),
+                        ),
+                    }
+                }
+                for added in unmatched {
+                    self.additive(
+                        ChangeKind::UnionMemberAdded,
+                        path,
+                        format!(
+                            "added @{} to union `{path}`",
+                            schema_type_name(&added.value)
+                        ),
+                    );
+                }
+            }
+            (Schema::OneOf(b), Schema::OneOf(c)) => {
+                self.compare(path, &b.0.0.value, &c.0.0.value);
+                for value in &b.0.1 {
+                    if !c.0.1.contains(value) {
+                        self.breaking(
+                            ChangeKind::AllowedValueRemoved,
+                            path,
+                            format!("removed allowed value {value} from `{path}`"),
+                        );
+                    }
+                }
+                for value in &c.0.1 {
+                    if !b.0.1.contains(value) {
+                        self.additive(
+                            ChangeKind::AllowedValueAdded,
+                            path,
+                            format!("added allowed value {value} to `{path}`"),
+                        );
+                    }
+                }
+            }
+            (Schema::Literal(b), Schema::Literal(c)) if b == c => {}
+            (Schema::Type { name: b }, Schema::Type { name: c }) if b == c => {}
+
+            (_, Schema::Any) => {
+                self.additive(
+                    ChangeKind::TypeWidened,
+                    path,
+                    format!(
+                        "widened `{path}` from @{} to @any",
+                        schema_type_name(baseline)
+                    ),
+                );
+            }
+            _ => {
+                self.breaking(
+                    ChangeKind::TypeChanged,
+                    path,
+                    format!(
+                        "changed type of `{path}` from @{} to @{}",
+                        schema_type_name(baseline),
+                        schema_type_name(current)
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Classify a set of constraint changes on a scalar type.
+    fn compare_bounds(&mut self, path: &str, type_name: &str, bounds: &[Bound]) {
+        if bounds.contains(&Bound::Narrowed) {
+            self.breaking(
+                ChangeKind::ConstraintsNarrowed,
+                path,
+                format!("narrowed @{type_name} constraints of `{path}`"),
+            );
+        } else if bounds.contains(&Bound::Widened) {
+            self.additive(
+                ChangeKind::ConstraintsWidened,
+                path,
+                format!("widened @{type_name} constraints of `{path}`"),
+            );
+        }
+    }
+
+    fn compare_objects(&mut self, path: &str, baseline: &ObjectSchema, current: &ObjectSchema) {
+        let owner = owner_label(path);
+        let current_fields: HashMap<&ObjectKey, _> = current
+            .0
+            .iter()
+            .map(|(key, schema)| (&key.value, (key.doc(), schema)))
+            .collect();
+
+        for (key, baseline_schema) in &baseline.0 {
+            let label = key_label(&key.value);
+            let field_path = child_path(path, &label);
+            match current_fields.get(&key.value) {
+                Some((current_doc, current_schema)) => {
+                    if key.doc() != *current_doc {
+                        self.patch(
+                            ChangeKind::DocChanged,
+                            &field_path,
+                            format!("updated documentation of field `{label}` in `{owner}`"),
+                        );
+                    }
+                    self.compare(&field_path, baseline_schema, current_schema);
+                }
+                None => {
+                    self.breaking(
+                        ChangeKind::FieldRemoved,
+                        &field_path,
+                        format!("removed field `{label}` from `{owner}`"),
+                    );
+                }
+            }
+        }
+
+        for (key, current_schema) in &current.0 {
+            if baseline.0.contains_key(key) {
+                continue;
+            }
+            let label = key_label(&key.value);
+            let field_path = child_path(path, &label);
+            if is_optional_field(current_schema) {
+                self.additive(
+                    ChangeKind::OptionalFieldAdded,
+                    &field_path,
+                    format!("added optional field `{label}` to `{owner}`"),
+                );
+            } else {
+                self.breaking(
+                    ChangeKind::RequiredFieldAdded,
+                    &field_path,
+                    format!("added required field `{label}` to `{owner}`"),
+                );
+            }
+        }
+    }
+
+    fn compare_enums(&mut self, path: &str, baseline: &EnumSchema, current: &EnumSchema) {
+        let owner = owner_label(path);
+        let current_variants: HashMap<&str, _> = current
+            .0
+            .iter()
+            .map(|(name, schema)| (name.value.as_str(), (name.doc(), schema)))
+            .collect();
+
+        for (name, baseline_schema) in &baseline.0 {
+            let variant = name.value.as_str();
+            let variant_path = child_path(path, variant);
+            match current_variants.get(variant) {
+                Some((current_doc, current_schema)) => {
+                    if name.doc() != *current_doc {
+                        self.patch(
+                            ChangeKind::DocChanged,
+                            &variant_path,
+                            format!("updated documentation of variant `{variant}` in `{owner}`"),
+                        );
+                    }
+                    self.compare(&variant_path, baseline_schema, current_schema);
+                }
+                None => {
+                    self.breaking(
+                        ChangeKind::VariantRemoved,
+                        &variant_path,
+                        format!("removed variant `{variant}` from `{owner}`"),
+                    );
+                }
+            }
+        }
+
+        for name in current.0.keys() {
+            if !baseline.0.contains_key(name) {
+                let variant = name.value.as_str();
+                self.additive(
+                    ChangeKind::VariantAdded,
+                    &child_path(path, variant),
+                    format!("added variant `{variant}` to `{owner}`"),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+    use facet_testhelpers::test;
+
+    fn schema(definitions: &str) -> SchemaFile {
+        from_str(&format!("meta {{id test}}\nschema {{\n{definitions}\n}}"))
+            .expect("schema should parse")
+    }
+
+    fn messages(changes: &[SchemaChange]) -> Vec<&str> {
+        changes.iter().map(|c| c.message.as_str()).collect()
+    }
+
+    #[test]
+    fn test_identical_schemas() {
+        let baseline = schema("@ @object{host @string, port @optional(@int)}");
+        let compat = compatibility(&baseline, &baseline.clone());
+        assert!(compat.is_empty());
+        assert!(compat.is_compatible());
+    }
+
+    #[test]
+    fn test_removed_field_is_breaking() {
+        let baseline = schema("Server @object{host @string, port @int}");
+        let current = schema("Server @object{host @string}");
+
+        let compat = compatibility(&baseline, &current);
+        assert_eq!(
+            messages(&compat.breaking),
+            ["removed field `port` from `Server`"]
+        );
+        assert_eq!(compat.breaking[0].kind, ChangeKind::FieldRemoved);
+        assert_eq!(compat.breaking[0].path, "Server.port");
+        assert!(compat.additive.is_empty());
+    }
+
+    #[test]
+    fn test_added_optional_field_is_additive() {
+        let baseline = schema("@ @object{host @string}");
+        let current = schema("@ @object{host @string, port @optional(@int), tls @bool}");
+
+        let compat = compatibility(&baseline, &current);
+        assert_eq!(
+            messages(&compat.additive),
+            ["added optional field `port` to `(root)`"]
+        );
+        assert_eq!(
+            messages(&compat.breaking),
+            ["added required field `tls` to `(root)`"]
+        );
+    }
+
+    #[test]
+    fn test_widened_type_is_additive() {
+        let baseline = schema("@ @object{port @int{min 1024, max 65535}, name @string}");
+        let current = schema("@ @object{port @int{min 1, max 65535}, name @any}");
+
+        let compat = compatibility(&baseline, &current);
+        assert!(compat.is_compatible());
+        assert_eq!(
+            messages(&compat.additive),
+            [
+                "widened `name` from @string to @any",
+                "widened @int constraints of `port`",
+            ]
+        );
+
+        // The reverse direction narrows both fields
+        let compat = compatibility(&current, &baseline);
+        assert_eq!(compat.breaking.len(), 2);
+    }
+
+    #[test]
+    fn test_scalar_type_change_is_breaking() {
+        let baseline = schema("@ @object{port @string}");
+        let current = schema("@ @object{port @int}");
+
+        let compat = compatibility(&baseline, &current);
+        assert_eq!(
+            messages(&compat.breaking),
+            ["changed type of `port` from @string to @int"]
+        );
+        assert!(compat.additive.is_empty());
+    }
+
+    #[test]
+    fn test_required_to_optional_is_additive() {
+        let baseline = schema("@ @object{port @int, host @string}");
+        let current = schema("@ @object{port @optional(@int), host @default(localhost @string)}");
+
+        let compat = compatibility(&baseline, &current);
+        assert!(compat.is_compatible());
+        assert_eq!(
+            messages(&compat.additive),
+            ["made `host` optional", "made `port` optional"]
+        );
+
+        // Making them required again breaks documents that omit them
+        let compat = compatibility(&current, &baseline);
+        assert_eq!(
+            messages(&compat.breaking),
+            ["made `host` required", "made `port` required"]
+        );
+    }
+
+    #[test]
+    fn test_optional_inner_type_change_is_breaking() {
+        let baseline = schema("@ @object{port @int}");
+        let current = schema("@ @object{port @optional(@string)}");
+
+        let compat = compatibility(&baseline, &current);
+        assert_eq!(messages(&compat.additive), ["made `port` optional"]);
+        assert_eq!(
+            messages(&compat.breaking),
+            ["changed type of `port` from @int to @string"]
+        );
+    }
+
+    #[test]
+    fn test_seq_element_type_change_is_breaking() {
+        let baseline = schema("@ @object{ids @seq(@int), names @seq(@string{maxLen 8})}");
+        let current = schema("@ @object{ids @seq(@string), names @seq(@string)}");
+
+        let compat = compatibility(&baseline, &current);
+        assert_eq!(
+            messages(&compat.breaking),
+            ["changed type of `ids[]` from @int to @string"]
+        );
+        assert_eq!(compat.breaking[0].path, "ids[]");
+        assert_eq!(
+            messages(&compat.additive),
+            ["widened @string constraints of `names[]`"]
+        );
+    }
+
+    #[test]
+    fn test_doc_and_deprecation_changes_are_patch() {
+        let baseline = schema("@ @object{\n/// Hostname\nhost @string\n}");
+        let current =
+            schema("@ @object{\n/// Hostname to bind\nhost @deprecated(\"use addr\" @string)\n}");
+
+        let compat = compatibility(&baseline, &current);
+        assert!(compat.breaking.is_empty());
+        assert!(compat.additive.is_empty());
+        assert_eq!(
+            messages(&compat.patch),
+            [
+                "deprecated `host`: use addr",
+                "updated documentation of field `host` in `(root)`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_union_members_are_matched_by_type() {
+        let baseline = schema("@ @object{id @union(@int @string{maxLen 8})}");
+
+        // Reordering members changes nothing
+        let reordered = schema("@ @object{id @union(@string{maxLen 8} @int)}");
+        assert!(compatibility(&baseline, &reordered).is_empty());
+
+        let current = schema("@ @object{id @union(@bool @string)}");
+        let compat = compatibility(&baseline, &current);
+        assert_eq!(messages(&compat.breaking), ["removed @int from union `id`"]);
+        assert_eq!(
+            messages(&compat.additive),
+            [
+                "added @bool to union `id`",
+                "widened @string constraints of `id`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enum_variants_and_types() {
+        let baseline = schema("Level @enum{debug, info}\nOld @string");
+        let current = schema("Level @enum{info, warn}\nNew @string");
+
+        let compat = compatibility(&baseline, &current);
+        assert_eq!(
+            messages(&compat.breaking),
+            ["removed variant `debug` from `Level`", "removed type `Old`"]
+        );
+        assert_eq!(
+            messages(&compat.additive),
+            ["added variant `warn` to `Level`", "added type `New`"]
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Structured reading of schema doc comments.
+
+use crate::schema_types::Documented;
+
+/// Tags recognized at the start of a doc comment line.
+///
+/// Other lines starting with `@` are ordinary text, so docs can mention
+/// types like `@string` at the start of a line.
+const DOC_TAGS: &[&str] = &["example", "deprecated", "since", "see"];
+
+/// A doc comment split into a summary, a body and tags.
+///
+/// ```text
+/// /// Port the server listens on.
+/// ///
+/// /// Ports below 1024 need elevated privileges.
+/// /// @example 8080
+/// port @int
+/// ```
+///
+/// has the summary `Port the server listens on.`, one body line and an
+/// `@example` tag with the text `8080`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocComment {
+    /// The first paragraph, joined into one line.
+    pub summary: String,
+    /// The lines after the first paragraph, up to the first tag. Paragraphs
+    /// are separated by empty lines.
+    pub body: Vec<String>,
+    /// Tags, in the order they appear.
+    pub tags: Vec<DocTag>,
+}
+
+/// A tag in a doc comment, like `@example {host localhost}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocTag {
+    /// Tag name, without the `@`.
+    pub name: String,
+    /// Text after the name. Lines following the tag line belong to it, up
+    /// to an empty line or the next tag.
+    pub text: String,
+}
+
+impl DocComment {
+    /// Parse doc comment lines, as stored in [`Documented::doc`].
+    pub fn parse<S: AsRef<str>>(lines: &[S]) -> Self {
+        let mut doc = DocComment::default();
+        let mut summary = Vec::new();
+        let mut in_summary = true;
+        let mut in_tag = false;
+
+        for line in lines.iter().map(AsRef::as_ref) {
+            if let Some((name, text)) = parse_tag_line(line) {
+                doc.tags.push(DocTag {
+                    name: name.to_string(),
+                    text: text.to_string(),
+                });
+                in_summary = false;
+                in_tag = true;
+                continue;
+            }
+
+            let blank = line.trim().is_empty();
+            if in_tag {
+                if blank {
+                    in_tag = false;
+                } else {
+                    let tag = doc.tags.last_mut().expect("in a tag");
+                    if !tag.text.is_empty() {
+                        tag.text.push('\n');
+                    }
+                    tag.text.push_str(line.trim_end());
+                }
+            } else if in_summary {
+                if blank {
+                    in_summary = summary.is_empty();
+                } else {
+                    summary.push(line.trim());
+                }
+            } else if doc.tags.is_empty() {
+                doc.body.push(line.trim_end().to_string());
+            }
+        }
+
+        doc.summary = summary.join(" ");
+        while doc.body.first().is_some_and(|l| l.is_empty()) {
+            doc.body.remove(0);
+        }
+        while doc.body.last().is_some_and(|l| l.is_empty()) {
+            doc.body.pop();
+        }
+        doc
+    }
+
+    /// Text of each `@example` tag.
+    pub fn examples(&self) -> impl Iterator<Item = &str> {
+        self.tags_named("example")
+    }
+
+    /// Text of each tag called `name`.
+    pub fn tags_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.tags
+            .iter()
+            .filter(move |tag| tag.name == name)
+            .map(|tag| tag.text.as_str())
+    }
+}
+
+/// Split `@name text` into name and text, if `name` is a known tag.
+fn parse_tag_line(line: &str) -> Option<(&str, &str)> {
+    let rest = line.trim_start().strip_prefix('@')?;
+    let (name, text) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    DOC_TAGS.contains(&name).then(|| (name, text.trim()))
+}
+
+impl<T> Documented<T> {
+    /// The documentation parsed into summary, body and tags, if there is any.
+    pub fn parsed_doc(&self) -> Option<DocComment> {
+        self.doc.as_deref().map(DocComment::parse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use facet_testhelpers::test;
+
+    #[test]
+    fn test_summary_body_and_example() {
+        let lines = [
+            "Address the server binds to.",
+            "Either a host name or an IP address.",
+            "",
+            "Binding to 0.0.0.0 accepts connections",
+            "on every interface.",
+            "",
+            "@string values are not resolved until startup.",
+            "@example {host localhost, port 8080}",
+            "@example {",
+            "    host 0.0.0.0",
+            "    port 80",
+            "}",
+            "",
+            "@deprecated use `listen` instead",
+        ];
+        let doc = DocComment::parse(&lines);
+
+        assert_eq!(
+            doc.summary,
+            "Address the server binds to. Either a host name or an IP address."
+        );
+        assert_eq!(
+            doc.body,
+            [
+                "Binding to 0.0.0.0 accepts connections",
+                "on every interface.",
+                "",
+                "@string values are not resolved until startup.",
+            ]
+        );
+        assert_eq!(
+            doc.examples().collect::<Vec<_>>(),
+            [
+                "{host localhost, port 8080}",
+                "{\n    host 0.0.0.0\n    port 80\n}"
+            ]
+        );
+        assert_eq!(
+            doc.tags_named("deprecated").collect::<Vec<_>>(),
+            ["use `listen` instead"]
+        );
+    }
+
+    #[test]
+    fn test_summary_only() {
+        let doc = DocComment::parse(&["Port to listen on."]);
+        assert_eq!(doc.summary, "Port to listen on.");
+        assert!(doc.body.is_empty());
+        assert!(doc.tags.is_empty());
+
+        assert_eq!(DocComment::parse::<&str>(&[]), DocComment::default());
+    }
+
+    #[test]
+    fn test_documented_parsed_doc() {
+        let port = Documented::with_doc(8080, vec!["Port.".into(), "@example 443".into()]);
+        let doc = port.parsed_doc().unwrap();
+        assert_eq!(doc.summary, "Port.");
+        assert_eq!(doc.examples().collect::<Vec<_>>(), ["443"]);
+
+        assert_eq!(Documented::new(8080).parsed_doc(), None);
+    }
+}
@@ -161,6 +161,19 @@ impl ValidationError {
         String::from_utf8(output).unwrap_or_else(|_| format!("{}", self))
     }
 
+    /// This error with its span as line and column positions in `source`,
+    /// the document it was found in.
+    pub fn to_diagnostic(&self, source: &str) -> Diagnostic {
+        Diagnostic::new(
+            Severity::Error,
+            &self.path,
+            self.span,
+            self.kind.code(),
+            &self.message,
+            source,
+        )
+    }
+
     /// Write the error report to a writer.
     pub fn write_report<W: std::io::Write>(&self, filename: &str, source: &str, writer: W) {
         let report = self.build_report(filename);
@@ -388,6 +401,28 @@ pub enum ValidationErrorKind {
     SchemaError { reason: String },
 }
 
+impl ValidationErrorKind {
+    /// Stable kebab-case identifier for this kind of error, e.g.
+    /// `missing-field`, for tools that match on diagnostics.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationErrorKind::MissingField { .. } => "missing-field",
+            ValidationErrorKind::UnknownField { .. } => "unknown-field",
+            ValidationErrorKind::TypeMismatch { .. } => "type-mismatch",
+            ValidationErrorKind::InvalidValue { .. } => "invalid-value",
+            ValidationErrorKind::UnknownType { .. } => "unknown-type",
+            ValidationErrorKind::InvalidVariant { .. } => "invalid-variant",
+            ValidationErrorKind::UnionMismatch { .. } => "union-mismatch",
+            ValidationErrorKind::ExpectedObject => "expected-object",
+            ValidationErrorKind::ExpectedSequence => "expected-sequence",
+            ValidationErrorKind::ExpectedScalar => "expected-scalar",
+            ValidationErrorKind::ExpectedTagged => "expected-tagged",
+            ValidationErrorKind::WrongTag { .. } => "wrong-tag",
+            ValidationErrorKind::SchemaError { .. } => "schema-error",
+        }
+    }
+}
+
 /// A validation warning (non-fatal).
 #[derive(Debug, Clone)]
 pub struct ValidationWarning {
@@ -422,6 +457,19 @@ impl ValidationWarning {
         self
     }
 
+    /// This warning with its span as line and column positions in `source`,
+    /// the document it was found in.
+    pub fn to_diagnostic(&self, source: &str) -> Diagnostic {
+        Diagnostic::new(
+            Severity::Warning,
+            &self.path,
+            self.span,
+            self.kind.code(),
+            &self.message,
+            source,
+        )
+    }
+
     /// Write the warning report to a writer.
     pub fn write_report<W: std::io::Write>(&self, filename: &str, source: &str, writer: W) {
         let range = self
@@ -448,6 +496,24 @@ impl ValidationWarning {
                             .with_color(Color::Yellow),
                     )
             }
+            ValidationWarningKind::InvalidExample { .. } => {
+                Report::build(ReportKind::Warning, (filename, range.clone()))
+                    .with_message("invalid example")
+                    .with_label(
+                        Label::new((filename, range))
+                            .with_message(&self.message)
+                            .with_color(Color::Yellow),
+                    )
+            }
+            ValidationWarningKind::MissingField { field } => {
+                Report::build(ReportKind::Warning, (filename, range.clone()))
+                    .with_message(format!("missing required field '{field}'"))
+                    .with_label(
+                        Label::new((filename, range))
+                            .with_message("add this field")
+                            .with_color(Color::Yellow),
+                    )
+            }
         };
 
         let _ = report
@@ -464,6 +530,113 @@ pub enum ValidationWarningKind {
     Deprecated { reason: String },
     /// Field will be ignored.
     IgnoredField { field: String },
+    /// An `@example` in a doc comment is not valid for the type it documents.
+    InvalidExample { example: String },
+    /// Required field is missing, where that isn't an error yet.
+    MissingField { field: String },
+}
+
+impl ValidationWarningKind {
+    /// Stable kebab-case identifier for this kind of warning, e.g. `deprecated`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationWarningKind::Deprecated { .. } => "deprecated",
+            ValidationWarningKind::IgnoredField { .. } => "ignored-field",
+            ValidationWarningKind::InvalidExample { .. } => "invalid-example",
+            ValidationWarningKind::MissingField { .. } => "missing-field",
+        }
+    }
+}
+
+/// A validation error or warning with its position as lines and columns,
+/// for machine-readable output.
+///
+/// The span stays the source of truth; `start` and `end` are rendered from
+/// it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Whether this is an error or a warning.
+    pub severity: Severity,
+    /// Path to the error location (e.g., "server.tls.cert").
+    pub path: String,
+    /// Source span in the document.
+    pub span: Option<Span>,
+    /// Position of the start of the span.
+    pub start: Option<Position>,
+    /// Position of the end of the span.
+    pub end: Option<Position>,
+    /// Stable kebab-case identifier of the error or warning kind.
+    pub code: &'static str,
+    /// Human-readable message.
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(
+        severity: Severity,
+        path: &str,
+        span: Option<Span>,
+        code: &'static str,
+        message: &str,
+        source: &str,
+    ) -> Self {
+        Self {
+            severity,
+            path: path.to_string(),
+            span,
+            start: span.map(|s| Position::of(source, s.start as usize)),
+            end: span.map(|s| Position::of(source, s.end as usize)),
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The document is invalid.
+    Error,
+    /// The document is valid, but something may be wrong with it.
+    Warning,
+}
+
+impl Severity {
+    /// Lowercase name, e.g. `error`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+/// A position in a source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column number in characters, starting at 1.
+    pub column: usize,
+}
+
+impl Position {
+    /// Position of the byte `offset` in `source`.
+    ///
+    /// Offsets past the end of `source` are clamped to its end, and offsets
+    /// inside a multi-byte character to the start of that character.
+    pub fn of(source: &str, offset: usize) -> Self {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Position {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -503,6 +676,46 @@ mod tests {
         assert_snapshot_stripped!(error.render("test.styx", source));
     }
 
+    #[test]
+    fn test_to_diagnostic_computes_line_and_column() {
+        let source = "name Alice\nage notanumber\ncity Zürich";
+        let error = ValidationError::new(
+            "age",
+            ValidationErrorKind::TypeMismatch {
+                expected: "int".into(),
+                got: "string".into(),
+            },
+            "expected int, got string",
+        )
+        .with_span(Some(Span { start: 15, end: 25 }));
+
+        let diagnostic = error.to_diagnostic(source);
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.code, "type-mismatch");
+        assert_eq!(diagnostic.start, Some(Position { line: 2, column: 5 }));
+        assert_eq!(
+            diagnostic.end,
+            Some(Position {
+                line: 2,
+                column: 15
+            })
+        );
+
+        // Columns count characters, not bytes
+        assert_eq!(
+            Position::of(source, source.len()),
+            Position {
+                line: 3,
+                column: 12
+            }
+        );
+        // A span ending right after a newline is at the start of the next line
+        assert_eq!(Position::of(source, 11), Position { line: 2, column: 1 });
+
+        let without_span = ValidationError::new("", ValidationErrorKind::ExpectedObject, "x");
+        assert_eq!(without_span.to_diagnostic(source).start, None);
+    }
+
     #[test]
     fn test_type_mismatch_diagnostic() {
         let source = "age notanumber";
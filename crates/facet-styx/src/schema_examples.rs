@@ -0,0 +1,222 @@
+//! Checking `@example` values in schema doc comments.
+
+use styx_parse::Span;
+use styx_tree::{BuildError, Payload, Value};
+
+use crate::schema_doc::DocComment;
+use crate::schema_error::{ValidationResult, ValidationWarning, ValidationWarningKind};
+use crate::schema_types::{Schema, SchemaFile};
+use crate::schema_validate::Validator;
+
+/// Check that every `@example` in the doc comments of a schema is valid for
+/// the type it documents.
+///
+/// `source` is the text `schema` was parsed from; it is needed to find the
+/// doc comments and their spans. Each bad example gives a warning pointing
+/// at the example, so stale examples are caught without failing the schema.
+///
+/// # Example
+///
+/// ```
+/// use facet_styx::{SchemaFile, validate_examples};
+///
+/// let source = "meta {id test}\nschema {@ @object{\n    /// @example eighty\n    port @int\n}}";
+/// let schema: SchemaFile = facet_styx::from_str(source).unwrap();
+/// let result = validate_examples(&schema, source);
+/// assert_eq!(result.warnings.len(), 1);
+/// ```
+pub fn validate_examples(schema: &SchemaFile, source: &str) -> ValidationResult {
+    let mut result = ValidationResult::ok();
+    let Ok(tree) = styx_tree::parse(source) else {
+        return result;
+    };
+    if let Some(definitions) = tree.get("schema") {
+        let mut checker = ExampleChecker {
+            validator: Validator::new(schema),
+            source,
+            result: &mut result,
+        };
+        checker.visit(definitions, "");
+    }
+    result
+}
+
+struct ExampleChecker<'a> {
+    validator: Validator<'a>,
+    source: &'a str,
+    result: &'a mut ValidationResult,
+}
+
+impl ExampleChecker<'_> {
+    fn visit(&mut self, value: &Value, path: &str) {
+        match &value.payload {
+            Some(Payload::Object(obj)) => {
+                for entry in &obj.entries {
+                    let key = entry.key.as_str().unwrap_or("@");
+                    let path = if path.is_empty() {
+                        key.to_string()
+                    } else {
+                        format!("{path}.{key}")
+                    };
+                    if let Some(doc) = &entry.doc_comment {
+                        let lines: Vec<&str> = doc.lines().collect();
+                        let doc = DocComment::parse(&lines);
+                        self.check_entry(&doc, &entry.value, entry.key.span, &path);
+                    }
+                    self.visit(&entry.value, &path);
+                }
+            }
+            Some(Payload::Sequence(seq)) => {
+                for item in &seq.items {
+                    self.visit(item, path);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Check the examples documenting the entry whose value is `type_value`.
+    fn check_entry(
+        &mut self,
+        doc: &DocComment,
+        type_value: &Value,
+        key_span: Option<Span>,
+        path: &str,
+    ) {
+        let examples: Vec<&str> = doc.examples().collect();
+        if examples.is_empty() {
+            return;
+        }
+        let Ok(schema) = crate::from_value::<Schema>(type_value) else {
+            return;
+        };
+
+        let spans = key_span.map(|span| example_spans(self.source, span.start, &examples));
+        for (i, example) in examples.iter().enumerate() {
+            let span = spans.as_ref().and_then(|spans| spans[i]);
+            let problem = match parse_example(example) {
+                Ok(value) => {
+                    let result = self.validator.validate_value(&value, &schema, path);
+                    match result.errors.first() {
+                        Some(error) => {
+                            format!("example does not match its type: {}", error.message)
+                        }
+                        None => continue,
+                    }
+                }
+                Err(reason) => format!("example is not a valid value: {reason}"),
+            };
+            self.result.warning(
+                ValidationWarning::new(
+                    path,
+                    ValidationWarningKind::InvalidExample {
+                        example: example.to_string(),
+                    },
+                    problem,
+                )
+                .with_span(span),
+            );
+        }
+    }
+}
+
+/// Parse an example the way it would be written as the value of an entry.
+fn parse_example(example: &str) -> Result<Value, String> {
+    // Spans would be off by the `example ` prefix, so report only the kind
+    let doc = styx_tree::parse(&format!("example {example}")).map_err(|e| match e {
+        BuildError::Parse(kind, _) => kind.to_string(),
+        e => e.to_string(),
+    })?;
+    let mut entries = doc
+        .as_object()
+        .map(|obj| obj.entries.iter())
+        .into_iter()
+        .flatten();
+    match (entries.next(), entries.next()) {
+        (Some(entry), None) => Ok(entry.value.clone()),
+        _ => Err("expected a single value".into()),
+    }
+}
+
+/// Spans of the first line of each example, searching the doc comment that
+/// ends before `key_start`.
+fn example_spans(source: &str, key_start: u32, examples: &[&str]) -> Vec<Option<Span>> {
+    let mut spans = vec![None; examples.len()];
+    let mut end = (key_start as usize).min(source.len());
+    for (i, example) in examples.iter().enumerate().rev() {
+        let first_line = example.lines().next().unwrap_or_default();
+        if first_line.is_empty() {
+            continue;
+        }
+        if let Some(start) = source[..end].rfind(first_line) {
+            spans[i] = Some(Span::new(start as u32, (start + first_line.len()) as u32));
+            end = start;
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use facet_testhelpers::test;
+
+    const SCHEMA: &str = r#"meta {id test}
+schema {
+    @ @object{
+        /// Port to listen on.
+        /// @example 8080
+        port @int
+        /// @example {host localhost, tls true}
+        /// @example {host localhost, tls maybe}
+        server @Server
+    }
+    /// @example {host example.com}
+    Server @object{
+        host @string
+        tls @optional(@bool)
+    }
+}"#;
+
+    fn check(source: &str) -> ValidationResult {
+        let schema: SchemaFile = crate::from_str(source).unwrap();
+        validate_examples(&schema, source)
+    }
+
+    #[test]
+    fn test_valid_examples_pass() {
+        let source = SCHEMA.replace("tls maybe", "tls false");
+        let result = check(&source);
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    }
+
+    #[test]
+    fn test_wrong_example_warns_at_example() {
+        let result = check(SCHEMA);
+        assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+
+        let warning = &result.warnings[0];
+        assert_eq!(warning.path, "@.server");
+        assert_eq!(
+            warning.kind,
+            ValidationWarningKind::InvalidExample {
+                example: "{host localhost, tls maybe}".into()
+            }
+        );
+        let span = warning.span.unwrap();
+        assert_eq!(
+            &SCHEMA[span.start as usize..span.end as usize],
+            "{host localhost, tls maybe}"
+        );
+    }
+
+    #[test]
+    fn test_unparseable_example_warns() {
+        let source = SCHEMA.replace("@example 8080", "@example {port");
+        let result = check(&source);
+        let messages: Vec<&str> = result.warnings.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(messages.len(), 2, "{messages:?}");
+        assert!(messages[0].starts_with("example is not a valid value"));
+        assert_eq!(result.warnings[0].path, "@.port");
+    }
+}
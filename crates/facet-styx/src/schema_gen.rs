@@ -8,7 +8,7 @@ use facet_core::{
     Shape, ShapeLayout, Type, UserType,
 };
 use facet_reflect::Peek;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::marker::PhantomData;
 use std::path::Path;
 use std::ptr::NonNull;
@@ -16,7 +16,7 @@ use std::ptr::NonNull;
 use crate::peek_to_string_expr;
 use crate::schema_types::{
     DefaultSchema, Documented, EnumSchema, LspExtensionConfig, MapSchema, Meta, ObjectKey,
-    ObjectSchema, OptionalSchema, RawStyx, Schema, SchemaFile, SeqSchema, TupleSchema,
+    ObjectSchema, OptionalSchema, RawStyx, Schema, SchemaFile, SeqSchema, TupleSchema, UnionSchema,
 };
 
 /// Strip exactly one leading space from a doc line if present.
@@ -107,6 +107,7 @@ pub struct GenerateSchema<T: facet_core::Facet<'static>> {
     version: Option<String>,
     cli: Option<String>,
     lsp: Option<LspExtensionConfig>,
+    source_map: Option<String>,
     _marker: PhantomData<T>,
 }
 
@@ -118,6 +119,7 @@ impl<T: facet_core::Facet<'static>> GenerateSchema<T> {
             version: None,
             cli: None,
             lsp: None,
+            source_map: None,
             _marker: PhantomData,
         }
     }
@@ -164,12 +166,28 @@ impl<T: facet_core::Facet<'static>> GenerateSchema<T> {
         self
     }
 
+    /// Also write a [`SchemaSourceMap`] as JSON to `$OUT_DIR/{filename}`
+    /// when [`write`](Self::write) is called.
+    ///
+    /// Editors can use it to jump from a config key to the Rust field that
+    /// defines it.
+    pub fn source_map(mut self, filename: impl Into<String>) -> Self {
+        self.source_map = Some(filename.into());
+        self
+    }
+
     /// Write the schema to `$OUT_DIR/{filename}`.
-    pub fn write(self, filename: &str) {
+    pub fn write(mut self, filename: &str) {
         let out_dir =
             std::env::var("OUT_DIR").expect("OUT_DIR not set - are you in a build script?");
         let path = Path::new(&out_dir).join(filename);
 
+        if let Some(map_filename) = self.source_map.take() {
+            let map = source_map_from_type::<T>();
+            std::fs::write(Path::new(&out_dir).join(map_filename), map.to_json())
+                .expect("failed to write schema source map");
+        }
+
         let schema = self.generate();
         std::fs::write(&path, schema).expect("failed to write schema");
     }
@@ -220,6 +238,154 @@ pub fn schema_file_from_type<T: facet_core::Facet<'static>>() -> SchemaFile {
     generate_schema_file_inner::<T>(id, None, None)
 }
 
+/// Map each definition, field and variant of the schema generated for a
+/// Facet type back to the Rust item it came from.
+///
+/// # Example
+///
+/// ```
+/// use facet::Facet;
+/// use facet_styx::source_map_from_type;
+///
+/// #[derive(Facet)]
+/// struct Config {
+///     #[facet(rename = "listen-port")]
+///     port: u16,
+/// }
+///
+/// let map = source_map_from_type::<Config>();
+/// let location = &map.entries["@.listen-port"];
+/// assert_eq!(location.type_name, "Config");
+/// assert_eq!(location.field.as_deref(), Some("port"));
+/// ```
+pub fn source_map_from_type<T: facet_core::Facet<'static>>() -> SchemaSourceMap {
+    let (_, shapes) = generate_definitions(T::SHAPE);
+    let mut map = SchemaSourceMap::default();
+    for (name, shape) in shapes {
+        let path = name.unwrap_or_else(|| "@".to_string());
+        map.add_type(&path, shape);
+    }
+    map
+}
+
+/// Where the parts of a generated schema came from in Rust.
+///
+/// Entries are keyed by schema path: the definition name (`@` for the root
+/// type), followed by `.variant` for an enum variant and `.field` for a
+/// field, using their names in the schema.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaSourceMap {
+    /// Source locations by schema path.
+    pub entries: BTreeMap<String, SourceLocation>,
+}
+
+/// The Rust item a part of a generated schema came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The Rust type, like `Config`.
+    pub type_name: String,
+    /// The enum variant, as written in Rust.
+    pub variant: Option<String>,
+    /// The field, as written in Rust.
+    pub field: Option<String>,
+}
+
+impl SchemaSourceMap {
+    /// Serialize the map as a JSON object of
+    /// `{"path": {"type": ..., "variant": ..., "field": ...}}`, leaving out
+    /// missing variants and fields.
+    pub fn to_json(&self) -> String {
+        let entries: serde_json::Map<String, serde_json::Value> = self
+            .entries
+            .iter()
+            .map(|(path, location)| {
+                let mut entry = serde_json::Map::new();
+                entry.insert("type".into(), location.type_name.clone().into());
+                if let Some(variant) = &location.variant {
+                    entry.insert("variant".into(), variant.clone().into());
+                }
+                if let Some(field) = &location.field {
+                    entry.insert("field".into(), field.clone().into());
+                }
+                (path.clone(), entry.into())
+            })
+            .collect();
+        serde_json::to_string_pretty(&entries).expect("source map is valid JSON")
+    }
+
+    fn insert(
+        &mut self,
+        path: String,
+        type_name: &str,
+        variant: Option<&str>,
+        field: Option<&str>,
+    ) {
+        self.entries.insert(
+            path,
+            SourceLocation {
+                type_name: type_name.to_string(),
+                variant: variant.map(String::from),
+                field: field.map(String::from),
+            },
+        );
+    }
+
+    /// Add the definition of `shape` at `path`, with its fields and variants.
+    fn add_type(&mut self, path: &str, shape: &'static Shape) {
+        let type_name = shape.type_identifier;
+        self.insert(path.to_string(), type_name, None, None);
+        match &shape.ty {
+            Type::User(UserType::Struct(struct_type)) => {
+                self.add_fields(path, type_name, None, struct_type);
+            }
+            // Enums with an `other` variant become @any, with no variants
+            Type::User(UserType::Enum(enum_type))
+                if !enum_type.variants.iter().any(|v| v.is_other()) =>
+            {
+                for variant in enum_type.variants {
+                    let variant_path = format!("{path}.{}", variant.effective_name());
+                    self.insert(variant_path.clone(), type_name, Some(variant.name), None);
+                    self.add_fields(&variant_path, type_name, Some(variant.name), &variant.data);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn add_fields(
+        &mut self,
+        path: &str,
+        type_name: &str,
+        variant: Option<&str>,
+        struct_type: &'static facet_core::StructType,
+    ) {
+        // Only named fields show up as keys in the schema
+        if !matches!(struct_type.kind, facet_core::StructKind::Struct) {
+            return;
+        }
+        for field in struct_type.fields {
+            // Flattened struct fields are inlined into this object
+            if field.is_flattened() {
+                let inner = field.shape();
+                if let Type::User(UserType::Struct(struct_type)) = &inner.ty {
+                    self.add_fields(path, inner.type_identifier, None, struct_type);
+                }
+                continue;
+            }
+            let name = field.effective_name();
+            if name.is_empty() {
+                continue;
+            }
+            self.insert(
+                format!("{path}.{name}"),
+                type_name,
+                variant,
+                Some(field.name),
+            );
+        }
+    }
+}
+
 /// Internal function that generates a SchemaFile with the given id and optional cli/lsp.
 fn generate_schema_file_inner<T: facet_core::Facet<'static>>(
     id: String,
@@ -227,7 +393,44 @@ fn generate_schema_file_inner<T: facet_core::Facet<'static>>(
     lsp: Option<LspExtensionConfig>,
 ) -> SchemaFile {
     let shape = T::SHAPE;
+    let (schema_map, _) = generate_definitions(shape);
+
+    let description = if shape.doc.is_empty() {
+        None
+    } else {
+        Some(
+            shape
+                .doc
+                .iter()
+                .map(|s| s.trim())
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+    };
+
+    SchemaFile {
+        meta: Meta {
+            id,
+            version: None,
+            cli,
+            description,
+            crate_name: None,
+            lsp,
+        },
+        imports: None,
+        schema: schema_map,
+    }
+}
+
+/// A schema definition's name (`None` for the root) and the shape it was
+/// generated from.
+type Definition = (Option<String>, &'static Shape);
 
+/// Generate the root definition for `shape` and the definitions of every
+/// type it refers to.
+fn generate_definitions(
+    shape: &'static Shape,
+) -> (HashMap<Option<String>, Schema>, Vec<Definition>) {
     let mut generator = SchemaGenerator::new();
 
     // Generate the root schema - inline it directly at @ (None key)
@@ -239,6 +442,7 @@ fn generate_schema_file_inner<T: facet_core::Facet<'static>>(
     // Build the schema map with root and all named type definitions
     let mut schema_map: HashMap<Option<String>, Schema> = HashMap::new();
     schema_map.insert(None, root_schema);
+    let mut shapes = vec![(None, shape)];
 
     // Process all pending types (types that were referenced but need definitions)
     while let Some(pending_shape) = generator.take_pending() {
@@ -252,34 +456,12 @@ fn generate_schema_file_inner<T: facet_core::Facet<'static>>(
         if !schema_map.contains_key(&Some(type_name.clone()))
             && let Some(type_schema) = generator.generate_type_definition(pending_shape)
         {
-            schema_map.insert(Some(type_name), type_schema);
+            schema_map.insert(Some(type_name.clone()), type_schema);
+            shapes.push((Some(type_name), pending_shape));
         }
     }
 
-    let description = if shape.doc.is_empty() {
-        None
-    } else {
-        Some(
-            shape
-                .doc
-                .iter()
-                .map(|s| s.trim())
-                .collect::<Vec<_>>()
-                .join(" "),
-        )
-    };
-
-    SchemaFile {
-        meta: Meta {
-            id,
-            version: None,
-            cli,
-            description,
-            lsp,
-        },
-        imports: None,
-        schema: schema_map,
-    }
+    (schema_map, shapes)
 }
 
 /// Convert a Schema to a tag name for use in ObjectKey.
@@ -355,6 +537,9 @@ impl SchemaGenerator {
                 self.generating.insert(type_id);
                 let result = match user {
                     UserType::Struct(struct_type) => Some(self.struct_to_schema(struct_type)),
+                    UserType::Enum(enum_type) if shape.is_untagged() => {
+                        Some(self.untagged_enum_to_schema(enum_type))
+                    }
                     UserType::Enum(enum_type) => Some(self.enum_to_schema(enum_type)),
                     _ => None,
                 };
@@ -494,6 +679,13 @@ impl SchemaGenerator {
             };
         }
 
+        // Transparent newtypes are written as their inner value
+        if let UserType::Struct(_) = user
+            && let Some(inner) = shape.inner
+        {
+            return self.shape_to_schema(inner);
+        }
+
         match user {
             // For structs and enums, always emit a type reference and queue for definition
             // This gives all complex types their own named definitions
@@ -631,8 +823,6 @@ impl SchemaGenerator {
     }
 
     fn enum_to_schema(&mut self, enum_type: &facet_core::EnumType) -> Schema {
-        use facet_core::StructKind;
-
         // If any variant has #[facet(other)], this enum accepts any tag,
         // so emit @any instead of trying to enumerate variants
         if enum_type.variants.iter().any(|v| v.is_other()) {
@@ -643,42 +833,11 @@ impl SchemaGenerator {
 
         for variant in enum_type.variants {
             let variant_name = variant.effective_name().to_string();
-            let variant_schema = match variant.data.kind {
-                StructKind::Unit => Schema::Unit,
-                StructKind::Tuple | StructKind::TupleStruct => {
-                    if variant.data.fields.len() == 1 {
-                        self.shape_to_schema(variant.data.fields[0].shape())
-                    } else {
-                        // Tuple variant with multiple fields
-                        let elements: Vec<Documented<Schema>> = variant
-                            .data
-                            .fields
-                            .iter()
-                            .map(|field| Documented::new(self.shape_to_schema(field.shape())))
-                            .collect();
-                        Schema::Tuple(TupleSchema(elements))
-                    }
-                }
-                StructKind::Struct => self.struct_to_schema(&variant.data),
-            };
-
-            // Extract doc comments from variant
-            let doc = if variant.doc.is_empty() {
-                None
-            } else {
-                Some(
-                    variant
-                        .doc
-                        .iter()
-                        .map(|s| strip_doc_leading_space(s))
-                        .collect(),
-                )
-            };
-
+            let variant_schema = self.variant_to_schema(variant);
             variants.insert(
                 Documented {
                     value: variant_name,
-                    doc,
+                    doc: variant_doc(variant),
                 },
                 variant_schema,
             );
@@ -686,6 +845,56 @@ impl SchemaGenerator {
 
         Schema::Enum(EnumSchema(variants))
     }
+
+    /// An untagged enum is written as the payload of one of its variants,
+    /// so it becomes a union of those. Union members are tried in order,
+    /// like the variants are when deserializing.
+    fn untagged_enum_to_schema(&mut self, enum_type: &facet_core::EnumType) -> Schema {
+        let members = enum_type
+            .variants
+            .iter()
+            .map(|variant| Documented::new(self.variant_to_schema(variant)))
+            .collect();
+        Schema::Union(UnionSchema(members))
+    }
+
+    fn variant_to_schema(&mut self, variant: &facet_core::Variant) -> Schema {
+        use facet_core::StructKind;
+
+        match variant.data.kind {
+            StructKind::Unit => Schema::Unit,
+            StructKind::Tuple | StructKind::TupleStruct => {
+                if variant.data.fields.len() == 1 {
+                    self.shape_to_schema(variant.data.fields[0].shape())
+                } else {
+                    // Tuple variant with multiple fields
+                    let elements: Vec<Documented<Schema>> = variant
+                        .data
+                        .fields
+                        .iter()
+                        .map(|field| Documented::new(self.shape_to_schema(field.shape())))
+                        .collect();
+                    Schema::Tuple(TupleSchema(elements))
+                }
+            }
+            StructKind::Struct => self.struct_to_schema(&variant.data),
+        }
+    }
+}
+
+/// Doc comments of an enum variant.
+fn variant_doc(variant: &facet_core::Variant) -> Option<Vec<String>> {
+    if variant.doc.is_empty() {
+        None
+    } else {
+        Some(
+            variant
+                .doc
+                .iter()
+                .map(|s| strip_doc_leading_space(s))
+                .collect(),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -725,6 +934,25 @@ mod tests {
         assert!(schema.contains("optional"));
     }
 
+    #[test]
+    fn test_transparent_newtype_uses_inner_schema() {
+        #[derive(Facet)]
+        #[facet(transparent)]
+        #[allow(dead_code)]
+        struct Port(u16);
+
+        #[derive(Facet)]
+        #[allow(dead_code)]
+        struct Config {
+            port: Port,
+        }
+
+        let schema = schema_from_type::<Config>();
+        tracing::debug!("Generated schema:\n{schema}");
+        assert!(schema.contains("port @int"));
+        assert!(!schema.contains("Port"));
+    }
+
     #[test]
     fn test_with_vec() {
         #[derive(Facet)]
@@ -1321,4 +1549,62 @@ mod tests {
             schema
         );
     }
+
+    #[test]
+    fn test_source_map_points_at_rust_fields() {
+        #[derive(Facet)]
+        #[allow(dead_code)]
+        struct Server {
+            #[facet(rename = "listen-port")]
+            port: u16,
+        }
+
+        #[derive(Facet)]
+        #[facet(rename_all = "lowercase")]
+        #[allow(dead_code)]
+        #[repr(u8)]
+        enum Mode {
+            Fast,
+            Custom { max_workers: u32 },
+        }
+
+        #[derive(Facet)]
+        #[allow(dead_code)]
+        struct Config {
+            server: Server,
+            mode: Mode,
+        }
+
+        let map = source_map_from_type::<Config>();
+        let location = |path: &str| {
+            let location = map
+                .entries
+                .get(path)
+                .unwrap_or_else(|| panic!("no entry for {path} in {:?}", map.entries.keys()));
+            (
+                location.type_name.as_str(),
+                location.variant.as_deref(),
+                location.field.as_deref(),
+            )
+        };
+
+        assert_eq!(location("@"), ("Config", None, None));
+        assert_eq!(location("@.server"), ("Config", None, Some("server")));
+        assert_eq!(location("Server"), ("Server", None, None));
+        assert_eq!(
+            location("Server.listen-port"),
+            ("Server", None, Some("port"))
+        );
+        assert_eq!(location("Mode.custom"), ("Mode", Some("Custom"), None));
+        assert_eq!(
+            location("Mode.custom.max_workers"),
+            ("Mode", Some("Custom"), Some("max_workers"))
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&map.to_json()).unwrap();
+        assert_eq!(
+            json["Server.listen-port"],
+            serde_json::json!({"type": "Server", "field": "port"})
+        );
+    }
 }
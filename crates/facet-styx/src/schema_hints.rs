@@ -0,0 +1,198 @@
+//! Formatting with hints for the fields a document leaves out.
+//!
+//! A minimal config only shows what it sets. [`format_with_field_hints`]
+//! formats it and adds the optional fields of the root object that are absent
+//! as comments, with their docs and declared types, so readers can see what
+//! else they could set.
+
+use std::collections::HashSet;
+
+use styx_format::{FormatOptions, format_source, format_value};
+use styx_tree::{Entry, Value};
+
+use crate::schema_error::{ValidationResult, ValidationWarning, ValidationWarningKind};
+use crate::schema_order::schema_definitions;
+use crate::schema_types::resolve_type_ref;
+
+/// Format `source`, adding a comment for each optional field of the root
+/// object that it doesn't set.
+///
+/// `schema` is a parsed schema document (with `meta` and `schema` blocks), as
+/// returned by [`styx_tree::parse`], which keeps the order fields are declared
+/// in. The hints are appended in that order, each as the field's doc comment
+/// followed by `// name <schema>`, and hints already in the document aren't
+/// added again. Missing required fields are reported as warnings.
+///
+/// Documents with an explicit `{...}` root are formatted without hints. Like
+/// [`format_source`], a document that doesn't parse is returned as it is.
+///
+/// # Example
+///
+/// ```
+/// use facet_styx::format_with_field_hints;
+///
+/// let schema = styx_tree::parse(
+///     "meta {id example}\nschema {@ @object{\n  name @string\n  /// Port to listen on.\n  port @default(8080 @int)\n}}",
+/// )
+/// .unwrap();
+///
+/// let (formatted, result) = format_with_field_hints("name app", &schema, Default::default());
+/// assert_eq!(
+///     formatted,
+///     "name app\n\n// Port to listen on.\n// port @default(8080 @int)\n"
+/// );
+/// assert!(result.warnings.is_empty());
+/// ```
+pub fn format_with_field_hints(
+    source: &str,
+    schema: &Value,
+    options: FormatOptions,
+) -> (String, ValidationResult) {
+    let mut result = ValidationResult::ok();
+    let Ok(doc) = styx_tree::parse(source) else {
+        return (source.to_string(), result);
+    };
+    let mut formatted = format_source(source, options);
+
+    let Some(fields) = root_fields(schema) else {
+        return (formatted, result);
+    };
+    let present: HashSet<&str> = doc
+        .as_object()
+        .into_iter()
+        .flat_map(|obj| &obj.entries)
+        .filter_map(|entry| entry.key.as_str())
+        .collect();
+
+    let mut hints = Vec::new();
+    for field in fields {
+        let Some(name) = field.key.as_str() else {
+            // Typed catch-alls like `@string @T` don't name a field
+            continue;
+        };
+        if present.contains(name) {
+            continue;
+        }
+        // Same rule as validation: only these wrappers make a field optional
+        if matches!(field.value.tag_name(), Some("optional" | "default")) {
+            hints.push(field_hint(name, field));
+        } else {
+            result.warning(
+                ValidationWarning::new(
+                    name,
+                    ValidationWarningKind::MissingField {
+                        field: name.to_string(),
+                    },
+                    format!("missing required field '{name}'"),
+                )
+                .with_span(doc.span),
+            );
+        }
+    }
+
+    if has_explicit_root(&formatted) {
+        return (formatted, result);
+    }
+    let existing: HashSet<&str> = formatted.lines().map(str::trim).collect();
+    let hints: Vec<String> = hints
+        .into_iter()
+        .filter(|hint| !existing.contains(hint.lines().last().unwrap_or_default()))
+        .collect();
+    for hint in hints {
+        if !formatted.is_empty() {
+            formatted.push('\n');
+        }
+        formatted.push_str(&hint);
+    }
+    (formatted, result)
+}
+
+/// The fields of the root object of a schema document, in declared order.
+fn root_fields(schema: &Value) -> Option<&[Entry]> {
+    let defs = schema_definitions(schema)?;
+    let root = resolve_type_ref(defs.get_unit()?, defs)?;
+    if root.tag_name() != Some("object") {
+        return None;
+    }
+    Some(&root.as_object()?.entries)
+}
+
+/// The commented-out lines for a missing field: its doc comment, then the
+/// field with its schema.
+fn field_hint(name: &str, field: &Entry) -> String {
+    let mut hint = String::new();
+    for line in field.doc_comment.iter().flat_map(|doc| doc.lines()) {
+        hint.push_str(format!("// {line}").trim_end());
+        hint.push('\n');
+    }
+    let schema = format_value(&field.value, FormatOptions::default().inline());
+    hint.push_str(&format!("// {name} {}\n", schema.trim_end()));
+    hint
+}
+
+/// Whether formatted output holds an explicit `{...}` root object, which
+/// comments can't be appended to.
+fn has_explicit_root(formatted: &str) -> bool {
+    formatted
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with("//"))
+        .is_some_and(|line| line.starts_with('{'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use facet_testhelpers::test;
+
+    const SCHEMA: &str = r#"meta {id test}
+schema {
+    @ @object{
+        /// Name of the service.
+        name @string
+        /// Address to bind.
+        host @optional(@string)
+        port @default(8080 @int)
+        tls @optional(@Tls)
+        version @int
+    }
+    Tls @object{cert @string}
+}"#;
+
+    #[test]
+    fn test_adds_missing_optional_fields_in_schema_order() {
+        let schema = styx_tree::parse(SCHEMA).unwrap();
+        let (formatted, result) =
+            format_with_field_hints("name   app\nversion 2", &schema, FormatOptions::default());
+
+        assert_eq!(
+            formatted,
+            "name app\nversion 2\n\n// Address to bind.\n// host @optional(@string)\n\n// port @default(8080 @int)\n\n// tls @optional(@Tls)\n"
+        );
+        assert!(result.is_valid());
+        assert!(result.warnings.is_empty());
+
+        // The hints are only added once
+        let (again, _) = format_with_field_hints(&formatted, &schema, FormatOptions::default());
+        assert_eq!(again, formatted);
+    }
+
+    #[test]
+    fn test_missing_required_fields_are_warnings() {
+        let schema = styx_tree::parse(SCHEMA).unwrap();
+        let (formatted, result) =
+            format_with_field_hints("host localhost", &schema, FormatOptions::default());
+
+        assert!(formatted.starts_with("host localhost\n\n// port @default"));
+        assert!(result.is_valid());
+        let warnings: Vec<_> = result
+            .warnings
+            .iter()
+            .map(|w| (w.path.as_str(), w.kind.code()))
+            .collect();
+        assert_eq!(
+            warnings,
+            [("name", "missing-field"), ("version", "missing-field")]
+        );
+    }
+}
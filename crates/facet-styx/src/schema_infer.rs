@@ -0,0 +1,399 @@
+//! Schema inference from example documents.
+//!
+//! The reverse of [`GenerateSchema`](crate::GenerateSchema): instead of a Rust
+//! type, the starting point is an existing config. Scalar types come from the
+//! form of each value, nested objects become inline `@object{...}` schemas and
+//! sequence element types are unified across all items.
+//!
+//! Several samples can be combined into one schema covering all of them. Object
+//! fields missing from some samples (or from some items of a sequence) are
+//! inferred `@optional`.
+
+use std::collections::HashMap;
+
+use styx_format::{FormatOptions, format_value};
+use styx_tree::{Payload, ScalarKind, Value};
+
+use crate::schema_types::{
+    Documented, Meta, ObjectKey, ObjectSchema, OptionalSchema, Schema, SchemaFile, SeqSchema,
+};
+
+/// Builder for inferring a schema from example documents.
+///
+/// # Example
+///
+/// ```
+/// use facet_styx::InferSchema;
+///
+/// let config = styx_tree::parse("name myapp\nport 8080\ntags (web api)").unwrap();
+/// let schema = InferSchema::new(&config).id("myapp").generate();
+/// assert!(schema.contains("port @int"));
+/// assert!(schema.contains("tags @seq(@string)"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct InferSchema {
+    id: String,
+    optional_fields: bool,
+    root: Shape,
+}
+
+impl InferSchema {
+    /// Infer the shape of `sample`, a parsed document.
+    ///
+    /// Entries with tagged keys, like a root `@schema` declaration, are skipped.
+    pub fn new(sample: &Value) -> Self {
+        Self {
+            id: "inferred".into(),
+            optional_fields: false,
+            root: Shape::of(sample),
+        }
+    }
+
+    /// Widen the inferred schema to also cover another sample.
+    ///
+    /// Fields that are missing from any sample become optional, and the types
+    /// of values present in several samples are unified.
+    pub fn sample(mut self, sample: &Value) -> Self {
+        let root = std::mem::replace(&mut self.root, Shape::Empty);
+        self.root = root.unify(Shape::of(sample));
+        self
+    }
+
+    /// Set the schema ID written to `meta` (default: `inferred`).
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    /// Mark every object field `@optional` instead of required.
+    pub fn optional_fields(mut self, optional: bool) -> Self {
+        self.optional_fields = optional;
+        self
+    }
+
+    /// Generate the schema as Styx source, with fields in document order.
+    pub fn generate(&self) -> String {
+        let mut meta = Value::object();
+        meta.as_object_mut()
+            .unwrap()
+            .insert("id", Value::scalar(self.id.clone()));
+
+        let mut schema = Value::object();
+        schema
+            .as_object_mut()
+            .unwrap()
+            .insert_unit(self.root.to_value(self.optional_fields));
+
+        let mut file = Value::object();
+        let obj = file.as_object_mut().unwrap();
+        obj.insert("meta", meta);
+        obj.insert("schema", schema);
+        format_value(&file, FormatOptions::default())
+    }
+
+    /// Generate the schema as a [`SchemaFile`].
+    pub fn generate_schema_file(&self) -> SchemaFile {
+        SchemaFile {
+            meta: Meta {
+                id: self.id.clone(),
+                version: None,
+                cli: None,
+                description: None,
+                crate_name: None,
+                lsp: None,
+            },
+            imports: None,
+            schema: HashMap::from([(None, self.root.to_schema(self.optional_fields))]),
+        }
+    }
+}
+
+/// Inferred type of a value, before it is turned into a [`Schema`].
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    /// No value seen yet, e.g. the items of an empty sequence.
+    Empty,
+    Unit,
+    Bool,
+    Int,
+    Float,
+    String,
+    Any,
+    Seq(Box<Shape>),
+    Object(Vec<Field>),
+}
+
+/// An object field, in document order.
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    name: String,
+    shape: Shape,
+    required: bool,
+}
+
+impl Shape {
+    fn of(value: &Value) -> Self {
+        if value.tag.is_some() {
+            // Tagged values are enum variants or custom types we can't name
+            return Shape::Any;
+        }
+        match &value.payload {
+            None => Shape::Unit,
+            Some(Payload::Scalar(scalar)) if scalar.kind == ScalarKind::Bare => {
+                Shape::of_bare_scalar(&scalar.text)
+            }
+            Some(Payload::Scalar(_)) => Shape::String,
+            Some(Payload::Sequence(seq)) => Shape::Seq(Box::new(
+                seq.items
+                    .iter()
+                    .map(Shape::of)
+                    .fold(Shape::Empty, Shape::unify),
+            )),
+            Some(Payload::Object(obj)) => Shape::Object(
+                obj.entries
+                    .iter()
+                    .filter_map(|entry| {
+                        Some(Field {
+                            name: entry.key.as_str()?.to_string(),
+                            shape: Shape::of(&entry.value),
+                            required: true,
+                        })
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    fn of_bare_scalar(text: &str) -> Self {
+        if text == "true" || text == "false" {
+            Shape::Bool
+        } else if text.parse::<i64>().is_ok() {
+            Shape::Int
+        } else if text.parse::<f64>().is_ok() && text.bytes().any(|b| b.is_ascii_digit()) {
+            // The digit check keeps `inf` and `NaN` strings
+            Shape::Float
+        } else {
+            Shape::String
+        }
+    }
+
+    fn is_scalar(&self) -> bool {
+        matches!(
+            self,
+            Shape::Bool | Shape::Int | Shape::Float | Shape::String
+        )
+    }
+
+    /// The narrowest shape accepting values of both shapes.
+    fn unify(self, other: Shape) -> Shape {
+        match (self, other) {
+            (Shape::Empty, shape) | (shape, Shape::Empty) => shape,
+            (a, b) if a == b => a,
+            (Shape::Int, Shape::Float) | (Shape::Float, Shape::Int) => Shape::Float,
+            (Shape::Seq(a), Shape::Seq(b)) => Shape::Seq(Box::new(a.unify(*b))),
+            (Shape::Object(a), Shape::Object(b)) => Shape::Object(unify_fields(a, b)),
+            // Every scalar is a valid string
+            (a, b) if a.is_scalar() && b.is_scalar() => Shape::String,
+            _ => Shape::Any,
+        }
+    }
+
+    fn to_schema(&self, optional_fields: bool) -> Schema {
+        match self {
+            Shape::Empty | Shape::Any => Schema::Any,
+            Shape::Unit => Schema::Unit,
+            Shape::Bool => Schema::Bool,
+            Shape::Int => Schema::Int(None),
+            Shape::Float => Schema::Float(None),
+            Shape::String => Schema::String(None),
+            Shape::Seq(items) => Schema::Seq(SeqSchema((Documented::new(Box::new(
+                items.to_schema(optional_fields),
+            )),))),
+            Shape::Object(fields) => Schema::Object(ObjectSchema(
+                fields
+                    .iter()
+                    .map(|field| {
+                        let mut schema = field.shape.to_schema(optional_fields);
+                        if optional_fields || !field.required {
+                            schema = Schema::Optional(OptionalSchema((Documented::new(Box::new(
+                                schema,
+                            )),)));
+                        }
+                        (Documented::new(ObjectKey::named(&field.name)), schema)
+                    })
+                    .collect(),
+            )),
+        }
+    }
+
+    fn to_value(&self, optional_fields: bool) -> Value {
+        match self {
+            Shape::Empty | Shape::Any => Value::tag("any"),
+            Shape::Unit => Value::tag("unit"),
+            Shape::Bool => Value::tag("bool"),
+            Shape::Int => Value::tag("int"),
+            Shape::Float => Value::tag("float"),
+            Shape::String => Value::tag("string"),
+            Shape::Seq(items) => {
+                Value::tagged("seq", Value::seq(vec![items.to_value(optional_fields)]))
+            }
+            Shape::Object(fields) => {
+                let mut obj = Value::object();
+                for field in fields {
+                    let mut value = field.shape.to_value(optional_fields);
+                    if optional_fields || !field.required {
+                        value = Value::tagged("optional", Value::seq(vec![value]));
+                    }
+                    obj.as_object_mut()
+                        .unwrap()
+                        .insert(field.name.clone(), value);
+                }
+                Value::tagged("object", obj)
+            }
+        }
+    }
+}
+
+/// Merge the fields of two objects. Fields missing from either side become
+/// optional.
+fn unify_fields(a: Vec<Field>, mut b: Vec<Field>) -> Vec<Field> {
+    let mut fields = Vec::with_capacity(a.len().max(b.len()));
+    for field in a {
+        match b.iter().position(|other| other.name == field.name) {
+            Some(index) => {
+                let other = b.remove(index);
+                fields.push(Field {
+                    name: field.name,
+                    shape: field.shape.unify(other.shape),
+                    required: field.required && other.required,
+                });
+            }
+            None => fields.push(Field {
+                required: false,
+                ..field
+            }),
+        }
+    }
+    fields.extend(b.into_iter().map(|field| Field {
+        required: false,
+        ..field
+    }));
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate;
+    use facet_testhelpers::test;
+
+    const CONFIG: &str = r#"name myapp
+port 8080
+ratio 0.75
+debug false
+motd "8080"
+tags (web api)
+ports (80 443 8443.5)
+server {
+    host localhost
+    tls {cert /etc/cert.pem}
+}
+workers ({name a, threads 2} {name b})
+"#;
+
+    fn root_fields(schema_file: &SchemaFile) -> &ObjectSchema {
+        match &schema_file.schema[&None] {
+            Schema::Object(obj) => obj,
+            other => panic!("expected an object root, got {other:?}"),
+        }
+    }
+
+    fn field<'a>(obj: &'a ObjectSchema, name: &str) -> &'a Schema {
+        &obj.0[&Documented::new(ObjectKey::named(name))]
+    }
+
+    #[test]
+    fn test_infer_field_types() {
+        let config = styx_tree::parse(CONFIG).unwrap();
+        let schema_file = InferSchema::new(&config).id("myapp").generate_schema_file();
+        assert_eq!(schema_file.meta.id, "myapp");
+
+        let root = root_fields(&schema_file);
+        assert!(matches!(field(root, "name"), Schema::String(None)));
+        assert!(matches!(field(root, "port"), Schema::Int(None)));
+        assert!(matches!(field(root, "ratio"), Schema::Float(None)));
+        assert!(matches!(field(root, "debug"), Schema::Bool));
+        // Quoted scalars are always strings
+        assert!(matches!(field(root, "motd"), Schema::String(None)));
+
+        let Schema::Seq(ports) = field(root, "ports") else {
+            panic!("ports should be a sequence");
+        };
+        assert!(matches!(*ports.0.0.value, Schema::Float(None)));
+
+        let Schema::Object(server) = field(root, "server") else {
+            panic!("server should be an object");
+        };
+        assert!(matches!(field(server, "tls"), Schema::Object(_)));
+
+        // `threads` is missing from one worker, so it's optional
+        let Schema::Seq(workers) = field(root, "workers") else {
+            panic!("workers should be a sequence");
+        };
+        let Schema::Object(worker) = &*workers.0.0.value else {
+            panic!("workers should hold objects");
+        };
+        assert!(matches!(field(worker, "name"), Schema::String(None)));
+        assert!(matches!(field(worker, "threads"), Schema::Optional(_)));
+
+        // The sample itself validates against its inferred schema
+        assert!(validate(&config, &schema_file).is_valid());
+    }
+
+    #[test]
+    fn test_infer_from_several_samples() {
+        let samples = [
+            "name a\nport 80\ntags (web)",
+            "name b\nport 8080.5\ntags (1 2)\ndebug true",
+            "name c\nport 443\ntags ()",
+        ]
+        .map(|source| styx_tree::parse(source).unwrap());
+
+        let schema_file = samples[1..]
+            .iter()
+            .fold(InferSchema::new(&samples[0]), InferSchema::sample)
+            .generate_schema_file();
+        let root = root_fields(&schema_file);
+
+        // In every sample: required
+        assert!(matches!(field(root, "name"), Schema::String(None)));
+        assert!(matches!(field(root, "port"), Schema::Float(None)));
+        // Only in one sample: optional
+        let Schema::Optional(debug) = field(root, "debug") else {
+            panic!("debug should be optional");
+        };
+        assert!(matches!(*debug.0.0.value, Schema::Bool));
+        // Element types unified across samples
+        let Schema::Seq(tags) = field(root, "tags") else {
+            panic!("tags should be a sequence");
+        };
+        assert!(matches!(*tags.0.0.value, Schema::String(None)));
+
+        for sample in &samples {
+            assert!(validate(sample, &schema_file).is_valid());
+        }
+    }
+
+    #[test]
+    fn test_generate_keeps_document_order() {
+        let config = styx_tree::parse("@schema app.styx\nport 8080\nname myapp").unwrap();
+        let schema = InferSchema::new(&config)
+            .id("app")
+            .optional_fields(true)
+            .generate();
+        assert_eq!(
+            schema,
+            "meta {id app}\n\nschema {\n    @ @object{\n        port @optional(@int)\n        name @optional(@string)\n    }\n}\n"
+        );
+    }
+}
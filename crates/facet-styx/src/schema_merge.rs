@@ -0,0 +1,199 @@
+//! Merging the schemas of a document that declares several.
+//!
+//! `@schema (base.styx extension.styx)` validates a document against both
+//! schemas at once, with later schemas taking precedence.
+
+use std::fmt;
+
+use crate::schema_compat::{child_path, compare_definitions, key_label, type_path};
+use crate::schema_types::{ObjectSchema, Schema, SchemaFile};
+
+/// A later schema redefines something incompatibly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaMergeError {
+    /// Dotted path to the conflicting definition, e.g. `Server.port`.
+    /// Root fields have no prefix; the root type itself is `@`.
+    pub path: String,
+    /// Why the later definition cannot replace the earlier one.
+    pub message: String,
+}
+
+impl fmt::Display for SchemaMergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "conflicting definitions of `{}`: {}",
+            self.path, self.message
+        )
+    }
+}
+
+impl std::error::Error for SchemaMergeError {}
+
+/// Merge `overlay` on top of `base`.
+///
+/// - types defined by only one schema are kept as they are
+/// - object types defined by both, including the root, are merged field by field
+/// - a field or other type defined by both is replaced by the overlay's
+///   definition, as long as that accepts every document the base one did
+/// - `meta` comes from the overlay and `imports` are combined
+///
+/// An overlay definition that would reject documents the base one accepts is a
+/// conflict. Merge more than two schemas by folding them in declaration order.
+///
+/// # Example
+///
+/// ```
+/// use facet_styx::{SchemaFile, from_str, merge_schemas};
+///
+/// let base: SchemaFile =
+///     from_str("meta {id base}\nschema {@ @object{host @string}}").unwrap();
+/// let overlay: SchemaFile =
+///     from_str("meta {id plugin}\nschema {@ @object{plugins @seq(@string)}}").unwrap();
+///
+/// let merged = merge_schemas(base, overlay).unwrap();
+/// assert_eq!(merged.meta.id, "plugin");
+/// assert!(facet_styx::validate(
+///     &styx_tree::parse("host localhost\nplugins (auth)").unwrap(),
+///     &merged,
+/// )
+/// .is_valid());
+/// ```
+pub fn merge_schemas(
+    base: SchemaFile,
+    overlay: SchemaFile,
+) -> Result<SchemaFile, SchemaMergeError> {
+    let mut merged = base;
+    merged.meta = overlay.meta;
+    if let Some(imports) = overlay.imports {
+        merged.imports.get_or_insert_default().extend(imports);
+    }
+
+    for (name, schema) in overlay.schema {
+        let schema = match merged.schema.remove(&name) {
+            Some(existing) => {
+                let field_prefix = name.as_deref().unwrap_or("");
+                merge_definition(&type_path(&name), field_prefix, existing, schema)?
+            }
+            None => schema,
+        };
+        merged.schema.insert(name, schema);
+    }
+
+    Ok(merged)
+}
+
+/// Merge two definitions of the same top-level type.
+fn merge_definition(
+    path: &str,
+    field_prefix: &str,
+    base: Schema,
+    overlay: Schema,
+) -> Result<Schema, SchemaMergeError> {
+    match (base, overlay) {
+        (Schema::Object(base), Schema::Object(overlay)) => {
+            merge_objects(field_prefix, base, overlay).map(Schema::Object)
+        }
+        (base, overlay) => {
+            check_replaces(path, &base, &overlay)?;
+            Ok(overlay)
+        }
+    }
+}
+
+fn merge_objects(
+    path: &str,
+    mut base: ObjectSchema,
+    overlay: ObjectSchema,
+) -> Result<ObjectSchema, SchemaMergeError> {
+    for (key, schema) in overlay.0 {
+        // Keys compare without their docs, so remove first to keep the overlay's
+        if let Some((_, existing)) = base.0.remove_entry(&key) {
+            let field_path = child_path(path, &key_label(&key.value));
+            check_replaces(&field_path, &existing, &schema)?;
+        }
+        base.0.insert(key, schema);
+    }
+    Ok(base)
+}
+
+/// Fail if `overlay` rejects documents that `base` accepts.
+fn check_replaces(path: &str, base: &Schema, overlay: &Schema) -> Result<(), SchemaMergeError> {
+    let compat = compare_definitions(path, base, overlay);
+    match compat.breaking.into_iter().next() {
+        Some(change) => Err(SchemaMergeError {
+            path: path.to_string(),
+            message: change.message,
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_str, validate};
+    use facet_testhelpers::test;
+
+    fn schema(id: &str, definitions: &str) -> SchemaFile {
+        from_str(&format!("meta {{id {id}}}\nschema {{\n{definitions}\n}}"))
+            .expect("schema should parse")
+    }
+
+    fn is_valid(source: &str, schema: &SchemaFile) -> bool {
+        validate(&styx_tree::parse(source).unwrap(), schema).is_valid()
+    }
+
+    #[test]
+    fn test_complementary_schemas_merge() {
+        let base = schema(
+            "base",
+            "@ @object{name @string, port @int{min 1024}, server @Server}\n\
+             Server @object{host @string}",
+        );
+        let extension = schema(
+            "extension",
+            "@ @object{port @int, plugins @seq(@Plugin)}\n\
+             Plugin @object{name @string, enabled @optional(@bool)}",
+        );
+
+        let merged = merge_schemas(base, extension).expect("schemas should merge");
+        assert_eq!(merged.meta.id, "extension");
+        assert!(merged.schema.contains_key(&Some("Server".into())));
+        assert!(merged.schema.contains_key(&Some("Plugin".into())));
+
+        // Fields from both roots are required; the extension's `port` wins
+        assert!(is_valid(
+            "name app\nport 80\nserver {host localhost}\nplugins ({name auth})",
+            &merged
+        ));
+        assert!(!is_valid(
+            "name app\nport 80\nserver {host localhost}",
+            &merged
+        ));
+        assert!(!is_valid("port 80\nplugins ()", &merged));
+    }
+
+    #[test]
+    fn test_conflicting_schemas_fail() {
+        let base = schema("base", "@ @object{port @int}\nServer @object{host @string}");
+
+        let err = merge_schemas(base.clone(), schema("extension", "@ @object{port @string}"))
+            .unwrap_err();
+        assert_eq!(err.path, "port");
+
+        let err = merge_schemas(
+            base.clone(),
+            schema("extension", "@ @object{port @int{max 10}}"),
+        )
+        .unwrap_err();
+        assert_eq!(err.path, "port");
+
+        let err = merge_schemas(base, schema("extension", "Server @string")).unwrap_err();
+        assert_eq!(err.path, "Server");
+        assert!(
+            err.to_string()
+                .starts_with("conflicting definitions of `Server`")
+        );
+    }
+}
@@ -1,8 +1,427 @@
-//! Bundled meta-schema.
+//! Bundled meta-schema, checking schema files, and reading their `meta`
+//! block.
 //!
 //! The meta-schema is the schema that describes STYX schema files themselves.
-//! It's bundled as a static string and deserialized at runtime to validate
-//! that facet-styx can handle our own schema format.
+//! It's bundled as a static string, and [`META_SCHEMA`] deserializes it once on
+//! first use so tools don't each have to parse it.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::LazyLock;
+
+use facet_format::DeserializeError;
+use styx_tree::{BuildError, Object, Payload, Span, Value};
+
+use crate::partial::{entry_path_at, from_value_collect};
+use crate::schema_compat::child_path;
+use crate::schema_error::{ValidationError, ValidationErrorKind, ValidationResult};
+use crate::schema_types::{Meta, Schema, SchemaFile};
+use crate::schema_validate::Validator;
 
 /// The STYX meta-schema source.
 pub const META_SCHEMA_SOURCE: &str = include_str!("../schema/meta.styx");
+
+/// The STYX meta-schema, parsed from [`META_SCHEMA_SOURCE`] on first use.
+///
+/// # Example
+///
+/// ```
+/// use facet_styx::META_SCHEMA;
+///
+/// assert_eq!(META_SCHEMA.meta.id, "https://styx.bearcove.eu/schemas/schema");
+/// assert!(META_SCHEMA.schema.contains_key(&Some("Meta".into())));
+/// ```
+pub static META_SCHEMA: LazyLock<SchemaFile> = LazyLock::new(|| {
+    crate::from_str(META_SCHEMA_SOURCE).expect("bundled meta-schema should deserialize")
+});
+
+/// Check that a parsed schema file is well-formed, reporting every problem
+/// with its span.
+///
+/// The root fields and `meta` are validated against [`META_SCHEMA`]. Its
+/// `Schema` type can't describe type references such as `@string` or
+/// `@MyType`, so each type definition is instead held to exactly what
+/// deserializing it as a [`Schema`] accepts, and every type it references
+/// must be defined in the file or come from an imported namespace.
+///
+/// # Example
+///
+/// ```
+/// let schema =
+///     styx_tree::parse("meta {id app}\nschema {@ @object{port @int, host @strng}}").unwrap();
+/// let result = facet_styx::validate_schema_file(&schema);
+/// assert_eq!(result.errors.len(), 1);
+/// assert_eq!(result.errors[0].path, "@.host");
+/// ```
+pub fn validate_schema_file(value: &Value) -> ValidationResult {
+    let validator = Validator::new(&META_SCHEMA);
+    let Some(root) = value.as_object() else {
+        return validator.validate_document(value);
+    };
+    let root_schema = validator
+        .root_object_schema()
+        .expect("meta-schema root should be an object");
+
+    let mut result = ValidationResult::ok();
+    let mut seen = HashSet::new();
+    for entry in &root.entries {
+        if entry.key.as_str() == Some("schema") {
+            seen.insert("schema");
+            check_definitions(&entry.value, root, &mut result);
+        } else if let Some(Some(key)) =
+            validator.validate_object_entry(entry, root_schema, "", &mut result)
+        {
+            seen.insert(key);
+        }
+    }
+    validator.check_missing_fields(
+        root_schema,
+        "",
+        value.span,
+        |name| seen.contains(name),
+        &mut result,
+    );
+    result
+}
+
+/// Check the type definitions in the `schema` block of a schema file.
+fn check_definitions(value: &Value, root: &Object, result: &mut ValidationResult) {
+    let Some(definitions) = value.as_object() else {
+        result.error(
+            ValidationError::new(
+                "schema",
+                ValidationErrorKind::ExpectedObject,
+                "expected an object of type definitions",
+            )
+            .with_span(value.span),
+        );
+        return;
+    };
+    let defined: Vec<&str> = definitions
+        .entries
+        .iter()
+        .filter_map(|e| e.key.as_str())
+        .collect();
+    let imports: Vec<&str> = root
+        .get("imports")
+        .and_then(Value::as_object)
+        .map(|imports| {
+            imports
+                .entries
+                .iter()
+                .filter_map(|e| e.key.as_str())
+                .collect()
+        })
+        .unwrap_or_default();
+    let is_known = |name: &str| {
+        let imported = name
+            .split_once('.')
+            .is_some_and(|(namespace, _)| imports.contains(&namespace));
+        defined.contains(&name) || imported
+    };
+
+    for entry in &definitions.entries {
+        let path = entry.key.as_str().unwrap_or("@");
+        let (_, errors) = from_value_collect::<Schema>(entry.value.clone());
+        for error in errors {
+            let span = error.span.as_ref().map(|span| {
+                let start = span.offset as u32;
+                Span::new(start, start + span.len as u32)
+            });
+            let path = match span {
+                Some(span) => entry_path_at(&entry.value, span.start, path),
+                None => path.to_string(),
+            };
+            let reason = error.to_string();
+            result.error(
+                ValidationError::new(
+                    path,
+                    ValidationErrorKind::SchemaError {
+                        reason: reason.clone(),
+                    },
+                    reason,
+                )
+                .with_span(span),
+            );
+        }
+        check_type_refs(&entry.value, path, &is_known, result);
+    }
+}
+
+/// Report every tag in `value` that references a type which is neither
+/// built in, defined nor imported.
+fn check_type_refs(
+    value: &Value,
+    path: &str,
+    is_known: &impl Fn(&str) -> bool,
+    result: &mut ValidationResult,
+) {
+    // Built-in types deserialize as their own variants, others as references
+    if let Some(name) = value.tag_name()
+        && !name.is_empty()
+        && !is_known(name)
+        && matches!(
+            crate::from_value::<Schema>(&Value::tag(name)),
+            Ok(Schema::Type { .. })
+        )
+    {
+        result.error(
+            ValidationError::new(
+                path,
+                ValidationErrorKind::UnknownType { name: name.into() },
+                format!("unknown type '@{name}'"),
+            )
+            .with_span(value.span),
+        );
+    }
+    match &value.payload {
+        Some(Payload::Object(obj)) => {
+            for entry in &obj.entries {
+                let key = entry.key.as_str().unwrap_or("@");
+                check_type_refs(&entry.value, &child_path(path, key), is_known, result);
+            }
+        }
+        Some(Payload::Sequence(seq)) => {
+            for item in &seq.items {
+                check_type_refs(item, path, is_known, result);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Why [`parse_meta`] could not read a schema's `meta` block.
+#[derive(Debug)]
+pub enum MetaError {
+    /// The schema is not valid Styx.
+    Parse(BuildError),
+    /// The schema has no root `meta` block.
+    Missing,
+    /// The `meta` block does not match the meta-schema, e.g. it has no `id`.
+    Invalid(DeserializeError),
+}
+
+impl fmt::Display for MetaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetaError::Parse(error) => write!(f, "failed to parse schema: {error}"),
+            MetaError::Missing => write!(f, "schema must have a `meta` block"),
+            MetaError::Invalid(error) => write!(f, "invalid `meta` block: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for MetaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MetaError::Parse(error) => Some(error),
+            MetaError::Missing | MetaError::Invalid(_) => None,
+        }
+    }
+}
+
+/// Read the `meta` block of a schema file.
+///
+/// Only the `meta` block has to be valid: the rest of the schema is parsed
+/// but not checked, so this works on schemas this version can't load, such
+/// as ones using newer constraints.
+///
+/// # Example
+///
+/// ```
+/// let meta = facet_styx::parse_meta(
+///     "meta {id crate:myapp@1, version 1.2.0, crate myapp-schema, cli myapp}\nschema {@ @any}",
+/// )
+/// .unwrap();
+/// assert_eq!(meta.id, "crate:myapp@1");
+/// assert_eq!(meta.crate_name.as_deref(), Some("myapp-schema"));
+/// assert_eq!(meta.description, None);
+/// ```
+pub fn parse_meta(source: &str) -> Result<Meta, MetaError> {
+    let meta = styx_tree::parse_meta(source)
+        .map_err(MetaError::Parse)?
+        .ok_or(MetaError::Missing)?;
+    crate::from_value(&meta).map_err(MetaError::Invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RenderError;
+    use crate::schema_compat::key_label;
+    use facet_testhelpers::test;
+
+    fn field_names(schema: &SchemaFile, name: Option<&str>) -> Vec<String> {
+        let Some(Schema::Object(obj)) = schema.schema.get(&name.map(String::from)) else {
+            panic!("{name:?} should be an object type");
+        };
+        let mut names: Vec<_> = obj.0.keys().map(|key| key_label(&key.value)).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_meta_schema_source_deserializes() {
+        let parsed: SchemaFile = crate::from_str(META_SCHEMA_SOURCE)
+            .unwrap_or_else(|e| panic!("{}", e.render("meta.styx", META_SCHEMA_SOURCE)));
+        assert_eq!(parsed.meta.id, META_SCHEMA.meta.id);
+        assert_eq!(parsed.meta.version, META_SCHEMA.meta.version);
+
+        let mut parsed_types: Vec<_> = parsed.schema.keys().collect();
+        let mut typed_types: Vec<_> = META_SCHEMA.schema.keys().collect();
+        parsed_types.sort();
+        typed_types.sort();
+        assert_eq!(parsed_types, typed_types);
+    }
+
+    #[test]
+    fn test_meta_schema_definitions() {
+        assert_eq!(
+            field_names(&META_SCHEMA, None),
+            ["imports", "meta", "schema"]
+        );
+        assert_eq!(
+            field_names(&META_SCHEMA, Some("Meta")),
+            ["cli", "crate", "description", "id", "lsp", "version"]
+        );
+        assert_eq!(
+            field_names(&META_SCHEMA, Some("LspExtension")),
+            ["capabilities", "launch"]
+        );
+        assert!(META_SCHEMA.schema.contains_key(&Some("Schema".into())));
+    }
+
+    #[test]
+    fn test_validate_schema_file_accepts_valid_schema() {
+        let source = "meta {id app, version 2026-01-11, cli app}\n\
+                      imports {common common.styx}\n\
+                      schema {\n\
+                        @ @object{server @Server, log @optional(@Log)}\n\
+                        Server @object{\n\
+                          host @default(localhost @string)\n\
+                          port @int{min 1}\n\
+                          mode @enum{fast, careful @object{retries @int}}\n\
+                        }\n\
+                        Log @object{level @one-of(@string debug info), file @deprecated(\"use path\" @string)}\n\
+                      }";
+        let result = validate_schema_file(&styx_tree::parse(source).unwrap());
+        assert!(
+            result.is_valid(),
+            "{}",
+            result.render("schema.styx", source)
+        );
+
+        // The bundled meta-schema is itself a valid schema
+        let meta = styx_tree::parse(META_SCHEMA_SOURCE).unwrap();
+        assert!(validate_schema_file(&meta).is_valid());
+    }
+
+    #[test]
+    fn test_validate_schema_file_reports_errors_with_spans() {
+        let source = "meta {version 2026-01-11}\nschema {@ @object{host @strng, port @int}}";
+        let result = validate_schema_file(&styx_tree::parse(source).unwrap());
+
+        let unknown = result
+            .errors
+            .iter()
+            .find(
+                |e| matches!(&e.kind, ValidationErrorKind::UnknownType { name } if name == "strng"),
+            )
+            .expect("should report the unknown type");
+        assert_eq!(unknown.path, "@.host");
+        let span = unknown.span.expect("error should have a span");
+        assert!(source[span.start as usize..span.end as usize].starts_with("@strng"));
+
+        assert!(result.errors.iter().any(|e| {
+            matches!(&e.kind, ValidationErrorKind::MissingField { field } if field == "id")
+                && e.path == "meta.id"
+                && e.span.is_some()
+        }));
+
+        let result = validate_schema_file(&styx_tree::parse("meta {id app}\ntypes {}").unwrap());
+        assert_eq!(
+            result.errors.len(),
+            2,
+            "unknown `types` and missing `schema`"
+        );
+    }
+
+    #[test]
+    fn test_validate_schema_file_reports_bad_definitions() {
+        let source = "meta {id app}\n\
+                      schema {\n\
+                        @ @object{port @int{min low}, server @Server}\n\
+                        Server @object{host @string{minLen -1}, weight @float{max big}}\n\
+                      }";
+        let result = validate_schema_file(&styx_tree::parse(source).unwrap());
+        let mut errors: Vec<_> = result
+            .errors
+            .iter()
+            .map(|e| {
+                let span = e.span.expect("error should have a span");
+                (
+                    e.path.as_str(),
+                    &source[span.start as usize..span.end as usize],
+                )
+            })
+            .collect();
+        errors.sort();
+        assert_eq!(
+            errors,
+            [
+                ("@.port.min", "low"),
+                ("Server.host.minLen", "-1"),
+                ("Server.weight.max", "big"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_meta_with_all_fields() {
+        let meta = parse_meta(
+            r#"meta {
+    id crate:myapp@1
+    version 2.1.0
+    description "Configuration for myapp."
+    crate myapp-schema
+    cli myapp
+    lsp {launch (myapp lsp-extension)}
+}
+schema {@ @object{port @int}}"#,
+        )
+        .unwrap();
+        assert_eq!(meta.id, "crate:myapp@1");
+        assert_eq!(meta.version.as_deref(), Some("2.1.0"));
+        assert_eq!(
+            meta.description.as_deref(),
+            Some("Configuration for myapp.")
+        );
+        assert_eq!(meta.crate_name.as_deref(), Some("myapp-schema"));
+        assert_eq!(meta.cli.as_deref(), Some("myapp"));
+        assert_eq!(meta.lsp.unwrap().launch, ["myapp", "lsp-extension"]);
+    }
+
+    #[test]
+    fn test_parse_meta_without_optional_fields() {
+        // The schema block isn't checked, only parsed
+        let meta = parse_meta("meta {id app, version 1.0.0}\nschema {@ @future_type}").unwrap();
+        assert_eq!(meta.id, "app");
+        assert_eq!(meta.version.as_deref(), Some("1.0.0"));
+        assert_eq!(meta.description, None);
+        assert_eq!(meta.crate_name, None);
+        assert_eq!(meta.cli, None);
+    }
+
+    #[test]
+    fn test_parse_meta_errors() {
+        assert!(matches!(parse_meta("meta {id"), Err(MetaError::Parse(_))));
+        assert!(matches!(
+            parse_meta("schema {@ @any}"),
+            Err(MetaError::Missing)
+        ));
+        assert!(matches!(
+            parse_meta("meta {version 1.0.0}"),
+            Err(MetaError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,240 @@
+//! Serialization with schema-declared key order.
+//!
+//! `SchemaFile` stores object fields in hash maps, so declaration order is only
+//! available from the schema document itself. This module serializes a value
+//! as usual, then reorders object entries to follow the field order of the
+//! matching `@object{...}` definitions in the schema tree.
+
+use facet_core::Facet;
+use facet_format::SerializeError;
+use styx_format::{FormatOptions, format_value};
+use styx_tree::{Object, Value};
+
+use crate::schema_types::resolve_type_ref;
+use crate::serializer::{StyxSerializeError, to_string_with_options};
+
+/// Serialize a value to a Styx string, emitting object keys in the order the
+/// schema declares them.
+///
+/// `schema` is a parsed schema document (with `meta` and `schema` blocks), as
+/// returned by [`styx_tree::parse`]. Keys the schema does not declare are kept
+/// after the declared ones, in their original order.
+///
+/// # Example
+///
+/// ```
+/// use facet::Facet;
+/// use facet_styx::to_string_ordered;
+///
+/// #[derive(Facet)]
+/// struct Config {
+///     port: u16,
+///     name: String,
+/// }
+///
+/// let schema = styx_tree::parse(
+///     "meta {id example}\nschema {@ @object{name @string, port @int}}",
+/// )
+/// .unwrap();
+///
+/// let config = Config { port: 8080, name: "myapp".into() };
+/// let styx = to_string_ordered(&config, &schema).unwrap();
+/// assert_eq!(styx, "name myapp\nport 8080\n");
+/// ```
+pub fn to_string_ordered<'facet, T>(
+    value: &T,
+    schema: &Value,
+) -> Result<String, SerializeError<StyxSerializeError>>
+where
+    T: Facet<'facet> + ?Sized,
+{
+    to_string_ordered_with_options(value, schema, &FormatOptions::default())
+}
+
+/// Serialize a value in schema-declared key order with custom options.
+pub fn to_string_ordered_with_options<'facet, T>(
+    value: &T,
+    schema: &Value,
+    options: &FormatOptions,
+) -> Result<String, SerializeError<StyxSerializeError>>
+where
+    T: Facet<'facet> + ?Sized,
+{
+    let serialized = to_string_with_options(value, options)?;
+
+    let Some(defs) = schema_definitions(schema) else {
+        return Ok(serialized);
+    };
+    let Some(root_schema) = defs.get_unit() else {
+        return Ok(serialized);
+    };
+
+    let mut tree = styx_tree::parse(&serialized).map_err(|e| {
+        SerializeError::Backend(StyxSerializeError::new(format!(
+            "failed to re-parse serialized output: {e}"
+        )))
+    })?;
+    apply_schema_order(&mut tree, root_schema, defs);
+
+    Ok(format_value(&tree, options.clone()))
+}
+
+/// Get the `schema { ... }` block of a schema document.
+pub(crate) fn schema_definitions(schema: &Value) -> Option<&Object> {
+    schema.get("schema")?.as_object()
+}
+
+/// Reorder `value` in place according to `schema`, recursing into children.
+fn apply_schema_order(value: &mut Value, schema: &Value, defs: &Object) {
+    let Some(schema) = resolve_type_ref(schema, defs) else {
+        return;
+    };
+
+    match schema.tag_name() {
+        Some("object") => {
+            let (Some(fields), Some(obj)) = (schema.as_object(), value.as_object_mut()) else {
+                return;
+            };
+            order_object(obj, fields, defs);
+        }
+        Some("seq") => {
+            let (Some(item_schema), Some(seq)) = (
+                schema.as_sequence().and_then(|s| s.items.first()),
+                value.as_sequence_mut(),
+            ) else {
+                return;
+            };
+            for item in &mut seq.items {
+                apply_schema_order(item, item_schema, defs);
+            }
+        }
+        Some("map") => {
+            // `@map(@V)` or `@map(@K @V)`: the value type is always last
+            let (Some(value_schema), Some(obj)) = (
+                schema.as_sequence().and_then(|s| s.items.last()),
+                value.as_object_mut(),
+            ) else {
+                return;
+            };
+            for entry in &mut obj.entries {
+                apply_schema_order(&mut entry.value, value_schema, defs);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Sort entries by their position in `fields`, then recurse into each value.
+fn order_object(obj: &mut Object, fields: &Object, defs: &Object) {
+    let position = |key: &Value| {
+        key.as_str()
+            .and_then(|name| {
+                fields
+                    .entries
+                    .iter()
+                    .position(|field| field.key.as_str() == Some(name))
+            })
+            .unwrap_or(usize::MAX)
+    };
+
+    // Stable sort keeps undeclared keys in their original relative order
+    obj.entries.sort_by_key(|entry| position(&entry.key));
+
+    // Typed catch-all (`@string @T`) applies to keys not declared by name
+    let catch_all = fields
+        .entries
+        .iter()
+        .find(|field| field.key.tag.is_some())
+        .map(|field| &field.value);
+
+    for entry in &mut obj.entries {
+        let field_schema = entry
+            .key
+            .as_str()
+            .and_then(|name| fields.get(name))
+            .or(catch_all);
+        if let Some(field_schema) = field_schema {
+            apply_schema_order(&mut entry.value, field_schema, defs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::to_string;
+    use facet::Facet;
+    use facet_testhelpers::test;
+
+    #[test]
+    fn test_schema_order_differs_from_struct_order() {
+        #[derive(Facet)]
+        struct Server {
+            port: u16,
+            host: String,
+        }
+
+        #[derive(Facet)]
+        struct Config {
+            verbose: bool,
+            server: Server,
+            name: String,
+        }
+
+        let schema = styx_tree::parse(
+            r#"meta {id test}
+schema {
+    @ @object{
+        name @string
+        server @Server
+        verbose @optional(@bool)
+    }
+    Server @object{
+        host @string
+        port @int
+    }
+}"#,
+        )
+        .unwrap();
+
+        let config = Config {
+            verbose: true,
+            server: Server {
+                port: 8080,
+                host: "localhost".into(),
+            },
+            name: "myapp".into(),
+        };
+
+        let plain = to_string(&config).unwrap();
+        assert!(plain.find("verbose").unwrap() < plain.find("name").unwrap());
+
+        let ordered = to_string_ordered(&config, &schema).unwrap();
+        assert_eq!(
+            ordered,
+            "name myapp\nserver {host localhost, port 8080}\nverbose true\n"
+        );
+    }
+
+    #[test]
+    fn test_unknown_keys_go_last() {
+        #[derive(Facet)]
+        struct Config {
+            extra: String,
+            b: i32,
+            a: i32,
+        }
+
+        let schema =
+            styx_tree::parse("meta {id test}\nschema {@ @object{a @int, b @int}}").unwrap();
+
+        let config = Config {
+            extra: "x".into(),
+            b: 2,
+            a: 1,
+        };
+
+        let ordered = to_string_ordered(&config, &schema).unwrap();
+        assert_eq!(ordered, "a 1\nb 2\nextra x\n");
+    }
+}
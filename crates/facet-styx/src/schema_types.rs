@@ -5,6 +5,7 @@
 use std::collections::HashMap;
 
 use facet::Facet;
+use styx_tree::{Object, Value};
 
 /// A complete schema file.
 #[derive(Facet, Debug, Clone)]
@@ -20,6 +21,64 @@ pub struct SchemaFile {
     pub schema: HashMap<Option<String>, Schema>,
 }
 
+/// Maximum number of `@Type` references followed while resolving a schema.
+/// Guards against alias cycles like `A @B` / `B @A`.
+pub(crate) const MAX_TYPE_REF_HOPS: usize = 32;
+
+impl SchemaFile {
+    /// Follow named type references from `schema` to the definition they
+    /// stand for.
+    ///
+    /// Returns `None` if a reference names no definition, or the references
+    /// form a cycle.
+    pub(crate) fn resolve<'a>(&'a self, mut schema: &'a Schema) -> Option<&'a Schema> {
+        for _ in 0..MAX_TYPE_REF_HOPS {
+            let Schema::Type { name: Some(name) } = schema else {
+                return Some(schema);
+            };
+            schema = self.schema.get(&Some(name.clone()))?;
+        }
+        None
+    }
+}
+
+/// Follow `@Type` references and unwrap `@optional(@T)`, `@default(v @T)`
+/// and `@deprecated("reason" @T)` in a parsed schema document, down to the
+/// schema that types a value.
+///
+/// `defs` is the document's `schema { ... }` block. Built-in types such as
+/// `@int` are returned as they are. Returns `None` if `schema` is untagged,
+/// or the references form a cycle.
+///
+/// # Example
+///
+/// ```
+/// let doc = styx_tree::parse(
+///     "meta {id test}\nschema {@ @object{port @Port}, Port @optional(@int)}",
+/// )
+/// .unwrap();
+/// let defs = doc.get("schema").unwrap().as_object().unwrap();
+/// let port = defs.get_unit().unwrap().get("port").unwrap();
+/// let resolved = facet_styx::resolve_type_ref(port, defs).unwrap();
+/// assert_eq!(resolved.tag_name(), Some("int"));
+/// ```
+pub fn resolve_type_ref<'a>(mut schema: &'a Value, defs: &'a Object) -> Option<&'a Value> {
+    for _ in 0..MAX_TYPE_REF_HOPS {
+        let name = schema.tag_name()?;
+        schema = match (name, &schema.payload) {
+            ("optional" | "default" | "deprecated", Some(_)) => {
+                schema.as_sequence()?.items.last()?
+            }
+            (_, Some(_)) => return Some(schema),
+            (_, None) => match defs.get(name) {
+                Some(target) => target,
+                None => return Some(schema),
+            },
+        };
+    }
+    None
+}
+
 /// Schema metadata.
 #[derive(Facet, Debug, Clone)]
 pub struct Meta {
@@ -34,6 +93,9 @@ pub struct Meta {
     /// Human-readable description.
     #[facet(skip_serializing_if = Option::is_none)]
     pub description: Option<String>,
+    /// Crate name for publishing to crates.io.
+    #[facet(rename = "crate", skip_serializing_if = Option::is_none)]
+    pub crate_name: Option<String>,
     /// LSP extension configuration.
     #[facet(skip_serializing_if = Option::is_none)]
     pub lsp: Option<LspExtensionConfig>,
@@ -4,7 +4,7 @@
 
 use std::collections::HashSet;
 
-use styx_tree::{Payload, Value};
+use styx_tree::{Entry, Payload, Span, Value};
 
 /// Compute Levenshtein distance between two strings.
 fn levenshtein(a: &str, b: &str) -> usize {
@@ -98,6 +98,20 @@ impl<'a> Validator<'a> {
         }
     }
 
+    /// Resolve the root schema, following type references, if it is an
+    /// object.
+    ///
+    /// Documents with an object root can be validated one entry at a time
+    /// with [`validate_object_entry`](Self::validate_object_entry) and
+    /// [`check_missing_fields`](Self::check_missing_fields).
+    pub fn root_object_schema(&self) -> Option<&'a ObjectSchema> {
+        let root = self.schema_file.schema.get(&None)?;
+        match self.schema_file.resolve(root)? {
+            Schema::Object(obj) => Some(obj),
+            _ => None,
+        }
+    }
+
     /// Validate a value against a specific named type.
     pub fn validate_as_type(&self, value: &Value, type_name: &str) -> ValidationResult {
         match self.schema_file.schema.get(&Some(type_name.to_string())) {
@@ -451,80 +465,115 @@ impl<'a> Validator<'a> {
         };
 
         let mut seen_fields: HashSet<Option<&str>> = HashSet::new();
+        for entry in &obj.entries {
+            if let Some(key_opt) = self.validate_object_entry(entry, schema, path, &mut result) {
+                seen_fields.insert(key_opt);
+            }
+        }
+
+        self.check_missing_fields(
+            schema,
+            path,
+            value.span,
+            |name| seen_fields.contains(&Some(name)),
+            &mut result,
+        );
+
+        result
+    }
+
+    /// Validate a single object entry against its field schema.
+    ///
+    /// Returns the entry's key (`None` for the unit key), or `None` at the
+    /// outer level if the key itself is invalid.
+    pub fn validate_object_entry<'v>(
+        &self,
+        entry: &'v Entry,
+        schema: &ObjectSchema,
+        path: &str,
+        result: &mut ValidationResult,
+    ) -> Option<Option<&'v str>> {
+        let key_opt: Option<&str> = if entry.key.is_unit() {
+            None
+        } else if let Some(s) = entry.key.as_str() {
+            Some(s)
+        } else {
+            result.error(
+                ValidationError::new(
+                    path,
+                    ValidationErrorKind::InvalidValue {
+                        reason: "object keys must be scalars or unit".into(),
+                    },
+                    "invalid object key",
+                )
+                .with_span(entry.key.span),
+            );
+            return None;
+        };
+
+        let key_display = key_opt.unwrap_or("@");
+        let field_path = if path.is_empty() {
+            key_display.to_string()
+        } else {
+            format!("{path}.{key_display}")
+        };
+
         // Look up catch-all schema - find any key that is a typed pattern or unit
         let additional_schema = schema
             .0
             .iter()
             .find_map(|(k, v)| if k.value.tag.is_some() { Some(v) } else { None });
 
-        for entry in &obj.entries {
-            let key_opt: Option<&str> = if entry.key.is_unit() {
-                None
-            } else if let Some(s) = entry.key.as_str() {
-                Some(s)
-            } else {
-                result.error(
-                    ValidationError::new(
-                        path,
-                        ValidationErrorKind::InvalidValue {
-                            reason: "object keys must be scalars or unit".into(),
-                        },
-                        "invalid object key",
-                    )
-                    .with_span(entry.key.span),
-                );
-                continue;
-            };
-
-            let key_display = key_opt.unwrap_or("@");
-            let field_path = if path.is_empty() {
-                key_display.to_string()
-            } else {
-                format!("{path}.{key_display}")
-            };
-
-            seen_fields.insert(key_opt);
-
-            // Look up by Documented<ObjectKey> - for named fields
-            let lookup_key = Documented::new(ObjectKey::named(key_opt.unwrap_or("")));
-            if let Some(field_schema) = schema.0.get(&lookup_key) {
-                result.merge(self.validate_value(&entry.value, field_schema, &field_path));
-            } else if let Some(add_schema) = additional_schema {
-                result.merge(self.validate_value(&entry.value, add_schema, &field_path));
-            } else {
-                // Collect valid field names for error message
-                let valid_fields: Vec<String> = schema
-                    .0
-                    .keys()
-                    .filter_map(|k| k.value.name().map(|s| s.to_string()))
-                    .collect();
+        // Look up by Documented<ObjectKey> - for named fields
+        let lookup_key = Documented::new(ObjectKey::named(key_opt.unwrap_or("")));
+        if let Some(field_schema) = schema.0.get(&lookup_key) {
+            result.merge(self.validate_value(&entry.value, field_schema, &field_path));
+        } else if let Some(add_schema) = additional_schema {
+            result.merge(self.validate_value(&entry.value, add_schema, &field_path));
+        } else {
+            // Collect valid field names for error message
+            let valid_fields: Vec<String> = schema
+                .0
+                .keys()
+                .filter_map(|k| k.value.name().map(|s| s.to_string()))
+                .collect();
 
-                // Try to find a similar field name (typo detection)
-                let suggestion = suggest_similar(key_display, &valid_fields).map(String::from);
+            // Try to find a similar field name (typo detection)
+            let suggestion = suggest_similar(key_display, &valid_fields).map(String::from);
 
-                result.error(
-                    ValidationError::new(
-                        &field_path,
-                        ValidationErrorKind::UnknownField {
-                            field: key_display.into(),
-                            valid_fields,
-                            suggestion,
-                        },
-                        format!("unknown field '{key_display}'"),
-                    )
-                    .with_span(entry.key.span),
-                );
-            }
+            result.error(
+                ValidationError::new(
+                    &field_path,
+                    ValidationErrorKind::UnknownField {
+                        field: key_display.into(),
+                        valid_fields,
+                        suggestion,
+                    },
+                    format!("unknown field '{key_display}'"),
+                )
+                .with_span(entry.key.span),
+            );
         }
 
-        // Check for missing required fields
+        Some(key_opt)
+    }
+
+    /// Report required fields of `schema` for which `seen` returns false.
+    pub fn check_missing_fields(
+        &self,
+        schema: &ObjectSchema,
+        path: &str,
+        span: Option<Span>,
+        seen: impl Fn(&str) -> bool,
+        result: &mut ValidationResult,
+    ) {
         for (field_name_doc, field_schema) in &schema.0 {
             // Skip catch-all fields (typed patterns like @string)
             let Some(name) = field_name_doc.value.name() else {
                 continue;
             };
 
-            if !seen_fields.contains(&Some(name)) {
+            if !seen(name) {
                 // Optional and Default fields are not required
                 if !matches!(field_schema, Schema::Optional(_) | Schema::Default(_)) {
                     let field_path = if path.is_empty() {
@@ -540,13 +589,11 @@ impl<'a> Validator<'a> {
                             },
                             format!("missing required field '{name}'"),
                         )
-                        .with_span(value.span),
+                        .with_span(span),
                     );
                 }
             }
         }
-
-        result
     }
 
     fn validate_seq(&self, value: &Value, schema: &SeqSchema, path: &str) -> ValidationResult {
@@ -1099,7 +1146,7 @@ fn value_type_name(value: &Value) -> &'static str {
 }
 
 /// Get a human-readable name for a schema type.
-fn schema_type_name(schema: &Schema) -> String {
+pub(crate) fn schema_type_name(schema: &Schema) -> String {
     match schema {
         Schema::String(_) => "string".into(),
         Schema::Int(_) => "int".into(),
@@ -0,0 +1,253 @@
+//! Streaming schema validation.
+//!
+//! [`Validator`] works on a fully built `styx_tree::Value`. For large documents
+//! where only pass/fail matters, [`StreamingValidator`] validates straight from
+//! parser events instead: each root entry is built, validated against its field
+//! schema and dropped before the next one is read, so memory is bounded by the
+//! largest root entry rather than the whole document.
+
+use std::collections::HashSet;
+
+use styx_parse::{Event, Parser};
+use styx_tree::{BuildError, RootEntries, TreeBuilder, Value};
+
+use crate::schema_error::ValidationResult;
+use crate::schema_types::{ObjectSchema, SchemaFile};
+use crate::schema_validate::Validator;
+
+/// Validator that checks a document against a schema while parsing it.
+///
+/// Produces the same errors as [`validate`](crate::validate) on the parsed
+/// tree. Root `@schema` declarations are skipped, like the CLI does before
+/// tree-based validation.
+///
+/// # Example
+///
+/// ```
+/// use facet_styx::{SchemaFile, StreamingValidator, from_str};
+///
+/// let schema: SchemaFile = from_str("meta {id test}\nschema {@ @object{port @int}}").unwrap();
+/// let result = StreamingValidator::new(&schema)
+///     .validate_source("port 8080")
+///     .unwrap();
+/// assert!(result.is_valid());
+/// ```
+pub struct StreamingValidator<'a> {
+    schema_file: &'a SchemaFile,
+    fail_fast: bool,
+}
+
+impl<'a> StreamingValidator<'a> {
+    /// Create a new streaming validator with the given schema.
+    pub fn new(schema_file: &'a SchemaFile) -> Self {
+        Self {
+            schema_file,
+            fail_fast: false,
+        }
+    }
+
+    /// Stop at the first root entry that produces a validation error.
+    ///
+    /// The rest of the document is neither parsed nor validated, so syntax
+    /// errors after that point go unreported.
+    pub fn fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// Parse and validate a document.
+    ///
+    /// Returns `Err` on the first syntax error.
+    pub fn validate_source(&self, source: &str) -> Result<ValidationResult, BuildError> {
+        let mut parser = Parser::new(source);
+        self.validate_events(std::iter::from_fn(|| parser.next_event()))
+    }
+
+    /// Validate a document from its parser events.
+    ///
+    /// Lets callers that have already read the start of a document, such as
+    /// its `@schema` declaration, pass on the events they read followed by the
+    /// parser's remaining ones instead of parsing the document again.
+    ///
+    /// Returns `Err` on the first syntax error.
+    pub fn validate_events<'src>(
+        &self,
+        events: impl IntoIterator<Item = Event<'src>>,
+    ) -> Result<ValidationResult, BuildError> {
+        let validator = Validator::new(self.schema_file);
+
+        let Some(root_schema) = validator.root_object_schema() else {
+            // Only object roots can be validated entry by entry
+            let mut builder = TreeBuilder::new();
+            for event in events {
+                builder.event(event);
+            }
+            let value = builder.finish()?;
+            return Ok(validator.validate_document(&strip_schema_declaration(value)));
+        };
+
+        let mut result = ValidationResult::ok();
+        let mut seen_fields: HashSet<Option<String>> = HashSet::new();
+        for entry_value in RootEntries::new(events) {
+            self.validate_root_entry(
+                &validator,
+                entry_value?,
+                root_schema,
+                &mut seen_fields,
+                &mut result,
+            );
+            if self.fail_fast && !result.is_valid() {
+                return Ok(result);
+            }
+        }
+
+        validator.check_missing_fields(
+            root_schema,
+            "",
+            None,
+            |name| seen_fields.contains(&Some(name.to_string())),
+            &mut result,
+        );
+
+        Ok(result)
+    }
+
+    /// Validate the single-entry object built for one root entry.
+    fn validate_root_entry(
+        &self,
+        validator: &Validator<'_>,
+        entry_value: Value,
+        root_schema: &ObjectSchema,
+        seen_fields: &mut HashSet<Option<String>>,
+        result: &mut ValidationResult,
+    ) {
+        let Some(obj) = entry_value.as_object() else {
+            return;
+        };
+        for entry in &obj.entries {
+            if entry.key.is_schema_tag() {
+                continue;
+            }
+            if let Some(key) = validator.validate_object_entry(entry, root_schema, "", result) {
+                if key.is_some_and(|k| seen_fields.contains(&Some(k.to_string()))) {
+                    // Duplicate keys are a syntax error, reported by the parser
+                    continue;
+                }
+                seen_fields.insert(key.map(str::to_string));
+            }
+        }
+    }
+}
+
+/// Validate a document from source without building the full tree.
+///
+/// Shorthand for [`StreamingValidator::new`] followed by
+/// [`StreamingValidator::validate_source`].
+pub fn validate_streaming(
+    source: &str,
+    schema: &SchemaFile,
+) -> Result<ValidationResult, BuildError> {
+    StreamingValidator::new(schema).validate_source(source)
+}
+
+/// Drop a root `@schema` entry before tree-based validation.
+fn strip_schema_declaration(mut value: Value) -> Value {
+    if let Some(obj) = value.as_object_mut() {
+        obj.entries.retain(|e| !e.key.is_schema_tag());
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate;
+    use facet_testhelpers::test;
+
+    const SCHEMA: &str = r#"meta {id test}
+schema {
+    @ @object{
+        name @string
+        port @int
+        tags @optional(@seq(@string))
+        server @optional(@Server)
+    }
+    Server @object{
+        host @string
+        tls @optional(@bool)
+    }
+}"#;
+
+    fn schema() -> SchemaFile {
+        crate::from_str(SCHEMA).expect("schema should parse")
+    }
+
+    fn tree_errors(source: &str, schema: &SchemaFile) -> Vec<(String, String)> {
+        let value = strip_schema_declaration(styx_tree::parse(source).unwrap());
+        let result = validate(&value, schema);
+        result
+            .errors
+            .iter()
+            .map(|e| (e.path.clone(), e.message.clone()))
+            .collect()
+    }
+
+    fn streaming_errors(source: &str, schema: &SchemaFile) -> Vec<(String, String)> {
+        let result = validate_streaming(source, schema).unwrap();
+        result
+            .errors
+            .iter()
+            .map(|e| (e.path.clone(), e.message.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_streaming_agrees_with_tree_validation() {
+        let schema = schema();
+        let documents = [
+            "name app\nport 8080",
+            "@schema schema.styx\nname app\nport 8080",
+            "name app\nport eighty",
+            "name app",
+            "name app\nport 1\nprot 2",
+            "name app\nport 1\ntags (a b c)",
+            "name app\nport 1\ntags (a {b c})",
+            "name app\nport 1\nserver {host localhost, tls maybe}",
+            "/// The name\nname app\nport 1\nserver {tls true}",
+            "",
+        ];
+
+        for source in documents {
+            let mut expected = tree_errors(source, &schema);
+            let mut actual = streaming_errors(source, &schema);
+            // Missing-field errors come from iterating a HashMap in both cases
+            expected.sort();
+            actual.sort();
+            assert_eq!(actual, expected, "disagreement on {source:?}");
+        }
+    }
+
+    #[test]
+    fn test_streaming_reports_syntax_errors() {
+        let schema = schema();
+        let err = validate_streaming("name app\nport {", &schema).unwrap_err();
+        assert!(matches!(err, BuildError::Parse(..)));
+    }
+
+    #[test]
+    fn test_fail_fast_stops_at_first_invalid_entry() {
+        let schema = schema();
+        // Two invalid entries, then a syntax error that is never reached
+        let source = "name app\nport eighty\nbogus 1\nbroken {";
+
+        let result = StreamingValidator::new(&schema)
+            .fail_fast(true)
+            .validate_source(source)
+            .unwrap();
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].path, "port");
+
+        // Without fail-fast the whole document is read, hitting the syntax error
+        assert!(validate_streaming(source, &schema).is_err());
+    }
+}
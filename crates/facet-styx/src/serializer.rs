@@ -1,6 +1,7 @@
 //! Styx serialization implementation.
 
 use std::borrow::Cow;
+use std::io;
 
 use crate::trace;
 use facet_core::Facet;
@@ -8,9 +9,10 @@ use facet_format::{
     FieldKey, FieldLocationHint, FormatSerializer, ScalarValue, SerializeError, serialize_root,
 };
 use facet_reflect::{HasFields, Peek};
-use styx_format::{FormatOptions, StyxWriter};
+use styx_format::{EnumRepr, FormatOptions, ScalarStyle, StyxWriter, redact_source, render_scalar};
 
 // Re-export FormatOptions as SerializeOptions for backwards compatibility
+pub use styx_format::EnumRepr;
 pub use styx_format::FormatOptions as SerializeOptions;
 
 /// Extract a FieldKey from a Peek value (typically a map key).
@@ -170,7 +172,7 @@ pub struct StyxSerializeError {
 }
 
 impl StyxSerializeError {
-    fn new(msg: impl Into<Cow<'static, str>>) -> Self {
+    pub(crate) fn new(msg: impl Into<Cow<'static, str>>) -> Self {
         Self { msg: msg.into() }
     }
 }
@@ -184,12 +186,19 @@ impl core::fmt::Display for StyxSerializeError {
 impl std::error::Error for StyxSerializeError {}
 
 /// Styx serializer with configurable formatting options.
-pub struct StyxSerializer {
-    writer: StyxWriter,
+///
+/// Output is written to `W` as values are serialized, so large collections
+/// don't have to be held in memory as a whole.
+pub struct StyxSerializer<W = Vec<u8>> {
+    writer: MapSortingWriter,
     /// Track if we're at root level (for struct unwrapping)
     at_root: bool,
     /// Track if we just wrote a variant tag (to skip None payload)
     just_wrote_tag: bool,
+    /// How enum variants are written
+    enum_repr: EnumRepr,
+    /// Where settled output goes
+    sink: W,
 }
 
 impl StyxSerializer {
@@ -200,26 +209,67 @@ impl StyxSerializer {
 
     /// Create a new Styx serializer with the given options.
     pub fn with_options(options: FormatOptions) -> Self {
+        Self::from_writer(Vec::new(), options)
+    }
+
+    /// Consume the serializer and return the output bytes, ensuring trailing newline.
+    pub fn finish(self) -> Vec<u8> {
+        let mut out = self.sink;
+        out.extend(self.writer.into_inner().finish_document());
+        out
+    }
+}
+
+impl<W: io::Write> StyxSerializer<W> {
+    /// Create a Styx serializer writing to `sink` with the given options.
+    pub fn from_writer(sink: W, options: FormatOptions) -> Self {
         Self {
-            writer: StyxWriter::with_options(options),
+            enum_repr: options.enum_repr,
+            writer: MapSortingWriter::new(options),
             at_root: true,
             just_wrote_tag: false,
+            sink,
         }
     }
 
-    /// Consume the serializer and return the output bytes, ensuring trailing newline.
-    pub fn finish(self) -> Vec<u8> {
-        self.writer.finish_document()
+    /// Write the rest of the output, ensuring trailing newline, and return the sink.
+    pub fn into_inner(mut self) -> Result<W, StyxSerializeError> {
+        let rest = self.writer.into_inner().finish_document();
+        self.sink
+            .write_all(&rest)
+            .and_then(|()| self.sink.flush())
+            .map_err(write_error)?;
+        Ok(self.sink)
+    }
+
+    /// Write `@name`, with the payload that follows as its value.
+    fn write_tag(&mut self, name: &str) -> Result<bool, StyxSerializeError> {
+        self.at_root = false;
+        self.just_wrote_tag = true;
+        self.writer.write_tag(name);
+        Ok(true)
+    }
+
+    /// Hand settled output to the sink once enough of it is buffered.
+    fn flush_settled(&mut self) -> Result<(), StyxSerializeError> {
+        self.writer
+            .inner_mut()
+            .flush_settled_if_full(&mut self.sink)
+            .map_err(write_error)
     }
 }
 
+fn write_error(e: io::Error) -> StyxSerializeError {
+    StyxSerializeError::new(format!("failed to write output: {e}"))
+}
+
 impl Default for StyxSerializer {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl FormatSerializer for StyxSerializer {
+impl<W: io::Write> FormatSerializer for StyxSerializer<W> {
     type Error = StyxSerializeError;
 
     fn begin_struct(&mut self) -> Result<(), Self::Error> {
@@ -254,11 +304,7 @@ impl FormatSerializer for StyxSerializer {
         ) {
             (Some(tag), Some(name)) => {
                 // @tag"name" - tagged with value
-                let key_str = if tag.is_empty() {
-                    format!("@\"{}\"", name)
-                } else {
-                    format!("@{}\"{}\"", tag, name)
-                };
+                let key_str = format!("@{}{}", tag, render_scalar(name, ScalarStyle::Quoted));
                 if !doc_lines.is_empty() {
                     self.writer
                         .write_doc_comment_and_key_raw(&doc_lines.join("\n"), &key_str);
@@ -312,7 +358,8 @@ impl FormatSerializer for StyxSerializer {
 
     fn end_struct(&mut self) -> Result<(), Self::Error> {
         trace!("end_struct");
-        self.writer.end_struct().map_err(StyxSerializeError::new)
+        self.writer.end_struct().map_err(StyxSerializeError::new)?;
+        self.flush_settled()
     }
 
     fn begin_seq(&mut self) -> Result<(), Self::Error> {
@@ -324,7 +371,8 @@ impl FormatSerializer for StyxSerializer {
 
     fn end_seq(&mut self) -> Result<(), Self::Error> {
         trace!("end_seq");
-        self.writer.end_seq().map_err(StyxSerializeError::new)
+        self.writer.end_seq().map_err(StyxSerializeError::new)?;
+        self.flush_settled()
     }
 
     fn scalar(&mut self, scalar: ScalarValue<'_>) -> Result<(), Self::Error> {
@@ -337,19 +385,8 @@ impl FormatSerializer for StyxSerializer {
             return Ok(());
         }
         self.just_wrote_tag = false;
-        match scalar {
-            ScalarValue::Unit | ScalarValue::Null => self.writer.write_null(),
-            ScalarValue::Bool(v) => self.writer.write_bool(v),
-            ScalarValue::Char(c) => self.writer.write_char(c),
-            ScalarValue::I64(v) => self.writer.write_i64(v),
-            ScalarValue::U64(v) => self.writer.write_u64(v),
-            ScalarValue::I128(v) => self.writer.write_i128(v),
-            ScalarValue::U128(v) => self.writer.write_u128(v),
-            ScalarValue::F64(v) => self.writer.write_f64(v),
-            ScalarValue::Str(s) => self.writer.write_string(&s),
-            ScalarValue::Bytes(bytes) => self.writer.write_bytes(&bytes),
-        }
-        Ok(())
+        self.writer.write_scalar(scalar);
+        self.flush_settled()
     }
 
     fn serialize_none(&mut self) -> Result<(), Self::Error> {
@@ -367,11 +404,12 @@ impl FormatSerializer for StyxSerializer {
     }
 
     fn write_variant_tag(&mut self, variant_name: &str) -> Result<bool, Self::Error> {
-        trace!(variant_name, "write_variant_tag");
-        self.at_root = false;
-        self.just_wrote_tag = true;
-        self.writer.write_tag(variant_name);
-        Ok(true)
+        trace!(variant_name, enum_repr = ?self.enum_repr, "write_variant_tag");
+        if self.enum_repr == EnumRepr::External {
+            // Declining the tag makes facet-format write `{variant payload}`
+            return Ok(false);
+        }
+        self.write_tag(variant_name)
     }
 
     fn begin_struct_after_tag(&mut self) -> Result<(), Self::Error> {
@@ -406,13 +444,13 @@ impl FormatSerializer for StyxSerializer {
         // For RawStyx, output the content directly without quoting
         self.at_root = false;
         self.just_wrote_tag = false;
-        self.writer.before_value();
-        self.writer.write_str(content);
+        self.writer.write_raw_scalar(content);
         Ok(())
     }
 
     fn serialize_map_key(&mut self, key: Peek<'_, '_>) -> Result<bool, Self::Error> {
         trace!(shape = key.shape().type_identifier, "serialize_map_key");
+        self.writer.begin_map_entry();
 
         // Try to extract a FieldKey from the map key
         if let Some(field_key) = extract_field_key(key) {
@@ -440,8 +478,8 @@ impl FormatSerializer for StyxSerializer {
                     && let Some(inner) = opt.value()
                     && let Some(s) = inner.as_str()
                 {
-                    // Emit the tag before the value
-                    self.write_variant_tag(s)?;
+                    // Emit the tag before the value, whatever the enum repr
+                    self.write_tag(s)?;
                 }
                 break;
             }
@@ -576,7 +614,68 @@ where
     let mut serializer = StyxSerializer::with_options(options.clone());
     serialize_root(&mut serializer, Peek::new(value))?;
     let bytes = serializer.finish();
-    Ok(String::from_utf8(bytes).expect("Styx output should always be valid UTF-8"))
+    redact(
+        String::from_utf8(bytes).expect("Styx output should always be valid UTF-8"),
+        options,
+    )
+}
+
+/// Serialize a value as Styx into an [`io::Write`].
+///
+/// Output is written as the value is serialized rather than built up as one
+/// string first, so large sequences and maps use little memory.
+///
+/// # Example
+///
+/// ```
+/// use facet::Facet;
+/// use facet_styx::to_writer;
+///
+/// #[derive(Facet)]
+/// struct Config {
+///     items: Vec<u32>,
+/// }
+///
+/// let mut out = Vec::new();
+/// to_writer(&mut out, &Config { items: vec![1, 2, 3] }).unwrap();
+/// assert_eq!(out, b"items (1 2 3)\n");
+/// ```
+pub fn to_writer<'facet, W, T>(
+    writer: W,
+    value: &T,
+) -> Result<(), SerializeError<StyxSerializeError>>
+where
+    W: io::Write,
+    T: Facet<'facet> + ?Sized,
+{
+    to_writer_with_options(writer, value, &FormatOptions::default())
+}
+
+/// Serialize a value as Styx into an [`io::Write`] with custom options.
+///
+/// Redacting keys needs the whole output, so with
+/// [`FormatOptions::redact_keys`] set the output is buffered before writing.
+pub fn to_writer_with_options<'facet, W, T>(
+    mut writer: W,
+    value: &T,
+    options: &FormatOptions,
+) -> Result<(), SerializeError<StyxSerializeError>>
+where
+    W: io::Write,
+    T: Facet<'facet> + ?Sized,
+{
+    if !options.redact_keys.is_empty() {
+        let output = to_string_with_options(value, options)?;
+        return writer
+            .write_all(output.as_bytes())
+            .and_then(|()| writer.flush())
+            .map_err(|e| SerializeError::Backend(write_error(e)));
+    }
+
+    let mut serializer = StyxSerializer::from_writer(writer, options.clone());
+    serialize_root(&mut serializer, Peek::new(value))?;
+    serializer.into_inner().map_err(SerializeError::Backend)?;
+    Ok(())
 }
 
 /// Serialize a `Peek` instance to a Styx string.
@@ -594,7 +693,25 @@ pub fn peek_to_string_with_options<'input, 'facet>(
     let mut serializer = StyxSerializer::with_options(options.clone());
     serialize_root(&mut serializer, peek)?;
     let bytes = serializer.finish();
-    Ok(String::from_utf8(bytes).expect("Styx output should always be valid UTF-8"))
+    redact(
+        String::from_utf8(bytes).expect("Styx output should always be valid UTF-8"),
+        options,
+    )
+}
+
+/// Hide the values of [`FormatOptions::redact_keys`] in serialized output.
+fn redact(
+    output: String,
+    options: &FormatOptions,
+) -> Result<String, SerializeError<StyxSerializeError>> {
+    if options.redact_keys.is_empty() {
+        return Ok(output);
+    }
+    redact_source(&output, options).map_err(|e| {
+        SerializeError::Backend(StyxSerializeError::new(format!(
+            "failed to re-parse serialized output: {e}"
+        )))
+    })
 }
 
 /// Serialize a `Peek` instance to a Styx expression string.
@@ -617,19 +734,19 @@ pub fn peek_to_string_expr<'input, 'facet>(
 
 /// A variant of StyxSerializer that always wraps in braces (for compact mode).
 struct CompactStyxSerializer {
-    writer: StyxWriter,
+    writer: MapSortingWriter,
 }
 
 impl CompactStyxSerializer {
     fn with_options(options: FormatOptions) -> Self {
         Self {
-            writer: StyxWriter::with_options(options),
+            writer: MapSortingWriter::new(options),
         }
     }
 
     fn finish(self) -> Vec<u8> {
         // Compact mode is for inline embedding - no trailing newline
-        self.writer.finish()
+        self.writer.into_inner().finish()
     }
 }
 
@@ -660,18 +777,7 @@ impl FormatSerializer for CompactStyxSerializer {
     }
 
     fn scalar(&mut self, scalar: ScalarValue<'_>) -> Result<(), Self::Error> {
-        match scalar {
-            ScalarValue::Unit | ScalarValue::Null => self.writer.write_null(),
-            ScalarValue::Bool(v) => self.writer.write_bool(v),
-            ScalarValue::Char(c) => self.writer.write_char(c),
-            ScalarValue::I64(v) => self.writer.write_i64(v),
-            ScalarValue::U64(v) => self.writer.write_u64(v),
-            ScalarValue::I128(v) => self.writer.write_i128(v),
-            ScalarValue::U128(v) => self.writer.write_u128(v),
-            ScalarValue::F64(v) => self.writer.write_f64(v),
-            ScalarValue::Str(s) => self.writer.write_string(&s),
-            ScalarValue::Bytes(bytes) => self.writer.write_bytes(&bytes),
-        }
+        self.writer.write_scalar(scalar);
         Ok(())
     }
 
@@ -694,6 +800,283 @@ impl FormatSerializer for CompactStyxSerializer {
         self.writer.begin_seq_after_tag();
         Ok(())
     }
+
+    fn serialize_map_key(&mut self, _key: Peek<'_, '_>) -> Result<bool, Self::Error> {
+        self.writer.begin_map_entry();
+        Ok(false)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Map entry ordering
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A [`StyxWriter`] that writes map entries sorted by key.
+///
+/// A `HashMap` iterates in an arbitrary order, so its entries are held back
+/// as recorded writer calls until the map ends, then written sorted: tagged
+/// and unit keys (`@tag`, `@`) first, then by key text. Struct fields keep
+/// their declaration order. With [`FormatOptions::preserve_map_order`] set,
+/// everything is written as it comes.
+struct MapSortingWriter {
+    writer: StyxWriter,
+    sort: bool,
+    /// Number of open structs.
+    depth: usize,
+    /// Maps whose entries are being recorded, innermost last.
+    maps: Vec<PendingMap>,
+    /// Whether the next key starts an entry of a map, rather than a field.
+    map_key_next: bool,
+}
+
+/// The entries of a map recorded so far.
+struct PendingMap {
+    /// Struct depth of the map.
+    depth: usize,
+    /// Writer calls of each entry, starting with its key.
+    entries: Vec<Vec<WriterOp>>,
+}
+
+/// A recorded [`StyxWriter`] call.
+enum WriterOp {
+    BeginStruct(bool),
+    BeginStructAfterTag(bool),
+    EndStruct,
+    BeginSeq,
+    BeginSeqAfterTag,
+    EndSeq,
+    FieldKey(String),
+    FieldKeyRaw(String),
+    DocAndKey { doc: String, key: String },
+    DocAndKeyRaw { doc: String, key: String },
+    Null,
+    Bool(bool),
+    Char(char),
+    I64(i64),
+    U64(u64),
+    I128(i128),
+    U128(u128),
+    F64(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    Tag(String),
+    RawScalar(String),
+    ClearSkipBeforeValue,
+}
+
+impl WriterOp {
+    fn apply(self, writer: &mut StyxWriter) -> Result<(), &'static str> {
+        match self {
+            WriterOp::BeginStruct(is_root) => writer.begin_struct(is_root),
+            WriterOp::BeginStructAfterTag(force_multiline) => {
+                writer.begin_struct_after_tag(force_multiline)
+            }
+            WriterOp::EndStruct => return writer.end_struct(),
+            WriterOp::BeginSeq => writer.begin_seq(),
+            WriterOp::BeginSeqAfterTag => writer.begin_seq_after_tag(),
+            WriterOp::EndSeq => return writer.end_seq(),
+            WriterOp::FieldKey(key) => return writer.field_key(&key),
+            WriterOp::FieldKeyRaw(key) => return writer.field_key_raw(&key),
+            WriterOp::DocAndKey { doc, key } => writer.write_doc_comment_and_key(&doc, &key),
+            WriterOp::DocAndKeyRaw { doc, key } => writer.write_doc_comment_and_key_raw(&doc, &key),
+            WriterOp::Null => writer.write_null(),
+            WriterOp::Bool(v) => writer.write_bool(v),
+            WriterOp::Char(c) => writer.write_char(c),
+            WriterOp::I64(v) => writer.write_i64(v),
+            WriterOp::U64(v) => writer.write_u64(v),
+            WriterOp::I128(v) => writer.write_i128(v),
+            WriterOp::U128(v) => writer.write_u128(v),
+            WriterOp::F64(v) => writer.write_f64(v),
+            WriterOp::String(s) => writer.write_string(&s),
+            WriterOp::Bytes(bytes) => writer.write_bytes(&bytes),
+            WriterOp::Tag(name) => writer.write_tag(&name),
+            WriterOp::RawScalar(content) => {
+                writer.before_value();
+                writer.write_str(&content);
+            }
+            WriterOp::ClearSkipBeforeValue => writer.clear_skip_before_value(),
+        }
+        Ok(())
+    }
+
+    /// The key this call writes, and whether it's a tagged or unit key.
+    fn key(&self) -> Option<(&str, bool)> {
+        match self {
+            WriterOp::FieldKey(key) | WriterOp::DocAndKey { key, .. } => Some((key, false)),
+            WriterOp::FieldKeyRaw(key) | WriterOp::DocAndKeyRaw { key, .. } => {
+                Some((key, key.starts_with('@')))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl MapSortingWriter {
+    fn new(options: FormatOptions) -> Self {
+        Self {
+            sort: !options.preserve_map_order,
+            writer: StyxWriter::with_options(options),
+            depth: 0,
+            maps: Vec::new(),
+            map_key_next: false,
+        }
+    }
+
+    fn into_inner(self) -> StyxWriter {
+        debug_assert!(self.maps.is_empty(), "map entries left unwritten");
+        self.writer
+    }
+
+    /// The underlying writer, for flushing. Recorded entries aren't in it yet.
+    fn inner_mut(&mut self) -> &mut StyxWriter {
+        &mut self.writer
+    }
+
+    /// Mark the next key as the start of a map entry.
+    fn begin_map_entry(&mut self) {
+        if self.sort {
+            self.map_key_next = true;
+        }
+    }
+
+    fn write(&mut self, op: WriterOp) -> Result<(), &'static str> {
+        let at_open_map = self.maps.last().is_some_and(|map| map.depth == self.depth);
+        match &op {
+            WriterOp::BeginStruct(_) | WriterOp::BeginStructAfterTag(_) => self.depth += 1,
+            WriterOp::EndStruct => {
+                if at_open_map {
+                    self.finish_map()?;
+                }
+                self.depth -= 1;
+            }
+            _ if op.key().is_some() && std::mem::take(&mut self.map_key_next) => {
+                if at_open_map {
+                    self.maps.last_mut().unwrap().entries.push(Vec::new());
+                } else {
+                    self.maps.push(PendingMap {
+                        depth: self.depth,
+                        entries: vec![Vec::new()],
+                    });
+                }
+            }
+            // A field after a flattened map
+            _ if op.key().is_some() && at_open_map => self.finish_map()?,
+            _ => {}
+        }
+        self.emit(op)
+    }
+
+    /// Write a map's entries sorted by key, to the enclosing map or the output.
+    fn finish_map(&mut self) -> Result<(), &'static str> {
+        let mut map = self.maps.pop().expect("a map is open");
+        map.entries
+            .sort_by(|a, b| entry_order(a).cmp(&entry_order(b)));
+        for op in map.entries.into_iter().flatten() {
+            self.emit(op)?;
+        }
+        Ok(())
+    }
+
+    fn emit(&mut self, op: WriterOp) -> Result<(), &'static str> {
+        match self.maps.last_mut() {
+            Some(map) => {
+                map.entries.last_mut().expect("a map has an entry").push(op);
+                Ok(())
+            }
+            None => op.apply(&mut self.writer),
+        }
+    }
+
+    /// Write a call that only fails on the writer when it's misused, and
+    /// isn't misused here.
+    fn write_infallible(&mut self, op: WriterOp) {
+        self.write(op)
+            .expect("only keys and struct ends can fail to write");
+    }
+
+    fn begin_struct(&mut self, is_root: bool) {
+        self.write_infallible(WriterOp::BeginStruct(is_root));
+    }
+
+    fn begin_struct_after_tag(&mut self, force_multiline: bool) {
+        self.write_infallible(WriterOp::BeginStructAfterTag(force_multiline));
+    }
+
+    fn end_struct(&mut self) -> Result<(), &'static str> {
+        self.write(WriterOp::EndStruct)
+    }
+
+    fn begin_seq(&mut self) {
+        self.write_infallible(WriterOp::BeginSeq);
+    }
+
+    fn begin_seq_after_tag(&mut self) {
+        self.write_infallible(WriterOp::BeginSeqAfterTag);
+    }
+
+    fn end_seq(&mut self) -> Result<(), &'static str> {
+        self.write(WriterOp::EndSeq)
+    }
+
+    fn field_key(&mut self, key: &str) -> Result<(), &'static str> {
+        self.write(WriterOp::FieldKey(key.to_string()))
+    }
+
+    fn field_key_raw(&mut self, key: &str) -> Result<(), &'static str> {
+        self.write(WriterOp::FieldKeyRaw(key.to_string()))
+    }
+
+    fn write_doc_comment_and_key(&mut self, doc: &str, key: &str) {
+        self.write_infallible(WriterOp::DocAndKey {
+            doc: doc.to_string(),
+            key: key.to_string(),
+        });
+    }
+
+    fn write_doc_comment_and_key_raw(&mut self, doc: &str, key: &str) {
+        self.write_infallible(WriterOp::DocAndKeyRaw {
+            doc: doc.to_string(),
+            key: key.to_string(),
+        });
+    }
+
+    fn write_scalar(&mut self, scalar: ScalarValue<'_>) {
+        self.write_infallible(match scalar {
+            ScalarValue::Unit | ScalarValue::Null => WriterOp::Null,
+            ScalarValue::Bool(v) => WriterOp::Bool(v),
+            ScalarValue::Char(c) => WriterOp::Char(c),
+            ScalarValue::I64(v) => WriterOp::I64(v),
+            ScalarValue::U64(v) => WriterOp::U64(v),
+            ScalarValue::I128(v) => WriterOp::I128(v),
+            ScalarValue::U128(v) => WriterOp::U128(v),
+            ScalarValue::F64(v) => WriterOp::F64(v),
+            ScalarValue::Str(s) => WriterOp::String(s.into_owned()),
+            ScalarValue::Bytes(bytes) => WriterOp::Bytes(bytes.into_owned()),
+        });
+    }
+
+    fn write_null(&mut self) {
+        self.write_infallible(WriterOp::Null);
+    }
+
+    fn write_tag(&mut self, name: &str) {
+        self.write_infallible(WriterOp::Tag(name.to_string()));
+    }
+
+    fn write_raw_scalar(&mut self, content: &str) {
+        self.write_infallible(WriterOp::RawScalar(content.to_string()));
+    }
+
+    fn clear_skip_before_value(&mut self) {
+        self.write_infallible(WriterOp::ClearSkipBeforeValue);
+    }
+}
+
+/// Sort key of a recorded map entry: tagged and unit keys first, then by key
+/// text.
+fn entry_order(entry: &[WriterOp]) -> (bool, &str) {
+    let (key, tagged) = entry.first().and_then(WriterOp::key).unwrap_or(("", true));
+    (!tagged, key)
 }
 
 #[cfg(test)]
@@ -701,6 +1084,7 @@ mod tests {
     use super::*;
     use facet::Facet;
     use facet_testhelpers::test;
+    use styx_testhelpers::CountingWriter;
 
     #[derive(Facet, Debug)]
     struct Simple {
@@ -778,6 +1162,75 @@ mod tests {
         assert!(result.contains("name \"hello world\""));
     }
 
+    #[test]
+    fn test_always_quote_strings() {
+        let options = SerializeOptions::default().always_quote_strings();
+
+        let value = Simple {
+            name: "true".into(),
+            value: 42,
+        };
+        let result = to_string_with_options(&value, &options).unwrap();
+        assert!(result.contains("name \"true\""));
+        assert!(result.contains("value 42"));
+
+        let value = Simple {
+            name: "123".into(),
+            value: 42,
+        };
+        let result = to_string_with_options(&value, &options).unwrap();
+        assert!(result.contains("name \"123\""));
+    }
+
+    #[test]
+    fn test_redact_keys() {
+        #[derive(Facet)]
+        struct Database {
+            user: String,
+            password: String,
+        }
+
+        #[derive(Facet)]
+        struct Config {
+            name: String,
+            port: u16,
+            db: Database,
+        }
+
+        let config = Config {
+            name: "myapp".into(),
+            port: 8080,
+            db: Database {
+                user: "admin".into(),
+                password: "hunter2".into(),
+            },
+        };
+        let options = SerializeOptions::default().redact_keys(["password"]);
+        let result = to_string_with_options(&config, &options).unwrap();
+        assert!(!result.contains("hunter2"), "{result}");
+        assert!(result.contains("password \"***\""));
+
+        // Everything else is written as without redaction
+        let plain = to_string(&config).unwrap();
+        assert_eq!(result, plain.replace("hunter2", "\"***\""));
+    }
+
+    #[test]
+    fn test_to_writer_streams_large_sequences() {
+        let value = WithVec {
+            items: (0..100_000).collect(),
+        };
+        let mut sink = CountingWriter::default();
+        to_writer(&mut sink, &value).unwrap();
+        assert_eq!(sink.bytes, to_string(&value).unwrap().len());
+        assert!(sink.writes > 1);
+        assert!(sink.largest_write < 64 * 1024, "{}", sink.largest_write);
+
+        let mut out = Vec::new();
+        to_writer(&mut out, &value).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), to_string(&value).unwrap());
+    }
+
     #[test]
     fn test_special_chars_need_quoting() {
         let value = Simple {
@@ -61,7 +61,11 @@ mod event_assert {
 
     /// Collect all events from a source string.
     fn collect_events(source: &str) -> Vec<CollectedEvent> {
-        let mut parser = StyxParser::new(source);
+        collect_parser_events(StyxParser::new(source))
+    }
+
+    /// Collect all events from a parser.
+    fn collect_parser_events(mut parser: StyxParser<'_>) -> Vec<CollectedEvent> {
         let mut events = Vec::new();
         loop {
             match parser.next_event() {
@@ -199,6 +203,34 @@ mod event_assert {
 
         panic!("Events do not match expected");
     }
+
+    /// Assert that replaying the parsed tree of `source` gives the same events
+    /// as parsing it, with keys and scalars at the same spans.
+    pub fn assert_tree_events_match_impl(source: &str) {
+        let tree = styx_tree::parse(source).expect("source should parse");
+        let expected = collect_events(source);
+        let actual = collect_parser_events(StyxParser::from_value(&tree));
+
+        assert_eq!(format_events(&actual), format_events(&expected));
+        for (actual, expected) in actual.iter().zip(&expected) {
+            let spanned = actual.label.starts_with("FieldKey")
+                || (actual.label.starts_with("Scalar") && actual.label != "Scalar(unit)");
+            if spanned {
+                assert_eq!(
+                    (actual.start, actual.end),
+                    (expected.start, expected.end),
+                    "span of {}",
+                    actual.label
+                );
+            }
+        }
+    }
+}
+
+macro_rules! assert_tree_events_match {
+    ($source:expr) => {
+        event_assert::assert_tree_events_match_impl($source)
+    };
 }
 
 macro_rules! assert_events_eq {
@@ -307,6 +339,34 @@ fn test_optional_absent() {
     assert_eq!(result.optional, None);
 }
 
+#[test]
+fn test_optional_unit_and_null() {
+    let result: WithOptional = from_str("required hello\noptional @").unwrap();
+    assert_eq!(result.optional, None);
+
+    let result: WithOptional = from_str("required hello\noptional 5").unwrap();
+    assert_eq!(result.optional, Some(5));
+
+    let result: WithOptional = from_str("required hello\noptional null").unwrap();
+    assert_eq!(result.optional, None);
+
+    // Only optional targets treat `null` as absence
+    let result: WithOptional = from_str("required null").unwrap();
+    assert_eq!(result.required, "null");
+
+    // The string `null` is written quoted so it reads back as a string
+    #[derive(Facet, Debug, PartialEq)]
+    struct OptionalName {
+        name: Option<String>,
+    }
+    let value = OptionalName {
+        name: Some("null".into()),
+    };
+    let output = to_string(&value).unwrap();
+    assert!(output.contains(r#"name "null""#), "{output}");
+    assert_eq!(from_str::<OptionalName>(&output).unwrap(), value);
+}
+
 #[test]
 fn test_bool_values() {
     #[derive(Facet, Debug, PartialEq)]
@@ -440,6 +500,297 @@ fn test_expr_roundtrip() {
     assert_eq!(original, parsed);
 }
 
+#[test]
+fn test_transparent_newtype_round_trip() {
+    #[derive(Facet, Debug, PartialEq)]
+    #[facet(transparent)]
+    struct Port(u16);
+
+    #[derive(Facet, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: Port,
+    }
+
+    let server = Server {
+        host: "localhost".into(),
+        port: Port(8080),
+    };
+    let styx = to_string(&server).unwrap();
+    assert_eq!(styx.trim(), "host localhost\nport 8080");
+    assert_eq!(from_str::<Server>(&styx).unwrap(), server);
+
+    let styx = to_string(&Port(8080)).unwrap();
+    assert_eq!(styx.trim(), "8080");
+    assert_eq!(from_str_expr::<Port>(&styx).unwrap(), Port(8080));
+}
+
+#[test]
+fn test_tuple_round_trip() {
+    #[derive(Facet, Debug, PartialEq)]
+    struct Pair(i32, i32);
+
+    #[derive(Facet, Debug, PartialEq)]
+    struct Doc {
+        range: Pair,
+        entry: (i32, String, bool),
+    }
+
+    let doc = Doc {
+        range: Pair(1, 10),
+        entry: (7, "seven".into(), true),
+    };
+    let styx = to_string(&doc).unwrap();
+    assert_eq!(styx.trim(), "range (1 10)\nentry (7 seven true)");
+    assert_eq!(from_str::<Doc>(&styx).unwrap(), doc);
+
+    let styx = to_string(&Pair(3, 4)).unwrap();
+    assert_eq!(styx.trim(), "(3 4)");
+    assert_eq!(from_str_expr::<Pair>(&styx).unwrap(), Pair(3, 4));
+}
+
+#[test]
+fn test_tuple_arity_mismatch() {
+    #[derive(Facet, Debug, PartialEq)]
+    struct Pair(i32, i32);
+
+    assert!(from_str_expr::<Pair>("(1)").is_err());
+    assert!(from_str_expr::<Pair>("(1 2 3)").is_err());
+    assert!(from_str_expr::<(i32, String, bool)>("(1 a)").is_err());
+    assert!(from_str_expr::<(i32, String, bool)>("(1 a true false)").is_err());
+}
+
+#[test]
+fn test_ordered_map_preserves_source_order() {
+    use indexmap::IndexMap;
+
+    #[derive(Facet, Debug, PartialEq)]
+    struct Step {
+        run: String,
+    }
+
+    #[derive(Facet, Debug)]
+    struct Pipeline {
+        env: IndexMap<String, String>,
+        steps: IndexMap<String, Step>,
+    }
+
+    let source = "env {ZED 1, ALPHA 2, MIDDLE 3}\n\
+                  steps {\n  test {run \"cargo test\"}\n  build {run \"cargo build\"}\n  lint {run clippy}\n}";
+    let pipeline: Pipeline = from_str(source).unwrap();
+    assert_eq!(
+        pipeline.env.keys().collect::<Vec<_>>(),
+        ["ZED", "ALPHA", "MIDDLE"]
+    );
+    assert_eq!(
+        pipeline.steps.keys().collect::<Vec<_>>(),
+        ["test", "build", "lint"]
+    );
+    assert_eq!(pipeline.steps["lint"].run, "clippy");
+
+    // Order survives a round trip and deserializing from a tree
+    let styx = to_string_with_options(&pipeline, &SerializeOptions::default().preserve_map_order())
+        .unwrap();
+    let again: Pipeline = from_str(&styx).unwrap();
+    assert_eq!(again.env, pipeline.env);
+    assert!(again.steps.keys().eq(pipeline.steps.keys()));
+
+    let tree = styx_tree::parse(source).unwrap();
+    let from_tree: Pipeline = from_value(&tree).unwrap();
+    assert!(from_tree.env.keys().eq(pipeline.env.keys()));
+
+    // A flattened root map keeps order too
+    let root: IndexMap<String, i64> = from_str("c 1\na 2\nb 3").unwrap();
+    assert_eq!(
+        root.into_iter().collect::<Vec<_>>(),
+        [
+            ("c".to_string(), 1),
+            ("a".to_string(), 2),
+            ("b".to_string(), 3),
+        ]
+    );
+}
+
+/// Keys of the object at `path` in a serialized document, in written order.
+fn written_keys(styx: &str, path: &str) -> Vec<String> {
+    let doc = styx_tree::parse(styx).unwrap();
+    let obj = doc.get(path).and_then(|v| v.as_object()).unwrap();
+    obj.entries
+        .iter()
+        .map(|e| e.key.as_str().unwrap_or("@").to_string())
+        .collect()
+}
+
+#[test]
+fn test_map_serialization_sorts_keys() {
+    use std::collections::HashMap;
+
+    #[derive(Facet, Debug)]
+    struct Hosts {
+        name: String,
+        ports: HashMap<String, u16>,
+        zones: HashMap<Option<String>, String>,
+    }
+
+    let hosts = Hosts {
+        name: "cluster".into(),
+        ports: ["web", "db", "cache", "api", "queue", "metrics"]
+            .into_iter()
+            .zip(8000..)
+            .map(|(host, port)| (host.to_string(), port))
+            .collect(),
+        zones: [(Some("west"), "b"), (None, "default"), (Some("east"), "a")]
+            .into_iter()
+            .map(|(k, v)| (k.map(String::from), v.to_string()))
+            .collect(),
+    };
+    let first = to_string(&hosts).unwrap();
+    assert_eq!(first, to_string(&hosts).unwrap());
+    assert_eq!(
+        written_keys(&first, "ports"),
+        ["api", "cache", "db", "metrics", "queue", "web"]
+    );
+    // The unit key sorts before named ones
+    assert_eq!(written_keys(&first, "zones"), ["@", "east", "west"]);
+
+    let compact = to_string_compact(&hosts).unwrap();
+    assert!(
+        compact.find("api").unwrap() < compact.find("web").unwrap(),
+        "{compact}"
+    );
+}
+
+#[test]
+fn test_ordered_map_serializes_in_insertion_order_when_requested() {
+    use indexmap::IndexMap;
+
+    #[derive(Facet, Debug)]
+    struct Pipeline {
+        env: IndexMap<String, i64>,
+    }
+
+    let pipeline = Pipeline {
+        env: [("ZED", 1), ("ALPHA", 2), ("MIDDLE", 3)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    };
+
+    let sorted = to_string(&pipeline).unwrap();
+    assert_eq!(written_keys(&sorted, "env"), ["ALPHA", "MIDDLE", "ZED"]);
+
+    let options = SerializeOptions::default().preserve_map_order();
+    let preserved = to_string_with_options(&pipeline, &options).unwrap();
+    assert_eq!(written_keys(&preserved, "env"), ["ZED", "ALPHA", "MIDDLE"]);
+}
+
+/// Whether `styx` reads as the same document as `expected`, however it's
+/// laid out.
+fn assert_same_document(styx: &str, expected: &str) {
+    let actual = styx_tree::parse(styx).unwrap();
+    let expected = styx_tree::parse(expected).unwrap();
+    assert!(actual.semantic_eq(&expected), "unexpected output:\n{styx}");
+}
+
+#[derive(Facet, Debug, PartialEq)]
+#[facet(rename_all = "lowercase")]
+#[repr(u8)]
+enum Shape {
+    Circle { radius: u32 },
+    Rect { width: u32, height: u32 },
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Drawing {
+    shape: Shape,
+}
+
+#[test]
+fn test_struct_variant_round_trips_as_tag() {
+    let drawing = Drawing {
+        shape: Shape::Rect {
+            width: 2,
+            height: 3,
+        },
+    };
+    let styx = to_string(&drawing).unwrap();
+    assert_same_document(&styx, "shape @rect{width 2, height 3}");
+    assert_eq!(from_str::<Drawing>(&styx).unwrap(), drawing);
+}
+
+#[test]
+fn test_struct_variant_round_trips_externally_tagged() {
+    let drawing = Drawing {
+        shape: Shape::Circle { radius: 5 },
+    };
+    let options = SerializeOptions::default().enum_repr(EnumRepr::External);
+    let styx = to_string_with_options(&drawing, &options).unwrap();
+    assert_same_document(&styx, "shape {circle {radius 5}}");
+    assert_eq!(from_str::<Drawing>(&styx).unwrap(), drawing);
+
+    // Both shapes read the same
+    assert_eq!(
+        from_str::<Drawing>("shape @circle{radius 5}").unwrap(),
+        from_str::<Drawing>("shape {circle {radius 5}}").unwrap()
+    );
+}
+
+#[test]
+fn test_as_typed_matches_from_str() {
+    #[derive(Facet, Debug, PartialEq)]
+    #[facet(rename_all = "lowercase")]
+    #[repr(u8)]
+    enum Mode {
+        Careful { retries: u32 },
+    }
+
+    #[derive(Facet, Debug, PartialEq)]
+    struct Config {
+        name: String,
+        port: u16,
+        tags: Vec<String>,
+        nested: Nested,
+        mode: Mode,
+        timeout: Option<u32>,
+        retries: Option<u32>,
+    }
+
+    let source = r#"@schema schema.styx
+
+/// The app name
+name "my app"
+port 8080
+tags (web api)
+nested {inner {name hello, value 42}}
+mode @careful{retries 3}
+timeout @
+retries 5"#;
+
+    let doc = styx_tree::parse(source).unwrap();
+    let typed: Config = doc.as_typed().unwrap();
+    assert_eq!(typed, from_str::<Config>(source).unwrap());
+
+    let inner: Simple = doc.get("nested.inner").unwrap().as_typed().unwrap();
+    assert_eq!(inner, from_str_expr("{name hello, value 42}").unwrap());
+}
+
+#[test]
+fn test_as_typed_error_spans() {
+    #[derive(Facet, Debug, PartialEq)]
+    struct IntOnly {
+        value: i32,
+    }
+
+    let annotated = r#"
+value "hello"
+^^^^^ Reflect
+"#;
+    let source = source_without_annotations(annotated);
+    let doc = styx_tree::parse(&source).unwrap();
+    let err = doc.as_typed::<IntOnly>().unwrap_err();
+    assert_deserialize_errors(annotated, &err);
+}
+
 // =========================================================================
 // Documented<T> tests
 // =========================================================================
@@ -488,6 +839,57 @@ fn test_documented_map() {
     assert_eq!(mapped.doc(), Some(&["The answer".to_string()][..]));
 }
 
+#[test]
+fn test_from_str_doc_comment_round_trip() {
+    #[derive(Facet, Debug)]
+    struct Server {
+        host: Documented<String>,
+        port: Documented<u16>,
+    }
+
+    #[derive(Facet, Debug)]
+    struct Config {
+        name: Documented<String>,
+        server: Documented<Server>,
+    }
+
+    let source = r#"/// The application name
+name myapp
+
+/// Server configuration
+server {
+    /// The hostname
+    host localhost
+
+    /// The port
+    port 8080
+}"#;
+
+    let config: Config = from_str(source).unwrap();
+    assert_eq!(*config.name, "myapp");
+    assert_eq!(
+        config.name.doc(),
+        Some(&["The application name".to_string()][..])
+    );
+    assert_eq!(
+        config.server.doc(),
+        Some(&["Server configuration".to_string()][..])
+    );
+    assert_eq!(
+        config.server.host.doc(),
+        Some(&["The hostname".to_string()][..])
+    );
+    assert_eq!(*config.server.port, 8080);
+    assert_eq!(
+        config.server.port.doc(),
+        Some(&["The port".to_string()][..])
+    );
+
+    // Re-serializing writes the captured comments back unchanged
+    let serialized = to_string(&config).unwrap();
+    assert_eq!(serialized.trim(), source);
+}
+
 #[test]
 fn test_unit_field_followed_by_another_field() {
     // When a field has unit value (no explicit value), followed by
@@ -660,6 +1062,32 @@ fn test_explicit_root_object_only_one_structstart() {
     );
 }
 
+#[test]
+fn test_tree_events_match_parser() {
+    assert_tree_events_match!("name hello\nport 8080");
+    assert_tree_events_match!("{key val}");
+    assert_tree_events_match!("outer { inner value, list (1 two \"three\") }");
+    assert_tree_events_match!("@schema {id crate:app@1, cli app}\n\nname app");
+    assert_tree_events_match!("/// Docs\n/// more docs\nport 8080\nenabled\nnothing @");
+    assert_tree_events_match!("mode @careful{retries 3}\nkind @string\nlist @seq(@int)");
+    assert_tree_events_match!("@ @object{a @string}\n@tag\"name\" value\n@other value");
+    assert_tree_events_match!("server.host localhost\nraw r#\"a \"quoted\" b\"#");
+}
+
+#[test]
+fn test_from_value_borrowed() {
+    #[derive(Facet, Debug, PartialEq)]
+    struct Person<'a> {
+        name: &'a str,
+        age: u32,
+    }
+
+    let source = "name Alice\nage 30";
+    let doc = styx_tree::parse(source).unwrap();
+    let person: Person = from_value_borrowed(&doc).unwrap();
+    assert_eq!(person, from_str_borrowed::<Person>(source).unwrap());
+}
+
 #[test]
 fn test_bare_true_is_string() {
     // In styx, all bare scalars are strings. "true" without quotes is the string "true",
@@ -0,0 +1,165 @@
+//! Replaying a parsed [`Value`] as parser events.
+//!
+//! This lets [`StyxParser`](crate::StyxParser) deserialize a tree that was
+//! already built, without printing it back to text and parsing it again.
+
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use styx_parse::{Event, EventKind, ScalarKind, Span};
+use styx_tree::{Object, Payload, Value};
+
+/// The events `styx_parse::Parser` would emit for the source of a tree.
+///
+/// Spans are the ones recorded in the tree, so errors point into the original
+/// source. Values built in code have no spans and report an empty one.
+#[derive(Clone)]
+pub struct TreeEvents<'de> {
+    events: Rc<[TreeEvent<'de>]>,
+    next: usize,
+    /// Whether the last unit event stood for an `@` in the source.
+    unit_token: bool,
+}
+
+struct TreeEvent<'de> {
+    event: Event<'de>,
+    unit_token: bool,
+}
+
+impl<'de> TreeEvents<'de> {
+    /// Events for a document whose root object is `root`.
+    pub fn document(root: &'de Object) -> Self {
+        let mut builder = Builder::default();
+        builder.push(Span::new(0, 0), EventKind::DocumentStart);
+        builder.object(root);
+        builder.push(Span::new(0, 0), EventKind::DocumentEnd);
+        builder.finish()
+    }
+
+    /// Events for a single value, as the parser emits them in expression mode.
+    pub fn expr(value: &'de Value) -> Self {
+        let mut builder = Builder::default();
+        builder.value(value, None);
+        builder.finish()
+    }
+
+    pub fn next_event(&mut self) -> Option<Event<'de>> {
+        let next = self.events.get(self.next)?;
+        self.next += 1;
+        self.unit_token = next.unit_token;
+        Some(next.event.clone())
+    }
+
+    /// Whether the last unit event came from an explicit `@`, rather than a
+    /// key written without a value.
+    pub fn unit_is_token(&self) -> bool {
+        self.unit_token
+    }
+}
+
+#[derive(Default)]
+struct Builder<'de> {
+    events: Vec<TreeEvent<'de>>,
+}
+
+impl<'de> Builder<'de> {
+    fn finish(self) -> TreeEvents<'de> {
+        TreeEvents {
+            events: self.events.into(),
+            next: 0,
+            unit_token: false,
+        }
+    }
+
+    fn push(&mut self, span: Span, kind: EventKind<'de>) {
+        self.events.push(TreeEvent {
+            event: Event { span, kind },
+            unit_token: false,
+        });
+    }
+
+    /// Push the events for `value`; `key_span` is set for entry values.
+    fn value(&mut self, value: &'de Value, key_span: Option<Span>) {
+        let span = span_or_empty(value.span);
+        if let Some(tag) = &value.tag {
+            self.push(
+                span_or_empty(tag.span),
+                EventKind::TagStart { name: &tag.name },
+            );
+        }
+
+        match &value.payload {
+            // A tag without payload ends with its TagEnd
+            None if value.tag.is_some() => {}
+            None => {
+                // A key without a value gets a unit spanning the key
+                let implicit = value.span.is_none() || value.span == key_span;
+                self.events.push(TreeEvent {
+                    event: Event {
+                        span,
+                        kind: EventKind::Unit,
+                    },
+                    unit_token: !implicit,
+                });
+            }
+            Some(Payload::Scalar(scalar)) => self.push(
+                span_or_empty(scalar.span),
+                EventKind::Scalar {
+                    value: Cow::Borrowed(&*scalar.text),
+                    kind: scalar.kind,
+                },
+            ),
+            Some(Payload::Sequence(seq)) => {
+                let span = span_or_empty(seq.span);
+                self.push(span, EventKind::SequenceStart);
+                for item in &seq.items {
+                    self.value(item, None);
+                }
+                self.push(span, EventKind::SequenceEnd);
+            }
+            Some(Payload::Object(obj)) => self.object(obj),
+        }
+
+        if let Some(tag) = &value.tag {
+            self.push(span_or_empty(tag.span), EventKind::TagEnd);
+        }
+    }
+
+    fn object(&mut self, obj: &'de Object) {
+        let span = span_or_empty(obj.span);
+        self.push(span, EventKind::ObjectStart);
+        for entry in &obj.entries {
+            let key_span = span_or_empty(entry.key.span);
+            if let Some(doc) = &entry.doc_comment {
+                self.push(
+                    key_span,
+                    EventKind::DocComment {
+                        lines: doc.lines().collect(),
+                    },
+                );
+            }
+            self.push(key_span, EventKind::EntryStart);
+
+            let (payload, kind) = match &entry.key.payload {
+                Some(Payload::Scalar(scalar)) => (Some(Cow::Borrowed(&*scalar.text)), scalar.kind),
+                _ => (None, ScalarKind::Bare),
+            };
+            self.push(
+                key_span,
+                EventKind::Key {
+                    tag: entry.key.tag.as_ref().map(|tag| tag.name.as_str()),
+                    payload,
+                    kind,
+                },
+            );
+
+            self.value(&entry.value, entry.key.span);
+            self.push(span_or_empty(entry.value.span), EventKind::EntryEnd);
+        }
+        self.push(span, EventKind::ObjectEnd);
+    }
+}
+
+fn span_or_empty(span: Option<Span>) -> Span {
+    span.unwrap_or(Span::new(0, 0))
+}
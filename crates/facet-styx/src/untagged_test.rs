@@ -0,0 +1,100 @@
+//! Tests for `#[facet(untagged)]` enums told apart by their fields.
+//!
+//! An untagged enum is written as the payload of one of its variants, with
+//! no `@variant` tag. The variants are tried in declaration order and the
+//! first one that deserializes wins, so when several variants fit, the one
+//! declared first is picked. The generated schema is a `@union` of the
+//! variants in the same order.
+
+use facet::Facet;
+use facet_testhelpers::test;
+
+use crate::{ValidationErrorKind, from_str, schema_file_from_type, schema_from_type, validate};
+
+#[derive(Facet, Debug, PartialEq)]
+#[facet(untagged)]
+#[repr(u8)]
+enum Source {
+    /// Fetched over the network
+    Remote { url: String, timeout: Option<u32> },
+    /// Read from disk
+    Local { path: String },
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Doc {
+    source: Source,
+}
+
+/// Both variants fit `{port 80}`.
+#[derive(Facet, Debug, PartialEq)]
+#[facet(untagged)]
+#[repr(u8)]
+enum Listen {
+    Port { port: u16 },
+    Address { port: u16, host: Option<String> },
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Server {
+    listen: Listen,
+}
+
+#[test]
+fn test_variant_selected_by_its_fields() {
+    let doc: Doc = from_str("source {url https://example.com, timeout 30}").unwrap();
+    assert_eq!(
+        doc.source,
+        Source::Remote {
+            url: "https://example.com".into(),
+            timeout: Some(30),
+        }
+    );
+
+    let doc: Doc = from_str("source {path ./config}").unwrap();
+    assert_eq!(
+        doc.source,
+        Source::Local {
+            path: "./config".into()
+        }
+    );
+}
+
+#[test]
+fn test_ambiguous_input_picks_first_declared_variant() {
+    let server: Server = from_str("listen {port 80}").unwrap();
+    assert_eq!(server.listen, Listen::Port { port: 80 });
+}
+
+#[test]
+fn test_no_matching_variant_is_an_error() {
+    assert!(from_str::<Doc>("source {name config}").is_err());
+}
+
+#[test]
+fn test_schema_is_union_of_variants_in_order() {
+    let schema = schema_from_type::<Doc>();
+    let union = schema
+        .find("@union(")
+        .expect("untagged enum becomes a union");
+    let remote = schema.find("url").unwrap();
+    let local = schema.find("path").unwrap();
+    assert!(union < remote && remote < local, "{schema}");
+    assert!(!schema.contains("@enum"), "{schema}");
+
+    let schema_file = schema_file_from_type::<Doc>();
+    for source in ["source {url https://example.com}", "source {path ./config}"] {
+        let result = validate(&styx_tree::parse(source).unwrap(), &schema_file);
+        assert!(result.is_valid(), "{source}: {:?}", result.errors);
+    }
+
+    let result = validate(
+        &styx_tree::parse("source {name config}").unwrap(),
+        &schema_file,
+    );
+    assert_eq!(result.errors.len(), 1, "{:?}", result.errors);
+    let ValidationErrorKind::UnionMismatch { tried } = &result.errors[0].kind else {
+        panic!("expected a union mismatch: {:?}", result.errors[0]);
+    };
+    assert_eq!(tried.len(), 2);
+}
@@ -0,0 +1,143 @@
+//! Deserialization of documents checked against a schema first.
+
+use std::fmt;
+
+use facet_format::DeserializeError;
+use styx_tree::BuildError;
+
+use crate::schema_error::ValidationResult;
+use crate::schema_types::SchemaFile;
+
+/// Why [`from_str_validated`] failed.
+#[derive(Debug)]
+pub enum ValidatedError {
+    /// The document is not valid Styx.
+    Parse(BuildError),
+    /// The document does not match the schema.
+    Invalid(ValidationResult),
+    /// The document matches the schema but does not fit the target type.
+    Deserialize(DeserializeError),
+}
+
+impl fmt::Display for ValidatedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidatedError::Parse(error) => write!(f, "cannot parse document: {error}"),
+            ValidatedError::Invalid(result) => {
+                write!(f, "document does not match its schema")?;
+                for error in &result.errors {
+                    write!(f, "\n  {error}")?;
+                }
+                Ok(())
+            }
+            ValidatedError::Deserialize(error) => write!(f, "invalid document: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ValidatedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ValidatedError::Parse(error) => Some(error),
+            ValidatedError::Invalid(_) | ValidatedError::Deserialize(_) => None,
+        }
+    }
+}
+
+/// Deserialize a value from a Styx string after validating it against
+/// `schema`.
+///
+/// The document is parsed once, validated, and only deserialized if it has
+/// no validation errors; warnings don't stop it. An `@schema` declaration in
+/// the document is ignored, so `schema` always decides.
+///
+/// # Example
+///
+/// ```
+/// use facet::Facet;
+/// use facet_styx::{SchemaFile, ValidatedError, from_str_validated};
+///
+/// #[derive(Facet, Debug)]
+/// struct Server {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// let schema: SchemaFile = facet_styx::from_str(
+///     "meta {id example}\nschema {@ @object{host @string, port @int{min 1024}}}",
+/// )
+/// .unwrap();
+///
+/// let server: Server = from_str_validated("host localhost\nport 8080", &schema).unwrap();
+/// assert_eq!(server.port, 8080);
+///
+/// let error = from_str_validated::<Server>("host localhost\nport 80", &schema).unwrap_err();
+/// assert!(matches!(error, ValidatedError::Invalid(_)));
+/// ```
+pub fn from_str_validated<T>(input: &str, schema: &SchemaFile) -> Result<T, ValidatedError>
+where
+    T: facet_core::Facet<'static>,
+{
+    let mut doc = styx_tree::parse(input).map_err(ValidatedError::Parse)?;
+    if let Some(obj) = doc.as_object_mut() {
+        obj.entries.retain(|entry| !entry.key.is_schema_tag());
+    }
+
+    let result = crate::validate(&doc, schema);
+    if !result.is_valid() {
+        return Err(ValidatedError::Invalid(result));
+    }
+    crate::from_value(&doc).map_err(ValidatedError::Deserialize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use facet::Facet;
+    use facet_testhelpers::test;
+
+    #[derive(Facet, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    fn schema() -> SchemaFile {
+        crate::from_str("meta {id test}\nschema {@ @object{host @string, port @int}}").unwrap()
+    }
+
+    #[test]
+    fn test_valid_document_deserializes() {
+        let server: Server =
+            from_str_validated("@schema {id test}\nhost localhost\nport 8080", &schema()).unwrap();
+        assert_eq!(
+            server,
+            Server {
+                host: "localhost".into(),
+                port: 8080
+            }
+        );
+    }
+
+    #[test]
+    fn test_schema_invalid_document_is_a_validation_error() {
+        let error =
+            from_str_validated::<Server>("host localhost\nport eighty", &schema()).unwrap_err();
+        let ValidatedError::Invalid(result) = &error else {
+            panic!("expected a validation error, got {error:?}");
+        };
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].path, "port");
+    }
+
+    #[test]
+    fn test_type_mismatch_is_a_deserialize_error() {
+        // Valid for the schema, but too large for a u16
+        let error =
+            from_str_validated::<Server>("host localhost\nport 70000", &schema()).unwrap_err();
+        assert!(
+            matches!(error, ValidatedError::Deserialize(_)),
+            "expected a deserialize error, got {error:?}"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use facet::Facet;
+use facet_styx::{Config, ConfigError, ConfigLayer, SchemaFile};
+use styx_testhelpers::TempDir;
+
+#[derive(Facet, Debug, PartialEq)]
+struct Settings {
+    name: String,
+    server: Server,
+}
+
+#[derive(Facet, Debug, PartialEq)]
+struct Server {
+    host: String,
+    port: u16,
+    max_connections: u32,
+}
+
+const SCHEMA: &str = r#"meta {id settings}
+schema {
+    @ @object{
+        name @string
+        server @object{
+            host @string
+            port @int
+            max_connections @int
+        }
+    }
+}"#;
+
+fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn base_override_and_env_layers() {
+    let dir = TempDir::new("config-layers");
+    let base = dir.write(
+        "config.styx",
+        "name app\nserver {host localhost, port 8080, max_connections 16}",
+    );
+    let local = dir.write("config.local.styx", "server {port 9090}");
+
+    let settings: Settings = Config::new(&base)
+        .local_override(&local)
+        .env_vars(
+            "APP",
+            env(&[
+                ("APP_SERVER__MAX_CONNECTIONS", "64"),
+                ("OTHER_NAME", "ignored"),
+            ]),
+        )
+        .schema(facet_styx::from_str::<SchemaFile>(SCHEMA).unwrap())
+        .load()
+        .unwrap();
+
+    assert_eq!(
+        settings,
+        Settings {
+            name: "app".into(),
+            server: Server {
+                host: "localhost".into(),
+                port: 9090,
+                max_connections: 64,
+            },
+        }
+    );
+
+    // A missing local override is skipped
+    let settings: Settings = Config::new(&base)
+        .local_override(dir.path().join("missing.styx"))
+        .load()
+        .unwrap();
+    assert_eq!(settings.server.port, 8080);
+}
+
+#[test]
+fn errors_name_the_failing_layer() {
+    let dir = TempDir::new("config-errors");
+    let base = dir.write(
+        "config.styx",
+        "name app\nserver {host localhost, port 8080, max_connections 16}",
+    );
+    let broken = dir.write("config.local.styx", "server {port 9090");
+
+    let error = Config::new(&base)
+        .local_override(&broken)
+        .load_value()
+        .unwrap_err();
+    assert!(
+        matches!(&error, ConfigError::Parse { layer: ConfigLayer::LocalOverride(path), .. } if *path == broken),
+        "{error}"
+    );
+
+    let error = Config::new(dir.path().join("missing.styx"))
+        .load_value()
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        ConfigError::Read {
+            layer: ConfigLayer::Base(_),
+            ..
+        }
+    ));
+
+    let error = Config::new(&base)
+        .env_vars("APP", env(&[("APP_SERVER__PORT", "eighty")]))
+        .schema(facet_styx::from_str::<SchemaFile>(SCHEMA).unwrap())
+        .load_value()
+        .unwrap_err();
+    let ConfigError::Invalid(errors) = error else {
+        panic!("expected a validation error, got {error}");
+    };
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert_eq!(errors[0].0, ConfigLayer::Env("APP".into()));
+    assert!(errors[0].1.path.ends_with("port"));
+
+    let local = dir.write("config.styx.local", "server {host 127.0.0.1, port eighty}");
+    let error = Config::new(&base)
+        .local_override(&local)
+        .env_vars("APP", env(&[("APP_SERVER__MAX_CONNECTIONS", "many")]))
+        .schema(facet_styx::from_str::<SchemaFile>(SCHEMA).unwrap())
+        .load_value()
+        .unwrap_err();
+    let ConfigError::Invalid(errors) = &error else {
+        panic!("expected a validation error, got {error}");
+    };
+    let mut layers: Vec<_> = errors
+        .iter()
+        .map(|(layer, error)| (error.path.as_str(), layer.clone()))
+        .collect();
+    layers.sort_by_key(|(path, _)| *path);
+    assert_eq!(
+        layers,
+        [
+            ("server.max_connections", ConfigLayer::Env("APP".into())),
+            ("server.port", ConfigLayer::LocalOverride(local.clone())),
+        ]
+    );
+    assert!(
+        error
+            .to_string()
+            .contains(&format!("override file '{}'", local.display())),
+        "{error}"
+    );
+}
@@ -361,6 +361,14 @@ impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
             }
             return visitor.visit_none();
         }
+        if let Some(token) = self.peek_token()
+            && token.kind == TokenKind::BareScalar
+            && token.text == "null"
+        {
+            // A bare `null` is absence only when the target is optional
+            self.next_token();
+            return visitor.visit_none();
+        }
         visitor.visit_some(self)
     }
 
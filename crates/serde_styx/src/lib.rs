@@ -136,10 +136,45 @@ where
     Ok(serializer.finish())
 }
 
+/// Serialize a value as Styx into an [`io::Write`](std::io::Write).
+///
+/// Output is written as the value is serialized rather than built up as one
+/// string first, so large sequences and maps use little memory.
+///
+/// # Example
+///
+/// ```
+/// use serde_styx::to_writer;
+///
+/// let mut out = Vec::new();
+/// to_writer(&mut out, &vec![1, 2, 3]).unwrap();
+/// assert_eq!(out, b"(1 2 3)");
+/// ```
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: std::io::Write,
+    T: serde::ser::Serialize + ?Sized,
+{
+    to_writer_with_options(writer, value, &FormatOptions::default())
+}
+
+/// Serialize a value as Styx into an [`io::Write`](std::io::Write) with custom options.
+pub fn to_writer_with_options<W, T>(writer: W, value: &T, options: &FormatOptions) -> Result<()>
+where
+    W: std::io::Write,
+    T: serde::ser::Serialize + ?Sized,
+{
+    let mut serializer = Serializer::from_writer(writer, options.clone());
+    value.serialize(&mut serializer)?;
+    serializer.into_inner()?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde::{Deserialize, Serialize};
+    use styx_testhelpers::CountingWriter;
 
     #[derive(Serialize, Deserialize, Debug, PartialEq)]
     struct Simple {
@@ -196,6 +231,37 @@ value 123"#;
         assert_eq!(result.optional, None);
     }
 
+    #[test]
+    fn test_deserialize_optional_unit_and_null() {
+        let result: WithOptional = from_str("required hello\noptional @").unwrap();
+        assert_eq!(result.optional, None);
+
+        let result: WithOptional = from_str("required hello\noptional 5").unwrap();
+        assert_eq!(result.optional, Some(5));
+
+        let result: WithOptional = from_str("required hello\noptional null").unwrap();
+        assert_eq!(result.optional, None);
+
+        // Only optional targets treat `null` as absence
+        let result: Simple = from_str("name null\nvalue 1").unwrap();
+        assert_eq!(result.name, "null");
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct OptionalName {
+            name: Option<String>,
+        }
+        let result: OptionalName = from_str(r#"name "null""#).unwrap();
+        assert_eq!(result.name, Some("null".into()));
+
+        // The string `null` is written quoted so it reads back as a string
+        let value = OptionalName {
+            name: Some("null".into()),
+        };
+        let output = to_string(&value).unwrap();
+        assert!(output.contains(r#"name "null""#), "{output}");
+        assert_eq!(from_str::<OptionalName>(&output).unwrap().name, value.name);
+    }
+
     #[test]
     fn test_deserialize_bool_values() {
         #[derive(Deserialize, Debug, PartialEq)]
@@ -270,6 +336,29 @@ value 123"#;
         assert!(result.contains("name \"hello world\""));
     }
 
+    #[test]
+    fn test_serialize_with_custom_options() {
+        let value = Simple {
+            name: "line one\nline two".into(),
+            value: 42,
+        };
+        let options = FormatOptions::new().heredoc_line_threshold(1);
+        let result = to_string_with_options(&value, &options).unwrap();
+        assert_eq!(
+            result,
+            "name <<TEXT\nline one\nline two\nTEXT\n\nvalue 42\n"
+        );
+
+        let value = Simple {
+            name: "true".into(),
+            value: 42,
+        };
+        let options = FormatOptions::new().always_quote_strings();
+        let result = to_string_with_options(&value, &options).unwrap();
+        assert_eq!(result, "name \"true\"\n\nvalue 42\n");
+        assert_eq!(from_str::<Simple>(&result).unwrap(), value);
+    }
+
     #[test]
     fn test_serialize_optional_none() {
         let value = WithOptional {
@@ -332,6 +421,56 @@ value 123"#;
         assert_eq!(original, parsed);
     }
 
+    #[test]
+    fn test_to_writer_streams_large_sequences() {
+        let items = WithVec {
+            items: (0..100_000).collect(),
+        };
+        let records: Vec<Simple> = (0..100_000)
+            .map(|i| Simple {
+                name: format!("record_{i}"),
+                value: i,
+            })
+            .collect();
+
+        let mut sink = CountingWriter::default();
+        to_writer(&mut sink, &items).unwrap();
+        assert_eq!(sink.bytes, to_string(&items).unwrap().len());
+        assert!(sink.writes > 1);
+        assert!(sink.largest_write < 64 * 1024, "{}", sink.largest_write);
+
+        let mut sink = CountingWriter::default();
+        to_writer(&mut sink, &records).unwrap();
+        assert_eq!(sink.bytes, to_string(&records).unwrap().len());
+        assert!(sink.largest_write < 64 * 1024, "{}", sink.largest_write);
+
+        let mut out = Vec::new();
+        to_writer(&mut out, &records).unwrap();
+        let parsed: Vec<Simple> = from_str(std::str::from_utf8(&out).unwrap()).unwrap();
+        assert_eq!(parsed, records);
+    }
+
+    #[test]
+    fn test_to_writer_streams_sequences_in_nested_structs() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Outer {
+            inner: WithVec,
+        }
+
+        let outer = Outer {
+            inner: WithVec {
+                items: (0..100_000).collect(),
+            },
+        };
+        let mut sink = CountingWriter::default();
+        to_writer(&mut sink, &outer).unwrap();
+        assert_eq!(sink.bytes, to_string(&outer).unwrap().len());
+        assert!(sink.largest_write < 64 * 1024, "{}", sink.largest_write);
+
+        let parsed: Outer = from_str(&to_string(&outer).unwrap()).unwrap();
+        assert_eq!(parsed, outer);
+    }
+
     #[test]
     fn test_roundtrip_quoted_string() {
         let original = Simple {
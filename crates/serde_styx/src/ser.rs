@@ -1,14 +1,20 @@
 //! Serde serializer for Styx.
 
+use std::io;
+
 use serde::ser::{self, Serialize};
 use styx_format::{FormatOptions, StyxWriter};
 
 use crate::error::{Error, Result};
 
 /// Styx serializer implementing serde::Serializer.
-pub struct Serializer {
+///
+/// Output is written to `W` as elements and fields are serialized, so large
+/// collections don't have to be held in memory as a whole.
+pub struct Serializer<W = Vec<u8>> {
     writer: StyxWriter,
     at_root: bool,
+    sink: W,
 }
 
 impl Serializer {
@@ -19,15 +25,41 @@ impl Serializer {
 
     /// Create a new serializer with the given options.
     pub fn with_options(options: FormatOptions) -> Self {
+        Self::from_writer(Vec::new(), options)
+    }
+
+    /// Consume the serializer and return the output as a string.
+    pub fn finish(self) -> String {
+        let mut out = self.sink;
+        out.extend(self.writer.finish());
+        String::from_utf8(out).expect("Styx output should always be valid UTF-8")
+    }
+}
+
+impl<W: io::Write> Serializer<W> {
+    /// Create a serializer writing to `sink` with the given options.
+    pub fn from_writer(sink: W, options: FormatOptions) -> Self {
         Self {
             writer: StyxWriter::with_options(options),
             at_root: true,
+            sink,
         }
     }
 
-    /// Consume the serializer and return the output as a string.
-    pub fn finish(self) -> String {
-        self.writer.finish_string()
+    /// Write the rest of the output and return the sink.
+    pub fn into_inner(mut self) -> Result<W> {
+        self.sink
+            .write_all(&self.writer.finish())
+            .and_then(|()| self.sink.flush())
+            .map_err(Error::custom)?;
+        Ok(self.sink)
+    }
+
+    /// Hand settled output to the sink once enough of it is buffered.
+    fn flush_settled(&mut self) -> Result<()> {
+        self.writer
+            .flush_settled_if_full(&mut self.sink)
+            .map_err(Error::custom)
     }
 }
 
@@ -37,17 +69,17 @@ impl Default for Serializer {
     }
 }
 
-impl<'a> ser::Serializer for &'a mut Serializer {
+impl<'a, W: io::Write> ser::Serializer for &'a mut Serializer<W> {
     type Ok = ();
     type Error = Error;
 
-    type SerializeSeq = SeqSerializer<'a>;
-    type SerializeTuple = SeqSerializer<'a>;
-    type SerializeTupleStruct = SeqSerializer<'a>;
-    type SerializeTupleVariant = SeqSerializer<'a>;
-    type SerializeMap = MapSerializer<'a>;
-    type SerializeStruct = StructSerializer<'a>;
-    type SerializeStructVariant = StructSerializer<'a>;
+    type SerializeSeq = SeqSerializer<'a, W>;
+    type SerializeTuple = SeqSerializer<'a, W>;
+    type SerializeTupleStruct = SeqSerializer<'a, W>;
+    type SerializeTupleVariant = SeqSerializer<'a, W>;
+    type SerializeMap = MapSerializer<'a, W>;
+    type SerializeStruct = StructSerializer<'a, W>;
+    type SerializeStructVariant = StructSerializer<'a, W>;
 
     fn serialize_bool(self, v: bool) -> Result<()> {
         self.at_root = false;
@@ -243,16 +275,17 @@ impl<'a> ser::Serializer for &'a mut Serializer {
 }
 
 /// Serializer for sequences.
-pub struct SeqSerializer<'a> {
-    ser: &'a mut Serializer,
+pub struct SeqSerializer<'a, W = Vec<u8>> {
+    ser: &'a mut Serializer<W>,
 }
 
-impl<'a> ser::SerializeSeq for SeqSerializer<'a> {
+impl<'a, W: io::Write> ser::SerializeSeq for SeqSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
     fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
-        value.serialize(&mut *self.ser)
+        value.serialize(&mut *self.ser)?;
+        self.ser.flush_settled()
     }
 
     fn end(self) -> Result<()> {
@@ -260,12 +293,13 @@ impl<'a> ser::SerializeSeq for SeqSerializer<'a> {
     }
 }
 
-impl<'a> ser::SerializeTuple for SeqSerializer<'a> {
+impl<'a, W: io::Write> ser::SerializeTuple for SeqSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
     fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
-        value.serialize(&mut *self.ser)
+        value.serialize(&mut *self.ser)?;
+        self.ser.flush_settled()
     }
 
     fn end(self) -> Result<()> {
@@ -273,12 +307,13 @@ impl<'a> ser::SerializeTuple for SeqSerializer<'a> {
     }
 }
 
-impl<'a> ser::SerializeTupleStruct for SeqSerializer<'a> {
+impl<'a, W: io::Write> ser::SerializeTupleStruct for SeqSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
     fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
-        value.serialize(&mut *self.ser)
+        value.serialize(&mut *self.ser)?;
+        self.ser.flush_settled()
     }
 
     fn end(self) -> Result<()> {
@@ -286,12 +321,13 @@ impl<'a> ser::SerializeTupleStruct for SeqSerializer<'a> {
     }
 }
 
-impl<'a> ser::SerializeTupleVariant for SeqSerializer<'a> {
+impl<'a, W: io::Write> ser::SerializeTupleVariant for SeqSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
     fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
-        value.serialize(&mut *self.ser)
+        value.serialize(&mut *self.ser)?;
+        self.ser.flush_settled()
     }
 
     fn end(self) -> Result<()> {
@@ -300,11 +336,11 @@ impl<'a> ser::SerializeTupleVariant for SeqSerializer<'a> {
 }
 
 /// Serializer for maps.
-pub struct MapSerializer<'a> {
-    ser: &'a mut Serializer,
+pub struct MapSerializer<'a, W = Vec<u8>> {
+    ser: &'a mut Serializer<W>,
 }
 
-impl<'a> ser::SerializeMap for MapSerializer<'a> {
+impl<'a, W: io::Write> ser::SerializeMap for MapSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -315,7 +351,8 @@ impl<'a> ser::SerializeMap for MapSerializer<'a> {
     }
 
     fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
-        value.serialize(&mut *self.ser)
+        value.serialize(&mut *self.ser)?;
+        self.ser.flush_settled()
     }
 
     fn end(self) -> Result<()> {
@@ -324,11 +361,11 @@ impl<'a> ser::SerializeMap for MapSerializer<'a> {
 }
 
 /// Serializer for structs.
-pub struct StructSerializer<'a> {
-    ser: &'a mut Serializer,
+pub struct StructSerializer<'a, W = Vec<u8>> {
+    ser: &'a mut Serializer<W>,
 }
 
-impl<'a> ser::SerializeStruct for StructSerializer<'a> {
+impl<'a, W: io::Write> ser::SerializeStruct for StructSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -338,7 +375,8 @@ impl<'a> ser::SerializeStruct for StructSerializer<'a> {
         value: &T,
     ) -> Result<()> {
         self.ser.writer.field_key(key).map_err(Error::new)?;
-        value.serialize(&mut *self.ser)
+        value.serialize(&mut *self.ser)?;
+        self.ser.flush_settled()
     }
 
     fn end(self) -> Result<()> {
@@ -346,7 +384,7 @@ impl<'a> ser::SerializeStruct for StructSerializer<'a> {
     }
 }
 
-impl<'a> ser::SerializeStructVariant for StructSerializer<'a> {
+impl<'a, W: io::Write> ser::SerializeStructVariant for StructSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -356,7 +394,8 @@ impl<'a> ser::SerializeStructVariant for StructSerializer<'a> {
         value: &T,
     ) -> Result<()> {
         self.ser.writer.field_key(key).map_err(Error::new)?;
-        value.serialize(&mut *self.ser)
+        value.serialize(&mut *self.ser)?;
+        self.ser.flush_settled()
     }
 
     fn end(self) -> Result<()> {
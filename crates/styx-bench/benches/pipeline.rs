@@ -0,0 +1,68 @@
+//! Benchmarks for each stage of the Styx pipeline.
+
+use criterion::{BenchmarkId, Criterion, Throughput, black_box, criterion_group, criterion_main};
+use styx_bench::corpus;
+use styx_format::{FormatOptions, format_value};
+use styx_parse::{Lexeme, Lexer, Parser};
+
+fn lex(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lex");
+    for (name, source) in corpus() {
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &source, |b, source| {
+            b.iter(|| {
+                let mut lexer = Lexer::new(black_box(source));
+                let mut count = 0usize;
+                while !matches!(lexer.next_lexeme(), Lexeme::Eof) {
+                    count += 1;
+                }
+                count
+            })
+        });
+    }
+    group.finish();
+}
+
+fn parse_events(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_events");
+    for (name, source) in corpus() {
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &source, |b, source| {
+            b.iter(|| {
+                let mut parser = Parser::new(black_box(source));
+                let mut count = 0usize;
+                while parser.next_event().is_some() {
+                    count += 1;
+                }
+                count
+            })
+        });
+    }
+    group.finish();
+}
+
+fn build_tree(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_tree");
+    for (name, source) in corpus() {
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &source, |b, source| {
+            b.iter(|| styx_tree::parse(black_box(source)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn serialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize");
+    for (name, source) in corpus() {
+        let tree = styx_tree::parse(&source).unwrap();
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &tree, |b, tree| {
+            b.iter(|| format_value(black_box(tree), FormatOptions::default()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lex, parse_events, build_tree, serialize);
+criterion_main!(benches);
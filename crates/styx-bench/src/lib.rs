@@ -0,0 +1,101 @@
+//! Representative Styx documents for benchmarking.
+//!
+//! Each generator returns a fresh document so benchmarks and regression tests
+//! exercise exactly the same input.
+
+use std::fmt::Write;
+
+/// A small, hand-written application config.
+pub fn small_config() -> String {
+    r#"@schema ./schema.styx
+
+/// Server settings
+server {
+    host localhost
+    port 8080
+    tls {cert /etc/ssl/cert.pem, key /etc/ssl/key.pem}
+}
+
+logging.level info
+logging.format json
+
+features (auth metrics "rate limiting")
+"#
+    .to_string()
+}
+
+/// A large flat-ish config with `services` entries of mixed scalar kinds.
+pub fn large_config(services: usize) -> String {
+    let mut out = String::new();
+    for i in 0..services {
+        writeln!(out, "service_{i} {{").unwrap();
+        writeln!(out, "    name \"service number {i}\"").unwrap();
+        writeln!(
+            out,
+            "    image registry.example.com/team/service-{i}:1.{i}.0"
+        )
+        .unwrap();
+        writeln!(out, "    replicas {}", i % 7 + 1).unwrap();
+        writeln!(out, "    ports (80{:02} 90{:02})", i % 100, i % 100).unwrap();
+        writeln!(out, "    env {{RUST_LOG debug, ID {i}, ENABLED true}}").unwrap();
+        writeln!(out, "    labels tier>backend owner>team-{}", i % 5).unwrap();
+        writeln!(out, "}}").unwrap();
+    }
+    out
+}
+
+/// Objects nested `depth` levels deep, each level holding a few scalars.
+pub fn deeply_nested(depth: usize) -> String {
+    let mut out = String::new();
+    for level in 0..depth {
+        let indent = "    ".repeat(level);
+        writeln!(out, "{indent}level_{level} {{").unwrap();
+        writeln!(out, "{indent}    id {level}").unwrap();
+        writeln!(out, "{indent}    name \"level {level}\"").unwrap();
+    }
+    for level in (0..depth).rev() {
+        let indent = "    ".repeat(level);
+        writeln!(out, "{indent}}}").unwrap();
+    }
+    out
+}
+
+/// A config where most lines are doc comments or line comments.
+pub fn comment_heavy(entries: usize) -> String {
+    let mut out = String::new();
+    for i in 0..entries {
+        writeln!(out, "// Section {i}").unwrap();
+        writeln!(
+            out,
+            "// Generated for benchmarking; this line is a regular comment."
+        )
+        .unwrap();
+        writeln!(out, "/// Documentation for key_{i}").unwrap();
+        writeln!(out, "/// spanning two lines.").unwrap();
+        writeln!(out, "key_{i} value_{i} // trailing comment").unwrap();
+    }
+    out
+}
+
+/// A config made of dotted-path entries, ten keys per group.
+///
+/// Consecutive entries share their leading segments, so the parser's path
+/// tracking sees both reused and freshly opened segments.
+pub fn dotted_keys(entries: usize) -> String {
+    let mut out = String::new();
+    for i in 0..entries {
+        writeln!(out, "group_{}.section.key_{i} value_{i}", i / 10).unwrap();
+    }
+    out
+}
+
+/// All benchmark documents with their names.
+pub fn corpus() -> Vec<(&'static str, String)> {
+    vec![
+        ("small_config", small_config()),
+        ("large_config", large_config(500)),
+        ("deeply_nested", deeply_nested(64)),
+        ("comment_heavy", comment_heavy(1000)),
+        ("dotted_keys", dotted_keys(2000)),
+    ]
+}
@@ -13,17 +13,25 @@
 //!   styx lsp                      - subcommand (bare word)
 //!   styx tree config.styx         - subcommand with file arg
 
-use std::io::{self, IsTerminal, Read};
-use std::path::Path;
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use facet::Facet;
-use facet_styx::{SchemaFile, validate};
+use facet_styx::{
+    Compatibility, DeserializeError, InferSchema, Position, RenderError, Schema, SchemaFile,
+    StreamingValidator, ValidationResult, compatibility, merge_schemas,
+};
 use figue as args;
-use styx_format::{FormatOptions, format_source};
+use regex::Regex;
+use styx_format::{FormatOptions, format_source, format_source_range, format_value};
 use styx_lsp::{TokenType, compute_highlight_spans};
-use styx_parse::{Lexer, Parser};
+use styx_parse::{Event, EventKind, Lexer, Parser};
 use styx_tokenizer::Tokenizer;
-use styx_tree::{Payload, Value};
+use styx_tree::{
+    BuildError, CanonicalNumber, ParseOptions, Payload, SchemaDirective, TreeBuilder, Value,
+};
 
 // ============================================================================
 // Exit codes
@@ -63,6 +71,10 @@ struct FileArgs {
     #[facet(args::named, default)]
     compact: bool,
 
+    /// Only format entries within these lines, e.g. `10:20` (1-based, inclusive)
+    #[facet(args::named, default)]
+    range: Option<String>,
+
     /// Validate against declared schema (no output unless -o specified)
     #[facet(args::named, default)]
     validate: bool,
@@ -70,6 +82,22 @@ struct FileArgs {
     /// Use this schema instead of declared @schema
     #[facet(args::named, default)]
     schema: Option<String>,
+
+    /// Stop validating at the first error
+    #[facet(args::named, default)]
+    fail_fast: bool,
+
+    /// Look up embedded schemas whose binary isn't installed in this registry
+    #[facet(args::named, default)]
+    schema_registry: Option<String>,
+
+    /// Report how long each phase took, to stderr
+    #[facet(args::named, default)]
+    timing: bool,
+
+    /// Reject objects that separate entries with both commas and newlines
+    #[facet(args::named, default)]
+    strict_separators: bool,
 }
 
 /// Top-level CLI with optional subcommand
@@ -135,6 +163,18 @@ enum Command {
         /// Binary file to extract from
         #[facet(args::positional)]
         binary: String,
+
+        /// Print each schema's id, version, sizes and section to stderr
+        #[facet(args::named, default)]
+        verbose: bool,
+
+        /// Print schemas ordered by their `meta` id instead of as found
+        #[facet(args::named, default)]
+        sort: bool,
+
+        /// Only print the schema with this `meta` id
+        #[facet(args::named, default)]
+        id: Option<String>,
     },
 
     /// Compare schema against published version
@@ -143,13 +183,29 @@ enum Command {
         #[facet(args::positional)]
         schema: String,
 
-        /// Crate name on staging.crates.io
+        /// Crate name on the registry
         #[facet(args::named, rename = "crate")]
         crate_name: String,
 
         /// Baseline version (default: latest)
         #[facet(args::named, default)]
         baseline: Option<String>,
+
+        /// Output format: text or json
+        #[facet(args::named, default = "text")]
+        format: String,
+
+        /// Registry name (default: staging)
+        #[facet(args::named, default)]
+        registry: Option<String>,
+
+        /// Registry index URL, for registries other than staging
+        #[facet(args::named, default)]
+        index: Option<String>,
+
+        /// Registry crates API URL, e.g. `https://example.com/api/v1/crates`
+        #[facet(args::named, default)]
+        api: Option<String>,
     },
 
     /// Generate publishable crate from schema
@@ -169,9 +225,132 @@ enum Command {
         /// Output directory (default: `./<name>`)
         #[facet(args::named, default)]
         output: Option<String>,
+
+        /// Output format: text or json
+        #[facet(args::named, default = "text")]
+        format: String,
+    },
+
+    /// Print a document in canonical form
+    Normalize {
+        /// Input file (`-` for stdin)
+        #[facet(args::positional)]
+        file: String,
+
+        /// Output file (default: stdout)
+        #[facet(args::named, args::short = 'o', default)]
+        output: Option<String>,
+    },
+
+    /// Check that a schema file is well-formed
+    ValidateSchema {
+        /// Schema file (`-` for stdin)
+        #[facet(args::positional)]
+        file: String,
+    },
+
+    /// Print the schema a document declares, and where it was found
+    SchemaOf {
+        /// Document with a `@schema` declaration (`-` for stdin)
+        #[facet(args::positional)]
+        file: String,
+    },
+
+    /// Parse, validate and check formatting of files in one pass
+    Check {
+        /// Files to check
+        #[facet(args::positional)]
+        files: Vec<String>,
+
+        /// Format files in place instead of reporting them
+        #[facet(args::named, default)]
+        fix: bool,
+    },
+
+    /// Find values by key, value, or tag
+    Grep {
+        /// Input file (`-` for stdin)
+        #[facet(args::positional)]
+        file: String,
+
+        /// Regex the key of the value's entry must match
+        #[facet(args::named, default)]
+        key_matches: Option<String>,
+
+        /// Regex the scalar value must match
+        #[facet(args::named, default)]
+        value_matches: Option<String>,
+
+        /// Comma-separated tag names the value must have
+        #[facet(args::named, default)]
+        tags: Option<String>,
+    },
+
+    /// List the keys a document may have, one per line
+    Keys {
+        /// Schema, or document (`-` for stdin)
+        #[facet(args::positional)]
+        file: String,
+
+        /// Print the dotted path of every nested key too
+        #[facet(args::named, default)]
+        paths: bool,
+    },
+
+    /// Infer a schema from example documents
+    Infer {
+        /// Example documents
+        #[facet(args::positional)]
+        files: Vec<String>,
+
+        /// Output file (default: stdout)
+        #[facet(args::named, args::short = 'o', default)]
+        output: Option<String>,
+
+        /// Schema ID (default: name of the first input file)
+        #[facet(args::named, default)]
+        id: Option<String>,
+
+        /// Mark all fields optional instead of required
+        #[facet(args::named, default)]
+        optional: bool,
+    },
+
+    /// Print a document as environment variable assignments
+    ExportEnv {
+        /// Input file (`-` for stdin)
+        #[facet(args::positional)]
+        file: String,
+
+        /// Prefix for every variable name, e.g. `APP`
+        #[facet(args::named, default)]
+        prefix: Option<String>,
+    },
+
+    /// Build a document from environment variables
+    ImportEnv {
+        /// Read variables starting with `{prefix}_`
+        #[facet(args::named)]
+        prefix: String,
+
+        /// Document mapping variable names to paths, for names whose
+        /// underscores aren't all path separators
+        #[facet(args::named, default)]
+        map: Option<String>,
+
+        /// Output file (default: stdout)
+        #[facet(args::named, args::short = 'o', default)]
+        output: Option<String>,
+    },
+
+    /// Parse lines from stdin interactively, building up a document
+    Repl {
+        /// Output format: styx or json
+        #[facet(args::named, default = "styx")]
+        format: String,
     },
 
-    /// Publish schema to staging.crates.io
+    /// Publish schema to staging.crates.io or another registry
     Publish {
         /// Schema file
         #[facet(args::positional)]
@@ -180,6 +359,22 @@ enum Command {
         /// Skip confirmation prompt
         #[facet(args::named, args::short = 'y', default)]
         yes: bool,
+
+        /// Generate the crate and print its version and path, without publishing
+        #[facet(args::named, default)]
+        dry_run: bool,
+
+        /// Registry name (default: staging)
+        #[facet(args::named, default)]
+        registry: Option<String>,
+
+        /// Registry index URL, for registries other than staging
+        #[facet(args::named, default)]
+        index: Option<String>,
+
+        /// Registry crates API URL, e.g. `https://example.com/api/v1/crates`
+        #[facet(args::named, default)]
+        api: Option<String>,
     },
 
     /// Cache management
@@ -238,7 +433,23 @@ fn is_file_arg(arg: &str) -> bool {
 }
 
 fn main() {
-    let raw_args: Vec<String> = std::env::args().skip(1).collect();
+    let mut raw_args: Vec<String> = std::env::args().skip(1).collect();
+
+    let json = match take_diagnostic_format(&mut raw_args) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(e.exit_code());
+        }
+    };
+    let max_errors = match take_max_errors(&mut raw_args) {
+        Ok(max_errors) => max_errors,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(e.exit_code());
+        }
+    };
+    let mut reporter = Reporter::new(json, max_errors);
 
     // Handle empty args or help
     if raw_args.is_empty() {
@@ -258,37 +469,31 @@ fn main() {
         std::process::exit(EXIT_SUCCESS);
     }
 
+    let result = run(&raw_args, &mut reporter);
+    reporter.finish(io::stderr());
+
+    match result {
+        Ok(()) => std::process::exit(EXIT_SUCCESS),
+        Err(e) => std::process::exit(e.exit_code()),
+    }
+}
+
+/// Run the file mode or subcommand in `args`, with the global options taken
+/// out, and report the error it ends with.
+fn run(args: &[String], reporter: &mut Reporter) -> Result<(), CliError> {
     // Disambiguation: is first arg a file or a subcommand?
-    let result = if is_file_arg(&raw_args[0]) {
+    let result = if is_file_arg(&args[0]) {
         // File mode: parse as FileArgs
-        run_file_mode(&raw_args)
+        run_file_mode(args, reporter)
     } else {
         // Subcommand mode: parse as Args with subcommand
-        run_subcommand_mode(&raw_args)
+        run_subcommand_mode(args, reporter)
     };
 
-    match result {
-        Ok(()) => std::process::exit(EXIT_SUCCESS),
-        Err(e) => {
-            match &e {
-                CliError::ParseDiagnostic {
-                    error,
-                    source,
-                    filename,
-                } => {
-                    if let Some(parse_error) = error.as_parse_error() {
-                        parse_error.write_report(filename, source, std::io::stderr());
-                    } else {
-                        eprintln!("error: {e}");
-                    }
-                }
-                _ => {
-                    eprintln!("error: {e}");
-                }
-            }
-            std::process::exit(e.exit_code());
-        }
+    if let Err(e) = &result {
+        reporter.error(e);
     }
+    result
 }
 
 fn print_help() {
@@ -303,17 +508,52 @@ fn print_help() {
     eprintln!("        --json-out <FILE>           Output as JSON (use '-' for stdout)");
     eprintln!("        --in-place                  Modify input file in place");
     eprintln!("        --compact                   Single-line/compact formatting");
+    eprintln!("        --range <START:END>         Only format entries within these lines");
     eprintln!("        --validate                  Validate against declared schema");
-    eprintln!("        --schema <FILE>             Use this schema instead of @schema\n");
+    eprintln!("        --schema <FILE>             Use this schema instead of @schema");
+    eprintln!("                                    ('-' reads it from stdin, ahead of a");
+    eprintln!("                                    '---' line if the document is on stdin)");
+    eprintln!("        --fail-fast                 Stop validating at the first error");
+    eprintln!("        --schema-registry <URL>     Fetch embedded schemas by id from here");
+    eprintln!("                                    when their binary isn't installed");
+    eprintln!("        --strict-separators         Reject objects that mix commas and newlines");
+    eprintln!("        --timing                    Report how long each phase took\n");
+    eprintln!("GLOBAL OPTIONS:");
+    eprintln!("        --diagnostic-format <FMT>   Print errors as 'human' text or a 'json' array");
+    eprintln!("        --max-errors <N>            Show at most N errors and warnings\n");
     eprintln!("SUBCOMMANDS:");
     eprintln!("    lsp                             Start language server (stdio)");
     eprintln!("    tree <file>                     Show parse tree");
     eprintln!("    cst <file>                      Show CST structure");
-    eprintln!("    extract <binary>                Extract embedded schemas");
+    eprintln!("    extract <binary> [--verbose]    Extract embedded schemas");
+    eprintln!("                                    (--sort to order them by id,");
+    eprintln!("                                    --id <id> for just one)");
     eprintln!("    diff <schema> --crate <name>    Compare against published version");
+    eprintln!("                                    (--format json for machine-readable output)");
     eprintln!("    package <schema> --name <n> --version <v>");
     eprintln!("                                    Generate publishable crate");
+    eprintln!("                                    (--format json to list the generated files)");
+    eprintln!("    normalize <file> [-o <file>]    Print in canonical form");
+    eprintln!("    validate-schema <schema>        Check that a schema file is well-formed");
+    eprintln!("    schema-of <file>                Print the schema a document declares");
+    eprintln!("    check <file>... [--fix]         Parse, validate and check formatting");
+    eprintln!("    grep <file> [--key-matches <re>] [--value-matches <re>] [--tags <t,...>]");
+    eprintln!("                                    Print paths of matching values");
+    eprintln!(
+        "    keys <file> [--paths]           List valid keys, from the schema if there is one"
+    );
+    eprintln!("    infer <file>... [-o <schema>]   Infer a schema from example documents");
+    eprintln!("                                    (--optional to make all fields optional)");
+    eprintln!("    export-env <file> [--prefix <p>]");
+    eprintln!("                                    Print values as NAME=value env lines");
+    eprintln!("    import-env --prefix <p> [--map <file>]");
+    eprintln!("                                    Build a document from <p>_* env vars");
+    eprintln!("    repl [--format json]            Parse lines from stdin interactively");
+    eprintln!("                                    (`.get <path>` queries what was entered)");
     eprintln!("    publish <schema> [-y]           Publish to staging.crates.io");
+    eprintln!("                                    (--dry-run to preview without publishing)");
+    eprintln!("        --registry <name> --index <url> --api <url>");
+    eprintln!("                                    Use another registry for diff and publish");
     eprintln!("    cache [--open|--clear]          Cache management");
     eprintln!("    skill                           Output Claude Code skill");
     eprintln!("    completions <shell>             Generate shell completions (bash, zsh, fish)");
@@ -326,7 +566,7 @@ fn print_help() {
     eprintln!("    styx completions bash           Generate bash completions");
 }
 
-fn run_file_mode(args: &[String]) -> Result<(), CliError> {
+fn run_file_mode(args: &[String], reporter: &mut Reporter) -> Result<(), CliError> {
     let args_strs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
     let opts: FileArgs = figue::from_slice(&args_strs).unwrap();
 
@@ -341,6 +581,22 @@ fn run_file_mode(args: &[String]) -> Result<(), CliError> {
         return Err(CliError::Usage("--schema requires --validate".into()));
     }
 
+    if opts.fail_fast && !opts.validate {
+        return Err(CliError::Usage("--fail-fast requires --validate".into()));
+    }
+
+    if opts.schema_registry.is_some() && !opts.validate {
+        return Err(CliError::Usage(
+            "--schema-registry requires --validate".into(),
+        ));
+    }
+
+    if opts.range.is_some() && (opts.compact || opts.json_out.is_some()) {
+        return Err(CliError::Usage(
+            "--range cannot be used with --compact or --json-out".into(),
+        ));
+    }
+
     // Safety check: prevent -o pointing to same file as input
     if let Some(ref output) = opts.output
         && opts.input != "-"
@@ -352,32 +608,112 @@ fn run_file_mode(args: &[String]) -> Result<(), CliError> {
         ));
     }
 
-    // Read input
-    let source = read_input(Some(&opts.input))?;
+    // Read input, and the schema too if `--schema -` puts it on stdin
+    let (stdin_schema, source) = match (opts.input == "-", opts.schema.as_deref() == Some("-")) {
+        (true, true) => {
+            let stdin = read_input(Some("-"))?;
+            let (schema, document) = split_stdin_schema(&stdin).ok_or_else(|| {
+                CliError::Usage(format!(
+                    "stdin must hold the schema, a '{STDIN_SCHEMA_SEPARATOR}' line, then the document"
+                ))
+            })?;
+            (Some(schema.to_string()), document.to_string())
+        }
+        (false, true) => (Some(read_input(Some("-"))?), read_input(Some(&opts.input))?),
+        _ => (None, read_input(Some(&opts.input))?),
+    };
     let filename = if opts.input == "-" {
         "<stdin>".to_string()
     } else {
         opts.input.clone()
     };
+    let schema = match (&stdin_schema, &opts.schema) {
+        (Some(source), _) => Some(SchemaOverride::Stdin(source)),
+        (None, Some(path)) => Some(SchemaOverride::Path(path)),
+        (None, None) => None,
+    };
 
-    // Parse
-    let value = styx_tree::parse(&source).map_err(|e| CliError::ParseDiagnostic {
-        error: e,
-        source: source.clone(),
-        filename: filename.clone(),
-    })?;
+    // Report timing even if processing fails part way
+    let mut timing = Timing::new(opts.timing);
+    let result = process_file(&opts, schema, &source, &filename, &mut timing, reporter);
+    if opts.timing {
+        eprint!("{}", timing.report());
+    }
+    result
+}
 
-    // Validate if requested
-    if opts.validate {
-        run_validation(&value, &source, &filename, opts.schema.as_deref())?;
+/// Line separating the schema from the document when both come from stdin.
+const STDIN_SCHEMA_SEPARATOR: &str = "---";
+
+/// A schema given with `--schema`, used instead of the declared `@schema`.
+#[derive(Debug, Clone, Copy)]
+enum SchemaOverride<'a> {
+    /// Path to a schema file.
+    Path(&'a str),
+    /// Schema source read from stdin (`--schema -`).
+    Stdin(&'a str),
+}
+
+/// Split stdin holding a schema and a document.
+///
+/// The schema comes first, then a line holding only `---`, then the
+/// document. Returns `None` if there is no separator line.
+fn split_stdin_schema(input: &str) -> Option<(&str, &str)> {
+    let mut line_start = 0;
+    for line in input.split_inclusive('\n') {
+        if line.trim_end_matches(['\n', '\r']) == STDIN_SCHEMA_SEPARATOR {
+            return Some((&input[..line_start], &input[line_start + line.len()..]));
+        }
+        line_start += line.len();
     }
+    None
+}
 
-    // If --validate with no explicit output, we're done (exit code only)
-    let has_explicit_output = opts.json_out.is_some() || opts.output.is_some() || opts.in_place;
-    if opts.validate && !has_explicit_output {
-        return Ok(());
+fn process_file(
+    opts: &FileArgs,
+    schema: Option<SchemaOverride<'_>>,
+    source: &str,
+    filename: &str,
+    timing: &mut Timing,
+    reporter: &mut Reporter,
+) -> Result<(), CliError> {
+    timing.measure_passes(source);
+
+    // Validate if requested. This streams parser events, so validate-only
+    // runs never build the full tree.
+    if opts.validate {
+        let registry = opts.schema_registry.as_deref().map(SchemaRegistry::new);
+        timing.time("validate", || {
+            run_validation(
+                source,
+                filename,
+                schema,
+                registry.as_ref(),
+                opts.fail_fast,
+                opts.strict_separators,
+                reporter,
+            )
+        })?;
+
+        // If --validate with no explicit output, we're done (exit code only)
+        let has_explicit_output = opts.json_out.is_some() || opts.output.is_some() || opts.in_place;
+        if !has_explicit_output {
+            return Ok(());
+        }
     }
 
+    // Parse
+    let value = timing
+        .time("tree", || {
+            let options = ParseOptions::new().strict_separators(opts.strict_separators);
+            styx_tree::parse_with(source, options)
+        })
+        .map_err(|e| CliError::ParseDiagnostic {
+            error: e,
+            source: source.to_string(),
+            filename: filename.to_string(),
+        })?;
+
     // Determine output format and destination
     if let Some(ref json_path) = opts.json_out {
         // JSON output
@@ -392,7 +728,13 @@ fn run_file_mode(args: &[String]) -> Result<(), CliError> {
         } else {
             FormatOptions::default()
         };
-        let output = format_source(&source, format_opts);
+        let output = match &opts.range {
+            Some(range) => {
+                let range = parse_line_range(source, range)?;
+                timing.time("format", || format_range(source, range, format_opts))
+            }
+            None => timing.time("format", || format_source(source, format_opts)),
+        };
 
         if opts.in_place {
             std::fs::write(&opts.input, &output)?;
@@ -406,114 +748,605 @@ fn run_file_mode(args: &[String]) -> Result<(), CliError> {
     Ok(())
 }
 
-fn run_subcommand_mode(args: &[String]) -> Result<(), CliError> {
-    let args_strs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
-    let parsed: Args = figue::from_slice(&args_strs).unwrap();
+/// Byte range covering the lines of a `--range START:END` argument.
+fn parse_line_range(source: &str, range: &str) -> Result<std::ops::Range<usize>, CliError> {
+    let usage = || CliError::Usage(format!("invalid --range '{range}', expected START:END"));
+    let (start, end) = range.split_once(':').ok_or_else(usage)?;
+    let start: usize = start.trim().parse().map_err(|_| usage())?;
+    let end: usize = end.trim().parse().map_err(|_| usage())?;
+    if start == 0 || end < start {
+        return Err(usage());
+    }
 
-    match parsed.command {
-        Some(Command::Lsp) => run_lsp(),
-        Some(Command::Tokens { file }) => run_tokens(&file),
-        Some(Command::Lexemes { file }) => run_lexemes(&file),
-        Some(Command::Events { file }) => run_events(&file),
-        Some(Command::Tree { format, file }) => run_tree(&format, &file),
-        Some(Command::Cst { file }) => run_cst(&file),
-        Some(Command::Extract { binary }) => run_extract(&binary),
-        Some(Command::Diff {
-            schema,
-            crate_name,
-            baseline,
-        }) => run_diff(&schema, &crate_name, baseline.as_deref()),
-        Some(Command::Package {
-            schema,
-            name,
-            version,
-            output,
-        }) => run_package(&schema, &name, &version, output.as_deref()),
-        Some(Command::Publish { schema, yes }) => run_publish(&schema, yes),
-        Some(Command::Cache { open, clear }) => run_cache(open, clear),
-        Some(Command::Skill) => run_skill(),
-        Some(Command::Completions { shell }) => run_completions(&shell),
-        Some(Command::Gen {
-            language,
-            schema,
-            output,
-            package,
-        }) => run_gen(&language, &schema, output.as_deref(), package.as_deref()),
-        None => {
-            print_help();
-            Ok(())
-        }
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let offset = |line: usize| line_starts.get(line).copied().unwrap_or(source.len());
+    Ok(offset(start - 1)..offset(end))
+}
+
+/// Format only the entries within `range`, leaving the rest of `source` as is.
+fn format_range(source: &str, range: std::ops::Range<usize>, options: FormatOptions) -> String {
+    let mut output = source.to_string();
+    for edit in format_source_range(source, range, options).iter().rev() {
+        output.replace_range(edit.range.clone(), &edit.new_text);
     }
+    output
 }
 
 // ============================================================================
-// Error handling
+// Diagnostics
 // ============================================================================
 
-#[derive(Debug)]
-#[allow(dead_code)]
-enum CliError {
-    Io(io::Error),
-    Parse(String),
-    ParseDiagnostic {
-        error: styx_tree::BuildError,
-        source: String,
-        filename: String,
-    },
-    Validation(String),
-    Usage(String),
+/// An error or warning in the form `--diagnostic-format json` emits.
+#[derive(Debug, Clone, PartialEq)]
+struct Diagnostic {
+    file: Option<String>,
+    /// Byte range in the file.
+    span: Option<(usize, usize)>,
+    /// Start and end of `span` as lines and columns, when the source is known.
+    positions: Option<(Position, Position)>,
+    severity: &'static str,
+    message: String,
+    code: &'static str,
 }
 
-impl CliError {
-    fn exit_code(&self) -> i32 {
-        match self {
-            CliError::Io(_) => EXIT_IO_ERROR,
-            CliError::Parse(_) => EXIT_SYNTAX_ERROR,
-            CliError::ParseDiagnostic { .. } => EXIT_SYNTAX_ERROR,
-            CliError::Validation(_) => EXIT_VALIDATION_ERROR,
-            CliError::Usage(_) => EXIT_SYNTAX_ERROR,
+impl Diagnostic {
+    fn from_parse_error(filename: &str, source: &str, error: &BuildError) -> Self {
+        match error {
+            BuildError::Parse(kind, span) => Diagnostic {
+                file: Some(filename.to_string()),
+                span: Some((span.start as usize, span.end as usize)),
+                positions: Some((
+                    Position::of(source, span.start as usize),
+                    Position::of(source, span.end as usize),
+                )),
+                severity: "error",
+                message: kind.to_string(),
+                code: kind.code(),
+            },
+            _ => Diagnostic {
+                file: Some(filename.to_string()),
+                span: None,
+                positions: None,
+                severity: "error",
+                message: error.to_string(),
+                code: "parse",
+            },
         }
     }
-}
 
-impl std::fmt::Display for CliError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            CliError::Io(e) => write!(f, "{e}"),
-            CliError::Parse(e) => write!(f, "{e}"),
-            CliError::ParseDiagnostic { error, .. } => write!(f, "{error}"),
-            CliError::Validation(e) => write!(f, "{e}"),
-            CliError::Usage(e) => write!(f, "{e}"),
+    /// Every parse error in `source` if `error` is one, as the tree builder
+    /// stops at the first.
+    fn from_parse_errors(filename: &str, source: &str, error: &BuildError) -> Vec<Self> {
+        let errors = match error.as_parse_error() {
+            Some(_) => parse_errors(source),
+            None => Vec::new(),
+        };
+        if errors.is_empty() {
+            return vec![Self::from_parse_error(filename, source, error)];
         }
+        errors
+            .into_iter()
+            .map(|e| Self::from_parse_error(filename, source, &BuildError::Parse(e.kind, e.span)))
+            .collect()
     }
-}
 
-impl From<io::Error> for CliError {
-    fn from(e: io::Error) -> Self {
-        CliError::Io(e)
+    fn from_validation(filename: &str, source: &str, result: &ValidationResult) -> Vec<Self> {
+        let errors = result.errors.iter().map(|e| e.to_diagnostic(source));
+        let warnings = result.warnings.iter().map(|w| w.to_diagnostic(source));
+        errors
+            .chain(warnings)
+            .map(|diagnostic| Diagnostic {
+                file: Some(filename.to_string()),
+                span: diagnostic.span.map(|s| (s.start as usize, s.end as usize)),
+                positions: diagnostic.start.zip(diagnostic.end),
+                severity: diagnostic.severity.as_str(),
+                message: diagnostic.message,
+                code: diagnostic.code,
+            })
+            .collect()
     }
-}
 
-impl From<styx_tree::BuildError> for CliError {
-    fn from(e: styx_tree::BuildError) -> Self {
-        CliError::Parse(e.to_string())
+    fn from_deserialize(filename: &str, source: &str, error: &DeserializeError) -> Self {
+        let span = error.span.as_ref().map(|s| {
+            let start = s.offset as usize;
+            (start, start + s.len as usize)
+        });
+        Diagnostic {
+            file: Some(filename.to_string()),
+            span,
+            positions: span
+                .map(|(start, end)| (Position::of(source, start), Position::of(source, end))),
+            severity: "error",
+            message: error.to_string(),
+            code: "deserialize",
+        }
     }
-}
 
-impl From<styx_gen_go::GenError> for CliError {
-    fn from(e: styx_gen_go::GenError) -> Self {
-        CliError::Io(io::Error::other(e.to_string()))
+    fn to_json(&self) -> serde_json::Value {
+        let position = |p: Position| serde_json::json!({"line": p.line, "column": p.column});
+        serde_json::json!({
+            "file": self.file,
+            "span": self.span.map(|(start, end)| [start, end]),
+            "start": self.positions.map(|(start, _)| position(start)),
+            "end": self.positions.map(|(_, end)| position(end)),
+            "severity": self.severity,
+            "message": self.message,
+            "code": self.code,
+        })
     }
 }
 
-// ============================================================================
-// Subcommand implementations
-// ============================================================================
+/// Where errors and warnings go, in the selected `--diagnostic-format`.
+#[derive(Debug, Default)]
+struct Reporter {
+    /// Diagnostics collected for `--diagnostic-format json`, printed as one
+    /// array by [`finish`](Self::finish). `None` when diagnostics are rendered
+    /// for humans as they are reported.
+    json: Option<Vec<Diagnostic>>,
+    /// Most errors and warnings to show per report, from `--max-errors`.
+    /// `None` shows all. JSON diagnostics end a capped report with a
+    /// `truncated` note giving how many were left out.
+    max_errors: Option<usize>,
+}
 
-fn run_lsp() -> Result<(), CliError> {
-    let rt = tokio::runtime::Runtime::new().map_err(CliError::Io)?;
-    rt.block_on(async {
-        styx_lsp::run()
+impl Reporter {
+    fn new(json: bool, max_errors: Option<usize>) -> Self {
+        Self {
+            json: json.then(Vec::new),
+            max_errors,
+        }
+    }
+
+    /// Report diagnostics: collected for JSON output, or shown right away by
+    /// calling `render` with the `--max-errors` limit.
+    fn report(&mut self, diagnostics: Vec<Diagnostic>, render: impl FnOnce(Option<usize>)) {
+        match self.json.as_mut() {
+            Some(collected) => collect_capped(collected, diagnostics, self.max_errors),
+            None => render(self.max_errors),
+        }
+    }
+
+    /// Report an error that ended the run, or the check of one file.
+    fn error(&mut self, e: &CliError) {
+        match (self.json.as_mut(), e) {
+            (
+                Some(collected),
+                CliError::ParseDiagnostic {
+                    error,
+                    source,
+                    filename,
+                },
+            ) => {
+                let diagnostics = Diagnostic::from_parse_errors(filename, source, error);
+                collect_capped(collected, diagnostics, self.max_errors);
+            }
+            // The validation errors themselves were already reported
+            (Some(collected), CliError::Validation(_)) if !collected.is_empty() => {}
+            (Some(collected), _) => collected.push(Diagnostic {
+                file: None,
+                span: None,
+                positions: None,
+                severity: "error",
+                message: e.to_string(),
+                code: e.code(),
+            }),
+            (
+                None,
+                CliError::ParseDiagnostic {
+                    error,
+                    source,
+                    filename,
+                },
+            ) => {
+                if error.as_parse_error().is_some() {
+                    write_parse_report(filename, source, self.max_errors, std::io::stderr());
+                } else {
+                    eprintln!("error: {e}");
+                }
+            }
+            (None, _) => eprintln!("error: {e}"),
+        }
+    }
+
+    /// Write the diagnostics collected for JSON output, if any, to `out`.
+    fn finish(self, mut out: impl io::Write) {
+        if let Some(diagnostics) = self.json {
+            let _ = writeln!(out, "{}", diagnostics_to_json(&diagnostics));
+        }
+    }
+}
+
+/// Collect the first `max_errors` of `diagnostics` for JSON output, followed
+/// by a note saying how many were left out, if any.
+fn collect_capped(
+    collected: &mut Vec<Diagnostic>,
+    mut diagnostics: Vec<Diagnostic>,
+    max_errors: Option<usize>,
+) {
+    let shown = max_errors
+        .unwrap_or(diagnostics.len())
+        .min(diagnostics.len());
+    let omitted = diagnostics.len() - shown;
+    let file = diagnostics.first().and_then(|d| d.file.clone());
+    diagnostics.truncate(shown);
+    collected.extend(diagnostics);
+    if omitted > 0 {
+        collected.push(Diagnostic {
+            file,
+            span: None,
+            positions: None,
+            severity: "note",
+            message: format!("{omitted} more left out by --max-errors"),
+            code: "truncated",
+        });
+    }
+}
+
+fn diagnostics_to_json(diagnostics: &[Diagnostic]) -> String {
+    let json: Vec<_> = diagnostics.iter().map(Diagnostic::to_json).collect();
+    serde_json::to_string_pretty(&json).expect("diagnostics serialize to JSON")
+}
+
+/// Remove `--diagnostic-format <format>` from the arguments, wherever it is.
+///
+/// Returns whether JSON diagnostics were requested.
+fn take_diagnostic_format(args: &mut Vec<String>) -> Result<bool, CliError> {
+    let Some(format) = take_global_option(args, "--diagnostic-format", "human or json")? else {
+        return Ok(false);
+    };
+    match format.as_str() {
+        "human" => Ok(false),
+        "json" => Ok(true),
+        other => Err(CliError::Usage(format!(
+            "unknown diagnostic format '{other}', expected 'human' or 'json'"
+        ))),
+    }
+}
+
+/// Remove `--max-errors <n>` from the arguments, wherever it is.
+fn take_max_errors(args: &mut Vec<String>) -> Result<Option<usize>, CliError> {
+    let Some(max) = take_global_option(args, "--max-errors", "a number")? else {
+        return Ok(None);
+    };
+    max.parse()
+        .map(Some)
+        .map_err(|_| CliError::Usage(format!("invalid --max-errors '{max}', expected a number")))
+}
+
+/// Remove `<flag> <value>` or `<flag>=<value>` from the arguments, wherever
+/// it is, and return the value. `expected` describes the value for errors.
+fn take_global_option(
+    args: &mut Vec<String>,
+    flag: &str,
+    expected: &str,
+) -> Result<Option<String>, CliError> {
+    let prefix = format!("{flag}=");
+    let Some(index) = args
+        .iter()
+        .position(|arg| arg == flag || arg.starts_with(&prefix))
+    else {
+        return Ok(None);
+    };
+
+    let arg = args.remove(index);
+    match arg.strip_prefix(&prefix) {
+        Some(value) => Ok(Some(value.to_string())),
+        None if index < args.len() => Ok(Some(args.remove(index))),
+        None => Err(CliError::Usage(format!(
+            "{flag} requires a value ({expected})"
+        ))),
+    }
+}
+
+/// Every parse error in `source`.
+///
+/// The tree builder stops at the first error, so the source is parsed again
+/// to find the rest.
+fn parse_errors(source: &str) -> Vec<styx_tree::ParseError> {
+    let mut parser = Parser::new(source);
+    let mut errors = Vec::new();
+    while let Some(event) = parser.next_event() {
+        if let EventKind::Error { kind } = event.kind {
+            errors.push(styx_tree::ParseError::new(kind, event.span));
+        }
+    }
+    errors
+}
+
+/// Write every parse error in `source`, stopping after `max_errors`.
+fn write_parse_report<W: io::Write>(
+    filename: &str,
+    source: &str,
+    max_errors: Option<usize>,
+    mut writer: W,
+) {
+    let errors = parse_errors(source);
+    let shown = max_errors.unwrap_or(errors.len()).min(errors.len());
+    for error in &errors[..shown] {
+        error.write_report(filename, source, &mut writer);
+    }
+    write_omitted(errors.len() - shown, writer);
+}
+
+/// Write validation errors, then warnings, stopping after `max_errors`.
+fn write_validation_report<W: io::Write>(
+    result: &ValidationResult,
+    filename: &str,
+    source: &str,
+    max_errors: Option<usize>,
+    mut writer: W,
+) {
+    let total = result.errors.len() + result.warnings.len();
+    let shown = max_errors.unwrap_or(total).min(total);
+    for error in result.errors.iter().take(shown) {
+        error.write_report(filename, source, &mut writer);
+    }
+    let warnings = shown.saturating_sub(result.errors.len());
+    for warning in result.warnings.iter().take(warnings) {
+        warning.write_report(filename, source, &mut writer);
+    }
+    write_omitted(total - shown, writer);
+}
+
+/// Say how many reports `--max-errors` left out, if any.
+fn write_omitted<W: io::Write>(omitted: usize, mut writer: W) {
+    if omitted > 0 {
+        let _ = writeln!(writer, "... and {omitted} more");
+    }
+}
+
+// ============================================================================
+// Timing
+// ============================================================================
+
+/// How long each phase of processing a file took, for `--timing`.
+struct Timing {
+    enabled: bool,
+    /// Lexing and parsing, measured in passes of their own. The real work
+    /// lexes and parses again as part of its phases.
+    passes: Vec<(&'static str, Duration)>,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl Timing {
+    fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            passes: Vec::new(),
+            phases: Vec::new(),
+        }
+    }
+
+    /// Run `f`, recording how long it took as `phase` if timing is enabled.
+    fn time<T>(&mut self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        if !self.enabled {
+            return f();
+        }
+        let start = Instant::now();
+        let result = f();
+        self.phases.push((phase, start.elapsed()));
+        result
+    }
+
+    /// Lex and parse `source` in passes of their own, so those phases can be
+    /// measured apart from validation and building the tree.
+    fn measure_passes(&mut self, source: &str) {
+        if !self.enabled {
+            return;
+        }
+        let start = Instant::now();
+        Lexer::new(source).count();
+        self.passes.push(("lex", start.elapsed()));
+
+        let start = Instant::now();
+        let mut parser = Parser::new(source);
+        while parser.next_event().is_some() {}
+        self.passes.push(("parse", start.elapsed()));
+    }
+
+    /// Time spent on the real work. Leaves out the lex and parse passes,
+    /// which the phases already include.
+    fn total(&self) -> Duration {
+        self.phases.iter().map(|(_, duration)| *duration).sum()
+    }
+
+    fn report(&self) -> String {
+        let mut report = String::from("timing:\n");
+        for (phase, duration) in self.passes.iter().chain(&self.phases) {
+            report.push_str(&format!("    {phase:<10}{}\n", format_duration(*duration)));
+        }
+        report.push_str(&format!(
+            "    {:<10}{}\n",
+            "total",
+            format_duration(self.total())
+        ));
+        report
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:>10.3} ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn run_subcommand_mode(args: &[String], reporter: &mut Reporter) -> Result<(), CliError> {
+    let args_strs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let parsed: Args = figue::from_slice(&args_strs).unwrap();
+
+    match parsed.command {
+        Some(Command::Lsp) => run_lsp(),
+        Some(Command::Tokens { file }) => run_tokens(&file),
+        Some(Command::Lexemes { file }) => run_lexemes(&file),
+        Some(Command::Events { file }) => run_events(&file),
+        Some(Command::Tree { format, file }) => run_tree(&format, &file),
+        Some(Command::Cst { file }) => run_cst(&file),
+        Some(Command::Extract {
+            binary,
+            verbose,
+            sort,
+            id,
+        }) => run_extract(&binary, verbose, sort, id.as_deref()),
+        Some(Command::Diff {
+            schema,
+            crate_name,
+            baseline,
+            format,
+            registry,
+            index,
+            api,
+        }) => {
+            let registry = Registry::from_options(registry, index, api)?;
+            run_diff(
+                &schema,
+                &crate_name,
+                baseline.as_deref(),
+                &format,
+                &registry,
+            )
+        }
+        Some(Command::Package {
+            schema,
+            name,
+            version,
+            output,
+            format,
+        }) => run_package(&schema, &name, &version, output.as_deref(), &format),
+        Some(Command::Normalize { file, output }) => run_normalize(&file, output.as_deref()),
+        Some(Command::ValidateSchema { file }) => run_validate_schema(&file, reporter),
+        Some(Command::SchemaOf { file }) => run_schema_of(&file),
+        Some(Command::Check { files, fix }) => run_check(&files, fix, reporter),
+        Some(Command::Keys { file, paths }) => run_keys(&file, paths),
+        Some(Command::Grep {
+            file,
+            key_matches,
+            value_matches,
+            tags,
+        }) => run_grep(
+            &file,
+            key_matches.as_deref(),
+            value_matches.as_deref(),
+            tags.as_deref(),
+        ),
+        Some(Command::Infer {
+            files,
+            output,
+            id,
+            optional,
+        }) => run_infer(&files, output.as_deref(), id.as_deref(), optional),
+        Some(Command::ExportEnv { file, prefix }) => run_export_env(&file, prefix.as_deref()),
+        Some(Command::ImportEnv {
+            prefix,
+            map,
+            output,
+        }) => run_import_env(&prefix, map.as_deref(), output.as_deref()),
+        Some(Command::Repl { format }) => run_repl(&format),
+        Some(Command::Publish {
+            schema,
+            yes,
+            dry_run,
+            registry,
+            index,
+            api,
+        }) => {
+            let registry = Registry::from_options(registry, index, api)?;
+            run_publish(&schema, yes, dry_run, &registry)
+        }
+        Some(Command::Cache { open, clear }) => run_cache(open, clear),
+        Some(Command::Skill) => run_skill(),
+        Some(Command::Completions { shell }) => run_completions(&shell),
+        Some(Command::Gen {
+            language,
+            schema,
+            output,
+            package,
+        }) => run_gen(&language, &schema, output.as_deref(), package.as_deref()),
+        None => {
+            print_help();
+            Ok(())
+        }
+    }
+}
+
+// ============================================================================
+// Error handling
+// ============================================================================
+
+#[derive(Debug)]
+#[allow(dead_code)]
+enum CliError {
+    Io(io::Error),
+    Parse(String),
+    ParseDiagnostic {
+        error: styx_tree::BuildError,
+        source: String,
+        filename: String,
+    },
+    Validation(String),
+    Usage(String),
+}
+
+impl CliError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Io(_) => EXIT_IO_ERROR,
+            CliError::Parse(_) => EXIT_SYNTAX_ERROR,
+            CliError::ParseDiagnostic { .. } => EXIT_SYNTAX_ERROR,
+            CliError::Validation(_) => EXIT_VALIDATION_ERROR,
+            CliError::Usage(_) => EXIT_SYNTAX_ERROR,
+        }
+    }
+
+    /// Diagnostic code for `--diagnostic-format json`, when the error has no
+    /// more specific one.
+    fn code(&self) -> &'static str {
+        match self {
+            CliError::Io(_) => "io",
+            CliError::Parse(_) | CliError::ParseDiagnostic { .. } => "parse",
+            CliError::Validation(_) => "validation",
+            CliError::Usage(_) => "usage",
+        }
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::Io(e) => write!(f, "{e}"),
+            CliError::Parse(e) => write!(f, "{e}"),
+            CliError::ParseDiagnostic { error, .. } => write!(f, "{error}"),
+            CliError::Validation(e) => write!(f, "{e}"),
+            CliError::Usage(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(e: io::Error) -> Self {
+        CliError::Io(e)
+    }
+}
+
+impl From<styx_tree::BuildError> for CliError {
+    fn from(e: styx_tree::BuildError) -> Self {
+        CliError::Parse(e.to_string())
+    }
+}
+
+impl From<styx_gen_go::GenError> for CliError {
+    fn from(e: styx_gen_go::GenError) -> Self {
+        CliError::Io(io::Error::other(e.to_string()))
+    }
+}
+
+// ============================================================================
+// Subcommand implementations
+// ============================================================================
+
+fn run_lsp() -> Result<(), CliError> {
+    let rt = tokio::runtime::Runtime::new().map_err(CliError::Io)?;
+    rt.block_on(async {
+        styx_lsp::run()
             .await
             .map_err(|e| CliError::Io(io::Error::other(e)))
     })
@@ -599,8 +1432,10 @@ fn run_cst(file: &str) -> Result<(), CliError> {
     Ok(())
 }
 
-fn run_extract(binary: &str) -> Result<(), CliError> {
-    let schemas = styx_embed::extract_schemas_from_file(Path::new(binary))
+fn run_extract(binary: &str, verbose: bool, sort: bool, id: Option<&str>) -> Result<(), CliError> {
+    let data = std::fs::read(binary)
+        .map_err(|e| CliError::Io(io::Error::other(format!("{binary}: {e}"))))?;
+    let schemas = styx_embed::extract_schemas_from_object(&data)
         .map_err(|e| CliError::Io(io::Error::other(format!("{binary}: {e}"))))?;
 
     if schemas.is_empty() {
@@ -608,11 +1443,22 @@ fn run_extract(binary: &str) -> Result<(), CliError> {
             "no embedded schemas found in {binary}"
         )));
     }
+    let schemas = select_extracted(schemas, sort, id).map_err(|available| {
+        CliError::Usage(format!(
+            "schema '{}' not found in {binary}\navailable schemas: {}",
+            id.unwrap_or_default(),
+            available.join(", ")
+        ))
+    })?;
 
-    for (i, schema) in schemas.iter().enumerate() {
-        if schemas.len() > 1 {
+    for (i, extracted) in schemas.iter().enumerate() {
+        if schemas.len() > 1 || verbose {
             eprintln!("--- schema {} ---", i + 1);
         }
+        if verbose {
+            eprint!("{}", describe_extracted(extracted));
+        }
+        let schema = &extracted.schema;
         print_styx(schema);
         // Ensure newline after schema (print_styx doesn't add one)
         if !schema.ends_with('\n') {
@@ -623,6 +1469,481 @@ fn run_extract(binary: &str) -> Result<(), CliError> {
     Ok(())
 }
 
+/// The schemas `styx extract` prints: the one whose `meta` id is `id`, or
+/// all of them, ordered by id with `sort` and as found otherwise. Schemas
+/// without an id sort last. If no schema has that id, the ids the schemas
+/// do have are returned instead.
+fn select_extracted(
+    schemas: Vec<styx_embed::ExtractedSchema>,
+    sort: bool,
+    id: Option<&str>,
+) -> Result<Vec<styx_embed::ExtractedSchema>, Vec<String>> {
+    let mut schemas: Vec<_> = schemas
+        .into_iter()
+        .map(|extracted| (schema_id(&extracted.schema), extracted))
+        .collect();
+    if let Some(target_id) = id {
+        let Some(index) = schemas
+            .iter()
+            .position(|(id, _)| id.as_deref() == Some(target_id))
+        else {
+            return Err(schemas.into_iter().filter_map(|(id, _)| id).collect());
+        };
+        return Ok(vec![schemas.swap_remove(index).1]);
+    }
+    if sort {
+        schemas.sort_by(|(a, _), (b, _)| (a.is_none(), a).cmp(&(b.is_none(), b)));
+    }
+    Ok(schemas
+        .into_iter()
+        .map(|(_, extracted)| extracted)
+        .collect())
+}
+
+/// The `meta` id of a schema's source, if it has one.
+fn schema_id(schema: &str) -> Option<String> {
+    facet_styx::parse_meta(schema).ok().map(|meta| meta.id)
+}
+
+/// Describe where an extracted schema came from, one `field: value` per line.
+fn describe_extracted(extracted: &styx_embed::ExtractedSchema) -> String {
+    let meta = facet_styx::parse_meta(&extracted.schema).ok();
+    let id = meta.as_ref().map(|meta| meta.id.as_str());
+    let version = meta.as_ref().and_then(|meta| meta.version.as_deref());
+    format!(
+        "id: {}\nversion: {}\nsize: {} bytes compressed, {} bytes decompressed\nsection: {}\n",
+        id.unwrap_or("(none)"),
+        version.unwrap_or("(none)"),
+        extracted.compressed_len,
+        extracted.decompressed_len,
+        extracted.section,
+    )
+}
+
+fn run_normalize(file: &str, output: Option<&str>) -> Result<(), CliError> {
+    let source = read_input(Some(file))?;
+    let value = styx_tree::parse(&source).map_err(|e| CliError::ParseDiagnostic {
+        error: e,
+        source: source.clone(),
+        filename: file.to_string(),
+    })?;
+
+    let normalized = format_value(&value, FormatOptions::canonical());
+    match output {
+        Some(path) => write_output(path, &normalized)?,
+        None => print_styx(&normalized),
+    }
+    Ok(())
+}
+
+fn run_infer(
+    files: &[String],
+    output: Option<&str>,
+    id: Option<&str>,
+    optional: bool,
+) -> Result<(), CliError> {
+    let Some(first) = files.first() else {
+        return Err(CliError::Usage(
+            "infer needs at least one example document".into(),
+        ));
+    };
+
+    let mut inferred: Option<InferSchema> = None;
+    for file in files {
+        let source = read_input(Some(file))?;
+        let value = styx_tree::parse(&source).map_err(|e| CliError::ParseDiagnostic {
+            error: e,
+            source: source.clone(),
+            filename: file.clone(),
+        })?;
+        inferred = Some(match inferred {
+            Some(inferred) => inferred.sample(&value),
+            None => InferSchema::new(&value),
+        });
+    }
+
+    let id = id.unwrap_or_else(|| {
+        Path::new(first)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("inferred")
+    });
+    let schema = inferred
+        .expect("at least one sample was read")
+        .id(id)
+        .optional_fields(optional)
+        .generate();
+
+    match output {
+        Some(path) => write_output(path, &schema)?,
+        None => print_styx(&schema),
+    }
+    Ok(())
+}
+
+fn run_validate_schema(file: &str, reporter: &mut Reporter) -> Result<(), CliError> {
+    let source = read_input(Some(file))?;
+    let filename = if file == "-" { "<stdin>" } else { file };
+    let value = styx_tree::parse(&source).map_err(|e| CliError::ParseDiagnostic {
+        error: e,
+        source: source.clone(),
+        filename: filename.to_string(),
+    })?;
+
+    let result = facet_styx::validate_schema_file(&value);
+    if !result.is_valid() {
+        reporter.report(
+            Diagnostic::from_validation(filename, &source, &result),
+            |max_errors| {
+                write_validation_report(&result, filename, &source, max_errors, io::stderr())
+            },
+        );
+        return Err(CliError::Validation(format!(
+            "{} schema error(s)",
+            result.errors.len()
+        )));
+    }
+
+    let schema = match facet_styx::from_value::<SchemaFile>(&value) {
+        Ok(schema) => schema,
+        Err(e) => {
+            reporter.report(
+                vec![Diagnostic::from_deserialize(filename, &source, &e)],
+                |_| e.write_report(filename, &source, std::io::stderr()),
+            );
+            return Err(CliError::Validation(format!(
+                "{filename} is not a valid schema"
+            )));
+        }
+    };
+
+    // Stale `@example`s in doc comments only warn
+    let examples = facet_styx::validate_examples(&schema, &source);
+    if !examples.warnings.is_empty() {
+        reporter.report(
+            Diagnostic::from_validation(filename, &source, &examples),
+            |max_errors| {
+                write_validation_report(&examples, filename, &source, max_errors, std::io::stderr())
+            },
+        );
+    }
+
+    Ok(())
+}
+
+fn run_schema_of(file: &str) -> Result<(), CliError> {
+    let source = read_input(Some(file))?;
+    let filename = if file == "-" { "<stdin>" } else { file };
+
+    for schema in resolve_declared_schemas(&source, filename)? {
+        eprintln!("--- {} ---", schema.origin);
+        print_styx(&schema.source);
+        if !schema.source.ends_with('\n') {
+            println!();
+        }
+    }
+    Ok(())
+}
+
+fn run_grep(
+    file: &str,
+    key_matches: Option<&str>,
+    value_matches: Option<&str>,
+    tags: Option<&str>,
+) -> Result<(), CliError> {
+    let filter = GrepFilter::new(key_matches, value_matches, tags)?;
+    let source = read_input(Some(file))?;
+    let filename = if file == "-" { "<stdin>" } else { file };
+    let value = styx_tree::parse(&source).map_err(|e| CliError::ParseDiagnostic {
+        error: e,
+        source: source.clone(),
+        filename: filename.to_string(),
+    })?;
+
+    for found in grep_value(&value, &filter) {
+        match found.span {
+            Some(span) => {
+                let (line, column) = line_column(&source, span.start as usize);
+                let text = source_excerpt(&source, span);
+                println!("{filename}:{line}:{column}: {} {text}", found.path);
+            }
+            None => println!("{filename}: {}", found.path),
+        }
+    }
+    Ok(())
+}
+
+/// What `styx grep` looks for. A value matches when every filter given does.
+struct GrepFilter {
+    /// Pattern for the key of the entry holding the value.
+    key: Option<Regex>,
+    /// Pattern for the text of a scalar value.
+    value: Option<Regex>,
+    /// Tag names the value may have; empty means any.
+    tags: Vec<String>,
+}
+
+impl GrepFilter {
+    fn new(
+        key_matches: Option<&str>,
+        value_matches: Option<&str>,
+        tags: Option<&str>,
+    ) -> Result<Self, CliError> {
+        let pattern = |flag: &str, pattern: Option<&str>| {
+            pattern
+                .map(Regex::new)
+                .transpose()
+                .map_err(|e| CliError::Usage(format!("invalid --{flag} pattern: {e}")))
+        };
+        let filter = GrepFilter {
+            key: pattern("key-matches", key_matches)?,
+            value: pattern("value-matches", value_matches)?,
+            tags: tags
+                .into_iter()
+                .flat_map(|tags| tags.split(','))
+                .map(|tag| tag.trim().trim_start_matches('@').to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+        };
+        if filter.key.is_none() && filter.value.is_none() && filter.tags.is_empty() {
+            return Err(CliError::Usage(
+                "grep needs --key-matches, --value-matches, or --tags".into(),
+            ));
+        }
+        Ok(filter)
+    }
+
+    /// Whether `value`, held under `key` (`None` for sequence items), matches.
+    fn matches(&self, key: Option<&str>, value: &Value) -> bool {
+        let key_ok = self
+            .key
+            .as_ref()
+            .is_none_or(|re| key.is_some_and(|key| re.is_match(key)));
+        let value_ok = self
+            .value
+            .as_ref()
+            .is_none_or(|re| value.scalar_text().is_some_and(|text| re.is_match(text)));
+        let tag_ok = self.tags.is_empty()
+            || value
+                .tag_name()
+                .is_some_and(|name| self.tags.iter().any(|tag| tag == name));
+        key_ok && value_ok && tag_ok
+    }
+}
+
+/// A value found by `styx grep`.
+#[derive(Debug, PartialEq)]
+struct GrepMatch {
+    /// Path to the value, as accepted by `Value::get`.
+    path: String,
+    span: Option<styx_tree::Span>,
+}
+
+/// Every value in `root` that matches `filter`, in document order.
+fn grep_value(root: &Value, filter: &GrepFilter) -> Vec<GrepMatch> {
+    let mut found = Vec::new();
+    grep_children(root, "", filter, &mut found);
+    found
+}
+
+fn grep_children(value: &Value, path: &str, filter: &GrepFilter, found: &mut Vec<GrepMatch>) {
+    match &value.payload {
+        Some(Payload::Object(obj)) => {
+            for entry in &obj.entries {
+                let key = match (entry.key.scalar_text(), entry.key.tag_name()) {
+                    (Some(text), _) => text.to_string(),
+                    (None, Some(tag)) => format!("@{tag}"),
+                    (None, None) => "@".to_string(),
+                };
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                grep_node(&entry.value, Some(&key), path, filter, found);
+            }
+        }
+        Some(Payload::Sequence(seq)) => {
+            for (index, item) in seq.items.iter().enumerate() {
+                grep_node(item, None, format!("{path}[{index}]"), filter, found);
+            }
+        }
+        Some(Payload::Scalar(_)) | None => {}
+    }
+}
+
+fn grep_node(
+    value: &Value,
+    key: Option<&str>,
+    path: String,
+    filter: &GrepFilter,
+    found: &mut Vec<GrepMatch>,
+) {
+    if filter.matches(key, value) {
+        // Tagged values span their payload; widen it to start at the tag
+        let tag_start = value.tag.as_ref().and_then(|tag| tag.span);
+        let span = match (tag_start, value.span) {
+            (Some(tag), Some(span)) => Some(styx_tree::Span::new(tag.start, span.end)),
+            (tag, span) => span.or(tag),
+        };
+        found.push(GrepMatch {
+            path: path.clone(),
+            span,
+        });
+    }
+    grep_children(value, &path, filter, found);
+}
+
+/// Print the keys a document may have: those its schema declares, or for a
+/// schema file its own, or else the keys the document sets.
+fn run_keys(file: &str, paths: bool) -> Result<(), CliError> {
+    let source = read_input(Some(file))?;
+    let filename = if file == "-" { "<stdin>" } else { file };
+    let value = styx_tree::parse(&source).map_err(|e| CliError::ParseDiagnostic {
+        error: e,
+        source: source.clone(),
+        filename: filename.to_string(),
+    })?;
+
+    let is_schema = value.get("meta").is_some() && value.get("schema").is_some();
+    let declares_schema = value
+        .as_object()
+        .is_some_and(|obj| obj.entries.iter().any(|e| e.key.is_schema_tag()));
+    let keys = if is_schema {
+        schema_keys(&parse_schema_file(&source, "schema")?, paths)
+    } else if declares_schema {
+        let declared = resolve_declared_schemas(&source, filename)?;
+        let mut keys = Vec::new();
+        for schema in declared {
+            let what = format!("schema {}", schema.origin);
+            for key in schema_keys(&parse_schema_file(&schema.source, &what)?, paths) {
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+        }
+        keys
+    } else {
+        document_keys(&value, paths)
+    };
+
+    for key in keys {
+        println!("{key}");
+    }
+    Ok(())
+}
+
+/// The fields a schema's root object declares, sorted, or with `paths`
+/// the dotted path of every field of every nested object.
+fn schema_keys(schema: &SchemaFile, paths: bool) -> Vec<String> {
+    let mut keys = Vec::new();
+    if let Some(root) = schema.schema.get(&None) {
+        collect_schema_keys(schema, root, "", paths, 0, &mut keys);
+    }
+    keys
+}
+
+fn collect_schema_keys(
+    file: &SchemaFile,
+    schema: &Schema,
+    prefix: &str,
+    paths: bool,
+    depth: usize,
+    keys: &mut Vec<String>,
+) {
+    // Recursive types describe documents of any depth; stop somewhere
+    if depth > 16 {
+        return;
+    }
+    match schema {
+        Schema::Object(fields) => {
+            let mut named: Vec<(&str, &Schema)> = fields
+                .0
+                .iter()
+                .filter_map(|(key, field)| Some((key.value.name()?, field)))
+                .collect();
+            named.sort_by_key(|(name, _)| *name);
+            for (name, field) in named {
+                // `@flatten(@Type)` fields are inlined into this object
+                if let Schema::Flatten(flatten) = field {
+                    collect_schema_keys(file, &flatten.0.0.value, prefix, paths, depth + 1, keys);
+                    continue;
+                }
+                let path = match prefix {
+                    "" => name.to_string(),
+                    _ => format!("{prefix}.{name}"),
+                };
+                keys.push(path.clone());
+                if paths {
+                    collect_schema_keys(file, field, &path, paths, depth + 1, keys);
+                }
+            }
+        }
+        Schema::Optional(inner) => {
+            collect_schema_keys(file, &inner.0.0.value, prefix, paths, depth + 1, keys)
+        }
+        Schema::Default(inner) => {
+            collect_schema_keys(file, &inner.0.1.value, prefix, paths, depth + 1, keys)
+        }
+        Schema::Deprecated(inner) => {
+            collect_schema_keys(file, &inner.0.1.value, prefix, paths, depth + 1, keys)
+        }
+        Schema::Type { name: Some(name) } => {
+            if let Some(target) = file.schema.get(&Some(name.clone())) {
+                collect_schema_keys(file, target, prefix, paths, depth + 1, keys);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The keys of a document's root object, or with `paths` the dotted path of
+/// every key of every nested object.
+fn document_keys(value: &Value, paths: bool) -> Vec<String> {
+    let mut keys = Vec::new();
+    collect_document_keys(value, "", paths, &mut keys);
+    keys
+}
+
+fn collect_document_keys(value: &Value, prefix: &str, paths: bool, keys: &mut Vec<String>) {
+    let Some(obj) = value.as_object() else {
+        return;
+    };
+    for entry in &obj.entries {
+        let Some(key) = entry.key.as_str() else {
+            continue;
+        };
+        let path = match prefix {
+            "" => key.to_string(),
+            _ => format!("{prefix}.{key}"),
+        };
+        keys.push(path.clone());
+        if paths {
+            collect_document_keys(&entry.value, &path, paths, keys);
+        }
+    }
+}
+
+/// One-based line and column of a byte offset.
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = before.matches('\n').count() + 1;
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+/// The first line of the source text under `span`, marked when cut short.
+fn source_excerpt(source: &str, span: styx_tree::Span) -> String {
+    let text = source
+        .get(span.start as usize..span.end as usize)
+        .unwrap_or_default();
+    match text.split_once('\n') {
+        Some((first, _)) => format!("{}…", first.trim_end()),
+        None => text.to_string(),
+    }
+}
+
 fn run_skill() -> Result<(), CliError> {
     print!("{}", include_str!("../contrib/SKILL.md"));
     Ok(())
@@ -655,7 +1976,7 @@ fn run_gen(
     match language.to_lowercase().as_str() {
         "go" => {
             // Load and parse schema
-            let schema_content = std::fs::read_to_string(schema_file).map_err(|e| {
+            let schema_content = read_source(schema_file).map_err(|e| {
                 CliError::Io(io::Error::new(
                     e.kind(),
                     format!("schema file '{}': {}", schema_file, e),
@@ -756,105 +2077,294 @@ fn run_cache(open: bool, clear: bool) -> Result<(), CliError> {
     Ok(())
 }
 
+// ============================================================================
+// Check
+// ============================================================================
+
+/// Something `styx check` found wrong with a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CheckProblem {
+    /// The file could not be read or does not parse.
+    Syntax,
+    /// The file does not match its declared schema.
+    Validation,
+    /// The file is not formatted.
+    Format,
+}
+
+fn run_check(files: &[String], fix: bool, reporter: &mut Reporter) -> Result<(), CliError> {
+    if files.is_empty() {
+        return Err(CliError::Usage("check requires at least one file".into()));
+    }
+
+    let problems: Vec<Vec<CheckProblem>> = files
+        .iter()
+        .map(|file| check_file(file, fix, reporter))
+        .collect();
+    let summary = check_summary(&problems);
+    let has = |problem: CheckProblem| problems.iter().flatten().any(|p| *p == problem);
+
+    if has(CheckProblem::Syntax) {
+        Err(CliError::Parse(summary))
+    } else if has(CheckProblem::Validation) || has(CheckProblem::Format) {
+        Err(CliError::Validation(summary))
+    } else {
+        eprintln!("{summary}");
+        Ok(())
+    }
+}
+
+/// Check one file, reporting each problem as it is found.
+///
+/// Files that don't parse are not validated or formatted. Files without a
+/// `@schema` declaration are not validated. With `fix`, unformatted files are
+/// rewritten instead of reported.
+fn check_file(path: &str, fix: bool, reporter: &mut Reporter) -> Vec<CheckProblem> {
+    let source = match read_source(path) {
+        Ok(source) => source,
+        Err(e) => {
+            reporter.error(&CliError::Io(io::Error::new(
+                e.kind(),
+                format!("{path}: {e}"),
+            )));
+            return vec![CheckProblem::Syntax];
+        }
+    };
+    let value = match styx_tree::parse(&source) {
+        Ok(value) => value,
+        Err(error) => {
+            reporter.error(&CliError::ParseDiagnostic {
+                error,
+                source,
+                filename: path.to_string(),
+            });
+            return vec![CheckProblem::Syntax];
+        }
+    };
+
+    let mut problems = Vec::new();
+
+    let declares_schema = value
+        .as_object()
+        .is_some_and(|obj| obj.entries.iter().any(|e| e.key.is_schema_tag()));
+    if declares_schema
+        && let Err(e) = run_validation(&source, path, None, None, false, false, reporter)
+    {
+        reporter.error(&e);
+        problems.push(CheckProblem::Validation);
+    }
+
+    let formatted = format_source(&source, FormatOptions::default());
+    if formatted != source {
+        let fixed = fix
+            && std::fs::write(path, &formatted)
+                .map_err(|e| reporter.error(&CliError::Io(e)))
+                .is_ok();
+        if !fixed {
+            reporter.report(
+                vec![Diagnostic {
+                    file: Some(path.to_string()),
+                    span: None,
+                    positions: None,
+                    severity: "error",
+                    message: "file is not formatted".to_string(),
+                    code: "format",
+                }],
+                |_| eprintln!("{path}: file is not formatted (run `styx check --fix`)"),
+            );
+            problems.push(CheckProblem::Format);
+        }
+    }
+
+    problems
+}
+
+/// One-line summary of what `styx check` found, given each file's problems.
+fn check_summary(problems: &[Vec<CheckProblem>]) -> String {
+    let count = |problem| problems.iter().filter(|p| p.contains(&problem)).count();
+    let counts = [
+        (count(CheckProblem::Syntax), "with syntax errors"),
+        (count(CheckProblem::Validation), "failing validation"),
+        (count(CheckProblem::Format), "not formatted"),
+    ];
+    let files = match problems.len() {
+        1 => "1 file".to_string(),
+        n => format!("{n} files"),
+    };
+
+    let found: Vec<String> = counts
+        .iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, what)| format!("{n} {what}"))
+        .collect();
+    if found.is_empty() {
+        format!("checked {files}, no problems found")
+    } else {
+        format!("checked {files}: {}", found.join(", "))
+    }
+}
+
 // ============================================================================
 // Validation
 // ============================================================================
 
 fn run_validation(
-    value: &Value,
     source: &str,
     filename: &str,
-    override_schema: Option<&str>,
+    override_schema: Option<SchemaOverride<'_>>,
+    registry: Option<&SchemaRegistry>,
+    fail_fast: bool,
+    strict_separators: bool,
+    reporter: &mut Reporter,
 ) -> Result<(), CliError> {
-    let schema_file = if let Some(schema_path) = override_schema {
-        load_schema_file(schema_path)?
+    let parse_error = |error| CliError::ParseDiagnostic {
+        error,
+        source: source.to_string(),
+        filename: filename.to_string(),
+    };
+
+    // Events read while looking for the schema declaration, which are passed
+    // on to the validator so the document is only parsed once
+    let mut parser = Parser::new(source).strict_separators(strict_separators);
+    let mut read = Vec::new();
+    let schema_file = if let Some(schema) = override_schema {
+        match schema {
+            SchemaOverride::Path(path) => load_schema_file(path)?,
+            SchemaOverride::Stdin(source) => parse_schema_file(source, "schema from stdin")?,
+        }
     } else {
-        let schema_ref = find_schema_declaration(value)?;
-        match schema_ref {
-            SchemaRef::External(path) => {
-                let resolved = resolve_schema_path(&path, Some(filename))?;
-                load_schema_file(&resolved)?
-            }
-            SchemaRef::Embedded { id, cli } => extract_embedded_schema(&cli, id.as_deref())?,
+        let declaration = read_schema_declaration(&mut parser, &mut read).map_err(parse_error)?;
+        // Later schemas take precedence over earlier ones
+        let mut merged: Option<SchemaFile> = None;
+        for directive in find_schema_declaration(&declaration)? {
+            let schema = match directive {
+                SchemaDirective::External(path) => {
+                    let resolved = resolve_schema_path(&path, Some(filename))?;
+                    load_schema_file(&resolved)?
+                }
+                SchemaDirective::Embedded { id, cli } => match (registry, id.as_deref()) {
+                    // Fall back to the registry only if the binary is missing
+                    (Some(registry), Some(id)) if which::which(&cli).is_err() => {
+                        registry.fetch(id)?
+                    }
+                    (_, id) => extract_embedded_schema(&cli, id)?,
+                },
+            };
+            merged = Some(match merged {
+                Some(base) => {
+                    merge_schemas(base, schema).map_err(|e| CliError::Validation(e.to_string()))?
+                }
+                None => schema,
+            });
         }
+        merged.expect("a schema declaration lists at least one schema")
     };
 
-    let value_for_validation = strip_schema_declaration(value);
-    let result = validate(&value_for_validation, &schema_file);
-
+    let events = read
+        .into_iter()
+        .chain(std::iter::from_fn(|| parser.next_event()));
+    let result = StreamingValidator::new(&schema_file)
+        .fail_fast(fail_fast)
+        .validate_events(events)
+        .map_err(parse_error)?;
+
+    if !result.is_valid() || !result.warnings.is_empty() {
+        reporter.report(
+            Diagnostic::from_validation(filename, source, &result),
+            |max_errors| {
+                write_validation_report(&result, filename, source, max_errors, io::stderr())
+            },
+        );
+    }
     if !result.is_valid() {
-        result.write_report(filename, source, std::io::stderr());
         return Err(CliError::Validation(format!(
             "{} validation error(s)",
             result.errors.len()
         )));
     }
 
-    if !result.warnings.is_empty() {
-        result.write_report(filename, source, std::io::stderr());
-    }
-
     Ok(())
 }
 
-enum SchemaRef {
-    External(String),
-    Embedded { id: Option<String>, cli: String },
+/// Build a document holding only the root `@schema` entry.
+///
+/// Stops reading at the end of that entry, so the rest of the document is
+/// left to the streaming validator.
+fn parse_schema_declaration(source: &str) -> Result<Value, BuildError> {
+    read_schema_declaration(&mut Parser::new(source), &mut Vec::new())
 }
 
-fn strip_schema_declaration(value: &Value) -> Value {
-    if let Some(obj) = value.as_object() {
-        let filtered_entries: Vec<_> = obj
-            .entries
-            .iter()
-            .filter(|e| !e.key.is_schema_tag())
-            .cloned()
-            .collect();
-        Value {
-            tag: value.tag.clone(),
-            payload: Some(Payload::Object(styx_tree::Object {
-                entries: filtered_entries,
-                span: obj.span,
-            })),
-            span: value.span,
+/// Like [`parse_schema_declaration`], reading from `parser` and keeping every
+/// event it reads in `read`.
+fn read_schema_declaration<'src>(
+    parser: &mut Parser<'src>,
+    read: &mut Vec<Event<'src>>,
+) -> Result<Value, BuildError> {
+    let mut builder = TreeBuilder::new();
+    let mut depth = 0usize;
+    let mut entry_start = None;
+    let mut in_declaration = false;
+
+    while let Some(event) = parser.next_event() {
+        if let EventKind::Error { kind } = event.kind {
+            return Err(BuildError::Parse(kind, event.span));
+        }
+        read.push(event.clone());
+
+        let at_root = depth == 1;
+        match event.kind {
+            EventKind::ObjectStart | EventKind::SequenceStart => depth += 1,
+            EventKind::ObjectEnd | EventKind::SequenceEnd => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+
+        if in_declaration {
+            let is_end = at_root && matches!(event.kind, EventKind::EntryEnd);
+            builder.event(event);
+            if is_end {
+                break;
+            }
+            continue;
+        }
+
+        match event.kind {
+            EventKind::EntryStart if at_root => entry_start = Some(event),
+            EventKind::Key {
+                tag: Some("schema"),
+                ..
+            } if entry_start.is_some() => {
+                builder.event(entry_start.take().unwrap());
+                builder.event(event);
+                in_declaration = true;
+            }
+            _ => entry_start = None,
         }
-    } else {
-        value.clone()
     }
+
+    builder.finish()
 }
 
-fn find_schema_declaration(value: &Value) -> Result<SchemaRef, CliError> {
+fn find_schema_declaration(value: &Value) -> Result<Vec<SchemaDirective>, CliError> {
     let obj = value.as_object().ok_or_else(|| {
         CliError::Validation("document root must be an object for validation".into())
     })?;
 
     for entry in &obj.entries {
         if entry.key.is_schema_tag() {
-            if let Some(path) = entry.value.as_str() {
-                return Ok(SchemaRef::External(path.to_string()));
+            if let Some(directives) = SchemaDirective::list_from_value(&entry.value) {
+                return Ok(directives);
             }
 
-            if let Some(schema_obj) = entry.value.as_object() {
-                if let Some(cli_value) = schema_obj.get("cli")
-                    && let Some(cli_name) = cli_value.as_str()
-                {
-                    // Also extract the optional schema ID
-                    let id = schema_obj
-                        .get("id")
-                        .and_then(|v| v.as_str())
-                        .map(|s| s.to_string());
-                    return Ok(SchemaRef::Embedded {
-                        id,
-                        cli: cli_name.to_string(),
-                    });
-                }
+            if entry.value.as_object().is_some() {
                 return Err(CliError::Validation(
                     "@schema directive must have a 'cli' field with the binary name".into(),
                 ));
             }
 
             return Err(CliError::Validation(
-                "@schema directive must be a path or an object with {id ..., cli ...}".into(),
+                "@schema directive must be a path, an object with {id ..., cli ...}, \
+                 or a sequence of those"
+                    .into(),
             ));
         }
     }
@@ -865,6 +2375,45 @@ fn find_schema_declaration(value: &Value) -> Result<SchemaRef, CliError> {
     ))
 }
 
+/// A schema named by a `@schema` directive, with where it was found.
+struct ResolvedSchema {
+    /// The schema file path, or the binary and schema id it was extracted from.
+    origin: String,
+    source: String,
+}
+
+/// Find the schemas a document declares, without parsing them.
+fn resolve_declared_schemas(source: &str, filename: &str) -> Result<Vec<ResolvedSchema>, CliError> {
+    let declaration =
+        parse_schema_declaration(source).map_err(|error| CliError::ParseDiagnostic {
+            error,
+            source: source.to_string(),
+            filename: filename.to_string(),
+        })?;
+
+    find_schema_declaration(&declaration)?
+        .into_iter()
+        .map(|directive| match directive {
+            SchemaDirective::External(path) => {
+                let path = resolve_schema_path(&path, Some(filename))?;
+                let source = read_schema_file(&path)?;
+                Ok(ResolvedSchema {
+                    origin: path,
+                    source,
+                })
+            }
+            SchemaDirective::Embedded { id, cli } => {
+                let (binary, source) = find_embedded_schema(&cli, id.as_deref())?;
+                let origin = match id {
+                    Some(id) => format!("embedded schema '{id}' in {}", binary.display()),
+                    None => format!("first embedded schema in {}", binary.display()),
+                };
+                Ok(ResolvedSchema { origin, source })
+            }
+        })
+        .collect()
+}
+
 fn resolve_schema_path(schema_path: &str, input_path: Option<&str>) -> Result<String, CliError> {
     if schema_path.starts_with("http://") || schema_path.starts_with("https://") {
         return Err(CliError::Usage(
@@ -888,21 +2437,40 @@ fn resolve_schema_path(schema_path: &str, input_path: Option<&str>) -> Result<St
 }
 
 fn load_schema_file(path: &str) -> Result<SchemaFile, CliError> {
-    let source = std::fs::read_to_string(path).map_err(|e| {
+    let source = read_schema_file(path)?;
+    facet_styx::from_str(&source)
+        .map_err(|e| CliError::Parse(format!("failed to parse schema '{}': {}", path, e)))
+}
+
+fn read_schema_file(path: &str) -> Result<String, CliError> {
+    read_source(path).map_err(|e| {
         CliError::Io(io::Error::new(
             e.kind(),
             format!("schema file '{}': {}", path, e),
         ))
-    })?;
-
-    facet_styx::from_str(&source)
-        .map_err(|e| CliError::Parse(format!("failed to parse schema '{}': {}", path, e)))
+    })
 }
 
 fn extract_embedded_schema(
     cli_name: &str,
     schema_id: Option<&str>,
 ) -> Result<SchemaFile, CliError> {
+    let (binary_path, schema_source) = find_embedded_schema(cli_name, schema_id)?;
+    facet_styx::from_str(&schema_source).map_err(|e| {
+        CliError::Parse(format!(
+            "failed to parse embedded schema from '{}': {}",
+            binary_path.display(),
+            e
+        ))
+    })
+}
+
+/// Find the binary `cli_name` and the source of its embedded schema with
+/// id `schema_id`, or of its first embedded schema.
+fn find_embedded_schema(
+    cli_name: &str,
+    schema_id: Option<&str>,
+) -> Result<(std::path::PathBuf, String), CliError> {
     let binary_path = which::which(cli_name).map_err(|_| {
         CliError::Validation(format!(
             "binary '{}' not found in PATH\nhint: ensure the binary is installed and in your PATH",
@@ -929,33 +2497,11 @@ fn extract_embedded_schema(
     let schema_source = if let Some(target_id) = schema_id {
         schemas
             .iter()
-            .find(|schema| {
-                // Parse the schema to check its meta.id
-                if let Ok(parsed) = styx_tree::parse(schema)
-                    && let Some(obj) = parsed.as_object()
-                    && let Some(meta) = obj.get("meta")
-                    && let Some(meta_obj) = meta.as_object()
-                    && let Some(id_value) = meta_obj.get("id")
-                    && let Some(id) = id_value.as_str()
-                {
-                    return id == target_id;
-                }
-                false
-            })
+            .find(|schema| schema_id(schema).as_deref() == Some(target_id))
             .ok_or_else(|| {
                 let available_ids: Vec<_> = schemas
                     .iter()
-                    .filter_map(|schema| {
-                        styx_tree::parse(schema).ok().and_then(|parsed| {
-                            parsed.as_object().and_then(|obj| {
-                                obj.get("meta").and_then(|meta| {
-                                    meta.as_object()
-                                        .and_then(|m| m.get("id").and_then(|v| v.as_str()))
-                                        .map(|s| s.to_string())
-                                })
-                            })
-                        })
-                    })
+                    .filter_map(|schema| schema_id(schema))
                     .collect();
                 CliError::Validation(format!(
                     "schema '{}' not found in '{}'\navailable schemas: {}",
@@ -966,16 +2512,10 @@ fn extract_embedded_schema(
             })?
     } else {
         // No ID specified, use the first schema
-        &schemas[0]
-    };
-
-    facet_styx::from_str(schema_source).map_err(|e| {
-        CliError::Parse(format!(
-            "failed to parse embedded schema from '{}': {}",
-            binary_path.display(),
-            e
-        ))
-    })
+        &schemas[0]
+    };
+
+    Ok((binary_path, schema_source.clone()))
 }
 
 // ============================================================================
@@ -985,14 +2525,32 @@ fn extract_embedded_schema(
 fn read_input(file: Option<&str>) -> Result<String, io::Error> {
     match file {
         Some("-") | None => {
-            let mut buf = String::new();
-            io::stdin().read_to_string(&mut buf)?;
-            Ok(buf)
+            let mut buf = Vec::new();
+            io::stdin().read_to_end(&mut buf)?;
+            decode_source(buf)
         }
-        Some(path) => std::fs::read_to_string(path),
+        Some(path) => read_source(path),
     }
 }
 
+/// Read a file as styx source, reporting where invalid UTF-8 starts.
+fn read_source(path: impl AsRef<Path>) -> Result<String, io::Error> {
+    decode_source(std::fs::read(path)?)
+}
+
+fn decode_source(bytes: Vec<u8>) -> Result<String, io::Error> {
+    String::from_utf8(bytes).map_err(|e| {
+        let e = e.utf8_error();
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "invalid UTF-8 at byte {}; styx files must be UTF-8",
+                e.valid_up_to()
+            ),
+        )
+    })
+}
+
 fn write_output(path: &str, content: &str) -> Result<(), io::Error> {
     if path == "-" {
         print!("{content}");
@@ -1376,7 +2934,10 @@ fn value_to_json(value: &Value) -> serde_json::Value {
 
 fn payload_to_json(payload: &Payload) -> serde_json::Value {
     match payload {
-        Payload::Scalar(s) => serde_json::Value::String(s.text.clone()),
+        Payload::Scalar(s) => match json_number(&s.text) {
+            Some(n) if s.kind == ScalarKind::Bare => serde_json::Value::Number(n),
+            _ => serde_json::Value::String(s.text.clone()),
+        },
         Payload::Sequence(s) => {
             serde_json::Value::Array(s.items.iter().map(value_to_json).collect())
         }
@@ -1397,6 +2958,297 @@ fn payload_to_json(payload: &Payload) -> serde_json::Value {
     }
 }
 
+/// The JSON number a bare scalar spells, if it is spelled canonically.
+///
+/// See [`styx_tree::parse_canonical_number`]: `01234`, `+5` and `1e3` stay
+/// strings so no text is lost, as do `inf`, `NaN` and integers past 64 bits.
+fn json_number(text: &str) -> Option<serde_json::Number> {
+    match styx_tree::parse_canonical_number(text)? {
+        CanonicalNumber::Int(n) => Some(n.into()),
+        CanonicalNumber::UInt(n) => Some(n.into()),
+        CanonicalNumber::Float(f) => serde_json::Number::from_f64(f),
+    }
+}
+
+// ============================================================================
+// Env export and import
+// ============================================================================
+
+fn run_export_env(file: &str, prefix: Option<&str>) -> Result<(), CliError> {
+    let source = read_input(Some(file))?;
+    let value = styx_tree::parse(&source).map_err(|e| CliError::ParseDiagnostic {
+        error: e,
+        source: source.clone(),
+        filename: file.to_string(),
+    })?;
+
+    for line in env_lines(&value, prefix) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// One `NAME=value` line per leaf of `value`.
+///
+/// Names are the leaf's path, uppercased, with every run of other
+/// characters than letters, digits and `_` replaced by a single `_`, so
+/// `db.hosts[0]` becomes `DB_HOSTS_0`. Values are quoted for the shell when
+/// they need to be.
+fn env_lines(value: &Value, prefix: Option<&str>) -> Vec<String> {
+    value
+        .leaves()
+        .map(|(path, leaf)| {
+            let name = match prefix {
+                Some(prefix) => env_name(&format!("{prefix}_{path}")),
+                None => env_name(&path),
+            };
+            let text = match (&leaf.tag, &leaf.payload) {
+                (None, Some(Payload::Scalar(scalar))) => scalar.text.clone(),
+                _ => format_value(leaf, FormatOptions::default().inline())
+                    .trim_end()
+                    .to_string(),
+            };
+            format!("{name}={}", shell_quote(&text))
+        })
+        .collect()
+}
+
+fn env_name(path: &str) -> String {
+    path.split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+        .to_uppercase()
+}
+
+/// Quote `text` in single quotes unless every character is safe unquoted.
+fn shell_quote(text: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-.,:/@%+=".contains(c);
+    if !text.is_empty() && text.chars().all(safe) {
+        return text.to_string();
+    }
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+fn run_import_env(prefix: &str, map: Option<&str>, output: Option<&str>) -> Result<(), CliError> {
+    let paths = match map {
+        Some(file) => {
+            let source = read_input(Some(file))?;
+            let value = styx_tree::parse(&source).map_err(|e| CliError::ParseDiagnostic {
+                error: e,
+                source: source.clone(),
+                filename: file.to_string(),
+            })?;
+            env_paths(&value)?
+        }
+        None => HashMap::new(),
+    };
+
+    let vars: Vec<(String, String)> = std::env::vars().collect();
+    let document = env_document(prefix, &vars, &paths)?;
+    let formatted = format_value(&document, FormatOptions::default());
+    match output {
+        Some(path) => write_output(path, &formatted)?,
+        None => print_styx(&formatted),
+    }
+    Ok(())
+}
+
+/// Read a `--map` document of `NAME path.to.value` entries.
+fn env_paths(map: &Value) -> Result<HashMap<String, String>, CliError> {
+    let Some(obj) = map.as_object() else {
+        return Err(CliError::Usage("env map must be an object".into()));
+    };
+    obj.iter()
+        .map(|(name, path)| match (name.as_str(), path.as_str()) {
+            (Some(name), Some(path)) => Ok((name.to_string(), path.to_string())),
+            _ => Err(CliError::Usage(
+                "env map entries must be `NAME path.to.value`".into(),
+            )),
+        })
+        .collect()
+}
+
+/// Build a document from the variables in `vars` starting with `{prefix}_`.
+///
+/// The rest of each name is lowercased and split on `_` into a path, unless
+/// `paths` gives the path for that name. Objects whose keys are the indices
+/// `0` to `n`, like those from `HOSTS_0` and `HOSTS_1`, become sequences.
+fn env_document(
+    prefix: &str,
+    vars: &[(String, String)],
+    paths: &HashMap<String, String>,
+) -> Result<Value, CliError> {
+    let mut vars: Vec<_> = vars
+        .iter()
+        .filter(|(name, _)| {
+            name.strip_prefix(prefix)
+                .and_then(|rest| rest.strip_prefix('_'))
+                .is_some_and(|rest| !rest.is_empty())
+        })
+        .collect();
+    vars.sort();
+
+    let mut document = Value::object();
+    for (name, text) in vars {
+        let path: Vec<String> = match paths.get(name) {
+            Some(path) => path.split('.').map(String::from).collect(),
+            None => name[prefix.len() + 1..]
+                .split('_')
+                .filter(|segment| !segment.is_empty())
+                .map(str::to_lowercase)
+                .collect(),
+        };
+        let conflict = || {
+            CliError::Usage(format!(
+                "{name} conflicts with another variable; give its path with --map"
+            ))
+        };
+        let Some((key, parents)) = path.split_last() else {
+            return Err(conflict());
+        };
+
+        let mut current = &mut document;
+        for segment in parents {
+            let obj = current.as_object_mut().ok_or_else(conflict)?;
+            if !obj.contains_key(segment) {
+                obj.insert(segment.clone(), Value::object());
+            }
+            current = obj.get_mut(segment).ok_or_else(conflict)?;
+        }
+        let obj = current.as_object_mut().ok_or_else(conflict)?;
+        if obj.contains_key(key) {
+            return Err(conflict());
+        }
+        obj.insert(key.clone(), Value::scalar(text.as_str()));
+    }
+
+    document.visit_mut(|value| {
+        let Some(obj) = value.as_object() else {
+            return;
+        };
+        let indices: Option<Vec<usize>> = obj
+            .entries
+            .iter()
+            .map(|entry| entry.key.as_str()?.parse().ok())
+            .collect();
+        let Some(mut indices) = indices.filter(|indices| !indices.is_empty()) else {
+            return;
+        };
+        indices.sort();
+        if !indices.iter().copied().eq(0..indices.len()) {
+            return;
+        }
+        if let Some(Payload::Object(mut obj)) = value.payload.take() {
+            obj.entries
+                .sort_by_key(|entry| entry.key.as_str().and_then(|key| key.parse::<usize>().ok()));
+            *value = Value::seq(obj.entries.into_iter().map(|entry| entry.value).collect());
+        }
+    });
+    Ok(document)
+}
+
+// ============================================================================
+// REPL
+// ============================================================================
+
+fn run_repl(format: &str) -> Result<(), CliError> {
+    if !matches!(format, "styx" | "json") {
+        return Err(CliError::Usage(format!(
+            "unknown format '{format}', expected 'styx' or 'json'"
+        )));
+    }
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    if interactive {
+        eprintln!("styx {VERSION} - enter entries or values, `.get <path>` to query");
+    }
+    repl(stdin.lock(), io::stdout().lock(), format, interactive)?;
+    Ok(())
+}
+
+/// Read lines from `input` and print what each one parses to.
+///
+/// Lines that are entries, like `server.port 8080`, are added to a document
+/// built up over the session. Other lines are read as a single value, like
+/// `(1 2 3)`, and only printed. `.get <path>` prints the value at `path` in
+/// the document, and `.get` alone the whole document.
+fn repl(
+    input: impl io::BufRead,
+    mut output: impl Write,
+    format: &str,
+    prompt: bool,
+) -> io::Result<()> {
+    let mut document = Value::object();
+    let print = |output: &mut dyn Write, value: &Value| -> io::Result<()> {
+        if format == "json" {
+            let json = serde_json::to_string_pretty(&value_to_json(value)).unwrap_or_default();
+            writeln!(output, "{json}")
+        } else {
+            let styx = format_value(value, FormatOptions::default());
+            writeln!(output, "{}", styx.trim_end())
+        }
+    };
+
+    if prompt {
+        eprint!("> ");
+    }
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if let Some(path) = line
+            .strip_prefix(".get")
+            .filter(|rest| rest.is_empty() || rest.starts_with(' '))
+        {
+            let path = path.trim();
+            match document.get(path) {
+                Some(value) => print(&mut output, value)?,
+                None => writeln!(output, "error: no value at '{path}'")?,
+            }
+        } else if line.starts_with('.') {
+            writeln!(
+                output,
+                "error: unknown command '{line}', expected `.get <path>`"
+            )?;
+        } else if !line.is_empty() {
+            match styx_tree::parse(line) {
+                Ok(entries) => {
+                    print(&mut output, &entries)?;
+                    document.merge(entries);
+                }
+                Err(e) => match parse_expression(line) {
+                    Some(value) => print(&mut output, &value)?,
+                    None => writeln!(output, "error: {e}")?,
+                },
+            }
+        }
+        if prompt {
+            eprint!("> ");
+        }
+    }
+    Ok(())
+}
+
+/// Parse a line holding a single value, like `(1 2 3)` or `@tag{x 1}`.
+fn parse_expression(line: &str) -> Option<Value> {
+    let mut parser = styx_parse::Parser::new_expr(line);
+    let mut builder = styx_tree::TreeBuilder::new();
+    while let Some(event) = parser.next_event() {
+        builder.event(event);
+    }
+    let mut value = builder.finish().ok()?;
+    // The builder holds a lone value as the `@` entry of an object
+    if let Some(obj) = value.as_object_mut()
+        && obj.len() == 1
+        && let Some(inner) = obj.get_unit_mut()
+    {
+        value = std::mem::replace(inner, Value::unit());
+    }
+    // Expression mode stops after the value, so make sure it is the whole line
+    let end = value.span.map_or(line.len(), |span| span.end as usize);
+    (end == line.len()).then_some(value)
+}
+
 // ============================================================================
 // Package command
 // ============================================================================
@@ -1406,28 +3258,32 @@ fn run_package(
     name: &str,
     version: &str,
     output: Option<&str>,
+    format: &str,
 ) -> Result<(), CliError> {
+    if format != "text" && format != "json" {
+        return Err(CliError::Usage(format!(
+            "unknown format '{format}', expected 'text' or 'json'"
+        )));
+    }
+
     let output_dir = output.unwrap_or(name);
     let output_path = Path::new(output_dir);
 
-    let schema_content = std::fs::read_to_string(schema_file)
+    let schema_content = read_source(schema_file)
         .map_err(|e| CliError::Io(io::Error::new(e.kind(), format!("{schema_file}: {e}"))))?;
 
     styx_tree::parse(&schema_content)
         .map_err(|e| CliError::Parse(format!("invalid schema: {e}")))?;
 
-    std::fs::create_dir_all(output_path.join("src"))?;
-
-    let cargo_toml = generate_cargo_toml(name, version);
-    std::fs::write(output_path.join("Cargo.toml"), cargo_toml)?;
-
-    let lib_rs = generate_lib_rs(name);
-    std::fs::write(output_path.join("src/lib.rs"), lib_rs)?;
+    let files = write_schema_crate(output_path, name, version, &schema_content)?;
 
-    let readme = generate_readme(name);
-    std::fs::write(output_path.join("README.md"), readme)?;
-
-    std::fs::write(output_path.join("schema.styx"), &schema_content)?;
+    if format == "json" {
+        let report = package_to_json(name, version, output_path, &files);
+        let output =
+            serde_json::to_string_pretty(&report).map_err(|e| CliError::Io(io::Error::other(e)))?;
+        println!("{output}");
+        return Ok(());
+    }
 
     eprintln!("Created crate in {output_dir}/");
     eprintln!();
@@ -1437,6 +3293,39 @@ fn run_package(
     Ok(())
 }
 
+/// Describe a generated crate as `{name, version, dir, files}`.
+fn package_to_json(name: &str, version: &str, dir: &Path, files: &[PathBuf]) -> serde_json::Value {
+    serde_json::json!({
+        "name": name,
+        "version": version,
+        "dir": dir.display().to_string(),
+        "files": files.iter().map(|f| f.display().to_string()).collect::<Vec<_>>(),
+    })
+}
+
+/// Write a schema crate's files into `dir`, returning their paths.
+fn write_schema_crate(
+    dir: &Path,
+    name: &str,
+    version: &str,
+    schema_content: &str,
+) -> io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir.join("src"))?;
+    let files = [
+        ("Cargo.toml", generate_cargo_toml(name, version)),
+        ("src/lib.rs", generate_lib_rs(name)),
+        ("README.md", generate_readme(name)),
+        ("schema.styx", schema_content.to_string()),
+    ];
+    let mut paths = Vec::new();
+    for (file, content) in files {
+        let path = dir.join(file);
+        std::fs::write(&path, content)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
 fn generate_cargo_toml(name: &str, version: &str) -> String {
     format!(
         r#"[package]
@@ -1492,120 +3381,198 @@ const STAGING_INDEX: &str = "sparse+https://index.staging.crates.io/";
 const STAGING_API: &str = "https://staging.crates.io/api/v1/crates";
 const STAGING_DOWNLOAD: &str = "https://static.staging.crates.io/crates";
 
-fn run_publish(schema_file: &str, yes: bool) -> Result<(), CliError> {
-    let token = std::env::var("STYX_STAGING_TOKEN").ok();
-    if token.is_none() {
-        return Err(CliError::Usage(
-            "STYX_STAGING_TOKEN environment variable not set".into(),
-        ));
+/// A crate registry that schema crates are published to and diffed against.
+#[derive(Debug, Clone, PartialEq)]
+struct Registry {
+    /// The name cargo knows the registry by.
+    name: String,
+    /// The index URL, needed to publish.
+    index: Option<String>,
+    /// The crates API URL, which crate info is fetched from.
+    api: String,
+    /// The URL crate downloads are fetched from.
+    download: String,
+}
+
+impl Registry {
+    fn staging() -> Self {
+        Registry {
+            name: "staging".into(),
+            index: Some(STAGING_INDEX.into()),
+            api: STAGING_API.into(),
+            download: STAGING_DOWNLOAD.into(),
+        }
     }
 
-    let schema_content = std::fs::read_to_string(schema_file)
-        .map_err(|e| CliError::Io(io::Error::new(e.kind(), format!("{schema_file}: {e}"))))?;
+    /// The registry chosen by the `--registry`, `--index` and `--api`
+    /// options. Registries other than staging need an API URL, and crates
+    /// are downloaded through it.
+    fn from_options(
+        name: Option<String>,
+        index: Option<String>,
+        api: Option<String>,
+    ) -> Result<Self, CliError> {
+        let mut registry = match name.as_deref() {
+            None | Some("staging") => Registry::staging(),
+            Some(name) if !is_valid_registry_name(name) => {
+                return Err(CliError::Usage(format!(
+                    "invalid registry name '{name}': registry names are letters, digits, \
+                     '-' and '_', and start with a letter or '_'"
+                )));
+            }
+            Some(name) => {
+                let api = api.clone().ok_or_else(|| {
+                    CliError::Usage(format!("--api is required for registry '{name}'"))
+                })?;
+                Registry {
+                    name: name.to_string(),
+                    index: None,
+                    download: api.clone(),
+                    api,
+                }
+            }
+        };
+        if let Some(index) = index {
+            registry.index = Some(index);
+        }
+        if let Some(api) = api {
+            registry.api = api;
+        }
+        Ok(registry)
+    }
 
-    let local_tree = styx_tree::parse(&schema_content)
-        .map_err(|e| CliError::Parse(format!("invalid schema: {e}")))?;
+    fn is_staging(&self) -> bool {
+        self.name == "staging"
+    }
 
-    let name = extract_meta_field(&local_tree, "crate").ok_or_else(|| {
-        CliError::Usage("schema must have meta.crate field for publishing".into())
-    })?;
+    /// The environment variable holding the token for publishing.
+    fn token_var(&self) -> String {
+        if self.is_staging() {
+            return "STYX_STAGING_TOKEN".into();
+        }
+        self.cargo_token_var()
+    }
 
-    let (version, _changes) = match fetch_latest_version(&name) {
-        Ok(latest_version) => {
-            eprintln!("Found {name}@{latest_version} on staging.crates.io");
-            eprintln!();
+    /// The environment variable cargo reads the token from.
+    fn cargo_token_var(&self) -> String {
+        format!(
+            "CARGO_REGISTRIES_{}_TOKEN",
+            self.name.to_uppercase().replace('-', "_")
+        )
+    }
 
-            let baseline_content = fetch_crate_schema(&name, &latest_version)?;
-            let baseline_tree = styx_tree::parse(&baseline_content)
-                .map_err(|e| CliError::Parse(format!("invalid baseline schema: {e}")))?;
+    fn crate_url(&self, crate_name: &str) -> String {
+        format!("{}/{crate_name}", self.api.trim_end_matches('/'))
+    }
 
-            let changes = compare_schemas(&baseline_tree, &local_tree);
+    fn download_url(&self, crate_name: &str, version: &str) -> String {
+        format!(
+            "{}/{crate_name}/{version}/download",
+            self.download.trim_end_matches('/')
+        )
+    }
 
-            if changes.breaking.is_empty()
-                && changes.additive.is_empty()
-                && changes.patch.is_empty()
-            {
-                eprintln!("No changes detected from {latest_version}.");
-                return Err(CliError::Usage("nothing to publish".into()));
-            }
+    /// The `.cargo/config.toml` that lets cargo publish to this registry.
+    fn cargo_config(&self) -> Result<String, CliError> {
+        let index = self.index.as_deref().ok_or_else(|| {
+            CliError::Usage(format!("--index is required for registry '{}'", self.name))
+        })?;
+        // A JSON string is also a valid TOML basic string
+        let index = serde_json::Value::from(index);
+        Ok(format!("[registries.{}]\nindex = {index}\n", self.name))
+    }
+}
 
-            if !changes.breaking.is_empty() {
-                eprintln!("Breaking changes:");
-                for change in &changes.breaking {
-                    eprintln!("  - {change}");
-                }
-            }
+/// Whether cargo accepts `name` as a registry name, which also makes it a
+/// bare TOML key.
+fn is_valid_registry_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
 
-            if !changes.additive.is_empty() {
-                eprintln!("Additive changes:");
-                for change in &changes.additive {
-                    eprintln!("  + {change}");
-                }
-            }
+impl std::fmt::Display for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_staging() {
+            f.write_str("staging.crates.io")
+        } else {
+            write!(f, "registry '{}'", self.name)
+        }
+    }
+}
 
-            if !changes.patch.is_empty() {
-                eprintln!("Patch changes:");
-                for change in &changes.patch {
-                    eprintln!("  ~ {change}");
-                }
-            }
+fn run_publish(
+    schema_file: &str,
+    yes: bool,
+    dry_run: bool,
+    registry: &Registry,
+) -> Result<(), CliError> {
+    let token_var = registry.token_var();
+    let token = std::env::var(&token_var).ok();
+    if token.is_none() && !dry_run {
+        return Err(CliError::Usage(format!(
+            "{token_var} environment variable not set"
+        )));
+    }
 
-            let next_version = calculate_next_version(&latest_version, &changes)?;
-            eprintln!();
-            eprintln!("Version bump: {latest_version} -> {next_version}");
+    let schema_content = read_source(schema_file)
+        .map_err(|e| CliError::Io(io::Error::new(e.kind(), format!("{schema_file}: {e}"))))?;
 
-            (next_version, Some(changes))
-        }
-        Err(_) => {
-            eprintln!("No existing version found - this will be the first publish.");
-            ("0.1.0".to_string(), None)
+    let meta = facet_styx::parse_meta(&schema_content)
+        .map_err(|e| CliError::Parse(format!("invalid schema: {e}")))?;
+
+    let name = meta.crate_name.ok_or_else(|| {
+        CliError::Usage("schema must have meta.crate field for publishing".into())
+    })?;
+
+    let latest = match fetch_latest_version(registry, &name) {
+        Ok(latest_version) => {
+            eprintln!("Found {name}@{latest_version} on {registry}");
+            eprintln!();
+            let baseline_content = fetch_crate_schema(registry, &name, &latest_version)?;
+            Some((latest_version, baseline_content))
         }
+        Err(_) => None,
     };
 
+    let temp_dir = std::env::temp_dir().join(format!("styx-publish-{name}-{}", std::process::id()));
+    let plan = prepare_publish(&schema_content, &name, latest, registry, &temp_dir, dry_run)?;
+    let version = &plan.version;
+
     eprintln!();
 
+    // The generated crate is kept after a dry run so it can be inspected
+    if dry_run {
+        println!("version: {version}");
+        println!("crate: {}", plan.dir.display());
+        eprintln!("Dry run, not publishing {name}@{version}.");
+        return Ok(());
+    }
+
     if !yes {
-        eprint!("Publish {name}@{version} to staging.crates.io? [y/N] ");
+        eprint!("Publish {name}@{version} to {registry}? [y/N] ");
         let mut input = String::new();
         io::stdin().read_line(&mut input)?;
         if !input.trim().eq_ignore_ascii_case("y") {
+            let _ = std::fs::remove_dir_all(&plan.dir);
             eprintln!("Aborted.");
             return Ok(());
         }
     }
 
-    let temp_dir = std::env::temp_dir().join(format!("styx-publish-{name}-{}", std::process::id()));
-    std::fs::create_dir_all(temp_dir.join("src"))?;
-    std::fs::create_dir_all(temp_dir.join(".cargo"))?;
-
-    let cargo_toml = generate_cargo_toml(&name, &version);
-    std::fs::write(temp_dir.join("Cargo.toml"), cargo_toml)?;
-
-    let lib_rs = generate_lib_rs(&name);
-    std::fs::write(temp_dir.join("src/lib.rs"), lib_rs)?;
-
-    let readme = generate_readme(&name);
-    std::fs::write(temp_dir.join("README.md"), readme)?;
-
-    std::fs::write(temp_dir.join("schema.styx"), &schema_content)?;
-
-    let cargo_config = format!(
-        r#"[registries.staging]
-index = "{STAGING_INDEX}"
-"#
-    );
-    std::fs::write(temp_dir.join(".cargo/config.toml"), cargo_config)?;
-
     eprintln!("Publishing {name}@{version}...");
 
     let mut cmd = std::process::Command::new("cargo");
     cmd.arg("publish")
         .arg("--registry")
-        .arg("staging")
-        .current_dir(&temp_dir);
+        .arg(&registry.name)
+        .current_dir(&plan.dir);
 
     if let Some(token) = token {
-        cmd.env("CARGO_REGISTRIES_STAGING_TOKEN", token);
+        cmd.env(registry.cargo_token_var(), token);
     }
 
     let status = cmd.status().map_err(|e| {
@@ -1615,10 +3582,10 @@ index = "{STAGING_INDEX}"
         ))
     })?;
 
-    let _ = std::fs::remove_dir_all(&temp_dir);
+    let _ = std::fs::remove_dir_all(&plan.dir);
 
     if status.success() {
-        eprintln!("Published {name}@{version} to staging.crates.io");
+        eprintln!("Published {name}@{version} to {registry}");
         Ok(())
     } else {
         Err(CliError::Usage(format!(
@@ -1628,26 +3595,92 @@ index = "{STAGING_INDEX}"
     }
 }
 
-fn extract_meta_field(value: &Value, field: &str) -> Option<String> {
-    if let Some(Payload::Object(obj)) = &value.payload {
-        for entry in &obj.entries {
-            if entry.key.as_str() == Some("meta")
-                && let Some(Payload::Object(meta_obj)) = &entry.value.payload
-            {
-                for meta_entry in &meta_obj.entries {
-                    if meta_entry.key.as_str() == Some(field)
-                        && let Some(Payload::Scalar(s)) = &meta_entry.value.payload
-                    {
-                        return Some(s.text.clone());
-                    }
+/// A schema crate generated and ready to publish.
+struct PublishPlan {
+    /// The version the crate will be published as.
+    version: String,
+    /// The directory holding the generated crate.
+    dir: PathBuf,
+}
+
+/// Work out the next version of crate `name` and generate it in `dir`.
+///
+/// `latest` is the latest published version and its schema, if any. The
+/// changes from it are printed and decide the version bump. A dry run leaves
+/// out the registry config if the registry has no index, since nothing is
+/// published.
+fn prepare_publish(
+    schema_content: &str,
+    name: &str,
+    latest: Option<(String, String)>,
+    registry: &Registry,
+    dir: &Path,
+    dry_run: bool,
+) -> Result<PublishPlan, CliError> {
+    let cargo_config = match registry.cargo_config() {
+        Ok(cargo_config) => Some(cargo_config),
+        Err(_) if dry_run => None,
+        Err(e) => return Err(e),
+    };
+    let local_schema = parse_schema_file(schema_content, "schema")?;
+
+    let version = match latest {
+        Some((latest_version, baseline_content)) => {
+            let baseline_schema = parse_schema_file(&baseline_content, "baseline schema")?;
+
+            let changes = compatibility(&baseline_schema, &local_schema);
+
+            if changes.is_empty() {
+                eprintln!("No changes detected from {latest_version}.");
+                return Err(CliError::Usage("nothing to publish".into()));
+            }
+
+            if !changes.breaking.is_empty() {
+                eprintln!("Breaking changes:");
+                for change in &changes.breaking {
+                    eprintln!("  - {change}");
+                }
+            }
+
+            if !changes.additive.is_empty() {
+                eprintln!("Additive changes:");
+                for change in &changes.additive {
+                    eprintln!("  + {change}");
                 }
             }
+
+            if !changes.patch.is_empty() {
+                eprintln!("Patch changes:");
+                for change in &changes.patch {
+                    eprintln!("  ~ {change}");
+                }
+            }
+
+            let next_version = calculate_next_version(&latest_version, &changes)?;
+            eprintln!();
+            eprintln!("Version bump: {latest_version} -> {next_version}");
+            next_version
+        }
+        None => {
+            eprintln!("No existing version found - this will be the first publish.");
+            "0.1.0".to_string()
         }
+    };
+
+    write_schema_crate(dir, name, &version, schema_content)?;
+
+    if let Some(cargo_config) = cargo_config {
+        std::fs::create_dir_all(dir.join(".cargo"))?;
+        std::fs::write(dir.join(".cargo/config.toml"), cargo_config)?;
     }
-    None
+
+    Ok(PublishPlan {
+        version,
+        dir: dir.to_path_buf(),
+    })
 }
 
-fn calculate_next_version(current: &str, changes: &SchemaChanges) -> Result<String, CliError> {
+fn calculate_next_version(current: &str, changes: &Compatibility) -> Result<String, CliError> {
     let parts: Vec<&str> = current.split('.').collect();
     if parts.len() != 3 {
         return Err(CliError::Parse(format!("invalid version: {current}")));
@@ -1682,28 +3715,51 @@ fn calculate_next_version(current: &str, changes: &SchemaChanges) -> Result<Stri
 // Diff command
 // ============================================================================
 
-fn run_diff(schema_file: &str, crate_name: &str, baseline: Option<&str>) -> Result<(), CliError> {
-    let local_content = std::fs::read_to_string(schema_file)
+fn run_diff(
+    schema_file: &str,
+    crate_name: &str,
+    baseline: Option<&str>,
+    format: &str,
+    registry: &Registry,
+) -> Result<(), CliError> {
+    if format != "text" && format != "json" {
+        return Err(CliError::Usage(format!(
+            "unknown format '{format}', expected 'text' or 'json'"
+        )));
+    }
+
+    let local_content = read_source(schema_file)
         .map_err(|e| CliError::Io(io::Error::new(e.kind(), format!("{schema_file}: {e}"))))?;
 
-    let local_tree = styx_tree::parse(&local_content)
-        .map_err(|e| CliError::Parse(format!("invalid schema: {e}")))?;
+    let local_schema = parse_schema_file(&local_content, "schema")?;
 
     let version = match baseline {
         Some(v) => v.to_string(),
-        None => fetch_latest_version(crate_name)?,
+        None => fetch_latest_version(registry, crate_name)?,
     };
 
     eprintln!("Comparing against {crate_name}@{version}...");
 
-    let baseline_content = fetch_crate_schema(crate_name, &version)?;
+    let baseline_content = fetch_crate_schema(registry, crate_name, &version)?;
+
+    let baseline_schema = parse_schema_file(&baseline_content, "baseline schema")?;
 
-    let baseline_tree = styx_tree::parse(&baseline_content)
-        .map_err(|e| CliError::Parse(format!("invalid baseline schema: {e}")))?;
+    let changes = compatibility(&baseline_schema, &local_schema);
 
-    let changes = compare_schemas(&baseline_tree, &local_tree);
+    if format == "json" {
+        let report = serde_json::json!({
+            "crate": crate_name,
+            "baseline": version,
+            "bump": suggested_bump(&changes),
+            "changes": changes_to_json(&changes),
+        });
+        let output =
+            serde_json::to_string_pretty(&report).map_err(|e| CliError::Io(io::Error::other(e)))?;
+        println!("{output}");
+        return Ok(());
+    }
 
-    if changes.breaking.is_empty() && changes.additive.is_empty() && changes.patch.is_empty() {
+    if changes.is_empty() {
         eprintln!("No changes detected.");
         return Ok(());
     }
@@ -1729,20 +3785,47 @@ fn run_diff(schema_file: &str, crate_name: &str, baseline: Option<&str>) -> Resu
         }
     }
 
-    let bump = if !changes.breaking.is_empty() {
+    eprintln!("\nSuggested bump: {}", suggested_bump(&changes));
+
+    Ok(())
+}
+
+fn suggested_bump(changes: &Compatibility) -> &'static str {
+    if !changes.breaking.is_empty() {
         "major"
     } else if !changes.additive.is_empty() {
         "minor"
-    } else {
+    } else if !changes.patch.is_empty() {
         "patch"
-    };
-    eprintln!("\nSuggested bump: {bump}");
+    } else {
+        "none"
+    }
+}
 
-    Ok(())
+/// Flatten all changes into `{kind, category, path, message}` records.
+fn changes_to_json(changes: &Compatibility) -> serde_json::Value {
+    let categories = [
+        ("breaking", &changes.breaking),
+        ("additive", &changes.additive),
+        ("patch", &changes.patch),
+    ];
+    categories
+        .into_iter()
+        .flat_map(|(category, list)| {
+            list.iter().map(move |change| {
+                serde_json::json!({
+                    "kind": change.kind.as_str(),
+                    "category": category,
+                    "path": change.path,
+                    "message": change.message,
+                })
+            })
+        })
+        .collect()
 }
 
-fn fetch_latest_version(crate_name: &str) -> Result<String, CliError> {
-    let url = format!("{STAGING_API}/{crate_name}");
+fn fetch_latest_version(registry: &Registry, crate_name: &str) -> Result<String, CliError> {
+    let url = registry.crate_url(crate_name);
 
     let output = std::process::Command::new("curl")
         .args(["-sfL", &url])
@@ -1751,12 +3834,12 @@ fn fetch_latest_version(crate_name: &str) -> Result<String, CliError> {
 
     if !output.status.success() {
         return Err(CliError::Usage(format!(
-            "crate {crate_name} not found on staging.crates.io"
+            "crate {crate_name} not found on {registry}"
         )));
     }
 
     let json: serde_json::Value = serde_json::from_slice(&output.stdout)
-        .map_err(|e| CliError::Parse(format!("invalid JSON from crates.io: {e}")))?;
+        .map_err(|e| CliError::Parse(format!("invalid JSON from {registry}: {e}")))?;
 
     json["crate"]["max_version"]
         .as_str()
@@ -1764,8 +3847,12 @@ fn fetch_latest_version(crate_name: &str) -> Result<String, CliError> {
         .ok_or_else(|| CliError::Parse("could not find max_version in response".into()))
 }
 
-fn fetch_crate_schema(crate_name: &str, version: &str) -> Result<String, CliError> {
-    let url = format!("{STAGING_DOWNLOAD}/{crate_name}/{version}/download");
+fn fetch_crate_schema(
+    registry: &Registry,
+    crate_name: &str,
+    version: &str,
+) -> Result<String, CliError> {
+    let url = registry.download_url(crate_name, version);
     let temp_dir =
         std::env::temp_dir().join(format!("styx-diff-{}-{}", crate_name, std::process::id()));
     std::fs::create_dir_all(&temp_dir)?;
@@ -1788,7 +3875,7 @@ fn fetch_crate_schema(crate_name: &str, version: &str) -> Result<String, CliErro
     }
 
     let schema_path = temp_dir.join(format!("{crate_name}-{version}/schema.styx"));
-    let content = std::fs::read_to_string(&schema_path).map_err(|e| {
+    let content = read_source(&schema_path).map_err(|e| {
         let _ = std::fs::remove_dir_all(&temp_dir);
         CliError::Io(io::Error::new(
             e.kind(),
@@ -1800,192 +3887,131 @@ fn fetch_crate_schema(crate_name: &str, version: &str) -> Result<String, CliErro
     Ok(content)
 }
 
-#[derive(Default)]
-struct SchemaChanges {
-    breaking: Vec<String>,
-    additive: Vec<String>,
-    patch: Vec<String>,
-}
-
-fn compare_schemas(baseline: &Value, local: &Value) -> SchemaChanges {
-    let mut changes = SchemaChanges::default();
+// ============================================================================
+// Schema registry
+// ============================================================================
 
-    let baseline_schema = extract_schema_map(baseline);
-    let local_schema = extract_schema_map(local);
+/// A registry serving schemas by id, for documents declaring an embedded
+/// schema whose binary isn't installed.
+///
+/// The schema with id `<id>` is fetched from `<url>/<id>.styx` and cached,
+/// so each id is only downloaded once per registry. `styx cache --clear` drops the cache.
+struct SchemaRegistry {
+    url: String,
+    /// Where downloaded schemas are kept, if there is a cache directory.
+    cache_dir: Option<PathBuf>,
+}
 
-    for name in baseline_schema.keys() {
-        if !local_schema.contains_key(name) {
-            let type_name = name.as_deref().unwrap_or("(root)");
-            changes.breaking.push(format!("removed type `{type_name}`"));
+impl SchemaRegistry {
+    fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            cache_dir: styx_lsp::cache::cache_dir().map(|dir| dir.join("registry")),
         }
     }
 
-    for name in local_schema.keys() {
-        if !baseline_schema.contains_key(name) {
-            let type_name = name.as_deref().unwrap_or("(root)");
-            changes.additive.push(format!("added type `{type_name}`"));
+    /// The schema with id `schema_id`.
+    ///
+    /// Cached schemas are kept per registry, and only once they parse.
+    fn fetch(&self, schema_id: &str) -> Result<SchemaFile, CliError> {
+        let file_name = format!("{}.styx", encode_path_segment(schema_id));
+        let url = format!("{}/{file_name}", self.url);
+        let what = format!("schema '{schema_id}' from {url}");
+        let cache_path = self
+            .cache_dir
+            .as_ref()
+            .map(|dir| dir.join(encode_path_segment(&self.url)).join(&file_name));
+        if let Some(schema) = cache_path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|source| parse_schema_file(&source, &what).ok())
+        {
+            return Ok(schema);
         }
-    }
 
-    for (name, baseline_type) in &baseline_schema {
-        if let Some(local_type) = local_schema.get(name) {
-            let type_name = name.as_deref().unwrap_or("(root)");
-            compare_type_definitions(type_name, baseline_type, local_type, &mut changes);
+        let output = std::process::Command::new("curl")
+            .args(["-sfL", &url])
+            .output()
+            .map_err(|e| CliError::Io(io::Error::new(e.kind(), format!("curl failed: {e}"))))?;
+        if !output.status.success() {
+            return Err(CliError::Validation(format!(
+                "failed to fetch schema '{schema_id}' from {url}\n\
+                 hint: check that the registry at {} serves it",
+                self.url
+            )));
         }
-    }
-
-    changes
-}
-
-fn extract_schema_map(value: &Value) -> std::collections::HashMap<Option<String>, &Value> {
-    let mut map = std::collections::HashMap::new();
-
-    if let Some(Payload::Object(obj)) = &value.payload {
-        for entry in &obj.entries {
-            if entry.key.as_str() == Some("schema")
-                && let Some(Payload::Object(schema_obj)) = &entry.value.payload
-            {
-                for schema_entry in &schema_obj.entries {
-                    let key = if schema_entry.key.is_unit() {
-                        None
-                    } else {
-                        schema_entry.key.as_str().map(String::from)
-                    };
-                    map.insert(key, &schema_entry.value);
-                }
-            }
+        let source = String::from_utf8(output.stdout)
+            .map_err(|_| CliError::Parse(format!("{what} is not UTF-8")))?;
+        let schema = parse_schema_file(&source, &what)?;
+
+        // Failing to cache only means downloading it again next time
+        if let Some(path) = &cache_path
+            && let Some(parent) = path.parent()
+            && std::fs::create_dir_all(parent).is_ok()
+        {
+            let _ = std::fs::write(path, &source);
         }
-    }
-
-    map
-}
-
-fn compare_type_definitions(
-    type_name: &str,
-    baseline: &Value,
-    local: &Value,
-    changes: &mut SchemaChanges,
-) {
-    let baseline_tag = baseline.tag.as_ref().map(|t| t.name.as_str());
-    let local_tag = local.tag.as_ref().map(|t| t.name.as_str());
-
-    if baseline_tag != local_tag {
-        changes.breaking.push(format!(
-            "type `{type_name}` changed from @{} to @{}",
-            baseline_tag.unwrap_or("(none)"),
-            local_tag.unwrap_or("(none)")
-        ));
-        return;
-    }
-
-    if baseline_tag == Some("object") {
-        compare_object_fields(type_name, baseline, local, changes);
-    }
-
-    if baseline_tag == Some("enum") {
-        compare_enum_variants(type_name, baseline, local, changes);
+        Ok(schema)
     }
 }
 
-fn compare_object_fields(
-    type_name: &str,
-    baseline: &Value,
-    local: &Value,
-    changes: &mut SchemaChanges,
-) {
-    let baseline_fields = extract_object_fields(baseline);
-    let local_fields = extract_object_fields(local);
-
-    for field_name in baseline_fields.keys() {
-        if !local_fields.contains_key(field_name) {
-            changes
-                .breaking
-                .push(format!("removed field `{field_name}` from `{type_name}`"));
-        }
-    }
-
-    for (field_name, field_type) in &local_fields {
-        if !baseline_fields.contains_key(field_name) {
-            let is_optional = is_optional_field(field_type);
-            if is_optional {
-                changes.additive.push(format!(
-                    "added optional field `{field_name}` to `{type_name}`"
-                ));
+/// Percent-encode everything in `segment` but unreserved URL characters.
+fn encode_path_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+                char::from(b).to_string()
             } else {
-                changes.breaking.push(format!(
-                    "added required field `{field_name}` to `{type_name}`"
-                ));
-            }
-        }
-    }
-}
-
-fn extract_object_fields(value: &Value) -> std::collections::HashMap<String, &Value> {
-    let mut fields = std::collections::HashMap::new();
-
-    if let Some(Payload::Object(obj)) = &value.payload {
-        for entry in &obj.entries {
-            if let Some(name) = entry.key.as_str() {
-                fields.insert(name.to_string(), &entry.value);
+                format!("%{b:02X}")
             }
-        }
-    }
-
-    fields
+        })
+        .collect()
 }
 
-fn is_optional_field(value: &Value) -> bool {
-    value
-        .tag
-        .as_ref()
-        .map(|t| t.name == "optional" || t.name == "default")
-        .unwrap_or(false)
+fn parse_schema_file(source: &str, what: &str) -> Result<SchemaFile, CliError> {
+    facet_styx::from_str(source).map_err(|e| CliError::Parse(format!("invalid {what}: {e}")))
 }
 
-fn compare_enum_variants(
-    type_name: &str,
-    baseline: &Value,
-    local: &Value,
-    changes: &mut SchemaChanges,
-) {
-    let baseline_variants = extract_enum_variants(baseline);
-    let local_variants = extract_enum_variants(local);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use styx_testhelpers::TempDir;
 
-    for variant in &baseline_variants {
-        if !local_variants.contains(variant) {
-            changes
-                .breaking
-                .push(format!("removed variant `{variant}` from `{type_name}`"));
-        }
-    }
+    #[test]
+    fn test_timing_report_lists_phases() {
+        let source = "name app\nport 8080";
+        let mut timing = Timing::new(true);
+        timing.measure_passes(source);
+        timing.time("tree", || styx_tree::parse(source)).unwrap();
+        timing.time("format", || format_source(source, FormatOptions::default()));
+
+        let report = timing.report();
+        let phases: Vec<&str> = report
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_whitespace().next())
+            .collect();
+        assert_eq!(phases, ["lex", "parse", "tree", "format", "total"]);
+        assert!(report.starts_with("timing:\n"));
+        assert!(report.lines().skip(1).all(|line| line.ends_with(" ms")));
 
-    for variant in &local_variants {
-        if !baseline_variants.contains(variant) {
-            changes
-                .additive
-                .push(format!("added variant `{variant}` to `{type_name}`"));
-        }
+        // The lex and parse passes are part of the tree phase already
+        let phase = |name| timing.phases.iter().find(|(p, _)| *p == name).unwrap().1;
+        assert_eq!(timing.total(), phase("tree") + phase("format"));
     }
-}
 
-fn extract_enum_variants(value: &Value) -> Vec<String> {
-    let mut variants = Vec::new();
-
-    if let Some(Payload::Object(obj)) = &value.payload {
-        for entry in &obj.entries {
-            if let Some(name) = entry.key.as_str() {
-                variants.push(name.to_string());
-            }
-        }
+    #[test]
+    fn test_timing_disabled_records_nothing() {
+        let mut timing = Timing::new(false);
+        timing.measure_passes("name app");
+        timing
+            .time("tree", || styx_tree::parse("name app"))
+            .unwrap();
+        assert!(timing.passes.is_empty());
+        assert!(timing.phases.is_empty());
     }
 
-    variants
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
     #[test]
     fn test_highlight_styx_produces_ansi_codes() {
         let source = "name value";
@@ -2063,4 +4089,831 @@ other @type{inner value}"#;
 
         result
     }
+
+    #[test]
+    fn test_json_diagnostics_for_parse_and_validation_errors() {
+        let dir = TempDir::new("json-diagnostics");
+        let write = |name: &str, content: &str| dir.write(name, content).display().to_string();
+        let broken = write("broken.styx", "port 1 2\nserver {host localhost");
+        let schema = write(
+            "schema.styx",
+            "meta {id test}\nschema {@ @object{port @int}}",
+        );
+        let invalid = write("invalid.styx", "port eighty");
+
+        // Run each command as `--diagnostic-format json` would
+        let diagnostics = |args: &[&str]| {
+            let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+            args.extend(["--diagnostic-format".into(), "json".into()]);
+            let mut reporter = Reporter::new(take_diagnostic_format(&mut args).unwrap(), None);
+            assert!(run(&args, &mut reporter).is_err());
+            let mut out = Vec::new();
+            reporter.finish(&mut out);
+            let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+            json.as_array().unwrap().clone()
+        };
+
+        // Every parse error is reported, not just the first
+        let json = diagnostics(&[&broken]);
+        assert_eq!(json.len(), 2, "{json:?}");
+        assert_eq!(
+            json[0],
+            serde_json::json!({
+                "file": broken,
+                "span": [7, 8],
+                "start": {"line": 1, "column": 8},
+                "end": {"line": 1, "column": 9},
+                "severity": "error",
+                "message": "unexpected atom after value (entry has too many atoms)",
+                "code": "too-many-atoms",
+            })
+        );
+        assert_eq!(json[1]["code"], "unclosed-object");
+        assert_eq!(json[1]["span"], serde_json::json!([16, 17]));
+
+        let json = diagnostics(&[&invalid, "--validate", "--schema", &schema]);
+        assert_eq!(json.len(), 1, "{json:?}");
+        assert_eq!(json[0]["file"], invalid.as_str());
+        assert_eq!(json[0]["span"], serde_json::json!([5, 11]));
+        assert_eq!(
+            json[0]["start"],
+            serde_json::json!({"line": 1, "column": 6})
+        );
+        assert_eq!(json[0]["end"], serde_json::json!({"line": 1, "column": 12}));
+        assert_eq!(json[0]["severity"], "error");
+        assert!(json[0]["code"].is_string());
+    }
+
+    #[test]
+    fn test_validate_document_and_schema_from_stdin() {
+        let schema = "meta {id test}\nschema {@ @object{port @int}}\n";
+        let stdin = format!("{schema}---\nport 8080\n");
+        let (stdin_schema, document) = split_stdin_schema(&stdin).unwrap();
+        assert_eq!(stdin_schema, schema);
+        assert_eq!(document, "port 8080\n");
+        run_validation(
+            document,
+            "<stdin>",
+            Some(SchemaOverride::Stdin(stdin_schema)),
+            None,
+            false,
+            false,
+            &mut Reporter::default(),
+        )
+        .unwrap();
+
+        let stdin = format!("{schema}---\r\nport eighty\n");
+        let (stdin_schema, document) = split_stdin_schema(&stdin).unwrap();
+        let result = run_validation(
+            document,
+            "<stdin>",
+            Some(SchemaOverride::Stdin(stdin_schema)),
+            None,
+            false,
+            false,
+            &mut Reporter::default(),
+        );
+        assert!(matches!(result, Err(CliError::Validation(_))));
+
+        assert_eq!(split_stdin_schema("port 8080\n-- -\n"), None);
+    }
+
+    #[test]
+    fn test_strict_separators_reject_mixed_objects() {
+        let schema =
+            SchemaOverride::Stdin("meta {id test}\nschema {@ @object{a @int, b @int, c @int}}\n");
+        let document = "a 1, b 2\nc 3\n";
+        let mut reporter = Reporter::default();
+        run_validation(
+            document,
+            "config.styx",
+            Some(schema),
+            None,
+            false,
+            false,
+            &mut reporter,
+        )
+        .unwrap();
+
+        let result = run_validation(
+            document,
+            "config.styx",
+            Some(schema),
+            None,
+            false,
+            true,
+            &mut reporter,
+        );
+        assert!(matches!(
+            result,
+            Err(CliError::ParseDiagnostic {
+                error: BuildError::Parse(styx_parse::ParseErrorKind::MixedSeparators, _),
+                ..
+            })
+        ));
+    }
+
+    /// Serve `body` to one HTTP request, returning the request line.
+    fn serve_once(body: &'static str) -> (String, std::thread::JoinHandle<String>) {
+        use std::io::{BufRead, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = io::BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut header = String::new();
+            while reader.read_line(&mut header).unwrap() > 2 {
+                header.clear();
+            }
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            request_line.trim_end().to_string()
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_validate_with_schema_from_registry() {
+        let schema = "meta {id app-config@1}\nschema {@ @object{port @int}}\n";
+        let (url, server) = serve_once(schema);
+        let cache_dir = TempDir::new("schema-registry");
+        let registry = SchemaRegistry {
+            url: url.clone(),
+            cache_dir: Some(cache_dir.path().to_path_buf()),
+        };
+
+        // The declared binary isn't installed, so the schema comes from the
+        // registry
+        let document = "@schema {id app-config@1, cli styx-test-missing-binary}\nport 8080\n";
+        let mut reporter = Reporter::default();
+        run_validation(
+            document,
+            "config.styx",
+            None,
+            Some(&registry),
+            false,
+            false,
+            &mut reporter,
+        )
+        .unwrap();
+        assert_eq!(server.join().unwrap(), "GET /app-config%401.styx HTTP/1.1");
+
+        // Later lookups are served from the cache, without a server
+        let cached = registry.fetch("app-config@1").unwrap();
+        assert_eq!(cached.meta.id, "app-config@1");
+        let invalid = "@schema {id app-config@1, cli styx-test-missing-binary}\nport eighty\n";
+        let result = run_validation(
+            invalid,
+            "config.styx",
+            None,
+            Some(&registry),
+            false,
+            false,
+            &mut reporter,
+        );
+        assert!(matches!(result, Err(CliError::Validation(e)) if e == "1 validation error(s)"));
+
+        // The cache is per registry
+        let other = SchemaRegistry {
+            url: format!("{url}/other"),
+            cache_dir: Some(cache_dir.path().to_path_buf()),
+        };
+        assert!(other.fetch("app-config@1").is_err());
+
+        // Responses that aren't schemas are not cached
+        let (url, server) = serve_once("not {a schema");
+        let broken = SchemaRegistry {
+            url,
+            cache_dir: Some(cache_dir.path().to_path_buf()),
+        };
+        assert!(matches!(
+            broken.fetch("app-config@1"),
+            Err(CliError::Parse(_))
+        ));
+        server.join().unwrap();
+        assert!(broken.fetch("app-config@1").is_err());
+    }
+
+    #[test]
+    fn test_schema_of_embedded_names_binary_and_id() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new("schema-of");
+        let schema = "meta {id app-config}\nschema {@ @object{port @int}}\n";
+        let mut binary = b"\x7fnot an object file".to_vec();
+        binary.extend(styx_embed::build_embedded_blob(schema));
+        let binary_path = dir.write("app", binary);
+        std::fs::set_permissions(&binary_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let document = format!(
+            "@schema {{id app-config, cli \"{}\"}}\n\nport 8080\n",
+            binary_path.display()
+        );
+        let schemas = resolve_declared_schemas(&document, "config.styx").unwrap();
+        assert_eq!(schemas.len(), 1);
+        assert_eq!(
+            schemas[0].origin,
+            format!("embedded schema 'app-config' in {}", binary_path.display())
+        );
+        assert_eq!(schemas[0].source, schema);
+    }
+
+    #[test]
+    fn test_select_extracted_sorts_and_picks_by_id() {
+        let mut binary = b"\x7fnot an object file".to_vec();
+        for schema in [
+            "meta {id web}\nschema {@ @string}\n",
+            "schema {@ @int}\n",
+            "meta {id api}\nschema {@ @int}\n",
+            "meta {id cli}\nschema {@ @bool}\n",
+        ] {
+            binary.extend(styx_embed::build_embedded_blob(schema));
+        }
+        let schemas = styx_embed::extract_schemas_from_object(&binary).unwrap();
+        let ids = |schemas: Vec<styx_embed::ExtractedSchema>| -> Vec<Option<String>> {
+            schemas.iter().map(|s| schema_id(&s.schema)).collect()
+        };
+        let id = |id: &str| Some(id.to_string());
+
+        let found = select_extracted(schemas.clone(), false, None).unwrap();
+        assert_eq!(ids(found), [id("web"), None, id("api"), id("cli")]);
+
+        let sorted = select_extracted(schemas.clone(), true, None).unwrap();
+        assert_eq!(ids(sorted), [id("api"), id("cli"), id("web"), None]);
+
+        let api = select_extracted(schemas.clone(), false, Some("api")).unwrap();
+        assert_eq!(api.len(), 1);
+        assert_eq!(api[0].schema, "meta {id api}\nschema {@ @int}\n");
+
+        let missing = select_extracted(schemas, true, Some("db")).unwrap_err();
+        assert_eq!(missing, ["web", "api", "cli"]);
+    }
+
+    #[test]
+    fn test_describe_extracted() {
+        let extracted = styx_embed::ExtractedSchema {
+            schema: "meta {id app-config, version 1.2.0}\nschema {@ @string}\n".into(),
+            section: styx_embed::SchemaSection::Elf,
+            compressed_len: 60,
+            decompressed_len: 55,
+        };
+        assert_eq!(
+            describe_extracted(&extracted),
+            "id: app-config\nversion: 1.2.0\nsize: 60 bytes compressed, 55 bytes decompressed\nsection: ELF section .styx_schemas\n"
+        );
+    }
+
+    #[test]
+    fn test_check_reports_mixed_problems() {
+        let dir = TempDir::new("check");
+        let write = |name: &str, content: &str| dir.write(name, content).display().to_string();
+        write(
+            "schema.styx",
+            "meta {id test}\nschema {@ @object{port @int}}\n",
+        );
+        let clean = write("clean.styx", "name app\n");
+        let unformatted = write("unformatted.styx", "name   app\n");
+        let broken = write("broken.styx", "server {host localhost\n");
+        let invalid = write("invalid.styx", "@schema schema.styx\n\nport eighty\n");
+        let missing = dir.path().join("missing.styx").display().to_string();
+
+        let mut reporter = Reporter::default();
+        let files = [&clean, &unformatted, &broken, &invalid, &missing];
+        let problems: Vec<_> = files
+            .iter()
+            .map(|f| check_file(f, false, &mut reporter))
+            .collect();
+        assert_eq!(
+            problems,
+            [
+                vec![],
+                vec![CheckProblem::Format],
+                vec![CheckProblem::Syntax],
+                vec![CheckProblem::Validation],
+                vec![CheckProblem::Syntax],
+            ]
+        );
+        assert_eq!(
+            check_summary(&problems),
+            "checked 5 files: 2 with syntax errors, 1 failing validation, 1 not formatted"
+        );
+        assert!(matches!(
+            run_check(&[clean.clone(), invalid.clone()], false, &mut reporter),
+            Err(CliError::Validation(_))
+        ));
+
+        // --fix formats the file, but validation errors still fail the check
+        assert_eq!(check_file(&unformatted, true, &mut reporter), []);
+        assert_eq!(std::fs::read_to_string(&unformatted).unwrap(), "name app\n");
+        assert!(matches!(
+            run_check(&[unformatted.clone(), invalid], true, &mut reporter),
+            Err(CliError::Validation(_))
+        ));
+        run_check(&[clean, unformatted], true, &mut reporter).unwrap();
+        assert_eq!(
+            check_summary(&[vec![]]),
+            "checked 1 file, no problems found"
+        );
+    }
+
+    #[test]
+    fn test_format_line_range() {
+        let source = "a   1\nb {\n  c   2\n}\nd   3\n";
+        let range = parse_line_range(source, "2:4").unwrap();
+        assert_eq!(&source[range.clone()], "b {\n  c   2\n}\n");
+        assert_eq!(
+            format_range(source, range, FormatOptions::default()),
+            "a   1\nb {\n    c 2\n}\nd   3\n"
+        );
+
+        assert_eq!(parse_line_range(source, "5:99").unwrap(), 20..source.len());
+        for bad in ["2", "0:1", "3:2", "a:b"] {
+            assert!(matches!(
+                parse_line_range(source, bad),
+                Err(CliError::Usage(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_read_source_rejects_invalid_utf8() {
+        let dir = TempDir::new("utf8");
+
+        let bom = dir.write("bom.styx", "\u{feff}name app\n");
+        let source = read_source(&bom).unwrap();
+        let doc = styx_tree::parse(&source).unwrap();
+        assert_eq!(doc.get("name").and_then(|v| v.as_str()), Some("app"));
+
+        let latin1 = dir.write("latin1.styx", b"name caf\xe9\n");
+        let err = read_source(&latin1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "invalid UTF-8 at byte 8; styx files must be UTF-8"
+        );
+    }
+
+    #[test]
+    fn test_json_keeps_number_kinds() {
+        let value = styx_tree::parse("count 42\nratio 1.5\nport \"8080\"\nversion 1.2.3").unwrap();
+        let json = value_to_json(&value);
+        assert_eq!(
+            json,
+            serde_json::json!({"count": 42, "ratio": 1.5, "port": "8080", "version": "1.2.3"})
+        );
+        assert!(json["count"].is_i64());
+        assert!(json["ratio"].is_f64());
+
+        let styx = format_value(&value, FormatOptions::default());
+        assert!(styx.contains("count 42\n"), "{styx}");
+
+        let value = styx_tree::parse("zip 01234\nshift +5\nsize 1e3").unwrap();
+        assert_eq!(
+            value_to_json(&value),
+            serde_json::json!({"zip": "01234", "shift": "+5", "size": "1e3"})
+        );
+    }
+
+    #[test]
+    fn test_take_diagnostic_format() {
+        let mut args: Vec<String> = ["config.styx", "--diagnostic-format", "json", "--validate"]
+            .map(String::from)
+            .to_vec();
+        assert!(take_diagnostic_format(&mut args).unwrap());
+        assert_eq!(args, ["config.styx", "--validate"]);
+
+        let mut args = vec!["--diagnostic-format=human".to_string(), "lsp".to_string()];
+        assert!(!take_diagnostic_format(&mut args).unwrap());
+        assert_eq!(args, ["lsp"]);
+
+        let mut args = vec!["--diagnostic-format=xml".to_string()];
+        assert!(take_diagnostic_format(&mut args).is_err());
+        let mut args = vec!["--diagnostic-format".to_string()];
+        assert!(take_diagnostic_format(&mut args).is_err());
+    }
+
+    #[test]
+    fn test_max_errors_caps_reports() {
+        let mut args: Vec<String> = ["config.styx", "--max-errors", "2"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(take_max_errors(&mut args).unwrap(), Some(2));
+        assert_eq!(args, ["config.styx"]);
+        let mut args = vec!["--max-errors=many".to_string()];
+        assert!(take_max_errors(&mut args).is_err());
+
+        let render = |report: &dyn Fn(&mut Vec<u8>)| {
+            let mut output = Vec::new();
+            report(&mut output);
+            String::from_utf8(output).unwrap()
+        };
+
+        // Four duplicate keys
+        let source = "a 1\na 2\na 3\na 4\na 5";
+        let all = render(&|out| write_parse_report("dup.styx", source, None, out));
+        assert_eq!(all.matches("dup.styx:").count(), 4, "{all}");
+        assert!(!all.contains("more"), "{all}");
+        let capped = render(&|out| write_parse_report("dup.styx", source, Some(2), out));
+        assert_eq!(capped.matches("dup.styx:").count(), 2, "{capped}");
+        assert!(capped.ends_with("... and 2 more\n"), "{capped}");
+
+        // Four unknown fields
+        let schema: SchemaFile =
+            facet_styx::from_str("meta {id test}\nschema {@ @object{port @optional(@int)}}")
+                .unwrap();
+        let source = "a 1\nb 2\nc 3\nd 4";
+        let result = facet_styx::validate(&styx_tree::parse(source).unwrap(), &schema);
+        assert_eq!(result.errors.len() + result.warnings.len(), 4);
+        let capped =
+            render(&|out| write_validation_report(&result, "config.styx", source, Some(3), out));
+        assert_eq!(capped.matches("config.styx:").count(), 3, "{capped}");
+        assert!(capped.ends_with("... and 1 more\n"), "{capped}");
+
+        // JSON diagnostics are capped too, and say how many were left out
+        let mut reporter = Reporter::new(true, Some(3));
+        let diagnostics = Diagnostic::from_validation("config.styx", source, &result);
+        reporter.report(diagnostics, |_| unreachable!());
+        let mut out = Vec::new();
+        reporter.finish(&mut out);
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let json = json.as_array().unwrap();
+        assert_eq!(json.len(), 4, "{json:?}");
+        assert_eq!(
+            json[3],
+            serde_json::json!({
+                "file": "config.styx",
+                "span": null,
+                "start": null,
+                "end": null,
+                "severity": "note",
+                "message": "1 more left out by --max-errors",
+                "code": "truncated",
+            })
+        );
+    }
+
+    fn grep_paths(
+        source: &str,
+        key_matches: Option<&str>,
+        value_matches: Option<&str>,
+        tags: Option<&str>,
+    ) -> Vec<String> {
+        let value = styx_tree::parse(source).unwrap();
+        let filter = GrepFilter::new(key_matches, value_matches, tags).unwrap();
+        grep_value(&value, &filter)
+            .into_iter()
+            .map(|found| found.path)
+            .collect()
+    }
+
+    const GREP_SOURCE: &str = "db {host localhost, password hunter2}\n\
+                               smtp {user mail, smtp_password @env\"SMTP_PASS\"}\n\
+                               hosts (localhost example.com)\n\
+                               token @env\"API_TOKEN\"";
+
+    #[test]
+    fn test_grep_keys_by_regex() {
+        assert_eq!(
+            grep_paths(GREP_SOURCE, Some("(?i)pass"), None, None),
+            ["db.password", "smtp.smtp_password"]
+        );
+
+        let value = styx_tree::parse(GREP_SOURCE).unwrap();
+        let filter = GrepFilter::new(Some("^password$"), None, None).unwrap();
+        let found = grep_value(&value, &filter);
+        let span = found[0].span.unwrap();
+        assert_eq!(
+            &GREP_SOURCE[span.start as usize..span.end as usize],
+            "hunter2"
+        );
+        assert_eq!(line_column(GREP_SOURCE, span.start as usize), (1, 30));
+
+        let filter = GrepFilter::new(None, None, Some("env")).unwrap();
+        let span = grep_value(&value, &filter)[1].span.unwrap();
+        assert_eq!(
+            &GREP_SOURCE[span.start as usize..span.end as usize],
+            "@env\"API_TOKEN\""
+        );
+    }
+
+    #[test]
+    fn test_grep_values_by_regex() {
+        assert_eq!(
+            grep_paths(GREP_SOURCE, None, Some("^localhost$"), None),
+            ["db.host", "hosts[0]"]
+        );
+        // Tagged scalars match on their text
+        assert_eq!(
+            grep_paths(GREP_SOURCE, None, Some("_(PASS|TOKEN)$"), None),
+            ["smtp.smtp_password", "token"]
+        );
+    }
+
+    #[test]
+    fn test_grep_combined_filters() {
+        assert_eq!(
+            grep_paths(GREP_SOURCE, Some("pass"), None, Some("env")),
+            ["smtp.smtp_password"]
+        );
+        assert_eq!(
+            grep_paths(GREP_SOURCE, None, Some("TOKEN"), Some("@env")),
+            ["token"]
+        );
+        assert!(grep_paths(GREP_SOURCE, Some("host"), Some("example"), None).is_empty());
+
+        assert!(GrepFilter::new(None, None, None).is_err());
+        assert!(GrepFilter::new(Some("("), None, None).is_err());
+    }
+
+    #[test]
+    fn test_changes_to_json() {
+        let baseline: SchemaFile =
+            facet_styx::from_str("meta {id test}\nschema {@ @object{host @string, port @int}}")
+                .unwrap();
+        let current: SchemaFile = facet_styx::from_str(
+            "meta {id test}\nschema {@ @object{host @string, tls @optional(@bool)}}",
+        )
+        .unwrap();
+
+        let changes = compatibility(&baseline, &current);
+        assert_eq!(suggested_bump(&changes), "major");
+        assert_eq!(
+            changes_to_json(&changes),
+            serde_json::json!([
+                {
+                    "kind": "field-removed",
+                    "category": "breaking",
+                    "path": "port",
+                    "message": "removed field `port` from `(root)`",
+                },
+                {
+                    "kind": "optional-field-added",
+                    "category": "additive",
+                    "path": "tls",
+                    "message": "added optional field `tls` to `(root)`",
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_publish_dry_run_generates_next_version() {
+        let dir = TempDir::new("dry-run");
+        let dir = dir.path();
+        let baseline = "meta {id test, crate test-schema}\nschema {@ @object{host @string}}";
+        let current = "meta {id test, crate test-schema}\nschema {@ @object{host @string, port @optional(@int)}}";
+
+        let plan = prepare_publish(
+            current,
+            "test-schema",
+            Some(("1.2.3".into(), baseline.into())),
+            &Registry::staging(),
+            dir,
+            true,
+        )
+        .unwrap();
+        assert_eq!(plan.version, "1.3.0");
+        assert_eq!(plan.dir, dir);
+        let cargo_toml = std::fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+        assert!(cargo_toml.contains("version = \"1.3.0\""), "{cargo_toml}");
+        assert_eq!(
+            std::fs::read_to_string(dir.join("schema.styx")).unwrap(),
+            current
+        );
+        assert!(dir.join("src/lib.rs").exists());
+        assert!(dir.join(".cargo/config.toml").exists());
+
+        // Nothing published yet starts at 0.1.0
+        let plan = prepare_publish(
+            current,
+            "test-schema",
+            None,
+            &Registry::staging(),
+            dir,
+            true,
+        )
+        .unwrap();
+        assert_eq!(plan.version, "0.1.0");
+    }
+
+    #[test]
+    fn test_package_to_json() {
+        let dir = TempDir::new("package");
+        let dir = dir.path();
+        let files = write_schema_crate(dir, "my-schema", "0.2.0", "meta {id test}").unwrap();
+
+        let path = |file: &str| dir.join(file).display().to_string();
+        assert_eq!(
+            package_to_json("my-schema", "0.2.0", dir, &files),
+            serde_json::json!({
+                "name": "my-schema",
+                "version": "0.2.0",
+                "dir": dir.display().to_string(),
+                "files": [
+                    path("Cargo.toml"),
+                    path("src/lib.rs"),
+                    path("README.md"),
+                    path("schema.styx"),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_custom_registry() {
+        let registry = Registry::from_options(
+            Some("my-registry".into()),
+            Some("sparse+https://index.example.com/".into()),
+            Some("https://example.com/api/v1/crates/".into()),
+        )
+        .unwrap();
+        assert_eq!(registry.token_var(), "CARGO_REGISTRIES_MY_REGISTRY_TOKEN");
+        assert_eq!(
+            registry.crate_url("my-schema"),
+            "https://example.com/api/v1/crates/my-schema"
+        );
+        assert_eq!(
+            registry.download_url("my-schema", "0.1.0"),
+            "https://example.com/api/v1/crates/my-schema/0.1.0/download"
+        );
+
+        let dir = TempDir::new("publish-registry");
+        prepare_publish(
+            "meta {id test}\nschema {@ @string}",
+            "my-schema",
+            None,
+            &registry,
+            dir.path(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(".cargo/config.toml")).unwrap(),
+            "[registries.my-registry]\nindex = \"sparse+https://index.example.com/\"\n"
+        );
+
+        // Other registries can't be used without an API URL, or published to
+        // without an index
+        assert!(Registry::from_options(Some("my-registry".into()), None, None).is_err());
+        let no_index =
+            Registry::from_options(Some("my-registry".into()), None, Some("https://x".into()))
+                .unwrap();
+        assert!(no_index.cargo_config().is_err());
+        let schema = "meta {id test}\nschema {@ @string}";
+        let dir = TempDir::new("publish-no-index");
+        assert!(prepare_publish(schema, "my-schema", None, &no_index, dir.path(), false).is_err());
+
+        // A dry run doesn't need the index
+        prepare_publish(schema, "my-schema", None, &no_index, dir.path(), true).unwrap();
+        assert!(dir.path().join("Cargo.toml").exists());
+        assert!(!dir.path().join(".cargo").exists());
+
+        // Names end up as TOML keys, and index URLs as TOML strings
+        for name in ["", "my registry", "a]\nb", "1st", "-x", "x.y"] {
+            let registry =
+                Registry::from_options(Some(name.into()), None, Some("https://x".into()));
+            assert!(registry.is_err(), "{name:?}");
+        }
+        let quoted = Registry::from_options(
+            Some("my_registry".into()),
+            Some("https://example.com/\"index\"\\\n".into()),
+            Some("https://x".into()),
+        )
+        .unwrap();
+        assert_eq!(
+            quoted.cargo_config().unwrap(),
+            "[registries.my_registry]\nindex = \"https://example.com/\\\"index\\\"\\\\\\n\"\n"
+        );
+        assert_eq!(
+            Registry::from_options(None, None, None).unwrap(),
+            Registry::staging()
+        );
+    }
+
+    #[test]
+    fn test_repl_prints_lines_and_answers_queries() {
+        let input = "server.host localhost\nserver.port 8080\n(1 2 @three)\nport 1 2\n.get server.port\n.get nope\n";
+        let mut output = Vec::new();
+        repl(io::Cursor::new(input), &mut output, "styx", false).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "server {host localhost}\n\
+             server {port 8080}\n\
+             (1 2 @three)\n\
+             error: parse error at 7-8: unexpected atom after value (entry has too many atoms)\n\
+             8080\n\
+             error: no value at 'nope'\n"
+        );
+
+        let mut output = Vec::new();
+        repl(
+            io::Cursor::new("a.b 1\n.get a\n"),
+            &mut output,
+            "json",
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\n  \"a\": {\n    \"b\": 1\n  }\n}\n{\n  \"b\": 1\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_env_lines() {
+        let value = styx_tree::parse(
+            "db {host localhost, port 5432, max-connections 10}\nhosts (a.example.com b.example.com)\nmotd \"it's up\"\nsecret @env\"SECRET\"",
+        )
+        .unwrap();
+        assert_eq!(
+            env_lines(&value, Some("APP")),
+            [
+                "APP_DB_HOST=localhost",
+                "APP_DB_PORT=5432",
+                "APP_DB_MAX_CONNECTIONS=10",
+                "APP_HOSTS_0=a.example.com",
+                "APP_HOSTS_1=b.example.com",
+                r"APP_MOTD='it'\''s up'",
+                "APP_SECRET='@env(SECRET)'",
+            ]
+        );
+        assert_eq!(env_lines(&value, None)[0], "DB_HOST=localhost");
+    }
+
+    #[test]
+    fn test_env_document() {
+        let vars: Vec<(String, String)> = [
+            ("APP_DB_HOST", "localhost"),
+            ("APP_DB_PORT", "5432"),
+            ("APP_DB_MAX_CONNECTIONS", "10"),
+            ("APP_HOSTS_1", "b.example.com"),
+            ("APP_HOSTS_0", "a.example.com"),
+            ("APP_NAME", "app"),
+            ("APPLICATION", "other"),
+            ("HOME", "/root"),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        let paths = HashMap::from([(
+            "APP_DB_MAX_CONNECTIONS".to_string(),
+            "db.max_connections".to_string(),
+        )]);
+
+        let document = env_document("APP", &vars, &paths).unwrap();
+        let expected = styx_tree::parse(
+            "db {host localhost, max_connections 10, port 5432}\nhosts (a.example.com b.example.com)\nname app",
+        )
+        .unwrap();
+        assert!(
+            document.semantic_eq(&expected),
+            "{}",
+            format_value(&document, FormatOptions::default())
+        );
+
+        // Without the map, `DB_MAX` would need to be both an object and a value
+        let vars = [
+            ("APP_DB_MAX".to_string(), "1".to_string()),
+            ("APP_DB_MAX_CONNECTIONS".to_string(), "10".to_string()),
+        ];
+        assert!(matches!(
+            env_document("APP", &vars, &HashMap::new()),
+            Err(CliError::Usage(message)) if message.starts_with("APP_DB_MAX_CONNECTIONS")
+        ));
+    }
+
+    #[test]
+    fn test_schema_keys() {
+        let schema: SchemaFile = facet_styx::from_str(
+            "meta {id test}\nschema {\n  @ @object{\n    name @string\n    server @optional(@Server)\n    base @flatten(@Base)\n  }\n  Server @object{host @string, tls @object{cert @string}}\n  Base @object{id @int}\n}",
+        )
+        .unwrap();
+
+        assert_eq!(schema_keys(&schema, false), ["id", "name", "server"]);
+        assert_eq!(
+            schema_keys(&schema, true),
+            [
+                "id",
+                "name",
+                "server",
+                "server.host",
+                "server.tls",
+                "server.tls.cert"
+            ]
+        );
+
+        let document = styx_tree::parse("name app\nserver {host localhost}").unwrap();
+        assert_eq!(document_keys(&document, false), ["name", "server"]);
+        assert_eq!(
+            document_keys(&document, true),
+            ["name", "server", "server.host"]
+        );
+    }
 }
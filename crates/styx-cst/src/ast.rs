@@ -143,6 +143,22 @@ impl Entry {
             .filter_map(|el| el.into_token())
             .filter(|t| t.kind() == SyntaxKind::DOC_COMMENT)
     }
+
+    /// Get the line comment on the same line as the end of this entry, as in
+    /// `port 8080 // main port`.
+    pub fn inline_comment(&self) -> Option<SyntaxToken> {
+        // The comment follows the entry in the parent, before any newline
+        self.0
+            .siblings_with_tokens(rowan::Direction::Next)
+            .skip(1) // Skip self
+            .take_while(|el| {
+                el.kind() == SyntaxKind::WHITESPACE
+                    || el.kind() == SyntaxKind::COMMA
+                    || el.kind() == SyntaxKind::LINE_COMMENT
+            })
+            .filter_map(|el| el.into_token())
+            .find(|t| t.kind() == SyntaxKind::LINE_COMMENT)
+    }
 }
 
 // === Key ===
@@ -524,6 +540,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_entry_inline_comment() {
+        let d = doc("port 8080 // main port
+// about host
+host localhost");
+        let entries: Vec<_> = d.entries().collect();
+        assert_eq!(
+            entries[0].inline_comment().map(|t| t.text().to_string()),
+            Some("// main port".to_string())
+        );
+        assert_eq!(entries[1].inline_comment(), None);
+    }
+
     #[test]
     fn test_object_entries() {
         let d = doc("config { host localhost, port 8080 }");
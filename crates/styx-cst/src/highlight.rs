@@ -0,0 +1,336 @@
+//! Byte ranges to highlight in a document, by kind of token.
+//!
+//! This is the highlighting the LSP's semantic tokens use, without an LSP:
+//! terminals and browser editors color the returned ranges themselves.
+
+use crate::{Parse, SyntaxKind, SyntaxNode, SyntaxToken};
+
+/// Semantic token types we support
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum TokenType {
+    /// Comments
+    Comment = 0,
+    /// String/scalar values
+    String = 1,
+    /// Numeric values (schema-aware)
+    Number = 2,
+    /// Keywords (for booleans if schema-aware)
+    Keyword = 3,
+    /// Type names (tags)
+    Type = 4,
+    /// Enum members (enum variant tags)
+    EnumMember = 5,
+    /// Properties (object keys)
+    Property = 6,
+    /// Operators (@ =)
+    Operator = 7,
+}
+
+impl TokenType {
+    /// Number of token types.
+    pub const COUNT: usize = 8;
+
+    /// Name of the token type, as in the LSP semantic token legend.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TokenType::Comment => "comment",
+            TokenType::String => "string",
+            TokenType::Number => "number",
+            TokenType::Keyword => "keyword",
+            TokenType::Type => "type",
+            TokenType::EnumMember => "enumMember",
+            TokenType::Property => "property",
+            TokenType::Operator => "operator",
+        }
+    }
+}
+
+/// A highlight span with byte range (for CLI/terminal output)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightSpan {
+    /// Byte offset where the span starts
+    pub start: usize,
+    /// Byte offset where the span ends (exclusive)
+    pub end: usize,
+    /// The type of token this span represents
+    pub token_type: TokenType,
+    /// Whether this is a doc comment
+    pub is_doc_comment: bool,
+}
+
+/// Context for semantic token collection
+#[derive(Clone, Copy, Default)]
+struct WalkContext {
+    /// Are we inside a sequence? (affects how keys are highlighted)
+    in_sequence: bool,
+}
+
+/// Compute highlight spans with byte ranges for terminal/CLI output
+pub fn compute_highlight_spans(parse: &Parse) -> Vec<HighlightSpan> {
+    let mut spans = Vec::new();
+    walk_node_for_spans(&parse.syntax(), &mut spans, WalkContext::default());
+
+    // Sort spans by start position
+    spans.sort_by_key(|s| s.start);
+    spans
+}
+
+/// Recursively walk a syntax node and collect highlight spans with byte ranges
+fn walk_node_for_spans(node: &SyntaxNode, spans: &mut Vec<HighlightSpan>, ctx: WalkContext) {
+    match node.kind() {
+        SyntaxKind::KEY => {
+            if ctx.in_sequence {
+                collect_key_spans_as_values(node, spans);
+            } else {
+                collect_key_spans(node, spans);
+            }
+        }
+        SyntaxKind::TAG => {
+            for child in node.children_with_tokens() {
+                if let Some(token) = child.as_token() {
+                    if token.kind() == SyntaxKind::TAG_TOKEN {
+                        add_span_from_syntax(spans, token, TokenType::Type, false);
+                    }
+                } else if let Some(child_node) = child.as_node()
+                    && child_node.kind() == SyntaxKind::TAG_PAYLOAD
+                {
+                    // A payload's keys are properties even for a tag in a
+                    // sequence
+                    walk_node_for_spans(child_node, spans, WalkContext::default());
+                }
+            }
+        }
+        SyntaxKind::SEQUENCE => {
+            let seq_ctx = WalkContext { in_sequence: true };
+            for child in node.children_with_tokens() {
+                if let Some(child_node) = child.as_node() {
+                    walk_node_for_spans(child_node, spans, seq_ctx);
+                }
+            }
+        }
+        SyntaxKind::UNIT => {
+            for child in node.children_with_tokens() {
+                if let Some(token) = child.as_token()
+                    && (token.kind() == SyntaxKind::TAG_TOKEN || token.kind() == SyntaxKind::AT)
+                {
+                    add_span_from_syntax(spans, token, TokenType::Type, false);
+                }
+            }
+        }
+        SyntaxKind::SCALAR => {
+            for child in node.children_with_tokens() {
+                if let Some(token) = child.as_token()
+                    && is_scalar_token(token.kind())
+                {
+                    add_span_from_syntax(spans, token, TokenType::String, false);
+                }
+            }
+        }
+        SyntaxKind::HEREDOC => {
+            for child in node.children_with_tokens() {
+                if let Some(token) = child.as_token() {
+                    match token.kind() {
+                        SyntaxKind::HEREDOC_START | SyntaxKind::HEREDOC_END => {
+                            add_span_from_syntax(spans, token, TokenType::Operator, false);
+                        }
+                        SyntaxKind::HEREDOC_CONTENT => {
+                            add_span_from_syntax(spans, token, TokenType::String, false);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        SyntaxKind::ATTRIBUTE => {
+            for child in node.children_with_tokens() {
+                if let Some(token) = child.as_token() {
+                    if token.kind() == SyntaxKind::GT {
+                        add_span_from_syntax(spans, token, TokenType::Operator, false);
+                    }
+                } else if let Some(child_node) = child.as_node() {
+                    if child_node.kind() == SyntaxKind::KEY {
+                        collect_key_spans(child_node, spans);
+                    } else {
+                        walk_node_for_spans(child_node, spans, ctx);
+                    }
+                }
+            }
+        }
+        _ => {
+            // Handle comments at token level
+            for child in node.children_with_tokens() {
+                if let Some(token) = child.as_token() {
+                    match token.kind() {
+                        SyntaxKind::LINE_COMMENT => {
+                            add_span_from_syntax(spans, token, TokenType::Comment, false);
+                        }
+                        SyntaxKind::DOC_COMMENT => {
+                            add_span_from_syntax(spans, token, TokenType::Comment, true);
+                        }
+                        _ => {}
+                    }
+                } else if let Some(child_node) = child.as_node() {
+                    walk_node_for_spans(child_node, spans, ctx);
+                }
+            }
+        }
+    }
+}
+
+/// Collect tokens from a KEY node (object property)
+fn collect_key_spans(node: &SyntaxNode, spans: &mut Vec<HighlightSpan>) {
+    for child in node.children_with_tokens() {
+        if let Some(child_node) = child.as_node() {
+            match child_node.kind() {
+                SyntaxKind::SCALAR => {
+                    for t in child_node.children_with_tokens() {
+                        if let Some(token) = t.as_token()
+                            && is_scalar_token(token.kind())
+                        {
+                            add_span_from_syntax(spans, token, TokenType::Property, false);
+                        }
+                    }
+                }
+                SyntaxKind::TAG => {
+                    // Tagged key like @schema
+                    for t in child_node.children_with_tokens() {
+                        if let Some(token) = t.as_token()
+                            && token.kind() == SyntaxKind::TAG_TOKEN
+                        {
+                            add_span_from_syntax(spans, token, TokenType::Property, false);
+                        }
+                    }
+                }
+                SyntaxKind::UNIT => {
+                    for t in child_node.children_with_tokens() {
+                        if let Some(token) = t.as_token()
+                            && (token.kind() == SyntaxKind::TAG_TOKEN
+                                || token.kind() == SyntaxKind::AT)
+                        {
+                            add_span_from_syntax(spans, token, TokenType::Type, false);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Collect tokens from a KEY node as values (in sequence context)
+fn collect_key_spans_as_values(node: &SyntaxNode, spans: &mut Vec<HighlightSpan>) {
+    for child in node.children_with_tokens() {
+        if let Some(child_node) = child.as_node() {
+            match child_node.kind() {
+                SyntaxKind::SCALAR => {
+                    for t in child_node.children_with_tokens() {
+                        if let Some(token) = t.as_token()
+                            && is_scalar_token(token.kind())
+                        {
+                            add_span_from_syntax(spans, token, TokenType::String, false);
+                        }
+                    }
+                }
+                SyntaxKind::TAG => {
+                    walk_node_for_spans(child_node, spans, WalkContext { in_sequence: true });
+                }
+                SyntaxKind::UNIT => {
+                    for t in child_node.children_with_tokens() {
+                        if let Some(token) = t.as_token()
+                            && (token.kind() == SyntaxKind::TAG_TOKEN
+                                || token.kind() == SyntaxKind::AT)
+                        {
+                            add_span_from_syntax(spans, token, TokenType::Type, false);
+                        }
+                    }
+                }
+                SyntaxKind::SEQUENCE => {
+                    walk_node_for_spans(child_node, spans, WalkContext { in_sequence: true });
+                }
+                SyntaxKind::OBJECT => {
+                    walk_node_for_spans(child_node, spans, WalkContext::default());
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Add a highlight span from a syntax token
+fn add_span_from_syntax(
+    spans: &mut Vec<HighlightSpan>,
+    token: &SyntaxToken,
+    token_type: TokenType,
+    is_doc_comment: bool,
+) {
+    let start: usize = token.text_range().start().into();
+    let end: usize = token.text_range().end().into();
+
+    spans.push(HighlightSpan {
+        start,
+        end,
+        token_type,
+        is_doc_comment,
+    });
+}
+
+/// Check if a syntax kind is a scalar token
+fn is_scalar_token(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::BARE_SCALAR | SyntaxKind::QUOTED_SCALAR | SyntaxKind::RAW_SCALAR
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    fn spans(source: &str) -> Vec<(&str, TokenType)> {
+        compute_highlight_spans(&parse(source))
+            .into_iter()
+            .map(|span| (&source[span.start..span.end], span.token_type))
+            .collect()
+    }
+
+    #[test]
+    fn test_spans_follow_the_document() {
+        assert_eq!(
+            spans("// note\nname \"app\"\nkind @web"),
+            [
+                ("// note", TokenType::Comment),
+                ("name", TokenType::Property),
+                ("\"app\"", TokenType::String),
+                ("kind", TokenType::Property),
+                ("@web", TokenType::Type),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sequence_items_are_values() {
+        assert_eq!(
+            spans("tags (a @b)"),
+            [
+                ("tags", TokenType::Property),
+                ("a", TokenType::String),
+                ("@b", TokenType::Type),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tag_payloads_in_sequences_have_properties() {
+        assert_eq!(
+            spans("routes (@route{path /})"),
+            [
+                ("routes", TokenType::Property),
+                ("@route", TokenType::Type),
+                ("path", TokenType::Property),
+                ("/", TokenType::String),
+            ]
+        );
+    }
+}
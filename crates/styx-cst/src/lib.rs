@@ -17,3 +17,6 @@ pub use parser::{Parse, ParseError, parse};
 
 mod validation;
 pub use validation::{Diagnostic, Severity, validate, validate_document};
+
+mod highlight;
+pub use highlight::{HighlightSpan, TokenType, compute_highlight_spans};
@@ -218,9 +218,26 @@ impl<'src> CstParser<'src> {
 
     /// Parse entries (at document level or inside an object).
     fn parse_entries(&mut self, closing: Option<TokenKind>) {
+        let mut seen_entry = false;
+        let mut comma_since_entry = false;
         loop {
             self.skip_trivia();
 
+            // A comma must follow an entry, and only one may: `{, a 1}` and
+            // `{a 1,, b 2}` are errors
+            if self.peek() == TokenKind::Comma {
+                if !seen_entry || comma_since_entry {
+                    let pos = self.current_pos();
+                    self.errors.push(ParseError::new(
+                        pos,
+                        format!("unexpected token: {:?}", TokenKind::Comma),
+                    ));
+                }
+                comma_since_entry = true;
+                self.bump();
+                continue;
+            }
+
             // Check for doc comments - they attach to the next entry
             while self.peek() == TokenKind::DocComment {
                 self.bump();
@@ -240,9 +257,11 @@ impl<'src> CstParser<'src> {
 
             // Parse an entry
             self.parse_entry(closing);
+            seen_entry = true;
 
             // Handle separator
             self.skip_whitespace();
+            comma_since_entry = self.peek() == TokenKind::Comma;
             if matches!(self.peek(), TokenKind::Comma | TokenKind::Newline) {
                 self.bump();
             }
@@ -611,6 +630,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_error_leading_and_doubled_commas() {
+        assert_parse_errors(
+            r#"
+a {x 1,, y 2}
+       ^ UnexpectedToken
+b {, x 1}
+   ^ UnexpectedToken
+c {x 1,,}
+       ^ UnexpectedToken
+d {x {y 1,,}}
+          ^ UnexpectedToken
+"#,
+        );
+        assert_parse_errors(
+            r#"
+x 1,, y 2
+    ^ UnexpectedToken
+"#,
+        );
+        assert_parse_errors(
+            r#"
+{
+  , x 1
+  ^ UnexpectedToken
+  y 2,
+}
+"#,
+        );
+        parse_ok("{a 1,\n b 2,}");
+        parse_ok("{a 1\n, b 2}");
+    }
+
     #[test]
     fn test_parse_error_attribute_whitespace_span() {
         assert_parse_errors(
@@ -7,6 +7,10 @@
 //! Each schema must have a `meta { id ... }` block. The ID is used to
 //! generate a unique static name, allowing multiple schemas to coexist
 //! in the same binary.
+//!
+//! Every macro accepts a leading `no_hash` option, e.g.
+//! `embed_file!(no_hash, "schema.styx")`, which embeds the schema without
+//! its BLAKE3 hash to save space on size-constrained targets.
 
 use proc_macro::{Delimiter, Group, Literal, Punct, Spacing, TokenStream, TokenTree};
 use unsynn::{Comma, DelimitedVec, Parse, TokenIter};
@@ -15,34 +19,27 @@ use unsynn::{Comma, DelimitedVec, Parse, TokenIter};
 /// 16 bytes: "STYX_SCHEMA_V2\0\0"
 const MAGIC: &[u8; 16] = b"STYX_SCHEMA_V2\0\0";
 
-/// Extract the schema ID from a parsed styx document.
-///
-/// Looks for `meta { id <value> }` at the root level.
-fn extract_schema_id(schema: &str) -> Result<String, String> {
-    let value = styx_tree::parse(schema).map_err(|e| format!("failed to parse schema: {e}"))?;
-
-    let obj = value
-        .as_object()
-        .ok_or_else(|| "schema root must be an object".to_string())?;
+/// Magic bytes that identify an embedded Styx schema without a hash.
+/// 16 bytes: "STYX_SCHEMA_V2N\0"
+const MAGIC_NO_HASH: &[u8; 16] = b"STYX_SCHEMA_V2N\0";
 
-    let meta = obj
-        .get("meta")
-        .ok_or_else(|| "schema must have a `meta` block".to_string())?;
+/// Extract the schema ID from the schema's `meta { id <value> }` block.
+fn extract_schema_id(schema: &str) -> Result<String, String> {
+    let meta = styx_tree::parse_meta(schema)
+        .map_err(|e| format!("failed to parse schema: {e}"))?
+        .ok_or("schema must have a `meta` block")?;
 
-    let meta_obj = meta
+    let id_value = meta
         .as_object()
-        .ok_or_else(|| "`meta` must be an object".to_string())?;
-
-    let id_value = meta_obj
+        .ok_or("`meta` must be an object")?
         .get("id")
-        .ok_or_else(|| "`meta` block must have an `id` field".to_string())?;
+        .ok_or("`meta` block must have an `id` field")?;
 
     // ID can be a bare identifier or a quoted string
-    if let Some(s) = id_value.as_str() {
-        return Ok(s.to_string());
+    match id_value.as_str() {
+        Some(id) => Ok(id.to_string()),
+        None => Err("`meta.id` must be a string or identifier".to_string()),
     }
-
-    Err("`meta.id` must be a string or identifier".to_string())
 }
 
 /// Sanitize an ID for the human-readable part of the symbol name.
@@ -102,6 +99,42 @@ fn build_embedded_blob(schema: &str) -> Vec<u8> {
     blob
 }
 
+/// Build the embedded blob for a single schema, without a hash.
+///
+/// Format (V2, hashless):
+/// ```text
+/// STYX_SCHEMA_V2N\0            // 16 bytes magic
+/// <decompressed_len:u32le>
+/// <compressed_len:u32le>
+/// <lz4 compressed schema>
+/// ```
+fn build_embedded_blob_no_hash(schema: &str) -> Vec<u8> {
+    let decompressed = schema.as_bytes();
+    let compressed = lz4_flex::compress_prepend_size(decompressed);
+
+    let mut blob = Vec::with_capacity(16 + 4 + 4 + compressed.len());
+    blob.extend_from_slice(MAGIC_NO_HASH);
+    blob.extend_from_slice(&(decompressed.len() as u32).to_le_bytes());
+    blob.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+    blob.extend_from_slice(&compressed);
+    blob
+}
+
+/// Split a leading `no_hash,` option off the macro input.
+///
+/// Returns whether the option was given, and the remaining input.
+fn take_no_hash_option(input: TokenStream) -> (bool, TokenStream) {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    match tokens.as_slice() {
+        [TokenTree::Ident(ident), TokenTree::Punct(comma), rest @ ..]
+            if ident.to_string() == "no_hash" && comma.as_char() == ',' =>
+        {
+            (true, rest.iter().cloned().collect())
+        }
+        _ => (false, tokens.into_iter().collect()),
+    }
+}
+
 /// Parse a string literal (regular or raw) and return its content.
 fn parse_string_literal(lit: &unsynn::Literal) -> Option<String> {
     let s = lit.to_string();
@@ -150,10 +183,14 @@ fn parse_string_literal(lit: &unsynn::Literal) -> Option<String> {
 }
 
 /// Generate the static declaration for an embedded schema.
-fn generate_static(schema: &str) -> Result<TokenStream, String> {
+fn generate_static(schema: &str, no_hash: bool) -> Result<TokenStream, String> {
     let id = extract_schema_id(schema)?;
     let suffix = id_to_symbol_suffix(&id);
-    let blob = build_embedded_blob(schema);
+    let blob = if no_hash {
+        build_embedded_blob_no_hash(schema)
+    } else {
+        build_embedded_blob(schema)
+    };
     let blob_len = blob.len();
 
     // Generate: [u8; N] = [b0, b1, b2, ...];
@@ -197,9 +234,16 @@ fn generate_static(schema: &str) -> Result<TokenStream, String> {
 /// meta { id my-schema, version 1.0.0 }
 /// schema { @ @string }
 /// "#);
+///
+/// // Without the integrity hash, for size-constrained targets
+/// styx_embed::embed_inline!(no_hash, r#"
+/// meta { id my-small-schema, version 1.0.0 }
+/// schema { @ @string }
+/// "#);
 /// ```
 #[proc_macro]
 pub fn embed_inline(input: TokenStream) -> TokenStream {
+    let (no_hash, input) = take_no_hash_option(input);
     let mut tokens = TokenIter::new(proc_macro2::TokenStream::from(input));
 
     let literal: unsynn::Literal = match Parse::parse(&mut tokens) {
@@ -220,7 +264,7 @@ pub fn embed_inline(input: TokenStream) -> TokenStream {
         }
     };
 
-    match generate_static(&schema) {
+    match generate_static(&schema, no_hash) {
         Ok(ts) => ts,
         Err(e) => format!("compile_error!(\"{}\")", e.replace('"', "\\\""))
             .parse()
@@ -239,6 +283,7 @@ pub fn embed_inline(input: TokenStream) -> TokenStream {
 /// ```
 #[proc_macro]
 pub fn embed_file(input: TokenStream) -> TokenStream {
+    let (no_hash, input) = take_no_hash_option(input);
     let mut tokens = TokenIter::new(proc_macro2::TokenStream::from(input));
 
     let literal: unsynn::Literal = match Parse::parse(&mut tokens) {
@@ -268,7 +313,7 @@ pub fn embed_file(input: TokenStream) -> TokenStream {
         }
     };
 
-    match generate_static(&content) {
+    match generate_static(&content, no_hash) {
         Ok(ts) => ts,
         Err(e) => format!("compile_error!(\"{}\")", e.replace('"', "\\\""))
             .parse()
@@ -291,6 +336,7 @@ pub fn embed_file(input: TokenStream) -> TokenStream {
 /// ```
 #[proc_macro]
 pub fn embed_files(input: TokenStream) -> TokenStream {
+    let (no_hash, input) = take_no_hash_option(input);
     let mut tokens = TokenIter::new(proc_macro2::TokenStream::from(input));
 
     let literals: DelimitedVec<unsynn::Literal, Comma> = match Parse::parse(&mut tokens) {
@@ -323,7 +369,7 @@ pub fn embed_files(input: TokenStream) -> TokenStream {
             }
         };
 
-        match generate_static(&content) {
+        match generate_static(&content, no_hash) {
             Ok(ts) => result.extend(ts),
             Err(e) => {
                 return format!("compile_error!(\"{}\")", e.replace('"', "\\\""))
@@ -357,6 +403,7 @@ pub fn embed_files(input: TokenStream) -> TokenStream {
 /// ```
 #[proc_macro]
 pub fn embed_outdir_file(input: TokenStream) -> TokenStream {
+    let (no_hash, input) = take_no_hash_option(input);
     let mut tokens = TokenIter::new(proc_macro2::TokenStream::from(input));
 
     let literal: unsynn::Literal = match Parse::parse(&mut tokens) {
@@ -398,7 +445,7 @@ pub fn embed_outdir_file(input: TokenStream) -> TokenStream {
         }
     };
 
-    match generate_static(&content) {
+    match generate_static(&content, no_hash) {
         Ok(ts) => ts,
         Err(e) => format!("compile_error!(\"{}\")", e.replace('"', "\\\""))
             .parse()
@@ -64,6 +64,20 @@
 //! Multiple schemas in a binary means multiple blobs, each with its own magic header.
 //! The schema's `meta { id ... }` is used to identify which schema is which.
 //!
+//! ## Hashless variant
+//!
+//! For size-constrained targets, the macros accept a leading `no_hash` option
+//! (e.g. `embed_file!(no_hash, "schema.styx")`) that embeds the schema without
+//! its BLAKE3 hash, saving 32 bytes per schema. Corruption is then only caught
+//! if it breaks decompression or UTF-8 decoding.
+//!
+//! ```text
+//! STYX_SCHEMA_V2N\0            // 16 bytes magic
+//! <decompressed_len:u32le>
+//! <compressed_len:u32le>
+//! <lz4 compressed schema>
+//! ```
+//!
 //! # Extracting schemas
 //!
 //! ```rust,ignore
@@ -84,6 +98,10 @@ pub use styx_embed_macros::{
 /// 16 bytes: "STYX_SCHEMA_V2\0\0"
 pub const MAGIC_V2: &[u8; 16] = b"STYX_SCHEMA_V2\0\0";
 
+/// Magic bytes for V2 blobs embedded without a hash.
+/// 16 bytes: "STYX_SCHEMA_V2N\0"
+pub const MAGIC_V2_NO_HASH: &[u8; 16] = b"STYX_SCHEMA_V2N\0";
+
 /// Magic bytes for legacy V1 format (multiple schemas per blob).
 /// 16 bytes: "STYX_SCHEMAS_V1\0"
 pub const MAGIC_V1: &[u8; 16] = b"STYX_SCHEMAS_V1\0";
@@ -117,6 +135,48 @@ impl std::fmt::Display for ExtractError {
 
 impl std::error::Error for ExtractError {}
 
+/// Where in a binary an embedded schema was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaSection {
+    /// The `.styx_schemas` section of an ELF binary.
+    Elf,
+    /// The `__DATA,__styx_schemas` section of a Mach-O binary.
+    MachO,
+    /// The `.styx` section of a PE binary.
+    Pe,
+    /// Not in a known section; found by scanning for magic bytes.
+    Scan,
+}
+
+impl std::fmt::Display for SchemaSection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaSection::Elf => write!(f, "ELF section {}", section_names::ELF),
+            SchemaSection::MachO => write!(
+                f,
+                "Mach-O section {},{}",
+                section_names::MACHO_SEGMENT,
+                section_names::MACHO_SECTION
+            ),
+            SchemaSection::Pe => write!(f, "PE section {}", section_names::PE),
+            SchemaSection::Scan => write!(f, "magic byte scan"),
+        }
+    }
+}
+
+/// An embedded schema along with where and how it was stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedSchema {
+    /// The schema source.
+    pub schema: String,
+    /// Where the schema was found.
+    pub section: SchemaSection,
+    /// Size of the LZ4-compressed schema in bytes.
+    pub compressed_len: usize,
+    /// Size of the decompressed schema in bytes.
+    pub decompressed_len: usize,
+}
+
 /// Compress a schema and return the blob (for testing).
 pub fn compress_schema(schema: &str) -> Vec<u8> {
     let decompressed = schema.as_bytes();
@@ -137,6 +197,20 @@ pub fn build_embedded_blob(schema: &str) -> Vec<u8> {
     compress_schema(schema)
 }
 
+/// Build the embedded blob for a single schema without a hash
+/// (hashless V2 format).
+pub fn build_embedded_blob_no_hash(schema: &str) -> Vec<u8> {
+    let decompressed = schema.as_bytes();
+    let compressed = lz4_flex::compress_prepend_size(decompressed);
+
+    let mut blob = Vec::with_capacity(16 + 4 + 4 + compressed.len());
+    blob.extend_from_slice(MAGIC_V2_NO_HASH);
+    blob.extend_from_slice(&(decompressed.len() as u32).to_le_bytes());
+    blob.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+    blob.extend_from_slice(&compressed);
+    blob
+}
+
 /// Extract all schemas from binary data.
 ///
 /// Scans for magic bytes and extracts all embedded schemas found.
@@ -144,14 +218,26 @@ pub fn build_embedded_blob(schema: &str) -> Vec<u8> {
 ///
 /// Returns an error only if no schemas are found at all.
 pub fn extract_schemas(data: &[u8]) -> Result<Vec<String>, ExtractError> {
+    let schemas = scan_schemas(data, SchemaSection::Scan)?;
+    Ok(schemas.into_iter().map(|s| s.schema).collect())
+}
+
+/// Scan `data` for schema blobs, recording `section` as their provenance.
+fn scan_schemas(data: &[u8], section: SchemaSection) -> Result<Vec<ExtractedSchema>, ExtractError> {
     let mut schemas = Vec::new();
     let mut search_start = 0;
+    let extracted = |(schema, compressed_len): (String, usize)| ExtractedSchema {
+        decompressed_len: schema.len(),
+        schema,
+        section,
+        compressed_len,
+    };
 
     // Find all V2 blobs
     while let Some(magic_pos) = find_magic_from(data, search_start, MAGIC_V2) {
         match try_extract_v2_at(data, magic_pos) {
             Ok(schema) => {
-                schemas.push(schema);
+                schemas.push(extracted(schema));
                 // Continue searching after this blob
                 search_start = magic_pos + MAGIC_V2.len();
             }
@@ -162,12 +248,26 @@ pub fn extract_schemas(data: &[u8]) -> Result<Vec<String>, ExtractError> {
         }
     }
 
+    // Find all hashless V2 blobs
+    search_start = 0;
+    while let Some(magic_pos) = find_magic_from(data, search_start, MAGIC_V2_NO_HASH) {
+        match try_extract_v2_no_hash_at(data, magic_pos) {
+            Ok(schema) => {
+                schemas.push(extracted(schema));
+                search_start = magic_pos + MAGIC_V2_NO_HASH.len();
+            }
+            Err(_) => {
+                search_start = magic_pos + 1;
+            }
+        }
+    }
+
     // Also try legacy V1 format for backwards compatibility
     search_start = 0;
     while let Some(magic_pos) = find_magic_from(data, search_start, MAGIC_V1) {
         match try_extract_v1_at(data, magic_pos) {
-            Ok(mut v1_schemas) => {
-                schemas.append(&mut v1_schemas);
+            Ok(v1_schemas) => {
+                schemas.extend(v1_schemas.into_iter().map(extracted));
                 search_start = magic_pos + MAGIC_V1.len();
             }
             Err(_) => {
@@ -184,7 +284,9 @@ pub fn extract_schemas(data: &[u8]) -> Result<Vec<String>, ExtractError> {
 }
 
 /// Try to extract a single schema from V2 format at a specific position.
-fn try_extract_v2_at(data: &[u8], magic_pos: usize) -> Result<String, ExtractError> {
+///
+/// Returns the schema and its compressed size.
+fn try_extract_v2_at(data: &[u8], magic_pos: usize) -> Result<(String, usize), ExtractError> {
     let mut pos = magic_pos + MAGIC_V2.len();
 
     // Read header: decompressed_len (4) + compressed_len (4) + hash (32) = 40 bytes
@@ -227,11 +329,51 @@ fn try_extract_v2_at(data: &[u8], magic_pos: usize) -> Result<String, ExtractErr
     }
 
     // Convert to string
-    String::from_utf8(decompressed).map_err(|_| ExtractError::InvalidUtf8)
+    let schema = String::from_utf8(decompressed).map_err(|_| ExtractError::InvalidUtf8)?;
+    Ok((schema, compressed_len))
+}
+
+/// Try to extract a single schema from hashless V2 format at a specific position.
+fn try_extract_v2_no_hash_at(
+    data: &[u8],
+    magic_pos: usize,
+) -> Result<(String, usize), ExtractError> {
+    let mut pos = magic_pos + MAGIC_V2_NO_HASH.len();
+
+    // Read header: decompressed_len (4) + compressed_len (4) = 8 bytes
+    if pos + 8 > data.len() {
+        return Err(ExtractError::Truncated);
+    }
+
+    let decompressed_len =
+        u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+    pos += 4;
+
+    let compressed_len =
+        u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+    pos += 4;
+
+    // Read compressed data
+    if pos + compressed_len > data.len() {
+        return Err(ExtractError::Truncated);
+    }
+    let compressed = &data[pos..pos + compressed_len];
+
+    // Decompress
+    let decompressed = lz4_flex::decompress_size_prepended(compressed)
+        .map_err(|_| ExtractError::DecompressFailed)?;
+
+    // Verify length; without a hash this is the only integrity check
+    if decompressed.len() != decompressed_len {
+        return Err(ExtractError::DecompressFailed);
+    }
+
+    let schema = String::from_utf8(decompressed).map_err(|_| ExtractError::InvalidUtf8)?;
+    Ok((schema, compressed_len))
 }
 
 /// Try to extract schemas from legacy V1 format at a specific position.
-fn try_extract_v1_at(data: &[u8], magic_pos: usize) -> Result<Vec<String>, ExtractError> {
+fn try_extract_v1_at(data: &[u8], magic_pos: usize) -> Result<Vec<(String, usize)>, ExtractError> {
     let mut pos = magic_pos + MAGIC_V1.len();
 
     // Read count
@@ -286,7 +428,7 @@ fn try_extract_v1_at(data: &[u8], magic_pos: usize) -> Result<Vec<String>, Extra
 
         // Convert to string
         let schema = String::from_utf8(decompressed).map_err(|_| ExtractError::InvalidUtf8)?;
-        schemas.push(schema);
+        schemas.push((schema, compressed_len));
     }
 
     Ok(schemas)
@@ -320,29 +462,112 @@ mod section_names {
 /// Parses ELF, Mach-O, or PE headers to locate the embedded schema section
 /// directly, avoiding a full binary scan. Falls back to magic byte scanning
 /// if the object format is unknown or section not found.
-pub fn extract_schemas_from_object(data: &[u8]) -> Result<Vec<String>, ExtractError> {
+///
+/// Each schema records which section it came from and its stored sizes.
+/// For fat Mach-O binaries, this is the union of the schemas embedded for
+/// each architecture, without duplicates; see
+/// [`extract_schemas_from_object_all_arches`] to tell them apart.
+pub fn extract_schemas_from_object(data: &[u8]) -> Result<Vec<ExtractedSchema>, ExtractError> {
+    let mut schemas: Vec<ExtractedSchema> = Vec::new();
+    for group in extract_schemas_from_object_all_arches(data)? {
+        for schema in group.schemas {
+            if !schemas.iter().any(|s| s.schema == schema.schema) {
+                schemas.push(schema);
+            }
+        }
+    }
+    Ok(schemas)
+}
+
+/// The schemas embedded for one architecture of a binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchSchemas {
+    /// Architecture name (e.g. `x86_64`, `arm64`) for a slice of a fat
+    /// Mach-O binary; `None` for single-architecture binaries.
+    pub arch: Option<String>,
+    /// The schemas found for this architecture.
+    pub schemas: Vec<ExtractedSchema>,
+}
+
+/// Extract schemas from binary data, grouped by architecture.
+///
+/// Fat Mach-O binaries may embed different schemas per architecture, or
+/// only in some of them, so each slice is searched separately and slices
+/// without schemas are left out. Any other binary yields a single group.
+/// Falls back to magic byte scanning like [`extract_schemas_from_object`].
+pub fn extract_schemas_from_object_all_arches(
+    data: &[u8],
+) -> Result<Vec<ArchSchemas>, ExtractError> {
     use goblin::Object;
+    use goblin::mach::Mach;
 
-    // Try to parse as a known object format
-    if let Ok(object) = Object::parse(data)
-        && let Some(section_data) = find_schema_section(&object, data)
-    {
-        // Found the section - extract directly from it
-        return extract_schemas(section_data);
+    let mut groups = Vec::new();
+    match Object::parse(data) {
+        Ok(Object::Mach(Mach::Fat(fat))) => {
+            for arch in fat.iter_arches().flatten() {
+                let start = arch.offset as usize;
+                let size = arch.size as usize;
+                if start + size > data.len() {
+                    continue;
+                }
+                let arch_data = &data[start..start + size];
+                if let Ok(Object::Mach(Mach::Binary(macho))) = Object::parse(arch_data)
+                    && let Some(section_data) = find_macho_section_in_binary(&macho, arch_data)
+                    && let Ok(schemas) = scan_schemas(section_data, SchemaSection::MachO)
+                {
+                    let name = goblin::mach::constants::cputype::get_arch_name_from_types(
+                        arch.cputype(),
+                        arch.cpusubtype(),
+                    );
+                    groups.push(ArchSchemas {
+                        arch: Some(
+                            name.map_or_else(
+                                || format!("cputype {}", arch.cputype()),
+                                str::to_string,
+                            ),
+                        ),
+                        schemas,
+                    });
+                }
+            }
+        }
+        Ok(object) => {
+            if let Some((section, section_data)) = find_schema_section(&object, data) {
+                // Found the section - extract directly from it
+                groups.push(ArchSchemas {
+                    arch: None,
+                    schemas: scan_schemas(section_data, section)?,
+                });
+            }
+        }
+        Err(_) => {}
     }
 
     // Fall back to magic byte scanning for unknown formats or missing section
-    extract_schemas(data)
+    if groups.is_empty() {
+        groups.push(ArchSchemas {
+            arch: None,
+            schemas: scan_schemas(data, SchemaSection::Scan)?,
+        });
+    }
+    Ok(groups)
 }
 
-/// Find the schema section in a parsed object file.
-fn find_schema_section<'a>(object: &goblin::Object, data: &'a [u8]) -> Option<&'a [u8]> {
+/// Find the schema section in a parsed single-architecture object file.
+fn find_schema_section<'a>(
+    object: &goblin::Object,
+    data: &'a [u8],
+) -> Option<(SchemaSection, &'a [u8])> {
     use goblin::Object;
+    use goblin::mach::Mach;
 
     match object {
-        Object::Elf(elf) => find_elf_section(elf, data),
-        Object::Mach(mach) => find_macho_section(mach, data),
-        Object::PE(pe) => find_pe_section(pe, data),
+        Object::Elf(elf) => Some((SchemaSection::Elf, find_elf_section(elf, data)?)),
+        Object::Mach(Mach::Binary(macho)) => Some((
+            SchemaSection::MachO,
+            find_macho_section_in_binary(macho, data)?,
+        )),
+        Object::PE(pe) => Some((SchemaSection::Pe, find_pe_section(pe, data)?)),
         _ => None,
     }
 }
@@ -363,33 +588,7 @@ fn find_elf_section<'a>(elf: &goblin::elf::Elf, data: &'a [u8]) -> Option<&'a [u
     None
 }
 
-/// Find the __DATA,__styx_schemas section in a Mach-O binary.
-fn find_macho_section<'a>(mach: &goblin::mach::Mach, data: &'a [u8]) -> Option<&'a [u8]> {
-    use goblin::mach::Mach;
-
-    match mach {
-        Mach::Binary(macho) => find_macho_section_in_binary(macho, data),
-        Mach::Fat(fat) => {
-            // For fat binaries, try each architecture
-            for arch in fat.iter_arches().flatten() {
-                let start = arch.offset as usize;
-                let size = arch.size as usize;
-                if start + size <= data.len() {
-                    let arch_data = &data[start..start + size];
-                    if let Ok(goblin::Object::Mach(Mach::Binary(macho))) =
-                        goblin::Object::parse(arch_data)
-                        && let Some(section) = find_macho_section_in_binary(&macho, arch_data)
-                    {
-                        return Some(section);
-                    }
-                }
-            }
-            None
-        }
-    }
-}
-
-/// Find the section in a single Mach-O binary (not fat).
+/// Find the __DATA,__styx_schemas section in a single Mach-O binary (not fat).
 fn find_macho_section_in_binary<'a>(
     macho: &goblin::mach::MachO,
     data: &'a [u8],
@@ -440,7 +639,8 @@ pub fn extract_schemas_from_file(
     use std::fs::File;
     let file = File::open(path)?;
     let mmap = unsafe { memmap2::Mmap::map(&file) }?;
-    Ok(extract_schemas_from_object(&mmap)?)
+    let schemas = extract_schemas_from_object(&mmap)?;
+    Ok(schemas.into_iter().map(|s| s.schema).collect())
 }
 
 #[cfg(test)]
@@ -523,4 +723,224 @@ schema {
             Err(ExtractError::NotFound) // No valid schemas found
         ));
     }
+
+    #[test]
+    fn corrupted_content_detected() {
+        let schema = "meta { id test, version 1.0.0 }\nschema { @ @unit }";
+        let mut blob = build_embedded_blob(schema);
+
+        // LZ4 blocks always end with literals, so flipping the last byte
+        // still decompresses but no longer matches the hash
+        let last = blob.len() - 1;
+        blob[last] ^= 0x01;
+
+        assert!(matches!(
+            try_extract_v2_at(&blob, 0),
+            Err(ExtractError::HashMismatch)
+        ));
+        assert!(matches!(
+            extract_schemas(&blob),
+            Err(ExtractError::NotFound)
+        ));
+    }
+
+    /// Build a minimal 64-bit little-endian ELF file with a single
+    /// `.styx_schemas` section holding `contents`.
+    fn elf_with_schema_section(contents: &[u8]) -> Vec<u8> {
+        let shstrtab = b"\0.styx_schemas\0.shstrtab\0";
+        let contents_offset = 64;
+        let shstrtab_offset = contents_offset + contents.len();
+        let shoff = (shstrtab_offset + shstrtab.len()).next_multiple_of(8);
+
+        let mut elf = Vec::new();
+        elf.extend_from_slice(b"\x7fELF\x02\x01\x01");
+        elf.resize(16, 0);
+        elf.extend_from_slice(&1u16.to_le_bytes()); // e_type: REL
+        elf.extend_from_slice(&0x3eu16.to_le_bytes()); // e_machine: x86-64
+        elf.extend_from_slice(&1u32.to_le_bytes()); // e_version
+        elf.extend_from_slice(&0u64.to_le_bytes()); // e_entry
+        elf.extend_from_slice(&0u64.to_le_bytes()); // e_phoff
+        elf.extend_from_slice(&(shoff as u64).to_le_bytes()); // e_shoff
+        elf.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+        elf.extend_from_slice(&64u16.to_le_bytes()); // e_ehsize
+        elf.extend_from_slice(&0u16.to_le_bytes()); // e_phentsize
+        elf.extend_from_slice(&0u16.to_le_bytes()); // e_phnum
+        elf.extend_from_slice(&64u16.to_le_bytes()); // e_shentsize
+        elf.extend_from_slice(&3u16.to_le_bytes()); // e_shnum
+        elf.extend_from_slice(&2u16.to_le_bytes()); // e_shstrndx
+
+        elf.extend_from_slice(contents);
+        elf.extend_from_slice(shstrtab);
+        elf.resize(shoff, 0);
+
+        let mut section = |name: u32, kind: u32, offset: usize, size: usize| {
+            elf.extend_from_slice(&name.to_le_bytes());
+            elf.extend_from_slice(&kind.to_le_bytes());
+            elf.extend_from_slice(&[0; 16]); // sh_flags, sh_addr
+            elf.extend_from_slice(&(offset as u64).to_le_bytes());
+            elf.extend_from_slice(&(size as u64).to_le_bytes());
+            elf.extend_from_slice(&[0; 8]); // sh_link, sh_info
+            elf.extend_from_slice(&1u64.to_le_bytes()); // sh_addralign
+            elf.extend_from_slice(&0u64.to_le_bytes()); // sh_entsize
+        };
+        section(0, 0, 0, 0);
+        section(1, 1, contents_offset, contents.len()); // PROGBITS
+        section(15, 3, shstrtab_offset, shstrtab.len()); // STRTAB
+        elf
+    }
+
+    #[test]
+    fn provenance_from_elf_section() {
+        let schema = "meta { id test, version 1.0.0 }\nschema { @ @string }";
+        let blob = build_embedded_blob(schema);
+        let mut elf = elf_with_schema_section(&blob);
+        // Outside the section, so it must not be picked up
+        elf.extend(build_embedded_blob("meta { id decoy }"));
+
+        let extracted = extract_schemas_from_object(&elf).unwrap();
+        assert_eq!(
+            extracted,
+            [ExtractedSchema {
+                schema: schema.to_string(),
+                section: SchemaSection::Elf,
+                compressed_len: blob.len() - (MAGIC_V2.len() + 4 + 4 + 32),
+                decompressed_len: schema.len(),
+            }]
+        );
+        assert_eq!(
+            extracted[0].section.to_string(),
+            "ELF section .styx_schemas"
+        );
+
+        let scanned = extract_schemas_from_object(&blob).unwrap();
+        assert_eq!(scanned[0].section, SchemaSection::Scan);
+    }
+
+    /// Build a minimal 64-bit Mach-O object with a single
+    /// `__DATA,__styx_schemas` section holding `contents`.
+    fn macho_with_schema_section(cputype: u32, cpusubtype: u32, contents: &[u8]) -> Vec<u8> {
+        let name = |name: &str| {
+            let mut field = [0u8; 16];
+            field[..name.len()].copy_from_slice(name.as_bytes());
+            field
+        };
+        let contents_offset = 32 + 72 + 80;
+
+        let mut macho = Vec::new();
+        for field in [0xfeedfacf, cputype, cpusubtype, 1, 1, 72 + 80, 0, 0] {
+            macho.extend_from_slice(&u32::to_le_bytes(field)); // MH_OBJECT header
+        }
+
+        macho.extend_from_slice(&0x19u32.to_le_bytes()); // LC_SEGMENT_64
+        macho.extend_from_slice(&(72u32 + 80).to_le_bytes());
+        macho.extend_from_slice(&name("__DATA"));
+        for field in [0, contents.len(), contents_offset, contents.len()] {
+            macho.extend_from_slice(&(field as u64).to_le_bytes()); // vm and file ranges
+        }
+        for field in [3, 3, 1, 0] {
+            macho.extend_from_slice(&u32::to_le_bytes(field)); // prot, nsects, flags
+        }
+
+        macho.extend_from_slice(&name("__styx_schemas"));
+        macho.extend_from_slice(&name("__DATA"));
+        macho.extend_from_slice(&0u64.to_le_bytes()); // addr
+        macho.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+        macho.extend_from_slice(&(contents_offset as u32).to_le_bytes());
+        macho.extend_from_slice(&[0; 28]); // align, relocations, flags, reserved
+
+        macho.extend_from_slice(contents);
+        macho
+    }
+
+    /// Wrap Mach-O slices in a fat binary.
+    fn fat_binary(slices: &[(u32, u32, Vec<u8>)]) -> Vec<u8> {
+        let mut fat = Vec::new();
+        fat.extend_from_slice(&0xcafebabeu32.to_be_bytes());
+        fat.extend_from_slice(&(slices.len() as u32).to_be_bytes());
+
+        let mut offset = 4096;
+        for (cputype, cpusubtype, slice) in slices {
+            for field in [*cputype, *cpusubtype, offset, slice.len() as u32, 12] {
+                fat.extend_from_slice(&field.to_be_bytes());
+            }
+            offset = (offset + slice.len() as u32).next_multiple_of(4096);
+        }
+        for (_, _, slice) in slices {
+            fat.resize(fat.len().next_multiple_of(4096), 0);
+            fat.extend_from_slice(slice);
+        }
+        fat
+    }
+
+    #[test]
+    fn fat_macho_schemas_per_arch() {
+        const X86_64: (u32, u32) = (0x0100_0007, 3);
+        const ARM64: (u32, u32) = (0x0100_000c, 0);
+        let shared = "meta { id shared }";
+        let intel = "meta { id intel-only }";
+        let arm = "meta { id arm-only }";
+
+        let mut intel_blobs = build_embedded_blob(shared);
+        intel_blobs.extend(build_embedded_blob(intel));
+        let mut arm_blobs = build_embedded_blob(shared);
+        arm_blobs.extend(build_embedded_blob(arm));
+        let binary = fat_binary(&[
+            (
+                X86_64.0,
+                X86_64.1,
+                macho_with_schema_section(X86_64.0, X86_64.1, &intel_blobs),
+            ),
+            (
+                ARM64.0,
+                ARM64.1,
+                macho_with_schema_section(ARM64.0, ARM64.1, &arm_blobs),
+            ),
+        ]);
+
+        let groups = extract_schemas_from_object_all_arches(&binary).unwrap();
+        let summary: Vec<(Option<&str>, Vec<&str>)> = groups
+            .iter()
+            .map(|group| {
+                let schemas = group.schemas.iter().map(|s| s.schema.as_str()).collect();
+                (group.arch.as_deref(), schemas)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (Some("x86_64"), vec![shared, intel]),
+                (Some("arm64"), vec![shared, arm]),
+            ]
+        );
+        assert!(
+            groups
+                .iter()
+                .flat_map(|group| &group.schemas)
+                .all(|s| s.section == SchemaSection::MachO)
+        );
+
+        let union: Vec<String> = extract_schemas_from_object(&binary)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.schema)
+            .collect();
+        assert_eq!(union, [shared, intel, arm]);
+    }
+
+    #[test]
+    fn roundtrip_single_schema_no_hash() {
+        let schema = "meta { id test, version 1.0.0 }\nschema { @ @bool }";
+
+        let blob = build_embedded_blob_no_hash(schema);
+        assert_eq!(blob.len() + 32, build_embedded_blob(schema).len());
+
+        let mut binary = vec![0u8; 100];
+        binary.extend_from_slice(&blob);
+        binary.extend_from_slice(&build_embedded_blob("meta { id other }"));
+        binary.extend_from_slice(&[0u8; 100]);
+
+        let extracted = extract_schemas(&binary).unwrap();
+        assert_eq!(extracted.len(), 2);
+        assert!(extracted.iter().any(|s| s == schema));
+    }
 }
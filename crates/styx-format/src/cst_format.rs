@@ -7,7 +7,12 @@ use styx_cst::{
     AstNode, Document, Entry, NodeOrToken, Object, Separator, Sequence, SyntaxKind, SyntaxNode,
 };
 
+use std::borrow::Cow;
+use std::ops::Range;
+
 use crate::FormatOptions;
+use crate::options::ForceStyle;
+use crate::scalar::{can_be_bare, unescape_quoted};
 
 /// Format a Styx document from its CST.
 ///
@@ -31,6 +36,105 @@ pub fn format_source(source: &str, options: FormatOptions) -> String {
     format_cst(&parsed.syntax(), options)
 }
 
+/// A replacement of part of the source, as returned by [`format_source_range`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// Byte range of the source to replace.
+    pub range: Range<usize>,
+    /// Text to put in its place.
+    pub new_text: String,
+}
+
+/// Format only the entries of `source` that lie entirely within `range`.
+///
+/// Each such entry is formatted at the indentation of its nesting level, and
+/// everything else, including blank lines and comments around the entries, is
+/// left as written. Returns one edit per entry that changes, in source order,
+/// or no edits if the source has parse errors.
+///
+/// This is what editors use to format a selection.
+pub fn format_source_range(
+    source: &str,
+    range: Range<usize>,
+    options: FormatOptions,
+) -> Vec<TextEdit> {
+    let parsed = styx_cst::parse(source);
+    if !parsed.is_ok() {
+        return Vec::new();
+    }
+
+    let mut edits = Vec::new();
+    collect_range_edits(&parsed.syntax(), source, &range, 0, &options, &mut edits);
+    edits
+}
+
+/// Find the entries under `node` to format for [`format_source_range`].
+///
+/// Entries are formatted whole when they sit on their own lines, at the root
+/// or in a multiline object. Entries only partly in the range are searched
+/// for nested entries instead.
+fn collect_range_edits(
+    node: &SyntaxNode,
+    source: &str,
+    range: &Range<usize>,
+    depth: usize,
+    options: &FormatOptions,
+    edits: &mut Vec<TextEdit>,
+) {
+    let in_block = match node.kind() {
+        SyntaxKind::DOCUMENT => true,
+        SyntaxKind::OBJECT => node.text().contains_char('\n'),
+        _ => false,
+    };
+    let depth = match node.kind() {
+        SyntaxKind::OBJECT | SyntaxKind::SEQUENCE => depth + 1,
+        _ => depth,
+    };
+
+    for child in node.children() {
+        let start: usize = child.text_range().start().into();
+        let end: usize = child.text_range().end().into();
+        if end <= range.start || start >= range.end {
+            continue;
+        }
+
+        let contained = range.start <= start && end <= range.end;
+        if child.kind() == SyntaxKind::ENTRY && in_block && contained {
+            edits.extend(format_entry_in_place(&child, source, depth, options));
+        } else {
+            collect_range_edits(&child, source, range, depth, options, edits);
+        }
+    }
+}
+
+/// Format one entry, replacing its indentation too if it starts a line.
+fn format_entry_in_place(
+    entry: &SyntaxNode,
+    source: &str,
+    depth: usize,
+    options: &FormatOptions,
+) -> Option<TextEdit> {
+    let start: usize = entry.text_range().start().into();
+    let end: usize = entry.text_range().end().into();
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let starts_line = source[line_start..start].trim().is_empty();
+
+    let mut formatter = CstFormatter::new(options.clone());
+    formatter.indent_level = depth;
+    formatter.at_line_start = starts_line;
+    formatter.format_node(entry);
+
+    let range = if starts_line {
+        line_start..end
+    } else {
+        start..end
+    };
+    (source[range.clone()] != formatter.out).then_some(TextEdit {
+        range,
+        new_text: formatter.out,
+    })
+}
+
 struct CstFormatter {
     out: String,
     options: FormatOptions,
@@ -39,6 +143,10 @@ struct CstFormatter {
     at_line_start: bool,
     /// Track if we just wrote a newline
     after_newline: bool,
+    /// Number of enclosing objects written on one line
+    inline_objects: usize,
+    /// Number of enclosing sequences of several elements written on one line
+    inline_sequences: usize,
 }
 
 impl CstFormatter {
@@ -49,6 +157,8 @@ impl CstFormatter {
             indent_level: 0,
             at_line_start: true,
             after_newline: false,
+            inline_objects: 0,
+            inline_sequences: 0,
         }
     }
 
@@ -151,7 +261,10 @@ impl CstFormatter {
                 }
                 SyntaxKind::LINE_COMMENT => {
                     if let Some(token) = el.into_token() {
-                        if wrote_content {
+                        if wrote_content && consecutive_newlines == 0 {
+                            // A comment on the same line as the entry before it
+                            self.write(" ");
+                        } else if wrote_content {
                             self.write_newline();
                             // 2+ consecutive newlines means there was a blank line
                             if consecutive_newlines >= 2 {
@@ -310,7 +423,13 @@ impl CstFormatter {
                     }
                     SyntaxKind::LINE_COMMENT | SyntaxKind::DOC_COMMENT => {
                         if let Some(token) = el.into_token() {
-                            if wrote_content {
+                            if wrote_content
+                                && consecutive_newlines == 0
+                                && token.kind() == SyntaxKind::LINE_COMMENT
+                            {
+                                // A comment on the same line as the entry before it
+                                self.write(" ");
+                            } else if wrote_content {
                                 self.write_newline();
                                 // 2+ consecutive newlines means there was a blank line
                                 if consecutive_newlines >= 2 {
@@ -350,6 +469,7 @@ impl CstFormatter {
             self.write("}");
         } else {
             // Inline format (comma-separated, no comments)
+            self.inline_objects += 1;
             for (i, entry) in entries.iter().enumerate() {
                 self.format_node(entry.syntax());
 
@@ -357,6 +477,7 @@ impl CstFormatter {
                     self.write(", ");
                 }
             }
+            self.inline_objects -= 1;
             self.write("}");
         }
     }
@@ -408,66 +529,14 @@ impl CstFormatter {
             }
             self.write(")");
         } else if is_multiline {
-            // Multiline format - preserve comments as children of the sequence
-            self.write_newline();
-            self.indent_level += 1;
-
-            // Iterate through all children to preserve comments in order
-            let mut wrote_content = false;
-            let mut consecutive_newlines = 0;
-            for el in node.children_with_tokens() {
-                match el.kind() {
-                    SyntaxKind::NEWLINE => {
-                        consecutive_newlines += 1;
-                    }
-                    SyntaxKind::LINE_COMMENT | SyntaxKind::DOC_COMMENT => {
-                        if let Some(token) = el.into_token() {
-                            if wrote_content {
-                                self.write_newline();
-                                // 2+ consecutive newlines means there was a blank line
-                                if consecutive_newlines >= 2 {
-                                    self.write_newline();
-                                }
-                            }
-                            self.write(token.text());
-                            wrote_content = true;
-                            consecutive_newlines = 0;
-                        }
-                    }
-                    SyntaxKind::ENTRY => {
-                        if let Some(entry_node) = el.into_node() {
-                            if wrote_content {
-                                self.write_newline();
-                                // 2+ consecutive newlines means there was a blank line
-                                if consecutive_newlines >= 2 {
-                                    self.write_newline();
-                                }
-                            }
-                            // Format the entry's value (sequence entries have implicit unit keys)
-                            if let Some(key) =
-                                entry_node.children().find(|n| n.kind() == SyntaxKind::KEY)
-                            {
-                                for child in key.children() {
-                                    self.format_node(&child);
-                                }
-                            }
-                            wrote_content = true;
-                            consecutive_newlines = 0;
-                        }
-                    }
-                    // Skip whitespace, parens - we handle formatting ourselves
-                    SyntaxKind::WHITESPACE | SyntaxKind::L_PAREN | SyntaxKind::R_PAREN => {}
-                    _ => {
-                        consecutive_newlines = 0;
-                    }
-                }
-            }
-
-            self.write_newline();
-            self.indent_level -= 1;
-            self.write(")");
+            self.format_sequence_multiline(node);
         } else {
             // Inline format - single line with spaces (no comments possible here)
+            let start = self.out.len();
+            // Nested sequences leave wrapping to this one: if it is too wide it
+            // wraps first, and then each element wraps if it is still too wide
+            let nests = entries.len() > 1;
+            self.inline_sequences += usize::from(nests);
             for (i, entry) in entries.iter().enumerate() {
                 // Get the actual value from the entry's key
                 if let Some(key) = entry
@@ -484,10 +553,105 @@ impl CstFormatter {
                     self.write(" ");
                 }
             }
+            self.inline_sequences -= usize::from(nests);
             self.write(")");
+
+            // A long list of scalars or sequences that doesn't fit on one line
+            // gets one element per line instead
+            if self.options.force_style == ForceStyle::None
+                && self.inline_objects == 0
+                && self.inline_sequences == 0
+                && nests
+                && entries
+                    .iter()
+                    .all(|entry| is_wrappable_item(entry.syntax()))
+                && self.line_too_wide(start)
+            {
+                self.out.truncate(start);
+                self.format_sequence_multiline(node);
+            }
         }
     }
 
+    /// Write the elements of a sequence one per line, keeping its comments,
+    /// then the closing paren. The opening paren is already written.
+    fn format_sequence_multiline(&mut self, node: &SyntaxNode) {
+        // Multiline format - preserve comments as children of the sequence
+        self.write_newline();
+        self.indent_level += 1;
+
+        // Iterate through all children to preserve comments in order
+        let mut wrote_content = false;
+        let mut consecutive_newlines = 0;
+        for el in node.children_with_tokens() {
+            match el.kind() {
+                SyntaxKind::NEWLINE => {
+                    consecutive_newlines += 1;
+                }
+                SyntaxKind::LINE_COMMENT | SyntaxKind::DOC_COMMENT => {
+                    if let Some(token) = el.into_token() {
+                        if wrote_content
+                            && consecutive_newlines == 0
+                            && token.kind() == SyntaxKind::LINE_COMMENT
+                        {
+                            // A comment on the same line as the element before it
+                            self.write(" ");
+                        } else if wrote_content {
+                            self.write_newline();
+                            // 2+ consecutive newlines means there was a blank line
+                            if consecutive_newlines >= 2 {
+                                self.write_newline();
+                            }
+                        }
+                        self.write(token.text());
+                        wrote_content = true;
+                        consecutive_newlines = 0;
+                    }
+                }
+                SyntaxKind::ENTRY => {
+                    if let Some(entry_node) = el.into_node() {
+                        if wrote_content {
+                            self.write_newline();
+                            // 2+ consecutive newlines means there was a blank line
+                            if consecutive_newlines >= 2 {
+                                self.write_newline();
+                            }
+                        }
+                        // Format the entry's value (sequence entries have implicit unit keys)
+                        if let Some(key) =
+                            entry_node.children().find(|n| n.kind() == SyntaxKind::KEY)
+                        {
+                            for child in key.children() {
+                                self.format_node(&child);
+                            }
+                        }
+                        wrote_content = true;
+                        consecutive_newlines = 0;
+                    }
+                }
+                // Skip whitespace, parens - we handle formatting ourselves
+                SyntaxKind::WHITESPACE | SyntaxKind::L_PAREN | SyntaxKind::R_PAREN => {}
+                _ => {
+                    consecutive_newlines = 0;
+                }
+            }
+        }
+
+        self.write_newline();
+        self.indent_level -= 1;
+        self.write(")");
+    }
+
+    /// Whether the line holding the output from `start` on is longer than
+    /// the maximum width.
+    fn line_too_wide(&self, start: usize) -> bool {
+        let line_start = self.out[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self.out[start..]
+            .find('\n')
+            .map_or(self.out.len(), |i| start + i);
+        self.out[line_start..line_end].chars().count() > self.options.max_width
+    }
+
     fn format_key(&mut self, node: &SyntaxNode) {
         // Format the key content (scalar, tag, unit, etc.)
         for child in node.children() {
@@ -497,8 +661,9 @@ impl CstFormatter {
         // Also check for direct tokens (like BARE_SCALAR in simple keys)
         for token in node.children_with_tokens().filter_map(|el| el.into_token()) {
             match token.kind() {
-                SyntaxKind::BARE_SCALAR | SyntaxKind::QUOTED_SCALAR | SyntaxKind::RAW_SCALAR => {
-                    self.write(token.text());
+                SyntaxKind::BARE_SCALAR | SyntaxKind::RAW_SCALAR => self.write(token.text()),
+                SyntaxKind::QUOTED_SCALAR => {
+                    self.write_quoted(token.text(), ScalarPosition::ObjectKey)
                 }
                 _ => {}
             }
@@ -515,14 +680,28 @@ impl CstFormatter {
         // Get the scalar token and write it as-is
         for token in node.children_with_tokens().filter_map(|el| el.into_token()) {
             match token.kind() {
-                SyntaxKind::BARE_SCALAR | SyntaxKind::QUOTED_SCALAR | SyntaxKind::RAW_SCALAR => {
-                    self.write(token.text());
+                SyntaxKind::BARE_SCALAR | SyntaxKind::RAW_SCALAR => self.write(token.text()),
+                SyntaxKind::QUOTED_SCALAR => {
+                    self.write_quoted(token.text(), ScalarPosition::of(node))
                 }
                 _ => {}
             }
         }
     }
 
+    /// Write a quoted scalar, without its quotes if `minimize_quotes` is set
+    /// and the content means the same as a bare scalar.
+    fn write_quoted(&mut self, quoted: &str, position: ScalarPosition) {
+        match self
+            .options
+            .minimize_quotes
+            .then(|| unquoted(quoted, position))
+        {
+            Some(Some(bare)) => self.write(&bare),
+            _ => self.write(quoted),
+        }
+    }
+
     fn format_tag(&mut self, node: &SyntaxNode) {
         // The TAG node contains:
         // - TAG_TOKEN: the full `@name` text (including @)
@@ -593,6 +772,63 @@ impl CstFormatter {
     }
 }
 
+/// Where a scalar appears, for deciding whether its quotes can go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScalarPosition {
+    /// Key of an object entry, where a bare dotted key is a path.
+    ObjectKey,
+    /// Payload of a tag: `@tag"x"` would become the tag `@tagx`.
+    TagPayload,
+    Value,
+}
+
+impl ScalarPosition {
+    fn of(scalar: &SyntaxNode) -> Self {
+        let Some(parent) = scalar.parent() else {
+            return ScalarPosition::Value;
+        };
+        match parent.kind() {
+            SyntaxKind::TAG_PAYLOAD => ScalarPosition::TagPayload,
+            // Sequence items are entries with only a key
+            SyntaxKind::KEY
+                if parent
+                    .parent()
+                    .and_then(|entry| entry.parent())
+                    .is_none_or(|container| container.kind() != SyntaxKind::SEQUENCE) =>
+            {
+                ScalarPosition::ObjectKey
+            }
+            _ => ScalarPosition::Value,
+        }
+    }
+}
+
+/// Content of a quoted scalar, if writing it bare keeps its meaning.
+fn unquoted(quoted: &str, position: ScalarPosition) -> Option<Cow<'_, str>> {
+    if position == ScalarPosition::TagPayload {
+        return None;
+    }
+    let content = unescape_quoted(quoted.strip_prefix('"')?.strip_suffix('"')?);
+    let keeps_meaning =
+        can_be_bare(&content) && !(position == ScalarPosition::ObjectKey && content.contains('.'));
+    keeps_meaning.then_some(content)
+}
+
+/// Whether a sequence element is a plain scalar, unit or sequence, without
+/// tags, so a sequence of them can be wrapped one element per line.
+fn is_wrappable_item(entry_node: &SyntaxNode) -> bool {
+    entry_node
+        .children()
+        .filter(|n| n.kind() == SyntaxKind::KEY)
+        .flat_map(|key| key.children())
+        .all(|n| {
+            matches!(
+                n.kind(),
+                SyntaxKind::SCALAR | SyntaxKind::UNIT | SyntaxKind::SEQUENCE
+            )
+        })
+}
+
 /// Check if an entry is a "block" entry (contains a multiline object at top level).
 /// Block entries need blank lines around them per issue #28.
 fn is_block_entry(entry: &Entry) -> bool {
@@ -716,6 +952,43 @@ mod tests {
         insta::assert_snapshot!(output);
     }
 
+    #[test]
+    fn test_minimize_quotes() {
+        let minimize = |source| format_source(source, FormatOptions::default().minimize_quotes());
+
+        assert_eq!(
+            minimize(r#"name "value""#),
+            "name value\n",
+            "safe value should be unquoted"
+        );
+        assert_eq!(minimize(r#""host" "localhost""#), "host localhost\n");
+        assert_eq!(
+            minimize("tags (\"a\" \"b c\")\nopts {x \"1\"}\nattr a>\"b\""),
+            "tags (a \"b c\")\nopts {x 1}\nattr a>b\n"
+        );
+        // Escapes are resolved before deciding
+        assert_eq!(minimize(r#"s "\u{61}b""#), "s ab\n");
+    }
+
+    #[test]
+    fn test_minimize_quotes_keeps_meaningful_quotes() {
+        let source = r#"empty ""
+comment "//not a comment"
+raw "r#x"
+heredoc "<<EOF"
+space "a b"
+at "@tag"
+attr "a>b"
+eq "a=b"
+"a.b" dotted
+tagged @tag"x"
+newline "a\nb"
+null "null"
+"#;
+        let output = format_source(source, FormatOptions::default().minimize_quotes());
+        assert_eq!(output, format(source));
+    }
+
     #[test]
     fn test_preserves_comments() {
         let input = r#"// This is a comment
@@ -726,6 +999,17 @@ age 30"#;
         insta::assert_snapshot!(output);
     }
 
+    #[test]
+    fn test_keeps_inline_comments_on_their_line() {
+        let input = "port 8080   // main port\nserver {\n  host localhost // the host\n  // about tls\n  tls true\n}\ntags (a // first\n b)";
+        let output = format(input);
+        assert_eq!(
+            output,
+            "port 8080 // main port\n\nserver {\n    host localhost // the host\n    // about tls\n    tls true\n}\n\ntags (\n    a // first\n    b\n)\n"
+        );
+        assert_eq!(format(&output), output);
+    }
+
     #[test]
     fn test_inline_object() {
         let input = "point {x 1, y 2}";
@@ -904,6 +1188,51 @@ schema {
         assert_eq!(output.trim(), "items (a b c)");
     }
 
+    #[test]
+    fn test_long_sequence_wraps() {
+        let items: Vec<String> = (1..=40).map(|i| i.to_string()).collect();
+        let input = format!("items ({})", items.join(" "));
+        let output = format(&input);
+        let expected: String = items.iter().map(|item| format!("    {item}\n")).collect();
+        assert_eq!(output, format!("items (\n{expected})\n"));
+        assert_eq!(format(&output), output, "formatting should be idempotent");
+
+        // Inside an object, elements are indented one level past the key
+        let output = format_source(
+            "server {\n    hosts (alpha beta gamma delta)\n}",
+            FormatOptions::default().max_width(20),
+        );
+        assert_eq!(
+            output,
+            "server {\n    hosts (\n        alpha\n        beta\n        gamma\n        delta\n    )\n}\n"
+        );
+
+        // Forcing inline output keeps it on one line
+        let output = format_source(&input, FormatOptions::default().inline());
+        assert_eq!(output.trim(), input);
+    }
+
+    #[test]
+    fn test_long_nested_sequences_wrap_outside_in() {
+        let wrap = |source| format_source(source, FormatOptions::default().max_width(30));
+
+        // The outer sequence wraps first, then any element still too wide
+        let output = wrap("m ((alpha beta gamma delta epsilon zeta) (a b))");
+        assert_eq!(
+            output,
+            "m (\n    (\n        alpha\n        beta\n        gamma\n        delta\n        epsilon\n        zeta\n    )\n    (a b)\n)\n"
+        );
+        assert_eq!(wrap(&output), output, "formatting should be idempotent");
+
+        // Short elements stay inline on their own lines
+        let output = wrap("m ((a b) (c d) (e f) (g h) (i j) (k l))");
+        assert_eq!(
+            output,
+            "m (\n    (a b)\n    (c d)\n    (e f)\n    (g h)\n    (i j)\n    (k l)\n)\n"
+        );
+        assert_eq!(wrap(&output), output, "formatting should be idempotent");
+    }
+
     #[test]
     fn test_sequence_with_doc_comment() {
         let input = r#"items (
@@ -1616,6 +1945,48 @@ schema {@ @object{
 }}"#
         ));
     }
+    fn apply_edits(source: &str, edits: &[TextEdit]) -> String {
+        let mut out = source.to_string();
+        for edit in edits.iter().rev() {
+            out.replace_range(edit.range.clone(), &edit.new_text);
+        }
+        out
+    }
+
+    #[test]
+    fn test_format_range_only_touches_selected_entries() {
+        let source = "name   app\n\nserver {\n    host   localhost\n    tls {\n  enabled   true\n          cert {path   /etc/cert.pem}\n    }\n}\n\nport   8080\n";
+        let start = source.find("tls").unwrap();
+        let end = source.find("\n}\n\nport").unwrap();
+
+        let edits = format_source_range(source, start..end, FormatOptions::default());
+        assert_eq!(edits.len(), 1, "{edits:?}");
+        assert_eq!(
+            apply_edits(source, &edits),
+            "name   app\n\nserver {\n    host   localhost\n    tls {\n        enabled true\n        cert {path /etc/cert.pem}\n    }\n}\n\nport   8080\n"
+        );
+    }
+
+    #[test]
+    fn test_format_range_partial_entry_formats_nested() {
+        let source = "server {\n    tls {\n  enabled   true\n          verify   false\n    }\n}\n";
+        let start = source.find("verify").unwrap();
+        let end = source.find("false").unwrap() + "false".len();
+
+        let edits = format_source_range(source, start..end, FormatOptions::default());
+        assert_eq!(
+            apply_edits(source, &edits),
+            "server {\n    tls {\n  enabled   true\n        verify false\n    }\n}\n"
+        );
+
+        // Already formatted and broken sources give no edits
+        let formatted = format(source);
+        assert!(
+            format_source_range(&formatted, 0..formatted.len(), FormatOptions::default())
+                .is_empty()
+        );
+        assert!(format_source_range("a {b", 0..4, FormatOptions::default()).is_empty());
+    }
 }
 
 #[cfg(test)]
@@ -6,12 +6,17 @@
 
 mod cst_format;
 mod options;
+mod redact;
 mod scalar;
 mod value_format;
 mod writer;
 
-pub use cst_format::{format_cst, format_source};
-pub use options::FormatOptions;
-pub use scalar::{can_be_bare, count_escapes, count_newlines, escape_quoted, unescape_quoted};
+pub use cst_format::{TextEdit, format_cst, format_source, format_source_range};
+pub use options::{EnumRepr, FormatOptions};
+pub use redact::{REDACTED, redact_source};
+pub use scalar::{
+    ScalarStyle, can_be_bare, canonical_number, count_escapes, count_newlines, escape_quoted,
+    render_scalar, unescape_quoted,
+};
 pub use value_format::{format_object_braced, format_value, format_value_default};
 pub use writer::StyxWriter;
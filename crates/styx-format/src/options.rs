@@ -12,6 +12,19 @@ pub enum ForceStyle {
     Multiline,
 }
 
+/// How serializers write enum variants.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum EnumRepr {
+    /// The variant is a tag on its payload: `@circle{radius 1}`.
+    #[default]
+    Tagged,
+
+    /// An object whose only key is the variant: `{circle {radius 1}}`, the
+    /// shape other formats call externally tagged. Unit variants are written
+    /// as plain scalars, like `circle`.
+    External,
+}
+
 /// Options for Styx serialization.
 #[derive(Debug, Clone)]
 pub struct FormatOptions {
@@ -35,6 +48,41 @@ pub struct FormatOptions {
     pub heredoc_line_threshold: usize,
 
     pub force_style: ForceStyle,
+
+    /// Sort object entries by key (default: false)
+    /// Only applies when formatting a `Value`, not existing source.
+    pub sort_keys: bool,
+
+    /// Write map entries in the order the map iterates them, rather than
+    /// sorted by key (default: false). Only applies when serializing a Rust
+    /// value, for ordered maps like `IndexMap`.
+    pub preserve_map_order: bool,
+
+    /// Rewrite number scalars in canonical form (default: false)
+    /// Only bare scalars are rewritten; quoted ones are strings and keep
+    /// their quotes.
+    /// See [`canonical_number`](crate::canonical_number).
+    pub canonical_numbers: bool,
+
+    /// Drop quotes that aren't needed, e.g. `name "value"` → `name value`
+    /// (default: false). Only applies when formatting existing source.
+    pub minimize_quotes: bool,
+
+    /// Quote every string value, even if it could be bare (default: false)
+    /// Keeps a string `"true"` or `"123"` apart from a bool or number.
+    pub always_quote_strings: bool,
+
+    /// Write objects parsed from attribute syntax (`key>value`) as
+    /// attributes again (default: true). Only applies when formatting a `Value`.
+    pub keep_attributes: bool,
+
+    /// Keys whose values are written as `"***"` (default: none)
+    /// See [`redact_keys`](Self::redact_keys).
+    pub redact_keys: Vec<String>,
+
+    /// How enum variants are written (default: [`EnumRepr::Tagged`])
+    /// Only applies when serializing a Rust value.
+    pub enum_repr: EnumRepr,
 }
 
 impl Default for FormatOptions {
@@ -47,6 +95,14 @@ impl Default for FormatOptions {
             inline_sequence_threshold: 8,
             heredoc_line_threshold: 2,
             force_style: ForceStyle::None,
+            sort_keys: false,
+            preserve_map_order: false,
+            canonical_numbers: false,
+            minimize_quotes: false,
+            always_quote_strings: false,
+            keep_attributes: true,
+            redact_keys: Vec::new(),
+            enum_repr: EnumRepr::Tagged,
         }
     }
 }
@@ -70,14 +126,157 @@ impl FormatOptions {
     }
 
     /// Set a custom indentation string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `indent` is empty or holds anything but spaces and tabs.
     pub fn indent(mut self, indent: &'static str) -> Self {
+        assert!(
+            !indent.is_empty() && indent.chars().all(|c| c == ' ' || c == '\t'),
+            "indent must be one or more spaces or tabs, got {indent:?}"
+        );
         self.indent = indent;
         self
     }
 
     /// Set max line width.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero.
     pub fn max_width(mut self, width: usize) -> Self {
+        assert!(width > 0, "max width must be greater than zero");
         self.max_width = width;
         self
     }
+
+    /// Set the width that must be left at an object's indentation for it to
+    /// be written inline.
+    pub fn min_inline_width(mut self, width: usize) -> Self {
+        self.min_inline_width = width;
+        self
+    }
+
+    /// Write strings with more than `lines` lines as heredocs.
+    pub fn heredoc_line_threshold(mut self, lines: usize) -> Self {
+        self.heredoc_line_threshold = lines;
+        self
+    }
+
+    /// Sort object entries by key.
+    pub fn sorted(mut self) -> Self {
+        self.sort_keys = true;
+        self
+    }
+
+    /// Write map entries in iteration order rather than sorted by key.
+    pub fn preserve_map_order(mut self) -> Self {
+        self.preserve_map_order = true;
+        self
+    }
+
+    /// Write numbers in canonical form.
+    pub fn canonical_numbers(mut self) -> Self {
+        self.canonical_numbers = true;
+        self
+    }
+
+    /// Write quoted scalars bare where that doesn't change their meaning.
+    ///
+    /// Quotes stay on empty strings, text that would read as other syntax
+    /// (`//`, `r#`, `<<`), text with special characters or whitespace, dotted
+    /// keys (which would become paths), and tag payloads.
+    pub fn minimize_quotes(mut self) -> Self {
+        self.minimize_quotes = true;
+        self
+    }
+
+    /// Quote every string value when serializing.
+    ///
+    /// Without this, a `String` field holding `true` is written bare and reads
+    /// back the same as a `bool`. Keys stay bare where possible.
+    pub fn always_quote_strings(mut self) -> Self {
+        self.always_quote_strings = true;
+        self
+    }
+
+    /// Write every object in braces, including ones parsed from attribute
+    /// syntax such as `server host>localhost port>8080`.
+    pub fn expand_attributes(mut self) -> Self {
+        self.keep_attributes = false;
+        self
+    }
+
+    /// Set how enum variants are written when serializing.
+    ///
+    /// Deserializing accepts both representations, so output written with
+    /// [`EnumRepr::External`] for tools expecting that shape reads back.
+    pub fn enum_repr(mut self, repr: EnumRepr) -> Self {
+        self.enum_repr = repr;
+        self
+    }
+
+    /// Hide the values of these keys, e.g. for logging a config with secrets.
+    ///
+    /// The value of every entry whose key matches one of `keys`, ignoring
+    /// ASCII case, is written as `"***"` instead, however deeply it is nested
+    /// and whatever its type. Applies when formatting a `Value` and when
+    /// serializing with `facet_styx::to_string_with_options`; see
+    /// [`redact_source`](crate::redact_source) for existing source.
+    ///
+    /// ```
+    /// use styx_format::{FormatOptions, format_value};
+    ///
+    /// let config = styx_tree::parse("db {user admin, password hunter2}").unwrap();
+    /// let options = FormatOptions::default().redact_keys(["password"]);
+    /// assert_eq!(
+    ///     format_value(&config, options),
+    ///     "db {user admin, password \"***\"}\n"
+    /// );
+    /// ```
+    pub fn redact_keys<I, K>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.redact_keys = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Whether the value of `key` is hidden by [`redact_keys`](Self::redact_keys).
+    pub fn is_redacted(&self, key: &str) -> bool {
+        self.redact_keys
+            .iter()
+            .any(|redacted| redacted.eq_ignore_ascii_case(key))
+    }
+
+    /// Options for a deterministic representation of a value, e.g. for
+    /// hashing or diffing: sorted keys, canonical numbers and braced objects.
+    ///
+    /// Equivalent documents format identically regardless of their key order,
+    /// quoting, number spelling, attribute syntax or whitespace. Comments
+    /// other than doc comments are not part of a `Value` and are dropped.
+    pub fn canonical() -> Self {
+        Self::default()
+            .sorted()
+            .canonical_numbers()
+            .expand_attributes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "indent must be one or more spaces or tabs")]
+    fn test_empty_indent_is_rejected() {
+        let _ = FormatOptions::new().indent("");
+    }
+
+    #[test]
+    #[should_panic(expected = "max width must be greater than zero")]
+    fn test_zero_max_width_is_rejected() {
+        let _ = FormatOptions::new().max_width(0);
+    }
 }
@@ -0,0 +1,153 @@
+//! Hiding the values of secret keys in Styx text.
+
+use styx_parse::Span;
+use styx_tree::{BuildError, Payload, Value};
+
+use crate::{FormatOptions, ScalarStyle, render_scalar};
+
+/// Text that replaces a redacted value, written as a quoted scalar.
+pub const REDACTED: &str = "***";
+
+/// Replace the values of keys matched by [`FormatOptions::redact_keys`] in a
+/// Styx document, leaving the rest of the text as it is.
+///
+/// Each redacted value becomes `"***"`, whether it was a scalar, a tagged
+/// value, an object or a sequence. Keys without a value have nothing to hide
+/// and are left alone. Fails if `source` is not a valid document, rather than
+/// returning text that may still hold secrets.
+///
+/// ```
+/// use styx_format::{FormatOptions, redact_source};
+///
+/// let options = FormatOptions::default().redact_keys(["token"]);
+/// let redacted = redact_source("api {url example.com, token @env\"API_TOKEN\"}", &options);
+/// assert_eq!(
+///     redacted.unwrap(),
+///     "api {url example.com, token \"***\"}"
+/// );
+/// ```
+pub fn redact_source(source: &str, options: &FormatOptions) -> Result<String, BuildError> {
+    if options.redact_keys.is_empty() {
+        return Ok(source.to_string());
+    }
+
+    let tree = styx_tree::parse(source)?;
+    let mut spans = Vec::new();
+    collect_redacted(&tree, options, &mut spans);
+    spans.sort_by_key(|span| span.start);
+
+    let replacement = render_scalar(REDACTED, ScalarStyle::Quoted);
+    let mut out = String::with_capacity(source.len());
+    let mut copied = 0;
+    for span in spans {
+        out.push_str(&source[copied..span.start as usize]);
+        out.push_str(&replacement);
+        copied = span.end as usize;
+    }
+    out.push_str(&source[copied..]);
+    Ok(out)
+}
+
+/// Collect the source spans of redacted values, outermost only.
+fn collect_redacted(value: &Value, options: &FormatOptions, spans: &mut Vec<Span>) {
+    match &value.payload {
+        Some(Payload::Object(obj)) => {
+            for entry in &obj.entries {
+                let redacted = entry
+                    .key
+                    .as_str()
+                    .is_some_and(|key| options.is_redacted(key));
+                match value_span(&entry.value) {
+                    // A key without a value has a unit spanning the key itself
+                    Some(span) if redacted && entry.key.span != Some(span) => spans.push(span),
+                    _ if redacted => {}
+                    _ => collect_redacted(&entry.value, options, spans),
+                }
+            }
+        }
+        Some(Payload::Sequence(seq)) => {
+            for item in &seq.items {
+                collect_redacted(item, options, spans);
+            }
+        }
+        Some(Payload::Scalar(_)) | None => {}
+    }
+}
+
+/// Span of a value including its tag, which the value's own span leaves out.
+fn value_span(value: &Value) -> Option<Span> {
+    let tag = value.tag.as_ref().and_then(|tag| tag.span);
+    match (tag, value.span) {
+        (Some(tag), Some(span)) => {
+            Some(Span::new(tag.start.min(span.start), tag.end.max(span.end)))
+        }
+        (tag, span) => span.or(tag),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redact(source: &str, keys: &[&str]) -> String {
+        let options = FormatOptions::default().redact_keys(keys.iter().copied());
+        redact_source(source, &options).unwrap()
+    }
+
+    #[test]
+    fn test_redact_scalars_and_structures() {
+        let source = "\
+name app
+db {
+    host localhost
+    password hunter2
+}
+keys (a b c)
+Secret <<TXT
+line one
+line two
+TXT
+";
+        assert_eq!(
+            redact(source, &["PASSWORD", "keys", "secret"]),
+            "\
+name app
+db {
+    host localhost
+    password \"***\"
+}
+keys \"***\"
+Secret \"***\"
+"
+        );
+    }
+
+    #[test]
+    fn test_redact_tagged_and_nested() {
+        assert_eq!(
+            redact(
+                "a @env\"X\", b @secret, c {token t, inner {token u}}",
+                &["a", "b", "token"]
+            ),
+            "a \"***\", b \"***\", c {token \"***\", inner {token \"***\"}}"
+        );
+        // The outermost match hides everything inside it
+        assert_eq!(redact("token {token t}", &["token"]), "token \"***\"");
+        // Dotted paths redact their last segment's value
+        assert_eq!(
+            redact("db.password pw", &["password"]),
+            "db.password \"***\""
+        );
+    }
+
+    #[test]
+    fn test_redact_leaves_the_rest_alone() {
+        let source = "// comment\nuser admin   // trailing\n/// doc\npassword @\nflag\n";
+        assert_eq!(
+            redact(source, &["password", "flag"]),
+            "// comment\nuser admin   // trailing\n/// doc\npassword \"***\"\nflag\n"
+        );
+        assert_eq!(redact(source, &[]), source);
+        assert!(redact_source("a {", &FormatOptions::default().redact_keys(["a"])).is_err());
+    }
+}
@@ -5,14 +5,19 @@
 
 use std::borrow::Cow;
 
+use crate::FormatOptions;
+
+pub use styx_tree::canonical_number;
+
 /// Check if a string can be written as a bare scalar.
 ///
 /// A bare scalar is valid when:
 /// 1. It's not empty
 /// 2. It doesn't start with characters that look like other syntax (`//`, `r#`, `<<`)
-/// 3. It doesn't contain special characters: `{}(),"=@` or whitespace
+/// 3. It doesn't contain special characters: `{}(),"=@>` or whitespace
+/// 4. It isn't `null`, which optional targets read back as no value
 pub fn can_be_bare(s: &str) -> bool {
-    if s.is_empty() {
+    if s.is_empty() || s == "null" {
         return false;
     }
     // Cannot start with characters that look like other syntax
@@ -20,8 +25,84 @@ pub fn can_be_bare(s: &str) -> bool {
         return false;
     }
     // Cannot contain special characters
-    !s.chars()
-        .any(|c| matches!(c, '{' | '}' | '(' | ')' | ',' | '"' | '=' | '@') || c.is_whitespace())
+    !s.chars().any(|c| {
+        matches!(c, '{' | '}' | '(' | ')' | ',' | '"' | '=' | '@' | '>') || c.is_whitespace()
+    })
+}
+
+/// How [`render_scalar`] may write a scalar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScalarStyle {
+    /// Bare if possible. Otherwise a heredoc for multi-line text, a raw string
+    /// when quoting would take many escapes, or a quoted string.
+    #[default]
+    Auto,
+    /// Bare if possible, otherwise a quoted string. For keys, which must stay
+    /// on one line.
+    Inline,
+    /// Always a quoted string, e.g. for tag payloads (`@tag"text"`), where
+    /// bare text would run into the tag name.
+    Quoted,
+}
+
+/// Render `text` as Styx scalar syntax.
+///
+/// This is the one place that decides between bare, quoted, raw and heredoc
+/// scalars; the serializers and formatters all go through it.
+///
+/// ```
+/// use styx_format::{ScalarStyle, render_scalar};
+///
+/// assert_eq!(render_scalar("localhost", ScalarStyle::Auto), "localhost");
+/// assert_eq!(render_scalar("hello world", ScalarStyle::Auto), r#""hello world""#);
+/// assert_eq!(render_scalar("localhost", ScalarStyle::Quoted), r#""localhost""#);
+/// ```
+pub fn render_scalar(text: &str, style: ScalarStyle) -> Cow<'_, str> {
+    render_scalar_with(text, style, FormatOptions::default().heredoc_line_threshold)
+}
+
+/// [`render_scalar`] with a custom heredoc threshold (see
+/// [`FormatOptions::heredoc_line_threshold`]).
+pub(crate) fn render_scalar_with(
+    text: &str,
+    style: ScalarStyle,
+    heredoc_line_threshold: usize,
+) -> Cow<'_, str> {
+    if style != ScalarStyle::Quoted && can_be_bare(text) {
+        return Cow::Borrowed(text);
+    }
+    if style == ScalarStyle::Auto {
+        if count_newlines(text) >= heredoc_line_threshold {
+            return Cow::Owned(heredoc(text));
+        }
+        if count_escapes(text) > 3 && !text.contains("\"#") {
+            return Cow::Owned(raw_string(text));
+        }
+    }
+    Cow::Owned(format!("\"{}\"", escape_quoted(text)))
+}
+
+/// `text` as a raw string (`r#"..."#`), with as few `#` as possible.
+fn raw_string(text: &str) -> String {
+    let mut hashes = 0;
+    let mut check = String::from("\"");
+    while text.contains(&check) {
+        hashes += 1;
+        check = format!("\"{}#", "#".repeat(hashes - 1));
+    }
+    let hashes = "#".repeat(hashes);
+    format!("r{hashes}\"{text}\"{hashes}")
+}
+
+/// `text` as a heredoc, with a delimiter that doesn't appear in it.
+fn heredoc(text: &str) -> String {
+    let delimiters = ["TEXT", "END", "HEREDOC", "DOC", "STR", "CONTENT"];
+    let delimiter = delimiters
+        .iter()
+        .find(|d| !text.contains(*d))
+        .unwrap_or(&"TEXT");
+    let newline = if text.ends_with('\n') { "" } else { "\n" };
+    format!("<<{delimiter}\n{text}{newline}{delimiter}")
 }
 
 /// Count escape sequences needed for a quoted string.
@@ -154,6 +235,7 @@ mod tests {
         assert!(can_be_bare("https://example.com/path"));
         assert!(can_be_bare("true"));
         assert!(can_be_bare("false"));
+        assert!(can_be_bare("nullable"));
 
         // These must be quoted
         assert!(!can_be_bare("")); // empty
@@ -165,6 +247,43 @@ mod tests {
         assert!(!can_be_bare("//comment")); // looks like comment
         assert!(!can_be_bare("r#raw")); // looks like raw string
         assert!(!can_be_bare("<<HERE")); // looks like heredoc
+        assert!(!can_be_bare("a>b")); // attribute syntax
+        assert!(!can_be_bare("null")); // reads back as no value
+    }
+
+    #[test]
+    fn test_render_scalar() {
+        use ScalarStyle::*;
+
+        // Plain identifiers are bare unless quoting is forced
+        assert_eq!(render_scalar("localhost", Auto), "localhost");
+        assert_eq!(render_scalar("localhost", Inline), "localhost");
+        assert_eq!(render_scalar("localhost", Quoted), r#""localhost""#);
+
+        // Empty strings and special characters need quotes
+        for style in [Auto, Inline, Quoted] {
+            assert_eq!(render_scalar("", style), r#""""#);
+            assert_eq!(render_scalar("hello world", style), r#""hello world""#);
+            assert_eq!(render_scalar("a>b", style), r#""a>b""#);
+            assert_eq!(render_scalar("//x", style), r#""//x""#);
+        }
+
+        // Multi-line text becomes a heredoc, except where it can't be one
+        assert_eq!(render_scalar("a\nb\nc", Auto), "<<TEXT\na\nb\nc\nTEXT");
+        assert_eq!(render_scalar("TEXT\nb\n", Auto), "<<END\nTEXT\nb\nEND");
+        assert_eq!(render_scalar("a\nb", Auto), r#""a\nb""#);
+        assert_eq!(render_scalar("a\nb\nc", Inline), r#""a\nb\nc""#);
+        assert_eq!(render_scalar("a\nb\nc", Quoted), r#""a\nb\nc""#);
+
+        // Lots of escapes make a raw string
+        assert_eq!(
+            render_scalar(r#"say "a" "b""#, Auto),
+            r##"r#"say "a" "b""#"##
+        );
+        assert_eq!(
+            render_scalar(r#"say "a" "b""#, Inline),
+            r#""say \"a\" \"b\"""#
+        );
     }
 
     #[test]
@@ -1,8 +1,10 @@
 //! Format `styx_tree::Value` to Styx text.
 
-use styx_tree::{Entry, Object, Payload, Sequence, Value};
+use std::borrow::Cow;
 
-use crate::{FormatOptions, StyxWriter};
+use styx_tree::{Entry, Object, ObjectOrigin, Payload, Scalar, ScalarKind, Sequence, Value};
+
+use crate::{FormatOptions, REDACTED, ScalarStyle, StyxWriter, can_be_bare, render_scalar};
 
 /// Format a Value as a Styx document string.
 ///
@@ -31,11 +33,15 @@ pub fn format_object_braced(obj: &Object, options: FormatOptions) -> String {
 
 struct ValueFormatter {
     writer: StyxWriter,
+    sort_keys: bool,
+    canonical_numbers: bool,
 }
 
 impl ValueFormatter {
     fn new(options: FormatOptions) -> Self {
         Self {
+            sort_keys: options.sort_keys,
+            canonical_numbers: options.canonical_numbers,
             writer: StyxWriter::with_options(options),
         }
     }
@@ -85,12 +91,21 @@ impl ValueFormatter {
             }
             Some(Payload::Scalar(s)) => {
                 // If tagged, wrap scalar in parens: @tag(scalar)
+                let text = self.scalar_text(s);
                 if has_tag {
                     self.writer.begin_seq_after_tag();
-                    self.writer.write_scalar(&s.text);
-                    self.writer.end_seq().ok();
+                }
+                // `render_scalar` quotes the string `null`, but a bare `null`
+                // must stay bare to keep reading back as no value
+                if s.kind == ScalarKind::Bare && text == "null" {
+                    self.writer.write_bare_scalar(&text);
+                } else if self.keeps_quotes(s) {
+                    self.writer.write_quoted_scalar(&text);
                 } else {
-                    self.writer.write_scalar(&s.text);
+                    self.writer.write_scalar(&text);
+                }
+                if has_tag {
+                    self.writer.end_seq().ok();
                 }
             }
             Some(Payload::Sequence(seq)) => {
@@ -145,8 +160,34 @@ impl ValueFormatter {
         self.writer.end_struct().ok();
     }
 
+    /// Scalar text to write, with numbers in canonical form if requested.
+    ///
+    /// Only bare scalars are numbers; a quoted `"007"` is a string and is
+    /// written as is.
+    fn scalar_text<'a>(&self, scalar: &'a Scalar) -> Cow<'a, str> {
+        let canonical = (self.canonical_numbers && scalar.kind == ScalarKind::Bare)
+            .then(|| crate::canonical_number(&scalar.text));
+        match canonical {
+            Some(Some(canonical)) => Cow::Owned(canonical),
+            _ => Cow::Borrowed(&scalar.text),
+        }
+    }
+
+    /// Whether a quoted scalar must stay quoted: with canonical numbers, a
+    /// quoted `"007"` written bare would be rewritten as `7` the next time.
+    fn keeps_quotes(&self, scalar: &Scalar) -> bool {
+        self.canonical_numbers
+            && scalar.kind != ScalarKind::Bare
+            && crate::canonical_number(&scalar.text).is_some()
+    }
+
     fn format_object_entries(&mut self, obj: &Object) {
-        for entry in &obj.entries {
+        let mut entries: Vec<&Entry> = obj.entries.iter().collect();
+        if self.sort_keys {
+            // Tagged and unit keys (`@schema`, `@`) first, then by key text
+            entries.sort_by_cached_key(|e| (e.key.as_str().is_some(), self.format_key(&e.key)));
+        }
+        for entry in entries {
             self.format_entry(entry);
         }
     }
@@ -162,9 +203,71 @@ impl ValueFormatter {
             self.writer.field_key_raw(&key_str).ok();
         }
 
+        let redacted = entry
+            .key
+            .as_str()
+            .is_some_and(|key| self.writer.options().is_redacted(key));
+        if redacted {
+            self.writer.before_value();
+            self.writer
+                .write_str(&render_scalar(REDACTED, ScalarStyle::Quoted));
+            return;
+        }
+
+        if let Some(attributes) = self.attribute_text(&entry.value) {
+            self.writer.before_value();
+            self.writer.write_str(&attributes);
+            return;
+        }
+
         self.format_value(&entry.value);
     }
 
+    /// `value` written as attributes (`key>value ...`), if it was parsed from
+    /// attribute syntax and can be written that way again.
+    ///
+    /// Attributes can't hold tags, comments or multi-line values, and their
+    /// keys must be bare; objects that gained any of these since parsing are
+    /// written in braces instead.
+    fn attribute_text(&self, value: &Value) -> Option<String> {
+        let options = self.writer.options();
+        if !options.keep_attributes || value.tag.is_some() {
+            return None;
+        }
+        let Some(Payload::Object(obj)) = &value.payload else {
+            return None;
+        };
+        if obj.origin != ObjectOrigin::Attributes || obj.entries.is_empty() {
+            return None;
+        }
+
+        let mut attributes = Vec::with_capacity(obj.entries.len());
+        for entry in &obj.entries {
+            let key = entry
+                .key
+                .as_str()
+                .filter(|key| can_be_bare(key) && !key.contains('.'))?;
+            if entry.value.tag.is_some()
+                || entry.doc_comment.is_some()
+                || !entry.comments.is_empty()
+            {
+                return None;
+            }
+            let text = if options.is_redacted(key) {
+                render_scalar(REDACTED, ScalarStyle::Quoted).into_owned()
+            } else {
+                let mut formatter = ValueFormatter::new(options.clone().inline());
+                formatter.format_value(&entry.value);
+                formatter.finish()
+            };
+            if text.contains('\n') {
+                return None;
+            }
+            attributes.push(format!("{key}>{text}"));
+        }
+        Some(attributes.join(" "))
+    }
+
     /// Format a key value to string.
     /// Keys are scalars or unit, optionally tagged.
     fn format_key(&self, key: &Value) -> String {
@@ -187,13 +290,7 @@ impl ValueFormatter {
             }
             Some(Payload::Scalar(s)) => {
                 // Always check if the text can be bare, regardless of original ScalarKind
-                if crate::scalar::can_be_bare(&s.text) {
-                    result.push_str(&s.text);
-                } else {
-                    result.push('"');
-                    result.push_str(&crate::scalar::escape_quoted(&s.text));
-                    result.push('"');
-                }
+                result.push_str(&render_scalar(&s.text, ScalarStyle::Inline));
             }
             Some(Payload::Sequence(_) | Payload::Object(_)) => {
                 panic!("object key cannot be a sequence or object: {:?}", key);
@@ -208,13 +305,13 @@ impl ValueFormatter {
 mod tests {
     use super::*;
     use styx_parse::ScalarKind;
-    use styx_tree::{Object, Payload, Scalar, Sequence, Tag};
+    use styx_tree::{Object, ObjectOrigin, Payload, Scalar, Sequence, Tag};
 
     fn scalar(text: &str) -> Value {
         Value {
             tag: None,
             payload: Some(Payload::Scalar(Scalar {
-                text: text.to_string(),
+                text: text.into(),
                 kind: ScalarKind::Bare,
                 span: None,
             })),
@@ -234,19 +331,13 @@ mod tests {
     }
 
     fn entry(key: &str, value: Value) -> Entry {
-        Entry {
-            key: scalar(key),
-            value,
-            doc_comment: None,
-        }
+        Entry::new(scalar(key), value)
     }
 
     fn entry_with_doc(key: &str, value: Value, doc: &str) -> Entry {
-        Entry {
-            key: scalar(key),
-            value,
-            doc_comment: Some(doc.to_string()),
-        }
+        let mut entry = entry(key, value);
+        entry.doc_comment = Some(doc.to_string());
+        entry
     }
 
     fn obj_value(entries: Vec<Entry>) -> Value {
@@ -255,6 +346,7 @@ mod tests {
             payload: Some(Payload::Object(Object {
                 entries,
                 span: None,
+                origin: ObjectOrigin::Braces,
             })),
             span: None,
         }
@@ -286,6 +378,7 @@ mod tests {
             payload: Some(Payload::Object(Object {
                 entries: vec![entry("name", scalar("Alice")), entry("age", scalar("30"))],
                 span: None,
+                origin: ObjectOrigin::Braces,
             })),
             span: None,
         };
@@ -325,6 +418,109 @@ mod tests {
         insta::assert_snapshot!(result);
     }
 
+    #[test]
+    fn test_canonical_output_is_style_independent() {
+        let a = styx_tree::parse(
+            "@schema app.styx\nname \"my-app\"\nport 0x1F90\nratio 0.50\nserver {tls true, host localhost}\ntags (a \"b\")",
+        )
+        .unwrap();
+        let b = styx_tree::parse(
+            "// reordered, requoted, respelled\n\
+             tags (\"a\" b)\n\
+             server {\n  host \"localhost\"\n  tls true\n}\n\
+             ratio 5e-1\n\
+             port +8_080\n\
+             name my-app\n\
+             @schema app.styx\n",
+        )
+        .unwrap();
+
+        let a = format_value(&a, FormatOptions::canonical());
+        let b = format_value(&b, FormatOptions::canonical());
+        assert_eq!(a, b);
+        insta::assert_snapshot!(a);
+    }
+
+    #[test]
+    fn test_simplified_values_round_trip() {
+        let mut doc = styx_tree::parse(
+            "hosts @list{@ (a b)}\nserver @service{@ {port @default{@ 8080}, tls @on{@ @}}}",
+        )
+        .unwrap();
+        doc.simplify();
+
+        let formatted = format_value_default(&doc);
+        assert_eq!(
+            formatted,
+            "hosts @list(a b)\n\nserver @service{\n    port @default{@ 8080}\n    tls @on\n}\n"
+        );
+        assert!(styx_tree::parse(&formatted).unwrap().semantic_eq(&doc));
+    }
+
+    #[test]
+    fn test_attribute_objects_keep_their_syntax() {
+        let doc = styx_tree::parse("server host>localhost port>8080\nlimits {cpu 2}").unwrap();
+        let server = doc.get("server").unwrap().as_object().unwrap();
+        assert_eq!(server.origin, ObjectOrigin::Attributes);
+        let limits = doc.get("limits").unwrap().as_object().unwrap();
+        assert_eq!(limits.origin, ObjectOrigin::Braces);
+
+        assert_eq!(
+            format_value_default(&doc),
+            "server host>localhost port>8080\n\nlimits {cpu 2}\n"
+        );
+        assert_eq!(
+            format_value(&doc, FormatOptions::default().expand_attributes()),
+            "server {host localhost, port 8080}\n\nlimits {cpu 2}\n"
+        );
+    }
+
+    #[test]
+    fn test_format_with_custom_options() {
+        let doc = styx_tree::parse(
+            "zeta 1.50\ndb {user admin, password hunter2, port 5432}\nsrv host>x port>1\nnote \"one\\ntwo\"",
+        )
+        .unwrap();
+        let options = FormatOptions::new()
+            .indent("\t")
+            .heredoc_line_threshold(1)
+            .sorted()
+            .canonical_numbers()
+            .expand_attributes()
+            .redact_keys(["password"]);
+
+        assert_eq!(
+            format_value(&doc, options.clone()),
+            "db {\n\tpassword \"***\"\n\tport 5432\n\tuser admin\n}\n\nnote <<TEXT\none\ntwo\nTEXT\n\nsrv {host x, port 1}\n\nzeta 1.5\n"
+        );
+    }
+
+    #[test]
+    fn test_null_keeps_its_quoting() {
+        let doc = styx_tree::parse("a null\nb \"null\"").unwrap();
+        assert_eq!(format_value_default(&doc), "a null\n\nb \"null\"\n");
+    }
+
+    #[test]
+    fn test_canonical_numbers_keep_quoted_scalars() {
+        let doc = styx_tree::parse("a 007\nb \"007\"\nc r#\"1.50\"#").unwrap();
+        assert_eq!(
+            format_value(&doc, FormatOptions::new().canonical_numbers()),
+            "a 7\n\nb \"007\"\n\nc \"1.50\"\n"
+        );
+    }
+
+    #[test]
+    fn test_canonical_form_is_idempotent() {
+        let doc = styx_tree::parse("a 007\nb \"007\"\nc r#\"1.50\"#\nd 0x10\ne \"+1_0\"").unwrap();
+        let once = format_value(&doc, FormatOptions::canonical());
+        let twice = format_value(
+            &styx_tree::parse(&once).unwrap(),
+            FormatOptions::canonical(),
+        );
+        assert_eq!(once, twice);
+    }
+
     #[test]
     fn test_format_unit() {
         let obj = obj_value(vec![entry("flag", Value::unit())]);
@@ -344,6 +540,7 @@ mod tests {
             payload: Some(Payload::Object(Object {
                 entries,
                 span: None,
+                origin: ObjectOrigin::Braces,
             })),
             span: None,
         }
@@ -356,6 +553,7 @@ mod tests {
             payload: Some(Payload::Object(Object {
                 entries,
                 span: None,
+                origin: ObjectOrigin::Braces,
             })),
             span: None,
         }
@@ -371,6 +569,7 @@ mod tests {
             payload: Some(Payload::Object(Object {
                 entries,
                 span: None,
+                origin: ObjectOrigin::Braces,
             })),
             span: None,
         }
@@ -384,7 +583,7 @@ mod tests {
                 span: None,
             }),
             payload: Some(Payload::Scalar(Scalar {
-                text: text.to_string(),
+                text: text.into(),
                 kind: ScalarKind::Bare,
                 span: None,
             })),
@@ -394,20 +593,12 @@ mod tests {
 
     /// Helper to create a unit entry (@ key)
     fn unit_entry(value: Value) -> Entry {
-        Entry {
-            key: Value::unit(),
-            value,
-            doc_comment: None,
-        }
+        Entry::new(Value::unit(), value)
     }
 
     /// Helper to create a schema declaration entry (@schema key)
     fn schema_entry(value: Value) -> Entry {
-        Entry {
-            key: Value::tag("schema"),
-            value,
-            doc_comment: None,
-        }
+        Entry::new(Value::tag("schema"), value)
     }
 
     // --- Edge Case 1: Schema declaration with blank line after ---
@@ -656,6 +847,7 @@ mod tests {
                     entry_with_doc("port", scalar("8080"), "The server port"),
                 ],
                 span: None,
+                origin: ObjectOrigin::Braces,
             })),
             span: None,
         };
@@ -3,8 +3,10 @@
 //! Provides a structured way to build Styx output with proper formatting,
 //! independent of any serialization framework.
 
+use std::io;
+
 use crate::options::{ForceStyle, FormatOptions};
-use crate::scalar::{can_be_bare, count_escapes, count_newlines, escape_quoted};
+use crate::scalar::{ScalarStyle, render_scalar, render_scalar_with};
 
 /// Context for tracking serialization state.
 #[derive(Debug, Clone)]
@@ -18,7 +20,8 @@ pub enum Context {
         inline_start: bool,
         /// Positions of comma separators written in this struct (for fixing mixed separators)
         comma_positions: Vec<usize>,
-        /// Position right after the opening `{` (for inserting newline when going multiline)
+        /// Position right after the opening `{` (for inserting newline when going multiline),
+        /// or `None` once it has been flushed
         open_brace_pos: Option<usize>,
     },
     /// Inside a sequence - tracks if we've written any items
@@ -44,6 +47,8 @@ pub struct StyxWriter {
     /// If true, force the next scalar to be quoted (used after writing a tag,
     /// since bare scalars cannot be tagged)
     force_quote_next_scalar: bool,
+    /// Last byte handed out by [`flush_settled`](Self::flush_settled), if any
+    last_flushed: Option<u8>,
 }
 
 impl StyxWriter {
@@ -59,11 +64,15 @@ impl StyxWriter {
             stack: Vec::new(),
             skip_next_before_value: false,
             force_quote_next_scalar: false,
+            last_flushed: None,
             options,
         }
     }
 
     /// Consume the writer and return the output bytes.
+    ///
+    /// Output already written by [`flush_settled`](Self::flush_settled) is
+    /// not included.
     pub fn finish(self) -> Vec<u8> {
         self.out
     }
@@ -71,12 +80,97 @@ impl StyxWriter {
     /// Consume the writer and return the output bytes, ensuring a trailing newline.
     /// This matches CST formatter behavior for standalone documents.
     pub fn finish_document(mut self) -> Vec<u8> {
-        if !self.out.is_empty() && !self.out.ends_with(b"\n") {
+        let last = self.out.last().copied().or(self.last_flushed);
+        if last.is_some_and(|b| b != b'\n') {
             self.out.push(b'\n');
         }
         self.out
     }
 
+    /// Number of output bytes held by the writer.
+    pub fn buffered_len(&self) -> usize {
+        self.out.len()
+    }
+
+    /// Buffered output size at which [`flush_settled_if_full`] writes to the
+    /// sink.
+    ///
+    /// [`flush_settled_if_full`]: Self::flush_settled_if_full
+    pub const FLUSH_THRESHOLD: usize = 8 * 1024;
+
+    /// [`flush_settled`](Self::flush_settled), once at least
+    /// [`FLUSH_THRESHOLD`](Self::FLUSH_THRESHOLD) bytes are buffered.
+    ///
+    /// Serializers call this after each value, so output reaches the sink in
+    /// chunks rather than one small write per value.
+    pub fn flush_settled_if_full<W: io::Write + ?Sized>(&mut self, sink: &mut W) -> io::Result<()> {
+        if self.out.len() < Self::FLUSH_THRESHOLD {
+            return Ok(());
+        }
+        self.flush_settled(sink)
+    }
+
+    /// Write the output that can no longer change to `sink`, and drop it
+    /// from the buffer.
+    ///
+    /// An inline struct that turns multi-line rewrites its separators, so
+    /// output after the opening brace of any open inline struct is kept.
+    /// Once more than `max_width` bytes are held back that way, the open
+    /// inline structs are written multi-line instead, as they no longer fit
+    /// on a line. Structs that are already multi-line don't hold anything back.
+    /// Calling this between values lets large collections be written without
+    /// holding the whole document in memory.
+    pub fn flush_settled<W: io::Write + ?Sized>(&mut self, sink: &mut W) -> io::Result<()> {
+        if self.options.force_style != ForceStyle::Inline
+            && self.out.len() - self.settled_len() > self.options.max_width
+        {
+            self.propagate_multiline_to_parents();
+        }
+        let settled = self.settled_len();
+        if settled == 0 {
+            return Ok(());
+        }
+
+        sink.write_all(&self.out[..settled])?;
+        self.last_flushed = Some(self.out[settled - 1]);
+        self.out.drain(..settled);
+        for ctx in &mut self.stack {
+            if let Context::Struct {
+                open_brace_pos,
+                comma_positions,
+                ..
+            } = ctx
+            {
+                *open_brace_pos = open_brace_pos.and_then(|pos| pos.checked_sub(settled));
+                for pos in comma_positions {
+                    *pos -= settled;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Length of the buffered output that can no longer change: everything
+    /// before the first separator or opening brace an open inline struct may
+    /// still rewrite.
+    fn settled_len(&self) -> usize {
+        self.stack
+            .iter()
+            .filter_map(|ctx| match ctx {
+                Context::Struct {
+                    force_multiline,
+                    open_brace_pos,
+                    comma_positions,
+                    ..
+                } => open_brace_pos
+                    .filter(|_| !force_multiline)
+                    .or(comma_positions.first().copied()),
+                Context::Seq { .. } => None,
+            })
+            .min()
+            .unwrap_or(self.out.len())
+    }
+
     /// Consume the writer and return the output as a String.
     ///
     /// # Panics
@@ -85,6 +179,11 @@ impl StyxWriter {
         String::from_utf8(self.out).expect("Styx output should always be valid UTF-8")
     }
 
+    /// The options this writer formats with.
+    pub fn options(&self) -> &FormatOptions {
+        &self.options
+    }
+
     /// Current nesting depth.
     pub fn depth(&self) -> usize {
         self.stack.len()
@@ -356,11 +455,8 @@ impl StyxWriter {
         }
 
         // Write the key - keys are typically bare identifiers
-        if can_be_bare(key) {
-            self.out.extend_from_slice(key.as_bytes());
-        } else {
-            self.write_quoted_string(key);
-        }
+        let key = render_scalar(key, ScalarStyle::Inline);
+        self.out.extend_from_slice(key.as_bytes());
         self.out.push(b' ');
         Ok(())
     }
@@ -487,9 +583,12 @@ impl StyxWriter {
     }
 
     /// Write a string value with appropriate quoting.
+    ///
+    /// Quoted regardless of content if
+    /// [`FormatOptions::always_quote_strings`] is set.
     pub fn write_string(&mut self, s: &str) {
         self.before_value();
-        self.write_scalar_string(s);
+        self.write_scalar_string(s, self.options.always_quote_strings);
     }
 
     /// Write a char value.
@@ -497,7 +596,7 @@ impl StyxWriter {
         self.before_value();
         let mut buf = [0u8; 4];
         let s = c.encode_utf8(&mut buf);
-        self.write_scalar_string(s);
+        self.write_scalar_string(s, self.options.always_quote_strings);
     }
 
     /// Write bytes as hex-encoded string.
@@ -526,9 +625,27 @@ impl StyxWriter {
     }
 
     /// Write a scalar value with appropriate quoting.
-    /// Alias for write_string, for when you have a pre-existing scalar.
+    ///
+    /// Like [`write_string`](Self::write_string), for when you have a
+    /// pre-existing scalar whose type isn't known: it is only quoted if needed.
     pub fn write_scalar(&mut self, s: &str) {
-        self.write_string(s);
+        self.before_value();
+        self.write_scalar_string(s, false);
+    }
+
+    /// Write a scalar bare, as is, even if [`write_scalar`](Self::write_scalar)
+    /// would quote it. For bare scalars from a parsed document, such as `null`.
+    pub fn write_bare_scalar(&mut self, s: &str) {
+        self.before_value();
+        self.out.extend_from_slice(s.as_bytes());
+    }
+
+    /// Write a scalar quoted, even if [`write_scalar`](Self::write_scalar)
+    /// would leave it bare. For quoted scalars from a parsed document that
+    /// would read back as something else bare, such as `"007"`.
+    pub fn write_quoted_scalar(&mut self, s: &str) {
+        self.before_value();
+        self.write_scalar_string(s, true);
     }
 
     /// Write a tag (e.g., `@string`). Same as write_variant_tag.
@@ -606,11 +723,8 @@ impl StyxWriter {
         }
 
         // Write the key
-        if can_be_bare(key) {
-            self.out.extend_from_slice(key.as_bytes());
-        } else {
-            self.write_quoted_string(key);
-        }
+        let key = render_scalar(key, ScalarStyle::Inline);
+        self.out.extend_from_slice(key.as_bytes());
         self.out.push(b' ');
     }
 
@@ -736,25 +850,26 @@ impl StyxWriter {
 
         let mut effective_depth = 0;
         for ctx in self.stack.iter_mut() {
+            // Root doesn't add depth
             if let Context::Struct {
                 inline_start,
                 force_multiline,
-                is_root,
-                open_brace_pos: Some(pos),
+                is_root: false,
+                open_brace_pos,
                 ..
             } = ctx
             {
-                // Root doesn't add depth
-                if !*is_root {
-                    // If this struct started inline but hasn't been fixed yet
-                    if *inline_start && !*force_multiline {
-                        *force_multiline = true;
-                        // Content inside this struct should be at effective_depth + 1
-                        fixes.push((*pos, effective_depth + 1));
-                    }
-                    // After forcing multiline, this struct now contributes to depth
-                    effective_depth += 1;
+                // If this struct started inline but hasn't been fixed yet
+                if *inline_start
+                    && !*force_multiline
+                    && let Some(pos) = open_brace_pos
+                {
+                    *force_multiline = true;
+                    // Content inside this struct should be at effective_depth + 1
+                    fixes.push((*pos, effective_depth + 1));
                 }
+                // After forcing multiline, this struct now contributes to depth
+                effective_depth += 1;
             }
         }
 
@@ -862,83 +977,15 @@ impl StyxWriter {
     }
 
     /// Write a scalar value with appropriate quoting.
-    fn write_scalar_string(&mut self, s: &str) {
-        // Check if we need to force quoting (e.g., after a tag)
-        let force_quote = self.force_quote_next_scalar;
-        self.force_quote_next_scalar = false;
-
-        // Rule 1: Prefer bare scalars when valid (but not after a tag)
-        if !force_quote && can_be_bare(s) {
-            self.out.extend_from_slice(s.as_bytes());
-            return;
-        }
-
-        let newline_count = count_newlines(s);
-        let escape_count = count_escapes(s);
-
-        // Rule 3: Use heredocs for multi-line text
-        if newline_count >= self.options.heredoc_line_threshold {
-            self.write_heredoc(s);
-            return;
-        }
-
-        // Rule 2: Use raw strings for complex escaping (> 3 escapes)
-        if escape_count > 3 && !s.contains("\"#") {
-            self.write_raw_string(s);
-            return;
-        }
-
-        // Default: quoted string
-        self.write_quoted_string(s);
-    }
-
-    /// Write a quoted string with proper escaping.
-    fn write_quoted_string(&mut self, s: &str) {
-        self.out.push(b'"');
-        let escaped = escape_quoted(s);
-        self.out.extend_from_slice(escaped.as_bytes());
-        self.out.push(b'"');
-    }
-
-    /// Write a raw string (r#"..."#).
-    fn write_raw_string(&mut self, s: &str) {
-        // Find the minimum number of # needed
-        let mut hashes = 0;
-        let mut check = String::from("\"");
-        while s.contains(&check) {
-            hashes += 1;
-            check = format!("\"{}#", "#".repeat(hashes - 1));
-        }
-
-        self.out.push(b'r');
-        for _ in 0..hashes {
-            self.out.push(b'#');
-        }
-        self.out.push(b'"');
-        self.out.extend_from_slice(s.as_bytes());
-        self.out.push(b'"');
-        for _ in 0..hashes {
-            self.out.push(b'#');
-        }
-    }
-
-    /// Write a heredoc string.
-    fn write_heredoc(&mut self, s: &str) {
-        // Find a delimiter that doesn't appear in the string
-        let delimiters = ["TEXT", "END", "HEREDOC", "DOC", "STR", "CONTENT"];
-        let delimiter = delimiters
-            .iter()
-            .find(|d| !s.contains(*d))
-            .unwrap_or(&"TEXT");
-
-        self.out.extend_from_slice(b"<<");
-        self.out.extend_from_slice(delimiter.as_bytes());
-        self.out.push(b'\n');
-        self.out.extend_from_slice(s.as_bytes());
-        if !s.ends_with('\n') {
-            self.out.push(b'\n');
-        }
-        self.out.extend_from_slice(delimiter.as_bytes());
+    fn write_scalar_string(&mut self, s: &str, always_quote: bool) {
+        // Bare scalars cannot be tagged, so a tag's payload must be quoted
+        let style = if std::mem::take(&mut self.force_quote_next_scalar) || always_quote {
+            ScalarStyle::Quoted
+        } else {
+            ScalarStyle::Auto
+        };
+        let rendered = render_scalar_with(s, style, self.options.heredoc_line_threshold);
+        self.out.extend_from_slice(rendered.as_bytes());
     }
 }
 
@@ -967,6 +1014,31 @@ mod tests {
         assert!(result.contains("value 42"));
     }
 
+    #[test]
+    fn test_always_quote_strings() {
+        let mut w = StyxWriter::with_options(FormatOptions::default().always_quote_strings());
+        w.begin_struct(true);
+        w.field_key("field").unwrap();
+        w.write_string("true");
+        w.field_key("count").unwrap();
+        w.write_string("123");
+        w.field_key("real").unwrap();
+        w.write_bool(true);
+        w.field_key("tagged").unwrap();
+        w.write_variant_tag("some");
+        w.write_string("x");
+        w.field_key("raw").unwrap();
+        w.write_scalar("bare");
+        w.end_struct().unwrap();
+
+        let result = w.finish_string();
+        assert!(result.contains(r#"field "true""#), "{result}");
+        assert!(result.contains(r#"count "123""#), "{result}");
+        assert!(result.contains("real true"), "{result}");
+        assert!(result.contains(r#"tagged @some"x""#), "{result}");
+        assert!(result.contains("raw bare"), "{result}");
+    }
+
     #[test]
     fn test_nested_inline() {
         let mut w = StyxWriter::with_options(FormatOptions::default());
@@ -1056,4 +1128,53 @@ mod tests {
             result
         );
     }
+
+    #[test]
+    fn test_flush_settled_keeps_open_structs() {
+        // Write `(1 {a 1, b 2, /// documented c 3})`, flushing part way
+        let write = |sink: Option<&mut Vec<u8>>| {
+            let mut w = StyxWriter::with_options(FormatOptions::default().inline());
+            w.begin_seq();
+            w.write_i64(1);
+            w.begin_struct(false);
+            w.field_key("a").unwrap();
+            w.write_i64(1);
+            w.field_key("b").unwrap();
+            w.write_i64(2);
+            let mut out = Vec::new();
+            if let Some(sink) = sink {
+                w.flush_settled(sink).unwrap();
+                assert_eq!(sink, b"(1 {");
+                out.append(sink);
+            }
+            // The struct's commas become newlines after the flush
+            w.write_doc_comment_and_key("documented", "c");
+            w.write_i64(3);
+            w.end_struct().unwrap();
+            w.end_seq().unwrap();
+            out.extend(w.finish_document());
+            String::from_utf8(out).unwrap()
+        };
+
+        let streamed = write(Some(&mut Vec::new()));
+        assert!(!streamed.contains(", "), "{streamed}");
+        assert_eq!(streamed, write(None));
+    }
+
+    #[test]
+    fn test_flush_settled_if_full_waits_for_threshold() {
+        let mut w = StyxWriter::new();
+        w.begin_seq();
+        let mut sink = Vec::new();
+        w.write_i64(1);
+        w.flush_settled_if_full(&mut sink).unwrap();
+        assert!(sink.is_empty());
+
+        while w.buffered_len() < StyxWriter::FLUSH_THRESHOLD {
+            w.write_i64(1);
+        }
+        w.flush_settled_if_full(&mut sink).unwrap();
+        assert!(!sink.is_empty());
+        assert!(w.buffered_len() < StyxWriter::FLUSH_THRESHOLD);
+    }
 }
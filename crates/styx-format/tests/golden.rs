@@ -0,0 +1,91 @@
+//! Golden-file tests for the formatter.
+//!
+//! Each `tests/golden/<name>.in.styx` is formatted and compared against
+//! `<name>.out.styx`. Set `STYX_UPDATE_GOLDEN=1` to rewrite the expected
+//! files from the current output, then review the diff.
+
+use std::path::Path;
+
+use styx_format::{FormatOptions, format_source};
+
+/// Environment variable that makes golden tests rewrite their expected files.
+const UPDATE_VAR: &str = "STYX_UPDATE_GOLDEN";
+
+/// Assert that `input` formats to exactly `expected`, and that formatting
+/// the result again leaves it unchanged.
+#[track_caller]
+fn assert_format_snapshot(input: &str, expected: &str) {
+    let formatted = format_source(input, FormatOptions::default());
+    assert_eq!(
+        formatted, expected,
+        "formatted output differs from expected"
+    );
+    assert_eq!(
+        format_source(&formatted, FormatOptions::default()),
+        formatted,
+        "formatting is not idempotent"
+    );
+}
+
+/// Check every `*.in.styx`/`*.out.styx` pair in `dir`, reporting all
+/// mismatches at once.
+fn check_golden_dir(dir: &Path) {
+    let update = std::env::var_os(UPDATE_VAR).is_some();
+
+    let mut inputs: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.to_str().is_some_and(|p| p.ends_with(".in.styx")))
+        .collect();
+    inputs.sort();
+    assert!(!inputs.is_empty(), "no golden inputs in {}", dir.display());
+
+    let mut failures = Vec::new();
+    for input_path in &inputs {
+        let input = std::fs::read_to_string(input_path).unwrap();
+        let output_path = input_path.with_extension("").with_extension("out.styx");
+        let formatted = format_source(&input, FormatOptions::default());
+
+        if update {
+            std::fs::write(&output_path, &formatted).unwrap();
+            continue;
+        }
+
+        let Ok(expected) = std::fs::read_to_string(&output_path) else {
+            failures.push(format!(
+                "{}: missing, run with {UPDATE_VAR}=1 to create it",
+                output_path.display()
+            ));
+            continue;
+        };
+        let result = std::panic::catch_unwind(|| assert_format_snapshot(&input, &expected));
+        if result.is_err() {
+            failures.push(format!(
+                "{}: expected\n{}\ngot\n{}",
+                output_path.display(),
+                expected,
+                formatted
+            ));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} golden file(s) differ (run with {UPDATE_VAR}=1 to update):\n\n{}",
+        failures.len(),
+        failures.join("\n\n")
+    );
+}
+
+#[test]
+fn golden_files() {
+    check_golden_dir(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden"));
+}
+
+#[test]
+fn snapshot_assertion_catches_differences() {
+    assert_format_snapshot("a   1\nb 2", "a 1\nb 2\n");
+
+    let result = std::panic::catch_unwind(|| assert_format_snapshot("a   1", "a   1\n"));
+    assert!(result.is_err());
+}
@@ -153,19 +153,20 @@ impl Position {
     /// the document content, allowing offset→position conversion without
     /// an RPC call back to the host.
     pub fn from_offset(content: &str, offset: u32) -> Self {
-        let offset = offset as usize;
-        if offset > content.len() {
-            // Past end of content - return last position
-            let line = content.chars().filter(|&c| c == '\n').count() as u32;
-            let last_newline = content.rfind('\n').map(|i| i + 1).unwrap_or(0);
-            let character = (content.len() - last_newline) as u32;
-            return Self { line, character };
-        }
+        Self::from_offset_with_tab_width(content, offset, 1)
+    }
+
+    /// Like [`Position::from_offset`], but counting each tab as `tab_width`
+    /// columns, to match editors that expand tabs.
+    pub fn from_offset_with_tab_width(content: &str, offset: u32, tab_width: u32) -> Self {
+        // Past end of content - return last position
+        let offset = (offset as usize).min(content.len());
 
         let before = &content[..offset];
         let line = before.chars().filter(|&c| c == '\n').count() as u32;
         let last_newline = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
-        let character = (offset - last_newline) as u32;
+        let tabs = before[last_newline..].matches('\t').count() as u32;
+        let character = (offset - last_newline) as u32 + tabs * tab_width.saturating_sub(1);
         Self { line, character }
     }
 }
@@ -189,9 +190,14 @@ impl Range {
     /// the document content, allowing span→range conversion without
     /// an RPC call back to the host.
     pub fn from_span(content: &str, span: &styx_tree::Span) -> Self {
+        Self::from_span_with_tab_width(content, span, 1)
+    }
+
+    /// Like [`Range::from_span`], but counting each tab as `tab_width` columns.
+    pub fn from_span_with_tab_width(content: &str, span: &styx_tree::Span, tab_width: u32) -> Self {
         Self {
-            start: Position::from_offset(content, span.start),
-            end: Position::from_offset(content, span.end),
+            start: Position::from_offset_with_tab_width(content, span.start, tab_width),
+            end: Position::from_offset_with_tab_width(content, span.end, tab_width),
         }
     }
 }
@@ -520,3 +526,29 @@ pub struct PositionToOffsetParams {
     /// Position (line/character).
     pub position: Position,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_tab_width() {
+        let content = "server {\n\tport 8080\n}";
+        let offset = content.find("8080").unwrap() as u32;
+
+        assert_eq!(Position::from_offset(content, offset), Position::new(1, 6));
+        assert_eq!(
+            Position::from_offset_with_tab_width(content, offset, 4),
+            Position::new(1, 9)
+        );
+        // Tabs on earlier lines don't count
+        assert_eq!(
+            Position::from_offset_with_tab_width(content, content.len() as u32, 4),
+            Position::new(2, 1)
+        );
+        assert_eq!(
+            Position::from_offset_with_tab_width(content, 999, 4),
+            Position::new(2, 1)
+        );
+    }
+}
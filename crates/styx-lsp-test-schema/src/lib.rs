@@ -40,7 +40,8 @@ pub struct TestCase {
     /// Name of the test (for reporting).
     pub name: String,
 
-    /// The input document. Use `|` to mark cursor position.
+    /// The input document. Use `|` to mark cursor position, and `{1|}`,
+    /// `{2|}`, ... for numbered markers checked through `cursors`.
     pub input: String,
 
     /// Optional schema to use (if not embedded in input).
@@ -75,6 +76,33 @@ pub struct TestCase {
     /// Expected code actions at cursor position.
     #[facet(default)]
     pub code_actions: Option<CodeActionExpectations>,
+
+    /// Expectations at numbered cursor markers.
+    #[facet(default)]
+    pub cursors: Vec<CursorExpectations>,
+}
+
+/// Expectations at one numbered cursor marker.
+#[derive(Debug, Clone, Facet)]
+pub struct CursorExpectations {
+    /// Which marker to check: `1` for `{1|}`.
+    pub at: String,
+
+    /// Expected completions at the marker.
+    #[facet(default)]
+    pub completions: Option<CompletionExpectations>,
+
+    /// Expected hover result at the marker.
+    #[facet(default)]
+    pub hover: Option<HoverExpectations>,
+
+    /// Expected definition locations at the marker.
+    #[facet(default)]
+    pub definition: Option<DefinitionExpectations>,
+
+    /// Expected code actions at the marker.
+    #[facet(default)]
+    pub code_actions: Option<CodeActionExpectations>,
 }
 
 /// Expectations for completion results.
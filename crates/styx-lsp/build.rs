@@ -14,6 +14,13 @@ mod config_types {
         /// Extensions can be added via the "Allow LSP extension" code action.
         #[facet(default)]
         pub allowed_extensions: Vec<String>,
+
+        /// LSP extensions the user declined to run.
+        ///
+        /// These are never spawned and no longer prompt for approval.
+        /// Extensions can be added via the "Deny LSP extension" code action.
+        #[facet(default)]
+        pub denied_extensions: Vec<String>,
     }
 }
 
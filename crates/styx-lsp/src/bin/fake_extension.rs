@@ -0,0 +1,89 @@
+//! A stand-in LSP extension for the test runner tests, so they can run
+//! without a real extension installed.
+//!
+//! It completes table names in `from` and column names in `select`, and
+//! provides nothing else.
+
+use roam_session::{Context, HandshakeConfig};
+use roam_stream::CobsFramed;
+use styx_lsp_ext::{
+    Capability, CodeAction, CodeActionParams, CompletionItem, CompletionKind, CompletionParams,
+    DefinitionParams, Diagnostic, DiagnosticParams, HoverParams, HoverResult, InitializeParams,
+    InitializeResult, InlayHint, InlayHintParams, Location, StyxLspExtension,
+    StyxLspExtensionDispatcher,
+};
+
+const TABLES: &[&str] = &["posts", "users"];
+const COLUMNS: &[&str] = &["id", "title", "body"];
+
+struct FakeExtension;
+
+impl StyxLspExtension for FakeExtension {
+    async fn initialize(&self, _cx: &Context, _params: InitializeParams) -> InitializeResult {
+        InitializeResult {
+            name: "fake".into(),
+            version: env!("CARGO_PKG_VERSION").into(),
+            capabilities: vec![Capability::Completions],
+        }
+    }
+
+    async fn completions(&self, _cx: &Context, params: CompletionParams) -> Vec<CompletionItem> {
+        let labels = params
+            .path
+            .iter()
+            .rev()
+            .find_map(|key| match key.as_str() {
+                "from" => Some(TABLES),
+                "select" => Some(COLUMNS),
+                _ => None,
+            })
+            .unwrap_or_default();
+        labels
+            .iter()
+            .map(|label| CompletionItem {
+                label: label.to_string(),
+                detail: None,
+                documentation: None,
+                kind: Some(CompletionKind::Value),
+                sort_text: None,
+                insert_text: None,
+            })
+            .collect()
+    }
+
+    async fn hover(&self, _cx: &Context, _params: HoverParams) -> Option<HoverResult> {
+        None
+    }
+
+    async fn inlay_hints(&self, _cx: &Context, _params: InlayHintParams) -> Vec<InlayHint> {
+        Vec::new()
+    }
+
+    async fn diagnostics(&self, _cx: &Context, _params: DiagnosticParams) -> Vec<Diagnostic> {
+        Vec::new()
+    }
+
+    async fn code_actions(&self, _cx: &Context, _params: CodeActionParams) -> Vec<CodeAction> {
+        Vec::new()
+    }
+
+    async fn definition(&self, _cx: &Context, _params: DefinitionParams) -> Vec<Location> {
+        Vec::new()
+    }
+
+    async fn shutdown(&self, _cx: &Context) {}
+}
+
+#[tokio::main]
+async fn main() {
+    let stdio = tokio::io::join(tokio::io::stdin(), tokio::io::stdout());
+    let framed = CobsFramed::new(stdio);
+    let dispatcher = StyxLspExtensionDispatcher::new(FakeExtension);
+
+    // The LSP initiates the handshake, so the extension accepts it
+    let (_handle, _incoming, driver) =
+        roam_session::accept_framed(framed, HandshakeConfig::default(), dispatcher)
+            .await
+            .expect("roam handshake failed");
+    let _ = driver.run().await;
+}
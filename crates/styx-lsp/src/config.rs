@@ -3,7 +3,7 @@
 //! Configuration is stored at `~/.config/styx/config.styx` and includes
 //! user preferences like allowed LSP extensions.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use facet::Facet;
 use tracing::{debug, info, warn};
@@ -25,6 +25,13 @@ pub struct StyxUserConfig {
     /// Extensions can be added via the "Allow LSP extension" code action.
     #[facet(default)]
     pub allowed_extensions: Vec<String>,
+
+    /// LSP extensions the user declined to run.
+    ///
+    /// These are never spawned and no longer prompt for approval.
+    /// Extensions can be added via the "Deny LSP extension" code action.
+    #[facet(default)]
+    pub denied_extensions: Vec<String>,
 }
 
 /// Get the path to the user config file.
@@ -44,7 +51,14 @@ pub fn load_config() -> Result<Option<StyxUserConfig>, LoadConfigError> {
         debug!("No config directory available");
         return Ok(None);
     };
+    load_config_from(&path)
+}
 
+/// Load the user configuration from `path`.
+///
+/// Returns `Ok(None)` if the file doesn't exist yet.
+pub fn load_config_from(path: &Path) -> Result<Option<StyxUserConfig>, LoadConfigError> {
+    let path = path.to_path_buf();
     if !path.exists() {
         debug!(?path, "Config file does not exist yet");
         return Ok(None);
@@ -64,6 +78,7 @@ pub fn load_config() -> Result<Option<StyxUserConfig>, LoadConfigError> {
     info!(
         ?path,
         extensions = config.allowed_extensions.len(),
+        denied = config.denied_extensions.len(),
         "Loaded user config"
     );
     Ok(Some(config))
@@ -77,6 +92,14 @@ pub fn save_config(config: &StyxUserConfig) -> Result<(), SaveConfigError> {
         warn!("No config directory available, cannot save config");
         return Err(SaveConfigError::NoConfigDir);
     };
+    save_config_to(&path, config)
+}
+
+/// Save the user configuration to `path`.
+///
+/// Creates the parent directory if it doesn't exist.
+pub fn save_config_to(path: &Path, config: &StyxUserConfig) -> Result<(), SaveConfigError> {
+    let path = path.to_path_buf();
 
     // Create the directory if it doesn't exist
     if let Some(parent) = path.parent() {
@@ -13,15 +13,52 @@ pub enum ExtensionResult {
         /// The command that needs to be allowed.
         command: String,
     },
+    /// Extension was denied by the user and won't be spawned.
+    Denied,
     /// Extension failed to spawn for another reason.
     Failed,
 }
 
+/// Why a call to an extension produced no result.
+#[derive(Debug)]
+pub enum ExtensionCallError {
+    /// The extension returned an error or the connection failed.
+    Failed(String),
+    /// The extension didn't answer within the call timeout.
+    TimedOut(Duration),
+}
+
+impl std::fmt::Display for ExtensionCallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtensionCallError::Failed(error) => f.write_str(error),
+            ExtensionCallError::TimedOut(timeout) => write!(f, "timed out after {timeout:?}"),
+        }
+    }
+}
+
+/// How long a single call to an extension may take before it's abandoned.
+const CALL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long an extension may take to connect and initialize.
+const SPAWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delay before the first restart of a crashed extension. Each further
+/// crash doubles it, up to [`MAX_RESTART_DELAY`].
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest delay between restarts of an extension that keeps crashing.
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+
 use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
 use std::io;
+use std::path::PathBuf;
 use std::pin::Pin;
 use std::process::Stdio;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use facet_styx::LspExtensionConfig;
 use roam_session::{ConnectionHandle, HandshakeConfig};
@@ -36,6 +73,7 @@ use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use tokio::process::{Child, ChildStdin, ChildStdout, Command};
 use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
+use tokio::time::Instant;
 use tower_lsp::lsp_types::Url;
 use tracing::{debug, info, warn};
 
@@ -89,10 +127,29 @@ impl AsyncWrite for ChildStdio {
 pub struct ExtensionManager {
     /// Spawned extensions, keyed by schema ID.
     extensions: RwLock<HashMap<String, Extension>>,
-    /// Allowed extensions (from user config).
-    allowlist: RwLock<Vec<String>>,
+    /// Allowed and denied extensions (from user config).
+    decisions: RwLock<StyxUserConfig>,
+    /// Where allow/deny decisions are persisted, if anywhere.
+    config_path: Option<PathBuf>,
+    /// Extensions that exited or failed to start, keyed by schema ID.
+    crashes: RwLock<HashMap<String, Crash>>,
     /// Shared document state for host callbacks.
     documents: DocumentMap,
+    call_timeout: Duration,
+    spawn_timeout: Duration,
+    restart_delay: Duration,
+}
+
+/// An extension that exited or failed to start, waiting to be restarted.
+struct Crash {
+    /// Extension config from the schema.
+    config: LspExtensionConfig,
+    /// The document the extension was spawned for.
+    document_uri: String,
+    /// Crashes since the extension last answered a call.
+    count: u32,
+    /// When the extension may be spawned again.
+    retry_at: Instant,
 }
 
 /// A spawned extension process with roam connection.
@@ -101,59 +158,128 @@ struct Extension {
     #[allow(dead_code)]
     process: Child,
     /// Extension config from the schema.
-    #[allow(dead_code)]
     config: LspExtensionConfig,
+    /// The document the extension was spawned for.
+    document_uri: String,
     /// Roam connection handle for making calls.
-    #[allow(dead_code)]
     handle: ConnectionHandle,
     /// Driver task handle.
-    #[allow(dead_code)]
     driver_handle: JoinHandle<()>,
 }
 
 impl ExtensionManager {
     /// Create a new extension manager, loading the allowlist from the user config.
     pub(crate) fn new(documents: DocumentMap) -> Self {
-        // Load allowed extensions from user config
-        let allowlist = match config::load_config() {
-            Ok(Some(config)) => config.allowed_extensions,
-            Ok(None) => Vec::new(),
+        Self::with_config_path(documents, config::config_path())
+    }
+
+    /// Create a new extension manager whose allowlist is loaded from and
+    /// saved to `config_path`.
+    pub(crate) fn with_config_path(documents: DocumentMap, config_path: Option<PathBuf>) -> Self {
+        let loaded = match &config_path {
+            Some(path) => config::load_config_from(path),
+            None => Ok(None),
+        };
+        let decisions = match loaded {
+            Ok(config) => config.unwrap_or_default(),
             Err(e) => {
                 warn!(error = %e, "Failed to load user config, starting with empty allowlist");
-                Vec::new()
+                StyxUserConfig::default()
             }
         };
 
         Self {
             extensions: RwLock::new(HashMap::new()),
-            allowlist: RwLock::new(allowlist),
+            decisions: RwLock::new(decisions),
+            config_path,
+            crashes: RwLock::new(HashMap::new()),
             documents,
+            call_timeout: CALL_TIMEOUT,
+            spawn_timeout: SPAWN_TIMEOUT,
+            restart_delay: RESTART_DELAY,
         }
     }
 
     /// Check if an extension is allowed.
     pub async fn is_allowed(&self, command: &str) -> bool {
-        let allowlist = self.allowlist.read().await;
+        let decisions = self.decisions.read().await;
         // For now, allow if the first component of the command is in the allowlist
-        allowlist.iter().any(|allowed| command.starts_with(allowed))
+        decisions
+            .allowed_extensions
+            .iter()
+            .any(|allowed| command.starts_with(allowed))
+    }
+
+    /// Check if an extension was denied.
+    pub async fn is_denied(&self, command: &str) -> bool {
+        let decisions = self.decisions.read().await;
+        decisions
+            .denied_extensions
+            .iter()
+            .any(|denied| command.starts_with(denied))
     }
 
     /// Add a command to the allowlist and persist to user config.
+    ///
+    /// This also lifts an earlier denial of the command.
     pub async fn allow(&self, command: String) {
-        let mut allowlist = self.allowlist.write().await;
-        if !allowlist.contains(&command) {
-            allowlist.push(command.clone());
+        let mut decisions = self.decisions.write().await;
+        decisions
+            .denied_extensions
+            .retain(|denied| *denied != command);
+        if !decisions.allowed_extensions.contains(&command) {
+            decisions.allowed_extensions.push(command);
+        }
+        self.save_decisions(&decisions);
+    }
 
-            // Persist to user config
-            let config = StyxUserConfig {
-                allowed_extensions: allowlist.clone(),
-            };
-            if let Err(e) = config::save_config(&config) {
-                warn!(error = %e, "Failed to save user config");
+    /// Deny a command and persist to user config.
+    ///
+    /// The command is removed from the allowlist and any extensions
+    /// launched with it are shut down.
+    pub async fn deny(&self, command: String) {
+        {
+            let mut decisions = self.decisions.write().await;
+            decisions
+                .allowed_extensions
+                .retain(|allowed| *allowed != command);
+            if !decisions.denied_extensions.contains(&command) {
+                decisions.denied_extensions.push(command.clone());
+            }
+            self.save_decisions(&decisions);
+        }
+
+        self.crashes
+            .write()
+            .await
+            .retain(|_, crash| crash.config.launch.first() != Some(&command));
+
+        let mut extensions = self.extensions.write().await;
+        let denied: Vec<String> = extensions
+            .iter()
+            .filter(|(_, ext)| ext.config.launch.first() == Some(&command))
+            .map(|(schema_id, _)| schema_id.clone())
+            .collect();
+        for schema_id in denied {
+            if let Some(mut ext) = extensions.remove(&schema_id) {
+                debug!(schema_id, "Shutting down denied extension");
+                ext.driver_handle.abort();
+                let _ = ext.process.kill().await;
             }
         }
     }
 
+    /// Persist allow/deny decisions to the user config.
+    fn save_decisions(&self, decisions: &StyxUserConfig) {
+        let Some(path) = &self.config_path else {
+            warn!("No config directory available, cannot save config");
+            return;
+        };
+        if let Err(e) = config::save_config_to(path, decisions) {
+            warn!(error = %e, "Failed to save user config");
+        }
+    }
+
     /// Get or spawn an extension for a schema.
     ///
     /// Returns the result of the operation, indicating whether the extension
@@ -164,18 +290,18 @@ impl ExtensionManager {
         config: &LspExtensionConfig,
         document_uri: &str,
     ) -> ExtensionResult {
-        // Check if already spawned
-        {
-            let extensions = self.extensions.read().await;
-            if extensions.contains_key(schema_id) {
-                return ExtensionResult::Running;
-            }
+        if self.is_running(schema_id).await {
+            return ExtensionResult::Running;
         }
 
         // Check if allowed
         let Some(command) = config.launch.first() else {
             return ExtensionResult::Failed;
         };
+        if self.is_denied(command).await {
+            debug!(schema_id, command, "Extension denied by user, skipping");
+            return ExtensionResult::Denied;
+        }
         if !self.is_allowed(command).await {
             info!(schema_id, command, "Extension not in allowlist, skipping");
             return ExtensionResult::NotAllowed {
@@ -183,8 +309,30 @@ impl ExtensionManager {
             };
         }
 
+        // Don't respawn a crashed extension until its backoff has elapsed
+        if let Some(crash) = self.crashes.read().await.get(schema_id)
+            && Instant::now() < crash.retry_at
+        {
+            debug!(
+                schema_id,
+                crashes = crash.count,
+                "Extension restart delayed"
+            );
+            return ExtensionResult::Failed;
+        }
+
         // Spawn the extension
-        let Some(extension) = self.spawn_extension(schema_id, config, document_uri).await else {
+        let spawned = tokio::time::timeout(
+            self.spawn_timeout,
+            self.spawn_extension(schema_id, config, document_uri),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            warn!(schema_id, command, "Extension did not initialize in time");
+            None
+        });
+        let Some(extension) = spawned else {
+            self.record_crash(schema_id, config, document_uri).await;
             return ExtensionResult::Failed;
         };
 
@@ -203,13 +351,89 @@ impl ExtensionManager {
 
     /// Get an extension client for a schema.
     ///
-    /// Returns `None` if no extension is spawned for this schema.
+    /// An extension that crashed is respawned here once its restart delay
+    /// has elapsed. Returns `None` if no extension is running for this schema.
     pub async fn get_client(&self, schema_id: &str) -> Option<StyxLspExtensionClient> {
+        if !self.is_running(schema_id).await {
+            let (config, document_uri) = {
+                let crashes = self.crashes.read().await;
+                let crash = crashes.get(schema_id)?;
+                (crash.config.clone(), crash.document_uri.clone())
+            };
+            self.get_or_spawn(schema_id, &config, &document_uri).await;
+        }
         self.get_handle(schema_id)
             .await
             .map(StyxLspExtensionClient::new)
     }
 
+    /// Run a call to a schema's extension, giving up after the call timeout.
+    ///
+    /// A successful call resets the extension's restart backoff.
+    pub async fn call<T, E: Display>(
+        &self,
+        schema_id: &str,
+        call: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, ExtensionCallError> {
+        match tokio::time::timeout(self.call_timeout, call).await {
+            Ok(Ok(value)) => {
+                self.crashes.write().await.remove(schema_id);
+                Ok(value)
+            }
+            Ok(Err(e)) => Err(ExtensionCallError::Failed(e.to_string())),
+            Err(_) => Err(ExtensionCallError::TimedOut(self.call_timeout)),
+        }
+    }
+
+    /// Check whether a schema's extension is running, reaping it if its
+    /// process or connection has gone away.
+    async fn is_running(&self, schema_id: &str) -> bool {
+        let mut extensions = self.extensions.write().await;
+        let Some(ext) = extensions.get_mut(schema_id) else {
+            return false;
+        };
+        let exited = !matches!(ext.process.try_wait(), Ok(None));
+        if !exited && !ext.driver_handle.is_finished() {
+            return true;
+        }
+
+        let Some(mut ext) = extensions.remove(schema_id) else {
+            return false;
+        };
+        drop(extensions);
+        warn!(schema_id, "Extension exited");
+        ext.driver_handle.abort();
+        let _ = ext.process.kill().await;
+        self.record_crash(schema_id, &ext.config, &ext.document_uri)
+            .await;
+        false
+    }
+
+    /// Remember that a schema's extension crashed, and when it may be
+    /// restarted.
+    async fn record_crash(&self, schema_id: &str, config: &LspExtensionConfig, document_uri: &str) {
+        let mut crashes = self.crashes.write().await;
+        let count = crashes.get(schema_id).map_or(0, |crash| crash.count) + 1;
+        let delay = self.restart_delay(count);
+        debug!(schema_id, count, ?delay, "Recording extension crash");
+        crashes.insert(
+            schema_id.to_string(),
+            Crash {
+                config: config.clone(),
+                document_uri: document_uri.to_string(),
+                count,
+                retry_at: Instant::now() + delay,
+            },
+        );
+    }
+
+    /// The delay before restarting an extension after its `count`th crash.
+    fn restart_delay(&self, count: u32) -> Duration {
+        self.restart_delay
+            .saturating_mul(1 << count.saturating_sub(1).min(16))
+            .min(MAX_RESTART_DELAY)
+    }
+
     /// Spawn an extension process, establish roam connection, and initialize it.
     async fn spawn_extension(
         &self,
@@ -233,6 +457,7 @@ impl ExtensionManager {
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::inherit())
+            .kill_on_drop(true)
             .spawn()
             .map_err(|e| {
                 warn!(command, error = %e, "Failed to spawn extension");
@@ -304,6 +529,7 @@ impl ExtensionManager {
         Some(Extension {
             process,
             config: config.clone(),
+            document_uri: document_uri.to_string(),
             handle,
             driver_handle,
         })
@@ -414,7 +640,7 @@ impl StyxLspHost for StyxLspHostImpl {
         let doc = docs.get(&uri)?;
         let tree = doc.tree.as_ref()?;
 
-        let resolved = resolve_schema(tree, &uri).ok()?;
+        let resolved = resolve_schema(tree, &uri).await.ok()?;
         Some(SchemaInfo {
             source: resolved.source,
             uri: resolved.uri.to_string(),
@@ -477,3 +703,85 @@ impl StyxLspHost for StyxLspHostImpl {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> ExtensionManager {
+        let documents = DocumentMap::default();
+        ExtensionManager::with_config_path(documents, None)
+    }
+
+    #[tokio::test]
+    async fn test_call_times_out() {
+        let mut manager = manager();
+        manager.call_timeout = Duration::from_millis(50);
+
+        let hung = std::future::pending::<Result<(), String>>();
+        let result = manager.call("test", hung).await;
+        assert!(matches!(result, Err(ExtensionCallError::TimedOut(_))));
+
+        let answered = async { Ok::<_, String>(vec!["completion"]) };
+        assert_eq!(
+            manager.call("test", answered).await.unwrap(),
+            ["completion"]
+        );
+
+        let failed = async { Err::<(), _>("connection closed") };
+        let result = manager.call("test", failed).await;
+        assert_eq!(result.unwrap_err().to_string(), "connection closed");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_crashed_extension_restarts_with_backoff() {
+        let mut manager = manager();
+        manager.restart_delay = Duration::from_millis(200);
+        manager.spawn_timeout = Duration::from_secs(5);
+        manager
+            .decisions
+            .write()
+            .await
+            .allowed_extensions
+            .push("sh".into());
+        let config = LspExtensionConfig {
+            launch: vec!["sh".into(), "-c".into(), "exit 1".into()],
+            capabilities: None,
+        };
+        let crash_count = async |manager: &ExtensionManager| {
+            manager.crashes.read().await.get("crashy").map(|c| c.count)
+        };
+
+        let result = manager
+            .get_or_spawn("crashy", &config, "file:///a.styx")
+            .await;
+        assert!(matches!(result, ExtensionResult::Failed));
+        assert_eq!(crash_count(&manager).await, Some(1));
+
+        // Still backing off, so no new attempt is made
+        assert!(manager.get_client("crashy").await.is_none());
+        assert_eq!(crash_count(&manager).await, Some(1));
+
+        // Once the delay has passed the next request respawns it
+        tokio::time::sleep(manager.restart_delay(1)).await;
+        assert!(manager.get_client("crashy").await.is_none());
+        assert_eq!(crash_count(&manager).await, Some(2));
+        assert_eq!(manager.restart_delay(2), Duration::from_millis(400));
+
+        // Answered calls reset the backoff
+        manager
+            .call("crashy", async { Ok::<_, String>(()) })
+            .await
+            .unwrap();
+        assert_eq!(crash_count(&manager).await, None);
+    }
+
+    #[test]
+    fn test_restart_delay_is_capped() {
+        let manager = manager();
+        assert_eq!(manager.restart_delay(1), RESTART_DELAY);
+        assert_eq!(manager.restart_delay(3), RESTART_DELAY * 4);
+        assert_eq!(manager.restart_delay(100), MAX_RESTART_DELAY);
+    }
+}
@@ -16,6 +16,7 @@ mod schema_validation;
 pub mod semantic_tokens;
 mod server;
 pub mod testing;
+mod validation_cache;
 
 pub use semantic_tokens::{HighlightSpan, TokenType, compute_highlight_spans};
 pub use server::{DocumentMap, DocumentState, StyxLanguageServer, run};
@@ -1,14 +1,18 @@
 //! Schema validation for the LSP.
 //!
 //! This module handles loading schema files and validating documents against them.
+//! Schemas are loaded asynchronously so a slow disk or a large CLI binary doesn't
+//! stall other requests on the server's runtime.
 
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::path::{Path, PathBuf};
 
-use facet_styx::{Documented, ObjectKey, Schema, SchemaFile, ValidationResult, validate};
-use styx_tree::Value;
+use facet_styx::{Documented, ObjectKey, Schema, SchemaFile, ValidationResult, merge_schemas};
+use styx_tree::{SchemaDirective, Value};
 use tower_lsp::lsp_types::Url;
 
 use crate::cache;
+use crate::validation_cache::ValidationCache;
 
 /// A field from a schema with its name and type info.
 #[derive(Debug, Clone)]
@@ -43,16 +47,16 @@ impl SchemaRef {
     /// Load the schema source text.
     ///
     /// Returns `Err` for `SchemaRef::None` since there's no source to load.
-    pub fn load_source(&self, document_uri: &Url) -> Result<String, String> {
+    pub async fn load_source(&self, document_uri: &Url) -> Result<String, String> {
         match self {
             SchemaRef::External(path) => {
                 let resolved = resolve_schema_path(path, document_uri)
                     .ok_or_else(|| format!("could not resolve schema path '{}'", path))?;
-                std::fs::read_to_string(&resolved).map_err(|e| {
+                tokio::fs::read_to_string(&resolved).await.map_err(|e| {
                     format!("failed to read schema file '{}': {}", resolved.display(), e)
                 })
             }
-            SchemaRef::Embedded { id, cli } => extract_embedded_schema_source(cli, id),
+            SchemaRef::Embedded { id, cli } => extract_embedded_schema_source(cli, id).await,
             SchemaRef::None => Err("schema validation explicitly disabled".to_string()),
         }
     }
@@ -60,14 +64,14 @@ impl SchemaRef {
     /// Load and parse the schema file.
     ///
     /// Returns `Err` for `SchemaRef::None` since there's no schema to load.
-    pub fn load_schema(&self, document_uri: &Url) -> Result<SchemaFile, String> {
+    pub async fn load_schema(&self, document_uri: &Url) -> Result<SchemaFile, String> {
         match self {
             SchemaRef::External(path) => {
                 let resolved = resolve_schema_path(path, document_uri)
                     .ok_or_else(|| format!("could not resolve schema path '{}'", path))?;
-                load_schema_file(&resolved)
+                load_schema_file_async(&resolved).await
             }
-            SchemaRef::Embedded { id, cli } => extract_embedded_schema(cli, id),
+            SchemaRef::Embedded { id, cli } => extract_embedded_schema(cli, id).await,
             SchemaRef::None => Err("schema validation explicitly disabled".to_string()),
         }
     }
@@ -131,37 +135,38 @@ pub struct ResolvedSchema {
 /// - `@schema @` - explicit opt-out (no schema)
 /// - `@schema "path/to/schema.styx"` - external schema file
 /// - `@schema {id ..., cli <binary>}` - embedded schema from binary
+///
+/// When several schemas are declared, returns the last one: it takes
+/// precedence, so navigation and hover point at it.
 pub fn find_schema_declaration(value: &Value) -> Option<SchemaRef> {
+    find_schema_declarations(value)?.pop()
+}
+
+/// Find all schemas declared by a document, in order of increasing precedence.
+///
+/// `@schema (base.styx extension.styx)` declares several; any other form
+/// declares one.
+pub fn find_schema_declarations(value: &Value) -> Option<Vec<SchemaRef>> {
     let obj = value.as_object()?;
+    let entry = obj.entries.iter().find(|e| e.key.is_schema_tag())?;
 
-    for entry in &obj.entries {
-        if entry.key.is_schema_tag() {
-            // @schema @ (explicit opt-out)
-            if entry.value.is_unit() {
-                return Some(SchemaRef::None);
-            }
+    // @schema @ (explicit opt-out)
+    if entry.value.is_unit() {
+        return Some(vec![SchemaRef::None]);
+    }
 
+    SchemaDirective::list_from_value(&entry.value)?
+        .into_iter()
+        .map(|directive| match directive {
             // @schema path/to/schema.styx
-            if let Some(path) = entry.value.as_str() {
-                return Some(SchemaRef::External(path.to_string()));
-            }
-
+            SchemaDirective::External(path) => Some(SchemaRef::External(path)),
             // @schema {id ..., cli ...}
-            if let Some(schema_obj) = entry.value.as_object()
-                && let Some(id_value) = schema_obj.get("id")
-                && let Some(id) = id_value.as_str()
-                && let Some(cli_value) = schema_obj.get("cli")
-                && let Some(cli_name) = cli_value.as_str()
-            {
-                return Some(SchemaRef::Embedded {
-                    id: id.to_string(),
-                    cli: cli_name.to_string(),
-                });
+            SchemaDirective::Embedded { id: Some(id), cli } => {
+                Some(SchemaRef::Embedded { id, cli })
             }
-        }
-    }
-
-    None
+            SchemaDirective::Embedded { id: None, .. } => None,
+        })
+        .collect()
 }
 
 /// Resolve a schema path relative to the document URI.
@@ -184,9 +189,10 @@ fn resolve_schema_path(schema_path: &str, document_uri: &Url) -> Option<PathBuf>
     Some(parent.join(schema_path))
 }
 
-/// Load a schema file from disk.
-fn load_schema_file(path: &Path) -> Result<SchemaFile, String> {
-    let content = std::fs::read_to_string(path)
+/// Load a schema file from disk without blocking the runtime.
+async fn load_schema_file_async(path: &Path) -> Result<SchemaFile, String> {
+    let content = tokio::fs::read_to_string(path)
+        .await
         .map_err(|e| format!("failed to read schema file '{}': {}", path.display(), e))?;
 
     facet_styx::from_str(&content)
@@ -207,6 +213,7 @@ pub fn strip_schema_declaration(value: &Value) -> Value {
             payload: Some(styx_tree::Payload::Object(styx_tree::Object {
                 entries: filtered_entries,
                 span: obj.span,
+                origin: obj.origin,
             })),
             span: value.span,
         }
@@ -216,13 +223,24 @@ pub fn strip_schema_declaration(value: &Value) -> Value {
 }
 
 /// Extract schema from a binary with embedded styx schemas.
-fn extract_embedded_schema(cli_name: &str, schema_id: &str) -> Result<SchemaFile, String> {
-    let source = extract_embedded_schema_source(cli_name, schema_id)?;
+async fn extract_embedded_schema(cli_name: &str, schema_id: &str) -> Result<SchemaFile, String> {
+    let source = extract_embedded_schema_source(cli_name, schema_id).await?;
     facet_styx::from_str(&source).map_err(|e| format!("failed to parse embedded schema: {}", e))
 }
 
 /// Extract schema source text from a binary with embedded styx schemas.
-fn extract_embedded_schema_source(cli_name: &str, schema_id: &str) -> Result<String, String> {
+///
+/// Looking up and scanning the binary is blocking work, so it runs on the
+/// blocking thread pool.
+async fn extract_embedded_schema_source(cli_name: &str, schema_id: &str) -> Result<String, String> {
+    let cli_name = cli_name.to_string();
+    let schema_id = schema_id.to_string();
+    tokio::task::spawn_blocking(move || read_embedded_schema_source(&cli_name, &schema_id))
+        .await
+        .map_err(|e| format!("embedded schema extraction failed: {}", e))?
+}
+
+fn read_embedded_schema_source(cli_name: &str, schema_id: &str) -> Result<String, String> {
     let binary_path =
         which::which(cli_name).map_err(|_| format!("binary '{}' not found in PATH", cli_name))?;
 
@@ -243,7 +261,7 @@ fn extract_embedded_schema_source(cli_name: &str, schema_id: &str) -> Result<Str
 
     // Find the schema with the matching ID
     for schema_source in schemas {
-        if extract_schema_id(&schema_source).is_some_and(|id| id == schema_id) {
+        if facet_styx::parse_meta(&schema_source).is_ok_and(|meta| meta.id == schema_id) {
             return Ok(schema_source);
         }
     }
@@ -255,16 +273,6 @@ fn extract_embedded_schema_source(cli_name: &str, schema_id: &str) -> Result<Str
     ))
 }
 
-/// Extract the schema ID from a schema source string.
-fn extract_schema_id(schema_source: &str) -> Option<String> {
-    let value = styx_tree::parse(schema_source).ok()?;
-    let obj = value.as_object()?;
-    let meta = obj.get("meta")?;
-    let meta_obj = meta.as_object()?;
-    let id_value = meta_obj.get("id")?;
-    id_value.as_str().map(|s| s.to_string())
-}
-
 /// The URI scheme for embedded schemas (fallback if caching fails).
 pub const EMBEDDED_SCHEMA_SCHEME: &str = "styx-embedded";
 
@@ -272,11 +280,11 @@ pub const EMBEDDED_SCHEMA_SCHEME: &str = "styx-embedded";
 ///
 /// This is the main entry point for getting schema information.
 /// Returns `Err` if no schema declaration, if schema is `@schema @`, or if loading fails.
-pub fn resolve_schema(value: &Value, document_uri: &Url) -> Result<ResolvedSchema, String> {
+pub async fn resolve_schema(value: &Value, document_uri: &Url) -> Result<ResolvedSchema, String> {
     let schema_ref =
         find_schema_declaration(value).ok_or_else(|| "no schema declaration found".to_string())?;
 
-    let source = schema_ref.load_source(document_uri)?;
+    let source = schema_ref.load_source(document_uri).await?;
 
     // Validate that the source is a valid schema
     let _: SchemaFile =
@@ -289,21 +297,44 @@ pub fn resolve_schema(value: &Value, document_uri: &Url) -> Result<ResolvedSchem
 
 /// Load and validate a document against its declared schema.
 ///
-/// Returns validation errors, or an error message if schema can't be loaded.
-/// Returns `Err` for `@schema @` (explicit opt-out).
-pub fn validate_against_schema(
+/// Root entries unchanged since the last validation with `cache` are not
+/// validated again. Returns validation errors, or an error message if schema
+/// can't be loaded. Returns `Err` for `@schema @` (explicit opt-out).
+pub async fn validate_against_schema(
     value: &Value,
+    source: &str,
     document_uri: &Url,
+    cache: &mut ValidationCache,
 ) -> Result<ValidationResult, String> {
-    let schema_ref =
-        find_schema_declaration(value).ok_or_else(|| "no schema declaration found".to_string())?;
-
-    let schema_file = schema_ref.load_schema(document_uri)?;
-
-    // Strip schema declaration before validation
-    let value_for_validation = strip_schema_declaration(value);
+    let (schema_file, schema_key) = load_document_schema_keyed(value, document_uri).await?;
+    Ok(cache.validate(&schema_key, &schema_file, value, source))
+}
 
-    Ok(validate(&value_for_validation, &schema_file))
+/// Load and merge every schema a document declares, like
+/// [`load_document_schema`], along with a key that changes whenever the
+/// source of any of them does.
+async fn load_document_schema_keyed(
+    value: &Value,
+    document_uri: &Url,
+) -> Result<(SchemaFile, String), String> {
+    let schema_refs =
+        find_schema_declarations(value).ok_or_else(|| "no schema declaration found".to_string())?;
+
+    let mut hasher = DefaultHasher::new();
+    let mut merged: Option<SchemaFile> = None;
+    for schema_ref in &schema_refs {
+        let source = schema_ref.load_source(document_uri).await?;
+        source.hash(&mut hasher);
+        let schema_file: SchemaFile =
+            facet_styx::from_str(&source).map_err(|e| format!("failed to parse schema: {}", e))?;
+        merged = Some(match merged {
+            Some(base) => merge_schemas(base, schema_file).map_err(|e| e.to_string())?,
+            None => schema_file,
+        });
+    }
+    let schema_file = merged.ok_or_else(|| "no schema declaration found".to_string())?;
+    let key = format!("{}:{:016x}", schema_file.meta.id, hasher.finish());
+    Ok((schema_file, key))
 }
 
 /// Find a value in the tree by path (e.g., "server.tls.cert").
@@ -459,11 +490,21 @@ pub fn generate_placeholder(schema: &Schema) -> String {
 }
 
 /// Load schema for a document and return the SchemaFile.
-pub fn load_document_schema(value: &Value, document_uri: &Url) -> Result<SchemaFile, String> {
-    let schema_ref =
-        find_schema_declaration(value).ok_or_else(|| "no schema declaration found".to_string())?;
-
-    schema_ref.load_schema(document_uri)
+///
+/// Multiple declared schemas are merged, later ones taking precedence.
+pub async fn load_document_schema(value: &Value, document_uri: &Url) -> Result<SchemaFile, String> {
+    let schema_refs =
+        find_schema_declarations(value).ok_or_else(|| "no schema declaration found".to_string())?;
+
+    let mut merged: Option<SchemaFile> = None;
+    for schema_ref in &schema_refs {
+        let schema_file = schema_ref.load_schema(document_uri).await?;
+        merged = Some(match merged {
+            Some(base) => merge_schemas(base, schema_file).map_err(|e| e.to_string())?,
+            None => schema_file,
+        });
+    }
+    merged.ok_or_else(|| "no schema declaration found".to_string())
 }
 
 /// Get the existing field names from a document.
@@ -714,6 +755,7 @@ fn resolve_type_reference(schema: &Schema, schema_file: &SchemaFile) -> Schema {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use styx_testhelpers::TempDir;
 
     #[test]
     fn test_find_schema_declaration_none() {
@@ -738,6 +780,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_schema_declarations_multiple() {
+        let value =
+            styx_tree::parse("@schema (base.styx {id crate:foo@1, cli foo})\nfoo bar").unwrap();
+        let decls = find_schema_declarations(&value).expect("should find declarations");
+        assert_eq!(decls.len(), 2);
+        assert!(matches!(&decls[0], SchemaRef::External(path) if path == "base.styx"));
+        assert!(matches!(&decls[1], SchemaRef::Embedded { id, .. } if id == "crate:foo@1"));
+
+        // The last declared schema takes precedence
+        let decl = find_schema_declaration(&value).expect("should find declaration");
+        assert!(matches!(decl, SchemaRef::Embedded { cli, .. } if cli == "foo"));
+    }
+
+    #[tokio::test]
+    async fn test_load_document_schema_merges_declarations() {
+        let dir = TempDir::new("lsp-schema-merge");
+        dir.write(
+            "base.styx",
+            "meta {id base}\nschema {@ @object{host @string}}",
+        );
+        dir.write(
+            "plugins.styx",
+            "meta {id plugins}\nschema {@ @object{plugins @seq(@string)}}",
+        );
+        dir.write(
+            "conflict.styx",
+            "meta {id conflict}\nschema {@ @object{host @int}}",
+        );
+        let uri = Url::from_file_path(dir.path().join("config.styx")).unwrap();
+
+        let source = "@schema (base.styx plugins.styx)\nhost localhost";
+        let value = styx_tree::parse(source).unwrap();
+        let result = validate_against_schema(&value, source, &uri, &mut ValidationCache::default())
+            .await
+            .unwrap();
+        let missing: Vec<_> = result.errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(missing, ["plugins"]);
+
+        let value = styx_tree::parse("@schema (base.styx conflict.styx)\nhost localhost").unwrap();
+        let err = load_document_schema(&value, &uri).await.unwrap_err();
+        assert!(err.contains("conflicting definitions of `host`"), "{err}");
+    }
+
     #[test]
     fn test_find_schema_declaration_opt_out() {
         // @schema @ means "no schema, stop asking"
@@ -747,17 +833,60 @@ mod tests {
         assert!(decl.is_none());
     }
 
-    #[test]
-    fn test_schema_ref_none_returns_error() {
+    #[tokio::test]
+    async fn test_schema_ref_none_returns_error() {
         let schema_ref = SchemaRef::None;
         let uri = Url::parse("file:///test.styx").unwrap();
 
         // All methods should return errors for SchemaRef::None
-        assert!(schema_ref.load_source(&uri).is_err());
-        assert!(schema_ref.load_schema(&uri).is_err());
+        assert!(schema_ref.load_source(&uri).await.is_err());
+        assert!(schema_ref.load_schema(&uri).await.is_err());
         assert!(schema_ref.to_uri(&uri, "").is_err());
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_concurrent_schema_loads_do_not_block() {
+        use std::time::Duration;
+
+        const SCHEMA: &str = "meta {id test}\nschema {@ @object{port @int}}";
+
+        let dir = TempDir::new("lsp-schema-load");
+        let fast = dir.write("fast.styx", SCHEMA);
+
+        // Opening a FIFO for reading blocks until a writer shows up, which makes
+        // for a schema source that is as slow as we want it to be
+        let slow = dir.path().join("slow.styx");
+        let status = std::process::Command::new("mkfifo")
+            .arg(&slow)
+            .status()
+            .expect("mkfifo should run");
+        assert!(status.success());
+
+        let slow_load = tokio::spawn({
+            let slow = slow.clone();
+            async move { load_schema_file_async(&slow).await }
+        });
+
+        // Single-threaded runtime: a blocking read would starve this load
+        let fast_result =
+            tokio::time::timeout(Duration::from_secs(5), load_schema_file_async(&fast))
+                .await
+                .expect("fast load should not wait for the slow one");
+        assert!(fast_result.is_ok(), "{fast_result:?}");
+        assert!(!slow_load.is_finished());
+
+        tokio::task::spawn_blocking(move || std::fs::write(&slow, SCHEMA))
+            .await
+            .unwrap()
+            .unwrap();
+        let slow_result = tokio::time::timeout(Duration::from_secs(5), slow_load)
+            .await
+            .expect("slow load should finish once written")
+            .unwrap();
+        assert!(slow_result.is_ok(), "{slow_result:?}");
+    }
+
     #[test]
     fn test_opt_out_prevents_schema_hints() {
         // With @schema @, find_schema_declaration returns Some(SchemaRef::None)
@@ -1,48 +1,9 @@
 //! Semantic token computation for syntax highlighting
 
-use styx_cst::{Parse, SyntaxKind, SyntaxNode, SyntaxToken};
+use styx_cst::Parse;
+pub use styx_cst::{HighlightSpan, TokenType, compute_highlight_spans};
 use tower_lsp::lsp_types::*;
 
-/// Semantic token types we support
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(u32)]
-pub enum TokenType {
-    /// Comments
-    Comment = 0,
-    /// String/scalar values
-    String = 1,
-    /// Numeric values (schema-aware)
-    Number = 2,
-    /// Keywords (for booleans if schema-aware)
-    Keyword = 3,
-    /// Type names (tags)
-    Type = 4,
-    /// Enum members (enum variant tags)
-    EnumMember = 5,
-    /// Properties (object keys)
-    Property = 6,
-    /// Operators (@ =)
-    Operator = 7,
-}
-
-impl TokenType {
-    #[allow(dead_code)]
-    pub const COUNT: usize = 8;
-
-    pub fn as_str(self) -> &'static str {
-        match self {
-            TokenType::Comment => "comment",
-            TokenType::String => "string",
-            TokenType::Number => "number",
-            TokenType::Keyword => "keyword",
-            TokenType::Type => "type",
-            TokenType::EnumMember => "enumMember",
-            TokenType::Property => "property",
-            TokenType::Operator => "operator",
-        }
-    }
-}
-
 /// Semantic token modifiers we support
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
@@ -95,494 +56,30 @@ struct RawToken {
     modifiers: u32,
 }
 
-/// A highlight span with byte range (for CLI/terminal output)
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct HighlightSpan {
-    /// Byte offset where the span starts
-    pub start: usize,
-    /// Byte offset where the span ends (exclusive)
-    pub end: usize,
-    /// The type of token this span represents
-    pub token_type: TokenType,
-    /// Whether this is a doc comment
-    pub is_doc_comment: bool,
-}
-
-/// Context for semantic token collection
-#[derive(Clone, Copy, Default)]
-struct WalkContext {
-    /// Are we inside a sequence? (affects how keys are highlighted)
-    in_sequence: bool,
-}
-
 /// Compute semantic tokens for a parsed document
 pub fn compute_semantic_tokens(parse: &Parse) -> Vec<SemanticToken> {
     let content = parse.syntax().to_string();
-    let mut raw_tokens = Vec::new();
-
-    // Walk the CST and emit tokens
-    walk_node(
-        &parse.syntax(),
-        &content,
-        &mut raw_tokens,
-        WalkContext::default(),
-    );
-
-    // Sort tokens by position
-    raw_tokens.sort_by(|a, b| a.line.cmp(&b.line).then(a.start_char.cmp(&b.start_char)));
-
-    // Encode as delta positions
-    encode_tokens(&raw_tokens)
-}
-
-/// Compute highlight spans with byte ranges for terminal/CLI output
-pub fn compute_highlight_spans(parse: &Parse) -> Vec<HighlightSpan> {
-    let mut spans = Vec::new();
-    walk_node_for_spans(&parse.syntax(), &mut spans, WalkContext::default());
-
-    // Sort spans by start position
-    spans.sort_by_key(|s| s.start);
-    spans
-}
-
-/// Recursively walk a syntax node and collect highlight spans with byte ranges
-fn walk_node_for_spans(node: &SyntaxNode, spans: &mut Vec<HighlightSpan>, ctx: WalkContext) {
-    match node.kind() {
-        SyntaxKind::KEY => {
-            if ctx.in_sequence {
-                collect_key_spans_as_values(node, spans);
-            } else {
-                collect_key_spans(node, spans);
-            }
-        }
-        SyntaxKind::TAG => {
-            for child in node.children_with_tokens() {
-                if let Some(token) = child.as_token() {
-                    if token.kind() == SyntaxKind::TAG_TOKEN {
-                        add_span_from_syntax(spans, token, TokenType::Type, false);
-                    }
-                } else if let Some(child_node) = child.as_node()
-                    && child_node.kind() == SyntaxKind::TAG_PAYLOAD
-                {
-                    walk_node_for_spans(child_node, spans, ctx);
-                }
-            }
-        }
-        SyntaxKind::SEQUENCE => {
-            let seq_ctx = WalkContext { in_sequence: true };
-            for child in node.children_with_tokens() {
-                if let Some(child_node) = child.as_node() {
-                    walk_node_for_spans(child_node, spans, seq_ctx);
-                }
-            }
-        }
-        SyntaxKind::UNIT => {
-            for child in node.children_with_tokens() {
-                if let Some(token) = child.as_token()
-                    && (token.kind() == SyntaxKind::TAG_TOKEN || token.kind() == SyntaxKind::AT)
-                {
-                    add_span_from_syntax(spans, token, TokenType::Type, false);
-                }
-            }
-        }
-        SyntaxKind::SCALAR => {
-            for child in node.children_with_tokens() {
-                if let Some(token) = child.as_token()
-                    && is_scalar_token(token.kind())
-                {
-                    add_span_from_syntax(spans, token, TokenType::String, false);
-                }
-            }
-        }
-        SyntaxKind::HEREDOC => {
-            for child in node.children_with_tokens() {
-                if let Some(token) = child.as_token() {
-                    match token.kind() {
-                        SyntaxKind::HEREDOC_START | SyntaxKind::HEREDOC_END => {
-                            add_span_from_syntax(spans, token, TokenType::Operator, false);
-                        }
-                        SyntaxKind::HEREDOC_CONTENT => {
-                            add_span_from_syntax(spans, token, TokenType::String, false);
-                        }
-                        _ => {}
-                    }
-                }
-            }
-        }
-        SyntaxKind::ATTRIBUTE => {
-            for child in node.children_with_tokens() {
-                if let Some(token) = child.as_token() {
-                    if token.kind() == SyntaxKind::GT {
-                        add_span_from_syntax(spans, token, TokenType::Operator, false);
-                    }
-                } else if let Some(child_node) = child.as_node() {
-                    if child_node.kind() == SyntaxKind::KEY {
-                        collect_key_spans(child_node, spans);
-                    } else {
-                        walk_node_for_spans(child_node, spans, ctx);
-                    }
-                }
-            }
-        }
-        _ => {
-            // Handle comments at token level
-            for child in node.children_with_tokens() {
-                if let Some(token) = child.as_token() {
-                    match token.kind() {
-                        SyntaxKind::LINE_COMMENT => {
-                            add_span_from_syntax(spans, token, TokenType::Comment, false);
-                        }
-                        SyntaxKind::DOC_COMMENT => {
-                            add_span_from_syntax(spans, token, TokenType::Comment, true);
-                        }
-                        _ => {}
-                    }
-                } else if let Some(child_node) = child.as_node() {
-                    walk_node_for_spans(child_node, spans, ctx);
-                }
-            }
-        }
-    }
-}
-
-/// Collect tokens from a KEY node (object property)
-fn collect_key_spans(node: &SyntaxNode, spans: &mut Vec<HighlightSpan>) {
-    for child in node.children_with_tokens() {
-        if let Some(child_node) = child.as_node() {
-            match child_node.kind() {
-                SyntaxKind::SCALAR => {
-                    for t in child_node.children_with_tokens() {
-                        if let Some(token) = t.as_token()
-                            && is_scalar_token(token.kind())
-                        {
-                            add_span_from_syntax(spans, token, TokenType::Property, false);
-                        }
-                    }
-                }
-                SyntaxKind::TAG => {
-                    // Tagged key like @schema
-                    for t in child_node.children_with_tokens() {
-                        if let Some(token) = t.as_token()
-                            && token.kind() == SyntaxKind::TAG_TOKEN
-                        {
-                            add_span_from_syntax(spans, token, TokenType::Property, false);
-                        }
-                    }
-                }
-                SyntaxKind::UNIT => {
-                    for t in child_node.children_with_tokens() {
-                        if let Some(token) = t.as_token()
-                            && (token.kind() == SyntaxKind::TAG_TOKEN
-                                || token.kind() == SyntaxKind::AT)
-                        {
-                            add_span_from_syntax(spans, token, TokenType::Type, false);
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-    }
-}
-
-/// Collect tokens from a KEY node as values (in sequence context)
-fn collect_key_spans_as_values(node: &SyntaxNode, spans: &mut Vec<HighlightSpan>) {
-    for child in node.children_with_tokens() {
-        if let Some(child_node) = child.as_node() {
-            match child_node.kind() {
-                SyntaxKind::SCALAR => {
-                    for t in child_node.children_with_tokens() {
-                        if let Some(token) = t.as_token()
-                            && is_scalar_token(token.kind())
-                        {
-                            add_span_from_syntax(spans, token, TokenType::String, false);
-                        }
-                    }
-                }
-                SyntaxKind::TAG => {
-                    walk_node_for_spans(child_node, spans, WalkContext { in_sequence: true });
-                }
-                SyntaxKind::UNIT => {
-                    for t in child_node.children_with_tokens() {
-                        if let Some(token) = t.as_token()
-                            && (token.kind() == SyntaxKind::TAG_TOKEN
-                                || token.kind() == SyntaxKind::AT)
-                        {
-                            add_span_from_syntax(spans, token, TokenType::Type, false);
-                        }
-                    }
-                }
-                SyntaxKind::SEQUENCE => {
-                    walk_node_for_spans(child_node, spans, WalkContext { in_sequence: true });
-                }
-                SyntaxKind::OBJECT => {
-                    walk_node_for_spans(child_node, spans, WalkContext::default());
-                }
-                _ => {}
-            }
-        }
-    }
-}
-
-/// Add a highlight span from a syntax token
-fn add_span_from_syntax(
-    spans: &mut Vec<HighlightSpan>,
-    token: &SyntaxToken,
-    token_type: TokenType,
-    is_doc_comment: bool,
-) {
-    let start: usize = token.text_range().start().into();
-    let end: usize = token.text_range().end().into();
-
-    spans.push(HighlightSpan {
-        start,
-        end,
-        token_type,
-        is_doc_comment,
-    });
-}
-
-/// Recursively walk a syntax node and collect semantic tokens
-fn walk_node(node: &SyntaxNode, content: &str, tokens: &mut Vec<RawToken>, ctx: WalkContext) {
-    match node.kind() {
-        SyntaxKind::KEY => {
-            // Key nodes contain scalars or tags
-            // In a sequence context, keys are actually values (strings)
-            // In object context, keys are properties
-            if ctx.in_sequence {
-                collect_key_tokens_as_values(node, content, tokens);
+    let raw_tokens: Vec<RawToken> = compute_highlight_spans(parse)
+        .into_iter()
+        .map(|span| {
+            let (line, start_char) = offset_to_line_col(&content, span.start);
+            let modifiers = if span.is_doc_comment {
+                1 << TokenModifier::Documentation as u32
             } else {
-                collect_key_tokens(node, content, tokens);
-            }
-        }
-        SyntaxKind::TAG => {
-            // Tag nodes - TAG_TOKEN is the full @name
-            for child in node.children_with_tokens() {
-                if let Some(token) = child.as_token() {
-                    if token.kind() == SyntaxKind::TAG_TOKEN {
-                        add_token_from_syntax(tokens, content, token, TokenType::Type, 0);
-                    }
-                } else if let Some(child_node) = child.as_node()
-                    && child_node.kind() == SyntaxKind::TAG_PAYLOAD
-                {
-                    // Recurse into payload
-                    walk_node(child_node, content, tokens, ctx);
-                }
-            }
-        }
-        SyntaxKind::SEQUENCE => {
-            // Sequence - entries inside are values, not key-value pairs
-            let seq_ctx = WalkContext { in_sequence: true };
-            for child in node.children_with_tokens() {
-                if let Some(child_node) = child.as_node() {
-                    walk_node(child_node, content, tokens, seq_ctx);
-                }
-            }
-        }
-        SyntaxKind::UNIT => {
-            // Unit @ token
-            for child in node.children_with_tokens() {
-                if let Some(token) = child.as_token()
-                    && (token.kind() == SyntaxKind::TAG_TOKEN || token.kind() == SyntaxKind::AT)
-                {
-                    add_token_from_syntax(tokens, content, token, TokenType::Type, 0);
-                }
-            }
-        }
-        SyntaxKind::SCALAR => {
-            // Scalar values (not in KEY position) get string highlighting
-            for child in node.children_with_tokens() {
-                if let Some(token) = child.as_token()
-                    && is_scalar_token(token.kind())
-                {
-                    add_token_from_syntax(tokens, content, token, TokenType::String, 0);
-                }
-            }
-        }
-        SyntaxKind::HEREDOC => {
-            // Heredoc - highlight markers and content
-            for child in node.children_with_tokens() {
-                if let Some(token) = child.as_token() {
-                    match token.kind() {
-                        SyntaxKind::HEREDOC_START | SyntaxKind::HEREDOC_END => {
-                            add_token_from_syntax(tokens, content, token, TokenType::Operator, 0);
-                        }
-                        SyntaxKind::HEREDOC_CONTENT => {
-                            add_token_from_syntax(tokens, content, token, TokenType::String, 0);
-                        }
-                        _ => {}
-                    }
-                }
-            }
-        }
-        SyntaxKind::ATTRIBUTE => {
-            // Attribute: key=value - highlight key as property, = as operator, value as string
-            for child in node.children_with_tokens() {
-                if let Some(token) = child.as_token() {
-                    match token.kind() {
-                        SyntaxKind::GT => {
-                            add_token_from_syntax(tokens, content, token, TokenType::Operator, 0);
-                        }
-                        kind if is_scalar_token(kind) => {
-                            // First scalar is key, subsequent are values
-                            // We need to track position to distinguish
-                            add_token_from_syntax(tokens, content, token, TokenType::Property, 0);
-                        }
-                        _ => {}
-                    }
-                } else if let Some(child_node) = child.as_node() {
-                    // Recurse into child nodes
-                    walk_node(child_node, content, tokens, ctx);
-                }
-            }
-        }
-        _ => {
-            // Recurse into children, but also check for tokens at this level
-            for child in node.children_with_tokens() {
-                if let Some(token) = child.as_token() {
-                    // Handle comments at any level
-                    match token.kind() {
-                        SyntaxKind::LINE_COMMENT => {
-                            add_token_from_syntax(tokens, content, token, TokenType::Comment, 0);
-                        }
-                        SyntaxKind::DOC_COMMENT => {
-                            let modifiers = 1 << TokenModifier::Documentation as u32;
-                            add_token_from_syntax(
-                                tokens,
-                                content,
-                                token,
-                                TokenType::Comment,
-                                modifiers,
-                            );
-                        }
-                        _ => {}
-                    }
-                } else if let Some(child_node) = child.as_node() {
-                    walk_node(child_node, content, tokens, ctx);
-                }
-            }
-        }
-    }
-}
-
-/// Collect tokens from a KEY node, highlighting them as properties
-/// KEY can contain: SCALAR, TAG, or direct scalar tokens
-fn collect_key_tokens(node: &SyntaxNode, content: &str, tokens: &mut Vec<RawToken>) {
-    for child in node.children_with_tokens() {
-        if let Some(token) = child.as_token() {
-            // Direct scalar token under KEY (might happen in some cases)
-            if is_scalar_token(token.kind()) {
-                add_token_from_syntax(tokens, content, token, TokenType::Property, 0);
-            }
-        } else if let Some(child_node) = child.as_node() {
-            match child_node.kind() {
-                SyntaxKind::SCALAR => {
-                    // KEY -> SCALAR -> token
-                    for scalar_child in child_node.children_with_tokens() {
-                        if let Some(token) = scalar_child.as_token()
-                            && is_scalar_token(token.kind())
-                        {
-                            add_token_from_syntax(tokens, content, token, TokenType::Property, 0);
-                        }
-                    }
-                }
-                SyntaxKind::TAG => {
-                    // KEY -> TAG (tag as key, e.g., `@schema foo.styx`)
-                    for tag_child in child_node.children_with_tokens() {
-                        if let Some(token) = tag_child.as_token()
-                            && token.kind() == SyntaxKind::TAG_TOKEN
-                        {
-                            add_token_from_syntax(tokens, content, token, TokenType::Property, 0);
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-    }
-}
-
-/// Collect tokens from a KEY node, highlighting them as values (for sequence elements)
-/// In sequences, "keys" are actually values (e.g., `(1 2 3)` - the 1, 2, 3 are values)
-fn collect_key_tokens_as_values(node: &SyntaxNode, content: &str, tokens: &mut Vec<RawToken>) {
-    for child in node.children_with_tokens() {
-        if let Some(token) = child.as_token() {
-            // Direct scalar token under KEY
-            if is_scalar_token(token.kind()) {
-                add_token_from_syntax(tokens, content, token, TokenType::String, 0);
-            }
-        } else if let Some(child_node) = child.as_node() {
-            match child_node.kind() {
-                SyntaxKind::SCALAR => {
-                    // KEY -> SCALAR -> token
-                    for scalar_child in child_node.children_with_tokens() {
-                        if let Some(token) = scalar_child.as_token()
-                            && is_scalar_token(token.kind())
-                        {
-                            add_token_from_syntax(tokens, content, token, TokenType::String, 0);
-                        }
-                    }
-                }
-                SyntaxKind::TAG => {
-                    // KEY -> TAG in sequence (e.g., `(@ok @err)` or `@route{...}`)
-                    for tag_child in child_node.children_with_tokens() {
-                        if let Some(token) = tag_child.as_token()
-                            && token.kind() == SyntaxKind::TAG_TOKEN
-                        {
-                            add_token_from_syntax(tokens, content, token, TokenType::Type, 0);
-                        } else if let Some(tag_node) = tag_child.as_node()
-                            && tag_node.kind() == SyntaxKind::TAG_PAYLOAD
-                        {
-                            // Recurse into tag payload (object context for properties)
-                            walk_node(tag_node, content, tokens, WalkContext::default());
-                        }
-                    }
-                }
-                SyntaxKind::SEQUENCE => {
-                    // KEY -> SEQUENCE (nested sequences like `((1 2) (3 4))`)
-                    // Recurse into nested sequence with sequence context
-                    let seq_ctx = WalkContext { in_sequence: true };
-                    walk_node(child_node, content, tokens, seq_ctx);
-                }
-                SyntaxKind::OBJECT => {
-                    // KEY -> OBJECT (objects in sequences like `({a 1} {b 2})`)
-                    // Objects should use regular object context (keys are properties)
-                    walk_node(child_node, content, tokens, WalkContext::default());
-                }
-                _ => {}
+                0
+            };
+            RawToken {
+                line,
+                start_char,
+                length: (span.end - span.start) as u32,
+                token_type: span.token_type,
+                modifiers,
             }
-        }
-    }
-}
+        })
+        .collect();
 
-/// Check if a syntax kind is a scalar token
-fn is_scalar_token(kind: SyntaxKind) -> bool {
-    matches!(
-        kind,
-        SyntaxKind::BARE_SCALAR | SyntaxKind::QUOTED_SCALAR | SyntaxKind::RAW_SCALAR
-    )
-}
-
-/// Add a token from a syntax token
-fn add_token_from_syntax(
-    tokens: &mut Vec<RawToken>,
-    content: &str,
-    token: &SyntaxToken,
-    token_type: TokenType,
-    modifiers: u32,
-) {
-    let start: usize = token.text_range().start().into();
-    let end: usize = token.text_range().end().into();
-    let (line, start_char) = offset_to_line_col(content, start);
-    let length = (end - start) as u32;
-
-    tokens.push(RawToken {
-        line,
-        start_char,
-        length,
-        token_type,
-        modifiers,
-    });
+    // Spans come sorted by offset, so the tokens are in position order
+    encode_tokens(&raw_tokens)
 }
 
 /// Convert byte offset to (line, column)
@@ -1,6 +1,7 @@
 //! LSP server implementation
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use styx_cst::{Parse, parse};
@@ -18,6 +19,7 @@ use crate::schema_validation::{
     load_document_schema, resolve_schema, validate_against_schema,
 };
 use crate::semantic_tokens::{compute_semantic_tokens, semantic_token_legend};
+use crate::validation_cache::ValidationCache;
 use styx_lsp_ext as ext;
 
 /// Document state tracked by the server
@@ -52,6 +54,8 @@ pub struct StyxLanguageServer {
     documents: DocumentMap,
     /// Extension manager
     extensions: Arc<ExtensionManager>,
+    /// Schema validation results of open documents, reused across edits
+    validation_caches: RwLock<HashMap<Url, ValidationCache>>,
 }
 
 impl StyxLanguageServer {
@@ -61,6 +65,19 @@ impl StyxLanguageServer {
             client,
             documents: documents.clone(),
             extensions: Arc::new(ExtensionManager::new(documents)),
+            validation_caches: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Create a server that keeps the extension allowlist at `config_path`
+    /// instead of the user config file.
+    pub fn with_config_path(client: Client, config_path: Option<PathBuf>) -> Self {
+        let documents: DocumentMap = Arc::new(RwLock::new(HashMap::new()));
+        Self {
+            client,
+            documents: documents.clone(),
+            extensions: Arc::new(ExtensionManager::with_config_path(documents, config_path)),
+            validation_caches: RwLock::new(HashMap::new()),
         }
     }
 
@@ -69,7 +86,7 @@ impl StyxLanguageServer {
     /// Returns information about blocked extensions if not allowed.
     async fn check_for_extension(&self, tree: &Value, uri: &Url) -> Option<BlockedExtensionInfo> {
         // Try to load the schema
-        let Ok(schema) = load_document_schema(tree, uri) else {
+        let Ok(schema) = load_document_schema(tree, uri).await else {
             return None;
         };
 
@@ -93,7 +110,30 @@ impl StyxLanguageServer {
                 schema_id: ext_info.schema_id,
                 command,
             }),
-            ExtensionResult::Failed => None,
+            ExtensionResult::Denied | ExtensionResult::Failed => None,
+        }
+    }
+
+    /// Re-publish diagnostics for all open documents, e.g. after the
+    /// extension allowlist changed.
+    async fn republish_all_diagnostics(&self) {
+        let docs = self.documents.read().await;
+        for (uri, doc) in docs.iter() {
+            let blocked_extension = if let Some(ref tree) = doc.tree {
+                self.check_for_extension(tree, uri).await
+            } else {
+                None
+            };
+            self.publish_diagnostics(
+                uri.clone(),
+                &doc.content,
+                &doc.parse,
+                doc.tree.as_ref(),
+                None, // tree_error already reported on initial load
+                doc.version,
+                blocked_extension,
+            )
+            .await;
         }
     }
 
@@ -109,7 +149,9 @@ impl StyxLanguageServer {
         version: i32,
         blocked_extension: Option<BlockedExtensionInfo>,
     ) {
-        let mut diagnostics = self.compute_diagnostics(&uri, content, parsed, tree, tree_error);
+        let mut diagnostics = self
+            .compute_diagnostics(&uri, content, parsed, tree, tree_error)
+            .await;
 
         // Add diagnostic for blocked extension if applicable
         if let Some(blocked) = blocked_extension
@@ -138,7 +180,7 @@ impl StyxLanguageServer {
 
         // Try to get diagnostics from extension
         if let Some(tree) = tree
-            && let Ok(schema_file) = load_document_schema(tree, &uri)
+            && let Ok(schema_file) = load_document_schema(tree, &uri).await
         {
             let schema_id = &schema_file.meta.id;
             if let Some(client) = self.extensions.get_client(schema_id).await {
@@ -148,7 +190,11 @@ impl StyxLanguageServer {
                     content: content.to_string(),
                 };
 
-                match client.diagnostics(ext_params).await {
+                match self
+                    .extensions
+                    .call(schema_id, client.diagnostics(ext_params))
+                    .await
+                {
                     Ok(ext_diagnostics) => {
                         tracing::debug!(
                             count = ext_diagnostics.len(),
@@ -190,7 +236,7 @@ impl StyxLanguageServer {
     }
 
     /// Compute diagnostics for document content
-    fn compute_diagnostics(
+    async fn compute_diagnostics(
         &self,
         uri: &Url,
         content: &str,
@@ -276,7 +322,7 @@ impl StyxLanguageServer {
         // Phase 3: Schema validation
         if let Some(tree) = tree {
             // Only validate if there's a schema declaration
-            if let Ok(schema) = resolve_schema(tree, uri) {
+            if let Ok(schema) = resolve_schema(tree, uri).await {
                 // Create related_information linking to schema
                 let schema_location = Some(DiagnosticRelatedInformation {
                     location: Location {
@@ -286,7 +332,19 @@ impl StyxLanguageServer {
                     message: format!("schema: {}", schema.uri),
                 });
 
-                match validate_against_schema(tree, uri) {
+                // Don't hold the lock while the schema loads
+                let mut cache = self
+                    .validation_caches
+                    .write()
+                    .await
+                    .remove(uri)
+                    .unwrap_or_default();
+                let validation = validate_against_schema(tree, content, uri, &mut cache).await;
+                self.validation_caches
+                    .write()
+                    .await
+                    .insert(uri.clone(), cache);
+                match validation {
                     Ok(result) => {
                         // Add validation errors
                         for error in &result.errors {
@@ -455,6 +513,8 @@ impl LanguageServer for StyxLanguageServer {
                 inlay_hint_provider: Some(OneOf::Left(true)),
                 // Document formatting
                 document_formatting_provider: Some(OneOf::Left(true)),
+                // Selection formatting
+                document_range_formatting_provider: Some(OneOf::Left(true)),
                 // On-type formatting (for auto-indent on Enter)
                 document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
                     first_trigger_character: "\n".to_string(),
@@ -464,7 +524,10 @@ impl LanguageServer for StyxLanguageServer {
                 document_symbol_provider: Some(OneOf::Left(true)),
                 // Execute command (for code action commands)
                 execute_command_provider: Some(ExecuteCommandOptions {
-                    commands: vec!["styx.allowExtension".to_string()],
+                    commands: vec![
+                        "styx.allowExtension".to_string(),
+                        "styx.denyExtension".to_string(),
+                    ],
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                 }),
                 ..Default::default()
@@ -600,6 +663,7 @@ impl LanguageServer for StyxLanguageServer {
             let mut docs = self.documents.write().await;
             docs.remove(&uri);
         }
+        self.validation_caches.write().await.remove(&uri);
 
         // Clear diagnostics
         self.client.publish_diagnostics(uri, vec![], None).await;
@@ -640,7 +704,7 @@ impl LanguageServer for StyxLanguageServer {
 
         // Find schema declaration and create a link for it
         if let Some(range) = find_schema_declaration_range(tree, &doc.content)
-            && let Ok(schema) = resolve_schema(tree, &uri)
+            && let Ok(schema) = resolve_schema(tree, &uri).await
         {
             links.push(DocumentLink {
                 range,
@@ -672,7 +736,7 @@ impl LanguageServer for StyxLanguageServer {
         let offset = position_to_offset(&doc.content, position);
 
         // Try to resolve the schema for this document
-        let resolved = resolve_schema(tree, &uri).ok();
+        let resolved = resolve_schema(tree, &uri).await.ok();
 
         // Case 1: On the schema declaration line - jump to schema file
         if let Some(range) = find_schema_declaration_range(tree, &doc.content)
@@ -709,7 +773,7 @@ impl LanguageServer for StyxLanguageServer {
                 }
                 if let Some(ref doc_tree) = doc_state.tree {
                     // Check if this doc references our schema
-                    if let Ok(doc_schema) = resolve_schema(doc_tree, doc_uri)
+                    if let Ok(doc_schema) = resolve_schema(doc_tree, doc_uri).await
                         && doc_schema.uri == uri
                     {
                         // This doc uses our schema - find the field
@@ -727,7 +791,7 @@ impl LanguageServer for StyxLanguageServer {
         }
 
         // Case 4: Try extension for domain-specific definition (e.g., $param → declaration)
-        if let Ok(schema_file) = load_document_schema(tree, &uri) {
+        if let Ok(schema_file) = load_document_schema(tree, &uri).await {
             let schema_id = &schema_file.meta.id;
             tracing::debug!(%schema_id, "Trying extension for definition");
             if let Some(client) = self.extensions.get_client(schema_id).await {
@@ -752,7 +816,11 @@ impl LanguageServer for StyxLanguageServer {
                     tagged_context,
                 };
 
-                match client.definition(ext_params).await {
+                match self
+                    .extensions
+                    .call(schema_id, client.definition(ext_params))
+                    .await
+                {
                     Ok(locations) if !locations.is_empty() => {
                         tracing::debug!(count = locations.len(), "Extension returned definitions");
                         let lsp_locations: Vec<Location> = locations
@@ -821,7 +889,7 @@ impl LanguageServer for StyxLanguageServer {
         let offset = position_to_offset(&doc.content, position);
 
         // Try to resolve the schema for this document
-        let resolved = resolve_schema(tree, &uri).ok();
+        let resolved = resolve_schema(tree, &uri).await.ok();
 
         // Case 1: Hover on schema declaration
         if let Some(range) = find_schema_declaration_range(tree, &doc.content)
@@ -843,7 +911,7 @@ impl LanguageServer for StyxLanguageServer {
         }
 
         // Case 2: Try extension for domain-specific hover (takes priority over schema hover)
-        if let Ok(schema_file) = load_document_schema(tree, &uri) {
+        if let Ok(schema_file) = load_document_schema(tree, &uri).await {
             let schema_id = &schema_file.meta.id;
             tracing::debug!(%schema_id, "Trying extension for hover");
             if let Some(client) = self.extensions.get_client(schema_id).await {
@@ -868,7 +936,11 @@ impl LanguageServer for StyxLanguageServer {
                     tagged_context,
                 };
 
-                match client.hover(ext_params).await {
+                match self
+                    .extensions
+                    .call(schema_id, client.hover(ext_params))
+                    .await
+                {
                     Ok(Some(result)) => {
                         tracing::debug!(contents = %result.contents, "Extension returned hover");
                         let range = result.range.map(|r| Range {
@@ -948,12 +1020,19 @@ impl LanguageServer for StyxLanguageServer {
             return Ok(None);
         };
 
-        let Some(tree) = &doc.tree else {
-            return Ok(None);
+        // A document being typed is often broken; complete against the
+        // entries that could still be read
+        let recovered;
+        let tree = match &doc.tree {
+            Some(tree) => tree,
+            None => {
+                recovered = styx_tree::parse_recovering(&doc.content).0;
+                &recovered
+            }
         };
 
         // Get resolved schema
-        let Ok(schema) = resolve_schema(tree, &uri) else {
+        let Ok(schema) = resolve_schema(tree, &uri).await else {
             return Ok(None);
         };
 
@@ -1013,11 +1092,9 @@ impl LanguageServer for StyxLanguageServer {
             end: position,
         });
 
-        // Filter out existing fields
-        let available_fields: Vec<_> = schema_fields
-            .into_iter()
-            .filter(|(name, _)| !existing_fields.contains(name))
-            .collect();
+        // Leave out fields already present, and put required fields first
+        let available_fields =
+            key_completion_candidates(schema_fields, &existing_fields, current_word.as_deref());
 
         // If there are too many fields, apply filtering
         const MAX_COMPLETIONS: usize = 50;
@@ -1058,16 +1135,8 @@ impl LanguageServer for StyxLanguageServer {
                     .map(|(name, type_str, _)| (name, type_str))
                     .collect()
             } else {
-                // No word typed - just show required fields first, up to limit
-                let mut sorted = available_fields;
-                sorted.sort_by_key(|(_, type_str)| {
-                    if type_str.starts_with("@optional") || type_str.starts_with("@default") {
-                        1
-                    } else {
-                        0
-                    }
-                });
-                sorted.into_iter().take(MAX_COMPLETIONS).collect()
+                // No word typed - candidates are already required-first, up to limit
+                available_fields.into_iter().take(MAX_COMPLETIONS).collect()
             }
         } else {
             available_fields
@@ -1077,8 +1146,7 @@ impl LanguageServer for StyxLanguageServer {
         let mut items: Vec<CompletionItem> = filtered_fields
             .into_iter()
             .map(|(name, type_str)| {
-                let is_optional =
-                    type_str.starts_with("@optional") || type_str.starts_with("@default");
+                let is_optional = is_optional_type_str(&type_str);
 
                 // Add "did you mean" label modifier for fuzzy matches
                 let label_details = current_word.as_ref().and_then(|word| {
@@ -1117,7 +1185,7 @@ impl LanguageServer for StyxLanguageServer {
             .collect();
 
         // Try to get completions from extension
-        if let Ok(schema_file) = load_document_schema(tree, &uri) {
+        if let Ok(schema_file) = load_document_schema(tree, &uri).await {
             let schema_id = &schema_file.meta.id;
             if let Some(client) = self.extensions.get_client(schema_id).await {
                 let tagged_context = find_tagged_context_at_offset(tree, offset);
@@ -1138,7 +1206,11 @@ impl LanguageServer for StyxLanguageServer {
                     tagged_context,
                 };
 
-                match client.completions(ext_params).await {
+                match self
+                    .extensions
+                    .call(schema_id, client.completions(ext_params))
+                    .await
+                {
                     Ok(ext_items) => {
                         tracing::debug!(count = ext_items.len(), "Got completions from extension");
                         for item in ext_items {
@@ -1250,6 +1322,20 @@ impl LanguageServer for StyxLanguageServer {
                         is_preferred: Some(true),
                         ..Default::default()
                     }));
+                    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title: format!("Deny LSP extension '{}'", command),
+                        kind: Some(CodeActionKind::QUICKFIX),
+                        diagnostics: Some(vec![diag.clone()]),
+                        command: Some(Command {
+                            title: format!("Deny LSP extension '{}'", command),
+                            command: "styx.denyExtension".to_string(),
+                            arguments: Some(vec![serde_json::json!({
+                                "command": command,
+                            })]),
+                        }),
+                        is_preferred: Some(false),
+                        ..Default::default()
+                    }));
                 }
                 continue;
             }
@@ -1295,7 +1381,7 @@ impl LanguageServer for StyxLanguageServer {
         let docs = self.documents.read().await;
         if let Some(doc) = docs.get(&uri)
             && let Some(ref tree) = doc.tree
-            && let Ok(schema_file) = load_document_schema(tree, &uri)
+            && let Ok(schema_file) = load_document_schema(tree, &uri).await
         {
             let schema_id = &schema_file.meta.id;
             if let Some(client) = self.extensions.get_client(schema_id).await {
@@ -1338,7 +1424,11 @@ impl LanguageServer for StyxLanguageServer {
                     diagnostics: ext_diagnostics,
                 };
 
-                match client.code_actions(ext_params).await {
+                match self
+                    .extensions
+                    .call(schema_id, client.code_actions(ext_params))
+                    .await
+                {
                     Ok(ext_actions) => {
                         tracing::debug!(
                             count = ext_actions.len(),
@@ -1419,7 +1509,7 @@ impl LanguageServer for StyxLanguageServer {
             && let Some(ref tree) = doc.tree
         {
             // Try to load the schema
-            if let Ok(schema_file) = load_document_schema(tree, &uri) {
+            if let Ok(schema_file) = load_document_schema(tree, &uri).await {
                 // Find the object at cursor position
                 let cursor_offset = position_to_offset(&doc.content, params.range.start);
                 let object_ctx = find_object_at_offset(tree, cursor_offset);
@@ -1603,32 +1693,33 @@ impl LanguageServer for StyxLanguageServer {
                         )
                         .await;
 
-                    // Re-publish diagnostics for all open documents to clear the warning
-                    // and trigger extension spawning
-                    let docs = self.documents.read().await;
-                    for (uri, doc) in docs.iter() {
-                        let blocked_extension = if let Some(ref tree) = doc.tree {
-                            self.check_for_extension(tree, uri).await
-                        } else {
-                            None
-                        };
-                        self.publish_diagnostics(
-                            uri.clone(),
-                            &doc.content,
-                            &doc.parse,
-                            doc.tree.as_ref(),
-                            None, // tree_error already reported on initial load
-                            doc.version,
-                            blocked_extension,
-                        )
-                        .await;
-                    }
+                    // Clear the warning and trigger extension spawning
+                    self.republish_all_diagnostics().await;
 
                     // Request inlay hint refresh so hints appear immediately
                     let _ = self.client.inlay_hint_refresh().await;
                 }
                 Ok(None)
             }
+            "styx.denyExtension" => {
+                if let Some(arg) = params.arguments.first()
+                    && let Some(command) = arg.get("command").and_then(|v| v.as_str())
+                {
+                    tracing::info!(command, "Denying LSP extension");
+                    self.extensions.deny(command.to_string()).await;
+
+                    self.client
+                        .log_message(
+                            MessageType::INFO,
+                            format!("Denied LSP extension: {}", command),
+                        )
+                        .await;
+
+                    // Clear the warning; denied extensions are not asked about again
+                    self.republish_all_diagnostics().await;
+                }
+                Ok(None)
+            }
             _ => {
                 tracing::warn!(command = %params.command, "Unknown command");
                 Ok(None)
@@ -1668,7 +1759,7 @@ impl LanguageServer for StyxLanguageServer {
                 }
                 if let Some(ref doc_tree) = doc_state.tree {
                     // Check if this doc references our schema
-                    if let Ok(doc_schema) = resolve_schema(doc_tree, doc_uri)
+                    if let Ok(doc_schema) = resolve_schema(doc_tree, doc_uri).await
                         && doc_schema.uri == uri
                     {
                         // This doc uses our schema - find the field usage
@@ -1685,7 +1776,7 @@ impl LanguageServer for StyxLanguageServer {
             }
         } else {
             // We're in a doc - find the schema definition and other docs using this field
-            if let Ok(schema) = resolve_schema(tree, &uri) {
+            if let Ok(schema) = resolve_schema(tree, &uri).await {
                 // Add the schema definition location
                 if let Some(field_range) = find_field_in_schema_source(&schema.source, &field_name)
                 {
@@ -1697,7 +1788,7 @@ impl LanguageServer for StyxLanguageServer {
                     // Find other docs using the same schema
                     for (doc_uri, doc_state) in docs.iter() {
                         if let Some(ref doc_tree) = doc_state.tree
-                            && let Ok(doc_schema) = resolve_schema(doc_tree, doc_uri)
+                            && let Ok(doc_schema) = resolve_schema(doc_tree, doc_uri).await
                             && doc_schema.uri == schema.uri
                         {
                             // This doc uses the same schema
@@ -1742,10 +1833,10 @@ impl LanguageServer for StyxLanguageServer {
 
         // Check for schema declaration
         if let Some(range) = find_schema_declaration_range(&tree, &content)
-            && let Ok(schema) = resolve_schema(&tree, &uri)
+            && let Ok(schema) = resolve_schema(&tree, &uri).await
         {
             // Extract meta info from schema
-            if let Some(meta) = get_schema_meta(&schema.source) {
+            if let Ok(meta) = facet_styx::parse_meta(&schema.source) {
                 // Show schema name/description as inlay hint after the schema path
                 // First line of description is the short desc
                 let short_desc = meta
@@ -1763,31 +1854,21 @@ impl LanguageServer for StyxLanguageServer {
 
                 if !label.is_empty() {
                     // Build tooltip with full description and id
-                    let tooltip = {
-                        let mut parts = Vec::new();
-                        if let Some(id) = &meta.id {
-                            parts.push(format!("Schema ID: {}", id));
-                        }
-                        // Show full description if it's multi-line
-                        if let Some(desc) = &meta.description
-                            && desc.contains('\n')
-                        {
-                            parts.push(String::new()); // blank line
-                            parts.push(desc.clone());
-                        }
-                        if parts.is_empty() {
-                            None
-                        } else {
-                            Some(InlayHintTooltip::String(parts.join("\n")))
-                        }
-                    };
+                    let mut tooltip = format!("Schema ID: {}", meta.id);
+                    // Show full description if it's multi-line
+                    if let Some(desc) = &meta.description
+                        && desc.contains('\n')
+                    {
+                        tooltip.push_str("\n\n");
+                        tooltip.push_str(desc);
+                    }
 
                     hints.push(InlayHint {
                         position: range.end,
                         label: InlayHintLabel::String(label),
                         kind: Some(InlayHintKind::TYPE),
                         text_edits: None,
-                        tooltip,
+                        tooltip: Some(InlayHintTooltip::String(tooltip)),
                         padding_left: Some(false),
                         padding_right: Some(true),
                         data: None,
@@ -1797,7 +1878,7 @@ impl LanguageServer for StyxLanguageServer {
         }
 
         // Try to get inlay hints from extension
-        if let Ok(schema_file) = load_document_schema(&tree, &uri) {
+        if let Ok(schema_file) = load_document_schema(&tree, &uri).await {
             let schema_id = &schema_file.meta.id;
             tracing::debug!(%schema_id, "Trying extension for inlay hints");
             if let Some(client) = self.extensions.get_client(schema_id).await {
@@ -1817,7 +1898,11 @@ impl LanguageServer for StyxLanguageServer {
                     context: Some(tree.clone()),
                 };
 
-                match client.inlay_hints(ext_params).await {
+                match self
+                    .extensions
+                    .call(schema_id, client.inlay_hints(ext_params))
+                    .await
+                {
                     Ok(ext_hints) => {
                         tracing::debug!(count = ext_hints.len(), "Got inlay hints from extension");
                         for hint in ext_hints {
@@ -1869,20 +1954,8 @@ impl LanguageServer for StyxLanguageServer {
             return Ok(None);
         }
 
-        // Build indent string from editor preferences
-        let indent = if params.options.insert_spaces {
-            " ".repeat(params.options.tab_size as usize)
-        } else {
-            "\t".to_string()
-        };
-
         // Format the document using CST formatter (preserves comments)
-        let options = styx_format::FormatOptions::default().indent(
-            // Leak the string since FormatOptions expects &'static str
-            // This is fine since we're not going to format millions of times
-            Box::leak(indent.into_boxed_str()),
-        );
-
+        let options = format_options(&params.options);
         let formatted = styx_format::format_source(&doc.content, options);
 
         // Only return an edit if the content changed
@@ -1910,6 +1983,30 @@ impl LanguageServer for StyxLanguageServer {
         }]))
     }
 
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let uri = params.text_document.uri;
+
+        let docs = self.documents.read().await;
+        let Some(doc) = docs.get(&uri) else {
+            return Ok(None);
+        };
+
+        // Only format if document parsed successfully
+        if doc.tree.is_none() {
+            return Ok(None);
+        }
+
+        let edits = range_format_edits(&doc.content, params.range, format_options(&params.options));
+        if edits.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(edits))
+        }
+    }
+
     async fn document_symbol(
         &self,
         params: DocumentSymbolParams,
@@ -2145,6 +2242,43 @@ fn find_schema_declaration_range(tree: &Value, content: &str) -> Option<Range> {
     None
 }
 
+/// Formatter options from the editor's formatting preferences.
+fn format_options(options: &FormattingOptions) -> styx_format::FormatOptions {
+    // Build indent string from editor preferences
+    let indent = if options.insert_spaces {
+        // An empty indent isn't valid, so a tab size of 0 falls back to 1
+        " ".repeat(options.tab_size.max(1) as usize)
+    } else {
+        "\t".to_string()
+    };
+
+    styx_format::FormatOptions::default().indent(
+        // Leak the string since FormatOptions expects &'static str
+        // This is fine since we're not going to format millions of times
+        Box::leak(indent.into_boxed_str()),
+    )
+}
+
+/// Edits formatting the entries of `content` that lie within `range`.
+fn range_format_edits(
+    content: &str,
+    range: Range,
+    options: styx_format::FormatOptions,
+) -> Vec<TextEdit> {
+    let start = position_to_offset(content, range.start);
+    let end = position_to_offset(content, range.end);
+    styx_format::format_source_range(content, start..end, options)
+        .into_iter()
+        .map(|edit| TextEdit {
+            range: Range {
+                start: offset_to_position(content, edit.range.start),
+                end: offset_to_position(content, edit.range.end),
+            },
+            new_text: edit.new_text,
+        })
+        .collect()
+}
+
 /// Convert byte offset to LSP Position
 fn offset_to_position(content: &str, offset: usize) -> Position {
     let mut line = 0u32;
@@ -2369,46 +2503,6 @@ fn find_field_in_object(value: &Value, source: &str, field_name: &str) -> Option
     None
 }
 
-/// Schema metadata extracted from the meta block
-struct SchemaMeta {
-    id: Option<String>,
-    version: Option<String>,
-    description: Option<String>,
-}
-
-/// Extract metadata from a schema file's meta block
-fn get_schema_meta(schema_source: &str) -> Option<SchemaMeta> {
-    let tree = styx_tree::parse(schema_source).ok()?;
-    let obj = tree.as_object()?;
-
-    for entry in &obj.entries {
-        if entry.key.as_str() == Some("meta") {
-            let meta_obj = entry.value.as_object()?;
-
-            let id = meta_obj
-                .get("id")
-                .and_then(|v| v.as_str())
-                .map(String::from);
-            let version = meta_obj
-                .get("version")
-                .and_then(|v| v.as_str())
-                .map(String::from);
-            let description = meta_obj
-                .get("description")
-                .and_then(|v| v.as_str())
-                .map(String::from);
-
-            return Some(SchemaMeta {
-                id,
-                version,
-                description,
-            });
-        }
-    }
-
-    None
-}
-
 /// Get the schema type for a field from schema source
 /// Information about a field from the schema
 struct FieldInfo {
@@ -2825,7 +2919,9 @@ fn schema_to_type_str(schema: &facet_styx::Schema) -> String {
         Schema::Union(_) => "@union(...)".to_string(),
         Schema::OneOf(_) => "@oneof(...)".to_string(),
         Schema::Flatten(_) => "@flatten(...)".to_string(),
-        Schema::Literal(s) => format!("\"{}\"", s),
+        Schema::Literal(s) => {
+            styx_format::render_scalar(s, styx_format::ScalarStyle::Quoted).into_owned()
+        }
         Schema::Optional(opt) => format!("@optional({})", schema_to_type_str(&opt.0.0)),
         Schema::Default(def) => format!("@default(..., {})", schema_to_type_str(&def.0.1)),
         Schema::Deprecated(dep) => format!("@deprecated({})", schema_to_type_str(&dep.0.1)),
@@ -2965,6 +3061,36 @@ fn collect_fields_from_object(value: &Value, source: &str, fields: &mut Vec<(Str
     }
 }
 
+/// Schema fields to offer as key completions, required fields first.
+///
+/// Fields whose key is already in `existing` are left out. The key being
+/// typed (`typing`) is in the tree too, so one occurrence of it is ignored.
+fn key_completion_candidates(
+    fields: Vec<(String, String)>,
+    existing: &[String],
+    typing: Option<&str>,
+) -> Vec<(String, String)> {
+    let mut existing: Vec<&str> = existing.iter().map(String::as_str).collect();
+    if let Some(typing) = typing
+        && let Some(index) = existing.iter().position(|key| *key == typing)
+    {
+        existing.remove(index);
+    }
+
+    let mut candidates: Vec<_> = fields
+        .into_iter()
+        .filter(|(name, _)| !existing.contains(&name.as_str()))
+        .collect();
+    // Stable, so schema order is kept within each group
+    candidates.sort_by_key(|(_, type_str)| is_optional_type_str(type_str));
+    candidates
+}
+
+/// Whether a field's type string (from completion) marks it as not required.
+fn is_optional_type_str(type_str: &str) -> bool {
+    type_str.starts_with("@optional") || type_str.starts_with("@default")
+}
+
 /// Get existing field names in a document
 fn get_existing_fields(tree: &Value) -> Vec<String> {
     let mut fields = Vec::new();
@@ -3119,8 +3245,8 @@ fn json_to_styx_value(json: &serde_json::Value) -> styx_tree::Value {
         serde_json::Value::Object(obj) => {
             let entries = obj
                 .iter()
-                .map(|(k, v)| Entry {
-                    key: Value {
+                .map(|(k, v)| {
+                    let key = Value {
                         tag: None,
                         payload: Some(Payload::Scalar(Scalar {
                             text: k.clone(),
@@ -3128,9 +3254,8 @@ fn json_to_styx_value(json: &serde_json::Value) -> styx_tree::Value {
                             span: None,
                         })),
                         span: None,
-                    },
-                    value: json_to_styx_value(v),
-                    doc_comment: None,
+                    };
+                    Entry::new(key, json_to_styx_value(v))
                 })
                 .collect();
 
@@ -3139,6 +3264,7 @@ fn json_to_styx_value(json: &serde_json::Value) -> styx_tree::Value {
                 payload: Some(Payload::Object(Object {
                     entries,
                     span: None,
+                    origin: styx_tree::ObjectOrigin::Braces,
                 })),
                 span: None,
             }
@@ -4004,6 +4130,59 @@ schema {
         );
     }
 
+    #[test]
+    fn test_range_formatting_only_changes_selection() {
+        let content = "name   app\nserver {\n    tls {\n  enabled   true\n    }\n}\nport   8080\n";
+        let range = Range {
+            start: Position::new(2, 4),
+            end: Position::new(4, 5),
+        };
+        let edits = range_format_edits(content, range, styx_format::FormatOptions::default());
+
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range.start, Position::new(2, 0));
+        assert_eq!(edits[0].range.end, Position::new(4, 5));
+        assert_eq!(edits[0].new_text, "    tls {\n        enabled true\n    }");
+    }
+
+    #[test]
+    fn test_key_completions_required_first_without_present_keys() {
+        let fields: Vec<(String, String)> = [
+            ("name", "@optional(@string)"),
+            ("host", "@string"),
+            ("port", "@default(8080 @int)"),
+            ("user", "@string"),
+            ("tls", "@optional(@bool)"),
+        ]
+        .into_iter()
+        .map(|(name, type_str)| (name.to_string(), type_str.to_string()))
+        .collect();
+        let names = |candidates: Vec<(String, String)>| {
+            candidates
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+        };
+
+        // `host` is present; `na` is being typed and doesn't hide anything
+        let existing = get_existing_fields(&styx_tree::parse("host localhost\nna").unwrap());
+        assert_eq!(
+            names(key_completion_candidates(
+                fields.clone(),
+                &existing,
+                Some("na")
+            )),
+            vec!["user", "name", "port", "tls"]
+        );
+
+        // A fully typed key still completes to itself
+        let existing = get_existing_fields(&styx_tree::parse("host localhost\nname").unwrap());
+        assert_eq!(
+            names(key_completion_candidates(fields, &existing, Some("name"))),
+            vec!["user", "name", "port", "tls"]
+        );
+    }
+
     #[test]
     fn test_find_object_context_at_cursor() {
         // Document with cursor inside nested object
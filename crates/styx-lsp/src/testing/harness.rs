@@ -22,7 +22,7 @@
 //! }
 //! ```
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::process::Stdio;
 use std::sync::Arc;
 
@@ -48,10 +48,12 @@ use styx_lsp_ext::{
 pub struct TestDocument {
     /// Document URI.
     pub uri: String,
-    /// Source text (with cursor marker removed).
+    /// Source text (with cursor markers removed).
     pub source: String,
     /// Cursor position (from `|` marker).
     pub cursor: Option<CursorInfo>,
+    /// Numbered cursor positions (from `{1|}`, `{2|}`, ... markers), by number.
+    pub markers: BTreeMap<String, CursorInfo>,
 }
 
 /// Cursor position information.
@@ -66,35 +68,65 @@ impl TestDocument {
     /// Create a new test document from source.
     ///
     /// If the source contains `|`, it marks the cursor position and is removed.
+    /// Numbered markers like `{1|}` are removed too and recorded in `markers`,
+    /// so one document can be checked at several positions.
     pub fn new(uri: impl Into<String>, source: impl Into<String>) -> Self {
         let uri = uri.into();
-        let mut source = source.into();
-
-        let cursor = if let Some(pos) = source.find('|') {
-            source.remove(pos);
-
-            let before = &source[..pos];
-            let line = before.chars().filter(|&c| c == '\n').count() as u32;
-            let last_nl = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
-            let character = (pos - last_nl) as u32;
-
-            Some(CursorInfo {
-                offset: pos,
-                line,
-                character,
-            })
-        } else {
-            None
-        };
+        let source = source.into();
+
+        let mut cleaned = String::with_capacity(source.len());
+        let mut cursor = None;
+        let mut markers = BTreeMap::new();
+        let mut rest = source.as_str();
+        while let Some(c) = rest.chars().next() {
+            if let Some(name) = numbered_marker(rest) {
+                markers.insert(name.to_string(), cleaned.len());
+                rest = &rest[name.len() + "{|}".len()..];
+                continue;
+            }
+            if c == '|' && cursor.is_none() {
+                cursor = Some(cleaned.len());
+            } else {
+                cleaned.push(c);
+            }
+            rest = &rest[c.len_utf8()..];
+        }
 
         Self {
             uri,
-            source,
-            cursor,
+            cursor: cursor.map(|offset| CursorInfo::at(&cleaned, offset)),
+            markers: markers
+                .into_iter()
+                .map(|(name, offset)| (name, CursorInfo::at(&cleaned, offset)))
+                .collect(),
+            source: cleaned,
         }
     }
 }
 
+impl CursorInfo {
+    /// Cursor info for byte `offset` of `source`.
+    fn at(source: &str, offset: usize) -> Self {
+        let before = &source[..offset];
+        let line = before.chars().filter(|&c| c == '\n').count() as u32;
+        let last_nl = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let character = (offset - last_nl) as u32;
+
+        CursorInfo {
+            offset,
+            line,
+            character,
+        }
+    }
+}
+
+/// The number of a `{N|}` marker at the start of `s`.
+fn numbered_marker(s: &str) -> Option<&str> {
+    let inner = s.strip_prefix('{')?;
+    let name = &inner[..inner.find("|}")?];
+    (!name.is_empty() && name.bytes().all(|b| b.is_ascii_digit())).then_some(name)
+}
+
 /// Cursor map type - tracks cursor positions for loaded documents.
 type CursorMap = Arc<RwLock<HashMap<String, CursorInfo>>>;
 
@@ -207,6 +239,12 @@ impl TestHarness {
         docs.insert(uri, state);
     }
 
+    /// Move the cursor of a loaded document, e.g. to one of its numbered markers.
+    pub async fn set_cursor(&self, document_uri: &str, cursor: CursorInfo) {
+        let mut cursors = self.cursors.write().await;
+        cursors.insert(document_uri.to_string(), cursor);
+    }
+
     /// Get the cursor position for a document.
     async fn get_cursor(&self, document_uri: &str) -> Option<CursorInfo> {
         let cursors = self.cursors.read().await;
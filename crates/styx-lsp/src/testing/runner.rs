@@ -16,6 +16,20 @@
 //! The `^` characters mark the exact span, and the quoted string is the expected
 //! message (substring match). Span marker lines are removed from the actual input
 //! before sending to the extension.
+//!
+//! # Cursor Markers
+//!
+//! A `|` in the input marks the cursor for `completions`, `hover`, `definition`
+//! and `code_actions`. To check several positions in one document, use numbered
+//! markers and select each one with `at` in a `cursors` entry:
+//!
+//! ```text
+//! input "AllPosts @query{from {1|}, select {{2|}}}"
+//! cursors (
+//!     {at 1, completions {has (posts)}}
+//!     {at 2, completions {has (title)}}
+//! )
+//! ```
 
 use std::path::Path;
 
@@ -307,36 +321,58 @@ async fn run_test_case(harness: &TestHarness, test_case: &TestCase, index: usize
 
     let mut errors = Vec::new();
 
-    // Check completions if expected
-    if let Some(ref expected) = test_case.completions {
-        if doc.cursor.is_none() {
-            errors.push("completions test requires cursor marker (|) in input".to_string());
-        } else {
-            match harness.completions(&uri).await {
-                Ok(completions) => {
-                    check_completions(&completions, expected, &mut errors);
-                }
-                Err(e) => {
-                    errors.push(format!("completions request failed: {}", e));
-                }
-            }
+    // Check expectations at the `|` cursor
+    let at_cursor = CursorChecks {
+        completions: test_case.completions.as_ref(),
+        hover: test_case.hover.as_ref(),
+        definition: test_case.definition.as_ref(),
+        code_actions: test_case.code_actions.as_ref(),
+    };
+    if doc.cursor.is_some() {
+        check_at_cursor(
+            harness,
+            &uri,
+            &cleaned_input,
+            &name,
+            &at_cursor,
+            &mut errors,
+        )
+        .await;
+    } else {
+        for kind in at_cursor.kinds() {
+            errors.push(format!("{} test requires cursor marker (|) in input", kind));
         }
     }
 
-    // Check hover if expected
-    if let Some(ref expected) = test_case.hover {
-        if doc.cursor.is_none() {
-            errors.push("hover test requires cursor marker (|) in input".to_string());
-        } else {
-            match harness.hover(&uri).await {
-                Ok(hover) => {
-                    check_hover(&hover, expected, &mut errors);
-                }
-                Err(e) => {
-                    errors.push(format!("hover request failed: {}", e));
-                }
-            }
-        }
+    // Check expectations at named markers, moving the cursor to each in turn
+    for expected in &test_case.cursors {
+        let Some(cursor) = doc.markers.get(&expected.at) else {
+            errors.push(format!("no cursor marker {{{}|}} in input", expected.at));
+            continue;
+        };
+        harness.set_cursor(&uri, *cursor).await;
+
+        let mut marker_errors = Vec::new();
+        let checks = CursorChecks {
+            completions: expected.completions.as_ref(),
+            hover: expected.hover.as_ref(),
+            definition: expected.definition.as_ref(),
+            code_actions: expected.code_actions.as_ref(),
+        };
+        check_at_cursor(
+            harness,
+            &uri,
+            &cleaned_input,
+            &name,
+            &checks,
+            &mut marker_errors,
+        )
+        .await;
+        errors.extend(
+            marker_errors
+                .into_iter()
+                .map(|e| format!("at {{{}|}}: {}", expected.at, e)),
+        );
     }
 
     // Build diagnostic expectations from explicit config + span markers
@@ -392,48 +428,102 @@ async fn run_test_case(harness: &TestHarness, test_case: &TestCase, index: usize
         }
     }
 
+    finish_test_case(name, test_case.expect_fail, errors)
+}
+
+/// Expectations that need a cursor position.
+struct CursorChecks<'a> {
+    completions: Option<&'a CompletionExpectations>,
+    hover: Option<&'a HoverExpectations>,
+    definition: Option<&'a DefinitionExpectations>,
+    code_actions: Option<&'a CodeActionExpectations>,
+}
+
+impl CursorChecks<'_> {
+    /// Names of the checks that are set, for error messages.
+    fn kinds(&self) -> impl Iterator<Item = &'static str> {
+        [
+            self.completions.map(|_| "completions"),
+            self.hover.map(|_| "hover"),
+            self.definition.map(|_| "definition"),
+            self.code_actions.map(|_| "code_actions"),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+/// Run the cursor-based checks against the cursor currently set for `uri`.
+async fn check_at_cursor(
+    harness: &TestHarness,
+    uri: &str,
+    cleaned_input: &str,
+    name: &str,
+    checks: &CursorChecks<'_>,
+    errors: &mut Vec<String>,
+) {
+    // Check completions if expected
+    if let Some(expected) = checks.completions {
+        match harness.completions(uri).await {
+            Ok(completions) => {
+                check_completions(&completions, expected, errors);
+            }
+            Err(e) => {
+                errors.push(format!("completions request failed: {}", e));
+            }
+        }
+    }
+
+    // Check hover if expected
+    if let Some(expected) = checks.hover {
+        match harness.hover(uri).await {
+            Ok(hover) => {
+                check_hover(&hover, expected, errors);
+            }
+            Err(e) => {
+                errors.push(format!("hover request failed: {}", e));
+            }
+        }
+    }
+
     // Check definition if expected
-    if let Some(ref expected) = test_case.definition {
-        if doc.cursor.is_none() {
-            errors.push("definition test requires cursor marker (|) in input".to_string());
-        } else {
-            match harness.definition(&uri).await {
-                Ok(locations) => {
-                    check_definition(&locations, expected, &cleaned_input, &mut errors);
-                }
-                Err(e) => {
-                    errors.push(format!("definition request failed: {}", e));
-                }
+    if let Some(expected) = checks.definition {
+        match harness.definition(uri).await {
+            Ok(locations) => {
+                check_definition(&locations, expected, cleaned_input, errors);
+            }
+            Err(e) => {
+                errors.push(format!("definition request failed: {}", e));
             }
         }
     }
 
     // Check code actions if expected
-    if let Some(ref expected) = test_case.code_actions {
-        if doc.cursor.is_none() {
-            errors.push("code_actions test requires cursor marker (|) in input".to_string());
-        } else {
-            eprintln!("[DEBUG] Requesting code actions for test '{}'", name);
-            match harness.code_actions(&uri).await {
-                Ok(actions) => {
-                    eprintln!("[DEBUG] Got {} code actions", actions.len());
-                    for (i, a) in actions.iter().enumerate() {
-                        eprintln!(
-                            "  [{}] title='{}', kind={:?}, preferred={}",
-                            i, a.title, a.kind, a.is_preferred
-                        );
-                    }
-                    check_code_actions(&actions, expected, &mut errors);
-                }
-                Err(e) => {
-                    errors.push(format!("code_actions request failed: {}", e));
+    if let Some(expected) = checks.code_actions {
+        eprintln!("[DEBUG] Requesting code actions for test '{}'", name);
+        match harness.code_actions(uri).await {
+            Ok(actions) => {
+                eprintln!("[DEBUG] Got {} code actions", actions.len());
+                for (i, a) in actions.iter().enumerate() {
+                    eprintln!(
+                        "  [{}] title='{}', kind={:?}, preferred={}",
+                        i, a.title, a.kind, a.is_preferred
+                    );
                 }
+                check_code_actions(&actions, expected, errors);
+            }
+            Err(e) => {
+                errors.push(format!("code_actions request failed: {}", e));
             }
         }
     }
+}
 
-    // Handle expect_fail: if test is expected to fail, invert the result
-    let (passed, error) = if test_case.expect_fail {
+/// Turn the failed checks of a test case into its result.
+///
+/// A case marked `expect_fail` passes only if at least one check failed.
+fn finish_test_case(name: String, expect_fail: bool, errors: Vec<String>) -> TestResult {
+    let (passed, error) = if expect_fail {
         if errors.is_empty() {
             // Expected failure but passed - that's a failure
             (
@@ -1094,4 +1184,44 @@ select {id}"#;
         assert_eq!(cleaned, input); // unchanged
         assert_eq!(markers.len(), 0);
     }
+
+    #[test]
+    fn test_finish_test_case() {
+        let passed = finish_test_case("ok".into(), false, vec![]);
+        assert!(passed.passed);
+        assert_eq!(passed.error, None);
+
+        let failed = finish_test_case("bad".into(), false, vec!["a".into(), "b".into()]);
+        assert!(!failed.passed);
+        assert_eq!(failed.error.as_deref(), Some("a\nb"));
+
+        let expected = finish_test_case("xfail".into(), true, vec!["a".into()]);
+        assert!(expected.passed);
+        assert_eq!(expected.error, None);
+
+        let unexpected = finish_test_case("xpass".into(), true, vec![]);
+        assert!(!unexpected.passed);
+        assert_eq!(
+            unexpected.error.as_deref(),
+            Some("expected test to fail, but it passed")
+        );
+    }
+
+    #[test]
+    fn test_report_counts_failures() {
+        let result = TestFileResult {
+            path: "sample.styx".into(),
+            results: vec![
+                finish_test_case("ok".into(), false, vec![]),
+                finish_test_case("bad".into(), false, vec!["missing completion".into()]),
+            ],
+        };
+
+        assert!(!result.all_passed());
+        assert_eq!(result.passed_count(), 1);
+        assert_eq!(result.failed_count(), 1);
+        let report = result.report();
+        assert!(report.contains("Results: 1 passed, 1 failed"));
+        assert!(report.contains("  ✗ bad\n      missing completion\n"));
+    }
 }
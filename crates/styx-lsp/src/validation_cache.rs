@@ -0,0 +1,208 @@
+//! Incremental schema validation.
+//!
+//! Validating a whole document on every keystroke repeats the work for every
+//! root entry the edit didn't touch. [`ValidationCache`] validates a document
+//! one root entry at a time and keeps each entry's result, keyed by the schema
+//! and a hash of the entry's source text. An edit only changes the hashes of
+//! the entries it touches, so only those are validated again.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use facet_styx::{SchemaFile, ValidationResult, Validator};
+use styx_tree::{Entry, Span, Value};
+
+use crate::schema_validation::strip_schema_declaration;
+
+/// Validation results of root entries, reused across edits of a document.
+#[derive(Debug, Default)]
+pub struct ValidationCache {
+    /// Results by (schema key, entry hash), with spans relative to the start
+    /// of the entry so they stay correct when the entry moves.
+    entries: HashMap<(String, u64), ValidationResult>,
+    /// Number of root entries validated rather than taken from the cache.
+    validated_entries: usize,
+}
+
+impl ValidationCache {
+    /// Validate `doc`, parsed from `source`, against `schema`.
+    ///
+    /// `schema_key` identifies the schema's content: results cached under
+    /// another key are never reused. Produces the same result as
+    /// [`facet_styx::validate`] on the document without its `@schema`
+    /// declaration. Only results for the entries of this document are kept.
+    pub fn validate(
+        &mut self,
+        schema_key: &str,
+        schema: &SchemaFile,
+        doc: &Value,
+        source: &str,
+    ) -> ValidationResult {
+        let validator = Validator::new(schema);
+        let (Some(root_schema), Some(obj)) = (validator.root_object_schema(), doc.as_object())
+        else {
+            // Only object roots can be validated entry by entry
+            self.entries.clear();
+            return validator.validate_document(&strip_schema_declaration(doc));
+        };
+
+        let mut result = ValidationResult::ok();
+        let mut seen_fields: HashSet<Option<&str>> = HashSet::new();
+        let mut entries = HashMap::new();
+        for entry in &obj.entries {
+            if entry.key.is_schema_tag() {
+                continue;
+            }
+
+            let Some((start, text)) = entry_source(entry, source) else {
+                self.validated_entries += 1;
+                if let Some(key) =
+                    validator.validate_object_entry(entry, root_schema, "", &mut result)
+                {
+                    seen_fields.insert(key);
+                }
+                continue;
+            };
+
+            let cache_key = (schema_key.to_string(), hash_text(text));
+            let cached = match self.entries.get(&cache_key) {
+                Some(cached) => cached.clone(),
+                None => {
+                    self.validated_entries += 1;
+                    let mut entry_result = ValidationResult::ok();
+                    validator.validate_object_entry(entry, root_schema, "", &mut entry_result);
+                    shift_spans(entry_result, |offset| offset.saturating_sub(start))
+                }
+            };
+            result.merge(shift_spans(cached.clone(), |offset| offset + start));
+            entries.insert(cache_key, cached);
+
+            if entry.key.is_unit() {
+                seen_fields.insert(None);
+            } else if let Some(key) = entry.key.as_str() {
+                seen_fields.insert(Some(key));
+            }
+        }
+        self.entries = entries;
+
+        validator.check_missing_fields(
+            root_schema,
+            "",
+            doc.span,
+            |name| seen_fields.contains(&Some(name)),
+            &mut result,
+        );
+        result
+    }
+
+    /// Number of root entries validated so far rather than taken from the
+    /// cache.
+    pub fn validated_entries(&self) -> usize {
+        self.validated_entries
+    }
+}
+
+/// The offset and source text of an entry, from its key to its value.
+fn entry_source<'s>(entry: &Entry, source: &'s str) -> Option<(u32, &'s str)> {
+    let start = entry.key.span?.start;
+    let end = entry.value.span.or(entry.key.span)?.end;
+    let text = source.get(start as usize..end as usize)?;
+    Some((start, text))
+}
+
+fn hash_text(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Move every span of `result` with `shift`.
+fn shift_spans(mut result: ValidationResult, shift: impl Fn(u32) -> u32) -> ValidationResult {
+    let spans = result
+        .errors
+        .iter_mut()
+        .map(|e| &mut e.span)
+        .chain(result.warnings.iter_mut().map(|w| &mut w.span));
+    for span in spans.flatten() {
+        *span = Span {
+            start: shift(span.start),
+            end: shift(span.end),
+        };
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"meta {id test}
+schema {
+    @ @object{
+        name @string
+        port @int
+        server @object{
+            host @string
+            timeout @int
+        }
+    }
+}"#;
+
+    fn errors(result: &ValidationResult) -> Vec<(String, Option<Span>)> {
+        result
+            .errors
+            .iter()
+            .map(|e| (e.path.clone(), e.span))
+            .collect()
+    }
+
+    fn validate(cache: &mut ValidationCache, schema: &SchemaFile, source: &str) {
+        let doc = styx_tree::parse(source).unwrap();
+        let cached = cache.validate("test", schema, &doc, source);
+        let full = facet_styx::validate(&strip_schema_declaration(&doc), schema);
+        assert_eq!(errors(&cached), errors(&full), "{source}");
+    }
+
+    #[test]
+    fn test_edit_revalidates_only_the_edited_entry() {
+        let schema: SchemaFile = facet_styx::from_str(SCHEMA).unwrap();
+        let mut cache = ValidationCache::default();
+
+        validate(
+            &mut cache,
+            &schema,
+            "name app\nport oops\nserver {host localhost, timeout 5}",
+        );
+        assert_eq!(cache.validated_entries(), 3);
+
+        // Editing a field of `server` only validates the `server` entry
+        validate(
+            &mut cache,
+            &schema,
+            "name app\nport oops\nserver {host localhost, timeout five}",
+        );
+        assert_eq!(cache.validated_entries(), 4);
+
+        // Entries after an edit move, but are still taken from the cache
+        // with their errors pointing at the new offsets
+        validate(
+            &mut cache,
+            &schema,
+            "name application\nport oops\nserver {host localhost, timeout five}",
+        );
+        assert_eq!(cache.validated_entries(), 5);
+
+        // Removing an entry needs no validation, only the missing field check
+        validate(
+            &mut cache,
+            &schema,
+            "name application\nserver {host localhost, timeout five}",
+        );
+        assert_eq!(cache.validated_entries(), 5);
+
+        // A different schema never reuses results
+        let doc = styx_tree::parse("name application").unwrap();
+        cache.validate("other", &schema, &doc, "name application");
+        assert_eq!(cache.validated_entries(), 6);
+    }
+}
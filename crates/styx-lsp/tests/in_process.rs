@@ -10,6 +10,7 @@ use tower_lsp::LspService;
 use tower_lsp::jsonrpc::Request;
 
 use styx_lsp::StyxLanguageServer;
+use styx_testhelpers::TempDir;
 
 /// Regression test: ensure CST catches "too many atoms" errors
 #[test]
@@ -350,3 +351,136 @@ async fn test_no_diagnostics_for_valid_document() {
         "Valid document should have no diagnostics"
     );
 }
+
+/// Wait for the next diagnostics published for `uri` and return their messages.
+async fn next_diagnostic_messages(
+    rx: &mut tokio::sync::mpsc::Receiver<Request>,
+    uri: &str,
+) -> Option<Vec<String>> {
+    let timeout = tokio::time::sleep(tokio::time::Duration::from_secs(5));
+    tokio::pin!(timeout);
+
+    loop {
+        tokio::select! {
+            Some(notification) = rx.recv() => {
+                if notification.method() == "textDocument/publishDiagnostics"
+                    && let Some(params) = notification.params()
+                    && params.get("uri").and_then(|u| u.as_str()) == Some(uri)
+                {
+                    let diagnostics = params.get("diagnostics")?.as_array()?;
+                    return Some(
+                        diagnostics
+                            .iter()
+                            .filter_map(|d| d.get("message")?.as_str().map(String::from))
+                            .collect(),
+                    );
+                }
+            }
+            _ = &mut timeout => return None,
+        }
+    }
+}
+
+/// Allowing a blocked extension from the editor persists the decision and
+/// clears the blocked-extension diagnostic; denying it keeps it cleared.
+#[tokio::test]
+async fn test_allow_and_deny_extension_commands() {
+    use futures::SinkExt;
+
+    let dir = TempDir::new("lsp-allowlist");
+    dir.write(
+        "schema.styx",
+        "meta {id test-ext, lsp {launch (styx-test-missing-extension)}}\nschema {@ @object{name @string}}\n",
+    );
+    let config_path = dir.path().join("config.styx");
+
+    let (mut service, socket) = LspService::new(|client| {
+        StyxLanguageServer::with_config_path(client, Some(config_path.clone()))
+    });
+    let (mut from_server, mut to_server) = socket.split();
+
+    // Forward notifications, and answer server requests (e.g. inlay hint refresh)
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<Request>(32);
+    let drain_task = tokio::spawn(async move {
+        while let Some(message) = from_server.next().await {
+            if let Some(id) = message.id().cloned() {
+                let _ = to_server
+                    .send(tower_lsp::jsonrpc::Response::from_ok(id, Value::Null))
+                    .await;
+            } else {
+                let _ = tx.send(message).await;
+            }
+        }
+    });
+
+    let _ = service
+        .call(make_request(
+            1,
+            "initialize",
+            json!({"processId": null, "capabilities": {}, "rootUri": null}),
+        ))
+        .await;
+    let _ = service
+        .call(make_notification("initialized", json!({})))
+        .await;
+
+    let uri = format!("file://{}", dir.path().join("doc.styx").display());
+    let _ = service
+        .call(make_notification(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": uri,
+                    "languageId": "styx",
+                    "version": 1,
+                    "text": "@schema schema.styx\n\nname hello\n"
+                }
+            }),
+        ))
+        .await;
+
+    let blocked = |messages: &[String]| messages.iter().any(|m| m.contains("is not allowed"));
+    let messages = next_diagnostic_messages(&mut rx, &uri).await.unwrap();
+    assert!(
+        blocked(&messages),
+        "extension should start blocked: {messages:?}"
+    );
+
+    let execute = |id, command: &str| {
+        make_request(
+            id,
+            "workspace/executeCommand",
+            json!({
+                "command": command,
+                "arguments": [{"command": "styx-test-missing-extension"}]
+            }),
+        )
+    };
+
+    let load_config = || {
+        styx_lsp::config::load_config_from(&config_path)
+            .unwrap()
+            .unwrap()
+    };
+    let command = vec!["styx-test-missing-extension".to_string()];
+
+    let _ = service.call(execute(2, "styx.allowExtension")).await;
+    let messages = next_diagnostic_messages(&mut rx, &uri).await.unwrap();
+    assert!(
+        !blocked(&messages),
+        "allowed extension is still blocked: {messages:?}"
+    );
+    assert_eq!(load_config().allowed_extensions, command);
+
+    let _ = service.call(execute(3, "styx.denyExtension")).await;
+    let messages = next_diagnostic_messages(&mut rx, &uri).await.unwrap();
+    assert!(
+        !blocked(&messages),
+        "denied extension still prompts: {messages:?}"
+    );
+    let config = load_config();
+    assert!(config.allowed_extensions.is_empty());
+    assert_eq!(config.denied_extensions, command);
+
+    drain_task.abort();
+}
@@ -0,0 +1,94 @@
+//! Tests for the `.styx` test file runner.
+
+use std::path::PathBuf;
+
+use styx_lsp::testing::TestDocument;
+use styx_lsp_test_schema::TestFile;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn sample_path() -> PathBuf {
+    fixture("runner_sample.styx")
+}
+
+/// The stand-in extension built from `src/bin/fake_extension.rs`.
+const FAKE_EXTENSION: &str = env!("CARGO_BIN_EXE_styx-lsp-fake-extension");
+
+/// The sample file must stay loadable, or the runner reports a parse error
+/// instead of results.
+#[test]
+fn test_sample_file_parses() {
+    let content = std::fs::read_to_string(sample_path()).unwrap();
+    let file: TestFile = facet_styx::from_str(&content).unwrap();
+
+    assert_eq!(file.tests.len(), 3);
+    let expect_fail: Vec<bool> = file.tests.iter().map(|t| t.expect_fail).collect();
+    assert_eq!(expect_fail, [false, false, true]);
+    assert!(file.tests.iter().all(|t| t.input.contains('|')));
+}
+
+#[tokio::test]
+async fn test_runner_reports_pass_and_fail() {
+    let result = styx_lsp::testing::run_test_file(
+        FAKE_EXTENSION,
+        &[],
+        sample_path(),
+        "crate:test-queries@1",
+    )
+    .await
+    .unwrap();
+
+    let passed: Vec<bool> = result.results.iter().map(|r| r.passed).collect();
+    assert_eq!(passed, [true, false, true], "{}", result.report());
+    assert_eq!(result.passed_count(), 2);
+    assert_eq!(result.failed_count(), 1);
+    assert!(
+        result.results[1]
+            .error
+            .as_deref()
+            .is_some_and(|e| e.contains("no_such_column")),
+        "{}",
+        result.report()
+    );
+}
+
+#[test]
+fn test_multi_cursor_file_markers() {
+    let content = std::fs::read_to_string(fixture("multi_cursor.styx")).unwrap();
+    let file: TestFile = facet_styx::from_str(&content).unwrap();
+    let case = &file.tests[0];
+
+    let at: Vec<&str> = case.cursors.iter().map(|c| c.at.as_str()).collect();
+    assert_eq!(at, ["1", "2"]);
+
+    let doc = TestDocument::new("file:///test.styx", case.input.as_str());
+    assert!(doc.cursor.is_none());
+    assert!(!doc.source.contains('|'));
+    let lines: Vec<&str> = doc.source.lines().collect();
+    assert_eq!(lines[2], "    from ");
+    assert_eq!(lines[3], "    select {}");
+
+    let first = doc.markers["1"];
+    assert_eq!((first.line, first.character), (2, 9));
+    let second = doc.markers["2"];
+    assert_eq!((second.line, second.character), (3, 12));
+    assert_eq!(&doc.source[second.offset - 1..=second.offset], "{}");
+}
+
+#[tokio::test]
+async fn test_runner_checks_each_marker() {
+    let result = styx_lsp::testing::run_test_file(
+        FAKE_EXTENSION,
+        &[],
+        fixture("multi_cursor.styx"),
+        "crate:test-queries@1",
+    )
+    .await
+    .unwrap();
+
+    assert!(result.all_passed(), "{}", result.report());
+}
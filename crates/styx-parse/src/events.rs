@@ -1,6 +1,9 @@
 //! Event types for the Styx event-based parser.
 
-use std::borrow::Cow;
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
 
 use styx_tokenizer::Span;
 
@@ -72,6 +75,15 @@ pub enum EventKind<'src> {
     /// End of a tag.
     TagEnd,
 
+    // Anchors
+    /// Anchor definition `&name` on an entry, before its value.
+    ///
+    /// Only emitted when [`Parser::anchors`](crate::Parser::anchors) is enabled.
+    Anchor {
+        /// Anchor name (without &).
+        name: Cow<'src, str>,
+    },
+
     // Comments
     /// Line comment `// ...`.
     Comment {
@@ -107,6 +119,44 @@ pub enum ScalarKind {
     Heredoc,
 }
 
+/// A tag name split into its namespace and local name.
+///
+/// Namespaced tags like `@sql:Query` are only accepted when
+/// [`Parser::namespaced_tags`](crate::Parser::namespaced_tags) is enabled;
+/// otherwise every tag name has no namespace.
+///
+/// ```
+/// use styx_parse::TagName;
+///
+/// let tag = TagName::split("sql:Query");
+/// assert_eq!(tag.namespace, Some("sql"));
+/// assert_eq!(tag.name, "Query");
+/// assert_eq!(TagName::split("string").namespace, None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagName<'a> {
+    /// Namespace before the `:`, if any.
+    pub namespace: Option<&'a str>,
+    /// Name within the namespace.
+    pub name: &'a str,
+}
+
+impl<'a> TagName<'a> {
+    /// Split a tag name (without `@`) at its first `:`.
+    pub fn split(tag: &'a str) -> Self {
+        match tag.split_once(':') {
+            Some((namespace, name)) => Self {
+                namespace: Some(namespace),
+                name,
+            },
+            None => Self {
+                namespace: None,
+                name: tag,
+            },
+        }
+    }
+}
+
 /// Parse error kinds.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParseErrorKind {
@@ -154,10 +204,43 @@ pub enum ParseErrorKind {
     MissingWhitespaceBeforeBlock,
     /// Trailing content after explicit root object.
     TrailingContent,
+    /// Object separates entries with both commas and newlines.
+    ///
+    /// Only reported when strict separators are enabled, see
+    /// [`Parser::strict_separators`](crate::Parser::strict_separators).
+    // parser[impl object.separators.strict]
+    MixedSeparators,
+}
+
+impl ParseErrorKind {
+    /// Stable kebab-case identifier for this kind of error, e.g.
+    /// `duplicate-key`, for tools that match on diagnostics.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseErrorKind::UnexpectedToken => "unexpected-token",
+            ParseErrorKind::UnclosedObject => "unclosed-object",
+            ParseErrorKind::UnclosedSequence => "unclosed-sequence",
+            ParseErrorKind::InvalidEscape(_) => "invalid-escape",
+            ParseErrorKind::ExpectedKey => "expected-key",
+            ParseErrorKind::ExpectedValue => "expected-value",
+            ParseErrorKind::UnexpectedEof => "unexpected-eof",
+            ParseErrorKind::DuplicateKey { .. } => "duplicate-key",
+            ParseErrorKind::InvalidTagName => "invalid-tag-name",
+            ParseErrorKind::InvalidKey => "invalid-key",
+            ParseErrorKind::DanglingDocComment => "dangling-doc-comment",
+            ParseErrorKind::TooManyAtoms => "too-many-atoms",
+            ParseErrorKind::ReopenedPath { .. } => "reopened-path",
+            ParseErrorKind::NestIntoTerminal { .. } => "nest-into-terminal",
+            ParseErrorKind::CommaInSequence => "comma-in-sequence",
+            ParseErrorKind::MissingWhitespaceBeforeBlock => "missing-whitespace",
+            ParseErrorKind::TrailingContent => "trailing-content",
+            ParseErrorKind::MixedSeparators => "mixed-separators",
+        }
+    }
 }
 
-impl std::fmt::Display for ParseErrorKind {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ParseErrorKind::UnexpectedToken => write!(f, "unexpected token"),
             ParseErrorKind::UnclosedObject => write!(f, "unclosed object (missing `}}`)"),
@@ -204,6 +287,12 @@ impl std::fmt::Display for ParseErrorKind {
             ParseErrorKind::TrailingContent => {
                 write!(f, "trailing content after explicit root object")
             }
+            ParseErrorKind::MixedSeparators => {
+                write!(
+                    f,
+                    "mixed separators in object (use either commas or newlines)"
+                )
+            }
         }
     }
 }
@@ -5,7 +5,8 @@
 //! - Lexer → Lexeme (atoms: Scalar, Tag, Unit, structural markers)
 //! - Parser → Events (structure: entries, objects, sequences)
 
-use std::borrow::Cow;
+use alloc::borrow::Cow;
+use alloc::string::String;
 
 use styx_tokenizer::{Span, Token, TokenKind, Tokenizer};
 
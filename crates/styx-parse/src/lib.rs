@@ -1,9 +1,12 @@
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 pub use styx_tokenizer::{Span, Token, TokenKind, Tokenizer};
 
 mod events;
-pub use events::{Event, EventKind, ParseErrorKind, ScalarKind};
+pub use events::{Event, EventKind, ParseErrorKind, ScalarKind, TagName};
 
 mod lexer;
 pub use lexer::{Lexeme, Lexer};
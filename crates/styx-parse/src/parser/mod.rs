@@ -1,7 +1,15 @@
 //! Pull-based event parser for Styx.
 
-use std::borrow::Cow;
-use std::collections::{HashMap, VecDeque};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as HashMap;
+use alloc::collections::VecDeque;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
 
 use styx_tokenizer::Span;
 use tracing::trace;
@@ -45,13 +53,18 @@ impl<'src> LexemeSource<'src> {
 pub struct Parser<'src> {
     input: &'src str,
     source: LexemeSource<'src>,
-    state: ParserState,
+    state: ParserState<'src>,
     event_queue: VecDeque<Event<'src>>,
+    strict_separators: bool,
+    trailing_commas: bool,
+    anchors: bool,
+    namespaced_tags: bool,
+    trailing_entries: bool,
 }
 
 /// Parser state machine states.
 #[derive(Clone)]
-enum ParserState {
+enum ParserState<'src> {
     /// Haven't emitted DocumentStart yet.
     BeforeDocument,
 
@@ -60,20 +73,24 @@ enum ParserState {
 
     /// At implicit document root.
     DocumentRoot {
-        seen_keys: HashMap<KeyValue, Span>,
+        seen_keys: HashMap<KeyValue<'src>, Span>,
+        /// Keys of the explicit root object that trailing entries follow.
+        root_object_keys: HashMap<KeyValue<'src>, Span>,
         pending_doc_comment: Option<Span>,
         path_state: PathState,
         /// Whether we've emitted ObjectStart for the implicit root object.
         emitted_object_start: bool,
+        separators: SeparatorCheck,
     },
 
     /// Inside explicit object { ... }.
     InObject {
         start_span: Span,
-        seen_keys: HashMap<KeyValue, Span>,
+        seen_keys: HashMap<KeyValue<'src>, Span>,
         pending_doc_comment: Option<Span>,
+        separators: SeparatorCheck,
         /// Parent state to restore when we pop.
-        parent: Box<ParserState>,
+        parent: Box<ParserState<'src>>,
     },
 
     /// Document ended.
@@ -91,6 +108,11 @@ impl<'src> Parser<'src> {
             source: LexemeSource::new(source),
             state: ParserState::BeforeDocument,
             event_queue: VecDeque::new(),
+            strict_separators: false,
+            trailing_commas: false,
+            anchors: false,
+            namespaced_tags: false,
+            trailing_entries: false,
         }
     }
 
@@ -104,9 +126,164 @@ impl<'src> Parser<'src> {
             source: LexemeSource::new(source),
             state: ParserState::BeforeExpression,
             event_queue: VecDeque::new(),
+            strict_separators: false,
+            trailing_commas: false,
+            anchors: false,
+            namespaced_tags: false,
+            trailing_entries: false,
         }
     }
 
+    /// Reject objects that separate their entries with both commas and newlines.
+    ///
+    /// Off by default: `{a 1, b 2\n c 3}` is valid Styx. When enabled, the
+    /// first separator that differs from the one between an object's first two
+    /// entries is reported as [`ParseErrorKind::MixedSeparators`]. The check
+    /// applies the same way to the implicit root, an explicit root `{...}`, and
+    /// nested objects; each object picks its own style.
+    ///
+    /// ```
+    /// use styx_parse::{EventKind, ParseErrorKind, Parser};
+    ///
+    /// let has_error = |parser: Parser| {
+    ///     parser.parse_to_vec().iter().any(|event| {
+    ///         matches!(
+    ///             event.kind,
+    ///             EventKind::Error { kind: ParseErrorKind::MixedSeparators }
+    ///         )
+    ///     })
+    /// };
+    ///
+    /// let source = "a 1, b 2\nc 3";
+    /// assert!(!has_error(Parser::new(source)));
+    /// assert!(has_error(Parser::new(source).strict_separators(true)));
+    /// ```
+    // parser[impl object.separators.strict]
+    pub fn strict_separators(mut self, strict: bool) -> Self {
+        self.strict_separators = strict;
+        self
+    }
+
+    /// Accept a single trailing comma before a sequence's closing `)`.
+    ///
+    /// Off by default, where every comma in a sequence is reported as
+    /// [`ParseErrorKind::CommaInSequence`]. When enabled, `(1 2 3,)` parses
+    /// without errors, but leading and doubled commas such as `(,1)` or
+    /// `(1,,2)` are still reported. Objects accept a trailing comma in either
+    /// mode.
+    ///
+    /// ```
+    /// use styx_parse::{EventKind, Parser};
+    ///
+    /// let has_errors = |parser: Parser| {
+    ///     parser
+    ///         .parse_to_vec()
+    ///         .iter()
+    ///         .any(|event| matches!(event.kind, EventKind::Error { .. }))
+    /// };
+    ///
+    /// assert!(has_errors(Parser::new("ports (80 443,)")));
+    /// assert!(!has_errors(Parser::new("ports (80 443,)").trailing_commas(true)));
+    /// assert!(has_errors(Parser::new("ports (80,,443)").trailing_commas(true)));
+    /// ```
+    // parser[impl sequence.trailing-comma]
+    pub fn trailing_commas(mut self, allow: bool) -> Self {
+        self.trailing_commas = allow;
+        self
+    }
+
+    /// Recognize anchor definitions written as `key &name value`.
+    ///
+    /// Off by default, where the `&name` atom makes the entry report
+    /// [`ParseErrorKind::TooManyAtoms`]. When enabled, an entry with a bare
+    /// `&name` between its key and value emits [`EventKind::Anchor`] before the
+    /// value. The parser does not resolve `*name` aliases: they stay bare
+    /// scalars for the tree builder to expand.
+    ///
+    /// ```
+    /// use styx_parse::{EventKind, Parser};
+    ///
+    /// let source = "base &defaults {port 8080}";
+    /// let anchor = |parser: Parser| {
+    ///     parser.parse_to_vec().into_iter().find_map(|event| match event.kind {
+    ///         EventKind::Anchor { name } => Some(name.into_owned()),
+    ///         _ => None,
+    ///     })
+    /// };
+    ///
+    /// assert_eq!(anchor(Parser::new(source)), None);
+    /// assert_eq!(anchor(Parser::new(source).anchors(true)).as_deref(), Some("defaults"));
+    /// ```
+    pub fn anchors(mut self, enabled: bool) -> Self {
+        self.anchors = enabled;
+        self
+    }
+
+    /// Accept tag names with a namespace, like `@sql:Query`.
+    ///
+    /// Off by default, where a `:` in a tag name is reported as
+    /// [`ParseErrorKind::InvalidTagName`]. When enabled, a tag name may have a
+    /// single `:` separating a namespace from the name; both parts follow the
+    /// usual tag name rules. Use [`TagName::split`](crate::TagName::split) to
+    /// take the name of a [`EventKind::TagStart`] apart.
+    ///
+    /// ```
+    /// use styx_parse::{EventKind, ParseErrorKind, Parser, TagName};
+    ///
+    /// let source = "query @sql:Query{text hi}";
+    /// let events = |parser: Parser<'static>| parser.parse_to_vec().into_iter();
+    ///
+    /// assert!(events(Parser::new(source)).any(|event| matches!(
+    ///     event.kind,
+    ///     EventKind::Error { kind: ParseErrorKind::InvalidTagName }
+    /// )));
+    /// let tag = events(Parser::new(source).namespaced_tags(true))
+    ///     .find_map(|event| match event.kind {
+    ///         EventKind::TagStart { name } => Some(TagName::split(name)),
+    ///         _ => None,
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!((tag.namespace, tag.name), (Some("sql"), "Query"));
+    /// ```
+    pub fn namespaced_tags(mut self, enabled: bool) -> Self {
+        self.namespaced_tags = enabled;
+        self
+    }
+
+    /// Read entries after an explicit root object as more root entries.
+    ///
+    /// Off by default, where anything but comments after the closing `}` of
+    /// an explicit root object is reported as
+    /// [`ParseErrorKind::TrailingContent`]. When enabled, the root object
+    /// stays open at its `}`: the entries after it are parsed as they would
+    /// be at an implicit root, and the root's [`EventKind::ObjectEnd`] comes
+    /// at the end of the document. A key defined both inside and after the
+    /// braces is a duplicate.
+    ///
+    /// ```
+    /// use styx_parse::{EventKind, Parser};
+    ///
+    /// let source = "{host localhost}\nport 8080";
+    /// let keys = |parser: Parser<'static>| {
+    ///     parser
+    ///         .parse_to_vec()
+    ///         .into_iter()
+    ///         .filter_map(|event| match event.kind {
+    ///             EventKind::Key { payload, .. } => payload,
+    ///             EventKind::Error { .. } => Some("error".into()),
+    ///             _ => None,
+    ///         })
+    ///         .collect::<Vec<_>>()
+    /// };
+    ///
+    /// assert_eq!(keys(Parser::new(source)), ["host", "error"]);
+    /// assert_eq!(keys(Parser::new(source).trailing_entries(true)), ["host", "port"]);
+    /// ```
+    pub fn trailing_entries(mut self, enabled: bool) -> Self {
+        self.trailing_entries = enabled;
+        self
+    }
+
     /// Get the next event from the parser.
     pub fn next_event(&mut self) -> Option<Event<'src>> {
         trace!(
@@ -140,9 +317,11 @@ impl<'src> Parser<'src> {
             ParserState::BeforeDocument => {
                 self.state = ParserState::DocumentRoot {
                     seen_keys: HashMap::new(),
+                    root_object_keys: HashMap::new(),
                     pending_doc_comment: None,
                     path_state: PathState::default(),
                     emitted_object_start: false,
+                    separators: SeparatorCheck::default(),
                 };
                 Some(Event {
                     span: Span::empty(0),
@@ -172,6 +351,7 @@ impl<'src> Parser<'src> {
                     // Parse a single atom as the value
                     let atom = self.parse_atom(lexeme);
                     self.emit_atom_as_value(&atom);
+                    self.order_errors_since(0);
                     self.state = ParserState::AfterExpression;
                     return self.event_queue.pop_front();
                 }
@@ -245,7 +425,14 @@ impl<'src> Parser<'src> {
                     self.state = ParserState::AfterDocument;
                     return self.event_queue.pop_front();
                 }
-                Lexeme::Newline { .. } | Lexeme::Comma { .. } => continue,
+                Lexeme::Newline { span } => {
+                    self.note_separator(Separator::Newline, span);
+                }
+                Lexeme::Comma { span } => {
+                    if let Some(error) = self.note_separator(Separator::Comma, span) {
+                        return Some(error);
+                    }
+                }
                 Lexeme::Comment { span, text } => {
                     return Some(Event {
                         span,
@@ -286,6 +473,7 @@ impl<'src> Parser<'src> {
                         start_span: span,
                         seen_keys: HashMap::new(),
                         pending_doc_comment: None,
+                        separators: SeparatorCheck::default(),
                         parent: Box::new(ParserState::AfterDocument),
                     };
                     return Some(Event {
@@ -310,10 +498,13 @@ impl<'src> Parser<'src> {
                             });
                         }
                     }
+                    let entry_events = self.event_queue.len();
+                    self.check_separator_before_entry();
                     let atoms = self.collect_entry_atoms(lexeme);
                     if !atoms.is_empty() {
                         self.emit_entry_at_root(&atoms);
                     }
+                    self.order_errors_since(entry_events);
                     return self.event_queue.pop_front();
                 }
             }
@@ -369,6 +560,34 @@ impl<'src> Parser<'src> {
                     return self.event_queue.pop_front();
                 }
                 Lexeme::ObjectEnd { span } => {
+                    if self.trailing_entries
+                        && let ParserState::InObject {
+                            seen_keys,
+                            pending_doc_comment,
+                            parent,
+                            ..
+                        } = &mut self.state
+                        && matches!(parent.as_ref(), ParserState::AfterDocument)
+                    {
+                        if let Some(doc_span) = pending_doc_comment.take() {
+                            self.event_queue.push_back(Event {
+                                span: doc_span,
+                                kind: EventKind::Error {
+                                    kind: ParseErrorKind::DanglingDocComment,
+                                },
+                            });
+                        }
+                        // Keep the root object open for the entries after it
+                        self.state = ParserState::DocumentRoot {
+                            seen_keys: HashMap::new(),
+                            root_object_keys: core::mem::take(seen_keys),
+                            pending_doc_comment: None,
+                            path_state: PathState::default(),
+                            emitted_object_start: true,
+                            separators: SeparatorCheck::default(),
+                        };
+                        return self.advance_document_root();
+                    }
                     if let ParserState::InObject {
                         pending_doc_comment,
                         parent,
@@ -398,7 +617,14 @@ impl<'src> Parser<'src> {
                         kind: EventKind::ObjectEnd,
                     });
                 }
-                Lexeme::Newline { .. } | Lexeme::Comma { .. } => continue,
+                Lexeme::Newline { span } => {
+                    self.note_separator(Separator::Newline, span);
+                }
+                Lexeme::Comma { span } => {
+                    if let Some(error) = self.note_separator(Separator::Comma, span) {
+                        return Some(error);
+                    }
+                }
                 Lexeme::Comment { span, text } => {
                     return Some(Event {
                         span,
@@ -431,21 +657,81 @@ impl<'src> Parser<'src> {
                     {
                         *pending_doc_comment = None;
                     }
+                    let entry_events = self.event_queue.len();
+                    self.check_separator_before_entry();
                     let atoms = self.collect_entry_atoms(lexeme);
                     if !atoms.is_empty() {
                         self.emit_entry_in_object(&atoms);
                     }
+                    self.order_errors_since(entry_events);
                     return self.event_queue.pop_front();
                 }
             }
         }
     }
 
+    /// Record a separator between entries of the current root-level object.
+    ///
+    /// Returns an error for a comma that doesn't follow an entry, such as the
+    /// second comma of `a 1,, b 2`.
+    fn note_separator(&mut self, separator: Separator, span: Span) -> Option<Event<'src>> {
+        if let ParserState::DocumentRoot { separators, .. }
+        | ParserState::InObject { separators, .. } = &mut self.state
+            && separators.separator(separator, span)
+        {
+            return Some(Event {
+                span,
+                kind: EventKind::Error {
+                    kind: ParseErrorKind::UnexpectedToken,
+                },
+            });
+        }
+        None
+    }
+
+    /// Note the start of an entry and, in strict mode, report a separator
+    /// that makes the current object mixed.
+    fn check_separator_before_entry(&mut self) {
+        if let ParserState::DocumentRoot { separators, .. }
+        | ParserState::InObject { separators, .. } = &mut self.state
+            && let Some(span) = separators.entry()
+            && self.strict_separators
+        {
+            self.event_queue.push_back(Event {
+                span,
+                kind: EventKind::Error {
+                    kind: ParseErrorKind::MixedSeparators,
+                },
+            });
+        }
+    }
+
+    /// Put the errors queued from index `first` onwards in source order.
+    ///
+    /// An entry's errors are found in two passes: while its atoms are
+    /// collected (commas in sequences, missing whitespace) and while they are
+    /// emitted (bad tags, extra atoms), so they would otherwise come out of
+    /// order. Only the errors move; every other event keeps its slot.
+    fn order_errors_since(&mut self, first: usize) {
+        let slots: Vec<usize> = (first..self.event_queue.len())
+            .filter(|&i| matches!(self.event_queue[i].kind, EventKind::Error { .. }))
+            .collect();
+        if slots.len() < 2 {
+            return;
+        }
+        let mut errors: Vec<Event<'src>> =
+            slots.iter().map(|&i| self.event_queue[i].clone()).collect();
+        errors.sort_by_key(|error| error.span.start);
+        for (slot, error) in slots.into_iter().zip(errors) {
+            self.event_queue[slot] = error;
+        }
+    }
+
     /// Pop the current state and restore parent.
     fn pop_state(&mut self) {
         let parent = match &mut self.state {
             ParserState::InObject { parent, .. } => {
-                std::mem::replace(parent.as_mut(), ParserState::AfterDocument)
+                core::mem::replace(parent.as_mut(), ParserState::AfterDocument)
             }
             _ => ParserState::AfterDocument,
         };
@@ -491,20 +777,26 @@ impl<'src> Parser<'src> {
         // (path_state handles both simple and dotted paths uniformly)
         let key_value = KeyValue::from_atom(key_atom);
 
-        if let ParserState::DocumentRoot { path_state, .. } = &mut self.state {
+        let anchor_offset = self.anchor_name(atoms).map_or(0, |_| 1);
+        if let ParserState::DocumentRoot {
+            path_state,
+            root_object_keys,
+            ..
+        } = &mut self.state
+        {
+            if let Some(&original) = root_object_keys.get(&key_value) {
+                self.event_queue.push_back(Event {
+                    span: key_atom.span,
+                    kind: EventKind::Error {
+                        kind: ParseErrorKind::DuplicateKey { original },
+                    },
+                });
+            }
+
             // Check path state - this handles duplicates for us
-            let key_text = key_value.to_key_string();
-            let path = vec![key_text];
-            let value_kind = if atoms.len() >= 2 {
-                match &atoms[1].content {
-                    AtomContent::Object { .. } | AtomContent::Attributes(_) => {
-                        PathValueKind::Object
-                    }
-                    _ => PathValueKind::Terminal,
-                }
-            } else {
-                PathValueKind::Terminal
-            };
+            let key_text = key_value.key_text();
+            let path = [key_text.as_ref()];
+            let value_kind = entry_value_kind(&atoms[anchor_offset..]);
 
             if let Err(err) = path_state.check_and_update(&path, key_atom.span, value_kind) {
                 self.emit_path_error(err, key_atom.span);
@@ -577,29 +869,67 @@ impl<'src> Parser<'src> {
             kind: EventKind::EntryStart,
         });
         self.emit_atom_as_key(key_atom);
+        self.emit_entry_value(atoms, key_atom.span);
+
+        self.event_queue.push_back(Event {
+            span: atoms.last().map(|a| a.span).unwrap_or(key_atom.span),
+            kind: EventKind::EntryEnd,
+        });
+    }
 
-        if atoms.len() == 1 {
+    /// Emit the value of an entry whose key is `atoms[0]`, preceded by its
+    /// anchor if it defines one. A missing value is a unit spanning `key_span`.
+    fn emit_entry_value(&mut self, atoms: &[Atom<'src>], key_span: Span) {
+        let mut value_index = 1;
+        if let Some(name) = self.anchor_name(atoms) {
             self.event_queue.push_back(Event {
-                span: key_atom.span,
-                kind: EventKind::Unit,
+                span: atoms[1].span,
+                kind: EventKind::Anchor { name },
             });
-        } else if atoms.len() >= 2 {
-            self.emit_atom_as_value(&atoms[1]);
+            value_index = 2;
         }
 
-        if atoms.len() > 2 {
+        match atoms.get(value_index) {
+            Some(value_atom) => self.emit_atom_as_value(value_atom),
+            None => self.event_queue.push_back(Event {
+                span: key_span,
+                kind: EventKind::Unit,
+            }),
+        }
+
+        if let Some(extra) = atoms.get(value_index + 1) {
             self.event_queue.push_back(Event {
-                span: atoms[2].span,
+                span: extra.span,
                 kind: EventKind::Error {
                     kind: ParseErrorKind::TooManyAtoms,
                 },
             });
         }
+    }
 
-        self.event_queue.push_back(Event {
-            span: atoms.last().map(|a| a.span).unwrap_or(key_atom.span),
-            kind: EventKind::EntryEnd,
-        });
+    /// The anchor defined by `key &name value`, when anchors are enabled.
+    ///
+    /// An entry with only two atoms is left alone, so `key &name` keeps
+    /// `&name` as its scalar value.
+    fn anchor_name(&self, atoms: &[Atom<'src>]) -> Option<Cow<'src, str>> {
+        if !self.anchors || atoms.len() < 3 {
+            return None;
+        }
+        let AtomContent::Scalar {
+            value,
+            kind: ScalarKind::Bare,
+        } = &atoms[1].content
+        else {
+            return None;
+        };
+        let name = value.strip_prefix('&')?;
+        if !is_valid_tag_name(name, false) {
+            return None;
+        }
+        Some(match value {
+            Cow::Borrowed(text) => Cow::Borrowed(&text[1..]),
+            Cow::Owned(_) => Cow::Owned(name.to_string()),
+        })
     }
 
     /// Collect atoms for an entry.
@@ -627,11 +957,9 @@ impl<'src> Parser<'src> {
                     self.source.stash(lexeme);
                     break;
                 }
-                Lexeme::Comment { span, text } => {
-                    self.event_queue.push_back(Event {
-                        span,
-                        kind: EventKind::Comment { text },
-                    });
+                Lexeme::Comment { .. } => {
+                    // Let the caller emit it after this entry's events
+                    self.source.stash(lexeme);
                     break;
                 }
                 Lexeme::DocComment { span, text } => {
@@ -745,7 +1073,7 @@ impl<'src> Parser<'src> {
                     self.source.stash(next);
                 }
 
-                let invalid_name = !is_valid_tag_name(name);
+                let invalid_name = !is_valid_tag_name(name, self.namespaced_tags);
                 let payload = if has_payload {
                     let next = self.source.next();
                     Some(Box::new(self.parse_atom(next)))
@@ -819,10 +1147,14 @@ impl<'src> Parser<'src> {
     /// Parse an object atom.
     fn parse_object_atom(&mut self, start_span: Span) -> Atom<'src> {
         let mut entries: Vec<ObjectEntry<'src>> = Vec::new();
-        let mut seen_keys: HashMap<KeyValue, Span> = HashMap::new();
+        let mut seen_keys: HashMap<KeyValue<'src>, Span> = HashMap::new();
         let mut duplicate_key_spans: Vec<(Span, Span)> = Vec::new();
         let mut dangling_doc_comment_spans: Vec<Span> = Vec::new();
         let mut pending_doc_comments: Vec<(Span, &'src str)> = Vec::new();
+        let mut pending_comments: Vec<(Span, &'src str)> = Vec::new();
+        let mut separators = SeparatorCheck::default();
+        let mut mixed_separator_span = None;
+        let mut stray_comma_spans: Vec<Span> = Vec::new();
         let mut unclosed = false;
         let mut end_span = start_span;
 
@@ -843,12 +1175,26 @@ impl<'src> Parser<'src> {
                     end_span = span;
                     break;
                 }
-                Lexeme::Newline { .. } | Lexeme::Comma { .. } => continue,
-                Lexeme::Comment { .. } => continue,
+                Lexeme::Newline { span } => {
+                    separators.separator(Separator::Newline, span);
+                }
+                Lexeme::Comma { span } => {
+                    if separators.separator(Separator::Comma, span) {
+                        stray_comma_spans.push(span);
+                    }
+                }
+                Lexeme::Comment { span, text } => {
+                    pending_comments.push((span, text));
+                }
                 Lexeme::DocComment { span, text } => {
                     pending_doc_comments.push((span, text));
                 }
                 _ => {
+                    if let Some(span) = separators.entry()
+                        && self.strict_separators
+                    {
+                        mixed_separator_span = Some(span);
+                    }
                     let doc_comment = if pending_doc_comments.is_empty() {
                         None
                     } else {
@@ -868,10 +1214,11 @@ impl<'src> Parser<'src> {
                         pending_doc_comments.clear();
                         Some((combined_span, lines))
                     };
-                    let entry_atoms = self.collect_entry_atoms(lexeme);
+                    // Move atoms out rather than cloning: values can be whole
+                    // nested objects, and cloning them at every level is quadratic.
+                    let mut entry_atoms = self.collect_entry_atoms(lexeme).into_iter();
 
-                    if !entry_atoms.is_empty() {
-                        let key = entry_atoms[0].clone();
+                    if let Some(key) = entry_atoms.next() {
                         let key_value = KeyValue::from_atom(&key);
 
                         if let Some(&original_span) = seen_keys.get(&key_value) {
@@ -880,24 +1227,17 @@ impl<'src> Parser<'src> {
                             seen_keys.insert(key_value, key.span);
                         }
 
-                        let (value, too_many_atoms_span) = if entry_atoms.len() == 1 {
-                            (
-                                Atom {
-                                    span: key.span,
-                                    content: AtomContent::Unit,
-                                },
-                                None,
-                            )
-                        } else if entry_atoms.len() == 2 {
-                            (entry_atoms[1].clone(), None)
-                        } else {
-                            (entry_atoms[1].clone(), Some(entry_atoms[2].span))
-                        };
+                        let value = entry_atoms.next().unwrap_or(Atom {
+                            span: key.span,
+                            content: AtomContent::Unit,
+                        });
+                        let too_many_atoms_span = entry_atoms.next().map(|atom| atom.span);
 
                         entries.push(ObjectEntry {
                             key,
                             value,
                             doc_comment,
+                            comments: core::mem::take(&mut pending_comments),
                             too_many_atoms_span,
                         });
                     }
@@ -911,6 +1251,9 @@ impl<'src> Parser<'src> {
                 entries,
                 duplicate_key_spans,
                 dangling_doc_comment_spans,
+                mixed_separator_span,
+                stray_comma_spans,
+                trailing_comments: pending_comments,
                 unclosed,
             },
         }
@@ -921,12 +1264,16 @@ impl<'src> Parser<'src> {
         let mut elements: Vec<Atom<'src>> = Vec::new();
         let mut unclosed = false;
         let mut comma_spans: Vec<Span> = Vec::new();
+        // A comma right after an element, accepted if `)` comes next
+        let mut trailing_comma: Option<Span> = None;
+        let mut after_element = false;
         let mut end_span = start_span;
 
         loop {
             let lexeme = self.source.next();
             match lexeme {
                 Lexeme::Eof => {
+                    comma_spans.extend(trailing_comma);
                     unclosed = true;
                     break;
                 }
@@ -936,13 +1283,20 @@ impl<'src> Parser<'src> {
                 }
                 Lexeme::Newline { .. } => continue,
                 Lexeme::Comma { span } => {
-                    comma_spans.push(span);
-                    continue;
+                    comma_spans.extend(trailing_comma.take());
+                    if self.trailing_commas && after_element {
+                        trailing_comma = Some(span);
+                    } else {
+                        comma_spans.push(span);
+                    }
+                    after_element = false;
                 }
                 Lexeme::Comment { .. } | Lexeme::DocComment { .. } => continue,
                 _ => {
+                    comma_spans.extend(trailing_comma.take());
                     let elem = self.parse_atom(lexeme);
                     elements.push(elem);
+                    after_element = true;
                 }
             }
         }
@@ -1002,6 +1356,9 @@ impl<'src> Parser<'src> {
     }
 
     /// Emit dotted path entry.
+    ///
+    /// Segments borrow from the source when the path text does, so the usual
+    /// bare-scalar case emits its keys without allocating per segment.
     fn emit_dotted_path_entry(
         &mut self,
         path_text: Cow<'src, str>,
@@ -1009,7 +1366,13 @@ impl<'src> Parser<'src> {
         atoms: &[Atom<'src>],
         check_path_state: bool,
     ) {
-        let segments: Vec<&str> = path_text.split('.').collect();
+        let segments: Vec<Cow<'src, str>> = match path_text {
+            Cow::Borrowed(text) => text.split('.').map(Cow::Borrowed).collect(),
+            Cow::Owned(text) => text
+                .split('.')
+                .map(|segment| Cow::Owned(segment.to_string()))
+                .collect(),
+        };
 
         if segments.is_empty() || segments.iter().any(|s| s.is_empty()) {
             self.event_queue.push_back(Event {
@@ -1030,29 +1393,29 @@ impl<'src> Parser<'src> {
         }
 
         // Check path state at root
+        let anchor_offset = self.anchor_name(atoms).map_or(0, |_| 1);
         if check_path_state
             && let ParserState::DocumentRoot {
                 seen_keys,
+                root_object_keys,
                 path_state,
                 ..
             } = &mut self.state
         {
-            let first_key_value = KeyValue::Scalar(segments[0].to_string());
-            seen_keys.entry(first_key_value).or_insert(path_span);
-
-            let path: Vec<String> = segments.iter().map(|s| s.to_string()).collect();
-            let value_kind = if atoms.len() >= 2 {
-                match &atoms[1].content {
-                    AtomContent::Object { .. } | AtomContent::Attributes(_) => {
-                        PathValueKind::Object
-                    }
-                    _ => PathValueKind::Terminal,
-                }
-            } else {
-                PathValueKind::Terminal
-            };
+            let first = KeyValue::Scalar(segments[0].clone());
+            if let Some(&original) = root_object_keys.get(&first) {
+                self.event_queue.push_back(Event {
+                    span: path_span,
+                    kind: EventKind::Error {
+                        kind: ParseErrorKind::DuplicateKey { original },
+                    },
+                });
+            }
+            seen_keys.entry(first).or_insert(path_span);
+
+            let value_kind = entry_value_kind(&atoms[anchor_offset..]);
 
-            if let Err(err) = path_state.check_and_update(&path, path_span, value_kind) {
+            if let Err(err) = path_state.check_and_update(&segments, path_span, value_kind) {
                 self.emit_path_error(err, path_span);
             }
         }
@@ -1061,7 +1424,7 @@ impl<'src> Parser<'src> {
         let depth = segments.len();
         let mut current_offset = path_span.start;
 
-        for (i, segment) in segments.iter().enumerate() {
+        for (i, segment) in segments.into_iter().enumerate() {
             let segment_len = segment.len() as u32;
             let segment_span = Span::new(current_offset, current_offset + segment_len);
 
@@ -1073,7 +1436,7 @@ impl<'src> Parser<'src> {
                 span: segment_span,
                 kind: EventKind::Key {
                     tag: None,
-                    payload: Some(Cow::Owned(segment.to_string())),
+                    payload: Some(segment),
                     kind: ScalarKind::Bare,
                 },
             });
@@ -1089,23 +1452,7 @@ impl<'src> Parser<'src> {
         }
 
         // Emit value
-        if atoms.len() == 1 {
-            self.event_queue.push_back(Event {
-                span: path_span,
-                kind: EventKind::Unit,
-            });
-        } else if atoms.len() >= 2 {
-            self.emit_atom_as_value(&atoms[1]);
-        }
-
-        if atoms.len() > 2 {
-            self.event_queue.push_back(Event {
-                span: atoms[2].span,
-                kind: EventKind::Error {
-                    kind: ParseErrorKind::TooManyAtoms,
-                },
-            });
-        }
+        self.emit_entry_value(atoms, path_span);
 
         // Close nested structures
         for i in (0..depth).rev() {
@@ -1323,6 +1670,9 @@ impl<'src> Parser<'src> {
                 entries,
                 duplicate_key_spans,
                 dangling_doc_comment_spans,
+                mixed_separator_span,
+                stray_comma_spans,
+                trailing_comments,
                 unclosed,
             } => {
                 self.event_queue.push_back(Event {
@@ -1359,7 +1709,31 @@ impl<'src> Parser<'src> {
                     });
                 }
 
+                if let Some(span) = mixed_separator_span {
+                    self.event_queue.push_back(Event {
+                        span: *span,
+                        kind: EventKind::Error {
+                            kind: ParseErrorKind::MixedSeparators,
+                        },
+                    });
+                }
+
+                for span in stray_comma_spans {
+                    self.event_queue.push_back(Event {
+                        span: *span,
+                        kind: EventKind::Error {
+                            kind: ParseErrorKind::UnexpectedToken,
+                        },
+                    });
+                }
+
                 for entry in entries {
+                    for &(span, text) in &entry.comments {
+                        self.event_queue.push_back(Event {
+                            span,
+                            kind: EventKind::Comment { text },
+                        });
+                    }
                     if let Some((span, lines)) = &entry.doc_comment {
                         self.event_queue.push_back(Event {
                             span: *span,
@@ -1391,6 +1765,13 @@ impl<'src> Parser<'src> {
                     });
                 }
 
+                for &(span, text) in trailing_comments {
+                    self.event_queue.push_back(Event {
+                        span,
+                        kind: EventKind::Comment { text },
+                    });
+                }
+
                 self.event_queue.push_back(Event {
                     span: atom.span,
                     kind: EventKind::ObjectEnd,
@@ -1547,6 +1928,12 @@ enum AtomContent<'src> {
         entries: Vec<ObjectEntry<'src>>,
         duplicate_key_spans: Vec<(Span, Span)>,
         dangling_doc_comment_spans: Vec<Span>,
+        /// First separator that mixes commas and newlines (strict mode only).
+        mixed_separator_span: Option<Span>,
+        /// Leading and doubled commas.
+        stray_comma_spans: Vec<Span>,
+        /// Comments after the last entry, before the closing brace.
+        trailing_comments: Vec<(Span, &'src str)>,
         unclosed: bool,
     },
     Sequence {
@@ -1571,6 +1958,8 @@ struct ObjectEntry<'src> {
     key: Atom<'src>,
     value: Atom<'src>,
     doc_comment: Option<(Span, Vec<&'src str>)>,
+    /// Comments on the lines before this entry.
+    comments: Vec<(Span, &'src str)>,
     too_many_atoms_span: Option<Span>,
 }
 
@@ -1585,41 +1974,43 @@ struct AttributeEntry<'src> {
 // Key comparison
 // ============================================================================
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-enum KeyValue {
-    Scalar(String),
+/// Key identity for duplicate detection. Borrows from the source where the
+/// key text does, so the common case of bare keys never allocates.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum KeyValue<'src> {
+    Scalar(Cow<'src, str>),
     Unit,
     Tagged {
-        name: String,
-        payload: Option<Box<KeyValue>>,
+        name: &'src str,
+        payload: Option<Box<KeyValue<'src>>>,
     },
 }
 
-impl KeyValue {
-    fn from_atom(atom: &Atom<'_>) -> Self {
+impl<'src> KeyValue<'src> {
+    fn from_atom(atom: &Atom<'src>) -> Self {
         match &atom.content {
-            AtomContent::Scalar { value, .. } => KeyValue::Scalar(value.to_string()),
+            AtomContent::Scalar { value, .. } => KeyValue::Scalar(value.clone()),
             AtomContent::Unit => KeyValue::Unit,
             AtomContent::Tag { name, payload, .. } => KeyValue::Tagged {
-                name: (*name).to_string(),
+                name,
                 payload: payload.as_ref().map(|p| Box::new(KeyValue::from_atom(p))),
             },
-            AtomContent::Object { .. } => KeyValue::Scalar("{}".into()),
-            AtomContent::Sequence { .. } => KeyValue::Scalar("()".into()),
-            AtomContent::Attributes(_) => KeyValue::Scalar("{}".into()),
+            AtomContent::Object { .. } => KeyValue::Scalar(Cow::Borrowed("{}")),
+            AtomContent::Sequence { .. } => KeyValue::Scalar(Cow::Borrowed("()")),
+            AtomContent::Attributes(_) => KeyValue::Scalar(Cow::Borrowed("{}")),
             AtomContent::InvalidEscapeScalar { raw_inner } => {
                 // This is raw text that failed escape processing - just use it as-is
-                KeyValue::Scalar(raw_inner.to_string())
+                KeyValue::Scalar(raw_inner.clone())
             }
-            AtomContent::Error { .. } => KeyValue::Scalar("<error>".into()),
+            AtomContent::Error { .. } => KeyValue::Scalar(Cow::Borrowed("<error>")),
         }
     }
 
-    fn to_key_string(&self) -> String {
+    fn key_text(&self) -> Cow<'src, str> {
         match self {
             KeyValue::Scalar(s) => s.clone(),
-            KeyValue::Unit => "@".to_string(),
-            KeyValue::Tagged { name, .. } => format!("@{}", name),
+            KeyValue::Unit => Cow::Borrowed("@"),
+            KeyValue::Tagged { name, .. } => Cow::Owned(format!("@{}", name)),
         }
     }
 }
@@ -1658,6 +2049,63 @@ struct PathSegment {
     closed_children: HashMap<String, Span>,
 }
 
+/// How two entries of an object are separated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Separator {
+    Comma,
+    Newline,
+}
+
+/// Tracks the separators between the entries of one object.
+///
+/// A gap containing a comma counts as a comma, even if it also spans
+/// newlines (`a 1,\n b 2`). Newlines before the first entry or after the last
+/// one are not separators. Each gap may hold at most one comma, and only
+/// after an entry: `{a 1,}` is fine, `{, a 1}` and `{a 1,, b 2}` are not.
+#[derive(Debug, Default, Clone)]
+struct SeparatorCheck {
+    /// Style of the gap between the first two entries.
+    style: Option<Separator>,
+    /// Separator seen since the previous entry.
+    gap: Option<(Separator, Span)>,
+    seen_entry: bool,
+    reported: bool,
+}
+
+impl SeparatorCheck {
+    /// Record a separator. Returns `true` for a comma that is leading or
+    /// doubled.
+    fn separator(&mut self, separator: Separator, span: Span) -> bool {
+        let stray = separator == Separator::Comma
+            && (!self.seen_entry || matches!(self.gap, Some((Separator::Comma, _))));
+        if separator == Separator::Comma || self.gap.is_none() {
+            self.gap = Some((separator, span));
+        }
+        stray
+    }
+
+    /// Record the start of an entry. Returns the span of the separator before
+    /// it if that is the first one to differ from the object's style.
+    fn entry(&mut self) -> Option<Span> {
+        let gap = self.gap.take();
+        if !core::mem::replace(&mut self.seen_entry, true) {
+            return None;
+        }
+        let (separator, span) = gap?;
+        match self.style {
+            None => {
+                self.style = Some(separator);
+                None
+            }
+            Some(style) if style != separator && !self.reported => {
+                self.reported = true;
+                Some(span)
+            }
+            Some(_) => None,
+        }
+    }
+}
+
 /// Path state tracker with O(depth) memory usage.
 ///
 /// Instead of tracking all paths ever seen (O(total paths)), we only track:
@@ -1672,9 +2120,9 @@ struct PathState {
 }
 
 impl PathState {
-    fn check_and_update(
+    fn check_and_update<S: AsRef<str>>(
         &mut self,
-        path: &[String],
+        path: &[S],
         span: Span,
         value_kind: PathValueKind,
     ) -> Result<(), PathError> {
@@ -1687,7 +2135,7 @@ impl PathState {
             .segments
             .iter()
             .zip(path.iter())
-            .take_while(|(seg, key)| seg.key == **key)
+            .take_while(|(seg, key)| seg.key == key.as_ref())
             .count();
 
         // Special case: if the entire path matches, check for duplicate
@@ -1717,6 +2165,7 @@ impl PathState {
 
         // Now process each new segment of the path
         for (i, key) in path.iter().enumerate().skip(common_len) {
+            let key = key.as_ref();
             let is_last = i == path.len() - 1;
             let segment_value_kind = if is_last {
                 value_kind
@@ -1732,7 +2181,7 @@ impl PathState {
             if i < self.segments.len() {
                 // We're on the same path segment - check for exact duplicate
                 let existing = &self.segments[i];
-                if existing.key == *key && is_last {
+                if existing.key == key && is_last {
                     return Err(PathError::Duplicate {
                         original: existing.span,
                     });
@@ -1740,7 +2189,7 @@ impl PathState {
             } else if i == 0 {
                 // Root level - no parent to check
                 // Check if we already have a root segment with this key
-                if !self.segments.is_empty() && self.segments[0].key == *key {
+                if !self.segments.is_empty() && self.segments[0].key == key {
                     if is_last {
                         return Err(PathError::Duplicate {
                             original: self.segments[0].span,
@@ -1751,7 +2200,7 @@ impl PathState {
                 }
                 // New root segment
                 self.segments.push(PathSegment {
-                    key: key.clone(),
+                    key: key.to_string(),
                     span,
                     value_kind: segment_value_kind,
                     closed_children: HashMap::new(),
@@ -1773,14 +2222,14 @@ impl PathState {
                         closed_path: self.segments[..i]
                             .iter()
                             .map(|s| s.key.clone())
-                            .chain(std::iter::once(key.clone()))
+                            .chain(core::iter::once(key.to_string()))
                             .collect(),
                     });
                 }
 
                 // Add new segment
                 self.segments.push(PathSegment {
-                    key: key.clone(),
+                    key: key.to_string(),
                     span,
                     value_kind: segment_value_kind,
                     closed_children: HashMap::new(),
@@ -1801,7 +2250,26 @@ impl PathState {
 // Helpers
 // ============================================================================
 
-fn is_valid_tag_name(name: &str) -> bool {
+/// Whether an entry's value, given the atoms that follow its key, is an object.
+fn entry_value_kind(atoms: &[Atom<'_>]) -> PathValueKind {
+    match atoms.get(1).map(|atom| &atom.content) {
+        Some(AtomContent::Object { .. } | AtomContent::Attributes(_)) => PathValueKind::Object,
+        _ => PathValueKind::Terminal,
+    }
+}
+
+/// Whether `name` is a valid tag name; with `namespaced`, it may be
+/// `namespace:name`.
+fn is_valid_tag_name(name: &str, namespaced: bool) -> bool {
+    match name.split_once(':') {
+        Some((namespace, name)) if namespaced => {
+            is_valid_tag_name(namespace, false) && is_valid_tag_name(name, false)
+        }
+        _ => is_valid_plain_tag_name(name),
+    }
+}
+
+fn is_valid_plain_tag_name(name: &str) -> bool {
     let mut chars = name.chars();
     match chars.next() {
         Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
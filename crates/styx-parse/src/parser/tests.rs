@@ -2,7 +2,7 @@
 
 use super::*;
 use crate::events::EventKind;
-use crate::{ParseErrorKind, ScalarKind};
+use crate::{ParseErrorKind, ScalarKind, TagName};
 use facet_testhelpers::test;
 use styx_testhelpers::{ActualError, assert_annotated_errors, source_without_annotations};
 use tracing::trace;
@@ -51,6 +51,7 @@ mod event_assert {
             EventKind::Unit => "Unit".to_string(),
             EventKind::TagStart { name } => format!("TagStart(@{})", name),
             EventKind::TagEnd => "TagEnd".to_string(),
+            EventKind::Anchor { name } => format!("Anchor(&{})", name),
             EventKind::Comment { text } => format!("Comment({:?})", text),
             EventKind::DocComment { lines } => format!("DocComment({:?})", lines),
             EventKind::Error { kind } => format!("Error({:?})", kind),
@@ -207,12 +208,24 @@ fn error_kind_name(kind: &ParseErrorKind) -> &'static str {
         ParseErrorKind::CommaInSequence => "CommaInSequence",
         ParseErrorKind::MissingWhitespaceBeforeBlock => "MissingWhitespaceBeforeBlock",
         ParseErrorKind::TrailingContent => "TrailingContent",
+        ParseErrorKind::MixedSeparators => "MixedSeparators",
     }
 }
 
 fn assert_parse_errors(annotated_source: &str) {
+    assert_parse_errors_with(annotated_source, |parser| parser);
+}
+
+fn assert_strict_parse_errors(annotated_source: &str) {
+    assert_parse_errors_with(annotated_source, |parser| parser.strict_separators(true));
+}
+
+fn assert_parse_errors_with(
+    annotated_source: &str,
+    configure: impl for<'a> FnOnce(Parser<'a>) -> Parser<'a>,
+) {
     let source = source_without_annotations(annotated_source);
-    let events = parse(&source);
+    let events = configure(Parser::new(&source)).parse_to_vec();
     let actual_errors: Vec<_> = events
         .iter()
         .filter_map(|e| match e {
@@ -370,6 +383,98 @@ fn test_comments() {
     );
 }
 
+#[test]
+fn test_comments_in_nested_object() {
+    let input = "a 1 // same line\nb {\n    // inner\n    c 2\n    // last\n}";
+    let events = parse(input);
+    assert_events_eq!(
+        input,
+        events,
+        "
+        DocumentStart
+        ObjectStart
+        EntryStart
+        Key(\"a\")
+        Scalar(\"1\")
+        EntryEnd
+        Comment(\"// same line\")
+        EntryStart
+        Key(\"b\")
+        ObjectStart
+        Comment(\"// inner\")
+        EntryStart
+        Key(\"c\")
+        Scalar(\"2\")
+        EntryEnd
+        Comment(\"// last\")
+        ObjectEnd
+        EntryEnd
+        ObjectEnd
+        DocumentEnd
+        "
+    );
+}
+
+#[test]
+fn test_deeply_nested_entries_keep_order() {
+    // Nested entry atoms are moved rather than cloned while building objects;
+    // the events must be exactly what a naive copy would produce.
+    let input = "a {b {c {d 1, e (x y)}, f @tag{g h}}, i j k}";
+    let events = parse(input);
+    assert_events_eq!(
+        input,
+        events,
+        "
+        DocumentStart
+        ObjectStart
+        EntryStart
+        Key(\"a\")
+        ObjectStart
+        EntryStart
+        Key(\"b\")
+        ObjectStart
+        EntryStart
+        Key(\"c\")
+        ObjectStart
+        EntryStart
+        Key(\"d\")
+        Scalar(\"1\")
+        EntryEnd
+        EntryStart
+        Key(\"e\")
+        SequenceStart
+        Scalar(\"x\")
+        Scalar(\"y\")
+        SequenceEnd
+        EntryEnd
+        ObjectEnd
+        EntryEnd
+        EntryStart
+        Key(\"f\")
+        TagStart(@tag)
+        ObjectStart
+        EntryStart
+        Key(\"g\")
+        Scalar(\"h\")
+        EntryEnd
+        ObjectEnd
+        TagEnd
+        EntryEnd
+        ObjectEnd
+        EntryEnd
+        EntryStart
+        Key(\"i\")
+        Scalar(\"j\")
+        Error(TooManyAtoms)
+        EntryEnd
+        ObjectEnd
+        EntryEnd
+        ObjectEnd
+        DocumentEnd
+        "
+    );
+}
+
 #[test]
 fn test_doc_comments() {
     let events = parse("/// doc\nfoo bar");
@@ -804,6 +909,65 @@ fn test_explicit_root_after_doc_comment() {
     );
 }
 
+#[test]
+fn test_trailing_content_after_explicit_root() {
+    assert_parse_errors(
+        r#"
+{host localhost}
+port 8080
+^^^^^^^^^ TrailingContent
+"#,
+    );
+}
+
+#[test]
+fn test_trailing_entries_merge_into_explicit_root() {
+    let input = "{host localhost}\n// more\nport 8080\nserver.tls true";
+    let events = Parser::new(input).trailing_entries(true).parse_to_vec();
+    assert_events_eq!(
+        input,
+        events,
+        "
+        DocumentStart
+        ObjectStart
+        EntryStart
+        Key(\"host\")
+        Scalar(\"localhost\")
+        EntryEnd
+        Comment(\"// more\")
+        EntryStart
+        Key(\"port\")
+        Scalar(\"8080\")
+        EntryEnd
+        EntryStart
+        Key(\"server\")
+        ObjectStart
+        EntryStart
+        Key(\"tls\")
+        Scalar(\"true\")
+        EntryEnd
+        ObjectEnd
+        EntryEnd
+        ObjectEnd
+        DocumentEnd
+        "
+    );
+}
+
+#[test]
+fn test_trailing_entries_duplicate_root_object_keys() {
+    assert_parse_errors_with(
+        r#"
+{host localhost, port 80}
+port 8080
+^^^^ DuplicateKey
+host.name example
+^^^^^^^^^ DuplicateKey
+"#,
+        |parser| parser.trailing_entries(true),
+    );
+}
+
 #[test]
 fn test_duplicate_bare_key() {
     assert_parse_errors(
@@ -887,6 +1051,229 @@ b 2, c 3}
     );
 }
 
+#[test]
+fn test_mixed_separators_allowed_at_root_and_nested() {
+    assert_parse_errors(
+        r#"
+a 1, b 2
+c 3
+server {host localhost, port 8080
+  tls true}
+"#,
+    );
+}
+
+#[test]
+fn test_strict_separators_at_root() {
+    assert_strict_parse_errors(
+        r#"
+a 1, b 2
+        ^ MixedSeparators
+c 3
+"#,
+    );
+    assert_strict_parse_errors(
+        r#"
+a 1
+b 2, c 3
+   ^ MixedSeparators
+"#,
+    );
+    assert_strict_parse_errors(
+        r#"
+{a 1
+b 2, c 3}
+   ^ MixedSeparators
+"#,
+    );
+}
+
+#[test]
+fn test_strict_separators_nested() {
+    assert_strict_parse_errors(
+        r#"
+server {host localhost, port 8080
+                                 ^ MixedSeparators
+  tls true}
+"#,
+    );
+    assert_strict_parse_errors(
+        r#"
+outer {
+  inner {a 1
+  b 2, c 3}
+     ^ MixedSeparators
+}
+"#,
+    );
+}
+
+#[test]
+fn test_strict_separators_consistent() {
+    // Each object picks its own style; a comma followed by a newline counts
+    // as a comma, and blank lines around entries are not separators.
+    assert_strict_parse_errors(
+        r#"
+
+a 1
+b 2
+
+server {host localhost, port 8080}
+list {
+  x 1,
+  y 2, z 3
+}
+
+"#,
+    );
+}
+
+#[test]
+fn test_comma_in_sequence() {
+    assert_parse_errors(
+        r#"
+a (1 2 3,)
+        ^ CommaInSequence
+b (1, 2)
+    ^ CommaInSequence
+"#,
+    );
+}
+
+#[test]
+fn test_trailing_commas_allowed() {
+    assert_parse_errors_with(
+        r#"
+a (1 2 3,)
+b {a 1, b 2,}
+c (
+  1
+  2,
+)
+d ((x y,) (z,),)
+"#,
+        |parser| parser.trailing_commas(true),
+    );
+}
+
+#[test]
+fn test_trailing_commas_still_reject_extra_commas() {
+    assert_parse_errors_with(
+        r#"
+a (1,,2)
+    ^ CommaInSequence
+     ^ CommaInSequence
+b (,1)
+   ^ CommaInSequence
+c (1 2,,)
+      ^ CommaInSequence
+       ^ CommaInSequence
+d (1, 2)
+    ^ CommaInSequence
+e (,)
+   ^ CommaInSequence
+"#,
+        |parser| parser.trailing_commas(true),
+    );
+}
+
+#[test]
+fn test_objects_reject_leading_and_doubled_commas() {
+    assert_parse_errors(
+        r#"
+a {x 1,, y 2}
+       ^ UnexpectedToken
+b {, x 1}
+   ^ UnexpectedToken
+c {x 1,,}
+       ^ UnexpectedToken
+d {x {y 1,,}}
+          ^ UnexpectedToken
+"#,
+    );
+    assert_parse_errors(
+        r#"
+x 1,, y 2
+    ^ UnexpectedToken
+"#,
+    );
+    assert_parse_errors(
+        r#"
+{
+  , x 1
+  ^ UnexpectedToken
+  y 2,
+}
+"#,
+    );
+}
+
+#[test]
+fn test_anchors_emit_anchor_event() {
+    let source = "base &defaults {port 8080}\nserver *defaults\nlist &items (a b)";
+    let events = Parser::new(source).anchors(true).parse_to_vec();
+    let anchors: Vec<_> = events
+        .iter()
+        .filter_map(|event| match &event.kind {
+            EventKind::Anchor { name } => Some((name.as_ref(), event.span)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        anchors,
+        vec![("defaults", Span::new(5, 14)), ("items", Span::new(49, 55))]
+    );
+    assert!(
+        !events
+            .iter()
+            .any(|event| matches!(event.kind, EventKind::Error { .. }))
+    );
+
+    // The anchored value follows the anchor; the alias is a plain scalar
+    let anchor_index = events
+        .iter()
+        .position(|event| matches!(event.kind, EventKind::Anchor { .. }))
+        .unwrap();
+    assert!(matches!(
+        events[anchor_index + 1].kind,
+        EventKind::ObjectStart
+    ));
+    assert!(events.iter().any(|event| matches!(
+        &event.kind,
+        EventKind::Scalar { value, .. } if value == "*defaults"
+    )));
+}
+
+#[test]
+fn test_anchors_require_opt_in_and_a_value() {
+    let has_anchor = |parser: Parser| {
+        parser
+            .parse_to_vec()
+            .iter()
+            .any(|event| matches!(event.kind, EventKind::Anchor { .. }))
+    };
+    assert!(!has_anchor(Parser::new("base &defaults {port 8080}")));
+    // Without a value after it, `&name` is the entry's value
+    assert!(!has_anchor(Parser::new("base &defaults").anchors(true)));
+    assert!(!has_anchor(
+        Parser::new("base &1x {port 8080}").anchors(true)
+    ));
+
+    assert_parse_errors(
+        r#"
+base &defaults {port 8080}
+               ^^^^^^^^^^^ TooManyAtoms
+"#,
+    );
+    assert_parse_errors_with(
+        r#"
+base &defaults {port 8080} extra
+                           ^^^^^ TooManyAtoms
+"#,
+        |parser| parser.anchors(true),
+    );
+}
+
 #[test]
 fn test_consistent_comma_separators() {
     assert_parse_errors(r#"{a 1, b 2, c 3}"#);
@@ -909,6 +1296,44 @@ fn test_valid_tag_names() {
     assert_parse_errors("@Type123");
 }
 
+#[test]
+fn test_namespaced_tags() {
+    let events = Parser::new("query @sql:Query{text hi}")
+        .namespaced_tags(true)
+        .parse_to_vec();
+    let name = events.iter().find_map(|event| match event.kind {
+        EventKind::TagStart { name } => Some(name),
+        _ => None,
+    });
+    assert_eq!(
+        name.map(TagName::split),
+        Some(TagName {
+            namespace: Some("sql"),
+            name: "Query"
+        })
+    );
+    assert!(
+        events
+            .iter()
+            .any(|e| matches!(e.kind, EventKind::ObjectStart))
+    );
+
+    assert_parse_errors_with(
+        r#"
+x @a:b:c
+  ^^^^^^ InvalidTagName
+"#,
+        |parser| parser.namespaced_tags(true),
+    );
+    // Off by default
+    assert_parse_errors(
+        r#"
+x @sql:Query
+  ^^^^^^^^^^ InvalidTagName
+"#,
+    );
+}
+
 #[test]
 fn test_invalid_tag_name_starts_with_hyphen() {
     assert_parse_errors(
@@ -1203,6 +1628,116 @@ foo.baz value3"#,
     assert!(keys.contains(&"y"));
 }
 
+#[test]
+fn test_dotted_paths_borrow_segments() {
+    // Segments are sliced out of the source rather than copied; events and
+    // spans must be exactly what per-segment owned strings produced.
+    let input = "a.b.c 1\na.b.d {e f}\ng.h\ng.h 2";
+    let events = parse(input);
+    assert_events_eq!(
+        input,
+        events,
+        "
+        DocumentStart
+        ObjectStart
+        EntryStart
+        Key(\"a\")
+        ObjectStart
+        EntryStart
+        Key(\"b\")
+        ObjectStart
+        EntryStart
+        Key(\"c\")
+        Scalar(\"1\")
+        EntryEnd
+        ObjectEnd
+        EntryEnd
+        ObjectEnd
+        EntryEnd
+        EntryStart
+        Key(\"a\")
+        ObjectStart
+        EntryStart
+        Key(\"b\")
+        ObjectStart
+        EntryStart
+        Key(\"d\")
+        ObjectStart
+        EntryStart
+        Key(\"e\")
+        Scalar(\"f\")
+        EntryEnd
+        ObjectEnd
+        EntryEnd
+        ObjectEnd
+        EntryEnd
+        ObjectEnd
+        EntryEnd
+        EntryStart
+        Key(\"g\")
+        ObjectStart
+        EntryStart
+        Key(\"h\")
+        Unit
+        EntryEnd
+        ObjectEnd
+        EntryEnd
+        Error(DuplicateKey { original: Span { start: 20, end: 23 } })
+        EntryStart
+        Key(\"g\")
+        ObjectStart
+        EntryStart
+        Key(\"h\")
+        Scalar(\"2\")
+        EntryEnd
+        ObjectEnd
+        EntryEnd
+        ObjectEnd
+        DocumentEnd
+        "
+    );
+
+    let keys: Vec<_> = events
+        .iter()
+        .filter_map(|e| match &e.kind {
+            EventKind::Key {
+                payload: Some(value),
+                ..
+            } => Some((value, &input[e.span.start as usize..e.span.end as usize])),
+            _ => None,
+        })
+        .collect();
+    for (payload, source_text) in keys {
+        assert_eq!(payload, source_text);
+        assert!(
+            matches!(payload, Cow::Borrowed(_)),
+            "{payload:?} was copied"
+        );
+    }
+}
+
+#[test]
+fn test_entry_errors_are_in_source_order() {
+    // The comma is found while the sequence is collected, the bad tag only
+    // when the entry is emitted
+    let errors: Vec<_> = parse("k (@1bad, x)\nnext (a, b) @2bad")
+        .into_iter()
+        .filter_map(|e| match e.kind {
+            EventKind::Error { kind } => Some((e.span.start, error_kind_name(&kind))),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            (3, "InvalidTagName"),
+            (8, "CommaInSequence"),
+            (20, "CommaInSequence"),
+            (25, "TooManyAtoms"),
+        ]
+    );
+}
+
 #[test]
 fn test_reopen_closed_path_error() {
     assert_parse_errors(
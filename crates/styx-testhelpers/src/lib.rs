@@ -1,7 +1,99 @@
 use std::ops::Range;
+use std::path::{Path, PathBuf};
 
 use ariadne::{CharSet, Color, Config, Label, Report, ReportKind, Source};
 
+/// A sink that records how much is written, and in what sizes, without
+/// keeping it.
+#[derive(Debug, Default)]
+pub struct CountingWriter {
+    pub bytes: usize,
+    pub writes: usize,
+    pub largest_write: usize,
+}
+
+impl std::io::Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.bytes += buf.len();
+        self.writes += 1;
+        self.largest_write = self.largest_write.max(buf.len());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A seeded xorshift generator, for fuzz-style tests that should see the
+/// same inputs on every run.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// A generator starting from `seed`, which must not be zero.
+    pub fn new(seed: u64) -> Self {
+        assert_ne!(seed, 0, "xorshift never leaves a zero state");
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// A number in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+/// A scratch directory for one test, removed when dropped, even if the test
+/// panics.
+///
+/// It lives at `styx-{name}-{pid}` under the system temp directory and starts
+/// out empty, so tests run in parallel need different names.
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("styx-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write `content` to `name` in the directory and return its path.
+    pub fn write(&self, name: &str, content: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.path.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ExpectedError {
     pub line: usize,
@@ -0,0 +1,70 @@
+//! Bulk scanning for the tokenizer's hot loops (`simd` feature).
+//!
+//! The default tokenizer decodes one `char` at a time. These helpers find the
+//! end of a token with `memchr` where the stop set is small (quotes, escapes,
+//! line ends) and with an ASCII lookup table for bare scalars, whose stop set
+//! is too large for `memchr`. Non-ASCII input in bare scalars falls back to
+//! `char` classification, so results match the scalar path exactly.
+
+use crate::tokenizer::is_bare_scalar_char;
+
+/// ASCII bytes that end a bare scalar.
+const BARE_STOP: [bool; 128] = {
+    let mut table = [false; 128];
+    let mut i = 0;
+    while i < 128 {
+        table[i] = !is_bare_scalar_char(i as u8 as char);
+        i += 1;
+    }
+    table
+};
+
+/// Byte length of the bare scalar at the start of `s`.
+pub(crate) fn bare_scalar_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while let Some(&b) = bytes.get(i) {
+        if b.is_ascii() {
+            if BARE_STOP[b as usize] {
+                break;
+            }
+            i += 1;
+        } else {
+            // Unicode whitespace also ends a bare scalar
+            let c = s[i..].chars().next().unwrap();
+            if !is_bare_scalar_char(c) {
+                break;
+            }
+            i += c.len_utf8();
+        }
+    }
+    i
+}
+
+/// Byte length of a quoted scalar body, including the closing quote.
+///
+/// `s` starts right after the opening quote. Returns `None` if the string is
+/// unterminated.
+pub(crate) fn quoted_scalar_len(s: &str) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    loop {
+        i += memchr::memchr2(b'"', b'\\', &bytes[i..])?;
+        if bytes[i] == b'"' {
+            return Some(i + 1);
+        }
+        // Skip the backslash and whatever character it escapes
+        i += 1;
+        i += s[i..].chars().next()?.len_utf8();
+    }
+}
+
+/// Byte length up to (not including) the next `\n` or `\r`.
+pub(crate) fn line_len(s: &str) -> usize {
+    memchr::memchr2(b'\n', b'\r', s.as_bytes()).unwrap_or(s.len())
+}
+
+/// Byte length up to and including the next `\n`, or to the end of `s`.
+pub(crate) fn line_len_with_newline(s: &str) -> usize {
+    memchr::memchr(b'\n', s.as_bytes()).map_or(s.len(), |i| i + 1)
+}
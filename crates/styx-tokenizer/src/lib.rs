@@ -1,4 +1,7 @@
 //! A tokenizer for styx
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 mod span;
 pub use span::Span;
@@ -6,5 +9,8 @@ pub use span::Span;
 mod token;
 pub use token::{Token, TokenKind};
 
+#[cfg(any(feature = "simd", test))]
+mod fast_scan;
+
 mod tokenizer;
 pub use tokenizer::Tokenizer;
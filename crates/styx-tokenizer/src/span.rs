@@ -55,13 +55,13 @@ impl Span {
     }
 }
 
-impl From<std::ops::Range<u32>> for Span {
-    fn from(range: std::ops::Range<u32>) -> Self {
+impl From<core::ops::Range<u32>> for Span {
+    fn from(range: core::ops::Range<u32>) -> Self {
         Span::new(range.start, range.end)
     }
 }
 
-impl From<Span> for std::ops::Range<usize> {
+impl From<Span> for core::ops::Range<usize> {
     fn from(span: Span) -> Self {
         span.start as usize..span.end as usize
     }
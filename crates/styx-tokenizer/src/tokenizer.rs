@@ -1,5 +1,7 @@
 //! Tokenizer for the Styx configuration language.
 
+use alloc::string::{String, ToString};
+
 use crate::{Span, Token, TokenKind};
 use tracing::trace;
 
@@ -15,6 +17,10 @@ pub struct Tokenizer<'src> {
 
     /// State for heredoc parsing.
     heredoc_state: Option<HeredocState>,
+
+    /// Whether to use the bulk scanners in [`crate::fast_scan`].
+    #[cfg(any(feature = "simd", test))]
+    fast_scan: bool,
 }
 
 /// State for tracking heredoc parsing.
@@ -35,6 +41,17 @@ impl<'src> Tokenizer<'src> {
             remaining: source,
             pos: 0,
             heredoc_state: None,
+            #[cfg(any(feature = "simd", test))]
+            fast_scan: true,
+        }
+    }
+
+    /// Create a tokenizer that always takes the char-by-char path.
+    #[cfg(test)]
+    fn without_fast_scan(source: &'src str) -> Self {
+        Self {
+            fast_scan: false,
+            ..Self::new(source)
         }
     }
 
@@ -173,6 +190,13 @@ impl<'src> Tokenizer<'src> {
             // Whitespace
             ' ' | '\t' => self.tokenize_whitespace(),
 
+            // A leading byte order mark is skipped like whitespace, so it
+            // doesn't end up in the first key
+            '\u{feff}' if start == 0 => {
+                self.advance();
+                self.token(TokenKind::Whitespace, start)
+            }
+
             // Newline
             '\n' => {
                 self.advance();
@@ -211,6 +235,11 @@ impl<'src> Tokenizer<'src> {
     /// Tokenize a bare (unquoted) scalar.
     fn tokenize_bare_scalar(&mut self) -> Token<'src> {
         let start = self.pos;
+        #[cfg(any(feature = "simd", test))]
+        if self.fast_scan {
+            self.advance_by(crate::fast_scan::bare_scalar_len(self.remaining));
+            return self.token(TokenKind::BareScalar, start);
+        }
         while let Some(c) = self.peek() {
             if is_bare_scalar_char(c) {
                 self.advance();
@@ -229,7 +258,8 @@ impl<'src> Tokenizer<'src> {
         // Check if followed by tag name start: [A-Za-z_]
         match self.peek() {
             Some(c) if c.is_ascii_alphabetic() || c == '_' => {
-                // Tag name: consume [A-Za-z0-9_-]*
+                // Tag name: consume [A-Za-z0-9_-]*, with `:` before a name
+                // start for namespaced tags like `@sql:Query`
                 // But stop before `r#` or `r"` which starts a raw string payload
                 self.advance();
                 while let Some(c) = self.peek() {
@@ -239,7 +269,9 @@ impl<'src> Tokenizer<'src> {
                         // Don't consume `r` - it's the start of a raw string
                         break;
                     }
-                    if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                    let namespace_separator = c == ':'
+                        && matches!(self.peek_nth(1), Some(c) if c.is_ascii_alphabetic() || c == '_');
+                    if c.is_ascii_alphanumeric() || c == '_' || c == '-' || namespace_separator {
                         self.advance();
                     } else {
                         break;
@@ -261,6 +293,20 @@ impl<'src> Tokenizer<'src> {
         // Consume opening quote
         self.advance();
 
+        #[cfg(any(feature = "simd", test))]
+        if self.fast_scan {
+            return match crate::fast_scan::quoted_scalar_len(self.remaining) {
+                Some(len) => {
+                    self.advance_by(len);
+                    self.token(TokenKind::QuotedScalar, start)
+                }
+                None => {
+                    self.advance_by(self.remaining.len());
+                    self.token(TokenKind::Error, start)
+                }
+            };
+        }
+
         loop {
             match self.peek() {
                 None => {
@@ -296,6 +342,12 @@ impl<'src> Tokenizer<'src> {
         self.advance();
         self.advance();
 
+        #[cfg(any(feature = "simd", test))]
+        if self.fast_scan {
+            self.advance_by(crate::fast_scan::line_len(self.remaining));
+            return self.token(TokenKind::LineComment, start);
+        }
+
         // Consume until end of line
         while let Some(c) = self.peek() {
             if c == '\n' || c == '\r' {
@@ -316,6 +368,12 @@ impl<'src> Tokenizer<'src> {
         self.advance();
         self.advance();
 
+        #[cfg(any(feature = "simd", test))]
+        if self.fast_scan {
+            self.advance_by(crate::fast_scan::line_len(self.remaining));
+            return self.token(TokenKind::DocComment, start);
+        }
+
         // Consume until end of line
         while let Some(c) = self.peek() {
             if c == '\n' || c == '\r' {
@@ -460,18 +518,7 @@ impl<'src> Tokenizer<'src> {
         let mut found_end = false;
         let mut closing_indent = 0usize;
         while !self.is_eof() {
-            // Consume the current line
-            while let Some(c) = self.peek() {
-                if c == '\n' {
-                    self.advance();
-                    break;
-                } else if c == '\r' && self.peek_nth(1) == Some('\n') {
-                    self.advance();
-                    self.advance();
-                    break;
-                }
-                self.advance();
-            }
+            self.consume_heredoc_line();
 
             // Check if next line starts with delimiter (possibly indented)
             if let Some(indent_len) = self.find_heredoc_delimiter(delimiter) {
@@ -511,6 +558,26 @@ impl<'src> Tokenizer<'src> {
         self.token(TokenKind::HeredocContent, start)
     }
 
+    /// Consume one heredoc content line, including its line ending.
+    fn consume_heredoc_line(&mut self) {
+        #[cfg(any(feature = "simd", test))]
+        if self.fast_scan {
+            self.advance_by(crate::fast_scan::line_len_with_newline(self.remaining));
+            return;
+        }
+        while let Some(c) = self.peek() {
+            if c == '\n' {
+                self.advance();
+                break;
+            } else if c == '\r' && self.peek_nth(1) == Some('\n') {
+                self.advance();
+                self.advance();
+                break;
+            }
+            self.advance();
+        }
+    }
+
     // parser[impl scalar.raw.syntax]
     /// Tokenize a raw string: `r#*"..."#*`.
     /// Returns the entire raw string including delimiters.
@@ -599,7 +666,7 @@ fn is_bare_scalar_start(c: char) -> bool {
 
 // parser[impl scalar.bare.chars]
 /// Check if a character can continue a bare scalar.
-fn is_bare_scalar_char(c: char) -> bool {
+pub(crate) const fn is_bare_scalar_char(c: char) -> bool {
     // Cannot be special chars or whitespace
     // `/`, `@`, and `=` are allowed after the first char
     // `>` is never allowed (attribute separator)
@@ -681,6 +748,36 @@ mod tests {
         assert_eq!(tokenize("\r\n"), vec![(TokenKind::Newline, "\r\n")]);
     }
 
+    #[test]
+    fn test_namespaced_tag() {
+        assert_eq!(
+            tokenize("@sql:Query{"),
+            vec![(TokenKind::Tag, "@sql:Query"), (TokenKind::LBrace, "{")]
+        );
+        assert_eq!(tokenize("@a:b:c"), vec![(TokenKind::Tag, "@a:b:c")]);
+        // A colon not followed by a name is not part of the tag
+        assert_eq!(
+            tokenize("@a:1"),
+            vec![(TokenKind::Tag, "@a"), (TokenKind::BareScalar, ":1")]
+        );
+    }
+
+    #[test]
+    fn test_leading_bom() {
+        assert_eq!(
+            tokenize("\u{feff}name"),
+            vec![
+                (TokenKind::Whitespace, "\u{feff}"),
+                (TokenKind::BareScalar, "name"),
+            ]
+        );
+        // Only the first character can be a byte order mark
+        assert_eq!(
+            tokenize("a\u{feff}"),
+            vec![(TokenKind::BareScalar, "a\u{feff}")]
+        );
+    }
+
     #[test]
     fn test_mixed() {
         let tokens = tokenize("{host localhost}");
@@ -811,6 +908,92 @@ mod tests {
         );
     }
 
+    fn tokenize_spans(mut tokenizer: Tokenizer<'_>) -> Vec<(TokenKind, Span, &str)> {
+        let mut tokens = Vec::new();
+        loop {
+            let token = tokenizer.next_token();
+            tokens.push((token.kind, token.span, token.text));
+            if token.kind == TokenKind::Eof {
+                return tokens;
+            }
+        }
+    }
+
+    fn assert_fast_scan_agrees(source: &str) {
+        assert_eq!(
+            tokenize_spans(Tokenizer::new(source)),
+            tokenize_spans(Tokenizer::without_fast_scan(source)),
+            "fast and char-by-char scanning disagree on {source:?}"
+        );
+    }
+
+    #[test]
+    fn test_fast_scan_matches_char_scan() {
+        let edge_cases = [
+            "key value\nother \"quoted \\\" value\"",
+            "caf\u{e9} na\u{ef}ve\u{a0}nbsp\u{2003}em \u{1f980}crab",
+            "\"escaped \\\u{e9} accent\" \"ends in backslash\\",
+            "\"unterminated",
+            "// comment\r\n/// doc\rstray\n// eof",
+            "x <<EOF\nline one\r\nline\rtwo\n  EOF\ny <<END\nnever closed",
+            "a>b c{d}(e,f)\"g\"@h/i=j",
+            "r#\"raw \"# inside\"# tail",
+        ];
+        for source in edge_cases {
+            assert_fast_scan_agrees(source);
+        }
+
+        let corpus = concat!(env!("CARGO_MANIFEST_DIR"), "/../../compliance/corpus");
+        let mut dirs = vec![std::path::PathBuf::from(corpus)];
+        while let Some(dir) = dirs.pop() {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if path.extension().is_some_and(|ext| ext == "styx") {
+                    assert_fast_scan_agrees(&std::fs::read_to_string(&path).unwrap());
+                }
+            }
+        }
+
+        // Random documents over characters each scanner treats specially
+        let alphabet = [
+            "a",
+            "Z",
+            "1",
+            " ",
+            "\t",
+            "\n",
+            "\r",
+            "\"",
+            "\\",
+            "/",
+            "{",
+            "}",
+            "(",
+            ")",
+            ",",
+            ">",
+            "@",
+            "=",
+            "<<E",
+            "r#",
+            "\u{85}",
+            "\u{a0}",
+            "\u{e9}",
+            "\u{3000}",
+            "\u{1f980}",
+        ];
+        let mut rng = styx_testhelpers::Rng::default();
+        for _ in 0..2000 {
+            let mut source = String::new();
+            for _ in 0..48 {
+                source.push_str(alphabet[rng.below(alphabet.len())]);
+            }
+            assert_fast_scan_agrees(&source);
+        }
+    }
+
     #[test]
     fn test_unterminated_string() {
         // String without closing quote should be an error
@@ -0,0 +1,518 @@
+//! Compact binary encoding of a [`Value`].
+//!
+//! Decoding the binary form is much cheaper than parsing Styx text, which
+//! makes it suitable for caching parsed documents or passing them between
+//! processes. The encoding keeps everything the tree holds: tags, scalar
+//! kinds, spans, doc comments and retained comments.
+//!
+//! The layout is a 4-byte magic `STYX`, a version byte, then the root value.
+//! Lengths and span offsets are LEB128 varints, strings are a length followed
+//! by UTF-8 bytes. It is not meant to be stable across versions of this
+//! crate; data with another version byte is rejected.
+
+use std::fmt;
+
+use styx_parse::{ScalarKind, Span};
+
+use crate::value::{
+    Comment, CommentPlacement, Entry, Object, ObjectOrigin, Payload, Scalar, Sequence, Tag, Value,
+};
+
+const MAGIC: &[u8; 4] = b"STYX";
+const VERSION: u8 = 1;
+
+/// Values nested deeper than this are rejected rather than risking the stack.
+const MAX_DEPTH: usize = 256;
+
+const HAS_TAG: u8 = 1 << 0;
+const HAS_SPAN: u8 = 1 << 1;
+
+const PAYLOAD_NONE: u8 = 0;
+const PAYLOAD_SCALAR: u8 = 1;
+const PAYLOAD_SEQUENCE: u8 = 2;
+const PAYLOAD_OBJECT: u8 = 3;
+/// An object written as attributes; laid out like [`PAYLOAD_OBJECT`].
+const PAYLOAD_ATTRIBUTES: u8 = 4;
+
+/// Error decoding bytes produced by [`to_bytes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data does not start with the `STYX` magic.
+    BadMagic,
+    /// The data was written by an incompatible version of the encoding.
+    UnsupportedVersion(u8),
+    /// The data ends in the middle of a value.
+    UnexpectedEnd,
+    /// A byte at `offset` is not valid at that point.
+    Invalid {
+        /// Byte offset into the input.
+        offset: usize,
+        /// What was wrong.
+        message: &'static str,
+    },
+    /// Values are nested deeper than the decoder allows.
+    TooDeep,
+    /// Bytes remain after the root value.
+    TrailingBytes(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::BadMagic => write!(f, "not a binary styx document"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported binary styx version {}", version)
+            }
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of data"),
+            DecodeError::Invalid { offset, message } => {
+                write!(f, "invalid data at byte {}: {}", offset, message)
+            }
+            DecodeError::TooDeep => write!(f, "values nested more than {} deep", MAX_DEPTH),
+            DecodeError::TrailingBytes(count) => {
+                write!(f, "{} unexpected bytes after the document", count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Encode a value in the compact binary form.
+///
+/// # Example
+///
+/// ```
+/// let doc = styx_tree::parse("server {host localhost, ports (80 443)}").unwrap();
+/// let bytes = styx_tree::to_bytes(&doc);
+/// assert_eq!(styx_tree::from_bytes(&bytes).unwrap(), doc);
+/// ```
+pub fn to_bytes(value: &Value) -> Vec<u8> {
+    let mut out = Vec::with_capacity(64);
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    write_value(&mut out, value);
+    out
+}
+
+/// Decode a value written by [`to_bytes`].
+pub fn from_bytes(bytes: &[u8]) -> Result<Value, DecodeError> {
+    let rest = bytes.strip_prefix(MAGIC).ok_or(DecodeError::BadMagic)?;
+    let mut reader = Reader {
+        bytes,
+        pos: MAGIC.len(),
+        depth: 0,
+    };
+    match rest.first() {
+        Some(&VERSION) => reader.pos += 1,
+        Some(&version) => return Err(DecodeError::UnsupportedVersion(version)),
+        None => return Err(DecodeError::UnexpectedEnd),
+    }
+
+    let value = reader.value()?;
+    match bytes.len() - reader.pos {
+        0 => Ok(value),
+        trailing => Err(DecodeError::TrailingBytes(trailing)),
+    }
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) {
+    let mut flags = 0;
+    if value.tag.is_some() {
+        flags |= HAS_TAG;
+    }
+    if value.span.is_some() {
+        flags |= HAS_SPAN;
+    }
+    out.push(flags);
+    if let Some(span) = value.span {
+        write_span(out, span);
+    }
+    if let Some(tag) = &value.tag {
+        write_str(out, &tag.name);
+        write_opt_span(out, tag.span);
+    }
+
+    match &value.payload {
+        None => out.push(PAYLOAD_NONE),
+        Some(Payload::Scalar(scalar)) => {
+            out.push(PAYLOAD_SCALAR);
+            out.push(match scalar.kind {
+                ScalarKind::Bare => 0,
+                ScalarKind::Quoted => 1,
+                ScalarKind::Raw => 2,
+                ScalarKind::Heredoc => 3,
+            });
+            write_str(out, &scalar.text);
+            write_opt_span(out, scalar.span);
+        }
+        Some(Payload::Sequence(seq)) => {
+            out.push(PAYLOAD_SEQUENCE);
+            write_opt_span(out, seq.span);
+            write_len(out, seq.items.len());
+            for item in &seq.items {
+                write_value(out, item);
+            }
+        }
+        Some(Payload::Object(obj)) => {
+            out.push(match obj.origin {
+                ObjectOrigin::Braces => PAYLOAD_OBJECT,
+                ObjectOrigin::Attributes => PAYLOAD_ATTRIBUTES,
+            });
+            write_opt_span(out, obj.span);
+            write_len(out, obj.entries.len());
+            for entry in &obj.entries {
+                write_entry(out, entry);
+            }
+        }
+    }
+}
+
+fn write_entry(out: &mut Vec<u8>, entry: &Entry) {
+    write_value(out, &entry.key);
+    write_value(out, &entry.value);
+    match &entry.doc_comment {
+        Some(doc) => {
+            out.push(1);
+            write_str(out, doc);
+        }
+        None => out.push(0),
+    }
+    write_len(out, entry.comments.len());
+    for comment in &entry.comments {
+        write_str(out, &comment.text);
+        out.push(match comment.placement {
+            CommentPlacement::Leading => 0,
+            CommentPlacement::Trailing => 1,
+            CommentPlacement::Inline => 2,
+        });
+        write_opt_span(out, comment.span);
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    write_varint(out, len as u64);
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_len(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// Spans are written as start and length, which is usually the shorter pair.
+fn write_span(out: &mut Vec<u8>, span: Span) {
+    write_varint(out, span.start.into());
+    write_varint(out, span.end.saturating_sub(span.start).into());
+}
+
+fn write_opt_span(out: &mut Vec<u8>, span: Option<Span>) {
+    match span {
+        Some(span) => {
+            out.push(1);
+            write_span(out, span);
+        }
+        None => out.push(0),
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn invalid<T>(&self, offset: usize, message: &'static str) -> Result<T, DecodeError> {
+        Err(DecodeError::Invalid { offset, message })
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        let byte = *self.bytes.get(self.pos).ok_or(DecodeError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn varint(&mut self) -> Result<u64, DecodeError> {
+        let start = self.pos;
+        let mut n = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            n |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        self.invalid(start, "varint is too long")
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        let start = self.pos;
+        match u32::try_from(self.varint()?) {
+            Ok(n) => Ok(n),
+            Err(_) => self.invalid(start, "offset does not fit in 32 bits"),
+        }
+    }
+
+    /// A length, checked against the bytes left so a corrupt length cannot
+    /// trigger a huge allocation. Every counted item takes at least one byte.
+    fn len(&mut self) -> Result<usize, DecodeError> {
+        let len = self.varint()?;
+        let remaining = self.bytes.len() - self.pos;
+        match usize::try_from(len) {
+            Ok(len) if len <= remaining => Ok(len),
+            _ => Err(DecodeError::UnexpectedEnd),
+        }
+    }
+
+    fn flag(&mut self) -> Result<bool, DecodeError> {
+        match self.byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => self.invalid(self.pos - 1, "expected 0 or 1"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let len = self.len()?;
+        let start = self.pos;
+        match std::str::from_utf8(self.take(len)?) {
+            Ok(s) => Ok(s.to_string()),
+            Err(_) => self.invalid(start, "string is not valid UTF-8"),
+        }
+    }
+
+    fn span(&mut self) -> Result<Span, DecodeError> {
+        let start_offset = self.pos;
+        let start = self.u32()?;
+        let len = self.u32()?;
+        match start.checked_add(len) {
+            Some(end) => Ok(Span::new(start, end)),
+            None => self.invalid(start_offset, "span ends past 32 bits"),
+        }
+    }
+
+    fn opt_span(&mut self) -> Result<Option<Span>, DecodeError> {
+        if self.flag()? {
+            Ok(Some(self.span()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, DecodeError> {
+        if self.depth == MAX_DEPTH {
+            return Err(DecodeError::TooDeep);
+        }
+        self.depth += 1;
+
+        let flags_offset = self.pos;
+        let flags = self.byte()?;
+        if flags & !(HAS_TAG | HAS_SPAN) != 0 {
+            return self.invalid(flags_offset, "unknown value flags");
+        }
+        let span = if flags & HAS_SPAN != 0 {
+            Some(self.span()?)
+        } else {
+            None
+        };
+        let tag = if flags & HAS_TAG != 0 {
+            Some(Tag {
+                name: self.string()?,
+                span: self.opt_span()?,
+            })
+        } else {
+            None
+        };
+
+        let payload_offset = self.pos;
+        let payload = match self.byte()? {
+            PAYLOAD_NONE => None,
+            PAYLOAD_SCALAR => {
+                let kind = match self.byte()? {
+                    0 => ScalarKind::Bare,
+                    1 => ScalarKind::Quoted,
+                    2 => ScalarKind::Raw,
+                    3 => ScalarKind::Heredoc,
+                    _ => return self.invalid(self.pos - 1, "unknown scalar kind"),
+                };
+                Some(Payload::Scalar(Scalar {
+                    text: self.string()?,
+                    kind,
+                    span: self.opt_span()?,
+                }))
+            }
+            PAYLOAD_SEQUENCE => {
+                let span = self.opt_span()?;
+                let len = self.len()?;
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(self.value()?);
+                }
+                Some(Payload::Sequence(Sequence { items, span }))
+            }
+            kind @ (PAYLOAD_OBJECT | PAYLOAD_ATTRIBUTES) => {
+                let span = self.opt_span()?;
+                let len = self.len()?;
+                let mut entries = Vec::with_capacity(len);
+                for _ in 0..len {
+                    entries.push(self.entry()?);
+                }
+                let origin = if kind == PAYLOAD_ATTRIBUTES {
+                    ObjectOrigin::Attributes
+                } else {
+                    ObjectOrigin::Braces
+                };
+                Some(Payload::Object(Object {
+                    entries,
+                    span,
+                    origin,
+                }))
+            }
+            _ => return self.invalid(payload_offset, "unknown payload kind"),
+        };
+
+        self.depth -= 1;
+        Ok(Value { tag, payload, span })
+    }
+
+    fn entry(&mut self) -> Result<Entry, DecodeError> {
+        let key = self.value()?;
+        let value = self.value()?;
+        let doc_comment = if self.flag()? {
+            Some(self.string()?)
+        } else {
+            None
+        };
+
+        let len = self.len()?;
+        let mut comments = Vec::with_capacity(len);
+        for _ in 0..len {
+            let text = self.string()?;
+            let placement = match self.byte()? {
+                0 => CommentPlacement::Leading,
+                1 => CommentPlacement::Trailing,
+                2 => CommentPlacement::Inline,
+                _ => return self.invalid(self.pos - 1, "unknown comment placement"),
+            };
+            comments.push(Comment {
+                text,
+                placement,
+                span: self.opt_span()?,
+            });
+        }
+
+        Ok(Entry {
+            key,
+            value,
+            doc_comment,
+            comments,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use facet_testhelpers::test;
+
+    const NESTED: &str = r##"/// The server
+server @Server{
+    host "localhost"
+    ports (80 443 @port{alt 8080})
+    // TLS settings
+    tls {cert r#"/etc/cert.pem"#, enabled @}
+}
+schema {@ @object{name @string, tags @seq(@string)}}
+script <<SH
+echo hi
+SH
+empty ()
+labels app>web tier>frontend
+unit @"##;
+
+    #[test]
+    fn test_round_trip_nested_document() {
+        let tree =
+            crate::parse_with(NESTED, crate::ParseOptions::new().retain_comments(true)).unwrap();
+        let bytes = to_bytes(&tree);
+        assert_eq!(from_bytes(&bytes).unwrap(), tree);
+
+        // Values built in code have no spans
+        let mut built = Value::object().with_tag("object");
+        let obj = built.as_object_mut().unwrap();
+        obj.insert("name", Value::tag("string"));
+        obj.insert("items", Value::seq(vec![Value::scalar("a"), Value::unit()]));
+        assert_eq!(from_bytes(&to_bytes(&built)).unwrap(), built);
+    }
+
+    #[test]
+    fn test_rejects_foreign_data() {
+        let bytes = to_bytes(&crate::parse("a 1").unwrap());
+        assert_eq!(from_bytes(b""), Err(DecodeError::BadMagic));
+        assert_eq!(from_bytes(b"a 1"), Err(DecodeError::BadMagic));
+        assert_eq!(from_bytes(b"STYX"), Err(DecodeError::UnexpectedEnd));
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 99;
+        assert_eq!(
+            from_bytes(&wrong_version),
+            Err(DecodeError::UnsupportedVersion(99))
+        );
+
+        let mut trailing = bytes;
+        trailing.push(0);
+        assert_eq!(from_bytes(&trailing), Err(DecodeError::TrailingBytes(1)));
+    }
+
+    #[test]
+    fn test_malformed_bytes_error() {
+        let bytes = to_bytes(
+            &crate::parse_with(NESTED, crate::ParseOptions::new().retain_comments(true)).unwrap(),
+        );
+
+        // Every truncation is an error
+        for len in 0..bytes.len() {
+            assert!(from_bytes(&bytes[..len]).is_err(), "truncated to {len}");
+        }
+
+        // Corrupted bytes either decode to something or error, never panic
+        let mut rng = styx_testhelpers::Rng::default();
+        for _ in 0..2000 {
+            let mut corrupt = bytes.clone();
+            for _ in 0..1 + rng.below(4) {
+                let at = rng.below(corrupt.len());
+                corrupt[at] = rng.next_u64() as u8;
+            }
+            let _ = from_bytes(&corrupt);
+        }
+
+        // Huge lengths and deep nesting are rejected without allocating
+        assert_eq!(
+            from_bytes(b"STYX\x01\x00\x02\x00\xff\xff\xff\xff\x0f"),
+            Err(DecodeError::UnexpectedEnd)
+        );
+        let mut deep = b"STYX\x01".to_vec();
+        for _ in 0..10_000 {
+            deep.extend_from_slice(&[0, PAYLOAD_SEQUENCE, 0, 1]);
+        }
+        assert_eq!(from_bytes(&deep), Err(DecodeError::TooDeep));
+    }
+}
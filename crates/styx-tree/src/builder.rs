@@ -1,10 +1,13 @@
 //! Tree builder from parse events.
 
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
 use styx_parse::{Event, ParseErrorKind, Span};
 
-use crate::value::{Entry, Object, Payload, Scalar, Sequence, Tag, Value};
+use crate::value::{
+    Comment, CommentPlacement, Entry, Object, ObjectOrigin, Payload, Scalar, Sequence, Tag, Value,
+};
 
 /// Error during tree building.
 #[derive(Debug, Clone, PartialEq)]
@@ -17,8 +20,23 @@ pub enum BuildError {
     EmptyDocument,
     /// Parse error from the lexer/parser.
     Parse(ParseErrorKind, Span),
+    /// An anchor name defined twice; the span is the second definition.
+    DuplicateAnchor(String, Span),
+    /// An alias to an anchor the document never defines.
+    UnknownAnchor(String, Span),
+    /// An alias whose expansion reaches itself again.
+    CyclicAnchor(String, Span),
+    /// Alias expansion copied more than [`MAX_ALIAS_NODES`] values; the
+    /// span is the alias that crossed the limit.
+    AliasLimit(Span),
 }
 
+/// Most values a document's aliases may expand to in total, counting the
+/// copies made for aliases inside expanded anchors. Bounds the work done for
+/// documents that alias large values many times, or nest aliases to grow
+/// exponentially.
+pub const MAX_ALIAS_NODES: usize = 1_000_000;
+
 impl std::fmt::Display for BuildError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -28,6 +46,30 @@ impl std::fmt::Display for BuildError {
             BuildError::Parse(kind, span) => {
                 write!(f, "parse error at {}-{}: {}", span.start, span.end, kind)
             }
+            BuildError::DuplicateAnchor(name, span) => {
+                write!(
+                    f,
+                    "anchor &{} defined again at {}-{}",
+                    name, span.start, span.end
+                )
+            }
+            BuildError::UnknownAnchor(name, span) => {
+                write!(f, "unknown anchor *{} at {}-{}", name, span.start, span.end)
+            }
+            BuildError::CyclicAnchor(name, span) => {
+                write!(
+                    f,
+                    "anchor *{} refers to itself at {}-{}",
+                    name, span.start, span.end
+                )
+            }
+            BuildError::AliasLimit(span) => {
+                write!(
+                    f,
+                    "aliases expand to more than {} values, stopped at {}-{}",
+                    MAX_ALIAS_NODES, span.start, span.end
+                )
+            }
         }
     }
 }
@@ -52,13 +94,31 @@ pub struct TreeBuilder {
     root_entries: Vec<Entry>,
     pending_doc_comment: Option<String>,
     errors: Vec<(ParseErrorKind, Span)>,
+    retain_comments: bool,
+    /// Comments waiting for the next root-level entry.
+    pending_comments: Vec<Comment>,
+    /// Root-level comments keyed by the index of the entry they precede.
+    comment_slots: Vec<(usize, Vec<Comment>)>,
+    /// Offsets of the line breaks in the source, when known.
+    line_breaks: Option<Vec<u32>>,
+    /// Key texts seen so far, when interning is enabled.
+    interner: Option<HashSet<String>>,
+    /// Whether `*name` aliases are expanded in [`finish`](Self::finish).
+    expand_aliases: bool,
+    /// Values of the anchors defined so far, by name.
+    anchors: HashMap<String, (Span, Value)>,
+    /// First anchor defined twice, reported by `finish`.
+    duplicate_anchor: Option<(String, Span)>,
 }
 
 enum BuilderFrame {
     Object {
         entries: Vec<Entry>,
         span: Span,
+        origin: ObjectOrigin,
         pending_doc_comment: Option<String>,
+        pending_comments: Vec<Comment>,
+        comment_slots: Vec<(usize, Vec<Comment>)>,
     },
     Sequence {
         items: Vec<Value>,
@@ -68,6 +128,11 @@ enum BuilderFrame {
         name: String,
         span: Span,
     },
+    /// An anchor waiting for the value it names.
+    Anchor {
+        name: String,
+        span: Span,
+    },
     Entry {
         key: Option<Value>,
         doc_comment: Option<String>,
@@ -82,13 +147,105 @@ impl TreeBuilder {
             root_entries: Vec::new(),
             pending_doc_comment: None,
             errors: Vec::new(),
+            retain_comments: false,
+            pending_comments: Vec::new(),
+            comment_slots: Vec::new(),
+            line_breaks: None,
+            interner: None,
+            expand_aliases: false,
+            anchors: HashMap::new(),
+            duplicate_anchor: None,
         }
     }
 
+    /// Retain regular (`//`) comments in the built tree.
+    ///
+    /// Each comment is attached to the entry that follows it in the same
+    /// object, or as a trailing comment of the last entry when nothing
+    /// follows. Comments in objects without entries are dropped.
+    pub fn retain_comments(mut self, retain: bool) -> Self {
+        self.retain_comments = retain;
+        self
+    }
+
+    /// Use `source`, the text being parsed, to find comments on the same
+    /// line as an entry.
+    ///
+    /// With [`retain_comments`](Self::retain_comments), a comment after the
+    /// end of an entry on the same line, like `port 8080 // main port`, is
+    /// attached to that entry as [`CommentPlacement::Inline`]. Without the
+    /// source, every comment is taken to be on a line of its own.
+    pub fn with_source(mut self, source: &str) -> Self {
+        let line_breaks = source.match_indices('\n').map(|(i, _)| i as u32);
+        self.line_breaks = Some(line_breaks.collect());
+        self
+    }
+
+    /// Intern key texts while building.
+    ///
+    /// The builder keeps one copy of each distinct key text and makes every
+    /// key from it, so keys hold exactly the memory their text needs rather
+    /// than whatever the parser allocated while unescaping them. The tree
+    /// still owns each key's `String`. Values are not interned.
+    pub fn with_interner(mut self) -> Self {
+        self.interner = Some(HashSet::new());
+        self
+    }
+
+    /// Expand `*name` aliases to the values of their anchors.
+    ///
+    /// Anchors come from [`EventKind::Anchor`](styx_parse::EventKind::Anchor)
+    /// events, which the parser emits when
+    /// [`Parser::anchors`](styx_parse::Parser::anchors) is enabled. When
+    /// [`finish`](Self::finish) builds the tree, every untagged bare scalar
+    /// `*name` is replaced by a copy of the value anchored as `&name`,
+    /// anywhere in the document, before or after the alias. Aliases inside
+    /// anchored values are expanded too; an alias that reaches itself again
+    /// is a [`BuildError::CyclicAnchor`]. Quoted `"*name"` stays a string.
+    ///
+    /// Off by default, where `*name` stays a scalar.
+    pub fn anchors(mut self, expand: bool) -> Self {
+        self.expand_aliases = expand;
+        self
+    }
+
+    /// Get the text for a key, from the interner if there is one.
+    fn key_text(&mut self, text: Cow<'_, str>) -> String {
+        let Some(interner) = &mut self.interner else {
+            return text.into_owned();
+        };
+        if let Some(interned) = interner.get(text.as_ref()) {
+            return interned.clone();
+        }
+        let interned = text.into_owned();
+        interner.insert(interned.clone());
+        interned
+    }
+
+    /// The entry a comment at `span` is on the same line as: the last entry
+    /// of the enclosing object, if no line break comes between its end and
+    /// the comment.
+    fn inline_comment_entry(&mut self, span: Span) -> Option<&mut Entry> {
+        let line_breaks = self.line_breaks.as_ref()?;
+        let entries = match self.stack.last_mut() {
+            Some(BuilderFrame::Object { entries, .. }) => entries,
+            None => &mut self.root_entries,
+            // Comments inside sequences and unfinished entries
+            Some(_) => return None,
+        };
+        let entry = entries.last_mut()?;
+        let end = entry.value.span.or(entry.key.span)?.end;
+        let next_break = line_breaks.partition_point(|&offset| offset < end);
+        let same_line = line_breaks
+            .get(next_break)
+            .is_none_or(|&offset| offset >= span.start);
+        same_line.then_some(entry)
+    }
+
     /// Finish building and return the root value.
-    pub fn finish(self) -> Result<Value, BuildError> {
+    pub fn finish(mut self) -> Result<Value, BuildError> {
         // Return the first error if any occurred during parsing
-        if let Some((kind, span)) = self.errors.into_iter().next() {
+        if let Some((kind, span)) = self.errors.drain(..).next() {
             return Err(BuildError::Parse(kind, span));
         }
 
@@ -96,15 +253,101 @@ impl TreeBuilder {
             return Err(BuildError::UnclosedStructure);
         }
 
+        if let Some((name, span)) = self.duplicate_anchor.take() {
+            return Err(BuildError::DuplicateAnchor(name, span));
+        }
+
+        match self.build_root() {
+            (root, None) => Ok(root),
+            (_, Some(error)) => Err(error),
+        }
+    }
+
+    /// Finish building, returning the tree built so far along with every
+    /// error, instead of failing at the first one.
+    ///
+    /// The parser recovers from most errors by skipping what it can't read,
+    /// so the tree holds the entries around a bad one. Structures left open
+    /// are closed with what they hold.
+    pub fn finish_recovering(mut self) -> (Value, Vec<BuildError>) {
+        let mut errors: Vec<BuildError> = std::mem::take(&mut self.errors)
+            .into_iter()
+            .map(|(kind, span)| BuildError::Parse(kind, span))
+            .collect();
+
+        if !self.stack.is_empty() {
+            errors.push(BuildError::UnclosedStructure);
+            self.close_open_frames();
+        }
+        if let Some((name, span)) = self.duplicate_anchor.take() {
+            errors.push(BuildError::DuplicateAnchor(name, span));
+        }
+
+        let (root, error) = self.build_root();
+        errors.extend(error);
+        (root, errors)
+    }
+
+    /// End every open structure, innermost first, as if its end event had
+    /// arrived.
+    fn close_open_frames(&mut self) {
+        while let Some(frame) = self.stack.last() {
+            let (kind, span) = match frame {
+                BuilderFrame::Object { span, .. } => (styx_parse::EventKind::ObjectEnd, *span),
+                BuilderFrame::Sequence { span, .. } => (styx_parse::EventKind::SequenceEnd, *span),
+                BuilderFrame::Tag { span, .. } => (styx_parse::EventKind::TagEnd, *span),
+                BuilderFrame::Anchor { span, .. } => (styx_parse::EventKind::Unit, *span),
+                BuilderFrame::Entry { .. } => {
+                    (styx_parse::EventKind::EntryEnd, Span { start: 0, end: 0 })
+                }
+            };
+            let depth = self.stack.len();
+            self.event(Event { span, kind });
+            // Every end event pops its frame; make sure the loop ends even
+            // if one didn't
+            if self.stack.len() >= depth {
+                self.stack.truncate(depth - 1);
+            }
+        }
+    }
+
+    /// The root object, with comments attached and aliases expanded, and the
+    /// error that stopped alias expansion, if any.
+    fn build_root(self) -> (Value, Option<BuildError>) {
+        let mut root_entries = self.root_entries;
+        attach_comments(&mut root_entries, self.comment_slots, self.pending_comments);
+
         // Root is always an implicit object (no tag)
-        Ok(Value {
+        let mut root = Value {
             tag: None,
             payload: Some(Payload::Object(Object {
-                entries: self.root_entries,
+                entries: root_entries,
                 span: None,
+                origin: ObjectOrigin::Braces,
             })),
             span: None,
-        })
+        };
+
+        let mut error = None;
+        if self.expand_aliases {
+            let mut aliases = AliasExpander {
+                anchors: &self.anchors,
+                resolving: Vec::new(),
+                copied: 0,
+            };
+            error = aliases.expand(&mut root).err();
+        }
+
+        (root, error)
+    }
+
+    /// Remember the value an anchor names.
+    fn define_anchor(&mut self, name: String, span: Span, value: &Value) {
+        if self.anchors.contains_key(&name) {
+            self.duplicate_anchor.get_or_insert((name, span));
+            return;
+        }
+        self.anchors.insert(name, (span, value.clone()));
     }
 
     /// Push a value to the current context.
@@ -128,6 +371,15 @@ impl TreeBuilder {
             return;
         }
 
+        // An anchor names the value that follows it, which then completes the entry
+        if let Some(BuilderFrame::Anchor { .. }) = self.stack.last() {
+            if let Some(BuilderFrame::Anchor { name, span }) = self.stack.pop() {
+                self.define_anchor(name, span, &value);
+                self.push_value(value);
+            }
+            return;
+        }
+
         // Check if we're in an Entry frame with a key - if so, this value completes the entry
         if let Some(BuilderFrame::Entry { key: Some(_), .. }) = self.stack.last() {
             // Pop the entry frame and add the complete entry to parent
@@ -139,6 +391,7 @@ impl TreeBuilder {
                             key: key_val,
                             value,
                             doc_comment,
+                            comments: Vec::new(),
                         });
                     }
                     _ => {
@@ -146,6 +399,7 @@ impl TreeBuilder {
                             key: key_val,
                             value,
                             doc_comment,
+                            comments: Vec::new(),
                         });
                     }
                 }
@@ -169,12 +423,13 @@ impl TreeBuilder {
                     key: Value::unit(),
                     value,
                     doc_comment: pending_doc_comment.take(),
+                    comments: Vec::new(),
                 });
             }
             Some(BuilderFrame::Sequence { items, .. }) => {
                 items.push(value);
             }
-            Some(BuilderFrame::Tag { .. }) => {
+            Some(BuilderFrame::Tag { .. } | BuilderFrame::Anchor { .. }) => {
                 // Already handled above
                 unreachable!()
             }
@@ -190,6 +445,7 @@ impl TreeBuilder {
                     key: Value::unit(),
                     value,
                     doc_comment: self.pending_doc_comment.take(),
+                    comments: Vec::new(),
                 });
             }
         }
@@ -212,20 +468,34 @@ impl TreeBuilder {
             }
 
             styx_parse::EventKind::ObjectStart => {
+                // Comments before the root object belong to its first entry
+                let pending_comments = if self.stack.is_empty() {
+                    std::mem::take(&mut self.pending_comments)
+                } else {
+                    Vec::new()
+                };
                 self.stack.push(BuilderFrame::Object {
                     entries: Vec::new(),
                     span,
+                    origin: ObjectOrigin::Braces,
                     pending_doc_comment: None,
+                    pending_comments,
+                    comment_slots: Vec::new(),
                 });
             }
 
             styx_parse::EventKind::ObjectEnd => {
                 if let Some(BuilderFrame::Object {
-                    entries,
+                    mut entries,
                     span: start_span,
+                    origin,
+                    pending_comments,
+                    comment_slots,
                     ..
                 }) = self.stack.pop()
                 {
+                    attach_comments(&mut entries, comment_slots, pending_comments);
+
                     // If stack is now empty, this is the root object
                     if self.stack.is_empty() {
                         self.root_entries = entries;
@@ -238,6 +508,7 @@ impl TreeBuilder {
                                     start: start_span.start,
                                     end: span.end,
                                 }),
+                                origin,
                             })),
                             span: Some(Span {
                                 start: start_span.start,
@@ -283,10 +554,32 @@ impl TreeBuilder {
             styx_parse::EventKind::EntryStart => {
                 let doc_comment = match self.stack.last_mut() {
                     Some(BuilderFrame::Object {
+                        entries,
+                        span: object_span,
+                        origin,
                         pending_doc_comment,
-                        ..
-                    }) => pending_doc_comment.take(),
-                    _ => self.pending_doc_comment.take(),
+                        pending_comments,
+                        comment_slots,
+                    }) => {
+                        // The parser spans attributes from their first key,
+                        // where a braced object's span starts at its `{`
+                        if entries.is_empty() && span.start == object_span.start {
+                            *origin = ObjectOrigin::Attributes;
+                        }
+                        if !pending_comments.is_empty() {
+                            comment_slots.push((entries.len(), std::mem::take(pending_comments)));
+                        }
+                        pending_doc_comment.take()
+                    }
+                    _ => {
+                        if !self.pending_comments.is_empty() {
+                            self.comment_slots.push((
+                                self.root_entries.len(),
+                                std::mem::take(&mut self.pending_comments),
+                            ));
+                        }
+                        self.pending_doc_comment.take()
+                    }
                 };
                 self.stack.push(BuilderFrame::Entry {
                     key: None,
@@ -315,6 +608,7 @@ impl TreeBuilder {
                                 key,
                                 value: Value::unit(),
                                 doc_comment,
+                                comments: Vec::new(),
                             });
                         }
                         _ => {
@@ -331,6 +625,7 @@ impl TreeBuilder {
                                 key,
                                 value: Value::unit(),
                                 doc_comment,
+                                comments: Vec::new(),
                             });
                         }
                     }
@@ -345,7 +640,7 @@ impl TreeBuilder {
                     }),
                     payload: payload.map(|text| {
                         Payload::Scalar(Scalar {
-                            text: cow_to_string(text),
+                            text: self.key_text(text),
                             kind,
                             span: Some(span),
                         })
@@ -361,7 +656,7 @@ impl TreeBuilder {
                 let scalar = Value {
                     tag: None,
                     payload: Some(Payload::Scalar(Scalar {
-                        text: cow_to_string(value),
+                        text: value.into(),
                         kind,
                         span: Some(span),
                     })),
@@ -386,6 +681,7 @@ impl TreeBuilder {
                                 key: key_val,
                                 value: scalar,
                                 doc_comment: doc,
+                                comments: Vec::new(),
                             });
                         }
                         _ => {
@@ -393,6 +689,7 @@ impl TreeBuilder {
                                 key: key_val,
                                 value: scalar,
                                 doc_comment: doc,
+                                comments: Vec::new(),
                             });
                         }
                     }
@@ -428,6 +725,7 @@ impl TreeBuilder {
                                 key: key_val,
                                 value: unit,
                                 doc_comment: doc,
+                                comments: Vec::new(),
                             });
                         }
                         _ => {
@@ -435,6 +733,7 @@ impl TreeBuilder {
                                 key: key_val,
                                 value: unit,
                                 doc_comment: doc,
+                                comments: Vec::new(),
                             });
                         }
                     }
@@ -455,6 +754,13 @@ impl TreeBuilder {
                 });
             }
 
+            styx_parse::EventKind::Anchor { name } => {
+                self.stack.push(BuilderFrame::Anchor {
+                    name: name.into_owned(),
+                    span,
+                });
+            }
+
             styx_parse::EventKind::TagEnd => {
                 // Only pop if the top frame is a Tag - otherwise the tag was already
                 // consumed when its payload was processed
@@ -486,6 +792,7 @@ impl TreeBuilder {
                                     key: key_val,
                                     value: tagged,
                                     doc_comment: doc,
+                                    comments: Vec::new(),
                                 });
                             }
                             _ => {
@@ -493,6 +800,7 @@ impl TreeBuilder {
                                     key: key_val,
                                     value: tagged,
                                     doc_comment: doc,
+                                    comments: Vec::new(),
                                 });
                             }
                         }
@@ -523,8 +831,31 @@ impl TreeBuilder {
                 }
             }
 
-            styx_parse::EventKind::Comment { .. } => {
-                // Ignore regular comments for tree building
+            styx_parse::EventKind::Comment { text } => {
+                if !self.retain_comments {
+                    return;
+                }
+                let mut comment = Comment {
+                    text: text.to_string(),
+                    placement: CommentPlacement::Leading,
+                    span: Some(span),
+                };
+                if let Some(entry) = self.inline_comment_entry(span) {
+                    comment.placement = CommentPlacement::Inline;
+                    entry.comments.push(comment);
+                    return;
+                }
+                // Comments belong to the innermost enclosing object
+                let target = self.stack.iter_mut().rev().find_map(|frame| match frame {
+                    BuilderFrame::Object {
+                        pending_comments, ..
+                    } => Some(pending_comments),
+                    _ => None,
+                });
+                match target {
+                    Some(pending_comments) => pending_comments.push(comment),
+                    None => self.pending_comments.push(comment),
+                }
             }
 
             styx_parse::EventKind::Error { kind } => {
@@ -534,8 +865,213 @@ impl TreeBuilder {
     }
 }
 
-fn cow_to_string(cow: Cow<'_, str>) -> String {
-    cow.into_owned()
+/// Builds a document's root entries one at a time.
+///
+/// Wraps the parser events of a whole document and yields each root entry,
+/// together with its doc comment, as a single-entry object as soon as the
+/// entry is complete. Callers that process a document entry by entry, such
+/// as streaming validation, never hold more than one entry's tree.
+///
+/// Stops with an error at the first syntax error, or at the end of the
+/// events if an entry is left open.
+///
+/// ```
+/// use styx_parse::Parser;
+/// use styx_tree::RootEntries;
+///
+/// let mut parser = Parser::new("host localhost\nport 8080");
+/// let entries = RootEntries::new(std::iter::from_fn(|| parser.next_event()))
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(entries.len(), 2);
+/// assert_eq!(entries[1].get("port").and_then(|v| v.as_str()), Some("8080"));
+/// ```
+pub struct RootEntries<I> {
+    events: I,
+    depth: usize,
+    entry_builder: Option<TreeBuilder>,
+    done: bool,
+}
+
+impl<'src, I: Iterator<Item = Event<'src>>> RootEntries<I> {
+    /// Read root entries from a document's events.
+    pub fn new(events: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            events: events.into_iter(),
+            depth: 0,
+            entry_builder: None,
+            done: false,
+        }
+    }
+}
+
+impl<'src, I: Iterator<Item = Event<'src>>> Iterator for RootEntries<I> {
+    type Item = Result<Value, BuildError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        for event in self.events.by_ref() {
+            if let styx_parse::EventKind::Error { kind } = event.kind {
+                self.done = true;
+                return Some(Err(BuildError::Parse(kind, event.span)));
+            }
+
+            let at_root = self.depth == 1;
+            match &event.kind {
+                styx_parse::EventKind::ObjectStart | styx_parse::EventKind::SequenceStart => {
+                    self.depth += 1
+                }
+                styx_parse::EventKind::ObjectEnd | styx_parse::EventKind::SequenceEnd => {
+                    self.depth = self.depth.saturating_sub(1)
+                }
+                _ => {}
+            }
+
+            match (&event.kind, self.entry_builder.as_mut()) {
+                // Doc comments and the entry itself start a new root entry
+                (
+                    styx_parse::EventKind::DocComment { .. } | styx_parse::EventKind::EntryStart,
+                    None,
+                ) if at_root => {
+                    let mut builder = TreeBuilder::new();
+                    builder.event(event);
+                    self.entry_builder = Some(builder);
+                }
+                (styx_parse::EventKind::EntryEnd, Some(builder)) if at_root => {
+                    builder.event(event);
+                    let entry = self.entry_builder.take().unwrap().finish();
+                    self.done = entry.is_err();
+                    return Some(entry);
+                }
+                (_, Some(builder)) => builder.event(event),
+                (_, None) => {}
+            }
+        }
+
+        self.done = true;
+        self.entry_builder
+            .take()
+            .map(|_| Err(BuildError::UnclosedStructure))
+    }
+}
+
+/// Replaces `*name` aliases with copies of their anchored values.
+struct AliasExpander<'a> {
+    anchors: &'a HashMap<String, (Span, Value)>,
+    /// Anchors whose values are being expanded, innermost last.
+    resolving: Vec<&'a str>,
+    /// Values copied for aliases so far.
+    copied: usize,
+}
+
+impl<'a> AliasExpander<'a> {
+    fn expand(&mut self, value: &mut Value) -> Result<(), BuildError> {
+        if let Some((name, span)) = alias_name(value) {
+            let Some((anchor_name, (_, anchored))) = self.anchors.get_key_value(name) else {
+                return Err(BuildError::UnknownAnchor(name.to_string(), span));
+            };
+            if self.resolving.contains(&anchor_name.as_str()) {
+                return Err(BuildError::CyclicAnchor(name.to_string(), span));
+            }
+            self.copied += node_count(anchored);
+            if self.copied > MAX_ALIAS_NODES {
+                return Err(BuildError::AliasLimit(span));
+            }
+
+            let mut expansion = anchored.clone();
+            self.resolving.push(anchor_name);
+            self.expand(&mut expansion)?;
+            self.resolving.pop();
+            *value = expansion;
+            return Ok(());
+        }
+
+        match &mut value.payload {
+            Some(Payload::Sequence(seq)) => {
+                for item in &mut seq.items {
+                    self.expand(item)?;
+                }
+            }
+            Some(Payload::Object(obj)) => {
+                for entry in &mut obj.entries {
+                    self.expand(&mut entry.value)?;
+                }
+            }
+            Some(Payload::Scalar(_)) | None => {}
+        }
+        Ok(())
+    }
+}
+
+/// Number of values in `value`'s subtree, including itself and object keys.
+fn node_count(value: &Value) -> usize {
+    1 + match &value.payload {
+        Some(Payload::Sequence(seq)) => seq.items.iter().map(node_count).sum(),
+        Some(Payload::Object(obj)) => obj
+            .entries
+            .iter()
+            .map(|entry| node_count(&entry.key) + node_count(&entry.value))
+            .sum(),
+        Some(Payload::Scalar(_)) | None => 0,
+    }
+}
+
+/// The anchor name and span of an alias: an untagged bare scalar `*name`.
+///
+/// Names follow the rules of tag names, so globs such as `*.rs` are not
+/// aliases.
+fn alias_name(value: &Value) -> Option<(&str, Span)> {
+    if value.tag.is_some() {
+        return None;
+    }
+    let Some(Payload::Scalar(scalar)) = &value.payload else {
+        return None;
+    };
+    if scalar.kind != styx_parse::ScalarKind::Bare {
+        return None;
+    }
+    let name = scalar.text.strip_prefix('*')?;
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return None;
+    }
+    Some((name, scalar.span.unwrap_or(Span::new(0, 0))))
+}
+
+/// Attach collected comments to the entries they were recorded against.
+///
+/// `slots` pairs an entry index with the comments seen just before it;
+/// `trailing` holds comments seen after the last entry.
+fn attach_comments(
+    entries: &mut [Entry],
+    slots: Vec<(usize, Vec<Comment>)>,
+    trailing: Vec<Comment>,
+) {
+    for (index, comments) in slots {
+        if let Some(entry) = entries.get_mut(index) {
+            entry.comments.extend(comments);
+        } else if let Some(last) = entries.last_mut() {
+            last.comments
+                .extend(comments.into_iter().map(into_trailing));
+        }
+    }
+    if let Some(last) = entries.last_mut() {
+        last.comments
+            .extend(trailing.into_iter().map(into_trailing));
+    }
+}
+
+fn into_trailing(comment: Comment) -> Comment {
+    Comment {
+        placement: CommentPlacement::Trailing,
+        ..comment
+    }
 }
 
 /// Append a doc comment line to an existing doc comment, joining with newline.
@@ -597,6 +1133,43 @@ mod tests {
         assert_eq!(obj.get("age").and_then(|v| v.as_str()), Some("30"));
     }
 
+    #[test]
+    fn test_leading_bom() {
+        let value = parse("\u{feff}name Alice\nage 30");
+        let obj = value.as_object().unwrap();
+        assert_eq!(obj.get("name").and_then(|v| v.as_str()), Some("Alice"));
+        assert_eq!(obj.entries[0].key.span.map(|s| s.start), Some(3));
+    }
+
+    #[test]
+    fn test_url_like_keys() {
+        let value = parse("a:b 1\nhttp://x 2\ncrate:pkg@2 3");
+        let obj = value.as_object().unwrap();
+        assert_eq!(obj.get("a:b").and_then(|v| v.as_str()), Some("1"));
+        assert_eq!(obj.get("http://x").and_then(|v| v.as_str()), Some("2"));
+        assert_eq!(obj.get("crate:pkg@2").and_then(|v| v.as_str()), Some("3"));
+
+        // `>` separates attributes, so keys containing it must be quoted
+        assert!(try_parse("a>b value").is_err());
+        let value = parse("\"a>b\" value");
+        assert_eq!(value.get("a>b").and_then(|v| v.as_str()), Some("value"));
+    }
+
+    #[test]
+    fn test_namespaced_tag() {
+        let mut parser = Parser::new("query @sql:Query{text hi}").namespaced_tags(true);
+        let mut builder = TreeBuilder::new();
+        while let Some(event) = parser.next_event() {
+            builder.event(event);
+        }
+        let value = builder.finish().unwrap();
+
+        let tag = value.get("query").and_then(|v| v.tag.as_ref()).unwrap();
+        assert_eq!(tag.name, "sql:Query");
+        assert_eq!(tag.split_name().namespace, Some("sql"));
+        assert_eq!(tag.split_name().name, "Query");
+    }
+
     #[test]
     fn test_path_access() {
         let value = parse("name Alice\nage 30");
@@ -654,6 +1227,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_object_origin() {
+        let value = parse("server host>localhost opts>{verbose true}\nlimits {cpu 2}");
+        let root = value.as_object().unwrap();
+        assert_eq!(root.origin, ObjectOrigin::Braces);
+
+        let server = root.get("server").unwrap().as_object().unwrap();
+        assert_eq!(server.origin, ObjectOrigin::Attributes);
+        assert_eq!(
+            server.get("host").and_then(|v| v.as_str()),
+            Some("localhost")
+        );
+        let opts = server.get("opts").unwrap().as_object().unwrap();
+        assert_eq!(opts.origin, ObjectOrigin::Braces);
+
+        let limits = root.get("limits").unwrap().as_object().unwrap();
+        assert_eq!(limits.origin, ObjectOrigin::Braces);
+    }
+
     #[test]
     fn test_tag_with_sequence_payload() {
         let value = parse("color @rgb(255 128 0)");
@@ -779,4 +1371,246 @@ value 42"#;
             "doc comment should contain all lines joined by newline"
         );
     }
+
+    fn parse_with_comments(source: &str) -> Value {
+        let mut parser = Parser::new(source);
+        let mut builder = TreeBuilder::new().retain_comments(true).with_source(source);
+        while let Some(event) = parser.next_event() {
+            builder.event(event);
+        }
+        builder.finish().unwrap()
+    }
+
+    fn comment_texts<'a>(comments: impl Iterator<Item = &'a Comment>) -> Vec<&'a str> {
+        comments.map(|c| c.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_comments_dropped_by_default() {
+        let value = parse("// about a\na 1");
+        let obj = value.as_object().unwrap();
+        assert!(obj.entries[0].comments.is_empty());
+    }
+
+    #[test]
+    fn test_standalone_comment_attached_to_following_entry() {
+        let source = r#"a 1
+// about b
+// more about b
+b 2"#;
+        let value = parse_with_comments(source);
+        let obj = value.as_object().unwrap();
+        assert_eq!(obj.entries.len(), 2);
+        assert_eq!(comment_texts(obj.entries[0].comments()), Vec::<&str>::new());
+        assert_eq!(
+            comment_texts(obj.entries[1].comments()),
+            vec!["// about b", "// more about b"]
+        );
+        let span = obj.entries[1].comments[0].span.unwrap();
+        assert_eq!(
+            &source[span.start as usize..span.end as usize],
+            "// about b"
+        );
+    }
+
+    #[test]
+    fn test_comment_before_first_entry() {
+        let value = parse_with_comments("// header\nname hello");
+        let obj = value.as_object().unwrap();
+        assert_eq!(comment_texts(obj.entries[0].comments()), vec!["// header"]);
+    }
+
+    #[test]
+    fn test_comments_in_nested_object() {
+        let source = r#"server {
+    // the host
+    host localhost
+    // end of server
+}
+// end of file"#;
+        let value = parse_with_comments(source);
+        let obj = value.as_object().unwrap();
+        let server = &obj.entries[0];
+        assert_eq!(
+            comment_texts(server.trailing_comments()),
+            vec!["// end of file"]
+        );
+
+        let inner = server.value.as_object().unwrap();
+        assert_eq!(
+            comment_texts(inner.entries[0].comments()),
+            vec!["// the host"]
+        );
+        assert_eq!(
+            comment_texts(inner.entries[0].trailing_comments()),
+            vec!["// end of server"]
+        );
+    }
+
+    #[test]
+    fn test_inline_comment_attached_to_its_entry() {
+        let source = r#"port 8080 // main port
+// about host
+host localhost
+server {
+    tls true // always
+    timeout 5
+} // end of server"#;
+        let value = parse_with_comments(source);
+        let obj = value.as_object().unwrap();
+
+        let port = &obj.entries[0];
+        assert_eq!(
+            port.inline_comment().map(|c| c.text.as_str()),
+            Some("// main port")
+        );
+        assert_eq!(comment_texts(port.comments()), Vec::<&str>::new());
+
+        let host = &obj.entries[1];
+        assert_eq!(host.inline_comment(), None);
+        assert_eq!(comment_texts(host.comments()), vec!["// about host"]);
+
+        let server = &obj.entries[2];
+        assert_eq!(
+            server.inline_comment().map(|c| c.text.as_str()),
+            Some("// end of server")
+        );
+        assert_eq!(
+            comment_texts(server.trailing_comments()),
+            Vec::<&str>::new()
+        );
+        let inner = server.value.as_object().unwrap();
+        assert_eq!(
+            inner.entries[0].inline_comment().map(|c| c.text.as_str()),
+            Some("// always")
+        );
+        assert_eq!(inner.entries[1].inline_comment(), None);
+    }
+
+    #[test]
+    fn test_inline_comment_needs_source() {
+        let mut parser = Parser::new(
+            "a 1 // about a
+b 2",
+        );
+        let mut builder = TreeBuilder::new().retain_comments(true);
+        while let Some(event) = parser.next_event() {
+            builder.event(event);
+        }
+        let value = builder.finish().unwrap();
+        let obj = value.as_object().unwrap();
+        assert_eq!(obj.entries[0].inline_comment(), None);
+        assert_eq!(comment_texts(obj.entries[1].comments()), vec!["// about a"]);
+    }
+
+    #[test]
+    fn test_comment_with_doc_comment() {
+        let source = r#"// regular
+/// documented
+key value"#;
+        let value = parse_with_comments(source);
+        let entry = &value.as_object().unwrap().entries[0];
+        assert_eq!(entry.doc_comment.as_deref(), Some("documented"));
+        assert_eq!(comment_texts(entry.comments()), vec!["// regular"]);
+    }
+
+    #[test]
+    fn test_interner_keeps_one_copy_of_each_key() {
+        let mut source = String::from("records (\n");
+        for i in 0..1000 {
+            source.push_str(&format!("    {{id {i}, name record_{i}, active true}}\n"));
+        }
+        source.push(')');
+
+        let feed = |builder: &mut TreeBuilder| {
+            let mut parser = Parser::new(&source);
+            while let Some(event) = parser.next_event() {
+                builder.event(event);
+            }
+        };
+        let mut plain = TreeBuilder::new();
+        feed(&mut plain);
+        let mut interning = TreeBuilder::new().with_interner();
+        feed(&mut interning);
+
+        // The record keys plus `records` itself
+        assert_eq!(interning.interner.as_ref().map(HashSet::len), Some(4));
+
+        let interned = interning.finish().unwrap();
+        assert_eq!(interned, plain.finish().unwrap());
+        assert_eq!(
+            interned.get("records[500].name").and_then(Value::as_str),
+            Some("record_500")
+        );
+        assert_eq!(
+            interned.get("records[999].id").and_then(Value::as_str),
+            Some("999")
+        );
+    }
+
+    #[test]
+    fn test_recovering_keeps_good_entries() {
+        let (value, errors) = crate::parse_recovering("host localhost\nport 80 80\nname app");
+        assert!(
+            matches!(
+                errors[..],
+                [BuildError::Parse(ParseErrorKind::TooManyAtoms, _)]
+            ),
+            "{errors:?}"
+        );
+        assert_eq!(
+            value.get("host").and_then(|v| v.as_str()),
+            Some("localhost")
+        );
+        assert_eq!(value.get("name").and_then(|v| v.as_str()), Some("app"));
+
+        let (value, errors) = crate::parse_recovering("host localhost\nserver {port 8080");
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert_eq!(
+            value.get("server.port").and_then(|v| v.as_str()),
+            Some("8080")
+        );
+
+        let (_, errors) = crate::parse_recovering("host localhost");
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_recovering_closes_open_structures() {
+        use styx_parse::{Event, EventKind, ScalarKind};
+
+        let span = Span { start: 0, end: 0 };
+        let mut builder = TreeBuilder::new();
+        for kind in [
+            EventKind::DocumentStart,
+            EventKind::ObjectStart,
+            EventKind::EntryStart,
+            EventKind::Key {
+                tag: None,
+                payload: Some("server".into()),
+                kind: ScalarKind::Bare,
+            },
+            EventKind::ObjectStart,
+            EventKind::EntryStart,
+            EventKind::Key {
+                tag: None,
+                payload: Some("ports".into()),
+                kind: ScalarKind::Bare,
+            },
+            EventKind::SequenceStart,
+            EventKind::Scalar {
+                value: "80".into(),
+                kind: ScalarKind::Bare,
+            },
+        ] {
+            builder.event(Event { span, kind });
+        }
+
+        let (value, errors) = builder.finish_recovering();
+        assert_eq!(errors, [BuildError::UnclosedStructure]);
+        assert_eq!(
+            value.get("server.ports[0]").and_then(|v| v.as_str()),
+            Some("80")
+        );
+    }
 }
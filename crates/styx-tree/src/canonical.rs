@@ -0,0 +1,332 @@
+//! Canonical forms of values, for comparing and hashing documents.
+//!
+//! Styx scalars are untyped and the same document can be written many ways:
+//! in any key order, with or without quotes, in attribute or brace syntax,
+//! and with numbers spelled `0x10`, `1_6` or `+16`. The canonical form keeps
+//! only what those spellings mean.
+
+use crate::ScalarKind;
+use crate::value::{Entry, Payload, Value};
+
+/// Canonical spelling of a number scalar, or `None` if `s` is not a number.
+///
+/// Integers in any base become plain decimal without underscores, leading
+/// zeros or a `+` sign (`0xFF_FF` → `65535`, `+007` → `7`). Floats become the
+/// shortest text that reads back as the same `f64` (`1.50` → `1.5`,
+/// `6_022E20` → `6.022e23`), and `+inf` becomes `inf`. Integers that don't fit
+/// in 128 bits and floats that overflow are left alone.
+///
+/// Styx scalars are untyped, so this assumes a number-like scalar is meant as
+/// a number: a string field holding `0x10` would be changed.
+pub fn canonical_number(s: &str) -> Option<String> {
+    let (sign, unsigned) = match s.as_bytes().first()? {
+        b'-' => ("-", &s[1..]),
+        b'+' => ("", &s[1..]),
+        _ => ("", s),
+    };
+
+    if let Some(magnitude) = parse_unsigned_int(unsigned) {
+        let sign = if magnitude == 0 { "" } else { sign };
+        return Some(format!("{sign}{magnitude}"));
+    }
+    match (s, unsigned) {
+        ("nan", _) => return Some("nan".into()),
+        (_, "inf") => return Some(format!("{sign}inf")),
+        _ => {}
+    }
+
+    let digits = strip_underscores(unsigned, 10)?;
+    if !is_float_syntax(&digits) {
+        return None;
+    }
+    let value: f64 = digits.parse().ok()?;
+    value.is_finite().then(|| format!("{sign}{value:?}"))
+}
+
+/// A number scalar that is already spelled canonically, see
+/// [`parse_canonical_number`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CanonicalNumber {
+    /// An integer that fits in an `i64`.
+    Int(i64),
+    /// A positive integer too large for an `i64` that fits in a `u64`.
+    UInt(u64),
+    /// A finite float.
+    Float(f64),
+}
+
+/// The number `s` spells, if `s` is its own [`canonical_number`] and fits in
+/// 64 bits.
+///
+/// For converting untyped scalars to typed formats like JSON without losing
+/// text: `8080`, `-1` and `1.5` are numbers, but `01234`, `+5`, `1e3`, `0x10`,
+/// `inf` and integers past `u64::MAX` would not read back the way they were
+/// written, so they stay strings.
+pub fn parse_canonical_number(s: &str) -> Option<CanonicalNumber> {
+    if canonical_number(s).as_deref() != Some(s) {
+        return None;
+    }
+    if let Ok(n) = s.parse() {
+        return Some(CanonicalNumber::Int(n));
+    }
+    if let Ok(n) = s.parse() {
+        return Some(CanonicalNumber::UInt(n));
+    }
+    if s.bytes().all(|b| b == b'-' || b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse()
+        .ok()
+        .filter(|f: &f64| f.is_finite())
+        .map(CanonicalNumber::Float)
+}
+
+/// Parse an unsigned integer in any of the Styx bases.
+fn parse_unsigned_int(s: &str) -> Option<u128> {
+    let (radix, digits) = match s.get(..2) {
+        Some("0x" | "0X") => (16, &s[2..]),
+        Some("0o" | "0O") => (8, &s[2..]),
+        Some("0b" | "0B") => (2, &s[2..]),
+        _ => (10, s),
+    };
+    let digits = strip_underscores(digits, radix)?;
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    u128::from_str_radix(&digits, radix).ok()
+}
+
+/// `s` without underscores, or `None` if an underscore isn't between two digits.
+fn strip_underscores(s: &str, radix: u32) -> Option<String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = String::with_capacity(s.len());
+    for (i, &c) in chars.iter().enumerate() {
+        if c != '_' {
+            out.push(c);
+            continue;
+        }
+        let is_digit = |j: Option<usize>| {
+            j.and_then(|j| chars.get(j))
+                .is_some_and(|c| c.is_digit(radix))
+        };
+        if !is_digit(i.checked_sub(1)) || !is_digit(Some(i + 1)) {
+            return None;
+        }
+    }
+    Some(out)
+}
+
+/// Whether `s` (without sign or underscores) is a float literal with a
+/// fractional part, an exponent, or both.
+fn is_float_syntax(s: &str) -> bool {
+    let (mantissa, exponent) = match s.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => {
+            let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            (mantissa, Some(exponent))
+        }
+        None => (s, None),
+    };
+    let (int, frac) = match mantissa.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (mantissa, None),
+    };
+    let all_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    all_digits(int)
+        && frac.is_none_or(all_digits)
+        && exponent.is_none_or(all_digits)
+        && (frac.is_some() || exponent.is_some())
+}
+
+impl Value {
+    /// A BLAKE3 hash of the value's canonical form, for content-addressing
+    /// and change detection.
+    ///
+    /// Equivalent documents hash identically: object keys are sorted,
+    /// bare numbers are compared by [`canonical_number`], and quoting, attribute
+    /// syntax, spans, comments and doc comments are ignored. Tags, sequence
+    /// order and everything else that changes the meaning of a document
+    /// change the hash.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let a = styx_tree::parse("port 0x1F90\nhost localhost").unwrap();
+    /// let b = styx_tree::parse("host \"localhost\"\nport 8080").unwrap();
+    /// assert_eq!(a.canonical_hash(), b.canonical_hash());
+    /// ```
+    pub fn canonical_hash(&self) -> [u8; 32] {
+        let mut out = Vec::new();
+        write_canonical(&mut out, self);
+        blake3::hash(&out).into()
+    }
+}
+
+/// Write the canonical form of `value`: the tag, then a payload byte and
+/// its contents, with strings and counts length-prefixed so no two values
+/// share an encoding.
+fn write_canonical(out: &mut Vec<u8>, value: &Value) {
+    match &value.tag {
+        Some(tag) => {
+            out.push(1);
+            write_str(out, &tag.name);
+        }
+        None => out.push(0),
+    }
+    match &value.payload {
+        None => out.push(0),
+        Some(Payload::Scalar(scalar)) => {
+            out.push(1);
+            // A quoted `"007"` is a string, not the number 7
+            let number = match scalar.kind {
+                ScalarKind::Bare => canonical_number(&scalar.text),
+                _ => None,
+            };
+            write_str(out, number.as_deref().unwrap_or(&scalar.text));
+        }
+        Some(Payload::Sequence(seq)) => {
+            out.push(2);
+            write_len(out, seq.items.len());
+            for item in &seq.items {
+                write_canonical(out, item);
+            }
+        }
+        Some(Payload::Object(obj)) => {
+            out.push(3);
+            write_len(out, obj.entries.len());
+            // Each entry starts with its key, so sorting the encoded entries
+            // sorts them by key
+            let mut entries: Vec<Vec<u8>> = obj.entries.iter().map(canonical_entry).collect();
+            entries.sort();
+            for entry in entries {
+                out.extend(entry);
+            }
+        }
+    }
+}
+
+fn canonical_entry(entry: &Entry) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_canonical(&mut out, &entry.key);
+    write_canonical(&mut out, &entry.value);
+    out
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    out.extend((len as u64).to_le_bytes());
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_len(out, s.len());
+    out.extend(s.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_number() {
+        let cases = [
+            ("8080", "8080"),
+            ("+007", "7"),
+            ("-0", "0"),
+            ("1_000_000", "1000000"),
+            ("0xFF_FF", "65535"),
+            ("-0x10", "-16"),
+            ("0o755", "493"),
+            ("0b1111_0000", "240"),
+            ("1.50", "1.5"),
+            ("1.0", "1.0"),
+            ("6_022E20", "6.022e23"),
+            ("3.141_592", "3.141592"),
+            ("1e0", "1.0"),
+            ("+inf", "inf"),
+            ("-inf", "-inf"),
+            ("nan", "nan"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                canonical_number(input).as_deref(),
+                Some(expected),
+                "{input}"
+            );
+        }
+
+        for input in [
+            "", "-", "abc", "1_", "_1", "1__0", "0x", "0x_1", "0b102", "1.", ".5", "1e", "1.5e_3",
+            "1e999", "Inf", "-nan", "1.2.3", "12ab",
+        ] {
+            assert_eq!(canonical_number(input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_canonical_number() {
+        use CanonicalNumber::*;
+        let cases = [
+            ("8080", Int(8080)),
+            ("-1", Int(-1)),
+            ("18446744073709551615", UInt(u64::MAX)),
+            ("1.5", Float(1.5)),
+            ("1.0", Float(1.0)),
+            ("6.022e23", Float(6.022e23)),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_canonical_number(input), Some(expected), "{input}");
+        }
+
+        for input in [
+            "01234", "+5", "-0", "1_000", "0x10", "1.50", "1e3", "1e0", "inf", "-inf", "nan", "abc",
+        ] {
+            assert_eq!(parse_canonical_number(input), None, "{input}");
+        }
+        let too_big = (u64::MAX as u128 + 1).to_string();
+        assert_eq!(parse_canonical_number(&too_big), None);
+    }
+
+    #[test]
+    fn test_canonical_hash_ignores_formatting() {
+        let a = crate::parse(
+            "@schema app.styx\nname \"my-app\"\nport 0x1F90\nratio 0.50\nserver {tls true, host localhost}\ntags (a \"b\")",
+        )
+        .unwrap();
+        let b = crate::parse(
+            "// reordered, requoted, respelled\n\
+             tags (\"a\" b)\n\
+             server host>localhost tls>true\n\
+             /// The port\n\
+             port +8_080\n\
+             ratio 5e-1\n\
+             name my-app\n\
+             @schema app.styx\n",
+        )
+        .unwrap();
+        assert_eq!(a.canonical_hash(), b.canonical_hash());
+    }
+
+    #[test]
+    fn test_canonical_hash_keeps_quoted_numbers_as_strings() {
+        let hash = |source| crate::parse(source).unwrap().canonical_hash();
+        assert_eq!(hash("a \"8080\""), hash("a 8080"));
+        assert_ne!(hash("a \"007\""), hash("a 7"));
+        assert_eq!(hash("a 007"), hash("a 7"));
+    }
+
+    #[test]
+    fn test_canonical_hash_detects_changes() {
+        let hash = |source: &str| crate::parse(source).unwrap().canonical_hash();
+        let base = hash("name app\nport 8080\ntags (a b)");
+
+        for changed in [
+            "name app\nport 8081\ntags (a b)",
+            "name app\nport 8080\ntags (b a)",
+            "name app\nport 8080\ntags (a b c)",
+            "name app\nport @int(8080)\ntags (a b)",
+            "name app\nport {8080 @}\ntags (a b)",
+            "name app\nport 8080",
+            "name \"app port\"\ntags (a b)",
+        ] {
+            assert_ne!(hash(changed), base, "{changed}");
+        }
+    }
+}
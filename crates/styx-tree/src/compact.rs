@@ -0,0 +1,490 @@
+//! Compact, read-only representation of a parsed document.
+//!
+//! [`Value`] is a tree of individually allocated nodes, which makes it easy to
+//! build and edit. [`CompactValue`] stores the same document as one flat array
+//! of nodes in document order plus a single text buffer, so it needs far fewer
+//! allocations and lookups walk contiguous memory.
+//!
+//! Use [`Value`] when the document is edited, re-serialized, or needs its
+//! comments. Use [`CompactValue`] for large documents that are parsed once and
+//! then only read, such as configuration loaded at startup and queried by path.
+//! Comments and doc comments are not kept.
+
+use styx_parse::{Parser, ScalarKind, Span};
+
+use crate::builder::{BuildError, RootEntries};
+use crate::value::{
+    Entry, Object, ObjectOrigin, Payload, Scalar, Sequence, Tag, Value, split_path,
+};
+
+/// A parsed document stored as a flat array of nodes.
+///
+/// Nodes are laid out in document order: every node is followed by its
+/// children, and object children alternate key, value. Navigate it through
+/// [`CompactRef`] handles.
+///
+/// # Example
+///
+/// ```
+/// use styx_tree::CompactValue;
+///
+/// let doc = CompactValue::parse("server {host localhost, ports (80 443)}").unwrap();
+/// assert_eq!(doc.get("server.host").and_then(|v| v.as_str()), Some("localhost"));
+/// assert_eq!(doc.get("server.ports[1]").and_then(|v| v.as_str()), Some("443"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactValue {
+    nodes: Vec<Node>,
+    /// Text of all scalars and tag names, back to back.
+    text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Node {
+    tag: Option<CompactTag>,
+    kind: NodeKind,
+    /// Number of nodes in this subtree, including this one.
+    size: u32,
+    span: Option<Span>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CompactTag {
+    name: TextRange,
+    span: Option<Span>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NodeKind {
+    Unit,
+    Scalar {
+        text: TextRange,
+        kind: ScalarKind,
+    },
+    Sequence {
+        len: u32,
+    },
+    /// `len` entries, stored as `2 * len` children.
+    Object {
+        len: u32,
+        origin: ObjectOrigin,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TextRange {
+    start: u32,
+    len: u32,
+}
+
+impl CompactValue {
+    /// Parse a Styx document straight into the compact representation.
+    ///
+    /// Root entries are built one at a time and flattened as soon as they
+    /// are complete, so the full [`Value`] tree never exists in memory.
+    pub fn parse(source: &str) -> Result<Self, BuildError> {
+        let mut compact = CompactValue {
+            nodes: Vec::new(),
+            text: String::new(),
+        };
+        compact.nodes.push(Node {
+            tag: None,
+            kind: NodeKind::Object {
+                len: 0,
+                origin: ObjectOrigin::Braces,
+            },
+            size: 1,
+            span: None,
+        });
+
+        let mut root_len = 0;
+        let mut parser = Parser::new(source);
+        for entry_value in RootEntries::new(std::iter::from_fn(|| parser.next_event())) {
+            if let Some(obj) = entry_value?.as_object() {
+                for entry in &obj.entries {
+                    compact.push_value(&entry.key);
+                    compact.push_value(&entry.value);
+                    root_len += 1;
+                }
+            }
+        }
+
+        compact.nodes[0].kind = NodeKind::Object {
+            len: root_len,
+            origin: ObjectOrigin::Braces,
+        };
+        compact.nodes[0].size = compact.nodes.len() as u32;
+        Ok(compact)
+    }
+
+    /// Get the root value.
+    pub fn root(&self) -> CompactRef<'_> {
+        CompactRef {
+            value: self,
+            index: 0,
+        }
+    }
+
+    /// Get a value by path, with the same syntax as [`Value::get`].
+    pub fn get(&self, path: &str) -> Option<CompactRef<'_>> {
+        self.root().get(path)
+    }
+
+    /// Rebuild a [`Value`] tree.
+    pub fn to_value(&self) -> Value {
+        self.root().to_value()
+    }
+
+    /// Append `value` and its subtree in document order.
+    fn push_value(&mut self, value: &Value) {
+        let index = self.nodes.len();
+        let tag = value.tag.as_ref().map(|tag| CompactTag {
+            name: self.push_text(&tag.name),
+            span: tag.span,
+        });
+        self.nodes.push(Node {
+            tag,
+            kind: NodeKind::Unit,
+            size: 1,
+            span: value.span,
+        });
+
+        let kind = match &value.payload {
+            None => NodeKind::Unit,
+            Some(Payload::Scalar(scalar)) => NodeKind::Scalar {
+                text: self.push_text(&scalar.text),
+                kind: scalar.kind,
+            },
+            Some(Payload::Sequence(seq)) => {
+                for item in &seq.items {
+                    self.push_value(item);
+                }
+                NodeKind::Sequence {
+                    len: seq.items.len() as u32,
+                }
+            }
+            Some(Payload::Object(obj)) => {
+                for entry in &obj.entries {
+                    self.push_value(&entry.key);
+                    self.push_value(&entry.value);
+                }
+                NodeKind::Object {
+                    len: obj.entries.len() as u32,
+                    origin: obj.origin,
+                }
+            }
+        };
+
+        let size = (self.nodes.len() - index) as u32;
+        let node = &mut self.nodes[index];
+        node.kind = kind;
+        node.size = size;
+    }
+
+    fn push_text(&mut self, text: &str) -> TextRange {
+        let range = TextRange {
+            start: self.text.len() as u32,
+            len: text.len() as u32,
+        };
+        self.text.push_str(text);
+        range
+    }
+
+    fn text(&self, range: TextRange) -> &str {
+        let start = range.start as usize;
+        &self.text[start..start + range.len as usize]
+    }
+}
+
+/// Convert a tree into the compact representation.
+///
+/// Comments and doc comments are dropped. Payload spans are not stored
+/// separately: they come back as the value's span.
+impl From<&Value> for CompactValue {
+    fn from(value: &Value) -> Self {
+        let mut compact = CompactValue {
+            nodes: Vec::new(),
+            text: String::new(),
+        };
+        compact.push_value(value);
+        compact
+    }
+}
+
+impl From<&CompactValue> for Value {
+    fn from(compact: &CompactValue) -> Self {
+        compact.to_value()
+    }
+}
+
+/// A borrowed handle to one value inside a [`CompactValue`].
+#[derive(Debug, Clone, Copy)]
+pub struct CompactRef<'a> {
+    value: &'a CompactValue,
+    index: u32,
+}
+
+impl<'a> CompactRef<'a> {
+    fn node(&self) -> &'a Node {
+        &self.value.nodes[self.index as usize]
+    }
+
+    /// Get the tag name if present.
+    pub fn tag_name(&self) -> Option<&'a str> {
+        self.node().tag.map(|tag| self.value.text(tag.name))
+    }
+
+    /// Check if this is unit (`@` - no tag, no payload).
+    pub fn is_unit(&self) -> bool {
+        let node = self.node();
+        node.tag.is_none() && node.kind == NodeKind::Unit
+    }
+
+    /// Get as string (for untagged scalars).
+    pub fn as_str(&self) -> Option<&'a str> {
+        if self.node().tag.is_some() {
+            return None;
+        }
+        self.scalar_text()
+    }
+
+    /// Get the scalar text regardless of tag.
+    pub fn scalar_text(&self) -> Option<&'a str> {
+        match self.node().kind {
+            NodeKind::Scalar { text, .. } => Some(self.value.text(text)),
+            _ => None,
+        }
+    }
+
+    /// Source span (None if programmatically constructed).
+    pub fn span(&self) -> Option<Span> {
+        self.node().span
+    }
+
+    /// Iterate over the entries of an object payload as (key, value) pairs.
+    pub fn entries(
+        &self,
+    ) -> Option<impl Iterator<Item = (CompactRef<'a>, CompactRef<'a>)> + use<'a>> {
+        match self.node().kind {
+            NodeKind::Object { len, .. } => {
+                let mut children = self.children(2 * len);
+                Some(std::iter::from_fn(move || {
+                    Some((children.next()?, children.next()?))
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Iterate over the items of a sequence payload.
+    pub fn items(&self) -> Option<impl Iterator<Item = CompactRef<'a>> + use<'a>> {
+        match self.node().kind {
+            NodeKind::Sequence { len } => Some(self.children(len)),
+            _ => None,
+        }
+    }
+
+    /// Get a value by path, with the same syntax as [`Value::get`].
+    pub fn get(&self, path: &str) -> Option<CompactRef<'a>> {
+        if path.is_empty() {
+            return Some(*self);
+        }
+
+        let (segment, rest) = split_path(path);
+        let value = match self.node().kind {
+            NodeKind::Object { .. } => self
+                .entries()?
+                .find(|(key, _)| key.as_str() == Some(segment))
+                .map(|(_, value)| value)?,
+            NodeKind::Sequence { .. } => {
+                let idx: usize = segment.strip_prefix('[')?.strip_suffix(']')?.parse().ok()?;
+                self.items()?.nth(idx)?
+            }
+            _ => return None,
+        };
+        value.get(rest)
+    }
+
+    /// Rebuild a [`Value`] tree for this value.
+    pub fn to_value(&self) -> Value {
+        let node = self.node();
+        let span = node.span;
+        let payload = match node.kind {
+            NodeKind::Unit => None,
+            NodeKind::Scalar { text, kind } => Some(Payload::Scalar(Scalar {
+                text: self.value.text(text).into(),
+                kind,
+                span,
+            })),
+            NodeKind::Sequence { .. } => Some(Payload::Sequence(Sequence {
+                items: self
+                    .items()
+                    .into_iter()
+                    .flatten()
+                    .map(|item| item.to_value())
+                    .collect(),
+                span,
+            })),
+            NodeKind::Object { origin, .. } => Some(Payload::Object(Object {
+                entries: self
+                    .entries()
+                    .into_iter()
+                    .flatten()
+                    .map(|(key, value)| Entry {
+                        key: key.to_value(),
+                        value: value.to_value(),
+                        doc_comment: None,
+                        comments: Vec::new(),
+                    })
+                    .collect(),
+                span,
+                origin,
+            })),
+        };
+        Value {
+            tag: node.tag.map(|tag| Tag {
+                name: self.value.text(tag.name).to_string(),
+                span: tag.span,
+            }),
+            payload,
+            span,
+        }
+    }
+
+    /// Iterate over the `count` children that follow this node.
+    fn children(&self, count: u32) -> impl Iterator<Item = CompactRef<'a>> + use<'a> {
+        let value = self.value;
+        let mut next = self.index + 1;
+        (0..count).map(move |_| {
+            let child = CompactRef { value, index: next };
+            next += value.nodes[next as usize].size;
+            child
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use facet_testhelpers::test;
+
+    const DOCUMENTS: &[(&str, &[&str])] = &[
+        ("name Alice\nage 30", &["name", "age", "missing", ""]),
+        (
+            "server {host localhost, port 8080, tls {cert /etc/cert.pem}}\n/// Docs\nlog.level debug",
+            &[
+                "server",
+                "server.host",
+                "server.tls.cert",
+                "log.level",
+                "log",
+                "server.port.x",
+            ],
+        ),
+        (
+            "items (a b (c d) {e f})\nempty ()",
+            &[
+                "items",
+                "items[0]",
+                "items[2][1]",
+                "items[3].e",
+                "items[4]",
+                "empty",
+                "empty[0]",
+            ],
+        ),
+        (
+            "schema {@ @object{name @string, tags @seq(@string)}}\nunit @\nquoted \"a b\"",
+            &["schema", "schema.name", "unit", "quoted"],
+        ),
+        (
+            "records ({id 1, name one} {id 2, name two})\nlast @enum{a, b}",
+            &["records[1].name", "records[0].id", "last", "last.a"],
+        ),
+        (
+            "server host>localhost port>8080\nnested {tls cert>a.pem}",
+            &["server", "server.port", "nested", "nested.tls"],
+        ),
+        ("", &["", "anything"]),
+    ];
+
+    #[test]
+    fn test_compact_parse_agrees_with_tree() {
+        for (source, paths) in DOCUMENTS {
+            let tree = crate::parse(source).unwrap();
+            let compact = CompactValue::parse(source).unwrap();
+
+            for path in *paths {
+                let expected = tree.get(path);
+                let actual = compact.get(path);
+                assert_eq!(
+                    actual.map(|v| v.to_value()),
+                    expected.map(without_comments),
+                    "{path:?} in {source:?}"
+                );
+                assert_eq!(
+                    actual.and_then(|v| v.as_str()),
+                    expected.and_then(Value::as_str)
+                );
+                assert_eq!(
+                    actual.and_then(|v| v.tag_name()),
+                    expected.and_then(Value::tag_name)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_compact_round_trips_value() {
+        for (source, _) in DOCUMENTS {
+            let tree = without_comments(&crate::parse(source).unwrap());
+            let compact = CompactValue::from(&tree);
+            assert_eq!(compact, CompactValue::parse(source).unwrap());
+            assert_eq!(Value::from(&compact), tree);
+        }
+    }
+
+    #[test]
+    fn test_compact_keeps_attribute_objects() {
+        let source = "server host>localhost port>8080";
+        let compact = CompactValue::parse(source).unwrap();
+        let value = compact.get("server").unwrap().to_value();
+        assert_eq!(
+            value.as_object().map(|obj| obj.origin),
+            Some(ObjectOrigin::Attributes)
+        );
+        assert_eq!(
+            CompactValue::from(&value).root().to_value(),
+            value,
+            "origin survives a round trip"
+        );
+    }
+
+    #[test]
+    fn test_compact_parse_reports_errors() {
+        assert_eq!(
+            CompactValue::parse("a {").unwrap_err(),
+            crate::parse("a {").unwrap_err()
+        );
+    }
+
+    /// Strip doc comments, which the compact form does not keep.
+    fn without_comments(value: &Value) -> Value {
+        let mut value = value.clone();
+        strip(&mut value);
+        fn strip(value: &mut Value) {
+            match &mut value.payload {
+                Some(Payload::Object(obj)) => {
+                    for entry in &mut obj.entries {
+                        entry.doc_comment = None;
+                        strip(&mut entry.key);
+                        strip(&mut entry.value);
+                    }
+                }
+                Some(Payload::Sequence(seq)) => seq.items.iter_mut().for_each(strip),
+                _ => {}
+            }
+        }
+        value
+    }
+}
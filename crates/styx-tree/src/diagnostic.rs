@@ -214,6 +214,16 @@ impl ParseError {
                         .with_color(Color::Red),
                 )
                 .with_help("an explicit root object `{...}` is the entire document; nothing can follow it"),
+
+            // diag[impl diagnostic.parser.mixed-separators]
+            ParseErrorKind::MixedSeparators => Report::build(ReportKind::Error, (filename, range.clone()))
+                .with_message("mixed separators in object")
+                .with_label(
+                    Label::new((filename, range))
+                        .with_message("mixing commas and newlines")
+                        .with_color(Color::Red),
+                )
+                .with_help("use either commas or newlines to separate entries, not both"),
         }
     }
 }
@@ -246,6 +256,7 @@ impl std::fmt::Display for ParseError {
             ParseErrorKind::TrailingContent => {
                 write!(f, "trailing content after explicit root object")
             }
+            ParseErrorKind::MixedSeparators => write!(f, "mixed separators in object"),
         }?;
         write!(f, " at offset {}", self.span.start)
     }
@@ -288,6 +299,21 @@ mod tests {
         assert_snapshot_stripped!(errors[0].render("test.styx", source));
     }
 
+    #[test]
+    fn test_mixed_separators_diagnostic() {
+        let source = "{\n  a 1,\n  b 2\n  c 3\n}";
+        let mut parser = styx_parse::Parser::new(source).strict_separators(true);
+        let mut errors = Vec::new();
+        while let Some(event) = parser.next_event() {
+            if let styx_parse::EventKind::Error { kind } = event.kind {
+                errors.push(ParseError::new(kind, event.span));
+            }
+        }
+        assert_eq!(errors.len(), 1);
+
+        assert_snapshot_stripped!(errors[0].render("test.styx", source));
+    }
+
     #[test]
     fn test_invalid_escape_diagnostic() {
         let source = r#"name "hello\qworld""#;
@@ -0,0 +1,154 @@
+//! Typed form of the `@schema` directive.
+
+use crate::value::Value;
+
+/// Where a document's schema comes from, as declared by its `@schema` entry.
+///
+/// # Example
+///
+/// ```
+/// use styx_tree::SchemaDirective;
+///
+/// let doc = styx_tree::parse("@schema {id crate:myapp@1, cli myapp}\nport 8080").unwrap();
+/// let entry = doc.as_object().unwrap().entries.iter().find(|e| e.key.is_schema_tag()).unwrap();
+/// assert_eq!(
+///     SchemaDirective::from_value(&entry.value),
+///     Some(SchemaDirective::Embedded {
+///         id: Some("crate:myapp@1".into()),
+///         cli: "myapp".into(),
+///     })
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaDirective {
+    /// A schema file path: `@schema path/to/schema.styx`.
+    External(String),
+    /// A schema embedded in a binary: `@schema {id crate:myapp@1, cli myapp}`.
+    Embedded {
+        /// ID of the schema to pick, if the binary embeds several.
+        id: Option<String>,
+        /// Name of the binary to extract the schema from.
+        cli: String,
+    },
+}
+
+impl SchemaDirective {
+    /// Parse the value of a `@schema` entry.
+    ///
+    /// Returns `None` if the value is neither a path nor an object with a
+    /// `cli` field. The opt-out form `@schema @` is also `None`.
+    pub fn from_value(value: &Value) -> Option<Self> {
+        if let Some(path) = value.as_str() {
+            return Some(SchemaDirective::External(path.to_string()));
+        }
+
+        let obj = value.as_object()?;
+        let cli = obj.get("cli")?.as_str()?;
+        let id = obj.get("id").and_then(Value::as_str);
+        Some(SchemaDirective::Embedded {
+            id: id.map(str::to_string),
+            cli: cli.to_string(),
+        })
+    }
+
+    /// Parse the value of a `@schema` entry that may declare several schemas.
+    ///
+    /// `@schema (base.styx extension.styx)` lists schemas in order of
+    /// increasing precedence; a single directive is a list of one. Returns
+    /// `None` if the value, or any item of the list, is malformed.
+    pub fn list_from_value(value: &Value) -> Option<Vec<Self>> {
+        match value.as_sequence() {
+            Some(seq) if value.tag.is_none() && !seq.items.is_empty() => {
+                seq.items.iter().map(Self::from_value).collect()
+            }
+            Some(_) => None,
+            None => Self::from_value(value).map(|directive| vec![directive]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use facet_testhelpers::test;
+
+    fn directive(source: &str) -> Option<SchemaDirective> {
+        let doc = crate::parse(source).unwrap();
+        let entry = doc
+            .as_object()
+            .unwrap()
+            .entries
+            .iter()
+            .find(|e| e.key.is_schema_tag())
+            .expect("document should have a @schema entry");
+        SchemaDirective::from_value(&entry.value)
+    }
+
+    #[test]
+    fn test_path_directive() {
+        assert_eq!(
+            directive("@schema schemas/app.styx"),
+            Some(SchemaDirective::External("schemas/app.styx".into()))
+        );
+        assert_eq!(
+            directive(r#"@schema "my schema.styx""#),
+            Some(SchemaDirective::External("my schema.styx".into()))
+        );
+    }
+
+    #[test]
+    fn test_embedded_directive() {
+        assert_eq!(
+            directive("@schema {id crate:myapp@1, cli myapp}"),
+            Some(SchemaDirective::Embedded {
+                id: Some("crate:myapp@1".into()),
+                cli: "myapp".into(),
+            })
+        );
+        assert_eq!(
+            directive("@schema {cli myapp}"),
+            Some(SchemaDirective::Embedded {
+                id: None,
+                cli: "myapp".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_directive_list() {
+        let doc = crate::parse("@schema (base.styx {id crate:myapp@1, cli myapp})").unwrap();
+        let entry = &doc.as_object().unwrap().entries[0];
+        assert_eq!(
+            SchemaDirective::list_from_value(&entry.value),
+            Some(vec![
+                SchemaDirective::External("base.styx".into()),
+                SchemaDirective::Embedded {
+                    id: Some("crate:myapp@1".into()),
+                    cli: "myapp".into(),
+                },
+            ])
+        );
+
+        let doc = crate::parse("@schema base.styx").unwrap();
+        let entry = &doc.as_object().unwrap().entries[0];
+        assert_eq!(
+            SchemaDirective::list_from_value(&entry.value),
+            Some(vec![SchemaDirective::External("base.styx".into())])
+        );
+
+        for source in ["@schema ()", "@schema (base.styx {id crate:myapp@1})"] {
+            let doc = crate::parse(source).unwrap();
+            let entry = &doc.as_object().unwrap().entries[0];
+            assert_eq!(SchemaDirective::list_from_value(&entry.value), None);
+        }
+    }
+
+    #[test]
+    fn test_malformed_directive() {
+        assert_eq!(directive("@schema {id crate:myapp@1}"), None);
+        assert_eq!(directive("@schema {cli {name myapp}}"), None);
+        assert_eq!(directive("@schema (a.styx b.styx)"), None);
+        assert_eq!(directive("@schema @string"), None);
+        assert_eq!(directive("@schema @"), None);
+    }
+}
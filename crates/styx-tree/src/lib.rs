@@ -4,14 +4,24 @@
 //! This crate provides a high-level API for working with Styx documents,
 //! including parsing, accessing values by path, and serialization.
 
+mod binary;
 mod builder;
+mod canonical;
+mod compact;
 mod diagnostic;
+mod directive;
 mod value;
 
-pub use builder::{BuildError, TreeBuilder};
+pub use binary::{DecodeError, from_bytes, to_bytes};
+pub use builder::{BuildError, MAX_ALIAS_NODES, RootEntries, TreeBuilder};
+pub use canonical::{CanonicalNumber, canonical_number, parse_canonical_number};
+pub use compact::{CompactRef, CompactValue};
 pub use diagnostic::ParseError;
-pub use styx_parse::{ParseErrorKind, ScalarKind, Span};
-pub use value::{Entry, Object, Payload, Scalar, Sequence, Tag, Value};
+pub use directive::SchemaDirective;
+pub use styx_parse::{ParseErrorKind, ScalarKind, Span, TagName};
+pub use value::{
+    Comment, CommentPlacement, Entry, Object, ObjectOrigin, Payload, Scalar, Sequence, Tag, Value,
+};
 
 /// Parse a Styx document into a tree.
 pub fn parse(source: &str) -> Result<Value, BuildError> {
@@ -23,6 +33,136 @@ pub fn parse(source: &str) -> Result<Value, BuildError> {
     builder.finish()
 }
 
+/// Parse a Styx document into a tree, recovering from errors.
+///
+/// Always returns a tree, with every error found. The parser skips what it
+/// can't read, so the tree holds the entries around a broken one, which is
+/// what editors need to offer completions in a document being typed.
+///
+/// ```
+/// let (doc, errors) = styx_tree::parse_recovering("host localhost\nport 80 80\nname app");
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(doc.get("name").and_then(|v| v.as_str()), Some("app"));
+/// ```
+pub fn parse_recovering(source: &str) -> (Value, Vec<BuildError>) {
+    let mut parser = styx_parse::Parser::new(source);
+    let mut builder = TreeBuilder::new();
+    while let Some(event) = parser.next_event() {
+        builder.event(event);
+    }
+    builder.finish_recovering()
+}
+
+/// Options for [`parse_with`]. All are off by default, which parses like
+/// [`parse`].
+///
+/// ```
+/// use styx_tree::ParseOptions;
+///
+/// let options = ParseOptions::new().anchors(true);
+/// let doc = styx_tree::parse_with("defaults &base {port 8080}\nserver *base", options).unwrap();
+/// assert_eq!(doc.get("server.port").and_then(|v| v.as_str()), Some("8080"));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Retain regular (`//`) comments.
+    ///
+    /// A comment on the same line as the end of an entry, like
+    /// `port 8080 // main port`, is attached to that entry. Other comments
+    /// are attached to the entry that follows them, or as trailing comments
+    /// of the last entry when nothing follows in the same object. See
+    /// [`Entry::comments`], [`Entry::inline_comment`] and
+    /// [`Entry::trailing_comments`].
+    pub retain_comments: bool,
+
+    /// Expand anchors and aliases.
+    ///
+    /// An entry written `key &name value` anchors its value as `name`, and
+    /// any bare `*name` elsewhere in the document is replaced by a copy of
+    /// it. Aliases may appear before their anchor. See
+    /// [`TreeBuilder::anchors`].
+    pub anchors: bool,
+
+    /// Reject objects that separate their entries with both commas and
+    /// newlines, with [`ParseErrorKind::MixedSeparators`]. See
+    /// [`Parser::strict_separators`](styx_parse::Parser::strict_separators).
+    pub strict_separators: bool,
+}
+
+impl ParseOptions {
+    /// Create new default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retain regular (`//`) comments.
+    pub fn retain_comments(mut self, retain: bool) -> Self {
+        self.retain_comments = retain;
+        self
+    }
+
+    /// Expand anchors and aliases.
+    pub fn anchors(mut self, expand: bool) -> Self {
+        self.anchors = expand;
+        self
+    }
+
+    /// Reject objects with mixed separators.
+    pub fn strict_separators(mut self, strict: bool) -> Self {
+        self.strict_separators = strict;
+        self
+    }
+}
+
+/// Parse a Styx document into a tree with the given options.
+///
+/// ```
+/// use styx_tree::ParseOptions;
+///
+/// let strict = ParseOptions::new().strict_separators(true);
+/// assert!(styx_tree::parse_with("a 1, b 2\nc 3", strict).is_err());
+/// assert!(styx_tree::parse_with("a 1\nb 2\nc 3", strict).is_ok());
+/// ```
+pub fn parse_with(source: &str, options: ParseOptions) -> Result<Value, BuildError> {
+    let mut parser = styx_parse::Parser::new(source)
+        .anchors(options.anchors)
+        .strict_separators(options.strict_separators);
+    let mut builder = TreeBuilder::new()
+        .retain_comments(options.retain_comments)
+        .anchors(options.anchors);
+    if options.retain_comments {
+        builder = builder.with_source(source);
+    }
+    while let Some(event) = parser.next_event() {
+        builder.event(event);
+    }
+    builder.finish()
+}
+
+/// Parse a schema file and return its root `meta` block, or `None` if it
+/// has none.
+///
+/// The block is not checked; `facet_styx::parse_meta` reads it into typed
+/// metadata.
+///
+/// ```
+/// let meta = styx_tree::parse_meta("meta {id my-schema}\nschema {@ @string}")
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(meta.get("id").and_then(|v| v.as_str()), Some("my-schema"));
+/// ```
+pub fn parse_meta(source: &str) -> Result<Option<Value>, BuildError> {
+    let mut root = parse(source)?;
+    let Some(Payload::Object(obj)) = &mut root.payload else {
+        return Ok(None);
+    };
+    let index = obj
+        .entries
+        .iter()
+        .position(|entry| entry.key.as_str() == Some("meta"));
+    Ok(index.map(|index| obj.entries.swap_remove(index).value))
+}
+
 /// A Styx document (root is always an implicit object).
 #[derive(Debug, Clone, PartialEq)]
 pub struct Document {
@@ -1,6 +1,10 @@
 use super::*;
 use facet_testhelpers::test;
 
+fn parse_anchored(source: &str) -> Result<Value, BuildError> {
+    parse_with(source, ParseOptions::new().anchors(true))
+}
+
 #[test]
 fn test_parse_simple() {
     let doc = Document::parse("name Alice\nage 30").unwrap();
@@ -92,3 +96,112 @@ fn test_schema_tree_structure() {
         "@string should have no payload"
     );
 }
+
+#[test]
+fn test_anchor_definition_and_alias() {
+    let doc = parse_anchored(
+        r#"
+defaults &base {host localhost, port 8080}
+primary *base
+replicas (*base *base)
+"#,
+    )
+    .unwrap();
+
+    let defaults = doc.get("defaults").unwrap();
+    for path in ["primary", "replicas[0]", "replicas[1]"] {
+        let value = doc.get(path).unwrap();
+        assert!(value.semantic_eq(defaults), "{path} should expand to &base");
+    }
+    assert_eq!(
+        doc.get("primary.port").and_then(|v| v.as_str()),
+        Some("8080")
+    );
+}
+
+#[test]
+fn test_anchor_forward_reference() {
+    let doc = parse_anchored(
+        r#"
+server {limits *limits, name api}
+shared &limits {
+    timeout 30s
+    retry *retry
+}
+retry &retry (1s 5s)
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        doc.get("server.limits.timeout").and_then(|v| v.as_str()),
+        Some("30s")
+    );
+    assert_eq!(
+        doc.get("server.limits.retry[1]").and_then(|v| v.as_str()),
+        Some("5s")
+    );
+}
+
+#[test]
+fn test_anchor_cyclic_reference() {
+    let err = parse_anchored("node &loop {next *loop}").unwrap_err();
+    assert!(
+        matches!(&err, BuildError::CyclicAnchor(name, span) if name == "loop" && span.start == 17),
+        "got {err:?}"
+    );
+
+    let err = parse_anchored("a &a {b *b}\nb &b {a *a}\nroot *a").unwrap_err();
+    assert!(matches!(err, BuildError::CyclicAnchor(..)), "got {err:?}");
+}
+
+#[test]
+fn test_anchor_errors() {
+    let err = parse_anchored("a *missing").unwrap_err();
+    assert!(matches!(&err, BuildError::UnknownAnchor(name, _) if name == "missing"));
+
+    let err = parse_anchored("a &x 1\nb &x 2").unwrap_err();
+    assert!(
+        matches!(&err, BuildError::DuplicateAnchor(name, span) if name == "x" && span.start == 9)
+    );
+
+    // Each level doubles the expansion; the limit stops it long before the
+    // last level
+    let mut source = String::from("l0 &l0 (x x)\n");
+    for level in 1..64 {
+        source.push_str(&format!(
+            "l{level} &l{level} (*l{prev} *l{prev})\n",
+            prev = level - 1
+        ));
+    }
+    let err = parse_anchored(&source).unwrap_err();
+    assert!(matches!(err, BuildError::AliasLimit(_)), "got {err:?}");
+
+    // Few aliases, but each copies a large value
+    let items = vec!["x"; 1_000].join(" ");
+    let aliases = vec!["*big"; 2_000].join(" ");
+    let source = format!("big &big ({items})\ncopies ({aliases})");
+    let err = parse_anchored(&source).unwrap_err();
+    assert!(matches!(err, BuildError::AliasLimit(_)), "got {err:?}");
+
+    // Up to the limit is fine
+    let aliases = vec!["*big"; 500].join(" ");
+    let source = format!("big &big ({items})\ncopies ({aliases})");
+    let doc = parse_anchored(&source).unwrap();
+    assert_eq!(
+        doc.get("copies[499][999]").and_then(|v| v.as_str()),
+        Some("x")
+    );
+}
+
+#[test]
+fn test_alias_literals() {
+    // Quoted text and globs are never aliases
+    let doc = parse_anchored(r#"a &x 1, b "*x", c *.rs"#).unwrap();
+    assert_eq!(doc.get("b").and_then(|v| v.as_str()), Some("*x"));
+    assert_eq!(doc.get("c").and_then(|v| v.as_str()), Some("*.rs"));
+
+    // Plain parsing leaves `*name` alone
+    let doc = parse("a *x").unwrap();
+    assert_eq!(doc.get("a").and_then(|v| v.as_str()), Some("*x"));
+}
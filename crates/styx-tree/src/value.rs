@@ -11,7 +11,7 @@
 //! - `@seq(a b)` is `Value { tag: Some("seq"), payload: Some(Payload::Sequence(...)) }`
 //! - `@object{...}` is `Value { tag: Some("object"), payload: Some(Payload::Object(...)) }`
 
-use styx_parse::{ScalarKind, Span};
+use styx_parse::{ScalarKind, Span, TagName};
 
 /// A Styx value: optional tag + optional payload.
 #[derive(Debug, Clone, PartialEq)]
@@ -83,12 +83,29 @@ pub struct Object {
     pub entries: Vec<Entry>,
     /// Source span.
     pub span: Option<Span>,
+    /// Syntax the object was written in.
+    pub origin: ObjectOrigin,
+}
+
+/// Syntax an object was written in, so formatting can reproduce it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "facet", derive(facet::Facet))]
+#[repr(u8)]
+pub enum ObjectOrigin {
+    /// Braces `{key value, ...}`, or an object built in code.
+    #[default]
+    Braces,
+    /// Attributes `key>value key>value`.
+    Attributes,
 }
 
 /// An entry in an object.
+///
+/// Build one with [`Entry::new`]; entries may gain fields in future.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "facet", derive(facet::Facet))]
 #[cfg_attr(feature = "facet", facet(skip_all_unless_truthy))]
+#[non_exhaustive]
 pub struct Entry {
     /// The key.
     pub key: Value,
@@ -96,6 +113,37 @@ pub struct Entry {
     pub value: Value,
     /// Doc comment attached to this entry.
     pub doc_comment: Option<String>,
+    /// Regular (`//`) comments attached to this entry.
+    ///
+    /// Only populated when the tree is built with
+    /// [`TreeBuilder::retain_comments`](crate::TreeBuilder::retain_comments).
+    pub comments: Vec<Comment>,
+}
+
+/// A regular (`//`) comment retained in the tree.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "facet", derive(facet::Facet))]
+#[cfg_attr(feature = "facet", facet(skip_all_unless_truthy))]
+pub struct Comment {
+    /// The comment text, including the leading `//`.
+    pub text: String,
+    /// Where the comment sits relative to its entry.
+    pub placement: CommentPlacement,
+    /// Source span.
+    pub span: Option<Span>,
+}
+
+/// Where a comment sits relative to the entry it is attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(facet::Facet))]
+#[repr(u8)]
+pub enum CommentPlacement {
+    /// On the lines before the entry (after any previous entry).
+    Leading,
+    /// After the entry, with no following entry in the same object.
+    Trailing,
+    /// On the same line as the end of the entry, like `port 8080 // main`.
+    Inline,
 }
 
 impl Value {
@@ -174,6 +222,7 @@ impl Value {
                 entries: Vec::new(),
 
                 span: None,
+                origin: ObjectOrigin::Braces,
             })),
             span: None,
         }
@@ -254,6 +303,34 @@ impl Value {
         self
     }
 
+    /// Compare two values by meaning, ignoring how they were written.
+    ///
+    /// Tags, scalar text and the contents of sequences and objects must match,
+    /// in order. Spans, the kind of scalar syntax (`a` and `"a"` are equal)
+    /// and comments are ignored. `==` compares all of those too.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let bare = styx_tree::parse("name app\nports (80 443)").unwrap();
+    /// let quoted = styx_tree::parse("name \"app\"\nports (\n  80\n  443\n)").unwrap();
+    /// assert!(bare.semantic_eq(&quoted));
+    /// assert_ne!(bare, quoted);
+    /// ```
+    pub fn semantic_eq(&self, other: &Value) -> bool {
+        let tags_match = match (&self.tag, &other.tag) {
+            (Some(a), Some(b)) => a.name == b.name,
+            (None, None) => true,
+            _ => false,
+        };
+        tags_match
+            && match (&self.payload, &other.payload) {
+                (None, None) => true,
+                (Some(a), Some(b)) => a.semantic_eq(b),
+                _ => false,
+            }
+    }
+
     /// Get a value by path.
     ///
     /// Path segments are separated by `.`.
@@ -292,7 +369,54 @@ impl Value {
         }
     }
 
-    /// Get a mutable value by path.
+    /// Get a value by [RFC 6901] JSON Pointer, like `/db/host` or `/items/0`.
+    ///
+    /// The empty pointer `""` refers to `self`. In reference tokens, `~1`
+    /// stands for `/` and `~0` for `~`. Sequence items are addressed by
+    /// index, without leading zeros.
+    ///
+    /// ```
+    /// let config = styx_tree::parse("db {host localhost}\nitems (a b)\n\"a/b\" 1").unwrap();
+    /// assert_eq!(config.get_pointer("/db/host").unwrap().as_str(), Some("localhost"));
+    /// assert_eq!(config.get_pointer("/items/1").unwrap().as_str(), Some("b"));
+    /// assert_eq!(config.get_pointer("/a~1b").unwrap().as_str(), Some("1"));
+    /// ```
+    ///
+    /// [RFC 6901]: https://www.rfc-editor.org/rfc/rfc6901
+    pub fn get_pointer(&self, pointer: &str) -> Option<&Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+
+        let mut value = self;
+        for token in pointer.strip_prefix('/')?.split('/') {
+            value = match &value.payload {
+                Some(Payload::Object(obj)) => {
+                    obj.get(&token.replace("~1", "/").replace("~0", "~"))?
+                }
+                Some(Payload::Sequence(seq)) => {
+                    let is_index = token.bytes().all(|b| b.is_ascii_digit())
+                        && (token == "0" || !token.starts_with('0'));
+                    if !is_index {
+                        return None;
+                    }
+                    seq.get(token.parse().ok()?)?
+                }
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    /// Get a mutable value by path, with the syntax [`get`] accepts.
+    ///
+    /// ```
+    /// let mut config = styx_tree::parse("server {host localhost, port 8080}").unwrap();
+    /// *config.get_mut("server.port").unwrap() = styx_tree::Value::scalar("9090");
+    /// assert_eq!(config.get("server.port").unwrap().as_str(), Some("9090"));
+    /// ```
+    ///
+    /// [`get`]: Value::get
     pub fn get_mut(&mut self, path: &str) -> Option<&mut Value> {
         if path.is_empty() {
             return Some(self);
@@ -325,6 +449,284 @@ impl Value {
             _ => None,
         }
     }
+
+    /// Set the value at `path`, creating objects for the missing keys on the
+    /// way, and return it.
+    ///
+    /// An existing value at `path` is replaced. Sequence items are only
+    /// reached through existing indices. Returns `None`, leaving `self` as it
+    /// was, if the path runs into a value that isn't an object or sequence,
+    /// or into a missing index.
+    ///
+    /// ```
+    /// let mut config = styx_tree::parse("server {host localhost}").unwrap();
+    /// config.upsert("server.tls.cert", styx_tree::Value::scalar("cert.pem")).unwrap();
+    /// assert_eq!(config.get("server.tls.cert").unwrap().as_str(), Some("cert.pem"));
+    ///
+    /// // `host` is a scalar, so nothing can go inside it
+    /// assert!(config.upsert("server.host.name", styx_tree::Value::unit()).is_none());
+    /// ```
+    pub fn upsert(&mut self, path: &str, value: Value) -> Option<&mut Value> {
+        if path.is_empty() {
+            *self = value;
+            return Some(self);
+        }
+
+        let (segment, rest) = split_path(path);
+
+        let child = match &mut self.payload {
+            Some(Payload::Object(obj)) if !segment.starts_with('[') => {
+                if !obj.contains_key(segment) {
+                    // Only sequence items that already exist can be reached,
+                    // so a new object can't lead to one
+                    if rest.contains('[') {
+                        return None;
+                    }
+                    obj.insert(segment, Value::object());
+                }
+                obj.get_mut(segment)?
+            }
+            Some(Payload::Sequence(seq)) => {
+                let idx: usize = segment.strip_prefix('[')?.strip_suffix(']')?.parse().ok()?;
+                seq.get_mut(idx)?
+            }
+            _ => return None,
+        };
+        child.upsert(rest, value)
+    }
+
+    /// Iterate over every leaf value with its path, in document order.
+    ///
+    /// Objects and sequences are walked into; everything else, including
+    /// tagged scalars and units, is a leaf. Paths use the syntax [`get`]
+    /// accepts, like `server.hosts[0]`. Entries whose key isn't a string are
+    /// skipped, since no path can reach them.
+    ///
+    /// ```
+    /// let config = styx_tree::parse("server {host localhost, ports (80 443)}").unwrap();
+    /// let paths: Vec<String> = config.leaves().map(|(path, _)| path).collect();
+    /// assert_eq!(paths, ["server.host", "server.ports[0]", "server.ports[1]"]);
+    /// ```
+    ///
+    /// [`get`]: Value::get
+    pub fn leaves(&self) -> impl Iterator<Item = (String, &Value)> {
+        let mut stack = vec![(String::new(), self)];
+        std::iter::from_fn(move || {
+            while let Some((path, value)) = stack.pop() {
+                match &value.payload {
+                    Some(Payload::Object(obj)) => {
+                        for entry in obj.entries.iter().rev() {
+                            let Some(key) = entry.key.as_str() else {
+                                continue;
+                            };
+                            let child = if path.is_empty() {
+                                key.to_string()
+                            } else {
+                                format!("{path}.{key}")
+                            };
+                            stack.push((child, &entry.value));
+                        }
+                    }
+                    Some(Payload::Sequence(seq)) => {
+                        for (i, item) in seq.items.iter().enumerate().rev() {
+                            stack.push((format!("{path}[{i}]"), item));
+                        }
+                    }
+                    _ => return Some((path, value)),
+                }
+            }
+            None
+        })
+    }
+
+    /// Merge `overlay` on top of this value.
+    ///
+    /// When both are objects, entries of `overlay` are merged into the entry
+    /// with the same key, or appended if there is none. Any other value is
+    /// replaced by `overlay` as a whole, so sequences are not concatenated.
+    ///
+    /// ```
+    /// let mut config = styx_tree::parse("server {host localhost, port 8080}").unwrap();
+    /// config.merge(styx_tree::parse("server {port 9090}\ndebug true").unwrap());
+    ///
+    /// let expected = styx_tree::parse("server {host localhost, port 9090}\ndebug true").unwrap();
+    /// assert!(config.semantic_eq(&expected));
+    /// ```
+    pub fn merge(&mut self, overlay: Value) {
+        match (&mut self.payload, overlay.payload) {
+            (Some(Payload::Object(base)), Some(Payload::Object(overlay))) => {
+                for entry in overlay.entries {
+                    match base
+                        .entries
+                        .iter_mut()
+                        .find(|existing| existing.key.semantic_eq(&entry.key))
+                    {
+                        Some(existing) => existing.value.merge(entry.value),
+                        None => base.entries.push(entry),
+                    }
+                }
+            }
+            (_, payload) => {
+                *self = Value {
+                    tag: overlay.tag,
+                    payload,
+                    span: overlay.span,
+                };
+            }
+        }
+    }
+
+    /// Apply `f` to this value and every value inside it, in pre-order.
+    ///
+    /// Entry keys are visited before their values, so `f` can rename keys
+    /// as well as rewrite values. Children are visited after `f` has run on
+    /// their parent, so values that `f` puts in place are visited too.
+    ///
+    /// ```
+    /// use styx_tree::{Payload, Value};
+    ///
+    /// let mut doc = styx_tree::parse("name app\nversion 1").unwrap();
+    /// doc.visit_mut(|value: &mut Value| {
+    ///     if value.as_str() == Some("version") {
+    ///         *value = Value::scalar("release");
+    ///     }
+    /// });
+    /// assert_eq!(doc.get("release").and_then(Value::as_str), Some("1"));
+    /// ```
+    pub fn visit_mut(&mut self, mut f: impl FnMut(&mut Value)) {
+        self.visit_mut_dyn(&mut f);
+    }
+
+    /// Collapse redundant wrapping, so `@tag{@ (a b)}` becomes `@tag(a b)`.
+    ///
+    /// A tagged object whose only entry has the unit key `@` is just a
+    /// wrapper around that entry's value, and is replaced by it under the
+    /// same tag. The value moves only when it can be written attached to the
+    /// tag, so the result reads back the same: it must be untagged and a
+    /// sequence, an object, or unit. Scalars stay wrapped, since `@tag(x)`
+    /// would read back as a sequence. Entries with comments are kept, and
+    /// objects with named keys are never collapsed: `@tag{value x}` names a
+    /// field. Nested values are simplified first, so wrappers inside
+    /// wrappers collapse too.
+    ///
+    /// ```
+    /// let mut doc = styx_tree::parse("hosts @list{@ (a b)}\nport @default{@ 8080}").unwrap();
+    /// doc.simplify();
+    ///
+    /// let expected = styx_tree::parse("hosts @list(a b)\nport @default{@ 8080}").unwrap();
+    /// assert!(doc.semantic_eq(&expected));
+    /// ```
+    pub fn simplify(&mut self) {
+        match &mut self.payload {
+            Some(Payload::Object(obj)) => {
+                for entry in &mut obj.entries {
+                    entry.key.simplify();
+                    entry.value.simplify();
+                }
+            }
+            Some(Payload::Sequence(seq)) => {
+                for item in &mut seq.items {
+                    item.simplify();
+                }
+            }
+            Some(Payload::Scalar(_)) | None => {}
+        }
+
+        if self.tag.is_none() {
+            return;
+        }
+        let Some(Payload::Object(obj)) = &mut self.payload else {
+            return;
+        };
+        if let [entry] = obj.entries.as_mut_slice()
+            && entry.key.is_unit()
+            && entry.value.tag.is_none()
+            && !matches!(entry.value.payload, Some(Payload::Scalar(_)))
+            && entry.doc_comment.is_none()
+            && entry.comments.is_empty()
+        {
+            self.payload = entry.value.payload.take();
+        }
+    }
+
+    fn visit_mut_dyn(&mut self, f: &mut dyn FnMut(&mut Value)) {
+        f(self);
+        match &mut self.payload {
+            Some(Payload::Object(obj)) => {
+                for entry in &mut obj.entries {
+                    entry.key.visit_mut_dyn(f);
+                    entry.value.visit_mut_dyn(f);
+                }
+            }
+            Some(Payload::Sequence(seq)) => {
+                for item in &mut seq.items {
+                    item.visit_mut_dyn(f);
+                }
+            }
+            Some(Payload::Scalar(_)) | None => {}
+        }
+    }
+}
+
+impl Tag {
+    /// The tag name split into namespace and name, for `@sql:Query`.
+    pub fn split_name(&self) -> TagName<'_> {
+        TagName::split(&self.name)
+    }
+}
+
+impl Payload {
+    /// Compare two payloads by meaning; see [`Value::semantic_eq`].
+    pub fn semantic_eq(&self, other: &Payload) -> bool {
+        match (self, other) {
+            (Payload::Scalar(a), Payload::Scalar(b)) => a.text == b.text,
+            (Payload::Sequence(a), Payload::Sequence(b)) => {
+                a.items.len() == b.items.len()
+                    && a.items.iter().zip(&b.items).all(|(a, b)| a.semantic_eq(b))
+            }
+            (Payload::Object(a), Payload::Object(b)) => {
+                a.entries.len() == b.entries.len()
+                    && a.entries
+                        .iter()
+                        .zip(&b.entries)
+                        .all(|(a, b)| a.key.semantic_eq(&b.key) && a.value.semantic_eq(&b.value))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Entry {
+    /// Create an entry without doc comment or comments.
+    pub fn new(key: Value, value: Value) -> Self {
+        Entry {
+            key,
+            value,
+            doc_comment: None,
+            comments: Vec::new(),
+        }
+    }
+
+    /// Comments on the lines before this entry.
+    pub fn comments(&self) -> impl Iterator<Item = &Comment> {
+        self.comments
+            .iter()
+            .filter(|c| c.placement == CommentPlacement::Leading)
+    }
+
+    /// Comments after this entry, before the end of its object.
+    pub fn trailing_comments(&self) -> impl Iterator<Item = &Comment> {
+        self.comments
+            .iter()
+            .filter(|c| c.placement == CommentPlacement::Trailing)
+    }
+
+    /// The comment on the same line as the end of this entry.
+    pub fn inline_comment(&self) -> Option<&Comment> {
+        self.comments
+            .iter()
+            .find(|c| c.placement == CommentPlacement::Inline)
+    }
 }
 
 impl Object {
@@ -395,14 +797,19 @@ impl Object {
         {
             entry.value = value;
         } else {
-            self.entries.push(Entry {
-                key: Value::scalar(key_str),
-                value,
-                doc_comment: None,
-            });
+            self.entries.push(Entry::new(Value::scalar(key_str), value));
         }
     }
 
+    /// Remove the entry with a string key, returning its value.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let index = self
+            .entries
+            .iter()
+            .position(|e| e.key.as_str() == Some(key))?;
+        Some(self.entries.remove(index).value)
+    }
+
     /// Insert or update an entry with a unit key.
     pub fn insert_unit(&mut self, value: Value) {
         if let Some(entry) = self.entries.iter_mut().find(|e| e.key.is_unit()) {
@@ -412,6 +819,7 @@ impl Object {
                 key: Value::unit(),
                 value,
                 doc_comment: None,
+                comments: Vec::new(),
             });
         }
     }
@@ -450,7 +858,7 @@ impl Sequence {
 }
 
 /// Split path at first `.` or `[`.
-fn split_path(path: &str) -> (&str, &str) {
+pub(crate) fn split_path(path: &str) -> (&str, &str) {
     // Handle [n] at start
     if path.starts_with('[')
         && let Some(end) = path.find(']')
@@ -513,6 +921,148 @@ mod tests {
         assert!(v.payload.is_none());
     }
 
+    #[test]
+    fn test_semantic_eq_ignores_spans_and_quoting() {
+        let a = crate::parse("name app\nserver {host localhost, tags (a b)}").unwrap();
+        let b = crate::parse(
+            "// comment\nname \"app\"\nserver {\n  host r#\"localhost\"#\n  tags (\"a\" b)\n}",
+        )
+        .unwrap();
+        assert!(a.semantic_eq(&b));
+        assert_ne!(a, b);
+
+        // Built in code, without spans
+        let mut built = Object {
+            entries: vec![],
+            span: None,
+            origin: ObjectOrigin::Braces,
+        };
+        built.insert("name", Value::scalar("app"));
+        let built = Value {
+            tag: None,
+            payload: Some(Payload::Object(built)),
+            span: None,
+        };
+        assert!(crate::parse("name \"app\"").unwrap().semantic_eq(&built));
+    }
+
+    #[test]
+    fn test_semantic_eq_compares_content() {
+        let eq = |a: &str, b: &str| {
+            crate::parse(a)
+                .unwrap()
+                .semantic_eq(&crate::parse(b).unwrap())
+        };
+        assert!(!eq("name app", "name other"));
+        assert!(!eq("name app", "title app"));
+        assert!(!eq("a 1\nb 2", "b 2\na 1"));
+        assert!(!eq("tags (a b)", "tags (a b c)"));
+        assert!(!eq("kind @string", "kind @int"));
+        assert!(!eq("kind @string", "kind string"));
+        assert!(!eq("value @", "value @unit"));
+        assert!(eq("kind @seq(@string)", "kind @seq( @string )"));
+    }
+
+    #[test]
+    fn test_leaves() {
+        let value = crate::parse(
+            "name app\nserver {host localhost, ports (80 443)}\nusers ({name alice} {name bob})\nkind @string\nempty {}",
+        )
+        .unwrap();
+
+        let leaves: Vec<(String, &Value)> = value.leaves().collect();
+        let paths: Vec<&str> = leaves.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "name",
+                "server.host",
+                "server.ports[0]",
+                "server.ports[1]",
+                "users[0].name",
+                "users[1].name",
+                "kind",
+            ]
+        );
+        for (path, leaf) in &leaves {
+            assert_eq!(value.get(path), Some(*leaf), "{path}");
+        }
+        assert_eq!(leaves[3].1.as_str(), Some("443"));
+        assert_eq!(leaves[5].1.as_str(), Some("bob"));
+        assert_eq!(leaves[6].1.tag_name(), Some("string"));
+
+        // A scalar is its own only leaf
+        let scalar = Value::scalar("x");
+        assert_eq!(
+            scalar.leaves().collect::<Vec<_>>(),
+            [(String::new(), &scalar)]
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut base = crate::parse(
+            "name app\nserver {host localhost, port 8080}\ntags (a b)\nkind @object{x 1}",
+        )
+        .unwrap();
+        base.merge(
+            crate::parse("server {port 9090, tls {enabled true}}\ntags (c)\nkind @int\nextra 1")
+                .unwrap(),
+        );
+
+        let expected = crate::parse(
+            "name app\nserver {host localhost, port 9090, tls {enabled true}}\ntags (c)\nkind @int\nextra 1",
+        )
+        .unwrap();
+        assert!(base.semantic_eq(&expected), "{base:#?}");
+    }
+
+    #[test]
+    fn test_visit_mut_doubles_integers() {
+        let mut doc =
+            crate::parse("name app\nport 8080\nlimits {workers 4, sizes (1 2 x)}\nratio 0.5")
+                .unwrap();
+        doc.visit_mut(|value: &mut Value| {
+            if let Some(Payload::Scalar(scalar)) = &mut value.payload
+                && let Ok(n) = scalar.text.parse::<i64>()
+            {
+                scalar.text = (n * 2).to_string();
+            }
+        });
+
+        let expected =
+            crate::parse("name app\nport 16160\nlimits {workers 8, sizes (2 4 x)}\nratio 0.5")
+                .unwrap();
+        assert!(doc.semantic_eq(&expected));
+    }
+
+    #[test]
+    fn test_simplify_collapses_unit_keyed_tag_objects() {
+        let mut doc = crate::parse(
+            "hosts @list{@ (a b)}\nnested @outer{@ {x @inner{@ (1)}}}\nempty @none{@ @}",
+        )
+        .unwrap();
+        doc.simplify();
+
+        let expected =
+            crate::parse("hosts @list(a b)\nnested @outer{x @inner(1)}\nempty @none").unwrap();
+        assert!(doc.semantic_eq(&expected), "{doc:#?}");
+    }
+
+    #[test]
+    fn test_simplify_keeps_other_structures() {
+        let source = "named @tag{value (x)}\ntwo @tag{@ (x), y 1}\ntagged @tag{@ @int}\nscalar @tag{@ x}\nuntagged {@ (x)}";
+        let mut doc = crate::parse(source).unwrap();
+        doc.simplify();
+        assert_eq!(doc, crate::parse(source).unwrap());
+
+        // A documented entry keeps its wrapper, and its doc comment
+        let source = "opt @tag{\n  /// Why x\n  @ (x)\n}";
+        let mut doc = crate::parse(source).unwrap();
+        doc.simplify();
+        assert_eq!(doc, crate::parse(source).unwrap());
+    }
+
     #[test]
     fn test_object_get() {
         let mut obj = Object {
@@ -520,9 +1070,11 @@ mod tests {
                 key: Value::scalar("name"),
                 value: Value::scalar("Alice"),
                 doc_comment: None,
+                comments: Vec::new(),
             }],
 
             span: None,
+            origin: ObjectOrigin::Braces,
         };
 
         assert_eq!(obj.get("name").and_then(|v| v.as_str()), Some("Alice"));
@@ -538,6 +1090,7 @@ mod tests {
             entries: vec![],
 
             span: None,
+            origin: ObjectOrigin::Braces,
         };
 
         obj.insert_unit(Value::scalar("root"));
@@ -560,13 +1113,16 @@ mod tests {
                                     key: Value::scalar("name"),
                                     value: Value::scalar("Alice"),
                                     doc_comment: None,
+                                    comments: Vec::new(),
                                 }],
 
                                 span: None,
+                                origin: ObjectOrigin::Braces,
                             })),
                             span: None,
                         },
                         doc_comment: None,
+                        comments: Vec::new(),
                     },
                     Entry {
                         key: Value::scalar("items"),
@@ -583,10 +1139,12 @@ mod tests {
                             span: None,
                         },
                         doc_comment: None,
+                        comments: Vec::new(),
                     },
                 ],
 
                 span: None,
+                origin: ObjectOrigin::Braces,
             })),
             span: None,
         };
@@ -600,6 +1158,97 @@ mod tests {
         assert_eq!(value.get("missing"), None);
     }
 
+    #[test]
+    fn test_get_pointer() {
+        let value = crate::parse(
+            "db {host localhost, ports (5432 5433)}\n\"a/b\" slash\n\"m~n\" tilde\n\"\" empty",
+        )
+        .unwrap();
+
+        assert_eq!(value.get_pointer(""), Some(&value));
+        assert_eq!(
+            value.get_pointer("/db/host").unwrap().as_str(),
+            Some("localhost")
+        );
+        assert_eq!(
+            value.get_pointer("/db/ports/1").unwrap().as_str(),
+            Some("5433")
+        );
+        assert_eq!(value.get_pointer("/a~1b").unwrap().as_str(), Some("slash"));
+        assert_eq!(value.get_pointer("/m~0n").unwrap().as_str(), Some("tilde"));
+        assert_eq!(value.get_pointer("/").unwrap().as_str(), Some("empty"));
+
+        assert!(value.get_pointer("db/host").is_none());
+        assert!(value.get_pointer("/db/ports/2").is_none());
+        assert!(value.get_pointer("/db/ports/01").is_none());
+        assert!(value.get_pointer("/db/ports/-").is_none());
+        assert!(value.get_pointer("/db/host/name").is_none());
+    }
+
+    #[test]
+    fn test_get_mut_changes_nested_scalar() {
+        let mut value = crate::parse("server {host localhost, ports (80 443)}").unwrap();
+        *value.get_mut("server.ports[1]").unwrap() = Value::scalar("8443");
+        value.get_mut("server.host").unwrap().tag = Some(Tag {
+            name: "env".into(),
+            span: None,
+        });
+
+        assert_eq!(value.get("server.ports[1]").unwrap().as_str(), Some("8443"));
+        assert_eq!(value.get("server.host").unwrap().tag_name(), Some("env"));
+        assert!(value.get_mut("server.ports[2]").is_none());
+    }
+
+    #[test]
+    fn test_object_remove() {
+        let mut value = crate::parse(
+            "name app
+port 8080",
+        )
+        .unwrap();
+        let obj = value.as_object_mut().unwrap();
+
+        assert_eq!(obj.remove("port").unwrap().as_str(), Some("8080"));
+        assert!(obj.remove("port").is_none());
+        assert_eq!(obj.len(), 1);
+        assert!(obj.contains_key("name"));
+    }
+
+    #[test]
+    fn test_upsert_creates_deep_path() {
+        let mut value = crate::parse("server {host localhost}").unwrap();
+        value.upsert("server.tls.cert", Value::scalar("cert.pem"));
+        value.upsert("server.host", Value::scalar("example.com"));
+
+        assert_eq!(
+            value.get("server.tls.cert").unwrap().as_str(),
+            Some("cert.pem")
+        );
+        assert_eq!(
+            value.get("server.host").unwrap().as_str(),
+            Some("example.com")
+        );
+        assert_eq!(value.get("server").unwrap().as_object().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_upsert_stops_at_non_objects() {
+        let mut value = crate::parse(
+            "name app
+ports (80)",
+        )
+        .unwrap();
+
+        assert!(value.upsert("name.first", Value::unit()).is_none());
+        assert!(value.upsert("ports[1]", Value::unit()).is_none());
+        // Nothing is created on the way to an index that can't exist
+        assert!(value.upsert("extra.list[0]", Value::unit()).is_none());
+        assert!(value.get("extra").is_none());
+
+        value.upsert("ports[0]", Value::scalar("8080"));
+        assert_eq!(value.get("ports[0]").unwrap().as_str(), Some("8080"));
+    }
+
     /// Test that Value can roundtrip through JSON via Facet.
     #[test]
     #[cfg(feature = "facet")]
@@ -614,18 +1263,21 @@ mod tests {
                         key: Value::tag("schema"),
                         value: Value::scalar("my-schema.styx"),
                         doc_comment: Some("Schema for this config".to_string()),
+                        comments: Vec::new(),
                     },
                     // Simple scalar
                     Entry {
                         key: Value::scalar("name"),
                         value: Value::scalar("my-app"),
                         doc_comment: None,
+                        comments: Vec::new(),
                     },
                     // Tagged value
                     Entry {
                         key: Value::scalar("port"),
                         value: Value::tagged("int", Value::scalar("8080")),
                         doc_comment: None,
+                        comments: Vec::new(),
                     },
                     // Nested object
                     Entry {
@@ -638,6 +1290,7 @@ mod tests {
                                         key: Value::scalar("host"),
                                         value: Value::scalar("localhost"),
                                         doc_comment: None,
+                                        comments: Vec::new(),
                                     },
                                     Entry {
                                         key: Value::scalar("tls"),
@@ -652,27 +1305,33 @@ mod tests {
                                                         key: Value::scalar("cert"),
                                                         value: Value::scalar("/path/to/cert.pem"),
                                                         doc_comment: None,
+                                                        comments: Vec::new(),
                                                     },
                                                     Entry {
                                                         key: Value::scalar("key"),
                                                         value: Value::scalar("/path/to/key.pem"),
                                                         doc_comment: None,
+                                                        comments: Vec::new(),
                                                     },
                                                 ],
 
                                                 span: None,
+                                                origin: ObjectOrigin::Braces,
                                             })),
                                             span: None,
                                         },
                                         doc_comment: Some("TLS configuration".to_string()),
+                                        comments: Vec::new(),
                                     },
                                 ],
 
                                 span: None,
+                                origin: ObjectOrigin::Braces,
                             })),
                             span: None,
                         },
                         doc_comment: Some("Server settings".to_string()),
+                        comments: Vec::new(),
                     },
                     // Sequence
                     Entry {
@@ -690,16 +1349,19 @@ mod tests {
                             span: None,
                         },
                         doc_comment: None,
+                        comments: Vec::new(),
                     },
                     // Unit value
                     Entry {
                         key: Value::scalar("debug"),
                         value: Value::unit(),
                         doc_comment: None,
+                        comments: Vec::new(),
                     },
                 ],
 
                 span: Some(Span::new(0, 100)),
+                origin: ObjectOrigin::Braces,
             })),
             span: Some(Span::new(0, 100)),
         };
@@ -739,6 +1401,7 @@ mod tests {
                         key: Value::scalar("name"),
                         value: Value::scalar("Alice"),
                         doc_comment: None,
+                        comments: Vec::new(),
                     },
                     Entry {
                         key: Value::scalar("nested"),
@@ -749,17 +1412,21 @@ mod tests {
                                     key: Value::scalar("inner"),
                                     value: Value::scalar("value"),
                                     doc_comment: None,
+                                    comments: Vec::new(),
                                 }],
 
                                 span: None,
+                                origin: ObjectOrigin::Braces,
                             })),
                             span: None,
                         },
                         doc_comment: Some("A nested object".to_string()),
+                        comments: Vec::new(),
                     },
                 ],
 
                 span: None,
+                origin: ObjectOrigin::Braces,
             })),
             span: None,
         };
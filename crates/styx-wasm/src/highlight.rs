@@ -0,0 +1,64 @@
+//! Syntax highlighting without a language server.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::to_js_value;
+
+/// A range of the source to color as one kind of token.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Highlight {
+    /// Start offset in the source.
+    pub start: u32,
+    /// End offset in the source.
+    pub end: u32,
+    /// Kind of token, named as in the LSP semantic token legend:
+    /// "comment", "string", "type", "property", "operator", ...
+    pub token_type: String,
+}
+
+/// Compute the highlighting of a Styx document.
+///
+/// Returns an array of `{start, end, tokenType}` objects in source order,
+/// the same highlighting the language server provides. Documents with errors
+/// are highlighted as far as they parse.
+#[wasm_bindgen]
+pub fn highlight(source: &str) -> JsValue {
+    to_js_value(&highlights(source)).unwrap_or(JsValue::NULL)
+}
+
+fn highlights(source: &str) -> Vec<Highlight> {
+    styx_cst::compute_highlight_spans(&styx_cst::parse(source))
+        .into_iter()
+        .map(|span| Highlight {
+            start: span.start as u32,
+            end: span.end as u32,
+            token_type: span.token_type.as_str().to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlights_comment_string_and_tag() {
+        let source = "// server\nhost \"localhost\"\nmode @dev";
+        let highlights: Vec<_> = highlights(source)
+            .into_iter()
+            .map(|h| (h.start, h.end, h.token_type))
+            .collect();
+        assert_eq!(
+            highlights,
+            [
+                (0, 9, "comment".to_string()),
+                (10, 14, "property".to_string()),
+                (15, 26, "string".to_string()),
+                (27, 31, "property".to_string()),
+                (32, 36, "type".to_string()),
+            ]
+        );
+    }
+}
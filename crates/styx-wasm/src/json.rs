@@ -0,0 +1,482 @@
+//! Conversion between Styx documents and JSON.
+
+use serde_json::json;
+use styx_parse::ScalarKind;
+use styx_tree::{
+    CanonicalNumber, Entry, Object, ObjectOrigin, Payload, Scalar, Sequence, Tag, Value,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::to_js_value;
+
+/// Convert a Styx document to JSON.
+///
+/// Returns a JSON string representation of the Styx document.
+/// Tags are represented as `{"$tag": "tagname", "$value": ...}`.
+/// Scalars that spell numbers, booleans or `null` become those; see
+/// [`to_json_with`] to choose another interpretation.
+/// Returns an error object if parsing fails.
+#[wasm_bindgen]
+pub fn to_json(source: &str) -> JsValue {
+    convert_to_json(source, Scalars::Literals)
+}
+
+/// Convert a Styx document to JSON, choosing how scalars are interpreted.
+///
+/// `mode` is one of:
+/// - `"strings"`: every scalar becomes a JSON string.
+/// - `"literals"`: scalars that spell numbers, booleans or `null` become
+///   those, as with [`to_json`].
+/// - `"schema"`: scalars become what `schema` (a Styx schema document)
+///   declares them as, so a quoted `"8080"` declared `@int` is a number.
+///   Scalars the schema doesn't type stay strings.
+///
+/// Returns the same object as [`to_json`], or an error object if the mode is
+/// unknown, the schema is missing or invalid, or parsing fails.
+#[wasm_bindgen]
+pub fn to_json_with(source: &str, mode: &str, schema: Option<String>) -> JsValue {
+    let schema = match schema.filter(|_| mode == "schema") {
+        Some(schema) => match styx_tree::parse(&schema) {
+            Ok(schema) => Some(schema),
+            Err(e) => return error_to_js(format!("invalid schema: {e}")),
+        },
+        None => None,
+    };
+    let scalars = match mode {
+        "strings" => Scalars::Strings,
+        "literals" => Scalars::Literals,
+        "schema" => {
+            let Some(schema) = &schema else {
+                return error_to_js("schema mode needs a schema".to_string());
+            };
+            match schema.get("schema").and_then(Value::as_object) {
+                Some(defs) => Scalars::Schema(defs),
+                None => return error_to_js("invalid schema: missing 'schema' block".to_string()),
+            }
+        }
+        _ => return error_to_js(format!("unknown scalar mode '{mode}'")),
+    };
+    convert_to_json(source, scalars)
+}
+
+fn convert_to_json(source: &str, scalars: Scalars) -> JsValue {
+    match styx_tree::parse(source) {
+        Ok(value) => {
+            let json_value = document_to_json(&value, scalars);
+            let json_string =
+                serde_json::to_string_pretty(&json_value).unwrap_or_else(|e| e.to_string());
+
+            to_js_value(&json!({
+                "success": true,
+                "json": json_value,
+                "jsonString": json_string
+            }))
+            .unwrap_or(JsValue::NULL)
+        }
+        Err(e) => error_to_js(e.to_string()),
+    }
+}
+
+fn error_to_js(error: String) -> JsValue {
+    to_js_value(&json!({
+        "success": false,
+        "error": error
+    }))
+    .unwrap_or(JsValue::NULL)
+}
+
+/// How scalars, which are all text in Styx, are interpreted in JSON.
+#[derive(Debug, Clone, Copy)]
+enum Scalars<'a> {
+    /// Every scalar is a string.
+    Strings,
+    /// Scalars that spell numbers, booleans or `null` are those.
+    Literals,
+    /// Scalars are what the schema with these definitions declares.
+    Schema(&'a Object),
+}
+
+impl<'a> Scalars<'a> {
+    /// The schema the root of a document is declared as.
+    fn root(self) -> Option<&'a Value> {
+        match self {
+            Scalars::Schema(defs) => self.declared(defs.get_unit()?),
+            _ => None,
+        }
+    }
+
+    /// The schema that types a value declared as `schema`, see
+    /// [`facet_styx::resolve_type_ref`].
+    fn declared(self, schema: &'a Value) -> Option<&'a Value> {
+        match self {
+            Scalars::Schema(defs) => facet_styx::resolve_type_ref(schema, defs),
+            _ => None,
+        }
+    }
+}
+
+/// Convert a parsed Styx document to a JSON value.
+fn document_to_json(value: &Value, scalars: Scalars) -> serde_json::Value {
+    value_to_json(value, scalars, scalars.root())
+}
+
+/// Convert a Styx Value to a JSON value, with `schema` the declared schema
+/// of the value in [`Scalars::Schema`] mode.
+fn value_to_json(value: &Value, scalars: Scalars, schema: Option<&Value>) -> serde_json::Value {
+    let tag = value.tag.as_ref().map(|t| t.name.as_str());
+    // A tagged value picks its own variant, which the schema doesn't type
+    let schema = if tag.is_some() { None } else { schema };
+    let payload = value
+        .payload
+        .as_ref()
+        .map(|p| payload_to_json(p, scalars, schema));
+
+    match (tag, payload) {
+        // Unit with no tag: null
+        (None, None) => json!(null),
+        // Scalar/sequence/object with no tag: just the payload
+        (None, Some(p)) => p,
+        // Tag with no payload: {"$tag": "name"}
+        (Some(t), None) => json!({"$tag": t}),
+        // Tagged value: {"$tag": "name", "$value": payload}
+        (Some(t), Some(p)) => json!({"$tag": t, "$value": p}),
+    }
+}
+
+/// Convert a Styx Payload to a JSON value.
+fn payload_to_json(
+    payload: &Payload,
+    scalars: Scalars,
+    schema: Option<&Value>,
+) -> serde_json::Value {
+    match payload {
+        Payload::Scalar(s) => scalar_to_json(&s.text, scalars, schema),
+        Payload::Sequence(seq) => sequence_to_json(seq, scalars, schema),
+        Payload::Object(obj) => object_to_json(obj, scalars, schema),
+    }
+}
+
+/// Convert a scalar's text to a JSON value.
+fn scalar_to_json(text: &str, scalars: Scalars, schema: Option<&Value>) -> serde_json::Value {
+    match scalars {
+        Scalars::Strings => json!(text),
+        Scalars::Literals => {
+            // Try to parse as number or boolean
+            if let Some(n) = json_number(text) {
+                serde_json::Value::Number(n)
+            } else if text == "true" {
+                json!(true)
+            } else if text == "false" {
+                json!(false)
+            } else if text == "null" {
+                json!(null)
+            } else {
+                json!(text)
+            }
+        }
+        Scalars::Schema(_) => match (schema.and_then(Value::tag_name), text) {
+            (Some("int" | "float"), _) => {
+                json_number(text).map_or_else(|| json!(text), serde_json::Value::Number)
+            }
+            (Some("bool"), "true") => json!(true),
+            (Some("bool"), "false") => json!(false),
+            _ => json!(text),
+        },
+    }
+}
+
+/// The JSON number a scalar's text spells, if it is spelled canonically.
+///
+/// See [`styx_tree::parse_canonical_number`]: `01234`, `+5` and `1e3` stay
+/// strings so no text is lost, as do `inf`, `NaN` and integers past 64 bits.
+fn json_number(text: &str) -> Option<serde_json::Number> {
+    match styx_tree::parse_canonical_number(text)? {
+        CanonicalNumber::Int(n) => Some(n.into()),
+        CanonicalNumber::UInt(n) => Some(n.into()),
+        CanonicalNumber::Float(f) => serde_json::Number::from_f64(f),
+    }
+}
+
+/// Convert a Styx Sequence to a JSON array.
+fn sequence_to_json(seq: &Sequence, scalars: Scalars, schema: Option<&Value>) -> serde_json::Value {
+    let items: Vec<serde_json::Value> = seq
+        .items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let item_schema = schema.and_then(|schema| match schema.tag_name() {
+                Some("seq") => scalars.declared(schema.as_sequence()?.items.first()?),
+                Some("tuple") => scalars.declared(schema.as_sequence()?.items.get(index)?),
+                _ => None,
+            });
+            value_to_json(item, scalars, item_schema)
+        })
+        .collect();
+    json!(items)
+}
+
+/// Convert a Styx Object to a JSON object.
+fn object_to_json(obj: &Object, scalars: Scalars, schema: Option<&Value>) -> serde_json::Value {
+    let mut map = serde_json::Map::new();
+
+    for entry in &obj.entries {
+        // Get key as string
+        let key = if entry.key.is_unit() {
+            "@".to_string()
+        } else if let Some(s) = entry.key.as_str() {
+            s.to_string()
+        } else if let Some(tag) = entry.key.tag_name() {
+            format!("@{}", tag)
+        } else {
+            // Complex key - serialize it
+            format!("{:?}", entry.key)
+        };
+
+        let field_schema = schema.and_then(|schema| field_schema(schema, &entry.key, scalars));
+        map.insert(key, value_to_json(&entry.value, scalars, field_schema));
+    }
+
+    serde_json::Value::Object(map)
+}
+
+/// The declared schema of the entry at `key` in an object typed `schema`.
+fn field_schema<'a>(schema: &'a Value, key: &Value, scalars: Scalars<'a>) -> Option<&'a Value> {
+    let field = match schema.tag_name()? {
+        "object" => {
+            let fields = schema.as_object()?;
+            match key.as_str() {
+                Some(name) if fields.contains_key(name) => fields.get(name),
+                // Otherwise a typed catch-all like `@string @T`
+                _ => fields
+                    .entries
+                    .iter()
+                    .find(|field| field.key.tag.is_some())
+                    .map(|field| &field.value),
+            }
+        }
+        // `@map(@V)` or `@map(@K @V)`
+        "map" => schema.as_sequence()?.items.last(),
+        _ => None,
+    }?;
+    scalars.declared(field)
+}
+
+/// Convert a JSON string to Styx format.
+///
+/// Returns a Styx document string representation of the JSON.
+/// Tagged values ({"$tag": "name", "$value": ...}) are converted back to tags.
+#[wasm_bindgen]
+pub fn from_json(json_source: &str) -> JsValue {
+    match serde_json::from_str::<serde_json::Value>(json_source) {
+        Ok(json_value) => {
+            let styx_value = json_to_value(&json_value);
+            let styx_string =
+                styx_format::format_value(&styx_value, styx_format::FormatOptions::default());
+
+            to_js_value(&json!({
+                "success": true,
+                "styxString": styx_string
+            }))
+            .unwrap_or(JsValue::NULL)
+        }
+        Err(e) => to_js_value(&json!({
+            "success": false,
+            "error": e.to_string()
+        }))
+        .unwrap_or(JsValue::NULL),
+    }
+}
+
+/// Convert a JSON value to a Styx Value.
+fn json_to_value(json: &serde_json::Value) -> Value {
+    match json {
+        serde_json::Value::Null => Value::unit(),
+
+        serde_json::Value::Bool(b) => Value {
+            tag: None,
+            payload: Some(Payload::Scalar(Scalar {
+                text: b.to_string(),
+                kind: ScalarKind::Bare,
+                span: None,
+            })),
+            span: None,
+        },
+
+        serde_json::Value::Number(n) => Value {
+            tag: None,
+            payload: Some(Payload::Scalar(Scalar {
+                text: n.to_string(),
+                kind: ScalarKind::Bare,
+                span: None,
+            })),
+            span: None,
+        },
+
+        serde_json::Value::String(s) => Value {
+            tag: None,
+            payload: Some(Payload::Scalar(Scalar {
+                text: s.clone(),
+                kind: scalar_kind(s),
+                span: None,
+            })),
+            span: None,
+        },
+
+        serde_json::Value::Array(arr) => {
+            let items = arr.iter().map(json_to_value).collect();
+            Value {
+                tag: None,
+                payload: Some(Payload::Sequence(Sequence { items, span: None })),
+                span: None,
+            }
+        }
+
+        serde_json::Value::Object(obj) => {
+            // Check for tagged value: {"$tag": "name", "$value": ...}
+            if let Some(serde_json::Value::String(tag_name)) = obj.get("$tag") {
+                let payload = obj.get("$value").and_then(|v| json_to_value(v).payload);
+                return Value {
+                    tag: Some(Tag {
+                        name: tag_name.clone(),
+                        span: None,
+                    }),
+                    payload,
+                    span: None,
+                };
+            }
+
+            // Regular object
+            let entries = obj
+                .iter()
+                .map(|(k, v)| {
+                    let key = Value {
+                        tag: None,
+                        payload: Some(Payload::Scalar(Scalar {
+                            text: k.clone(),
+                            kind: scalar_kind(k),
+                            span: None,
+                        })),
+                        span: None,
+                    };
+                    Entry::new(key, json_to_value(v))
+                })
+                .collect();
+
+            Value {
+                tag: None,
+                payload: Some(Payload::Object(Object {
+                    entries,
+                    span: None,
+                    origin: ObjectOrigin::Braces,
+                })),
+                span: None,
+            }
+        }
+    }
+}
+
+/// How a JSON string is written in Styx: bare where
+/// [`styx_format::can_be_bare`] allows it, quoted otherwise.
+fn scalar_kind(text: &str) -> ScalarKind {
+    if styx_format::can_be_bare(text) {
+        ScalarKind::Bare
+    } else {
+        ScalarKind::Quoted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_to_json(source: &str) -> serde_json::Value {
+        document_to_json(&styx_tree::parse(source).unwrap(), Scalars::Literals)
+    }
+
+    #[test]
+    fn test_numbers_keep_their_kind() {
+        let json = parse_to_json("count 42\nratio 1.5\nwhole 2.0\nbig 18446744073709551615");
+        assert!(json["count"].is_i64());
+        assert_eq!(json["count"], 42);
+        assert!(json["ratio"].is_f64());
+        assert_eq!(json["ratio"], 1.5);
+        assert!(json["whole"].is_f64());
+        assert!(json["big"].is_u64());
+
+        let json = parse_to_json("huge 123456789012345678901234567890\nnan NaN\nversion 1.2.3");
+        assert_eq!(json["huge"], "123456789012345678901234567890");
+        assert_eq!(json["nan"], "NaN");
+        assert_eq!(json["version"], "1.2.3");
+
+        let json = parse_to_json("zip 01234\nshift +5\nsize 1e3");
+        assert_eq!(json, json!({"zip": "01234", "shift": "+5", "size": "1e3"}));
+    }
+
+    #[test]
+    fn test_integer_round_trips_through_json() {
+        let json = parse_to_json("count 42");
+        let value = json_to_value(&json);
+        let styx = styx_format::format_value(&value, styx_format::FormatOptions::default());
+        assert_eq!(styx.trim(), "count 42");
+        assert_eq!(parse_to_json(&styx), json);
+    }
+
+    #[test]
+    fn test_strings_are_quoted_like_the_formatter_quotes_them() {
+        let json = json!({"url": "https://example.com/a", "text": "a b", "none": "null"});
+        let styx =
+            styx_format::format_value(&json_to_value(&json), styx_format::FormatOptions::default());
+        assert_eq!(
+            styx,
+            "url https://example.com/a\n\ntext \"a b\"\n\nnone \"null\"\n"
+        );
+    }
+
+    const MIXED: &str = "port \"8080\"\ndebug true\nname app";
+
+    fn schema_to_json(source: &str, schema: &str) -> serde_json::Value {
+        let schema = styx_tree::parse(schema).unwrap();
+        let defs = schema.get("schema").and_then(Value::as_object).unwrap();
+        document_to_json(&styx_tree::parse(source).unwrap(), Scalars::Schema(defs))
+    }
+
+    #[test]
+    fn test_strings_mode_keeps_scalars_as_text() {
+        let json = document_to_json(&styx_tree::parse(MIXED).unwrap(), Scalars::Strings);
+        assert_eq!(
+            json,
+            json!({"port": "8080", "debug": "true", "name": "app"})
+        );
+    }
+
+    #[test]
+    fn test_literals_mode_coerces_known_literals() {
+        let json = parse_to_json(MIXED);
+        assert_eq!(json, json!({"port": 8080, "debug": true, "name": "app"}));
+    }
+
+    #[test]
+    fn test_schema_mode_coerces_declared_types() {
+        let schema = "meta {id test}\nschema {\n  @ @object{port @Port, debug @optional(@bool), name @string}\n  Port @default(80 @int)\n}";
+        let json = schema_to_json(MIXED, schema);
+        assert_eq!(json, json!({"port": 8080, "debug": true, "name": "app"}));
+
+        // Declared strings stay strings, however they look
+        let schema = "meta {id test}\nschema {@ @object{port @string, debug @string}}";
+        let json = schema_to_json(MIXED, schema);
+        assert_eq!(
+            json,
+            json!({"port": "8080", "debug": "true", "name": "app"})
+        );
+    }
+
+    #[test]
+    fn test_schema_mode_follows_sequences_and_maps() {
+        let schema = "meta {id test}\nschema {@ @object{ports @seq(@int), flags @map(@bool)}}";
+        let json = schema_to_json("ports (80 \"443\")\nflags {fast true, safe false}", schema);
+        assert_eq!(
+            json,
+            json!({"ports": [80, 443], "flags": {"fast": true, "safe": false}})
+        );
+    }
+}
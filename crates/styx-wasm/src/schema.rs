@@ -0,0 +1,158 @@
+//! Validation of documents against a schema.
+
+use facet_styx::{SchemaFile, ValidationResult, Validator};
+use serde::Serialize;
+use styx_parse::Span;
+use styx_tree::BuildError;
+use wasm_bindgen::prelude::*;
+
+use crate::to_js_value;
+
+/// Result of validating a document against a schema.
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    /// Whether the document matches the schema (warnings don't count).
+    pub valid: bool,
+    /// Errors and warnings, in the order they were found.
+    pub errors: Vec<ValidationIssue>,
+}
+
+/// A problem found while validating a document.
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationIssue {
+    /// Where in the document, if known.
+    pub span: Option<SourceSpan>,
+    /// Human-readable message, prefixed with the path when there is one.
+    pub message: String,
+    /// Severity: "error" or "warning".
+    pub severity: String,
+}
+
+/// A byte range in the validated document.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SourceSpan {
+    /// Start offset in the source.
+    pub start: u32,
+    /// End offset in the source.
+    pub end: u32,
+}
+
+impl From<Span> for SourceSpan {
+    fn from(span: Span) -> Self {
+        SourceSpan {
+            start: span.start,
+            end: span.end,
+        }
+    }
+}
+
+/// Validate a Styx document against a schema.
+///
+/// `schema_source` is a schema document, with `meta` and `schema` blocks.
+/// Returns `{valid, errors}`, where each of `errors` is a
+/// `{span, message, severity}` object and `span` is `{start, end}` in
+/// `source`, or `null`. A document or schema that doesn't parse is reported
+/// as an error too.
+#[wasm_bindgen]
+pub fn validate_against(source: &str, schema_source: &str) -> JsValue {
+    to_js_value(&validation_report(source, schema_source)).unwrap_or(JsValue::NULL)
+}
+
+fn validation_report(source: &str, schema_source: &str) -> ValidationReport {
+    let schema = match facet_styx::from_str::<SchemaFile>(schema_source) {
+        Ok(schema) => schema,
+        Err(e) => return failure(None, format!("invalid schema: {e}")),
+    };
+    let mut doc = match styx_tree::parse(source) {
+        Ok(doc) => doc,
+        Err(e) => return failure(build_error_span(&e), e.to_string()),
+    };
+    // The schema to check against is given, so a declared one is ignored
+    if let Some(obj) = doc.as_object_mut() {
+        obj.entries.retain(|entry| !entry.key.is_schema_tag());
+    }
+
+    report(&Validator::new(&schema).validate_document(&doc))
+}
+
+fn report(result: &ValidationResult) -> ValidationReport {
+    let errors = result.errors.iter().map(|error| ValidationIssue {
+        span: error.span.map(Into::into),
+        message: error.to_string(),
+        severity: "error".to_string(),
+    });
+    let warnings = result.warnings.iter().map(|warning| ValidationIssue {
+        span: warning.span.map(Into::into),
+        message: if warning.path.is_empty() {
+            warning.message.clone()
+        } else {
+            format!("{}: {}", warning.path, warning.message)
+        },
+        severity: "warning".to_string(),
+    });
+    ValidationReport {
+        valid: result.is_valid(),
+        errors: errors.chain(warnings).collect(),
+    }
+}
+
+fn failure(span: Option<Span>, message: String) -> ValidationReport {
+    ValidationReport {
+        valid: false,
+        errors: vec![ValidationIssue {
+            span: span.map(Into::into),
+            message,
+            severity: "error".to_string(),
+        }],
+    }
+}
+
+/// The span of the document a build error points at, if it has one.
+fn build_error_span(error: &BuildError) -> Option<Span> {
+    match error {
+        BuildError::Parse(_, span)
+        | BuildError::DuplicateAnchor(_, span)
+        | BuildError::UnknownAnchor(_, span)
+        | BuildError::CyclicAnchor(_, span)
+        | BuildError::AliasLimit(span) => Some(*span),
+        BuildError::UnexpectedEvent(_)
+        | BuildError::UnclosedStructure
+        | BuildError::EmptyDocument => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = "meta {id test}\nschema {@ @object{host @string, port @int}}";
+
+    #[test]
+    fn test_reports_the_error_with_its_span() {
+        let source = "@schema {id test}\nhost localhost\nport eighty";
+        let report = validation_report(source, SCHEMA);
+        assert!(!report.valid);
+        assert_eq!(report.errors.len(), 1);
+
+        let error = &report.errors[0];
+        assert_eq!(error.message, "port: 'eighty' is not a valid integer");
+        assert_eq!(error.severity, "error");
+        let span = error.span.expect("the error should have a span");
+        assert_eq!(&source[span.start as usize..span.end as usize], "eighty");
+    }
+
+    #[test]
+    fn test_valid_document_has_no_errors() {
+        let report = validation_report("host localhost\nport 8080", SCHEMA);
+        assert!(report.valid);
+        assert!(report.errors.is_empty());
+    }
+
+    #[test]
+    fn test_unparsable_schema_is_an_error() {
+        let report = validation_report("host localhost", "schema {");
+        assert!(!report.valid);
+        assert!(report.errors[0].message.starts_with("invalid schema: "));
+        assert!(report.errors[0].span.is_none());
+    }
+}
@@ -0,0 +1,12 @@
+//! The bindings built with `--no-default-features`, without JSON conversion.
+#![cfg(not(feature = "json"))]
+
+// `parse` returns a `JsValue`, which only exists on wasm targets, so it is
+// checked to exist rather than called.
+#[test]
+fn test_core_functions_without_json() {
+    let _parse: fn(&str) -> wasm_bindgen::JsValue = styx_wasm::parse;
+    assert!(styx_wasm::validate("name app"));
+    assert!(!styx_wasm::validate("name {"));
+    assert_eq!(styx_wasm::version(), env!("CARGO_PKG_VERSION"));
+}
@@ -180,11 +180,6 @@ func (p *parser) parse() (*Document, error) {
 	start := p.current.Span.Start
 	ps := newPathState()
 
-	// Skip any leading commas
-	for p.check(TokenComma) {
-		p.advance()
-	}
-
 	// Check for explicit root object: { ... } at document start
 	if p.check(TokenLBrace) {
 		// Explicit root object - parse it and check for trailing content
@@ -227,7 +222,7 @@ func (p *parser) parse() (*Document, error) {
 		if p.err != nil {
 			return nil, p.err
 		}
-		entry, err := p.parseEntryWithPathCheck(ps)
+		entry, err := p.parseEntryWithPathCheck(ps, len(entries) > 0)
 		if err != nil {
 			return nil, err
 		}
@@ -242,10 +237,14 @@ func (p *parser) parse() (*Document, error) {
 	}, nil
 }
 
-func (p *parser) parseEntryWithPathCheck(ps *pathState) (*Entry, error) {
-	for p.check(TokenComma) {
+func (p *parser) parseEntryWithPathCheck(ps *pathState, afterEntry bool) (*Entry, error) {
+	// A comma must follow an entry, and at most one may
+	if afterEntry && p.check(TokenComma) {
 		p.advance()
 	}
+	if p.check(TokenComma) {
+		return nil, &ParseError{Message: "unexpected token", Span: p.current.Span}
+	}
 
 	if p.err != nil {
 		return nil, p.err
@@ -318,8 +317,10 @@ func (p *parser) parseEntryWithPathCheck(ps *pathState) (*Entry, error) {
 }
 
 func (p *parser) parseEntryWithDupCheck(seenKeys map[string]Span) (*Entry, error) {
-	for p.check(TokenComma) {
-		p.advance()
+	// The comma after an entry is consumed by parseObject, so any comma here
+	// is leading or doubled
+	if p.check(TokenComma) {
+		return nil, &ParseError{Message: "unexpected token", Span: p.current.Span}
 	}
 
 	if p.err != nil {
@@ -60,11 +60,6 @@ export class Parser {
     const pathState = new PathState();
 
     // Check for explicit root object: { ... } at document start
-    // Skip any leading commas first (just like parseEntryWithPathCheck does)
-    while (this.check("comma")) {
-      this.advance();
-    }
-
     if (this.check("lbrace")) {
       // Explicit root object - parse it and check for trailing content
       const obj = this.parseObject();
@@ -100,7 +95,7 @@ export class Parser {
     }
 
     while (!this.check("eof")) {
-      const entry = this.parseEntryWithPathCheck(pathState);
+      const entry = this.parseEntryWithPathCheck(pathState, entries.length > 0);
       if (entry) {
         entries.push(entry);
       }
@@ -112,10 +107,14 @@ export class Parser {
     };
   }
 
-  private parseEntryWithPathCheck(pathState: PathState): Entry | null {
-    while (this.check("comma")) {
+  private parseEntryWithPathCheck(pathState: PathState, afterEntry: boolean): Entry | null {
+    // A comma must follow an entry, and at most one may
+    if (afterEntry && this.check("comma")) {
       this.advance();
     }
+    if (this.check("comma")) {
+      throw new ParseError("unexpected token", this.current.span);
+    }
 
     // Trailing > without a value is a parse error
     if (this.check("gt")) {
@@ -240,8 +239,10 @@ export class Parser {
   }
 
   private parseEntryWithDupCheck(seenKeys: Map<string, Span>): Entry | null {
-    while (this.check("comma")) {
-      this.advance();
+    // The comma after an entry is consumed by parseObject, so any comma here
+    // is leading or doubled
+    if (this.check("comma")) {
+      throw new ParseError("unexpected token", this.current.span);
     }
 
     // Trailing > without a value is a parse error
@@ -122,10 +122,6 @@ class Parser:
         start = self.current.span.start
         path_state = PathState()
 
-        # Skip any leading commas
-        while self._check(TokenType.COMMA):
-            self._advance()
-
         # Check for explicit root object: { ... } at document start
         if self._check(TokenType.LBRACE):
             # Explicit root object - parse it and check for trailing content
@@ -158,7 +154,7 @@ class Parser:
             )
 
         while not self._check(TokenType.EOF):
-            entry = self._parse_entry_with_path_check(path_state)
+            entry = self._parse_entry_with_path_check(path_state, after_entry=bool(entries))
             if entry:
                 entries.append(entry)
 
@@ -167,10 +163,15 @@ class Parser:
             span=Span(start, self.current.span.end),
         )
 
-    def _parse_entry_with_path_check(self, path_state: PathState) -> Entry | None:
+    def _parse_entry_with_path_check(
+        self, path_state: PathState, after_entry: bool
+    ) -> Entry | None:
         """Parse an entry at document level with path state checking."""
-        while self._check(TokenType.COMMA):
+        # A comma must follow an entry, and at most one may
+        if after_entry and self._check(TokenType.COMMA):
             self._advance()
+        if self._check(TokenType.COMMA):
+            raise ParseError("unexpected token", self.current.span)
 
         # Stray > tokens without a value are an error
         if self._check(TokenType.GT):
@@ -225,8 +226,10 @@ class Parser:
 
     def _parse_entry_with_dup_check(self, seen_keys: dict[str, Span]) -> Entry | None:
         """Parse an entry with duplicate key checking."""
-        while self._check(TokenType.COMMA):
-            self._advance()
+        # The comma after an entry is consumed by _parse_object, so any comma
+        # here is leading or doubled
+        if self._check(TokenType.COMMA):
+            raise ParseError("unexpected token", self.current.span)
 
         # Stray > tokens without a value are an error
         if self._check(TokenType.GT):
@@ -16,7 +16,17 @@ export interface StyxWasm {
   init(wasmUrl?: string): Promise<void>;
   parse(source: string): ParseResult;
   to_json(source: string): ToJsonResult;
+  to_json_with?(source: string, mode: 'strings' | 'literals' | 'schema', schema?: string): ToJsonResult;
   from_json(json: string): FromJsonResult;
+  highlight?(source: string): Array<{ start: number; end: number; tokenType: string }>;
+  validate_against?(source: string, schemaSource: string): {
+    valid: boolean;
+    errors: Array<{
+      span: { start: number; end: number } | null;
+      message: string;
+      severity: string;
+    }>;
+  };
   version(): string;
 }
 
@@ -24,6 +34,7 @@ export interface ParseResult {
   success: boolean;
   diagnostics?: Array<{
     message: string;
+    code?: string;
     start: number;
     end: number;
   }>;